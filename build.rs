@@ -0,0 +1,21 @@
+use std::process::Command;
+
+/// Embeds the commit wrappy is built from as `WRAPPY_GIT_COMMIT`, for `wrappy version`.
+/// Builds outside a git checkout or without git simply leave it unset.
+fn main() {
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    // A new commit on the checked-out branch only touches the branch's ref file
+    if let Ok(head) = std::fs::read_to_string(".git/HEAD") {
+        if let Some(branch) = head.trim().strip_prefix("ref: ") {
+            println!("cargo:rerun-if-changed=.git/{}", branch);
+        }
+    }
+
+    let output = Command::new("git").args(["rev-parse", "--short=12", "HEAD"]).output();
+    if let Ok(output) = output {
+        let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !commit.is_empty() {
+            println!("cargo:rustc-env=WRAPPY_GIT_COMMIT={}", commit);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+#ifndef WRAPPY_H
+#define WRAPPY_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// `wrappy_validate` flag: host requirements, interpreters and broken symlinks fail validation.
+#define WRAPPY_VALIDATE_STRICT 1
+
+// `wrappy_enable_bindings` flag: create bindings the binding policy would block.
+#define WRAPPY_ENABLE_OVERRIDE_POLICY 1
+
+// Validates the container directory at `path` and returns the validation report as JSON,
+// the same document `wrappy container validate --output <file>` writes. An invalid
+// container still yields a report (with `"valid": false`); NULL means the call itself failed.
+//
+// The returned string must be released with `wrappy_string_free`.
+//
+// # Safety
+// `path` must be NULL or point to a NUL-terminated string that stays valid for the call.
+char *wrappy_validate(const char *path, int flags);
+
+// Enables every binding of the container directory at `path`, like `wrappy bindings enable`.
+// Returns 0 on success and -1 on failure. Progress is printed to stdout as in the CLI.
+//
+// # Safety
+// `path` must be NULL or point to a NUL-terminated string that stays valid for the call.
+int wrappy_enable_bindings(const char *path, int flags);
+
+// The last error of the calling thread as `{"code": "E_...", "message": "..."}`, or NULL when
+// no call on this thread has failed yet. Codes are the stable ones of `ContainerError::code`.
+//
+// The returned string must be released with `wrappy_string_free`.
+char *wrappy_last_error_json(void);
+
+// Releases a string returned by wrappy. Passing NULL does nothing.
+//
+// # Safety
+// `value` must be NULL or a pointer returned by a wrappy function that was not freed yet.
+void wrappy_string_free(char *value);
+
+#endif  /* WRAPPY_H */
@@ -2,9 +2,18 @@ mod router;
 
 use clap::{Parser, Subcommand};
 use std::env;
+use std::path::PathBuf;
 
-use crate::features::container::ContainerCommands;
+use crate::features::about::VersionFormat;
+use crate::features::backup::BackupCommands;
+use crate::features::container::{ContainerCommands, ExecArgs, RunArgs};
 use crate::features::bindings::BindingsCommands;
+use crate::features::settings::ConfigCommands;
+use crate::features::profile::ProfileCommands;
+use crate::features::shell::Shell;
+use crate::features::stats::StatsCommands;
+use crate::features::store::StoreCommands;
+use crate::shared::summary::SummaryFormat;
 pub use router::CommandRouter;
 
 #[derive(Parser)]
@@ -14,6 +23,18 @@ pub use router::CommandRouter;
     version = env!("CARGO_PKG_VERSION")
 )]
 pub struct Cli {
+    /// Parse every manifest from disk instead of using the manifest cache
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// Refuse every operation that needs network access
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Allow running as root on files that belong to another user
+    #[arg(long, global = true)]
+    pub allow_root: bool,
+
     #[command(subcommand)]
     pub command: MainCommands,
 }
@@ -25,6 +46,10 @@ pub enum MainCommands {
         #[command(subcommand)]
         action: ContainerCommands,
     },
+    /// Run a script of a container (shorthand for `wrappy container run`)
+    Run(RunArgs),
+    /// Run a command in a container's environment (shorthand for `wrappy container exec`)
+    X(ExecArgs),
     /// Flathub integration commands (coming soon)
     Flathub {
         #[command(subcommand)]
@@ -35,6 +60,90 @@ pub enum MainCommands {
         #[command(subcommand)]
         action: BindingsCommands,
     },
+    /// Read and edit the wrappy config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
+    /// Enable or disable named groups of containers from the config together
+    Profile {
+        #[command(subcommand)]
+        action: ProfileCommands,
+    },
+    /// Back up the state of this machine and restore it here or elsewhere
+    Backup {
+        #[command(subcommand)]
+        action: BackupCommands,
+    },
+    /// Run statistics commands
+    Stats {
+        #[command(subcommand)]
+        action: StatsCommands,
+    },
+    /// Deduplicated object store commands
+    Store {
+        #[command(subcommand)]
+        action: StoreCommands,
+    },
+    /// Print shell integration code, e.g. eval "$(wrappy env bash)"
+    Env {
+        /// Shell to generate code for
+        #[arg(value_enum)]
+        shell: Shell,
+        /// Only define wrappy-cd targets for these containers
+        #[arg(long, value_delimiter = ',')]
+        containers: Vec<String>,
+    },
+    /// Bring this machine in line with a desired-state file of containers and bindings
+    Apply {
+        /// JSON file listing containers and their desired binding state
+        file: PathBuf,
+        /// Apply the plan instead of only printing it
+        #[arg(long)]
+        yes: bool,
+        /// Progress lines and a summary table, or only the summary as JSON
+        #[arg(long, value_enum, default_value = "text")]
+        format: SummaryFormat,
+    },
+    /// Serve container and binding operations as JSON-RPC over a unix socket
+    Daemon {
+        /// Socket path to listen on
+        #[arg(long)]
+        socket: PathBuf,
+    },
+    /// Interactive dashboard of containers, bindings and services
+    #[cfg(feature = "tui")]
+    Ui,
+    /// Print the wrappy version; --full adds features, format versions and host tools
+    Version {
+        /// Also list enabled features, state format versions and optional host tools
+        #[arg(long)]
+        full: bool,
+        #[arg(long, value_enum, default_value = "text")]
+        format: VersionFormat,
+    },
+    /// Initialize config, store and PATH for first use
+    Setup {
+        /// Accept all defaults without prompting (never edits shell profiles)
+        #[arg(long)]
+        defaults: bool,
+    },
+}
+
+impl MainCommands {
+    /// Whether the output is meant for another program, so nothing else may be printed around it.
+    pub fn is_machine_readable(&self) -> bool {
+        match self {
+            MainCommands::Container { action } => action.is_machine_readable(),
+            MainCommands::Run(args) => args.is_machine_readable(),
+            MainCommands::Bindings { action } => action.is_machine_readable(),
+            MainCommands::Backup { action } => action.is_machine_readable(),
+            MainCommands::Apply { format, .. } => *format == SummaryFormat::Json,
+            MainCommands::Version { format, .. } => *format == VersionFormat::Json,
+            MainCommands::Daemon { .. } => true,
+            _ => false,
+        }
+    }
 }
 
 // Placeholder для майбутніх команд
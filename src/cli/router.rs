@@ -1,25 +1,99 @@
+use std::io::{self, IsTerminal};
+
 use crate::cli::MainCommands;
-use crate::features::container::ContainerHandler;
+use crate::features::about::AboutHandler;
+use crate::features::apply::ApplyHandler;
+use crate::features::backup::BackupHandler;
+use crate::features::container::{ContainerCommands, ContainerHandler};
+use crate::features::daemon::DaemonHandler;
 use crate::features::bindings::BindingsHandler;
+use crate::features::settings::ConfigHandler;
+use crate::features::profile::ProfileHandler;
+use crate::features::setup::SetupHandler;
+use crate::features::shell::ShellHandler;
+use crate::features::stats::StatsHandler;
+use crate::features::store::StoreHandler;
+#[cfg(feature = "tui")]
+use crate::features::ui::UiHandler;
+use crate::shared::NetworkPolicy;
 
 pub struct CommandRouter;
 
 impl CommandRouter {
     pub fn execute(command: MainCommands) -> i32 {
+        // `env` output is evaluated by shells on every startup, so it stays silent;
+        // `config` reports problems with the config file itself. Scripts and programs
+        // reading JSON get no hint either; it is meant for a person at a terminal.
+        let hint_wanted = !matches!(command, MainCommands::Setup { .. } | MainCommands::Env { .. } | MainCommands::Config { .. } | MainCommands::Version { .. })
+            && !command.is_machine_readable()
+            && io::stderr().is_terminal();
+        if hint_wanted {
+            SetupHandler::print_setup_hint();
+        }
+
         match command {
             MainCommands::Container { action } => {
                 ContainerHandler::execute_command(action)
             }
+            MainCommands::Run(args) => {
+                ContainerHandler::execute_command(ContainerCommands::Run(args))
+            }
+            MainCommands::X(args) => {
+                ContainerHandler::execute_command(ContainerCommands::Exec(args))
+            }
             MainCommands::Flathub { action } => {
                 Self::handle_flathub_placeholder(action)
             }
             MainCommands::Bindings { action } => {
                 BindingsHandler::execute_command(action)
             }
+            MainCommands::Config { action } => {
+                ConfigHandler::execute_command(action)
+            }
+            MainCommands::Profile { action } => {
+                ProfileHandler::execute_command(action)
+            }
+            MainCommands::Backup { action } => {
+                BackupHandler::execute_command(action)
+            }
+            MainCommands::Stats { action } => {
+                StatsHandler::execute_command(action)
+            }
+            MainCommands::Store { action } => {
+                StoreHandler::execute_command(action)
+            }
+            MainCommands::Env { shell, containers } => {
+                ShellHandler::execute_command(shell, containers)
+            }
+            MainCommands::Apply { file, yes, format } => {
+                ApplyHandler::execute_command(file, yes, format)
+            }
+            MainCommands::Daemon { socket } => {
+                DaemonHandler::execute_command(socket)
+            }
+            MainCommands::Version { full, format } => {
+                AboutHandler::handle_version_command(full, format)
+            }
+            #[cfg(feature = "tui")]
+            MainCommands::Ui => {
+                UiHandler::execute_command()
+            }
+            MainCommands::Setup { defaults } => {
+                SetupHandler::execute_command(defaults)
+            }
         }
     }
 
     fn handle_flathub_placeholder(action: crate::cli::FlathubCommands) -> i32 {
+        let operation = match &action {
+            crate::cli::FlathubCommands::Install { .. } => "install from Flathub",
+            crate::cli::FlathubCommands::Search { .. } => "search Flathub",
+        };
+        if let Err(error) = NetworkPolicy::ensure_online(operation) {
+            eprintln!("❌ {}", error);
+            return 1;
+        }
+
         match action {
             crate::cli::FlathubCommands::Install { app_id } => {
                 println!("🚧 Flathub integration coming soon!");
@@ -34,4 +108,96 @@ impl CommandRouter {
         }
     }
 
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, FlathubCommands};
+    use crate::shared::test_support::{fixture_container, TempHome};
+    use clap::Parser;
+
+    /// Parses `args` as main does and routes the command.
+    fn wrappy(args: &[&str]) -> i32 {
+        let cli = Cli::try_parse_from(std::iter::once("wrappy").chain(args.iter().copied())).unwrap();
+        CommandRouter::execute(cli.command)
+    }
+
+    #[test]
+    fn parsed_commands_reach_their_handlers() {
+        let home = TempHome::enter();
+        let valid = fixture_container(home.path(), "web", "1.0.0");
+        let empty = home.path().join("empty");
+        std::fs::create_dir_all(&empty).unwrap();
+
+        assert_eq!(wrappy(&["container", "validate", "--quiet", "--path", &valid.to_string_lossy()]), 0);
+        assert_ne!(wrappy(&["container", "validate", "--quiet", &empty.to_string_lossy()]), 0);
+        assert_eq!(wrappy(&["bindings", "list"]), 0);
+        assert_eq!(wrappy(&["flathub", "search", "editor"]), 0);
+    }
+
+    #[test]
+    fn global_flags_parse_before_and_after_the_subcommand() {
+        let cli = Cli::try_parse_from(["wrappy", "--offline", "bindings", "list", "--no-cache", "--allow-root"]).unwrap();
+
+        assert!(cli.offline && cli.no_cache && cli.allow_root);
+        assert!(matches!(cli.command, MainCommands::Bindings { .. }));
+        assert!(Cli::try_parse_from(["wrappy", "no-such-command"]).is_err());
+    }
+
+    #[test]
+    fn flathub_commands_refuse_to_run_offline() {
+        let _home = TempHome::enter();
+
+        NetworkPolicy::set_offline(true);
+        let install = CommandRouter::handle_flathub_placeholder(FlathubCommands::Install {
+            app_id: "org.example.App".to_string(),
+        });
+        let search = CommandRouter::handle_flathub_placeholder(FlathubCommands::Search {
+            query: "editor".to_string(),
+        });
+        NetworkPolicy::set_offline(false);
+
+        assert_eq!((install, search), (1, 1));
+        assert_eq!(
+            CommandRouter::handle_flathub_placeholder(FlathubCommands::Search { query: "editor".to_string() }),
+            0
+        );
+    }
+
+    /// The handler a parsed command line is routed to, as its canonical command.
+    fn handler_of(args: &[&str]) -> String {
+        let cli = Cli::try_parse_from(std::iter::once("wrappy").chain(args.iter().copied())).unwrap();
+        match cli.command {
+            MainCommands::Run(run) | MainCommands::Container { action: ContainerCommands::Run(run) } => {
+                format!("container run {} {} watch={:?} dry_run={}", run.container, run.script, run.watch, run.dry_run)
+            }
+            MainCommands::X(exec) | MainCommands::Container { action: ContainerCommands::Exec(exec) } => {
+                format!("container exec {} inherit={} {:?}", exec.container, !exec.no_inherit_env, exec.command)
+            }
+            MainCommands::Container { .. } => "container".to_string(),
+            MainCommands::Stats { .. } => "stats".to_string(),
+            _ => "other".to_string(),
+        }
+    }
+
+    #[test]
+    fn shorthands_parse_to_the_same_handlers_as_the_canonical_commands() {
+        type Case<'a> = (&'a [&'a str], &'a [&'a str]);
+        let cases: [Case; 5] = [
+            (&["run", "web"], &["container", "run", "web"]),
+            (&["run", "web@1.2", "-s", "build", "--dry-run"], &["container", "run", "web@1.2", "--script", "build", "--dry-run"]),
+            (&["run", "./web", "-w", "src"], &["container", "run", "./web", "--watch", "src"]),
+            (&["x", "web", "--", "ls", "-la"], &["container", "exec", "web", "--", "ls", "-la"]),
+            (&["--offline", "x", "web", "--no-inherit-env", "--", "env"], &["container", "exec", "web", "--no-inherit-env", "--", "env"]),
+        ];
+
+        for (shorthand, canonical) in cases {
+            assert_eq!(handler_of(shorthand), handler_of(canonical), "{:?}", shorthand);
+        }
+        assert_eq!(handler_of(&["run", "web"]), "container run web default watch=[] dry_run=false");
+        assert_eq!(handler_of(&["container", "list"]), "container");
+        assert_eq!(handler_of(&["stats", "export-prom"]), "stats");
+        assert!(Cli::try_parse_from(["wrappy", "x", "web"]).is_err(), "x needs a command after --");
+        assert!(Cli::try_parse_from(["wrappy", "run"]).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use crate::features::about::{AboutService, VersionFormat, VersionReport};
+use crate::shared::buildinfo;
+
+pub struct AboutHandler;
+
+impl AboutHandler {
+    /// Handles `wrappy version`
+    pub fn handle_version_command(full: bool, format: VersionFormat) -> i32 {
+        let report = AboutService::version_report(full);
+        match format {
+            VersionFormat::Json => match serde_json::to_string_pretty(&report) {
+                Ok(json) => println!("{}", json),
+                Err(e) => {
+                    eprintln!("❌ Failed to serialize version report: {}", e);
+                    return 1;
+                }
+            },
+            VersionFormat::Text => Self::print_report(&report),
+        }
+        0
+    }
+
+    fn print_report(report: &VersionReport) {
+        println!("{}", buildinfo::version_line());
+        if report.formats.is_none() && report.tools.is_none() {
+            return;
+        }
+
+        let features = match report.features.is_empty() {
+            true => "none".to_string(),
+            false => report.features.join(", "),
+        };
+        println!("  Features: {}", features);
+        if let Some(formats) = &report.formats {
+            println!("  Formats:");
+            for (name, version) in formats {
+                println!("    {:<16} {}", name, version);
+            }
+        }
+        if let Some(tools) = &report.tools {
+            println!("  Host tools:");
+            for tool in tools {
+                match (&tool.path, &tool.version) {
+                    (Some(_), Some(version)) => println!("    ✅ {:<12} {}", tool.command, version),
+                    (Some(path), None) => println!("    ✅ {:<12} {} (version unknown)", tool.command, path.display()),
+                    (None, _) => println!("    ➖ {:<12} not installed ({})", tool.command, tool.feature),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use crate::features::about::{ToolReport, VersionReport};
+use crate::features::backup::BACKUP_FORMAT_VERSION;
+use crate::features::bindings::BINDING_STATE_VERSION;
+use crate::features::container::REGISTRY_VERSION;
+use crate::features::daemon::PROTOCOL_VERSION;
+use crate::features::setup::SetupService;
+use crate::shared::buildinfo;
+
+pub struct AboutService;
+
+impl AboutService {
+    /// Collects the report; `full` adds the format versions and probes the host tools.
+    pub fn version_report(full: bool) -> VersionReport {
+        let mut report = VersionReport {
+            version: buildinfo::CRATE_VERSION.to_string(),
+            git_commit: buildinfo::git_commit().map(str::to_string),
+            features: buildinfo::enabled_features().into_iter().map(str::to_string).collect(),
+            formats: None,
+            tools: None,
+        };
+        if full {
+            report.formats = Some(Self::formats());
+            report.tools = Some(Self::tools());
+        }
+        report
+    }
+
+    fn formats() -> BTreeMap<String, u32> {
+        BTreeMap::from([
+            ("registry".to_string(), REGISTRY_VERSION),
+            ("bindings_state".to_string(), BINDING_STATE_VERSION),
+            ("backup".to_string(), BACKUP_FORMAT_VERSION),
+            ("daemon_protocol".to_string(), PROTOCOL_VERSION),
+        ])
+    }
+
+    fn tools() -> Vec<ToolReport> {
+        SetupService::optional_tools()
+            .into_iter()
+            .map(|tool| ToolReport {
+                command: tool.command.to_string(),
+                feature: tool.feature.to_string(),
+                version: tool.path.as_ref().and_then(|path| buildinfo::tool_version(&path.to_string_lossy())),
+                path: tool.path,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn keys(value: &Value) -> Vec<&str> {
+        value.as_object().unwrap().keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn the_short_report_has_only_the_build_fields() {
+        let report = serde_json::to_value(AboutService::version_report(false)).unwrap();
+
+        assert_eq!(keys(&report), ["features", "git_commit", "version"]);
+        assert_eq!(report["version"], buildinfo::CRATE_VERSION);
+        assert!(report["features"].as_array().unwrap().iter().all(Value::is_string));
+        assert!(report["git_commit"].is_string() || report["git_commit"].is_null());
+    }
+
+    #[test]
+    fn the_full_report_adds_format_versions_and_every_optional_tool() {
+        let report = serde_json::to_value(AboutService::version_report(true)).unwrap();
+
+        assert_eq!(keys(&report), ["features", "formats", "git_commit", "tools", "version"]);
+        assert_eq!(keys(&report["formats"]), ["backup", "bindings_state", "daemon_protocol", "registry"]);
+        assert_eq!(report["formats"]["registry"], REGISTRY_VERSION);
+        assert_eq!(report["formats"]["bindings_state"], BINDING_STATE_VERSION);
+        let tools = report["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), SetupService::optional_tools().len());
+        for tool in tools {
+            assert_eq!(keys(tool), ["command", "feature", "path", "version"]);
+            assert!(tool["command"].is_string() && tool["feature"].is_string());
+            if tool["path"].is_null() {
+                assert!(tool["version"].is_null(), "a missing tool has no version: {}", tool);
+            }
+        }
+    }
+}
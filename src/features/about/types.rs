@@ -0,0 +1,35 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Output formats of `wrappy version`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum VersionFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// What `wrappy version` reports. The fields after `features` are only
+/// collected for `--full`, since probing host tools starts processes.
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionReport {
+    pub version: String,
+    pub git_commit: Option<String>,
+    pub features: Vec<String>,
+    /// Format version of each state file and protocol this wrappy reads and writes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formats: Option<BTreeMap<String, u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<ToolReport>>,
+}
+
+/// An optional host tool, as `wrappy setup` lists them, with its version.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolReport {
+    pub command: String,
+    pub feature: String,
+    pub path: Option<PathBuf>,
+    /// First line of `<command> --version`
+    pub version: Option<String>,
+}
@@ -0,0 +1,117 @@
+use std::path::{Path, PathBuf};
+
+use crate::features::apply::{ApplyPlan, ApplyService, PlannedAction};
+use crate::features::container::{install_interrupt_handler, interrupted, ContainerResolver};
+use crate::shared::error::ContainerError;
+use crate::shared::summary::{Reporter, SummaryFormat};
+use crate::shared::CancellationToken;
+
+pub struct ApplyHandler;
+
+impl ApplyHandler {
+    /// Handles the apply command execution
+    pub fn execute_command(file: PathBuf, yes: bool, format: SummaryFormat) -> i32 {
+        let reporter = Reporter::new(format!("apply {}", file.display()), format);
+        let cancelled = match Self::apply(&file, yes, &reporter) {
+            Ok(()) => None,
+            Err(error @ ContainerError::Cancelled { .. }) => {
+                reporter.failed(
+                    file.display(),
+                    &error,
+                    "⏹️  Apply was cancelled, the remaining changes were not made",
+                );
+                Some(error.exit_code())
+            }
+            Err(error) => {
+                reporter.failed(file.display(), &error, format!("❌ Failed to apply {}: {}", file.display(), error));
+                None
+            }
+        };
+        let summary = reporter.finish();
+        cancelled.unwrap_or(summary.exit_code())
+    }
+
+    /// Prints the plan and, with `--yes`, applies it. Without `--yes` every action is
+    /// reported as skipped; with it, changes that need installing or updating containers
+    /// are reported as failed, so the command fails even if the rest was applied.
+    fn apply(file: &Path, yes: bool, reporter: &Reporter) -> Result<(), ContainerError> {
+        let state = ApplyService::load(file)?;
+        let plan = ApplyService::plan(&state, &ContainerResolver::new()?)?;
+
+        if plan.is_empty() {
+            reporter.info(format!("✅ This machine already matches {}", file.display()));
+            return Ok(());
+        }
+
+        reporter.info(format!("📋 Plan for {}:", file.display()));
+        Self::report_plan(&plan, yes, reporter);
+        reporter.info("");
+
+        if !yes {
+            reporter.info("Run again with --yes to apply these changes.");
+            return Ok(());
+        }
+
+        install_interrupt_handler();
+        let applied = ApplyService::apply(&plan, &CancellationToken::linked_to(interrupted), reporter)?;
+        if applied > 0 {
+            reporter.info("");
+            reporter.info(format!("✅ Applied {} change(s)", applied));
+        }
+
+        let unsupported = ContainerError::Runtime {
+            message: "wrappy cannot install or update containers yet".to_string(),
+        };
+        for action in plan.unsupported() {
+            reporter.failed(
+                Self::subject(action),
+                &unsupported,
+                format!("⚠️  {}: {}", Self::subject(action), unsupported),
+            );
+        }
+        Ok(())
+    }
+
+    /// Prints each planned action; a plan that is not applied reports them as skipped.
+    fn report_plan(plan: &ApplyPlan, yes: bool, reporter: &Reporter) {
+        for action in &plan.actions {
+            let line = match action {
+                PlannedAction::Install { name, requirement, url } => {
+                    let requirement = requirement.as_deref().unwrap_or("any version");
+                    match url {
+                        Some(url) => format!("  ➕ install {} ({}) from {} (not supported yet)", name, requirement, url),
+                        None => format!("  ➕ install {} ({}) (not supported yet)", name, requirement),
+                    }
+                }
+                PlannedAction::Update { name, installed, requirement, adopted: true } => format!(
+                    "  ⬆️  update {} {} to match {} (adopted, update its directory yourself)",
+                    name, installed, requirement
+                ),
+                PlannedAction::Update { name, installed, requirement, adopted: false } => {
+                    format!("  ⬆️  update {} {} to match {} (not supported yet)", name, installed, requirement)
+                }
+                PlannedAction::EnableBindings { container, category, targets } => {
+                    format!("  🔗 enable {} of {}@{}: {}", category.label(), container.name, container.version, targets.join(", "))
+                }
+                PlannedAction::DisableBindings { container, category, targets } => {
+                    format!("  🗑️  disable {} of {}@{}: {}", category.label(), container.name, container.version, targets.join(", "))
+                }
+            };
+            if yes {
+                reporter.info(line);
+            } else {
+                reporter.skipped(Self::subject(action), "not applied without --yes", line);
+            }
+        }
+    }
+
+    fn subject(action: &PlannedAction) -> String {
+        match action {
+            PlannedAction::Install { name, .. } | PlannedAction::Update { name, .. } => name.clone(),
+            PlannedAction::EnableBindings { container, category, .. }
+            | PlannedAction::DisableBindings { container, category, .. } => {
+                format!("{} of {}@{}", category.label(), container.name, container.version)
+            }
+        }
+    }
+}
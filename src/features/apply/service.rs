@@ -0,0 +1,289 @@
+use std::path::Path;
+
+use crate::features::apply::{ApplyPlan, DesiredContainer, DesiredState, InstalledTarget, PlannedAction};
+use crate::features::bindings::{BindingCategory, BindingFilter, BindingManager};
+use crate::features::container::{ContainerResolver, ContainerService, DependencyIndex, ResolutionCandidate};
+use crate::features::manifest::ManifestCache;
+use crate::features::query;
+use crate::features::VersionReq;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::CancellationToken;
+use crate::shared::summary::Reporter;
+
+/// Computes and applies the difference between a desired-state file and this machine.
+/// Plans only contain changes, so applying an up-to-date file does nothing.
+pub struct ApplyService;
+
+impl ApplyService {
+    pub fn load(path: &Path) -> ContainerResult<DesiredState> {
+        let invalid = |reason: String| ContainerError::InvalidConfig {
+            path: path.to_path_buf(),
+            reason,
+        };
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "yaml" || extension == "yml")
+        {
+            return Err(invalid("YAML is not supported yet, use JSON".to_string()));
+        }
+
+        let content = filesystem::read_json_file(path).map_err(|e| ContainerError::IoError {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        let state: DesiredState = serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+
+        for desired in &state.containers {
+            if let Some(requirement) = &desired.version {
+                requirement.parse::<VersionReq>().map_err(|e| {
+                    invalid(format!("container '{}': {}", desired.name, e))
+                })?;
+            }
+        }
+        Ok(state)
+    }
+
+    pub fn plan(state: &DesiredState, resolver: &ContainerResolver) -> ContainerResult<ApplyPlan> {
+        let mut cache = ManifestCache::open();
+        let mut index = DependencyIndex::new();
+        let mut installed = Vec::new();
+        let mut actions = Vec::new();
+
+        for desired in &state.containers {
+            match Self::select_installed(desired, resolver) {
+                Ok(candidate) => {
+                    if let Ok(manifest) = cache.get_or_load(&candidate.path.join("manifest.json")) {
+                        index.register(&manifest);
+                    }
+                    installed.push((desired, candidate));
+                }
+                Err(action) => actions.push(action),
+            }
+        }
+        // A cache that cannot be written only costs speed on the next run
+        let _ = cache.save();
+
+        let names: Vec<String> = installed.iter().map(|(desired, _)| desired.name.clone()).collect();
+        for name in index.install_order(&names) {
+            if let Some((desired, candidate)) = installed.iter().find(|(desired, _)| desired.name == name) {
+                actions.extend(Self::binding_actions(desired, candidate));
+            }
+        }
+
+        Ok(ApplyPlan { actions })
+    }
+
+    /// Runs every supported action in plan order and returns how many were applied.
+    /// Cancellation stops before the next action; the interrupted one rolls itself back.
+    pub fn apply(plan: &ApplyPlan, cancellation: &CancellationToken, reporter: &Reporter) -> ContainerResult<usize> {
+        let manager = BindingManager::new()?
+            .with_cancellation(cancellation.clone())
+            .with_reporter(reporter.clone());
+        let mut applied = 0;
+
+        for action in &plan.actions {
+            cancellation.check("apply")?;
+            let (container, category, targets, enable) = match action {
+                PlannedAction::EnableBindings { container, category, targets } => (container, category, targets, true),
+                PlannedAction::DisableBindings { container, category, targets } => (container, category, targets, false),
+                PlannedAction::Install { .. } | PlannedAction::Update { .. } => continue,
+            };
+
+            let loaded = ContainerService::load_from_directory(&container.path)?;
+            for target in targets {
+                let filter = BindingFilter {
+                    only: Some(*category),
+                    target: Some(target.clone()),
+                    label: None,
+                };
+                let mut filtered = loaded.clone();
+                filtered.manifest.bindings = manager.filter_bindings(&loaded, &loaded.manifest.bindings, &filter)?;
+                if enable {
+                    manager.install_bindings(&filtered)?;
+                } else {
+                    manager.remove_selected_bindings(&loaded, &filtered.manifest.bindings)?;
+                }
+            }
+            applied += 1;
+        }
+
+        Ok(applied)
+    }
+
+    /// Newest installed version satisfying the desired requirement,
+    /// or the install/update action that is needed instead.
+    fn select_installed(desired: &DesiredContainer, resolver: &ContainerResolver) -> Result<ResolutionCandidate, PlannedAction> {
+        let mut candidates = resolver.installed_versions(&desired.name);
+        if candidates.is_empty() {
+            return Err(PlannedAction::Install {
+                name: desired.name.clone(),
+                requirement: desired.version.clone(),
+                url: desired.url.clone(),
+            });
+        }
+
+        let Some(requirement) = &desired.version else {
+            return Ok(candidates.remove(0));
+        };
+        // Requirements were validated when the file was loaded
+        let parsed: Option<VersionReq> = requirement.parse().ok();
+        match candidates
+            .iter()
+            .position(|candidate| parsed.as_ref().is_none_or(|req| req.matches(&candidate.version)))
+        {
+            Some(position) => Ok(candidates.remove(position)),
+            None => {
+                let newest = candidates.remove(0);
+                Err(PlannedAction::Update {
+                    name: desired.name.clone(),
+                    installed: newest.version,
+                    requirement: requirement.clone(),
+                    adopted: newest.adopted,
+                })
+            }
+        }
+    }
+
+    fn binding_actions(desired: &DesiredContainer, candidate: &ResolutionCandidate) -> Vec<PlannedAction> {
+        let statuses = query::bindings_of(&candidate.path.to_string_lossy());
+        let target = InstalledTarget {
+            name: candidate.name.clone(),
+            version: candidate.version.clone(),
+            path: candidate.path.clone(),
+        };
+
+        let mut actions = Vec::new();
+        for category in BindingCategory::ALL {
+            let enable = desired.bindings.enabled.contains(&category);
+            let targets: Vec<String> = statuses
+                .iter()
+                .filter(|status| status.category == category && status.active != enable)
+                .map(|status| status.target.clone())
+                .collect();
+            if targets.is_empty() {
+                continue;
+            }
+
+            let container = target.clone();
+            actions.push(if enable {
+                PlannedAction::EnableBindings { container, category, targets }
+            } else {
+                PlannedAction::DisableBindings { container, category, targets }
+            });
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::setup::SetupService;
+    use crate::shared::summary::SummaryFormat;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, TempHome};
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    /// `app` 1.0.0 depending on `lib` 1.0.0, both in the default store.
+    fn store(home: &TempHome) {
+        SetupService::initialize(&home.paths()).unwrap();
+        let store = home.paths().default_store_dir();
+        let mut app = fixture_manifest("app", "1.0.0");
+        app["dependencies"] = json!([{ "name": "lib", "version": "1.0.0" }]);
+        fixture_container_at(&store.join("app/1.0.0"), app);
+        fixture_container_at(&store.join("lib/1.0.0"), fixture_manifest("lib", "1.0.0"));
+    }
+
+    fn desired(value: serde_json::Value) -> DesiredState {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn plan(state: &DesiredState) -> ApplyPlan {
+        ApplyService::plan(state, &ContainerResolver::new().unwrap()).unwrap()
+    }
+
+    fn apply(plan: &ApplyPlan) -> usize {
+        ApplyService::apply(plan, &CancellationToken::new(), &Reporter::new("apply", SummaryFormat::Json)).unwrap()
+    }
+
+    fn subjects(plan: &ApplyPlan) -> Vec<String> {
+        plan.actions
+            .iter()
+            .map(|action| match action {
+                PlannedAction::Install { name, .. } => format!("install {}", name),
+                PlannedAction::Update { name, .. } => format!("update {}", name),
+                PlannedAction::EnableBindings { container, category, .. } => {
+                    format!("enable {} of {}", category.label(), container.name)
+                }
+                PlannedAction::DisableBindings { container, category, .. } => {
+                    format!("disable {} of {}", category.label(), container.name)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn applying_twice_changes_nothing_the_second_time() {
+        let home = TempHome::enter();
+        store(&home);
+        let state = desired(json!({ "containers": [{ "name": "app" }, { "name": "lib" }] }));
+
+        let first = plan(&state);
+        // lib comes first even though the file lists app first
+        assert_eq!(subjects(&first), ["enable executables of lib", "enable executables of app"]);
+        assert_eq!(apply(&first), 2);
+        assert!(home.paths().bin_dir.join("app-tool").exists());
+
+        let second = plan(&state);
+        assert!(second.is_empty(), "{:?}", subjects(&second));
+        assert_eq!(apply(&second), 0);
+    }
+
+    #[test]
+    fn categories_left_out_are_disabled_once() {
+        let home = TempHome::enter();
+        store(&home);
+        apply(&plan(&desired(json!({ "containers": [{ "name": "lib" }] }))));
+        let state = desired(json!({ "containers": [{ "name": "lib", "bindings": { "enabled": [] } }] }));
+
+        let first = plan(&state);
+        assert_eq!(subjects(&first), ["disable executables of lib"]);
+        assert_eq!(apply(&first), 1);
+
+        assert!(!home.paths().bin_dir.join("lib-tool").exists());
+        assert!(plan(&state).is_empty());
+    }
+
+    #[test]
+    fn missing_and_outdated_containers_are_planned_but_not_applied() {
+        let home = TempHome::enter();
+        store(&home);
+        let state = desired(json!({ "containers": [
+            { "name": "lib", "version": ">=2.0.0", "bindings": { "enabled": [] } },
+            { "name": "new", "url": "https://example.com/new.tar" }
+        ] }));
+
+        let plan = plan(&state);
+
+        assert_eq!(subjects(&plan), ["update lib", "install new"]);
+        assert_matches!(&plan.actions[0], PlannedAction::Update { installed, adopted: false, .. } if installed.as_str() == "1.0.0");
+        assert_eq!(plan.unsupported().count(), 2);
+        assert_eq!(apply(&plan), 0);
+    }
+
+    #[test]
+    fn load_rejects_yaml_and_invalid_requirements() {
+        let home = TempHome::enter();
+        let yaml = home.path().join("containers.yaml");
+        std::fs::write(&yaml, "containers: []").unwrap();
+        let json_file = home.path().join("containers.json");
+        std::fs::write(&json_file, r#"{ "containers": [{ "name": "app", "version": "not a version" }] }"#).unwrap();
+
+        assert_matches!(ApplyService::load(&yaml), Err(ContainerError::InvalidConfig { .. }));
+        assert_matches!(
+            ApplyService::load(&json_file),
+            Err(ContainerError::InvalidConfig { reason, .. }) if reason.starts_with("container 'app':")
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use crate::features::bindings::BindingCategory;
+use crate::features::Version;
+
+/// Desired state of a machine as read by `wrappy apply`, e.g. a team-wide `containers.json`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DesiredState {
+    #[serde(default)]
+    pub containers: Vec<DesiredContainer>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DesiredContainer {
+    pub name: String,
+    /// Version requirement; any installed version satisfies a missing one
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Where to install the container from when it is missing
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub bindings: DesiredBindings,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DesiredBindings {
+    /// Categories whose bindings should be enabled; all others are disabled
+    #[serde(default = "DesiredBindings::all")]
+    pub enabled: Vec<BindingCategory>,
+}
+
+impl DesiredBindings {
+    fn all() -> Vec<BindingCategory> {
+        BindingCategory::ALL.to_vec()
+    }
+}
+
+impl Default for DesiredBindings {
+    fn default() -> Self {
+        Self { enabled: Self::all() }
+    }
+}
+
+/// One change needed to bring the machine to the desired state.
+#[derive(Debug, Clone)]
+pub enum PlannedAction {
+    Install {
+        name: String,
+        requirement: Option<String>,
+        url: Option<String>,
+    },
+    Update {
+        name: String,
+        installed: Version,
+        requirement: String,
+        /// Adopted directories are updated by their owner, never by wrappy
+        adopted: bool,
+    },
+    EnableBindings {
+        container: InstalledTarget,
+        category: BindingCategory,
+        /// Manifest targets of the bindings that are not active yet
+        targets: Vec<String>,
+    },
+    DisableBindings {
+        container: InstalledTarget,
+        category: BindingCategory,
+        /// Manifest targets of the bindings that are still active
+        targets: Vec<String>,
+    },
+}
+
+impl PlannedAction {
+    /// Installing and updating need a registry, which wrappy does not have yet.
+    pub fn is_supported(&self) -> bool {
+        matches!(self, Self::EnableBindings { .. } | Self::DisableBindings { .. })
+    }
+}
+
+/// Installed container version an action applies to.
+#[derive(Debug, Clone)]
+pub struct InstalledTarget {
+    pub name: String,
+    pub version: Version,
+    pub path: PathBuf,
+}
+
+/// Ordered actions; containers appear after the containers they depend on.
+#[derive(Debug, Clone, Default)]
+pub struct ApplyPlan {
+    pub actions: Vec<PlannedAction>,
+}
+
+impl ApplyPlan {
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    pub fn unsupported(&self) -> impl Iterator<Item = &PlannedAction> {
+        self.actions.iter().filter(|action| !action.is_supported())
+    }
+}
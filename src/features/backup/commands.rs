@@ -0,0 +1,123 @@
+use clap::Subcommand;
+use std::path::{Path, PathBuf};
+
+use crate::features::backup::{BackupComponent, BackupService};
+use crate::shared::filesystem;
+use crate::shared::summary::{Reporter, SummaryFormat};
+
+#[derive(Subcommand)]
+pub enum BackupCommands {
+    /// Write the config, variables, installed containers and active bindings to one archive
+    Create {
+        /// Archive to write, e.g. wrappy-backup.tar
+        file: PathBuf,
+        /// Embed every installed container, so restore can install them again
+        #[arg(long)]
+        include_containers: bool,
+    },
+    /// Restore a backup; safe to run again, finished parts are skipped
+    Restore {
+        /// Archive written by `wrappy backup create`
+        file: PathBuf,
+        /// Restore only these components
+        #[arg(long, value_enum, value_delimiter = ',')]
+        only: Vec<BackupComponent>,
+        /// Progress lines and a summary table, or only the summary as JSON
+        #[arg(long, value_enum, default_value = "text")]
+        format: SummaryFormat,
+    },
+    /// List what a backup holds without restoring it
+    Show {
+        /// Archive written by `wrappy backup create`
+        file: PathBuf,
+    },
+}
+
+impl BackupCommands {
+    /// Whether the command prints JSON for another program to read.
+    pub fn is_machine_readable(&self) -> bool {
+        matches!(self, BackupCommands::Restore { format: SummaryFormat::Json, .. })
+    }
+}
+
+pub struct BackupHandler;
+
+impl BackupHandler {
+    /// Routes and executes the appropriate backup command
+    pub fn execute_command(command: BackupCommands) -> i32 {
+        match command {
+            BackupCommands::Create { file, include_containers } => Self::handle_create_command(&file, include_containers),
+            BackupCommands::Restore { file, only, format } => Self::handle_restore_command(&file, &only, format),
+            BackupCommands::Show { file } => Self::handle_show_command(&file),
+        }
+    }
+
+    /// Handles the create command execution
+    fn handle_create_command(file: &Path, include_containers: bool) -> i32 {
+        match BackupService::new().and_then(|service| service.create(file, include_containers)) {
+            Ok(summary) => {
+                println!(
+                    "📦 Backed up {} file(s) and {} container(s) to {} ({})",
+                    summary.files - summary.embedded_containers,
+                    summary.containers,
+                    summary.path.display(),
+                    filesystem::format_size(summary.size)
+                );
+                if summary.embedded_containers == 0 && summary.containers > 0 {
+                    println!("  ℹ️  Containers are only listed; use --include-containers to embed them");
+                }
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to create the backup: {}", error);
+                1
+            }
+        }
+    }
+
+    /// Handles the restore command execution
+    fn handle_restore_command(file: &Path, only: &[BackupComponent], format: SummaryFormat) -> i32 {
+        let reporter = Reporter::new(format!("backup restore {}", file.display()), format);
+        let result = BackupService::new().and_then(|service| service.restore(file, only, &reporter));
+        if let Err(error) = result {
+            reporter.failed(file.display(), &error, format!("❌ Failed to restore {}: {}", file.display(), error));
+        }
+        reporter.finish().exit_code()
+    }
+
+    /// Handles the show command execution
+    fn handle_show_command(file: &Path) -> i32 {
+        let manifest = match BackupService::read_manifest(file) {
+            Ok(manifest) => manifest,
+            Err(error) => {
+                eprintln!("❌ {}", error);
+                return 1;
+            }
+        };
+
+        println!(
+            "📦 Backup from {} (wrappy {}, format {})",
+            manifest.created_at.format("%Y-%m-%d %H:%M"),
+            manifest.created_with,
+            manifest.format_version
+        );
+        for component in BackupComponent::ALL {
+            let files: Vec<_> = manifest.files_of(component).collect();
+            let size: u64 = files.iter().map(|file| file.size).sum();
+            println!("  {:<11} {} file(s), {}", component, files.len(), filesystem::format_size(size));
+        }
+        if !manifest.containers.is_empty() {
+            println!();
+            for container in &manifest.containers {
+                let embedded = if container.archive.is_some() { "embedded" } else { "listed only" };
+                println!(
+                    "  🔗 {} ({}, {} active binding(s))",
+                    container.label(),
+                    embedded,
+                    container.active_bindings.len()
+                );
+            }
+        }
+        0
+    }
+}
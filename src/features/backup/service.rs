@@ -0,0 +1,608 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use crate::features::backup::{
+    BackedUpContainer, BackupComponent, BackupFile, BackupManifest, BackupSummary, BACKUP_FORMAT_VERSION,
+    BACKUP_MANIFEST_FILE,
+};
+use crate::features::bindings::{BindingManager, BindingsConfig};
+use crate::features::container::{Container, ContainerService, ExportService, InstallMetadata, RegistryEntry};
+use crate::features::query;
+use crate::shared::archive::{EntryHeader, EntryKind, TarReader, TarWriter};
+use crate::shared::clock;
+use crate::shared::config::Config;
+use crate::shared::digest::{DigestWriter, Sha256};
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::staging::StagingArea;
+use crate::shared::summary::Reporter;
+
+/// Kept next to the config file when a restore replaces a different one.
+const PRE_RESTORE_SUFFIX: &str = ".pre-restore";
+
+/// A file of the backup, read and checked against its hash.
+enum Staged {
+    Bytes(Vec<u8>),
+    /// Container archives are spooled to disk instead of memory
+    File(PathBuf),
+}
+
+/// Backs up everything wrappy knows about a machine into one tar archive and
+/// replays it elsewhere. The archive starts with a `BackupManifest` listing every
+/// file with its component and SHA-256; restore only applies files whose hash matches.
+///
+/// Restore never redoes finished work: identical files, installed containers and
+/// active bindings are skipped, so it can simply be re-run after a partial failure.
+pub struct BackupService {
+    paths: WrappyPaths,
+}
+
+impl BackupService {
+    pub fn new() -> ContainerResult<Self> {
+        Ok(Self::with_paths(WrappyPaths::new()?))
+    }
+
+    pub fn with_paths(paths: WrappyPaths) -> Self {
+        Self { paths }
+    }
+
+    /// Writes a backup to `output` through a temporary file. Container directories
+    /// are embedded as exports only with `include_containers`.
+    pub fn create(&self, output: &Path, include_containers: bool) -> ContainerResult<BackupSummary> {
+        let mut sources: Vec<(BackupFile, PathBuf)> = Vec::new();
+        let config_file = self.paths.config_file();
+        if config_file.is_file() {
+            sources.push(Self::describe(BackupComponent::Config, "config/config.json".to_string(), &config_file)?);
+        }
+        for file in Self::files_in(&self.paths.variables_dir())? {
+            let name = file.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            sources.push(Self::describe(BackupComponent::Variables, format!("variables/{}", name), &file)?);
+        }
+        let state_file = self.paths.binding_state_file();
+        if state_file.is_file() {
+            sources.push(Self::describe(BackupComponent::Bindings, "bindings/bindings.json".to_string(), &state_file)?);
+        }
+
+        let spool = StagingArea::create_in(&self.paths.staging_dir())?;
+        let mut containers = Vec::new();
+        let result = (|| -> ContainerResult<BackupSummary> {
+            for installed in query::installed_containers() {
+                let active_bindings = query::bindings_of(&installed.path.to_string_lossy())
+                    .into_iter()
+                    .filter(|binding| binding.active)
+                    .map(|binding| binding.target)
+                    .collect();
+                let mut entry = BackedUpContainer {
+                    name: installed.name.clone(),
+                    version: installed.version.clone(),
+                    path: installed.path.clone(),
+                    active_bindings,
+                    archive: None,
+                };
+                if include_containers {
+                    let container = ContainerService::load_from_directory(&installed.path)?;
+                    let archive_path = format!("containers/{}", ExportService::default_file_name(&container));
+                    let spooled = spool.join(ExportService::default_file_name(&container));
+                    // A backup is where the data has to survive, unlike a shared export
+                    let export = ExportService::export_to_file(&container, &spooled, false, true, None)?;
+                    sources.push((
+                        BackupFile {
+                            component: BackupComponent::Containers,
+                            path: archive_path.clone(),
+                            sha256: export.sha256,
+                            size: export.size,
+                        },
+                        spooled,
+                    ));
+                    entry.archive = Some(archive_path);
+                }
+                containers.push(entry);
+            }
+
+            let manifest = BackupManifest {
+                format_version: BACKUP_FORMAT_VERSION,
+                created_at: clock::now(),
+                created_with: env!("CARGO_PKG_VERSION").to_string(),
+                files: sources.iter().map(|(file, _)| file.clone()).collect(),
+                containers,
+            };
+            self.write_archive(output, &manifest, &sources)
+        })();
+        drop(spool);
+        result
+    }
+
+    /// The manifest of the backup at `input`, without reading the rest.
+    pub fn read_manifest(input: &Path) -> ContainerResult<BackupManifest> {
+        let mut tar = Self::open(input)?;
+        Self::manifest_from(input, &mut tar)
+    }
+
+    /// Replays the backup at `input` onto this machine, limited to `only` when it is not
+    /// empty. Every file, container and binding is reported through `reporter`; what
+    /// cannot be reconstructed is reported as failed and the rest still restored.
+    pub fn restore(&self, input: &Path, only: &[BackupComponent], reporter: &Reporter) -> ContainerResult<()> {
+        let selected: BTreeSet<BackupComponent> = match only {
+            [] => BackupComponent::ALL.into_iter().collect(),
+            only => only.iter().copied().collect(),
+        };
+        let mut tar = Self::open(input)?;
+        let manifest = Self::manifest_from(input, &mut tar)?;
+        reporter.info(format!(
+            "📦 Backup from {} (wrappy {}), {} container(s)",
+            manifest.created_at.format("%Y-%m-%d %H:%M"),
+            manifest.created_with,
+            manifest.containers.len()
+        ));
+
+        let spool = StagingArea::create_in(&self.paths.staging_dir())?;
+        let result = (|| -> ContainerResult<()> {
+            let staged = Self::stage(input, &mut tar, &manifest, &selected, spool.path(), reporter)?;
+            for component in BackupComponent::ALL.into_iter().filter(|component| selected.contains(component)) {
+                match component {
+                    BackupComponent::Config => self.restore_config(&manifest, &staged, reporter)?,
+                    BackupComponent::Variables => self.restore_variables(&manifest, &staged, reporter)?,
+                    BackupComponent::Containers => self.restore_containers(&manifest, &staged, reporter)?,
+                    BackupComponent::Bindings => self.restore_bindings(&manifest, reporter)?,
+                }
+            }
+            Ok(())
+        })();
+        drop(spool);
+        result
+    }
+
+    /// Reads every selected file of the archive and checks its hash. Files that are
+    /// missing or do not match are reported as failed and left out of the result.
+    fn stage(
+        input: &Path,
+        tar: &mut TarReader<BufReader<File>>,
+        manifest: &BackupManifest,
+        selected: &BTreeSet<BackupComponent>,
+        spool: &Path,
+        reporter: &Reporter,
+    ) -> ContainerResult<HashMap<String, Staged>> {
+        let wanted: HashMap<&str, &BackupFile> = manifest
+            .files
+            .iter()
+            .filter(|file| selected.contains(&file.component))
+            .map(|file| (file.path.as_str(), file))
+            .collect();
+
+        let mut staged = HashMap::new();
+        let mut mismatched = BTreeSet::new();
+        while let Some(header) = tar.next_entry().map_err(|e| Self::io_error(input, e))? {
+            let Some(file) = wanted.get(header.path.as_str()) else {
+                continue;
+            };
+            let (content, sha256) = match file.component {
+                BackupComponent::Containers => {
+                    let path = spool.join(header.path.replace('/', "_"));
+                    let output = File::create(&path).map_err(|e| Self::io_error(&path, e))?;
+                    let mut writer = DigestWriter::new(output);
+                    io::copy(&mut tar.data(), &mut writer).map_err(|e| Self::io_error(input, e))?;
+                    let (_, sha256, _) = writer.finish();
+                    (Staged::File(path), sha256)
+                }
+                _ => {
+                    let mut bytes = Vec::new();
+                    tar.data().read_to_end(&mut bytes).map_err(|e| Self::io_error(input, e))?;
+                    let mut hasher = Sha256::new();
+                    hasher.update(&bytes);
+                    (Staged::Bytes(bytes), hasher.finish_hex())
+                }
+            };
+            if sha256 == file.sha256 {
+                staged.insert(file.path.clone(), content);
+            } else {
+                mismatched.insert(file.path.clone());
+                let error = ContainerError::InvalidPath {
+                    path: input.to_path_buf(),
+                    reason: format!("'{}' does not match its hash in the backup manifest", file.path),
+                };
+                reporter.failed(&file.path, &error, format!("❌ {}", error));
+            }
+        }
+
+        let mut missing: Vec<&&BackupFile> = wanted
+            .values()
+            .filter(|file| !staged.contains_key(&file.path) && !mismatched.contains(&file.path))
+            .collect();
+        missing.sort_by(|a, b| a.path.cmp(&b.path));
+        for file in missing {
+            let error = ContainerError::InvalidPath {
+                path: input.to_path_buf(),
+                reason: format!("'{}' is listed in the backup manifest but missing from it", file.path),
+            };
+            reporter.failed(&file.path, &error, format!("❌ {}", error));
+        }
+        Ok(staged)
+    }
+
+    fn restore_config(&self, manifest: &BackupManifest, staged: &HashMap<String, Staged>, reporter: &Reporter) -> ContainerResult<()> {
+        for file in manifest.files_of(BackupComponent::Config) {
+            let Some(Staged::Bytes(content)) = staged.get(&file.path) else {
+                continue;
+            };
+            if let Err(error) = serde_json::from_slice::<Config>(content) {
+                let error = ContainerError::InvalidConfig {
+                    path: self.paths.config_file(),
+                    reason: format!("the backed-up config is not valid: {}", error),
+                };
+                reporter.failed(&file.path, &error, format!("❌ {}", error));
+                continue;
+            }
+            filesystem::create_state_dir(&self.paths.config_dir)?;
+            self.restore_file(&self.paths.config_file(), content, reporter)?;
+        }
+        Ok(())
+    }
+
+    fn restore_variables(&self, manifest: &BackupManifest, staged: &HashMap<String, Staged>, reporter: &Reporter) -> ContainerResult<()> {
+        for file in manifest.files_of(BackupComponent::Variables) {
+            let Some(Staged::Bytes(content)) = staged.get(&file.path) else {
+                continue;
+            };
+            let name = Path::new(&file.path).file_name().unwrap_or_default();
+            filesystem::create_state_dir(&self.paths.variables_dir())?;
+            self.restore_file(&self.paths.variables_dir().join(name), content, reporter)?;
+        }
+        Ok(())
+    }
+
+    /// Unpacks embedded containers that are not installed yet. The store is taken
+    /// from the config as it is now, i.e. after a restored config.
+    fn restore_containers(&self, manifest: &BackupManifest, staged: &HashMap<String, Staged>, reporter: &Reporter) -> ContainerResult<()> {
+        let store_dir = Config::load_or_default(&self.paths)?.store_dir(&self.paths);
+        for entry in &manifest.containers {
+            let label = entry.label();
+            // The name becomes a path below the store, so one like `../x` must never reach a join
+            if let Err(error) = RegistryEntry::check_name(&entry.name) {
+                reporter.failed(&label, &error, format!("❌ Refusing to install {}: {}", label, error));
+                continue;
+            }
+            let destination = store_dir.join(&entry.name).join(entry.version.to_string());
+            if destination.join("manifest.json").is_file() {
+                reporter.skipped(&label, "already installed", format!("✅ {} is already installed", label));
+                continue;
+            }
+            let Some(archive) = &entry.archive else {
+                let error = ContainerError::ContainerNotFound {
+                    name: label.clone(),
+                    suggestions: Vec::new(),
+                };
+                reporter.failed(
+                    &label,
+                    &error,
+                    format!("❌ {} is not embedded in the backup; install it again or back up with --include-containers", label),
+                );
+                continue;
+            };
+            let Some(Staged::File(spooled)) = staged.get(archive) else {
+                // Already reported while staging
+                continue;
+            };
+
+            match self.install_archive(spooled, &store_dir, &destination, entry) {
+                Ok(()) => reporter.created(&label, format!("📦 Installed {} into {}", label, destination.display())),
+                Err(error) => reporter.failed(&label, &error, format!("❌ Failed to install {}: {}", label, error)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Enables the bindings that were active and are not active now.
+    fn restore_bindings(&self, manifest: &BackupManifest, reporter: &Reporter) -> ContainerResult<()> {
+        let store_dir = Config::load_or_default(&self.paths)?.store_dir(&self.paths);
+        for entry in manifest.containers.iter().filter(|entry| !entry.active_bindings.is_empty()) {
+            if RegistryEntry::check_name(&entry.name).is_err() {
+                // Reported by restore_containers
+                continue;
+            }
+            let label = entry.label();
+            let path = store_dir.join(&entry.name).join(entry.version.to_string());
+            let container = match ContainerService::load_from_directory(&path) {
+                Ok(container) => container,
+                Err(error) => {
+                    reporter.failed(
+                        &label,
+                        &error,
+                        format!("❌ Cannot enable the bindings of {}, it is not installed: {}", label, error),
+                    );
+                    continue;
+                }
+            };
+
+            let active: BTreeSet<String> = query::bindings_of(&path.to_string_lossy())
+                .into_iter()
+                .filter(|binding| binding.active)
+                .map(|binding| binding.target)
+                .collect();
+            let wanted: BTreeSet<&String> = entry.active_bindings.iter().filter(|target| !active.contains(*target)).collect();
+            if wanted.is_empty() {
+                reporter.skipped(&label, "bindings already active", format!("✅ Bindings of {} are already active", label));
+                continue;
+            }
+
+            let bindings = Self::select_bindings(&container, &wanted);
+            let declared: BTreeSet<&String> = bindings
+                .executables
+                .iter()
+                .map(|binding| &binding.target)
+                .chain(bindings.configs.iter().map(|binding| &binding.target))
+                .chain(bindings.data.iter().map(|binding| &binding.target))
+                .collect();
+            for target in wanted.iter().filter(|target| !declared.contains(*target)) {
+                reporter.warn(format!("{} no longer declares a binding for '{}'", label, target));
+            }
+            if bindings.is_empty() {
+                continue;
+            }
+
+            let mut filtered = container.clone();
+            filtered.manifest.bindings = bindings;
+            let result = BindingManager::new().and_then(|manager| manager.with_reporter(reporter.clone()).install_bindings(&filtered));
+            if let Err(error) = result {
+                reporter.failed(&label, &error, format!("❌ Failed to enable the bindings of {}: {}", label, error));
+            }
+        }
+        Ok(())
+    }
+
+    /// Bindings of `container` whose manifest target is one of `targets`.
+    fn select_bindings(container: &Container, targets: &BTreeSet<&String>) -> BindingsConfig {
+        let declared = &container.manifest.bindings;
+        let mut selected = BindingsConfig::new();
+        selected.bin_dir = declared.bin_dir.clone();
+        for executable in declared.executables.iter().filter(|binding| targets.contains(&binding.target)) {
+            selected.add_executable(executable.clone());
+        }
+        for config in declared.configs.iter().filter(|binding| targets.contains(&binding.target)) {
+            selected.add_config(config.clone());
+        }
+        for data in declared.data.iter().filter(|binding| targets.contains(&binding.target)) {
+            selected.add_data(data.clone());
+        }
+        for autostart in declared.autostart.iter().filter(|binding| targets.contains(&binding.executable)) {
+            selected.add_autostart(autostart.clone());
+        }
+        selected
+    }
+
+    /// Unpacks into a staging area on the filesystem of the store and moves the result
+    /// into place, so an interrupted restore never leaves a half-installed container.
+    fn install_archive(&self, archive: &Path, store_dir: &Path, destination: &Path, entry: &BackedUpContainer) -> ContainerResult<()> {
+        RegistryEntry::check_name(&entry.name)?;
+        let area = StagingArea::create(&self.paths, store_dir)?;
+        let staging = area.join(entry.label());
+        ExportService::unpack_archive(archive, &staging).and_then(|_| {
+            let container = ContainerService::load_from_directory(&staging)?;
+            if container.name() != entry.name || *container.version() != entry.version {
+                return Err(ContainerError::InvalidStructure(format!(
+                    "archive holds {}@{} instead of {}",
+                    container.name(),
+                    container.version(),
+                    entry.label()
+                )));
+            }
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).map_err(|e| Self::io_error(parent, e))?;
+            }
+            fs::rename(&staging, destination).map_err(|e| Self::io_error(destination, e))
+        })?;
+        drop(area);
+        InstallMetadata::record_installed_with(destination)
+    }
+
+    /// Writes `content` unless the file already holds it. A different file is
+    /// replaced, keeping the first version it had before any restore.
+    fn restore_file(&self, path: &Path, content: &[u8], reporter: &Reporter) -> ContainerResult<()> {
+        let subject = path.display();
+        match fs::read(path) {
+            Ok(existing) if existing == content => {
+                reporter.skipped(&subject, "unchanged", format!("✅ {} is already up to date", subject));
+                return Ok(());
+            }
+            Ok(existing) => {
+                let mut backup = path.as_os_str().to_owned();
+                backup.push(PRE_RESTORE_SUFFIX);
+                let backup = PathBuf::from(backup);
+                if !backup.exists() {
+                    filesystem::atomic_write(&backup, &existing, Some(filesystem::STATE_FILE_MODE))?;
+                }
+                filesystem::atomic_write(path, content, Some(filesystem::STATE_FILE_MODE))?;
+                reporter.updated(&subject, format!("🔄 Restored {} (previous kept as {})", subject, backup.display()));
+            }
+            Err(_) => {
+                filesystem::atomic_write(path, content, Some(filesystem::STATE_FILE_MODE))?;
+                reporter.created(&subject, format!("✅ Restored {}", subject));
+            }
+        }
+        Ok(())
+    }
+
+    fn write_archive(&self, output: &Path, manifest: &BackupManifest, sources: &[(BackupFile, PathBuf)]) -> ContainerResult<BackupSummary> {
+        let mut partial = output.as_os_str().to_owned();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+
+        let result = (|| -> ContainerResult<u64> {
+            let file = filesystem::state_file_options()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&partial)
+                .map_err(|e| Self::io_error(&partial, e))?;
+            let mut tar = TarWriter::new(DigestWriter::new(file));
+            let mut manifest_json = serde_json::to_vec_pretty(manifest)?;
+            manifest_json.push(b'\n');
+            tar.append(&Self::header(BACKUP_MANIFEST_FILE, manifest_json.len() as u64), manifest_json.as_slice())
+                .map_err(|e| Self::io_error(&partial, e))?;
+
+            for (file, source) in sources {
+                let reader = File::open(source).map_err(|e| Self::io_error(source, e))?;
+                tar.append(&Self::header(&file.path, file.size), reader)
+                    .map_err(|e| Self::io_error(source, e))?;
+            }
+            let (file, _, size) = tar.finish().map_err(|e| Self::io_error(&partial, e))?.finish();
+            file.sync_all().map_err(|e| Self::io_error(&partial, e))?;
+            fs::rename(&partial, output).map_err(|e| Self::io_error(output, e))?;
+            Ok(size)
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&partial);
+        }
+
+        Ok(BackupSummary {
+            path: output.to_path_buf(),
+            size: result?,
+            files: manifest.files.len(),
+            containers: manifest.containers.len(),
+            embedded_containers: manifest.containers.iter().filter(|entry| entry.archive.is_some()).count(),
+        })
+    }
+
+    fn open(input: &Path) -> ContainerResult<TarReader<BufReader<File>>> {
+        let file = File::open(input).map_err(|e| Self::io_error(input, e))?;
+        Ok(TarReader::new(BufReader::new(file)))
+    }
+
+    /// The manifest must be the first entry; newer formats are refused.
+    fn manifest_from(input: &Path, tar: &mut TarReader<BufReader<File>>) -> ContainerResult<BackupManifest> {
+        let not_a_backup = || ContainerError::InvalidPath {
+            path: input.to_path_buf(),
+            reason: format!("not a wrappy backup ({} is not its first entry)", BACKUP_MANIFEST_FILE),
+        };
+        let header = tar
+            .next_entry()
+            .map_err(|e| Self::io_error(input, e))?
+            .ok_or_else(not_a_backup)?;
+        if header.path != BACKUP_MANIFEST_FILE {
+            return Err(not_a_backup());
+        }
+        let mut content = Vec::new();
+        tar.data().read_to_end(&mut content).map_err(|e| Self::io_error(input, e))?;
+
+        let format_version = serde_json::from_slice::<serde_json::Value>(&content)?
+            .get("format_version")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(0) as u32;
+        if format_version > BACKUP_FORMAT_VERSION {
+            return Err(ContainerError::StateFileTooNew {
+                path: input.to_path_buf(),
+                version: format_version,
+                supported: BACKUP_FORMAT_VERSION,
+            });
+        }
+        Ok(serde_json::from_slice(&content)?)
+    }
+
+    /// A file to back up with its hash and size.
+    fn describe(component: BackupComponent, path: String, source: &Path) -> ContainerResult<(BackupFile, PathBuf)> {
+        let mut writer = DigestWriter::new(io::sink());
+        let mut reader = File::open(source).map_err(|e| Self::io_error(source, e))?;
+        io::copy(&mut reader, &mut writer).map_err(|e| Self::io_error(source, e))?;
+        let (_, sha256, size) = writer.finish();
+        Ok((BackupFile { component, path, sha256, size }, source.to_path_buf()))
+    }
+
+    /// Regular files directly in `directory`, sorted; none when it does not exist.
+    fn files_in(directory: &Path) -> ContainerResult<Vec<PathBuf>> {
+        let Ok(entries) = fs::read_dir(directory) else {
+            return Ok(Vec::new());
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| Self::io_error(directory, e))?;
+            if entry.file_type().is_ok_and(|file_type| file_type.is_file()) {
+                files.push(entry.path());
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Entries are private to the user: the config and variables may hold secrets.
+    fn header(path: &str, size: u64) -> EntryHeader {
+        EntryHeader {
+            path: path.to_string(),
+            kind: EntryKind::File,
+            mode: 0o600,
+            size,
+            mtime: clock::now().timestamp().max(0) as u64,
+            uid: 0,
+            gid: 0,
+            user_name: String::new(),
+            group_name: String::new(),
+        }
+    }
+
+    fn io_error(path: &Path, source: io::Error) -> ContainerError {
+        ContainerError::IoError {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::{ContainerResolver, InstallService};
+    use crate::features::setup::SetupService;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, TempHome};
+    use crate::shared::CancellationToken;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn an_embedded_container_whose_name_is_a_path_is_never_unpacked() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        let store_dir = paths.default_store_dir();
+        fs::create_dir_all(&store_dir).unwrap();
+        let archive = home.path().join("container.tar");
+        fs::write(&archive, "").unwrap();
+        let entry = BackedUpContainer {
+            name: "../../escaped".to_string(),
+            version: "1.0.0".parse().unwrap(),
+            path: PathBuf::from("/elsewhere"),
+            active_bindings: Vec::new(),
+            archive: Some("containers/evil.tar".to_string()),
+        };
+        let destination = store_dir.join(&entry.name).join("1.0.0");
+
+        let result = BackupService::with_paths(paths).install_archive(&archive, &store_dir, &destination, &entry);
+
+        assert!(matches!(result, Err(ContainerError::ManifestValidation(_))), "{:?}", result);
+        assert!(!home.path().join("escaped").exists());
+        assert!(!store_dir.parent().unwrap().join("escaped").exists());
+    }
+
+    #[test]
+    fn a_container_with_links_between_its_directories_survives_a_backup_round_trip() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        SetupService::initialize(&paths).unwrap();
+        let source = home.path().join("src/app");
+        fixture_container_at(&source, fixture_manifest("app", "1.0.0"));
+        fs::write(source.join("content/tool"), "real tool").unwrap();
+        fs::remove_file(source.join("bin/tool")).unwrap();
+        symlink("../content/tool", source.join("bin/tool")).unwrap();
+        let resolver = ContainerResolver::new().unwrap();
+        InstallService::install(&source, None, false, false, &paths, &resolver, &CancellationToken::new()).unwrap();
+        let backup = home.path().join("backup.tar");
+        let service = BackupService::with_paths(paths.clone());
+
+        service.create(&backup, true).unwrap();
+        let installed = paths.default_store_dir().join("app/1.0.0");
+        fs::remove_dir_all(&installed).unwrap();
+        let reporter = Reporter::default();
+        service.restore(&backup, &[BackupComponent::Containers], &reporter).unwrap();
+
+        assert_eq!(fs::read_link(installed.join("bin/tool")).unwrap(), Path::new("../content/tool"));
+        assert_eq!(fs::read_to_string(installed.join("bin/tool")).unwrap(), "real tool");
+    }
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::features::Version;
+
+/// Format of `wrappy-backup.json`; raised whenever older wrappy could misread a backup.
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// First entry of every backup archive.
+pub const BACKUP_MANIFEST_FILE: &str = "wrappy-backup.json";
+
+/// Independently restorable part of a backup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupComponent {
+    /// The config file, profiles included
+    Config,
+    /// Values chosen for manifest variables
+    Variables,
+    /// Installed containers, from the embedded archives
+    Containers,
+    /// Bindings that were active, enabled again
+    Bindings,
+}
+
+impl BackupComponent {
+    /// Restore order: containers need their variables, bindings need their containers.
+    pub const ALL: [BackupComponent; 4] = [Self::Config, Self::Variables, Self::Containers, Self::Bindings];
+}
+
+impl fmt::Display for BackupComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::Config => "config",
+            Self::Variables => "variables",
+            Self::Containers => "containers",
+            Self::Bindings => "bindings",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Index of a backup archive: what it holds and the hash of every file in it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub created_at: DateTime<Utc>,
+    pub created_with: String,
+    pub files: Vec<BackupFile>,
+    /// Containers installed when the backup was made
+    pub containers: Vec<BackedUpContainer>,
+}
+
+impl BackupManifest {
+    pub fn files_of(&self, component: BackupComponent) -> impl Iterator<Item = &BackupFile> {
+        self.files.iter().filter(move |file| file.component == component)
+    }
+}
+
+/// One file of the archive with the component it belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupFile {
+    pub component: BackupComponent,
+    /// Path inside the archive
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// Registry entry of one installed container.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackedUpContainer {
+    pub name: String,
+    pub version: Version,
+    /// Where it was installed on the backed-up machine
+    pub path: PathBuf,
+    /// Manifest targets of the bindings that were active
+    #[serde(default)]
+    pub active_bindings: Vec<String>,
+    /// Path of its export inside the backup, with `--include-containers`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<String>,
+}
+
+impl BackedUpContainer {
+    pub fn label(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+/// Result of `backup create`.
+#[derive(Debug, Clone)]
+pub struct BackupSummary {
+    pub path: PathBuf,
+    pub size: u64,
+    pub files: usize,
+    pub containers: usize,
+    pub embedded_containers: usize,
+}
@@ -0,0 +1,226 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::features::bindings::AutostartBinding;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+
+/// Key every autostart entry wrappy writes carries, naming its container.
+const CONTAINER_KEY: &str = "X-Wrappy-Container";
+
+/// Key naming the executable binding an entry starts, as written in the manifest.
+const EXECUTABLE_KEY: &str = "X-Wrappy-Executable";
+
+/// What an installed autostart entry says about itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutostartMetadata {
+    pub container: String,
+    pub executable: String,
+    pub hidden: bool,
+}
+
+/// State of a declared autostart entry on the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutostartState {
+    /// Installed and started with the session
+    Active,
+    /// Installed with `Hidden=true`, so the session skips it
+    Hidden,
+    NotInstalled,
+}
+
+impl std::fmt::Display for AutostartState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Self::Active => "active",
+            Self::Hidden => "hidden (disabled with 'wrappy bindings autostart disable')",
+            Self::NotInstalled => "not installed",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Writes XDG autostart entries (`~/.config/autostart/*.desktop`) that start an
+/// executable binding with the session. `Hidden=true` switches an entry off
+/// without removing it, which is how desktop environments disable autostart too.
+pub struct AutostartGenerator {
+    autostart_dir: PathBuf,
+}
+
+impl AutostartGenerator {
+    pub fn new(autostart_dir: PathBuf) -> Self {
+        Self { autostart_dir }
+    }
+
+    /// Directory the entries are written to.
+    pub fn autostart_dir(&self) -> &Path {
+        &self.autostart_dir
+    }
+
+    /// `wrappy-<container>-<executable>.desktop`, so entries of different containers never collide.
+    pub fn entry_path(&self, container_name: &str, binding: &AutostartBinding) -> PathBuf {
+        let executable: String = binding
+            .executable_name()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+            .collect();
+        self.autostart_dir
+            .join(format!("wrappy-{}-{}.desktop", container_name, executable))
+    }
+
+    /// Writes the entry for `binding`, starting `exec_path`. An entry that was hidden stays
+    /// hidden, so re-enabling bindings does not undo `wrappy bindings autostart disable`.
+    /// Returns the entry path and whether it replaced an existing one.
+    pub fn create_entry(
+        &self,
+        container_name: &str,
+        binding: &AutostartBinding,
+        exec_path: &Path,
+    ) -> ContainerResult<(PathBuf, bool)> {
+        let path = self.entry_path(container_name, binding);
+        let existing = Self::read_metadata(&path);
+        let hidden = existing.as_ref().is_some_and(|metadata| metadata.hidden);
+
+        fs::create_dir_all(&self.autostart_dir).map_err(|e| ContainerError::IoError {
+            path: self.autostart_dir.clone(),
+            source: e,
+        })?;
+        let content = Self::render_entry(container_name, binding, exec_path, hidden);
+        filesystem::atomic_write(&path, content.as_bytes(), Some(0o644))?;
+        Ok((path, existing.is_some()))
+    }
+
+    /// Switches an installed entry on or off. Returns `false` when it was already in that state.
+    pub fn set_hidden(path: &Path, hidden: bool) -> ContainerResult<bool> {
+        let content = fs::read_to_string(path).map_err(|e| ContainerError::IoError {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        let current = Self::parse(&content).is_some_and(|metadata| metadata.hidden);
+        if current == hidden {
+            return Ok(false);
+        }
+
+        let mut lines: Vec<String> = content
+            .lines()
+            .filter(|line| !line.starts_with("Hidden="))
+            .map(str::to_string)
+            .collect();
+        if hidden {
+            // Right after the group header, where the rest of the entry cannot shadow it
+            let header = lines.iter().position(|line| line == "[Desktop Entry]").map_or(0, |index| index + 1);
+            lines.insert(header, "Hidden=true".to_string());
+        }
+        let mut updated = lines.join("\n");
+        updated.push('\n');
+        filesystem::atomic_write(path, updated.as_bytes(), Some(0o644))?;
+        Ok(true)
+    }
+
+    /// Removes an entry wrappy wrote; anything else at that path is left alone.
+    pub fn remove_entry(path: &Path) -> ContainerResult<bool> {
+        if Self::read_metadata(path).is_none() {
+            return Ok(false);
+        }
+        fs::remove_file(path).map_err(|e| ContainerError::IoError {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        Ok(true)
+    }
+
+    /// State of the entry for `binding`.
+    pub fn state(&self, container_name: &str, binding: &AutostartBinding) -> AutostartState {
+        match Self::read_metadata(&self.entry_path(container_name, binding)) {
+            Some(metadata) if metadata.hidden => AutostartState::Hidden,
+            Some(_) => AutostartState::Active,
+            None => AutostartState::NotInstalled,
+        }
+    }
+
+    /// Installed entries of `container_name`, found by their marker rather than their file name.
+    pub fn entries_of(&self, container_name: &str) -> Vec<(PathBuf, AutostartMetadata)> {
+        let Ok(entries) = fs::read_dir(&self.autostart_dir) else {
+            return Vec::new();
+        };
+        let mut found: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "desktop"))
+            .filter_map(|path| Self::read_metadata(&path).map(|metadata| (path, metadata)))
+            .filter(|(_, metadata)| metadata.container == container_name)
+            .collect();
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        found
+    }
+
+    /// Reads the wrappy keys of an entry; `None` for files wrappy did not write.
+    pub fn read_metadata(path: &Path) -> Option<AutostartMetadata> {
+        Self::parse(&fs::read_to_string(path).ok()?)
+    }
+
+    fn parse(content: &str) -> Option<AutostartMetadata> {
+        let value = |key: &str| {
+            content
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+                .map(Self::unescape_value)
+        };
+        Some(AutostartMetadata {
+            container: value(CONTAINER_KEY)?,
+            executable: value(EXECUTABLE_KEY)?,
+            hidden: value("Hidden").is_some_and(|hidden| hidden == "true"),
+        })
+    }
+
+    fn render_entry(container_name: &str, binding: &AutostartBinding, exec_path: &Path, hidden: bool) -> String {
+        let name = binding.name.clone().unwrap_or_else(|| binding.executable_name());
+        let mut lines = vec!["[Desktop Entry]".to_string()];
+        if hidden {
+            lines.push("Hidden=true".to_string());
+        }
+        lines.extend([
+            "Type=Application".to_string(),
+            format!("Name={}", Self::escape_value(&name)),
+            format!("Comment={}", Self::escape_value(&format!("Started by wrappy from container {}", container_name))),
+            format!("Exec={}", Self::escape_value(&Self::quote_exec_argument(&exec_path.to_string_lossy()))),
+            "Terminal=false".to_string(),
+        ]);
+        if let Some(delay) = binding.delay_secs {
+            lines.push(format!("X-GNOME-Autostart-Delay={}", delay));
+        }
+        lines.push(format!("{}={}", CONTAINER_KEY, Self::escape_value(container_name)));
+        lines.push(format!("{}={}", EXECUTABLE_KEY, Self::escape_value(&binding.executable)));
+
+        let mut content = lines.join("\n");
+        content.push('\n');
+        content
+    }
+
+    /// Quotes one `Exec` argument as the desktop entry spec asks: inside double quotes,
+    /// `"`, `` ` ``, `$` and `\` are backslash-escaped, and `%` is doubled everywhere.
+    fn quote_exec_argument(argument: &str) -> String {
+        let mut quoted = String::from("\"");
+        for c in argument.chars() {
+            match c {
+                '"' | '`' | '$' | '\\' => {
+                    quoted.push('\\');
+                    quoted.push(c);
+                }
+                '%' => quoted.push_str("%%"),
+                _ => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    /// String escaping of desktop entry values, applied before `Exec` quoting is undone.
+    fn escape_value(value: &str) -> String {
+        value.replace('\\', r"\\")
+    }
+
+    fn unescape_value(value: &str) -> String {
+        value.replace(r"\\", "\\")
+    }
+}
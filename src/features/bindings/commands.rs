@@ -1,14 +1,37 @@
 use clap::Subcommand;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-use crate::features::bindings::BindingManager;
-use crate::features::container::{Container, ContainerService};
+use crate::features::bindings::{
+    Argv0Mode, BindingCategory, BindingFilter, BindingManager, BindingType, CommandPrecedence, ListingStatus,
+    PolicyViolation, PrecedenceFormat, PrecedenceService, ReconcileService, ResolvedBinding, TargetState, VerifyService, WrapperState,
+};
+use crate::features::container::{
+    install_interrupt_handler, interrupted, parse_variable_overrides, Container, ContainerResolver, ContainerService,
+    VariableStore,
+};
+use crate::features::profile::ProfileService;
 use crate::shared::error::ContainerError;
+use crate::shared::path_sanity;
+use crate::shared::shell_quote::posix_quote;
+use crate::shared::summary::{Reporter, SummaryFormat};
+use crate::shared::paths::WrappyPaths;
+use crate::shared::{CancellationToken, Redact};
 
 #[derive(Subcommand)]
 pub enum BindingsCommands {
-    /// List all active bindings
-    List,
+    /// List the bindings of every container: executables, configs and data
+    List {
+        /// Only list bindings of the containers in this profile
+        #[arg(long, conflicts_with = "container")]
+        profile: Option<String>,
+        /// Only list bindings of this container
+        #[arg(long)]
+        container: Option<String>,
+        /// Print the bindings as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Enable bindings for a container
     Enable {
         /// Container name or path to enable bindings for
@@ -22,49 +45,175 @@ pub enum BindingsCommands {
         /// Only enable data bindings
         #[arg(long)]
         data_only: bool,
+        /// Only enable the config or data binding with this label
+        #[arg(long)]
+        label: Option<String>,
+        /// Put this container's wrappers and bare-name targets here instead of the bin directory
+        #[arg(long)]
+        bin_dir: Option<PathBuf>,
+        /// Value for a manifest variable, e.g. --set PROJECTS_DIR=~/code (repeatable)
+        #[arg(long = "set", value_name = "NAME=VALUE")]
+        set: Vec<String>,
+        /// Create bindings even if the config's binding policy forbids them
+        #[arg(long)]
+        override_policy: bool,
+        /// Skip the checks that fail enable before anything is created, for setups they misjudge
+        #[arg(long)]
+        skip_preflight: bool,
+        /// Take over targets another container's bindings own, removing its bindings there
+        #[arg(long)]
+        force: bool,
+        /// Progress lines and a summary table, or only the summary as JSON
+        #[arg(long, value_enum, default_value = "text")]
+        format: SummaryFormat,
     },
     /// Disable bindings for a container
     Disable {
         /// Container name or path to disable bindings for
         container: String,
+        /// Only disable the binding with this target path
+        #[arg(long)]
+        target: Option<String>,
+        /// Only disable bindings of this kind
+        #[arg(long, value_enum)]
+        only: Option<BindingCategory>,
+        /// Only disable the config or data binding with this label
+        #[arg(long)]
+        label: Option<String>,
+        /// Progress lines and a summary table, or only the summary as JSON
+        #[arg(long, value_enum, default_value = "text")]
+        format: SummaryFormat,
+    },
+    /// Show which file wins for every command wrappy binds, given the current PATH
+    Precedence {
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: PrecedenceFormat,
+    },
+    /// Compare the bin directory with wrappy's records and the wrappers enable would generate
+    Reconcile {
+        /// Regenerate modified wrappers and reinstall missing ones
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Check that every recorded binding still works; exits non-zero if one is broken
+    Verify {
+        /// Only check the bindings of this container
+        container: Option<String>,
+        /// Regenerate wrappers, relink symlinks and copy again where the container still has the source
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Switch the session autostart entries of a container on or off without removing them
+    Autostart {
+        #[command(subcommand)]
+        action: AutostartCommands,
     },
     /// Show bindings configuration for a container
     Show {
         /// Container name or path to show bindings for
         container: String,
+        /// Show whether each binding is currently active on the host
+        #[arg(long)]
+        status: bool,
+        /// Show the absolute source and target paths enable would use, and what is there now
+        #[arg(long)]
+        resolve: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AutostartCommands {
+    /// Start the container's autostart entries with the session again
+    Enable {
+        /// Container name or path
+        container: String,
+    },
+    /// Keep the container's autostart entries but mark them Hidden, so the session skips them
+    Disable {
+        /// Container name or path
+        container: String,
     },
 }
 
+/// Checks of `bindings enable` the user chose to override.
+#[derive(Debug, Clone, Copy)]
+struct EnableOverrides {
+    override_policy: bool,
+    skip_preflight: bool,
+    force: bool,
+}
+
+impl BindingsCommands {
+    /// Whether the command prints JSON for another program to read.
+    pub fn is_machine_readable(&self) -> bool {
+        match self {
+            BindingsCommands::List { json, .. } => *json,
+            BindingsCommands::Enable { format, .. } | BindingsCommands::Disable { format, .. } => {
+                *format == SummaryFormat::Json
+            }
+            BindingsCommands::Precedence { format } => *format == PrecedenceFormat::Json,
+            _ => false,
+        }
+    }
+}
+
 pub struct BindingsHandler;
 
 impl BindingsHandler {
     /// Routes and executes the appropriate bindings command
     pub fn execute_command(command: BindingsCommands) -> i32 {
         match command {
-            BindingsCommands::List => Self::handle_list_command(),
-            BindingsCommands::Enable { 
-                container, 
-                executables_only, 
-                configs_only, 
-                data_only 
-            } => Self::handle_enable_command(
-                container, 
-                executables_only, 
-                configs_only, 
-                data_only
-            ),
-            BindingsCommands::Disable { container } => {
-                Self::handle_disable_command(container)
+            BindingsCommands::List { profile, container, json } => Self::handle_list_command(profile, container, json),
+            BindingsCommands::Enable {
+                container,
+                executables_only,
+                configs_only,
+                data_only,
+                label,
+                bin_dir,
+                set,
+                override_policy,
+                skip_preflight,
+                force,
+                format,
+            } => {
+                let only = if executables_only {
+                    Some(BindingCategory::Executables)
+                } else if configs_only {
+                    Some(BindingCategory::Configs)
+                } else if data_only {
+                    Some(BindingCategory::Data)
+                } else {
+                    None
+                };
+                let filter = BindingFilter { only, target: None, label };
+                let overrides = EnableOverrides {
+                    override_policy,
+                    skip_preflight,
+                    force,
+                };
+                Self::handle_enable_command(container, filter, bin_dir, set, overrides, format)
             }
-            BindingsCommands::Show { container } => {
-                Self::handle_show_command(container)
+            BindingsCommands::Disable { container, target, only, label, format } => {
+                Self::handle_disable_command(container, BindingFilter { only, target, label }, format)
+            }
+            BindingsCommands::Precedence { format } => Self::handle_precedence_command(format),
+            BindingsCommands::Reconcile { fix } => Self::handle_reconcile_command(fix),
+            BindingsCommands::Verify { container, fix } => Self::handle_verify_command(container, fix),
+            BindingsCommands::Autostart { action } => match action {
+                AutostartCommands::Enable { container } => Self::handle_autostart_command(container, false),
+                AutostartCommands::Disable { container } => Self::handle_autostart_command(container, true),
+            },
+            BindingsCommands::Show { container, status, resolve } => {
+                Self::handle_show_command(container, status, resolve)
             }
         }
     }
 
     /// Handles the list command execution
-    fn handle_list_command() -> i32 {
-        match Self::list_active_bindings() {
+    fn handle_list_command(profile: Option<String>, container: Option<String>, json: bool) -> i32 {
+        match Self::list_bindings(profile.as_deref(), container.as_deref(), json) {
             Ok(()) => 0,
             Err(error) => {
                 eprintln!("❌ Failed to list bindings: {}", error);
@@ -76,33 +225,234 @@ impl BindingsHandler {
     /// Handles the enable command execution
     fn handle_enable_command(
         container_input: String,
-        executables_only: bool,
-        configs_only: bool,
-        data_only: bool,
+        filter: BindingFilter,
+        bin_dir: Option<PathBuf>,
+        set: Vec<String>,
+        overrides: EnableOverrides,
+        format: SummaryFormat,
     ) -> i32 {
-        match Self::enable_bindings(container_input, executables_only, configs_only, data_only) {
-            Ok(()) => 0,
+        let reporter = Reporter::new(format!("enable bindings of '{}'", container_input), format);
+        let result =
+            Self::enable_bindings(&container_input, filter, bin_dir, &set, overrides, &reporter);
+        let cancelled = match &result {
+            Ok(()) => None,
+            Err(error @ ContainerError::Cancelled { .. }) => {
+                reporter.failed(
+                    &container_input,
+                    error,
+                    "⏹️  Enabling bindings was cancelled, nothing was left half-installed",
+                );
+                Some(error.exit_code())
+            }
             Err(error) => {
-                eprintln!("❌ Failed to enable bindings: {}", error);
-                1
+                reporter.failed(&container_input, error, format!("❌ Failed to enable bindings: {}", error));
+                None
             }
-        }
+        };
+        let summary = reporter.finish();
+        cancelled.unwrap_or(summary.exit_code())
     }
 
     /// Handles the disable command execution
-    fn handle_disable_command(container_input: String) -> i32 {
-        match Self::disable_bindings(container_input) {
-            Ok(()) => 0,
+    fn handle_disable_command(container_input: String, filter: BindingFilter, format: SummaryFormat) -> i32 {
+        let reporter = Reporter::new(format!("disable bindings of '{}'", container_input), format);
+        if let Err(error) = Self::disable_bindings(&container_input, filter, &reporter) {
+            reporter.failed(&container_input, &error, format!("❌ Failed to disable bindings: {}", error));
+        }
+        reporter.finish().exit_code()
+    }
+
+    /// Handles the autostart enable and disable commands
+    fn handle_autostart_command(container_input: String, hidden: bool) -> i32 {
+        let verb = if hidden { "disable" } else { "enable" };
+        let reporter = Reporter::new(format!("{} autostart of '{}'", verb, container_input), SummaryFormat::Text);
+        let result = Self::resolve_container(container_input.clone()).and_then(|container| {
+            BindingManager::open()?
+                .with_reporter(reporter.clone())
+                .set_autostart_hidden(&container, hidden)
+        });
+        if let Err(error) = result {
+            reporter.failed(&container_input, &error, format!("❌ Failed to {} autostart: {}", verb, error));
+        }
+        reporter.finish().exit_code()
+    }
+
+    /// Handles the precedence command execution
+    fn handle_precedence_command(format: PrecedenceFormat) -> i32 {
+        let report = match PrecedenceService::current() {
+            Ok(report) => report,
             Err(error) => {
-                eprintln!("❌ Failed to disable bindings: {}", error);
-                1
+                eprintln!("❌ Failed to resolve command precedence: {}", error);
+                return 1;
+            }
+        };
+
+        match format {
+            PrecedenceFormat::Json => match serde_json::to_string_pretty(&report) {
+                Ok(json) => println!("{}", json),
+                Err(error) => {
+                    eprintln!("❌ Failed to serialize command precedence: {}", error);
+                    return 1;
+                }
+            },
+            PrecedenceFormat::Text => Self::print_precedence(&report),
+        }
+        0
+    }
+
+    /// Handles the reconcile command execution; fails while anything is not consistent
+    /// Prints OK or BROKEN for every recorded binding, repairing the broken ones with `fix`
+    fn handle_verify_command(container: Option<String>, fix: bool) -> i32 {
+        let result = BindingManager::open()
+            .and_then(|manager| Ok((VerifyService::check(&manager, container.as_deref())?, manager)));
+        let (checks, manager) = match result {
+            Ok(result) => result,
+            Err(error) => {
+                eprintln!("❌ Failed to verify bindings: {}", error);
+                return 1;
+            }
+        };
+
+        println!("🔍 Verifying bindings");
+        if checks.is_empty() {
+            println!("  No recorded bindings found.");
+            return 0;
+        }
+
+        let mut remaining = 0;
+        for check in &checks {
+            let kind = check.kind.map_or("-", BindingCategory::label);
+            let line = format!("{} ({}, {} {})", check.target.display(), check.container, kind, check.binding_type);
+            let Some(problem) = &check.problem else {
+                println!("  ✅ OK      {}", line);
+                continue;
+            };
+            if fix && check.can_repair() {
+                match VerifyService::repair(&manager, check) {
+                    Ok(()) => {
+                        println!("  🔧 FIXED   {}: {}", line, problem);
+                        continue;
+                    }
+                    Err(error) => eprintln!("     ❌ Repair failed: {}", error),
+                }
+            }
+            println!("  ❌ BROKEN  {}: {}", line, problem);
+            remaining += 1;
+        }
+
+        println!();
+        let repairable = checks.iter().filter(|check| check.can_repair()).count();
+        if remaining == 0 {
+            println!("✅ All {} binding(s) work", checks.len());
+            return 0;
+        }
+        if !fix && repairable > 0 {
+            println!("ℹ️  Run 'wrappy bindings verify --fix' to repair {} binding(s)", repairable);
+        }
+        println!("❌ {} of {} binding(s) are broken", remaining, checks.len());
+        1
+    }
+
+    fn handle_reconcile_command(fix: bool) -> i32 {
+        let result = BindingManager::open().and_then(|manager| Ok((ReconcileService::scan(&manager)?, manager)));
+        let (reports, manager) = match result {
+            Ok(result) => result,
+            Err(error) => {
+                eprintln!("❌ Failed to reconcile bindings: {}", error);
+                return 1;
+            }
+        };
+
+        let bin_dirs = manager.bin_dirs().unwrap_or_else(|_| vec![manager.bin_dir().to_path_buf()]);
+        let bin_dirs: Vec<String> = bin_dirs.iter().map(|bin_dir| bin_dir.display().to_string()).collect();
+        println!("🔍 Wrappers in {}", bin_dirs.join(", "));
+        if reports.is_empty() {
+            println!("  No wrappy bindings found.");
+            return 0;
+        }
+
+        let mut remaining = 0;
+        for report in &reports {
+            let marker = match report.state {
+                WrapperState::Consistent => "✅",
+                WrapperState::Modified => "✏️ ",
+                WrapperState::Missing => "❓",
+                WrapperState::Foreign => "⛔",
+                WrapperState::Untracked => "⚪",
+            };
+            let container = report.container.as_deref().map(|name| format!(" ({})", name)).unwrap_or_default();
+            let detail = report.detail.as_deref().map(|detail| format!(": {}", detail)).unwrap_or_default();
+            println!("  {} {}{} {}{}", marker, report.name, container, report.state, detail);
+
+            if report.state == WrapperState::Consistent {
+                continue;
+            }
+            if fix && report.can_repair() {
+                match ReconcileService::repair(&manager, report) {
+                    Ok(()) => continue,
+                    Err(error) => eprintln!("     ❌ Repair failed: {}", error),
+                }
+            }
+            remaining += 1;
+        }
+
+        let repairable = reports.iter().filter(|report| report.can_repair()).count();
+        println!();
+        if !fix && repairable > 0 {
+            println!("ℹ️  Run 'wrappy bindings reconcile --fix' to regenerate or reinstall {} binding(s)", repairable);
+        }
+        if reports.iter().any(|report| matches!(report.state, WrapperState::Foreign | WrapperState::Untracked)) {
+            println!("ℹ️  Foreign and untracked files are never changed; remove or rename them yourself");
+        }
+        if remaining == 0 {
+            println!("✅ Bin directory matches wrappy's records");
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Prints one block per command: the winning file, then every provider
+    fn print_precedence(report: &[CommandPrecedence]) {
+        println!("🧭 Command precedence (current PATH order)");
+        println!();
+
+        if report.is_empty() {
+            println!("  No installed container binds executables.");
+            return;
+        }
+
+        for entry in report {
+            println!("  {}", entry.command);
+            match (&entry.winner, &entry.winning_container) {
+                (Some(winner), Some(container)) => {
+                    println!("    ✅ Runs {} (from '{}')", winner.display(), container)
+                }
+                (Some(winner), None) if entry.shadowed => {
+                    println!("    ⚠️  Shadowed: {} comes earlier in PATH than the wrappy binding", winner.display())
+                }
+                (Some(winner), None) => println!("    ℹ️  Runs {} (not managed by wrappy)", winner.display()),
+                (None, _) => println!("    ℹ️  Not found on PATH"),
+            }
+            for provider in &entry.providers {
+                println!(
+                    "    {} {} {} ({}) -> {}",
+                    if provider.active { "●" } else { "○" },
+                    provider.container,
+                    provider.version,
+                    provider.binding_type,
+                    provider.target_path.display()
+                );
+            }
+            for target in &entry.unreachable {
+                println!("    ⚠️  {} is not in a PATH directory", target.display());
             }
         }
     }
 
     /// Handles the show command execution
-    fn handle_show_command(container_input: String) -> i32 {
-        match Self::show_bindings(container_input) {
+    fn handle_show_command(container_input: String, status: bool, resolve: bool) -> i32 {
+        match Self::show_bindings(container_input, status, resolve) {
             Ok(()) => 0,
             Err(error) => {
                 eprintln!("❌ Failed to show bindings: {}", error);
@@ -111,81 +461,203 @@ impl BindingsHandler {
         }
     }
 
-    /// Lists all active bindings in the system
-    fn list_active_bindings() -> Result<(), ContainerError> {
-        let binding_manager = BindingManager::new()?;
-        let wrappers = binding_manager.list_active_wrappers()?;
+    /// Lists every binding grouped by container, flagging the ones whose target is
+    /// gone or broken. `profile` and `container` narrow the containers listed.
+    fn list_bindings(profile: Option<&str>, container: Option<&str>, json: bool) -> Result<(), ContainerError> {
+        let members = match profile {
+            Some(profile) => Some(
+                ProfileService::new()?
+                    .members(profile)?
+                    .iter()
+                    .map(|member| member.split_once('@').map_or(member.as_str(), |(name, _)| name).to_string())
+                    .collect::<Vec<_>>(),
+            ),
+            None => None,
+        };
+        let mut listings = BindingManager::open()?.list_bindings(container)?;
+        if let Some(members) = &members {
+            listings.retain(|listing| members.contains(&listing.container));
+        }
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&listings)?);
+            return Ok(());
+        }
 
-        println!("🔗 Active Wrappy Bindings");
+        match profile {
+            Some(profile) => println!("🔗 Active Bindings of Profile '{}'", profile),
+            None => println!("🔗 Active Wrappy Bindings"),
+        }
         println!();
 
-        if wrappers.is_empty() {
+        if listings.is_empty() {
             println!("  No active bindings found.");
-            println!("  Use 'wrappy bindings enable <container>' to create bindings.");
-        } else {
-            println!("  Wrapper Scripts in ~/.local/bin/:");
-            for wrapper in wrappers {
-                println!("    📋 {}", wrapper);
+            match (profile, container) {
+                (Some(profile), _) => println!("  Use 'wrappy profile enable {}' to create them.", profile),
+                (None, Some(container)) => println!("  Use 'wrappy bindings enable {}' to create them.", container),
+                (None, None) => println!("  Use 'wrappy bindings enable <container>' to create bindings."),
             }
+            return Ok(());
+        }
+
+        let mut by_container: BTreeMap<&str, Vec<[String; 5]>> = BTreeMap::new();
+        for listing in &listings {
+            let since = match (listing.created_at, listing.recorded) {
+                (Some(created_at), _) => created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string(),
+                (None, false) => "untracked".to_string(),
+                (None, true) => "-".to_string(),
+            };
+            let status = match listing.status {
+                ListingStatus::Ok => "✅ ok".to_string(),
+                status => format!("❌ {}", status),
+            };
+            by_container.entry(listing.container.as_str()).or_default().push([
+                listing.kind.map_or("-", BindingCategory::label).to_string(),
+                listing.binding_type.to_string(),
+                since,
+                status,
+                listing.target.display().to_string(),
+            ]);
         }
 
+        let headers = ["KIND", "TYPE", "SINCE", "STATUS", "TARGET"];
+        let widths: Vec<usize> = (0..4)
+            .map(|column| {
+                by_container
+                    .values()
+                    .flatten()
+                    .map(|row| row[column].chars().count())
+                    .chain([headers[column].len()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let line = |cells: [&str; 5]| {
+            let mut line = String::from("   ");
+            for (column, width) in widths.iter().enumerate() {
+                line.push_str(&format!(" {:<width$} ", cells[column], width = width));
+            }
+            line.push(' ');
+            line.push_str(cells[4]);
+            line
+        };
+        for (container, rows) in &by_container {
+            println!("  📦 {}", container);
+            println!("{}", line(headers));
+            for row in rows {
+                println!("{}", line([&row[0], &row[1], &row[2], &row[3], &row[4]]));
+            }
+            println!();
+        }
+
+        let unusable = listings.iter().filter(|listing| listing.status != ListingStatus::Ok).count();
+        if unusable > 0 {
+            println!("⚠️  {} binding(s) are missing or broken; 'wrappy bindings enable <container>' recreates them", unusable);
+        }
         Ok(())
     }
 
     /// Enables bindings for a container
     fn enable_bindings(
-        container_input: String,
-        executables_only: bool,
-        configs_only: bool,
-        data_only: bool,
+        container_input: &str,
+        filter: BindingFilter,
+        bin_dir: Option<PathBuf>,
+        set: &[String],
+        overrides: EnableOverrides,
+        reporter: &Reporter,
     ) -> Result<(), ContainerError> {
-        let container = Self::resolve_container(container_input)?;
-        let binding_manager = BindingManager::new()?;
+        let mut container = Self::resolve_container(container_input.to_string())?;
+        if !container.manifest.variables.is_empty() {
+            container = Self::resolve_variables(container, set)?;
+        } else if !set.is_empty() {
+            return Err(ContainerError::ManifestValidation(format!(
+                "Container '{}' declares no variables",
+                container.name()
+            )));
+        }
+        if let Some(bin_dir) = bin_dir {
+            let bin_dir = std::path::absolute(&bin_dir).map_err(|e| ContainerError::IoError { path: bin_dir, source: e })?;
+            path_sanity::check_path_entry(&bin_dir)?;
+            container.manifest.bindings.bin_dir = Some(bin_dir.to_string_lossy().into_owned());
+        }
+        // Ctrl-C stops between bindings so the ones already created can be rolled back
+        install_interrupt_handler();
+        let binding_manager = BindingManager::new()?
+            .override_policy(overrides.override_policy)
+            .skip_preflight(overrides.skip_preflight)
+            .take_over(overrides.force)
+            .with_cancellation(CancellationToken::linked_to(interrupted))
+            .with_reporter(reporter.clone());
 
         // Check if container has any bindings configured
         if container.manifest.bindings.is_empty() {
-            println!("ℹ️  Container '{}' has no bindings configured.", container.name());
-            println!("   Add bindings to the manifest.json file to enable integration.");
+            reporter.info(format!("ℹ️  Container '{}' has no bindings configured.", container.name()));
+            reporter.info("   Add bindings to the manifest.json file to enable integration.");
             return Ok(());
         }
 
-        // Filter bindings based on flags
         let mut filtered_container = container.clone();
-        if executables_only {
-            filtered_container.manifest.bindings.configs.clear();
-            filtered_container.manifest.bindings.data.clear();
-        } else if configs_only {
-            filtered_container.manifest.bindings.executables.clear();
-            filtered_container.manifest.bindings.data.clear();
-        } else if data_only {
-            filtered_container.manifest.bindings.executables.clear();
-            filtered_container.manifest.bindings.configs.clear();
-        }
-
-        println!("🔗 Enabling bindings for container '{}'...", container.name());
+        if !filter.is_empty() {
+            filtered_container.manifest.bindings =
+                binding_manager.filter_bindings(&container, &container.manifest.bindings, &filter)?;
+
+            if filtered_container.manifest.bindings.is_empty() {
+                reporter.info(format!("ℹ️  No bindings of container '{}' match the given filter.", container.name()));
+                return Ok(());
+            }
+        }
+
+        reporter.info(format!("🔗 Enabling bindings for container '{}'...", container.name()));
+        if filter.is_empty() {
+            binding_manager.remove_dropped_bindings(&container)?;
+        }
         let active_bindings = binding_manager.install_bindings(&filtered_container)?;
 
         if active_bindings.is_empty() {
-            println!("ℹ️  No bindings were created (they may already exist).");
+            reporter.info("ℹ️  No bindings were created (they may already exist).");
         }
 
         Ok(())
     }
 
+    /// Chooses and stores values for the container's manifest variables, then
+    /// reloads it so binding targets and the environment use them.
+    fn resolve_variables(container: Container, set: &[String]) -> Result<Container, ContainerError> {
+        let paths = WrappyPaths::new()?;
+        let store = VariableStore::for_container(&paths, container.name());
+        let overrides = parse_variable_overrides(set)?;
+        let values = store.resolve(&container.manifest, &overrides)?;
+        store.save(&values)?;
+        ContainerService::load_from_directory(&container.path)
+    }
+
     /// Disables bindings for a container
-    fn disable_bindings(container_input: String) -> Result<(), ContainerError> {
-        let container = Self::resolve_container(container_input)?;
-        let binding_manager = BindingManager::new()?;
+    fn disable_bindings(container_input: &str, filter: BindingFilter, reporter: &Reporter) -> Result<(), ContainerError> {
+        let container = Self::resolve_container(container_input.to_string())?;
+        let binding_manager = BindingManager::new()?.with_reporter(reporter.clone());
 
-        println!("🗑️  Disabling bindings for container '{}'...", container.name());
-        binding_manager.remove_bindings(&container)?;
+        if filter.is_empty() {
+            reporter.info(format!("🗑️  Disabling bindings for container '{}'...", container.name()));
+            return binding_manager.remove_bindings(&container);
+        }
+
+        let selected = binding_manager.filter_bindings(&container, &container.manifest.bindings, &filter)?;
+        if selected.is_empty() {
+            reporter.info(format!("ℹ️  No bindings of container '{}' match the given filter.", container.name()));
+            return Ok(());
+        }
+
+        reporter.info(format!("🗑️  Disabling bindings for container '{}'...", container.name()));
+        binding_manager.remove_selected_bindings(&container, &selected)?;
 
         Ok(())
     }
 
     /// Shows bindings configuration for a container
-    fn show_bindings(container_input: String) -> Result<(), ContainerError> {
+    fn show_bindings(container_input: String, show_status: bool, resolve: bool) -> Result<(), ContainerError> {
         let container = Self::resolve_container(container_input)?;
+        let manager = BindingManager::open()?;
+        let binding_manager = if show_status { Some(&manager) } else { None };
 
         println!("🔗 Bindings configuration for container '{}'", container.name());
         println!();
@@ -213,15 +685,35 @@ impl BindingsHandler {
         if !bindings.executables.is_empty() {
             println!("  📋 Executable Bindings:");
             for executable in &bindings.executables {
-                let display_name = executable.display_name
-                    .as_ref()
-                    .unwrap_or(&executable.source);
                 println!("    {} -> {} ({})", 
                          executable.source, executable.target, 
-                         format!("{:?}", executable.binding_type).to_lowercase());
+                         format!("{:?}", executable.binding_type()).to_lowercase());
                 if let Some(display) = &executable.display_name {
                     println!("      Display name: {}", display);
                 }
+                Self::print_policy_block(manager.check_executable_policy(&container, executable));
+                if executable.argv0 == Argv0Mode::Preserve {
+                    println!("      argv[0]: preserved from wrapper name");
+                }
+                if !executable.args_prepend.is_empty() {
+                    let args: Vec<String> = executable.args_prepend.iter().map(|arg| posix_quote(arg)).collect();
+                    println!("      Arguments: {}", args.join(" "));
+                }
+                let mut env: Vec<_> = executable.env.redacted().into_iter().collect();
+                env.sort();
+                for (key, value) in env {
+                    println!("      Environment: {}={}", key, value);
+                }
+                if let Some(manager) = &binding_manager {
+                    Self::print_binding_status(
+                        manager.executable_target_path(&container, executable),
+                        &executable.binding_type(),
+                        &container.path.join(&executable.source),
+                    );
+                }
+                if resolve {
+                    Self::print_resolved(&manager.resolve_executable(&container, executable));
+                }
             }
             println!();
         }
@@ -230,12 +722,28 @@ impl BindingsHandler {
         if !bindings.configs.is_empty() {
             println!("  ⚙️  Config Bindings:");
             for config in &bindings.configs {
-                println!("    {} -> {} ({})", 
-                         config.source, config.target,
-                         format!("{:?}", config.binding_type).to_lowercase());
+                println!("    [{}] {} -> {} ({})",
+                         config.label(), config.source, config.target,
+                         format!("{:?}", config.binding_type()).to_lowercase());
                 if config.backup_existing {
                     println!("      Backup existing: yes");
                 }
+                Self::print_policy_block(manager.check_directory_policy(&config.binding_type(), &config.target));
+                if let Some(manager) = &binding_manager {
+                    Self::print_binding_status(
+                        manager.directory_target_path(&config.target),
+                        &config.binding_type(),
+                        &container.path.join(&config.source),
+                    );
+                }
+                if resolve {
+                    Self::print_resolved(&manager.resolve_directory(
+                        &container,
+                        &config.source,
+                        &config.target,
+                        &config.binding_type(),
+                    ));
+                }
             }
             println!();
         }
@@ -244,31 +752,154 @@ impl BindingsHandler {
         if !bindings.data.is_empty() {
             println!("  💾 Data Bindings:");
             for data in &bindings.data {
-                println!("    {} -> {} ({})", 
-                         data.source, data.target,
-                         format!("{:?}", data.binding_type).to_lowercase());
+                println!("    [{}] {} -> {} ({})",
+                         data.label(), data.source, data.target,
+                         format!("{:?}", data.binding_type()).to_lowercase());
                 if data.backup_existing {
                     println!("      Backup existing: yes");
                 }
+                Self::print_policy_block(manager.check_directory_policy(&data.binding_type(), &data.target));
+                if let Some(manager) = &binding_manager {
+                    Self::print_binding_status(
+                        manager.directory_target_path(&data.target),
+                        &data.binding_type(),
+                        &container.path.join(&data.source),
+                    );
+                }
+                if resolve {
+                    Self::print_resolved(&manager.resolve_directory(
+                        &container,
+                        &data.source,
+                        &data.target,
+                        &data.binding_type(),
+                    ));
+                }
             }
             println!();
         }
 
+        if !bindings.autostart.is_empty() {
+            println!("  🚀 Autostart Entries:");
+            for autostart in &bindings.autostart {
+                let name = autostart.name.as_deref().map(|name| format!(" {:?}", name)).unwrap_or_default();
+                let delay = autostart.delay_secs.map(|delay| format!(", after {}s", delay)).unwrap_or_default();
+                println!("    {}{} (with the session{})", autostart.executable, name, delay);
+                if binding_manager.is_some() {
+                    println!("      Status: {}", manager.autostart_state(&container, autostart));
+                }
+            }
+            println!();
+        }
+
+        let owned = manager.foreign_owners(&container, bindings)?;
+        if !owned.is_empty() {
+            println!("  ⚠️  Target Conflicts:");
+            for record in &owned {
+                println!("    {} is owned by '{}'", manager.installed_path(record).display(), record.container);
+            }
+            println!("    Enabling fails until they are disabled; 'wrappy bindings enable {} --force' takes them over", container.name());
+            println!();
+        }
+
         Ok(())
     }
 
+    /// Prints the absolute paths of a binding; expansion errors stay on their own entry.
+    fn print_resolved(resolved: &ResolvedBinding) {
+        let source_state = if resolved.source_exists { "✅ exists" } else { "❌ missing" };
+        println!("      Source: {} ({})", resolved.source_path.display(), source_state);
+        match &resolved.target {
+            Ok((target_path, state)) => {
+                let marker = if *state == TargetState::Occupied { "⚠️ " } else { "✅" };
+                println!("      Target: {} ({} {})", target_path.display(), marker, state);
+            }
+            Err(error) => println!("      Target: ❌ {}", error),
+        }
+    }
+
+    /// Marks a binding the local config policy would refuse to create.
+    fn print_policy_block(check: Result<Result<(), PolicyViolation>, ContainerError>) {
+        if let Ok(Err(violation)) = check {
+            println!("      ⛔ Blocked by policy 'bindings.{}': {}", violation.key, violation.details);
+        }
+    }
+
+    /// Prints whether a binding is currently installed on the host.
+    fn print_binding_status(
+        target_path: Result<std::path::PathBuf, ContainerError>,
+        binding_type: &BindingType,
+        source_path: &std::path::Path,
+    ) {
+        let target_path = match target_path {
+            Ok(path) => path,
+            Err(error) => {
+                println!("      Status: unknown ({})", error);
+                return;
+            }
+        };
+
+        let active = BindingManager::is_binding_active(&target_path, binding_type, source_path);
+        println!("      Status: {}", if active { "active" } else { "inactive" });
+    }
+
     /// Resolves container input to Container instance
     fn resolve_container(container_input: String) -> Result<Container, ContainerError> {
-        // Try as path first
-        let path = PathBuf::from(&container_input);
-        if path.exists() && path.is_dir() {
-            return ContainerService::load_from_directory(&path);
+        ContainerResolver::new()?.resolve_container(&container_input)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::setup::SetupService;
+    use crate::shared::test_support::{fixture_container, fixture_container_at, fixture_manifest, TempHome};
+
+    /// Installs `name` at 1.0.0 into the default store.
+    fn installed(home: &TempHome, name: &str) -> PathBuf {
+        let dir = home.paths().default_store_dir().join(name).join("1.0.0");
+        fixture_container_at(&dir, fixture_manifest(name, "1.0.0"));
+        dir
+    }
+
+    #[test]
+    fn an_installed_name_resolves_to_its_store_directory() {
+        let home = TempHome::enter();
+        SetupService::initialize(&home.paths()).unwrap();
+        let dir = installed(&home, "webserver");
+
+        let container = BindingsHandler::resolve_container("webserver".to_string()).unwrap();
+
+        assert_eq!(container.path.canonicalize().unwrap(), dir.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn an_unknown_name_fails_with_close_installed_names() {
+        let home = TempHome::enter();
+        SetupService::initialize(&home.paths()).unwrap();
+        installed(&home, "webserver");
+        installed(&home, "database");
+
+        let typo = BindingsHandler::resolve_container("websrever".to_string());
+        let prefix = BindingsHandler::resolve_container("web".to_string());
+
+        match (typo, prefix) {
+            (Err(ContainerError::ContainerNotFound { suggestions: typo, .. }), Err(ContainerError::ContainerNotFound { suggestions: prefix, .. })) => {
+                assert_eq!(typo, ["webserver"]);
+                assert_eq!(prefix, ["webserver"]);
+            }
+            other => panic!("expected two not-found errors, got {:?}", other),
         }
+    }
+
+    #[test]
+    fn a_path_that_is_not_installed_still_loads() {
+        let home = TempHome::enter();
+        SetupService::initialize(&home.paths()).unwrap();
+        installed(&home, "webserver");
+        let source = fixture_container(home.path(), "webserver", "2.0.0");
 
-        // For now, just try as path - in the future we could search by name
-        Err(ContainerError::InvalidPath {
-            path,
-            reason: format!("Container '{}' not found. Please provide a valid container directory path.", container_input),
-        })
+        let container = BindingsHandler::resolve_container(source.to_string_lossy().into_owned()).unwrap();
+
+        assert_eq!(container.path, source);
+        assert_eq!(container.version().to_string(), "2.0.0");
     }
-}
\ No newline at end of file
+}
@@ -1,120 +1,1040 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::os::unix::fs as unix_fs;
 use std::path::{Path, PathBuf};
 
 use crate::features::bindings::{
-    ActiveBinding, BindingType, BindingsConfig, ConfigBinding, DataBinding, 
-    ExecutableBinding, WrapperGenerator,
+    ActiveBinding, AutostartBinding, AutostartGenerator, AutostartState, BindingCategory, BindingFilter, BindingListing, BindingPolicy, BindingRecord, BindingStateFile, BindingType, BindingsConfig,
+    ConfigBinding, DataBinding, ExecutableBinding, HostFs, ListingStatus, PlannedBinding, PolicyViolation, Preflight, ResolvedBinding, TargetConflict, TargetState,
+    WrapperGenerator,
 };
-use crate::features::Container;
+use crate::features::{Container, ContainerService};
 use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::config::Config;
+use crate::shared::cancellation::CancellationToken;
+use crate::shared::clock;
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::privilege::PrivilegeGuard;
+use crate::shared::summary::Reporter;
+use crate::shared::template;
+use crate::shared::traversal::Walk;
 
 /// Manages container bindings to host system including executables, configs, and data.
 pub struct BindingManager {
     user_bin_dir: PathBuf,
-    user_config_dir: PathBuf,
-    user_data_dir: PathBuf,
-    wrapper_generator: WrapperGenerator,
+    state: BindingStateFile,
+    autostart: AutostartGenerator,
+    policy: BindingPolicy,
+    override_policy: bool,
+    skip_preflight: bool,
+    take_over: bool,
+    cancellation: CancellationToken,
+    reporter: Reporter,
 }
 
 impl BindingManager {
     /// Creates binding manager with standard user directories.
     pub fn new() -> ContainerResult<Self> {
-        let home = dirs::home_dir().ok_or_else(|| {
-            ContainerError::InvalidPath {
-                path: PathBuf::from("~"),
-                reason: "Could not determine home directory".to_string(),
-            }
-        })?;
-
-        let user_bin_dir = home.join(".local/bin");
-        let user_config_dir = home.join(".config");
-        let user_data_dir = home.join(".local/share");
+        let manager = Self::open()?;
+        let paths = WrappyPaths::new()?;
+        let user_config_dir = paths.home.join(".config");
+        let user_data_dir = paths.home.join(".local/share");
 
         // Ensure directories exist
-        for dir in &[&user_bin_dir, &user_config_dir, &user_data_dir] {
+        for dir in &[&manager.user_bin_dir, &user_config_dir, &user_data_dir] {
             fs::create_dir_all(dir).map_err(|e| ContainerError::IoError {
                 path: dir.to_path_buf(),
                 source: e,
             })?;
         }
 
-        let wrapper_generator = WrapperGenerator::new(user_bin_dir.clone());
+        Ok(manager)
+    }
+
+    /// Creates binding manager for read-only inspection, without creating any directories.
+    pub fn open() -> ContainerResult<Self> {
+        let paths = WrappyPaths::new()?;
+        let config = Config::load_or_default(&paths)?;
+
+        let user_bin_dir = config.bin_dir(&paths);
+        let policy = BindingPolicy::from_config(&config.bindings, &paths)?;
 
         Ok(Self {
             user_bin_dir,
-            user_config_dir,
-            user_data_dir,
-            wrapper_generator,
+            state: BindingStateFile::new(&paths),
+            autostart: AutostartGenerator::new(paths.home.join(".config/autostart")),
+            policy,
+            override_policy: false,
+            skip_preflight: false,
+            take_over: false,
+            cancellation: CancellationToken::new(),
+            reporter: Reporter::default(),
         })
     }
 
+    /// Lets bindings the config policy would block be created anyway, for one-off exceptions.
+    pub fn override_policy(mut self, override_policy: bool) -> Self {
+        self.override_policy = override_policy;
+        self
+    }
+
+    /// Installs without the pre-flight checks, for setups they misjudge.
+    pub fn skip_preflight(mut self, skip_preflight: bool) -> Self {
+        self.skip_preflight = skip_preflight;
+        self
+    }
+
+    /// Lets enable take over targets whose bindings another container owns: the owner's
+    /// binding is removed and the record passes to the container being enabled.
+    pub fn take_over(mut self, take_over: bool) -> Self {
+        self.take_over = take_over;
+        self
+    }
+
+    /// Stops installation between bindings (and copies between files) once `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Reports created and removed bindings through `reporter` instead of printing them directly.
+    pub fn with_reporter(mut self, reporter: Reporter) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// Checks an executable binding against the config policy at the path it would occupy.
+    pub fn check_executable_policy(
+        &self,
+        container: &Container,
+        executable: &ExecutableBinding,
+    ) -> ContainerResult<Result<(), PolicyViolation>> {
+        let target_path = self.executable_target_path(container, executable)?;
+        Ok(self.policy.evaluate(&executable.binding_type(), &target_path))
+    }
+
+    /// Checks a config or data binding against the config policy.
+    pub fn check_directory_policy(&self, binding_type: &BindingType, target: &str) -> ContainerResult<Result<(), PolicyViolation>> {
+        let target_path = self.expand_path(target)?;
+        Ok(self.policy.evaluate(binding_type, &target_path))
+    }
+
+    /// Host paths the given bindings of `container` occupy once installed.
+    pub fn target_paths(&self, container: &Container, bindings: &BindingsConfig) -> ContainerResult<Vec<PathBuf>> {
+        let bin_dir = self.bin_dir_for(container.name(), bindings)?;
+        let mut targets = Vec::new();
+        for executable in &bindings.executables {
+            targets.push(self.executable_path_in(&bin_dir, executable)?);
+        }
+        for target in bindings.configs.iter().map(|config| &config.target).chain(bindings.data.iter().map(|data| &data.target)) {
+            targets.push(self.expand_path(target)?);
+        }
+        Ok(targets)
+    }
+
+    /// Records of other containers at the host paths `bindings` of `container` would occupy.
+    /// Each target has one owner: the container whose binding is recorded there.
+    pub fn foreign_owners(&self, container: &Container, bindings: &BindingsConfig) -> ContainerResult<Vec<BindingRecord>> {
+        let targets = self.target_paths(container, bindings)?;
+        let mut owned: Vec<BindingRecord> = self
+            .recorded_bindings()?
+            .into_iter()
+            .filter(|record| record.container != container.name() && targets.contains(&self.installed_path(record)))
+            .collect();
+        owned.sort_by(|a, b| a.target_path.cmp(&b.target_path));
+        Ok(owned)
+    }
+
+    /// Fails when another container owns a target of `container`, or with `take_over`
+    /// removes the owner's binding and record so enable can install its own.
+    fn claim_targets(&self, container: &Container) -> ContainerResult<()> {
+        let owned = self.foreign_owners(container, &container.manifest.bindings)?;
+        if owned.is_empty() {
+            return Ok(());
+        }
+        if !self.take_over {
+            return Err(ContainerError::BindingConflict {
+                conflicts: owned
+                    .iter()
+                    .map(|record| {
+                        format!(
+                            "{} is owned by '{}'; pass --force to take it over",
+                            self.installed_path(record).display(),
+                            record.container
+                        )
+                    })
+                    .collect(),
+            });
+        }
+        for record in owned {
+            self.remove_recorded_binding(&record)?;
+            self.state.forget(&record.container, std::slice::from_ref(&record.target_path))?;
+            self.reporter.warn(format!(
+                "Took over {} from '{}'",
+                self.installed_path(&record).display(),
+                record.container
+            ));
+        }
+        Ok(())
+    }
+
+    /// Containers binding each host path, by container name. A container binding the same
+    /// path twice counts once; that is a manifest error, not a conflict between containers.
+    pub fn target_owners<'a>(
+        &self,
+        containers: impl IntoIterator<Item = &'a Container>,
+    ) -> ContainerResult<BTreeMap<PathBuf, BTreeSet<String>>> {
+        let mut owners: BTreeMap<PathBuf, BTreeSet<String>> = BTreeMap::new();
+        for container in containers {
+            for target in self.target_paths(container, &container.manifest.bindings)? {
+                owners.entry(target).or_default().insert(container.name().to_string());
+            }
+        }
+        Ok(owners)
+    }
+
+    /// Paths of `owners` claimed by more than one container. Profiles and dependency
+    /// checks both go through this, so they agree on what a conflict is.
+    pub fn conflicts(owners: BTreeMap<PathBuf, BTreeSet<String>>) -> Vec<TargetConflict> {
+        owners
+            .into_iter()
+            .filter(|(_, names)| names.len() > 1)
+            .map(|(target, names)| TargetConflict {
+                target,
+                containers: names.into_iter().collect(),
+            })
+            .collect()
+    }
+
+    /// Directory wrappers are generated into unless a container overrides it.
+    pub fn bin_dir(&self) -> &Path {
+        &self.user_bin_dir
+    }
+
+    /// Bin directory of a container's executables: the `bin_dir` of `bindings` (set from
+    /// the manifest or `--bin-dir`), else the one its bindings were last enabled into,
+    /// else the configured one.
+    pub fn bin_dir_for(&self, container: &str, bindings: &BindingsConfig) -> ContainerResult<PathBuf> {
+        if let Some(bin_dir) = &bindings.bin_dir {
+            return self.expand_path(bin_dir);
+        }
+        let recorded = self.state.load().ok().and_then(|state| {
+            state
+                .bindings
+                .into_iter()
+                .find(|record| record.container == container && record.bin_dir.is_some())
+                .and_then(|record| record.bin_dir)
+        });
+        Ok(recorded.unwrap_or_else(|| self.user_bin_dir.clone()))
+    }
+
+    /// The configured bin directory and every other one recorded bindings were enabled into.
+    pub fn bin_dirs(&self) -> ContainerResult<Vec<PathBuf>> {
+        Ok(Self::record_bin_dirs(&self.recorded_bindings()?, &self.user_bin_dir))
+    }
+
+    /// Whether the autostart entry of `binding` is installed, and whether it is hidden.
+    pub fn autostart_state(&self, container: &Container, binding: &AutostartBinding) -> AutostartState {
+        self.autostart.state(container.name(), binding)
+    }
+
+    /// Turns the installed autostart entries of a container on or off by rewriting their
+    /// `Hidden` key; the files stay in place. Fails when none of them is installed.
+    pub fn set_autostart_hidden(&self, container: &Container, hidden: bool) -> ContainerResult<()> {
+        let declared = &container.manifest.bindings.autostart;
+        if declared.is_empty() {
+            return Err(ContainerError::ManifestValidation(format!(
+                "Container '{}' declares no autostart entries",
+                container.name()
+            )));
+        }
+
+        let mut installed = 0;
+        for binding in declared {
+            let path = self.autostart.entry_path(container.name(), binding);
+            if AutostartGenerator::read_metadata(&path).is_none() {
+                self.reporter.warn(format!(
+                    "Autostart entry of '{}' is not installed; run 'wrappy bindings enable {}' first",
+                    binding.executable,
+                    container.name()
+                ));
+                continue;
+            }
+            installed += 1;
+            let subject = path.display();
+            let state = if hidden { "disabled" } else { "enabled" };
+            if AutostartGenerator::set_hidden(&path, hidden)? {
+                self.reporter.updated(&subject, format!("🚀 Autostart of {} {}", binding.executable_name(), state));
+            } else {
+                let reason = format!("already {}", state);
+                self.reporter.skipped(&subject, &reason, format!("ℹ️  Autostart of {} is {}", binding.executable_name(), reason));
+            }
+        }
+
+        if installed == 0 {
+            return Err(ContainerError::InvalidPath {
+                path: self.autostart.autostart_dir().to_path_buf(),
+                reason: format!("no autostart entry of '{}' is installed", container.name()),
+            });
+        }
+        Ok(())
+    }
+
+    /// Recreates one executable binding exactly as enable would and records it again.
+    /// Used to repair wrappers that were edited or deleted by hand.
+    pub fn reinstall_executable(&self, container: &Container, executable: &ExecutableBinding) -> ContainerResult<ActiveBinding> {
+        let bin_dir = self.bin_dir_for(container.name(), &container.manifest.bindings)?;
+        PrivilegeGuard::ensure_owner(&bin_dir, "repair bindings")?;
+        let binding = self.install_executable_binding(container, executable)?;
+        self.state.record(std::slice::from_ref(&binding))?;
+        Ok(binding)
+    }
+
+    /// Recreates a recorded config or data binding whose target is gone: links the
+    /// source again or copies it again. Whatever else occupies the target is left alone.
+    pub fn reinstall_directory(&self, record: &BindingRecord) -> ContainerResult<()> {
+        let target = &record.target_path;
+        if !record.source_path.is_dir() {
+            return Err(ContainerError::InvalidPath {
+                path: record.source_path.clone(),
+                reason: "source of the binding no longer exists".to_string(),
+            });
+        }
+        if target.symlink_metadata().is_ok() {
+            return Err(ContainerError::InvalidPath {
+                path: target.clone(),
+                reason: "is occupied; remove it to let the binding be recreated".to_string(),
+            });
+        }
+        if let Some(parent) = target.parent() {
+            PrivilegeGuard::ensure_owner(parent, "repair bindings")?;
+            fs::create_dir_all(parent).map_err(|e| ContainerError::IoError {
+                path: parent.to_path_buf(),
+                source: e,
+            })?;
+        }
+        match record.binding_type {
+            BindingType::Symlink => self.create_symlink(&record.source_path, target),
+            BindingType::Copy => self.copy_directory(&record.source_path, target),
+            BindingType::Wrapper => Err(ContainerError::InvalidPath {
+                path: target.clone(),
+                reason: "is recorded as a wrapper, which only executables use".to_string(),
+            }),
+        }
+    }
+
+    /// Bindings recorded in the state file.
+    pub fn recorded_bindings(&self) -> ContainerResult<Vec<BindingRecord>> {
+        Ok(self.state.load()?.bindings)
+    }
+
+    /// Where a recorded binding lives on disk. Wrapper records keep the declared
+    /// target, but the wrapper itself is generated into the bin directory it was enabled into.
+    pub fn installed_path(&self, record: &BindingRecord) -> PathBuf {
+        match (&record.binding_type, record.target_path.file_name()) {
+            (BindingType::Wrapper, Some(name)) => record.bin_dir.as_ref().unwrap_or(&self.user_bin_dir).join(name),
+            _ => record.target_path.clone(),
+        }
+    }
+
+    fn records_of(&self, container: &str) -> ContainerResult<Vec<BindingRecord>> {
+        let mut records = self.recorded_bindings()?;
+        records.retain(|record| record.container == container);
+        Ok(records)
+    }
+
+    /// Fails on the first binding the policy blocks, before anything is created.
+    fn enforce_policy(&self, container: &Container) -> ContainerResult<()> {
+        if self.override_policy {
+            return Ok(());
+        }
+
+        let bindings = &container.manifest.bindings;
+        for executable in &bindings.executables {
+            self.check_executable_policy(container, executable)??;
+        }
+        for config in &bindings.configs {
+            self.check_directory_policy(&config.binding_type(), &config.target)??;
+        }
+        for data in &bindings.data {
+            self.check_directory_policy(&data.binding_type(), &data.target)??;
+        }
+        Ok(())
+    }
+
     /// Installs all bindings for a container based on its manifest configuration.
+    /// When cancelled midway, the bindings created so far are removed again.
     pub fn install_bindings(&self, container: &Container) -> ContainerResult<Vec<ActiveBinding>> {
+        ContainerService::ensure_variables_resolved(&container.manifest)?;
+        let bin_dir = self.bin_dir_for(container.name(), &container.manifest.bindings)?;
+        PrivilegeGuard::ensure_owner(&bin_dir, "enable bindings")?;
+        self.enforce_policy(container)?;
+        self.claim_targets(container)?;
+        if !self.skip_preflight {
+            self.preflight(container)?;
+        }
+        self.ensure_targets_writable(container, &container.manifest.bindings, &[], "enable bindings")?;
+        if !container.manifest.bindings.executables.is_empty() {
+            fs::create_dir_all(&bin_dir).map_err(|e| ContainerError::IoError {
+                path: bin_dir.clone(),
+                source: e,
+            })?;
+        }
+
+        let mut installed = BindingsConfig::new();
+        let active_bindings = match self.install_all(container, &mut installed) {
+            Ok(active_bindings) => active_bindings,
+            Err(error @ ContainerError::Cancelled { .. }) => {
+                self.reporter.info("⏹️  Cancelled, removing the bindings created so far...");
+                self.remove_all(container, &installed)?;
+                return Err(error);
+            }
+            Err(error) => return Err(error),
+        };
+
+        self.remove_moved_bindings(container.name(), &active_bindings)?;
+        self.state.record(&active_bindings)?;
+        self.reporter.info(format!(
+            "✅ Installed {} bindings for container '{}'",
+            active_bindings.len(),
+            container.name()
+        ));
+
+        Ok(active_bindings)
+    }
+
+    /// Removes what an earlier enable put in another bin directory for the same targets,
+    /// e.g. before `--bin-dir` moved them, since their records are about to be replaced.
+    fn remove_moved_bindings(&self, container: &str, active_bindings: &[ActiveBinding]) -> ContainerResult<()> {
+        for record in self.records_of(container)? {
+            let moved = active_bindings
+                .iter()
+                .any(|binding| binding.target_path == record.target_path && binding.bin_dir != record.bin_dir);
+            if moved {
+                self.remove_recorded_binding(&record)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fails with every problem the pre-flight checks predict, before anything is created.
+    fn preflight(&self, container: &Container) -> ContainerResult<()> {
+        let problems = Preflight::new(&HostFs).run(&self.plan(container)?);
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(ContainerError::PreflightFailed {
+            container: container.name().to_string(),
+            problems: problems.iter().map(ToString::to_string).collect(),
+        })
+    }
+
+    /// The bindings `install_all` would create, with the paths they would occupy.
+    fn plan(&self, container: &Container) -> ContainerResult<Vec<PlannedBinding>> {
+        let bindings = &container.manifest.bindings;
+        let mut plan = Vec::new();
+        for executable in &bindings.executables {
+            plan.push(PlannedBinding {
+                description: format!("executable '{}'", executable.target),
+                source: container.path.join(&executable.source),
+                target: self.executable_target_path(container, executable)?,
+                binding_type: executable.binding_type(),
+            });
+        }
+        for config in &bindings.configs {
+            plan.push(PlannedBinding {
+                description: format!("config '{}'", config.label()),
+                source: container.path.join(&config.source),
+                target: self.directory_target_path(&config.target)?,
+                binding_type: config.binding_type(),
+            });
+        }
+        for data in &bindings.data {
+            plan.push(PlannedBinding {
+                description: format!("data '{}'", data.label()),
+                source: container.path.join(&data.source),
+                target: self.directory_target_path(&data.target)?,
+                binding_type: data.binding_type(),
+            });
+        }
+        for autostart in bindings.autostart_of_executables() {
+            let Some(executable) = bindings.executables.iter().find(|executable| executable.target == autostart.executable) else {
+                continue;
+            };
+            plan.push(PlannedBinding {
+                description: format!("autostart '{}'", autostart.executable_name()),
+                source: container.path.join(&executable.source),
+                target: self.autostart.entry_path(container.name(), autostart),
+                binding_type: BindingType::Symlink,
+            });
+        }
+        Ok(plan)
+    }
+
+    /// Installs every binding in manifest order, noting each created one in `installed`.
+    fn install_all(&self, container: &Container, installed: &mut BindingsConfig) -> ContainerResult<Vec<ActiveBinding>> {
+        let operation = format!("enabling bindings for container '{}'", container.name());
+        let bindings = &container.manifest.bindings;
         let mut active_bindings = Vec::new();
 
         // Install executable bindings
-        for executable in &container.manifest.bindings.executables {
-            let binding = self.install_executable_binding(container, executable)?;
-            active_bindings.push(binding);
+        for executable in &bindings.executables {
+            self.cancellation.check(&operation)?;
+            active_bindings.push(self.install_executable_binding(container, executable)?);
+            installed.add_executable(executable.clone());
         }
 
         // Install config bindings
-        for config in &container.manifest.bindings.configs {
-            let binding = self.install_config_binding(container, config)?;
-            active_bindings.push(binding);
+        for config in &bindings.configs {
+            self.cancellation.check(&operation)?;
+            active_bindings.push(self.install_config_binding(container, config)?);
+            installed.add_config(config.clone());
         }
 
         // Install data bindings
-        for data in &container.manifest.bindings.data {
-            let binding = self.install_data_binding(container, data)?;
-            active_bindings.push(binding);
+        for data in &bindings.data {
+            self.cancellation.check(&operation)?;
+            active_bindings.push(self.install_data_binding(container, data)?);
+            installed.add_data(data.clone());
         }
 
-        println!("✅ Installed {} bindings for container '{}'", 
-                 active_bindings.len(), container.name());
+        // Autostart entries of the executables enabled above; they are found by their
+        // marker when removed, so they need no record in the state file
+        for autostart in bindings.autostart_of_executables() {
+            self.cancellation.check(&operation)?;
+            self.install_autostart(container, autostart)?;
+            installed.add_autostart(autostart.clone());
+        }
 
         Ok(active_bindings)
     }
 
-    /// Removes all bindings for a container.
+    /// Writes the autostart entry of one executable binding, pointing at the path the
+    /// executable is bound to (the wrapper, for wrapper bindings).
+    fn install_autostart(&self, container: &Container, autostart: &AutostartBinding) -> ContainerResult<()> {
+        let executable = container
+            .manifest
+            .bindings
+            .executables
+            .iter()
+            .find(|executable| executable.target == autostart.executable)
+            .ok_or_else(|| {
+                ContainerError::ManifestValidation(format!(
+                    "Autostart entry '{}' does not name the target of an executable binding",
+                    autostart.executable
+                ))
+            })?;
+        let exec_path = self.executable_target_path(container, executable)?;
+        let (path, existed) = self.autostart.create_entry(container.name(), autostart, &exec_path)?;
+        let line = format!("🚀 Created autostart entry: {} -> {}", path.display(), exec_path.display());
+        match existed {
+            true => self.reporter.updated(path.display(), line),
+            false => self.reporter.created(path.display(), line),
+        }
+        Ok(())
+    }
+
+    /// Removes the installed autostart entries of a container, or only those starting one
+    /// of `executables` (manifest targets). Returns how many were removed.
+    fn remove_autostart(&self, container: &Container, executables: Option<&[String]>) -> ContainerResult<usize> {
+        let mut removed = 0;
+        for (path, metadata) in self.autostart.entries_of(container.name()) {
+            if executables.is_some_and(|executables| !executables.contains(&metadata.executable)) {
+                continue;
+            }
+            if AutostartGenerator::remove_entry(&path)? {
+                self.reporter.removed(path.display(), format!("🗑️  Removed autostart entry: {}", path.display()));
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Removes all bindings for a container. The install records say what was actually
+    /// created, so bindings the manifest dropped since enable are removed too; the
+    /// manifest is only used for bindings that have no record.
     pub fn remove_bindings(&self, container: &Container) -> ContainerResult<()> {
+        let records = self.records_of(container.name())?;
+        self.remove_autostart(container, None)?;
+        self.remove_recorded(container, &container.manifest.bindings, records)
+    }
+
+    /// Removes only the `selected` bindings of a container, e.g. one category or target.
+    pub fn remove_selected_bindings(&self, container: &Container, selected: &BindingsConfig) -> ContainerResult<()> {
+        let targets = self.target_paths(container, selected)?;
+        let mut records = self.records_of(container.name())?;
+        records.retain(|record| targets.contains(&self.installed_path(record)));
+        let executables: Vec<String> = selected.executables.iter().map(|executable| executable.target.clone()).collect();
+        self.remove_autostart(container, Some(&executables))?;
+        self.remove_recorded(container, selected, records)
+    }
+
+    /// Removes recorded bindings of a container that its current manifest no longer
+    /// declares, so an updated container does not leave the old ones behind.
+    pub fn remove_dropped_bindings(&self, container: &Container) -> ContainerResult<usize> {
+        let declared = &container.manifest.bindings;
+        let dropped_autostart: Vec<String> = self
+            .autostart
+            .entries_of(container.name())
+            .into_iter()
+            .map(|(_, metadata)| metadata.executable)
+            .filter(|executable| !declared.autostart.iter().any(|autostart| &autostart.executable == executable))
+            .collect();
+        let mut removed_count = match dropped_autostart.is_empty() {
+            true => 0,
+            false => self.remove_autostart(container, Some(&dropped_autostart))?,
+        };
+
+        let targets = self.target_paths(container, &container.manifest.bindings)?;
+        let mut dropped = self.records_of(container.name())?;
+        dropped.retain(|record| !targets.contains(&self.installed_path(record)));
+        if dropped.is_empty() {
+            return Ok(removed_count);
+        }
+
+        for bin_dir in Self::record_bin_dirs(&dropped, &self.user_bin_dir) {
+            PrivilegeGuard::ensure_owner(&bin_dir, "disable bindings")?;
+        }
+        self.ensure_targets_writable(container, &BindingsConfig::new(), &dropped, "remove dropped bindings")?;
+        self.reporter.info(format!(
+            "ℹ️  Removing {} binding(s) the manifest of '{}' no longer declares",
+            dropped.len(),
+            container.name()
+        ));
+        for record in &dropped {
+            if self.remove_recorded_binding(record)? {
+                removed_count += 1;
+            }
+        }
+        let forgotten: Vec<PathBuf> = dropped.iter().map(|record| record.target_path.clone()).collect();
+        self.state.forget(container.name(), &forgotten)?;
+        Ok(removed_count)
+    }
+
+    /// Removes `records`, then whatever of `declared` exists on disk without a record.
+    fn remove_recorded(&self, container: &Container, declared: &BindingsConfig, records: Vec<BindingRecord>) -> ContainerResult<()> {
+        let mut bin_dirs = Self::record_bin_dirs(&records, &self.user_bin_dir);
+        bin_dirs.push(self.bin_dir_for(container.name(), declared)?);
+        for bin_dir in &bin_dirs {
+            PrivilegeGuard::ensure_owner(bin_dir, "disable bindings")?;
+        }
+        let unrecorded = self.unrecorded_bindings(container, declared, &records)?;
+        self.ensure_targets_writable(container, &unrecorded, &records, "disable bindings")?;
+
+        let mut removed_count = 0;
+        for record in &records {
+            if self.remove_recorded_binding(record)? {
+                removed_count += 1;
+            }
+        }
+
+        let unrecorded_targets = self.target_paths(container, &unrecorded)?;
+        if !unrecorded.is_empty() {
+            self.reporter.warn(format!(
+                "{} binding(s) of '{}' have no install record, removing them as the manifest declares them:",
+                unrecorded_targets.len(),
+                container.name()
+            ));
+            for target in &unrecorded_targets {
+                self.reporter.info(format!("   {}", target.display()));
+            }
+            removed_count += self.remove_all(container, &unrecorded)?;
+        }
+
+        let mut forgotten: Vec<PathBuf> = records.iter().map(|record| record.target_path.clone()).collect();
+        forgotten.extend(unrecorded_targets);
+        self.state.forget(container.name(), &forgotten)?;
+
+        if removed_count > 0 {
+            self.reporter.info(format!(
+                "✅ Removed {} bindings for container '{}'",
+                removed_count,
+                container.name()
+            ));
+        } else {
+            self.reporter.info(format!("ℹ️  No bindings found to remove for container '{}'", container.name()));
+        }
+
+        Ok(())
+    }
+
+    /// Declared bindings that exist on disk but match none of `records`.
+    fn unrecorded_bindings(&self, container: &Container, declared: &BindingsConfig, records: &[BindingRecord]) -> ContainerResult<BindingsConfig> {
+        let recorded: Vec<PathBuf> = records.iter().map(|record| self.installed_path(record)).collect();
+        // A target another container owns is its binding, even if this manifest declares it too
+        let owned_by_others: Vec<PathBuf> = self
+            .recorded_bindings()?
+            .iter()
+            .filter(|record| record.container != container.name())
+            .map(|record| self.installed_path(record))
+            .collect();
+        let unrecorded =
+            |path: PathBuf| !recorded.contains(&path) && !owned_by_others.contains(&path) && path.symlink_metadata().is_ok();
+
+        let bin_dir = self.bin_dir_for(container.name(), declared)?;
+        let mut bindings = BindingsConfig::new();
+        bindings.bin_dir = Some(bin_dir.to_string_lossy().into_owned());
+        for executable in &declared.executables {
+            if unrecorded(self.executable_path_in(&bin_dir, executable)?) {
+                bindings.add_executable(executable.clone());
+            }
+        }
+        for config in &declared.configs {
+            if unrecorded(self.expand_path(&config.target)?) {
+                bindings.add_config(config.clone());
+            }
+        }
+        for data in &declared.data {
+            if unrecorded(self.expand_path(&data.target)?) {
+                bindings.add_data(data.clone());
+            }
+        }
+        Ok(bindings)
+    }
+
+    /// Removes one binding as recorded at install time. Files another tool put in
+    /// its place are left alone, so a stale record never deletes foreign data.
+    fn remove_recorded_binding(&self, record: &BindingRecord) -> ContainerResult<bool> {
+        let path = self.installed_path(record);
+        if path.symlink_metadata().is_err() {
+            return Ok(false);
+        }
+
+        match record.binding_type {
+            BindingType::Wrapper if WrapperGenerator::read_metadata(&path).is_none() => {
+                let reason = "it is no longer a wrappy wrapper";
+                self.reporter.skipped(path.display(), reason, format!("⚠️  Left {} alone: {}", path.display(), reason));
+                Ok(false)
+            }
+            BindingType::Symlink if !Self::is_binding_active(&path, &record.binding_type, &record.source_path) => {
+                let reason = format!("it no longer links to {}", record.source_path.display());
+                self.reporter.skipped(path.display(), &reason, format!("⚠️  Left {} alone: {}", path.display(), reason));
+                Ok(false)
+            }
+            BindingType::Wrapper => {
+                let executable_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+                let bin_dir = path.parent().unwrap_or(&self.user_bin_dir);
+                WrapperGenerator::new(bin_dir.to_path_buf()).remove_wrapper(&executable_name)?;
+                self.reporter.removed(path.display(), format!("🗑️  Removed wrapper: {}", executable_name));
+                Ok(true)
+            }
+            _ => self.remove_directory_binding(&path, &record.binding_type.to_string()),
+        }
+    }
+
+    /// Removes the given bindings, returning how many were present.
+    fn remove_all(&self, container: &Container, bindings: &BindingsConfig) -> ContainerResult<usize> {
         let mut removed_count = 0;
 
         // Remove executable bindings
-        for executable in &container.manifest.bindings.executables {
+        for executable in &bindings.executables {
             if self.remove_executable_binding(container, executable)? {
                 removed_count += 1;
             }
         }
 
         // Remove config bindings
-        for config in &container.manifest.bindings.configs {
+        for config in &bindings.configs {
             if self.remove_config_binding(container, config)? {
                 removed_count += 1;
             }
         }
 
         // Remove data bindings
-        for data in &container.manifest.bindings.data {
+        for data in &bindings.data {
             if self.remove_data_binding(container, data)? {
                 removed_count += 1;
             }
         }
 
-        if removed_count > 0 {
-            println!("✅ Removed {} bindings for container '{}'", 
-                     removed_count, container.name());
-        } else {
-            println!("ℹ️  No bindings found to remove for container '{}'", container.name());
+        for autostart in &bindings.autostart {
+            let path = self.autostart.entry_path(container.name(), autostart);
+            if AutostartGenerator::remove_entry(&path)? {
+                self.reporter.removed(path.display(), format!("🗑️  Removed autostart entry: {}", path.display()));
+            }
+        }
+
+        Ok(removed_count)
+    }
+
+    /// Checks every directory a container's bindings would touch before any change is made,
+    /// so a read-only mount fails the whole operation instead of leaving it half applied.
+    fn ensure_targets_writable(
+        &self,
+        container: &Container,
+        bindings: &BindingsConfig,
+        records: &[BindingRecord],
+        operation: &str,
+    ) -> ContainerResult<()> {
+        let mut directories: Vec<PathBuf> = records
+            .iter()
+            .map(|record| match record.binding_type {
+                BindingType::Wrapper => record.bin_dir.clone().unwrap_or_else(|| self.user_bin_dir.clone()),
+                _ => record.target_path.clone(),
+            })
+            .collect();
+
+        let bin_dir = self.bin_dir_for(container.name(), bindings)?;
+        for executable in &bindings.executables {
+            match executable.binding_type() {
+                BindingType::Wrapper => directories.push(bin_dir.clone()),
+                _ => directories.push(self.executable_path_in(&bin_dir, executable)?),
+            }
+        }
+        for config in &bindings.configs {
+            directories.push(self.expand_path(&config.target)?);
+        }
+        for data in &bindings.data {
+            directories.push(self.expand_path(&data.target)?);
+        }
+        if !bindings.autostart.is_empty() {
+            directories.push(self.autostart.autostart_dir().to_path_buf());
+        }
+
+        directories.sort();
+        directories.dedup();
+
+        let operation = format!("{} for container '{}'", operation, container.name());
+        for directory in &directories {
+            filesystem::ensure_writable(directory, &operation)?;
         }
 
         Ok(())
     }
 
-    /// Lists all active wrapper scripts managed by this system.
-    pub fn list_active_wrappers(&self) -> ContainerResult<Vec<String>> {
-        self.wrapper_generator.list_wrappers()
+    /// Narrows a container's bindings to the entries matched by `filter`.
+    /// Targets are compared after expansion so `~/.config/app` and the absolute path are equivalent.
+    pub fn filter_bindings(&self, container: &Container, bindings: &BindingsConfig, filter: &BindingFilter) -> ContainerResult<BindingsConfig> {
+        let wanted_target = filter
+            .target
+            .as_deref()
+            .map(|target| self.expand_path(target))
+            .transpose()?;
+
+        let matches_target = |target: &str| -> bool {
+            match &wanted_target {
+                Some(wanted) => self.expand_path(target).is_ok_and(|path| &path == wanted),
+                None => true,
+            }
+        };
+
+        let matches_label = |label: &str| filter.label.as_deref().is_none_or(|wanted| wanted == label);
+
+        let bin_dir = self.bin_dir_for(container.name(), bindings)?;
+        let mut filtered = BindingsConfig::new();
+        filtered.bin_dir = bindings.bin_dir.clone();
+        if filter.includes(BindingCategory::Executables) {
+            filtered.executables = bindings
+                .executables
+                .iter()
+                .filter(|executable| {
+                    matches_target(&executable.target)
+                        || self.executable_path_in(&bin_dir, executable).ok() == wanted_target
+                })
+                .cloned()
+                .collect();
+        }
+        if filter.includes(BindingCategory::Configs) {
+            filtered.configs = bindings
+                .configs
+                .iter()
+                .filter(|config| matches_target(&config.target) && matches_label(&config.label()))
+                .cloned()
+                .collect();
+        }
+        if filter.includes(BindingCategory::Data) {
+            filtered.data = bindings
+                .data
+                .iter()
+                .filter(|data| matches_target(&data.target) && matches_label(&data.label()))
+                .cloned()
+                .collect();
+        }
+
+        Ok(filtered)
+    }
+
+    /// Host path an executable binding of `container` occupies once installed
+    /// (wrappers live in the container's bin directory).
+    pub fn executable_target_path(&self, container: &Container, executable: &ExecutableBinding) -> ContainerResult<PathBuf> {
+        let bin_dir = self.bin_dir_for(container.name(), &container.manifest.bindings)?;
+        self.executable_path_in(&bin_dir, executable)
+    }
+
+    /// Host path an executable binding occupies with `bin_dir` as the bin directory.
+    pub fn executable_path_in(&self, bin_dir: &Path, executable: &ExecutableBinding) -> ContainerResult<PathBuf> {
+        let target_path = self.declared_target_path(bin_dir, executable)?;
+        match executable.binding_type() {
+            BindingType::Wrapper => {
+                let executable_name = target_path.file_name().ok_or_else(|| ContainerError::InvalidPath {
+                    path: target_path.clone(),
+                    reason: "Invalid executable name".to_string(),
+                })?;
+                Ok(bin_dir.join(executable_name))
+            }
+            _ => Ok(target_path),
+        }
+    }
+
+    /// The target as written, with a bare name placed in `bin_dir`.
+    fn declared_target_path(&self, bin_dir: &Path, executable: &ExecutableBinding) -> ContainerResult<PathBuf> {
+        match executable.is_bare_name() {
+            true => {
+                self.expand_path(&executable.target)?;
+                Ok(bin_dir.join(&executable.target))
+            }
+            false => self.expand_path(&executable.target),
+        }
+    }
+
+    /// The configured bin directory followed by the other ones `records` were enabled into.
+    fn record_bin_dirs(records: &[BindingRecord], user_bin_dir: &Path) -> Vec<PathBuf> {
+        let mut dirs = vec![user_bin_dir.to_path_buf()];
+        for bin_dir in records.iter().filter_map(|record| record.bin_dir.clone()) {
+            if !dirs.contains(&bin_dir) {
+                dirs.push(bin_dir);
+            }
+        }
+        dirs
+    }
+
+    /// Host path a config or data binding occupies once installed.
+    pub fn directory_target_path(&self, target: &str) -> ContainerResult<PathBuf> {
+        self.expand_path(target)
+    }
+
+    /// Resolves an executable binding through the same path logic enable uses.
+    pub fn resolve_executable(&self, container: &Container, executable: &ExecutableBinding) -> ResolvedBinding {
+        self.resolve(container, &executable.source, self.executable_target_path(container, executable), &executable.binding_type())
+    }
+
+    /// Resolves a config or data binding through the same path logic enable uses.
+    pub fn resolve_directory(
+        &self,
+        container: &Container,
+        source: &str,
+        target: &str,
+        binding_type: &BindingType,
+    ) -> ResolvedBinding {
+        self.resolve(container, source, self.directory_target_path(target), binding_type)
+    }
+
+    fn resolve(
+        &self,
+        container: &Container,
+        source: &str,
+        target_path: ContainerResult<PathBuf>,
+        binding_type: &BindingType,
+    ) -> ResolvedBinding {
+        let source_path = container.path.join(source);
+        let target = target_path.map(|target_path| {
+            let state = if Self::is_binding_active(&target_path, binding_type, &source_path) {
+                TargetState::Bound
+            } else if target_path.symlink_metadata().is_ok() {
+                TargetState::Occupied
+            } else if target_path.parent().is_some_and(|parent| !parent.is_dir()) {
+                TargetState::ParentMissing
+            } else {
+                TargetState::Free
+            };
+            (target_path, state)
+        });
+
+        ResolvedBinding {
+            source_exists: source_path.exists(),
+            source_path,
+            target,
+        }
+    }
+
+    /// Reports whether a binding is currently installed on the host.
+    /// Symlinks only count when they still point into the container, so a
+    /// restored backup at the same target is reported as inactive.
+    pub fn is_binding_active(target_path: &Path, binding_type: &BindingType, source_path: &Path) -> bool {
+        match binding_type {
+            BindingType::Symlink => fs::read_link(target_path)
+                .map(|link| link == source_path)
+                .unwrap_or(false),
+            BindingType::Wrapper | BindingType::Copy => target_path.symlink_metadata().is_ok(),
+        }
+    }
+
+    /// Lists the wrapper scripts in every bin directory, by directory.
+    pub fn list_active_wrappers(&self) -> ContainerResult<Vec<(PathBuf, Vec<String>)>> {
+        let mut wrappers = Vec::new();
+        for bin_dir in self.bin_dirs()? {
+            let names = WrapperGenerator::new(bin_dir.clone()).list_wrappers()?;
+            wrappers.push((bin_dir, names));
+        }
+        Ok(wrappers)
+    }
+
+    /// Every recorded binding, plus the wrappy wrappers in the bin directories that
+    /// have no record, optionally of one container only; sorted by container and target.
+    pub fn list_bindings(&self, container: Option<&str>) -> ContainerResult<Vec<BindingListing>> {
+        let records = self.recorded_bindings()?;
+        let recorded: Vec<PathBuf> = records.iter().map(|record| self.installed_path(record)).collect();
+
+        let mut listings = Vec::new();
+        for record in records {
+            let target = self.installed_path(&record);
+            // Bindings in a bin directory are executables even in records without a category
+            let in_bin_dir = record.binding_type == BindingType::Wrapper
+                || target.parent() == Some(record.bin_dir.as_deref().unwrap_or(&self.user_bin_dir));
+            listings.push(BindingListing {
+                container: record.container,
+                kind: record.category.or(in_bin_dir.then_some(BindingCategory::Executables)),
+                status: Self::listing_status(&record.binding_type, &target),
+                binding_type: record.binding_type,
+                target,
+                source: record.source_path,
+                created_at: Some(record.created_at),
+                recorded: true,
+            });
+        }
+        for (bin_dir, names) in self.list_active_wrappers()? {
+            for path in names.into_iter().map(|name| bin_dir.join(name)) {
+                if recorded.contains(&path) {
+                    continue;
+                }
+                let Some(metadata) = WrapperGenerator::read_metadata(&path) else {
+                    continue;
+                };
+                listings.push(BindingListing {
+                    container: metadata.container,
+                    kind: Some(BindingCategory::Executables),
+                    status: Self::listing_status(&BindingType::Wrapper, &path),
+                    binding_type: BindingType::Wrapper,
+                    target: path,
+                    source: metadata.executable_path,
+                    created_at: None,
+                    recorded: false,
+                });
+            }
+        }
+
+        listings.retain(|listing| container.is_none_or(|container| listing.container == container));
+        listings.sort_by(|a, b| (&a.container, &a.target).cmp(&(&b.container, &b.target)));
+        Ok(listings)
+    }
+
+    fn listing_status(binding_type: &BindingType, target: &Path) -> ListingStatus {
+        if target.symlink_metadata().is_err() {
+            return ListingStatus::Missing;
+        }
+        let usable = match binding_type {
+            // `exists` follows the link, so it is false for a dangling one
+            BindingType::Symlink => target.exists(),
+            BindingType::Wrapper => {
+                WrapperGenerator::read_metadata(target).is_none_or(|metadata| metadata.executable_path.exists())
+            }
+            BindingType::Copy => true,
+        };
+        match usable {
+            true => ListingStatus::Ok,
+            false => ListingStatus::Broken,
+        }
     }
 
     /// Installs binding for a single executable.
@@ -124,7 +1044,8 @@ impl BindingManager {
         executable: &ExecutableBinding,
     ) -> ContainerResult<ActiveBinding> {
         let source_path = container.path.join(&executable.source);
-        let target_path = self.expand_path(&executable.target)?;
+        let bin_dir = self.bin_dir_for(container.name(), &container.manifest.bindings)?;
+        let target_path = self.declared_target_path(&bin_dir, executable)?;
 
         // Validate source exists and is executable
         if !source_path.exists() {
@@ -141,7 +1062,15 @@ impl BindingManager {
             });
         }
 
-        match executable.binding_type {
+        // Wrappers and copies overwrite what is there, which makes them updates
+        let installed_path = self.executable_path_in(&bin_dir, executable)?;
+        let existed = installed_path.symlink_metadata().is_ok();
+        let report = |line: String| match existed {
+            true => self.reporter.updated(installed_path.display(), line),
+            false => self.reporter.created(installed_path.display(), line),
+        };
+
+        match executable.binding_type() {
             BindingType::Wrapper => {
                 let executable_name = target_path
                     .file_name()
@@ -151,28 +1080,26 @@ impl BindingManager {
                         reason: "Invalid executable name".to_string(),
                     })?;
 
-                self.wrapper_generator.create_wrapper(
+                WrapperGenerator::new(bin_dir.clone()).create_wrapper(
                     executable_name,
                     container.name(),
+                    &container.path,
+                    executable,
                     &source_path,
-                    executable.display_name.as_deref(),
                 )?;
 
-                println!("🔗 Created wrapper: {} -> {}", 
-                         executable_name, source_path.display());
+                report(format!("🔗 Created wrapper: {} -> {}", executable_name, source_path.display()));
             }
             BindingType::Symlink => {
                 self.create_symlink(&source_path, &target_path)?;
-                println!("🔗 Created symlink: {} -> {}", 
-                         target_path.display(), source_path.display());
+                report(format!("🔗 Created symlink: {} -> {}", target_path.display(), source_path.display()));
             }
             BindingType::Copy => {
                 fs::copy(&source_path, &target_path).map_err(|e| ContainerError::IoError {
                     path: target_path.clone(),
                     source: e,
                 })?;
-                println!("📋 Copied executable: {} -> {}", 
-                         source_path.display(), target_path.display());
+                report(format!("📋 Copied executable: {} -> {}", source_path.display(), target_path.display()));
             }
         }
 
@@ -180,8 +1107,10 @@ impl BindingManager {
             container_name: container.name().to_string(),
             source_path,
             target_path,
-            binding_type: executable.binding_type.clone(),
-            created_at: std::time::SystemTime::now(),
+            binding_type: executable.binding_type(),
+            category: BindingCategory::Executables,
+            bin_dir: (bin_dir != self.user_bin_dir).then_some(bin_dir),
+            created_at: clock::system_now(),
         })
     }
 
@@ -192,16 +1121,20 @@ impl BindingManager {
         config: &ConfigBinding,
     ) -> ContainerResult<ActiveBinding> {
         let source_path = container.path.join(&config.source);
-        let target_path = self.expand_path(&config.target)?;
+        let target_path = self.directory_target_path(&config.target)?;
 
         self.install_directory_binding(
             container,
             &source_path,
             &target_path,
-            &config.binding_type,
+            &config.binding_type(),
             config.backup_existing,
-            "config",
+            &format!("config '{}'", config.label()),
         )
+        .map(|binding| ActiveBinding {
+            category: BindingCategory::Configs,
+            ..binding
+        })
     }
 
     /// Installs binding for a data directory.
@@ -211,16 +1144,20 @@ impl BindingManager {
         data: &DataBinding,
     ) -> ContainerResult<ActiveBinding> {
         let source_path = container.path.join(&data.source);
-        let target_path = self.expand_path(&data.target)?;
+        let target_path = self.directory_target_path(&data.target)?;
 
         self.install_directory_binding(
             container,
             &source_path,
             &target_path,
-            &data.binding_type,
+            &data.binding_type(),
             data.backup_existing,
-            "data",
+            &format!("data '{}'", data.label()),
         )
+        .map(|binding| ActiveBinding {
+            category: BindingCategory::Data,
+            ..binding
+        })
     }
 
     /// Generic directory binding installation.
@@ -237,24 +1174,27 @@ impl BindingManager {
         if !source_path.exists() {
             return Err(ContainerError::InvalidPath {
                 path: source_path.to_path_buf(),
-                reason: format!("Source {} directory does not exist", binding_kind),
+                reason: format!("Source directory of {} binding does not exist", binding_kind),
             });
         }
 
         // Handle existing target
         if target_path.exists() {
             if backup_existing {
-                let backup_path = format!("{}.wrappy-backup", target_path.display());
+                let backup_path = Self::backup_path(target_path);
                 fs::rename(target_path, &backup_path).map_err(|e| ContainerError::IoError {
                     path: target_path.to_path_buf(),
                     source: e,
                 })?;
-                println!("📦 Backed up existing {} to {}", 
-                         target_path.display(), backup_path);
+                self.reporter.info(format!(
+                    "📦 Backed up existing {} to {}",
+                    target_path.display(),
+                    backup_path.display()
+                ));
             } else {
                 return Err(ContainerError::InvalidPath {
                     path: target_path.to_path_buf(),
-                    reason: format!("Target {} already exists", binding_kind),
+                    reason: format!("Target of {} binding already exists", binding_kind),
                 });
             }
         }
@@ -270,18 +1210,27 @@ impl BindingManager {
         match binding_type {
             BindingType::Symlink => {
                 self.create_symlink(source_path, target_path)?;
-                println!("🔗 Created {} symlink: {} -> {}", 
-                         binding_kind, target_path.display(), source_path.display());
+                self.reporter.created(
+                    target_path.display(),
+                    format!("🔗 Created {} symlink: {} -> {}", binding_kind, target_path.display(), source_path.display()),
+                );
             }
             BindingType::Copy => {
-                self.copy_directory(source_path, target_path)?;
-                println!("📋 Copied {} directory: {} -> {}", 
-                         binding_kind, source_path.display(), target_path.display());
+                if let Err(error) = self.copy_directory(source_path, target_path) {
+                    // A half-copied directory is useless, so put the original target back
+                    let _ = fs::remove_dir_all(target_path);
+                    self.restore_backup(target_path)?;
+                    return Err(error);
+                }
+                self.reporter.created(
+                    target_path.display(),
+                    format!("📋 Copied {} directory: {} -> {}", binding_kind, source_path.display(), target_path.display()),
+                );
             }
             BindingType::Wrapper => {
                 return Err(ContainerError::InvalidPath {
                     path: target_path.to_path_buf(),
-                    reason: format!("Wrapper binding not supported for {} directories", binding_kind),
+                    reason: format!("Wrapper binding type not supported for {} binding", binding_kind),
                 });
             }
         }
@@ -291,7 +1240,10 @@ impl BindingManager {
             source_path: source_path.to_path_buf(),
             target_path: target_path.to_path_buf(),
             binding_type: binding_type.clone(),
-            created_at: std::time::SystemTime::now(),
+            // Set by the config and data callers
+            category: BindingCategory::Configs,
+            bin_dir: None,
+            created_at: clock::system_now(),
         })
     }
 
@@ -301,9 +1253,10 @@ impl BindingManager {
         container: &Container,
         executable: &ExecutableBinding,
     ) -> ContainerResult<bool> {
-        let target_path = self.expand_path(&executable.target)?;
+        let bin_dir = self.bin_dir_for(container.name(), &container.manifest.bindings)?;
+        let target_path = self.declared_target_path(&bin_dir, executable)?;
 
-        match executable.binding_type {
+        match executable.binding_type() {
             BindingType::Wrapper => {
                 let executable_name = target_path
                     .file_name()
@@ -313,8 +1266,8 @@ impl BindingManager {
                         reason: "Invalid executable name".to_string(),
                     })?;
 
-                self.wrapper_generator.remove_wrapper(executable_name)?;
-                println!("🗑️  Removed wrapper: {}", executable_name);
+                WrapperGenerator::new(bin_dir.clone()).remove_wrapper(executable_name)?;
+                self.reporter.removed(target_path.display(), format!("🗑️  Removed wrapper: {}", executable_name));
                 Ok(true)
             }
             _ => {
@@ -323,7 +1276,7 @@ impl BindingManager {
                         path: target_path.clone(),
                         source: e,
                     })?;
-                    println!("🗑️  Removed executable: {}", target_path.display());
+                    self.reporter.removed(target_path.display(), format!("🗑️  Removed executable: {}", target_path.display()));
                     Ok(true)
                 } else {
                     Ok(false)
@@ -335,21 +1288,21 @@ impl BindingManager {
     /// Removes config binding.
     fn remove_config_binding(
         &self,
-        container: &Container,
+        _container: &Container,
         config: &ConfigBinding,
     ) -> ContainerResult<bool> {
         let target_path = self.expand_path(&config.target)?;
-        self.remove_directory_binding(&target_path, "config")
+        self.remove_directory_binding(&target_path, &format!("config '{}'", config.label()))
     }
 
     /// Removes data binding.
     fn remove_data_binding(
         &self,
-        container: &Container,
+        _container: &Container,
         data: &DataBinding,
     ) -> ContainerResult<bool> {
         let target_path = self.expand_path(&data.target)?;
-        self.remove_directory_binding(&target_path, "data")
+        self.remove_directory_binding(&target_path, &format!("data '{}'", data.label()))
     }
 
     /// Generic directory binding removal.
@@ -370,13 +1323,37 @@ impl BindingManager {
                     source: e,
                 })?;
             }
-            println!("🗑️  Removed {} binding: {}", binding_kind, target_path.display());
+            self.reporter.removed(
+                target_path.display(),
+                format!("🗑️  Removed {} binding: {}", binding_kind, target_path.display()),
+            );
+            self.restore_backup(target_path)?;
             Ok(true)
         } else {
             Ok(false)
         }
     }
 
+    /// Location where an existing target is moved before a binding replaces it.
+    fn backup_path(target_path: &Path) -> PathBuf {
+        PathBuf::from(format!("{}.wrappy-backup", target_path.display()))
+    }
+
+    /// Moves a backup made at install time back into place once the binding is gone.
+    fn restore_backup(&self, target_path: &Path) -> ContainerResult<()> {
+        let backup_path = Self::backup_path(target_path);
+        if !backup_path.exists() {
+            return Ok(());
+        }
+
+        fs::rename(&backup_path, target_path).map_err(|e| ContainerError::IoError {
+            path: backup_path.clone(),
+            source: e,
+        })?;
+        self.reporter.info(format!("📦 Restored backup {} to {}", backup_path.display(), target_path.display()));
+        Ok(())
+    }
+
     /// Creates a symbolic link with error handling.
     fn create_symlink(&self, source: &Path, target: &Path) -> ContainerResult<()> {
         unix_fs::symlink(source, target).map_err(|e| ContainerError::IoError {
@@ -386,29 +1363,45 @@ impl BindingManager {
         Ok(())
     }
 
-    /// Recursively copies a directory.
+    /// Recursively copies a directory, checking for cancellation before every entry.
     fn copy_directory(&self, source: &Path, target: &Path) -> ContainerResult<()> {
-        fs::create_dir_all(target).map_err(|e| ContainerError::IoError {
-            path: target.to_path_buf(),
-            source: e,
-        })?;
-
-        for entry in fs::read_dir(source).map_err(|e| ContainerError::IoError {
-            path: source.to_path_buf(),
-            source: e,
-        })? {
-            let entry = entry.map_err(|e| ContainerError::IoError {
-                path: source.to_path_buf(),
-                source: e,
-            })?;
-
-            let source_path = entry.path();
-            let target_path = target.join(entry.file_name());
+        for entry in Walk::new(source).follow_root_link() {
+            let entry = entry?;
+            let relative = entry.path.strip_prefix(source).unwrap_or(&entry.path);
+            let target_path = target.join(relative);
+            if entry.depth == 0 && !entry.file_type.is_dir() {
+                return Err(ContainerError::InvalidPath {
+                    path: entry.path,
+                    reason: "not a directory".to_string(),
+                });
+            }
+            if entry.depth == 0 {
+                fs::create_dir_all(target).map_err(|e| ContainerError::IoError {
+                    path: target.to_path_buf(),
+                    source: e,
+                })?;
+                continue;
+            }
+            let directory = entry.path.parent().unwrap_or(source);
+            self.cancellation.check(&format!("copying {}", directory.display()))?;
 
-            if source_path.is_dir() {
-                self.copy_directory(&source_path, &target_path)?;
+            // Links are recreated as they are, so dangling or cyclic ones survive the copy
+            if entry.file_type.is_symlink() {
+                let link = fs::read_link(&entry.path).map_err(|e| ContainerError::IoError {
+                    path: entry.path.clone(),
+                    source: e,
+                })?;
+                unix_fs::symlink(&link, &target_path).map_err(|e| ContainerError::IoError {
+                    path: target_path,
+                    source: e,
+                })?;
+            } else if entry.file_type.is_dir() {
+                fs::create_dir(&target_path).map_err(|e| ContainerError::IoError {
+                    path: target_path,
+                    source: e,
+                })?;
             } else {
-                fs::copy(&source_path, &target_path).map_err(|e| ContainerError::IoError {
+                fs::copy(&entry.path, &target_path).map_err(|e| ContainerError::IoError {
                     path: target_path,
                     source: e,
                 })?;
@@ -419,17 +1412,214 @@ impl BindingManager {
     }
 
     /// Expands ~ in paths to actual home directory.
+    /// Install variables still in the path mean nobody chose their values yet.
     fn expand_path(&self, path: &str) -> ContainerResult<PathBuf> {
-        if path.starts_with("~/") {
+        let unresolved = template::names_in(path, template::INSTALL_NAMESPACE);
+        if !unresolved.is_empty() {
+            return Err(ContainerError::InvalidPath {
+                path: PathBuf::from(path),
+                reason: format!("unresolved install variables: {}", unresolved.join(", ")),
+            });
+        }
+        if let Some(relative) = path.strip_prefix("~/") {
             let home = dirs::home_dir().ok_or_else(|| {
                 ContainerError::InvalidPath {
                     path: PathBuf::from(path),
                     reason: "Could not determine home directory".to_string(),
                 }
             })?;
-            Ok(home.join(&path[2..]))
+            Ok(home.join(relative))
         } else {
             Ok(PathBuf::from(path))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::ContainerService;
+    use crate::features::setup::SetupService;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, write_executable, write_manifest, TempHome};
+    use serde_json::{json, Value};
+
+    /// `app` with the wrappers `app-tool`, `app-a` and `app-b` enabled; returns its directory and manifest.
+    fn enabled_with_three(home: &TempHome) -> (PathBuf, Value) {
+        SetupService::initialize(&home.paths()).unwrap();
+        let dir = home.paths().default_store_dir().join("app/1.0.0");
+        let mut manifest = fixture_manifest("app", "1.0.0");
+        manifest["bindings"]["executables"] = json!([
+            { "source": "bin/tool", "target": "app-tool" },
+            { "source": "bin/a", "target": "app-a" },
+            { "source": "bin/b", "target": "app-b" },
+        ]);
+        fixture_container_at(&dir, manifest.clone());
+        write_executable(&dir.join("bin/a"), "#!/bin/sh\n");
+        write_executable(&dir.join("bin/b"), "#!/bin/sh\n");
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+        BindingManager::new().unwrap().install_bindings(&container).unwrap();
+        (dir, manifest)
+    }
+
+    /// Rewrites the manifest to declare only `app-tool`, as an update that dropped two bindings would.
+    fn drop_to_one(dir: &Path, mut manifest: Value) -> Container {
+        manifest["bindings"]["executables"] = json!([{ "source": "bin/tool", "target": "app-tool" }]);
+        write_manifest(dir, &manifest);
+        ContainerService::load_from_directory(dir).unwrap()
+    }
+
+    fn wrappers(home: &TempHome) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(&home.paths().bin_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn bindings_the_updated_manifest_dropped_are_removed_and_forgotten() {
+        let home = TempHome::enter();
+        let (dir, manifest) = enabled_with_three(&home);
+        assert_eq!(wrappers(&home), ["app-a", "app-b", "app-tool"]);
+        let updated = drop_to_one(&dir, manifest);
+        let manager = BindingManager::new().unwrap();
+
+        let removed = manager.remove_dropped_bindings(&updated).unwrap();
+
+        assert_eq!(removed, 2);
+        assert_eq!(wrappers(&home), ["app-tool"]);
+        let recorded: Vec<PathBuf> = manager.records_of("app").unwrap().into_iter().map(|record| record.target_path).collect();
+        assert_eq!(recorded, [home.paths().bin_dir.join("app-tool")]);
+        assert_eq!(manager.remove_dropped_bindings(&updated).unwrap(), 0);
+    }
+
+    #[test]
+    fn disabling_after_the_manifest_shrank_removes_what_was_actually_created() {
+        let home = TempHome::enter();
+        let (dir, manifest) = enabled_with_three(&home);
+        let updated = drop_to_one(&dir, manifest);
+        let manager = BindingManager::new().unwrap();
+
+        manager.remove_bindings(&updated).unwrap();
+
+        assert!(wrappers(&home).is_empty());
+        assert!(manager.records_of("app").unwrap().is_empty());
+    }
+
+    #[test]
+    fn without_install_records_removal_falls_back_to_the_manifest() {
+        let home = TempHome::enter();
+        let (dir, _) = enabled_with_three(&home);
+        fs::remove_file(home.paths().binding_state_file()).unwrap();
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+
+        BindingManager::new().unwrap().remove_bindings(&container).unwrap();
+
+        assert!(wrappers(&home).is_empty());
+    }
+
+    #[test]
+    fn a_failing_preflight_reports_every_problem_and_creates_nothing() {
+        let home = TempHome::enter();
+        SetupService::initialize(&home.paths()).unwrap();
+        let dir = home.paths().default_store_dir().join("app/1.0.0");
+        let mut manifest = fixture_manifest("app", "1.0.0");
+        manifest["bindings"]["executables"] = json!([
+            { "source": "bin/tool", "target": "app-tool" },
+            { "source": "bin/gone", "target": "app-gone" },
+        ]);
+        manifest["bindings"]["data"] = json!([{ "source": "missing", "target": "~/.local/share/app", "binding_type": "copy" }]);
+        fixture_container_at(&dir, manifest);
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+
+        let error = BindingManager::new().unwrap().install_bindings(&container).unwrap_err();
+
+        match &error {
+            ContainerError::PreflightFailed { problems, .. } => assert_eq!(problems.len(), 2, "{:?}", problems),
+            other => panic!("expected a pre-flight failure, got {:?}", other),
+        }
+        assert!(wrappers(&home).is_empty());
+        assert!(!home.path().join(".local/share/app").exists());
+    }
+
+    /// `name` installed in the store with executable bindings on `targets`.
+    fn declaring(home: &TempHome, name: &str, targets: &[&str]) -> Container {
+        let dir = home.paths().default_store_dir().join(name).join("1.0.0");
+        let mut manifest = fixture_manifest(name, "1.0.0");
+        manifest["bindings"]["executables"] =
+            targets.iter().map(|target| json!({ "source": "bin/tool", "target": target })).collect();
+        fixture_container_at(&dir, manifest);
+        ContainerService::load_from_directory(&dir).unwrap()
+    }
+
+    fn owner_of(home: &TempHome, wrapper: &str) -> String {
+        WrapperGenerator::read_metadata(&home.paths().bin_dir.join(wrapper)).unwrap().container
+    }
+
+    #[test]
+    fn a_target_another_container_owns_is_refused_and_left_untouched() {
+        let home = TempHome::enter();
+        SetupService::initialize(&home.paths()).unwrap();
+        let alpha = declaring(&home, "alpha", &["tool"]);
+        let beta = declaring(&home, "beta", &["tool", "beta-only"]);
+        let manager = BindingManager::new().unwrap();
+        manager.install_bindings(&alpha).unwrap();
+
+        let refused = manager.install_bindings(&beta);
+
+        match refused {
+            Err(ContainerError::BindingConflict { conflicts }) => {
+                assert_eq!(conflicts.len(), 1);
+                assert!(conflicts[0].contains("owned by 'alpha'") && conflicts[0].contains("--force"), "{}", conflicts[0]);
+            }
+            other => panic!("expected a binding conflict, got {:?}", other.map(|bindings| bindings.len())),
+        }
+        assert_eq!(owner_of(&home, "tool"), "alpha");
+        assert_eq!(wrappers(&home), ["tool"], "nothing of beta was installed");
+        assert!(manager.records_of("beta").unwrap().is_empty());
+    }
+
+    #[test]
+    fn force_takes_the_target_over_with_its_record() {
+        let home = TempHome::enter();
+        SetupService::initialize(&home.paths()).unwrap();
+        let alpha = declaring(&home, "alpha", &["tool"]);
+        let beta = declaring(&home, "beta", &["tool"]);
+        BindingManager::new().unwrap().install_bindings(&alpha).unwrap();
+
+        let manager = BindingManager::new().unwrap().take_over(true);
+        manager.install_bindings(&beta).unwrap();
+
+        assert_eq!(owner_of(&home, "tool"), "beta");
+        assert!(manager.records_of("alpha").unwrap().is_empty());
+        assert_eq!(manager.records_of("beta").unwrap().len(), 1);
+        let owners: Vec<String> = manager
+            .foreign_owners(&alpha, &alpha.manifest.bindings)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.container)
+            .collect();
+        assert_eq!(owners, ["beta"], "alpha now conflicts with beta");
+    }
+
+    #[test]
+    fn disable_only_removes_targets_the_container_owns() {
+        let home = TempHome::enter();
+        SetupService::initialize(&home.paths()).unwrap();
+        let alpha = declaring(&home, "alpha", &["tool"]);
+        let beta = declaring(&home, "beta", &["beta-only"]);
+        let manager = BindingManager::new().unwrap();
+        manager.install_bindings(&alpha).unwrap();
+        manager.install_bindings(&beta).unwrap();
+        // An update that now also declares alpha's target, which beta never got to own
+        let updated = declaring(&home, "beta", &["tool", "beta-only"]);
+
+        manager.remove_bindings(&updated).unwrap();
+
+        assert_eq!(wrappers(&home), ["tool"]);
+        assert_eq!(owner_of(&home, "tool"), "alpha");
+        assert_eq!(manager.records_of("alpha").unwrap().len(), 1);
+        assert!(manager.records_of("beta").unwrap().is_empty());
+    }
+}
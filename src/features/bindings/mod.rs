@@ -1,9 +1,23 @@
 mod types;
 mod manager;
 mod wrapper;
+mod autostart;
 mod commands;
+mod policy;
+mod preflight;
+mod precedence;
+mod reconcile;
+mod state;
+mod verify;
 
 pub use types::*;
 pub use manager::*;
 pub use wrapper::*;
-pub use commands::*;
\ No newline at end of file
+pub use autostart::*;
+pub use commands::*;
+pub use policy::*;
+pub use preflight::*;
+pub use precedence::*;
+pub use reconcile::*;
+pub use state::*;
+pub use verify::*;
@@ -0,0 +1,212 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::features::bindings::{BindingType, BindingsConfig};
+use crate::shared::config::BindingsPolicyConfig;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::paths::WrappyPaths;
+
+/// Machine-wide rules for creating bindings, read from the `bindings` section of the config.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BindingPolicy {
+    pub default_type: Option<BindingType>,
+    pub always_backup: bool,
+    pub forbid_types: Vec<BindingType>,
+    /// Expanded target roots; empty allows any target
+    pub allowed_target_roots: Vec<PathBuf>,
+}
+
+/// A binding the policy does not allow, with the config key that forbids it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyViolation {
+    pub key: &'static str,
+    pub details: String,
+}
+
+impl From<PolicyViolation> for ContainerError {
+    fn from(violation: PolicyViolation) -> Self {
+        ContainerError::PolicyViolation {
+            key: violation.key.to_string(),
+            details: format!("{} (use --override-policy for a one-off exception)", violation.details),
+        }
+    }
+}
+
+impl BindingPolicy {
+    /// Interprets the config section; unknown type names are reported with their key.
+    pub fn from_config(config: &BindingsPolicyConfig, paths: &WrappyPaths) -> ContainerResult<Self> {
+        let parse_type = |key: &str, value: &str| {
+            serde_json::from_value::<BindingType>(serde_json::Value::String(value.to_string())).map_err(|_| {
+                ContainerError::InvalidConfig {
+                    path: paths.config_file(),
+                    reason: format!(
+                        "bindings.{}: unknown binding type '{}', expected wrapper, symlink or copy",
+                        key, value
+                    ),
+                }
+            })
+        };
+
+        Ok(Self {
+            default_type: config
+                .default_type
+                .as_deref()
+                .map(|value| parse_type("default_type", value))
+                .transpose()?,
+            always_backup: config.always_backup,
+            forbid_types: config
+                .forbid_types
+                .iter()
+                .map(|value| parse_type("forbid_types", value))
+                .collect::<ContainerResult<_>>()?,
+            allowed_target_roots: config
+                .allowed_target_roots
+                .iter()
+                .map(|root| expand_home(root, &paths.home))
+                .collect(),
+        })
+    }
+
+    /// Policy of the current user's config; no config means no restrictions.
+    pub fn load() -> ContainerResult<Self> {
+        let paths = WrappyPaths::new()?;
+        let config = crate::shared::Config::load_or_default(&paths)?;
+        Self::from_config(&config.bindings, &paths)
+    }
+
+    /// Fills what the manifest left open: the binding type where none is given
+    /// (wrappers only for executables) and backups when `always_backup` is set.
+    pub fn apply_defaults(&self, bindings: &mut BindingsConfig) {
+        if let Some(default_type) = &self.default_type {
+            for executable in &mut bindings.executables {
+                executable.binding_type.get_or_insert_with(|| default_type.clone());
+            }
+            if default_type != &BindingType::Wrapper {
+                for config in &mut bindings.configs {
+                    config.binding_type.get_or_insert_with(|| default_type.clone());
+                }
+                for data in &mut bindings.data {
+                    data.binding_type.get_or_insert_with(|| default_type.clone());
+                }
+            }
+        }
+
+        if self.always_backup {
+            bindings.configs.iter_mut().for_each(|config| config.backup_existing = true);
+            bindings.data.iter_mut().for_each(|data| data.backup_existing = true);
+        }
+    }
+
+    /// Decides whether a binding of `binding_type` may occupy `target_path` (already expanded).
+    /// A target with a `..` component counts as outside every root, since it can climb out of one.
+    pub fn evaluate(&self, binding_type: &BindingType, target_path: &Path) -> Result<(), PolicyViolation> {
+        if self.forbid_types.contains(binding_type) {
+            return Err(PolicyViolation {
+                key: "forbid_types",
+                details: format!("{} bindings are forbidden", binding_type),
+            });
+        }
+
+        let climbs = target_path.components().any(|component| component == Component::ParentDir);
+        if !self.allowed_target_roots.is_empty()
+            && (climbs || !self.allowed_target_roots.iter().any(|root| target_path.starts_with(root)))
+        {
+            return Err(PolicyViolation {
+                key: "allowed_target_roots",
+                details: format!("{} is outside the allowed target roots", target_path.display()),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn expand_home(path: &str, home: &Path) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(relative) => home.join(relative),
+        None if path == "~" => home.to_path_buf(),
+        None => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths() -> WrappyPaths {
+        WrappyPaths {
+            home: PathBuf::from("/home/user"),
+            config_dir: PathBuf::from("/home/user/.config/wrappy"),
+            data_dir: PathBuf::from("/home/user/.local/share/wrappy"),
+            cache_dir: PathBuf::from("/home/user/.cache/wrappy"),
+            bin_dir: PathBuf::from("/home/user/.local/bin"),
+        }
+    }
+
+    fn policy(forbid_types: &[&str], allowed_target_roots: &[&str]) -> BindingPolicy {
+        let config = BindingsPolicyConfig {
+            forbid_types: forbid_types.iter().map(|value| value.to_string()).collect(),
+            allowed_target_roots: allowed_target_roots.iter().map(|value| value.to_string()).collect(),
+            ..BindingsPolicyConfig::default()
+        };
+        BindingPolicy::from_config(&config, &paths()).unwrap()
+    }
+
+    /// Forbidden types, allowed roots, binding type, target and the violated key if any.
+    type Case = (&'static [&'static str], &'static [&'static str], BindingType, &'static str, Option<&'static str>);
+
+    #[test]
+    fn evaluate_follows_the_forbidden_types_and_target_roots() {
+        let cases: &[Case] = &[
+            (&[], &[], BindingType::Copy, "/etc/anything", None),
+            (&["copy"], &[], BindingType::Copy, "/home/user/.local/bin/tool", Some("forbid_types")),
+            (&["copy"], &[], BindingType::Symlink, "/home/user/.local/bin/tool", None),
+            (&["copy"], &["~/.local"], BindingType::Copy, "/etc/tool", Some("forbid_types")),
+            (&[], &["~/.local", "~/.config"], BindingType::Symlink, "/home/user/.config/app", None),
+            (&[], &["~/.local", "~/.config"], BindingType::Wrapper, "/home/user/.local/bin/tool", None),
+            (&[], &["~/.local"], BindingType::Symlink, "/home/user/.localized/app", Some("allowed_target_roots")),
+            (&[], &["~/.local"], BindingType::Symlink, "/home/user/.ssh/config", Some("allowed_target_roots")),
+            (&[], &["~/.local"], BindingType::Symlink, "/home/user/.local/../.ssh/config", Some("allowed_target_roots")),
+            (&[], &["~"], BindingType::Symlink, "/home/user/.bashrc", None),
+            (&[], &["/opt/tools"], BindingType::Symlink, "/opt/tools/bin/tool", None),
+        ];
+
+        for (forbid, roots, binding_type, target, expected) in cases {
+            let result = policy(forbid, roots).evaluate(binding_type, Path::new(target));
+
+            assert_eq!(result.err().map(|violation| violation.key), *expected, "{:?} {:?} {} {}", forbid, roots, binding_type, target);
+        }
+    }
+
+    #[test]
+    fn unknown_type_names_are_reported_with_their_key() {
+        let config = BindingsPolicyConfig {
+            default_type: Some("hardlink".to_string()),
+            ..BindingsPolicyConfig::default()
+        };
+
+        let error = BindingPolicy::from_config(&config, &paths()).unwrap_err().to_string();
+
+        assert!(error.contains("bindings.default_type") && error.contains("hardlink"), "{}", error);
+    }
+
+    #[test]
+    fn defaults_fill_only_what_the_manifest_left_open() {
+        let mut bindings: BindingsConfig = serde_json::from_value(serde_json::json!({
+            "executables": [{ "source": "bin/a", "target": "a" }, { "source": "bin/b", "target": "b", "binding_type": "wrapper" }],
+            "configs": [{ "source": "etc", "target": "~/.config/app" }],
+        }))
+        .unwrap();
+        let policy = BindingPolicy {
+            default_type: Some(BindingType::Symlink),
+            always_backup: true,
+            ..BindingPolicy::default()
+        };
+
+        policy.apply_defaults(&mut bindings);
+
+        assert_eq!(bindings.executables[0].binding_type, Some(BindingType::Symlink));
+        assert_eq!(bindings.executables[1].binding_type, Some(BindingType::Wrapper));
+        assert_eq!(bindings.configs[0].binding_type, Some(BindingType::Symlink));
+        assert!(bindings.configs[0].backup_existing);
+    }
+}
@@ -0,0 +1,265 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::features::bindings::{BindingManager, BindingType, WrapperGenerator};
+use crate::features::container::ContainerService;
+use crate::features::query;
+use crate::shared::error::ContainerResult;
+use crate::shared::host;
+
+/// Output formats of `bindings precedence`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PrecedenceFormat {
+    Text,
+    Json,
+}
+
+/// One installed container version whose manifest binds an executable under a command name.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandProvider {
+    pub container: String,
+    pub version: String,
+    pub binding_type: BindingType,
+    pub source_path: PathBuf,
+    pub target_path: PathBuf,
+    /// Whether the file at `target_path` currently belongs to this provider
+    pub active: bool,
+}
+
+/// How the shell resolves one command name wrappy manages.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandPrecedence {
+    pub command: String,
+    pub providers: Vec<CommandProvider>,
+    /// Every executable of that name along PATH, in lookup order
+    pub path_matches: Vec<PathBuf>,
+    /// The file the shell runs, the first of `path_matches`
+    pub winner: Option<PathBuf>,
+    /// Active provider owning the winning file, if wrappy owns it
+    pub winning_container: Option<String>,
+    /// An active binding exists but a file earlier in PATH is run instead
+    pub shadowed: bool,
+    /// Active bindings whose directory is not on PATH at all
+    pub unreachable: Vec<PathBuf>,
+}
+
+/// Aggregates every command name wrappy binds across the store and the PATH.
+pub struct PrecedenceService;
+
+impl PrecedenceService {
+    /// Providers of all installed containers, with the current PATH.
+    pub fn current() -> ContainerResult<Vec<CommandPrecedence>> {
+        let providers = Self::installed_providers()?;
+        let search_path = env::var_os("PATH").unwrap_or_default();
+        Ok(Self::analyze(providers, &search_path))
+    }
+
+    /// Groups providers by command name and resolves each name against `search_path`.
+    /// Works on the given inputs only, so it can be checked against a synthetic PATH.
+    pub fn analyze(providers: Vec<CommandProvider>, search_path: &OsStr) -> Vec<CommandPrecedence> {
+        let mut by_command: BTreeMap<String, Vec<CommandProvider>> = BTreeMap::new();
+        for provider in providers {
+            if let Some(command) = provider.target_path.file_name().and_then(|name| name.to_str()) {
+                by_command.entry(command.to_string()).or_default().push(provider);
+            }
+        }
+
+        let path_dirs: Vec<PathBuf> = env::split_paths(search_path).collect();
+        by_command
+            .into_iter()
+            .map(|(command, providers)| {
+                let path_matches: Vec<PathBuf> = path_dirs
+                    .iter()
+                    .map(|dir| dir.join(&command))
+                    .filter(|candidate| host::is_executable(candidate))
+                    .collect();
+                let winner = path_matches.first().cloned();
+
+                let active: Vec<&CommandProvider> = providers.iter().filter(|provider| provider.active).collect();
+                let winning_container = active
+                    .iter()
+                    .find(|provider| Some(&provider.target_path) == winner.as_ref())
+                    .map(|provider| provider.container.clone());
+                let shadowed = winning_container.is_none()
+                    && active.iter().any(|provider| path_matches.contains(&provider.target_path));
+                let unreachable = active
+                    .iter()
+                    .filter(|provider| {
+                        provider
+                            .target_path
+                            .parent()
+                            .is_some_and(|dir| !path_dirs.iter().any(|entry| entry == dir))
+                    })
+                    .map(|provider| provider.target_path.clone())
+                    .collect();
+
+                CommandPrecedence {
+                    command,
+                    providers,
+                    path_matches,
+                    winner,
+                    winning_container,
+                    shadowed,
+                    unreachable,
+                }
+            })
+            .collect()
+    }
+
+    /// Every executable binding of every installed container version.
+    fn installed_providers() -> ContainerResult<Vec<CommandProvider>> {
+        let manager = BindingManager::open()?;
+        let mut providers = Vec::new();
+
+        for summary in query::installed_containers() {
+            let Ok(container) = ContainerService::load_from_directory(&summary.path) else {
+                continue;
+            };
+            for executable in &container.manifest.bindings.executables {
+                let Ok(target_path) = manager.executable_target_path(&container, executable) else {
+                    continue;
+                };
+                let source_path = container.path.join(&executable.source);
+                let binding_type = executable.binding_type();
+                let active = Self::owns_target(&binding_type, &source_path, &target_path);
+
+                providers.push(CommandProvider {
+                    container: summary.name.clone(),
+                    version: summary.version.to_string(),
+                    binding_type,
+                    source_path,
+                    target_path,
+                    active,
+                });
+            }
+        }
+        Ok(providers)
+    }
+
+    /// Several containers may bind the same name, so a wrapper only counts for the
+    /// container its header names and a symlink only for the file it points to.
+    fn owns_target(binding_type: &BindingType, source_path: &std::path::Path, target_path: &std::path::Path) -> bool {
+        match binding_type {
+            BindingType::Wrapper => WrapperGenerator::read_metadata(target_path)
+                .is_some_and(|metadata| metadata.executable_path == source_path),
+            BindingType::Symlink => fs::read_link(target_path).is_ok_and(|link| link == source_path),
+            BindingType::Copy => BindingManager::is_binding_active(target_path, binding_type, source_path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::write_executable;
+    use std::ffi::OsString;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    /// `wrappy`, `early` and `late` bin directories with a `node` in each; only `wrappy/node` is a binding.
+    struct Fixture {
+        _root: TempDir,
+        wrappy: PathBuf,
+        early: PathBuf,
+        late: PathBuf,
+    }
+
+    fn fixture() -> Fixture {
+        let root = TempDir::new().unwrap();
+        let dir = |name: &str| {
+            let dir = root.path().join(name);
+            write_executable(&dir.join("node"), "#!/bin/sh\n");
+            dir
+        };
+        Fixture {
+            wrappy: dir("wrappy"),
+            early: dir("early"),
+            late: dir("late"),
+            _root: root,
+        }
+    }
+
+    fn provider(container: &str, target_path: PathBuf, active: bool) -> CommandProvider {
+        CommandProvider {
+            container: container.to_string(),
+            version: "1.0.0".to_string(),
+            binding_type: BindingType::Wrapper,
+            source_path: PathBuf::from(format!("/store/{}/bin/node", container)),
+            target_path,
+            active,
+        }
+    }
+
+    fn search_path(dirs: &[&Path]) -> OsString {
+        env::join_paths(dirs).unwrap()
+    }
+
+    #[test]
+    fn the_wrapper_wins_when_its_directory_comes_first() {
+        let fixture = fixture();
+        let providers = vec![provider("node", fixture.wrappy.join("node"), true)];
+
+        let analyzed = PrecedenceService::analyze(providers, &search_path(&[&fixture.wrappy, &fixture.late]));
+
+        assert_eq!(analyzed.len(), 1);
+        assert_eq!(analyzed[0].command, "node");
+        assert_eq!(analyzed[0].path_matches, [fixture.wrappy.join("node"), fixture.late.join("node")]);
+        assert_eq!(analyzed[0].winning_container.as_deref(), Some("node"));
+        assert!(!analyzed[0].shadowed);
+        assert!(analyzed[0].unreachable.is_empty());
+    }
+
+    #[test]
+    fn an_earlier_binary_shadows_the_wrapper() {
+        let fixture = fixture();
+        let providers = vec![provider("node", fixture.wrappy.join("node"), true)];
+
+        let analyzed = PrecedenceService::analyze(providers, &search_path(&[&fixture.early, &fixture.wrappy, &fixture.late]));
+
+        assert_eq!(analyzed[0].winner, Some(fixture.early.join("node")));
+        assert_eq!(analyzed[0].winning_container, None);
+        assert!(analyzed[0].shadowed);
+    }
+
+    #[test]
+    fn a_binding_outside_path_is_unreachable_rather_than_shadowed() {
+        let fixture = fixture();
+        let providers = vec![provider("node", fixture.wrappy.join("node"), true)];
+
+        let analyzed = PrecedenceService::analyze(providers, &search_path(&[&fixture.early]));
+
+        assert_eq!(analyzed[0].winner, Some(fixture.early.join("node")));
+        assert!(!analyzed[0].shadowed);
+        assert_eq!(analyzed[0].unreachable, [fixture.wrappy.join("node")]);
+    }
+
+    #[test]
+    fn only_the_active_provider_of_a_shared_name_wins() {
+        let fixture = fixture();
+        let providers = vec![
+            provider("node18", fixture.wrappy.join("node"), false),
+            provider("node20", fixture.wrappy.join("node"), true),
+        ];
+
+        let analyzed = PrecedenceService::analyze(providers, &search_path(&[&fixture.wrappy]));
+
+        assert_eq!(analyzed[0].providers.len(), 2);
+        assert_eq!(analyzed[0].winning_container.as_deref(), Some("node20"));
+    }
+
+    #[test]
+    fn files_that_are_not_executable_are_skipped() {
+        let fixture = fixture();
+        fs::set_permissions(fixture.early.join("node"), std::os::unix::fs::PermissionsExt::from_mode(0o644)).unwrap();
+        let providers = vec![provider("node", fixture.wrappy.join("node"), true)];
+
+        let analyzed = PrecedenceService::analyze(providers, &search_path(&[&fixture.early, &fixture.wrappy]));
+
+        assert_eq!(analyzed[0].path_matches, [fixture.wrappy.join("node")]);
+        assert_eq!(analyzed[0].winning_container.as_deref(), Some("node"));
+    }
+}
@@ -0,0 +1,502 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::CString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::features::bindings::BindingType;
+use crate::shared::error::ContainerError;
+use crate::shared::filesystem;
+use crate::shared::traversal::Walk;
+
+/// Longest file name most Linux filesystems accept.
+const NAME_MAX: usize = 255;
+
+/// Free space of the filesystem holding a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreeSpace {
+    /// Device id; paths with the same one share the space
+    pub device: u64,
+    pub available: u64,
+}
+
+/// What copying a binding source needs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceUsage {
+    pub bytes: u64,
+    /// Longest path below the source, in bytes; 0 for a single file
+    pub longest_relative: usize,
+}
+
+/// Filesystem queries the pre-flight checks make. `HostFs` asks the host; another
+/// implementation can inject failures to drive a single check.
+pub trait PreflightFs {
+    /// Nearest ancestor of `path` (itself included) that exists.
+    fn existing_ancestor(&self, path: &Path) -> PathBuf;
+    fn is_read_only(&self, path: &Path) -> io::Result<bool>;
+    fn is_writable(&self, path: &Path) -> bool;
+    /// Fails when `path` is missing or cannot be opened (a directory: listed).
+    fn check_readable(&self, path: &Path) -> io::Result<()>;
+    fn free_space(&self, path: &Path) -> io::Result<FreeSpace>;
+    /// Fails on the first unreadable entry below `path`.
+    fn usage(&self, path: &Path) -> io::Result<SourceUsage>;
+}
+
+/// The real filesystem.
+pub struct HostFs;
+
+impl PreflightFs for HostFs {
+    fn existing_ancestor(&self, path: &Path) -> PathBuf {
+        filesystem::nearest_existing_ancestor(path)
+    }
+
+    fn is_read_only(&self, path: &Path) -> io::Result<bool> {
+        Ok(filesystem::statvfs(path)?.f_flag & libc::ST_RDONLY != 0)
+    }
+
+    fn is_writable(&self, path: &Path) -> bool {
+        let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+            return false;
+        };
+        // SAFETY: access only reads the NUL-terminated path that outlives the call.
+        unsafe { libc::access(c_path.as_ptr(), libc::W_OK) == 0 }
+    }
+
+    fn check_readable(&self, path: &Path) -> io::Result<()> {
+        match fs::metadata(path)?.is_dir() {
+            true => fs::read_dir(path).map(|_| ()),
+            false => fs::File::open(path).map(|_| ()),
+        }
+    }
+
+    fn free_space(&self, path: &Path) -> io::Result<FreeSpace> {
+        let stats = filesystem::statvfs(path)?;
+        Ok(FreeSpace {
+            device: fs::metadata(path)?.dev(),
+            available: stats.f_bavail as u64 * stats.f_frsize as u64,
+        })
+    }
+
+    fn usage(&self, path: &Path) -> io::Result<SourceUsage> {
+        let mut usage = SourceUsage::default();
+        for entry in Walk::new(path).follow_root_link() {
+            let entry = entry.map_err(|error| match error {
+                ContainerError::IoError { source, .. } => source,
+                other => io::Error::other(other.to_string()),
+            })?;
+            if entry.file_type.is_dir() {
+                continue;
+            }
+            if entry.file_type.is_file() {
+                fs::File::open(&entry.path)?;
+            }
+            usage.bytes += fs::symlink_metadata(&entry.path)?.len();
+            let relative = entry.path.strip_prefix(path).unwrap_or(&entry.path);
+            usage.longest_relative = usage.longest_relative.max(relative.as_os_str().len());
+        }
+        Ok(usage)
+    }
+}
+
+/// One binding enable is about to create.
+#[derive(Debug, Clone)]
+pub struct PlannedBinding {
+    /// How the binding is named in messages, e.g. "config 'nvim'"
+    pub description: String,
+    pub source: PathBuf,
+    /// Where the binding ends up; for wrappers the file in the bin directory
+    pub target: PathBuf,
+    pub binding_type: BindingType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PreflightCheck {
+    SourceReadable,
+    PathLength,
+    ReadOnlyMount,
+    TargetWritable,
+    FreeSpace,
+}
+
+impl fmt::Display for PreflightCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::SourceReadable => "source",
+            Self::PathLength => "path length",
+            Self::ReadOnlyMount => "read-only",
+            Self::TargetWritable => "not writable",
+            Self::FreeSpace => "free space",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// A reason the plan would fail partway.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreflightProblem {
+    pub check: PreflightCheck,
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for PreflightProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.check, self.path.display(), self.message)
+    }
+}
+
+/// Checks that predict whether enabling a set of bindings can succeed, run before
+/// anything is created so a predictable failure leaves no partial work behind.
+/// Every check looks at the whole plan and all problems are reported together.
+pub struct Preflight<'a> {
+    fs: &'a dyn PreflightFs,
+    /// Copy sources are walked once even though several checks need their usage
+    usage: RefCell<HashMap<PathBuf, Result<SourceUsage, String>>>,
+}
+
+impl<'a> Preflight<'a> {
+    pub fn new(fs: &'a dyn PreflightFs) -> Self {
+        Self {
+            fs,
+            usage: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Problems of every check, sorted by check and path; empty when the plan is feasible.
+    pub fn run(&self, plan: &[PlannedBinding]) -> Vec<PreflightProblem> {
+        let mut problems = self.check_sources(plan);
+        problems.extend(self.check_path_lengths(plan));
+        problems.extend(self.check_target_directories(plan));
+        problems.extend(self.check_free_space(plan));
+        problems.sort_by(|a, b| (a.check, &a.path).cmp(&(b.check, &b.path)));
+        problems
+    }
+
+    /// Sources must exist and be readable; copies also need everything below them readable.
+    pub fn check_sources(&self, plan: &[PlannedBinding]) -> Vec<PreflightProblem> {
+        let mut problems = Vec::new();
+        for binding in plan {
+            let result = match binding.binding_type {
+                BindingType::Copy => self.usage(&binding.source).map(|_| ()),
+                _ => self.fs.check_readable(&binding.source).map_err(|error| error.to_string()),
+            };
+            if let Err(error) = result {
+                problems.push(PreflightProblem {
+                    check: PreflightCheck::SourceReadable,
+                    path: binding.source.clone(),
+                    message: format!("{} cannot be read: {}", binding.description, error),
+                });
+            }
+        }
+        problems
+    }
+
+    /// Targets, and for copied directories the deepest copied path, must fit the
+    /// kernel's limits on path and file name length.
+    pub fn check_path_lengths(&self, plan: &[PlannedBinding]) -> Vec<PreflightProblem> {
+        let path_max = libc::PATH_MAX as usize;
+        let mut problems = Vec::new();
+        for binding in plan {
+            let nested = match binding.binding_type {
+                BindingType::Copy => self.usage(&binding.source).map(|usage| usage.longest_relative).unwrap_or(0),
+                _ => 0,
+            };
+            let length = binding.target.as_os_str().len() + if nested > 0 { nested + 1 } else { 0 };
+            let long_name = binding
+                .target
+                .iter()
+                .find(|component| component.len() > NAME_MAX)
+                .map(|component| component.to_string_lossy().into_owned());
+
+            let message = match (length >= path_max, long_name) {
+                (_, Some(name)) => format!("file name '{}' is longer than {} bytes", name, NAME_MAX),
+                (true, None) => format!("{} needs paths of {} bytes, the limit is {}", binding.description, length, path_max - 1),
+                (false, None) => continue,
+            };
+            problems.push(PreflightProblem {
+                check: PreflightCheck::PathLength,
+                path: binding.target.clone(),
+                message,
+            });
+        }
+        problems
+    }
+
+    /// The directory each target is created in must be on a writable mount and writable
+    /// by the user; for wrappers that is the bin directory. Checked at the nearest
+    /// existing ancestor, since missing parents are created.
+    pub fn check_target_directories(&self, plan: &[PlannedBinding]) -> Vec<PreflightProblem> {
+        let mut directories: BTreeMap<PathBuf, &PlannedBinding> = BTreeMap::new();
+        for binding in plan {
+            if let Some(parent) = binding.target.parent() {
+                directories.entry(self.fs.existing_ancestor(parent)).or_insert(binding);
+            }
+        }
+
+        let mut problems = Vec::new();
+        for (directory, binding) in directories {
+            let kind = match binding.binding_type {
+                BindingType::Wrapper => "wrapper directory",
+                _ => "target directory",
+            };
+            let problem = match self.fs.is_read_only(&directory) {
+                Ok(true) => Some((
+                    PreflightCheck::ReadOnlyMount,
+                    format!("{} of {} is on a read-only mount", kind, binding.description),
+                )),
+                Ok(false) if !self.fs.is_writable(&directory) => Some((
+                    PreflightCheck::TargetWritable,
+                    format!("{} of {} is not writable by this user", kind, binding.description),
+                )),
+                Ok(false) => None,
+                Err(error) => Some((
+                    PreflightCheck::TargetWritable,
+                    format!("cannot inspect the {} of {}: {}", kind, binding.description, error),
+                )),
+            };
+            if let Some((check, message)) = problem {
+                problems.push(PreflightProblem { check, path: directory, message });
+            }
+        }
+        problems
+    }
+
+    /// Copies on one filesystem must fit its free space together.
+    pub fn check_free_space(&self, plan: &[PlannedBinding]) -> Vec<PreflightProblem> {
+        // Device -> (available, needed, directory reported, copies)
+        let mut devices: BTreeMap<u64, (u64, u64, PathBuf, Vec<&str>)> = BTreeMap::new();
+        for binding in plan.iter().filter(|binding| binding.binding_type == BindingType::Copy) {
+            let Ok(usage) = self.usage(&binding.source) else {
+                // Reported by check_sources
+                continue;
+            };
+            let directory = self.fs.existing_ancestor(binding.target.parent().unwrap_or(&binding.target));
+            let Ok(space) = self.fs.free_space(&directory) else {
+                continue;
+            };
+            let entry = devices
+                .entry(space.device)
+                .or_insert_with(|| (space.available, 0, directory, Vec::new()));
+            entry.1 += usage.bytes;
+            entry.3.push(&binding.description);
+        }
+
+        devices
+            .into_values()
+            .filter(|(available, needed, _, _)| needed > available)
+            .map(|(available, needed, directory, copies)| PreflightProblem {
+                check: PreflightCheck::FreeSpace,
+                message: format!(
+                    "copying {} needs {}, only {} is free",
+                    copies.join(", "),
+                    filesystem::format_size(needed),
+                    filesystem::format_size(available)
+                ),
+                path: directory,
+            })
+            .collect()
+    }
+
+    fn usage(&self, source: &Path) -> Result<SourceUsage, String> {
+        self.usage
+            .borrow_mut()
+            .entry(source.to_path_buf())
+            .or_insert_with(|| self.fs.usage(source).map_err(|error| error.to_string()))
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::collections::HashSet;
+
+    /// Answers from tables; every path not listed is readable, writable and on device 1.
+    #[derive(Default)]
+    struct FakeFs {
+        existing: HashSet<PathBuf>,
+        read_only: HashSet<PathBuf>,
+        not_writable: HashSet<PathBuf>,
+        unreadable: HashSet<PathBuf>,
+        /// Directory -> (device, bytes available)
+        space: HashMap<PathBuf, (u64, u64)>,
+        sizes: HashMap<PathBuf, SourceUsage>,
+        usage_calls: Cell<usize>,
+    }
+
+    impl PreflightFs for FakeFs {
+        fn existing_ancestor(&self, path: &Path) -> PathBuf {
+            path.ancestors()
+                .find(|ancestor| self.existing.contains(*ancestor))
+                .unwrap_or(Path::new("/"))
+                .to_path_buf()
+        }
+
+        fn is_read_only(&self, path: &Path) -> io::Result<bool> {
+            Ok(self.read_only.contains(path))
+        }
+
+        fn is_writable(&self, path: &Path) -> bool {
+            !self.not_writable.contains(path)
+        }
+
+        fn check_readable(&self, path: &Path) -> io::Result<()> {
+            match self.unreadable.contains(path) {
+                true => Err(io::Error::from(io::ErrorKind::PermissionDenied)),
+                false => Ok(()),
+            }
+        }
+
+        fn free_space(&self, path: &Path) -> io::Result<FreeSpace> {
+            let (device, available) = self.space.get(path).copied().unwrap_or((1, u64::MAX));
+            Ok(FreeSpace { device, available })
+        }
+
+        fn usage(&self, path: &Path) -> io::Result<SourceUsage> {
+            self.usage_calls.set(self.usage_calls.get() + 1);
+            self.check_readable(path)?;
+            Ok(self.sizes.get(path).copied().unwrap_or_default())
+        }
+    }
+
+    fn planned(binding_type: BindingType, source: &str, target: &str) -> PlannedBinding {
+        PlannedBinding {
+            description: format!("binding '{}'", target.rsplit('/').next().unwrap()),
+            source: PathBuf::from(source),
+            target: PathBuf::from(target),
+            binding_type,
+        }
+    }
+
+    fn checks(problems: &[PreflightProblem]) -> Vec<(PreflightCheck, &Path)> {
+        problems.iter().map(|problem| (problem.check, problem.path.as_path())).collect()
+    }
+
+    #[test]
+    fn unreadable_sources_are_reported_for_links_and_copies() {
+        let fs = FakeFs {
+            unreadable: HashSet::from([PathBuf::from("/c/bin/locked"), PathBuf::from("/c/data")]),
+            ..FakeFs::default()
+        };
+        let plan = [
+            planned(BindingType::Wrapper, "/c/bin/locked", "/home/u/.local/bin/locked"),
+            planned(BindingType::Copy, "/c/data", "/home/u/.local/share/data"),
+            planned(BindingType::Symlink, "/c/config", "/home/u/.config/app"),
+        ];
+
+        let problems = Preflight::new(&fs).check_sources(&plan);
+
+        assert_eq!(
+            checks(&problems),
+            [(PreflightCheck::SourceReadable, Path::new("/c/bin/locked")), (PreflightCheck::SourceReadable, Path::new("/c/data"))]
+        );
+        assert!(problems[0].message.contains("binding 'locked' cannot be read"), "{}", problems[0]);
+    }
+
+    #[test]
+    fn over_long_targets_and_file_names_are_reported() {
+        let fs = FakeFs {
+            sizes: HashMap::from([(
+                PathBuf::from("/c/deep"),
+                SourceUsage { bytes: 1, longest_relative: libc::PATH_MAX as usize },
+            )]),
+            ..FakeFs::default()
+        };
+        let long_name = format!("/home/u/.config/{}", "n".repeat(NAME_MAX + 1));
+        let plan = [
+            planned(BindingType::Symlink, "/c/config", &long_name),
+            planned(BindingType::Copy, "/c/deep", "/home/u/.local/share/deep"),
+            planned(BindingType::Symlink, "/c/fine", &format!("/home/u/.config/{}", "n".repeat(NAME_MAX))),
+        ];
+
+        let problems = Preflight::new(&fs).check_path_lengths(&plan);
+
+        assert_eq!(checks(&problems).len(), 2);
+        assert!(problems[0].message.contains("longer than 255 bytes"), "{}", problems[0]);
+        assert!(problems[1].message.contains("the limit is"), "{}", problems[1]);
+    }
+
+    #[test]
+    fn read_only_and_unwritable_target_directories_are_checked_at_the_nearest_existing_ancestor() {
+        let fs = FakeFs {
+            existing: HashSet::from([PathBuf::from("/ro"), PathBuf::from("/locked"), PathBuf::from("/ok")]),
+            read_only: HashSet::from([PathBuf::from("/ro")]),
+            not_writable: HashSet::from([PathBuf::from("/locked")]),
+            ..FakeFs::default()
+        };
+        let plan = [
+            planned(BindingType::Wrapper, "/c/bin/a", "/ro/bin/a"),
+            planned(BindingType::Wrapper, "/c/bin/b", "/ro/bin/b"),
+            planned(BindingType::Symlink, "/c/config", "/locked/missing/app"),
+            planned(BindingType::Symlink, "/c/other", "/ok/app"),
+        ];
+
+        let problems = Preflight::new(&fs).check_target_directories(&plan);
+
+        // One problem per directory, however many bindings land in it
+        assert_eq!(
+            checks(&problems),
+            [(PreflightCheck::TargetWritable, Path::new("/locked")), (PreflightCheck::ReadOnlyMount, Path::new("/ro"))]
+        );
+        assert!(problems[1].message.contains("wrapper directory"), "{}", problems[1]);
+        assert!(problems[0].message.contains("target directory"), "{}", problems[0]);
+    }
+
+    #[test]
+    fn copies_to_one_filesystem_must_fit_together() {
+        let fs = FakeFs {
+            existing: HashSet::from([PathBuf::from("/small"), PathBuf::from("/big")]),
+            space: HashMap::from([(PathBuf::from("/small"), (7, 150)), (PathBuf::from("/big"), (8, 10_000))]),
+            sizes: HashMap::from([
+                (PathBuf::from("/c/a"), SourceUsage { bytes: 100, longest_relative: 0 }),
+                (PathBuf::from("/c/b"), SourceUsage { bytes: 100, longest_relative: 0 }),
+            ]),
+            ..FakeFs::default()
+        };
+        let plan = [
+            planned(BindingType::Copy, "/c/a", "/small/a"),
+            planned(BindingType::Copy, "/c/b", "/small/b"),
+            planned(BindingType::Copy, "/c/a", "/big/a"),
+            planned(BindingType::Symlink, "/c/b", "/small/link"),
+        ];
+
+        let problems = Preflight::new(&fs).check_free_space(&plan);
+
+        assert_eq!(checks(&problems), [(PreflightCheck::FreeSpace, Path::new("/small"))]);
+        assert!(problems[0].message.contains("binding 'a', binding 'b'"), "{}", problems[0]);
+    }
+
+    #[test]
+    fn run_reports_every_problem_sorted_and_walks_each_copy_source_once() {
+        let fs = FakeFs {
+            existing: HashSet::from([PathBuf::from("/ro"), PathBuf::from("/small")]),
+            read_only: HashSet::from([PathBuf::from("/ro")]),
+            unreadable: HashSet::from([PathBuf::from("/c/locked")]),
+            space: HashMap::from([(PathBuf::from("/small"), (7, 10))]),
+            sizes: HashMap::from([(PathBuf::from("/c/big"), SourceUsage { bytes: 100, longest_relative: 3 })]),
+            ..FakeFs::default()
+        };
+        let plan = [
+            planned(BindingType::Copy, "/c/big", "/small/big"),
+            planned(BindingType::Symlink, "/c/locked", "/ro/locked"),
+        ];
+
+        let problems = Preflight::new(&fs).run(&plan);
+
+        assert_eq!(
+            checks(&problems),
+            [
+                (PreflightCheck::SourceReadable, Path::new("/c/locked")),
+                (PreflightCheck::ReadOnlyMount, Path::new("/ro")),
+                (PreflightCheck::FreeSpace, Path::new("/small")),
+            ]
+        );
+        assert_eq!(fs.usage_calls.get(), 1);
+        assert!(Preflight::new(&FakeFs::default()).run(&plan).is_empty());
+    }
+}
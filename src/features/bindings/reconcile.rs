@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::features::bindings::{BindingManager, BindingRecord, BindingType, ExecutableBinding, WrapperGenerator};
+use crate::features::container::{Container, ContainerService};
+use crate::shared::error::{ContainerError, ContainerResult};
+
+/// How one name in the bin directory relates to wrappy's records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WrapperState {
+    /// Recorded, and the file is what enable would create
+    Consistent,
+    /// Recorded and wrappy-made, but the content differs from what enable would create
+    Modified,
+    /// Recorded, but nothing is on disk
+    Missing,
+    /// Recorded, but another tool's file occupies the name
+    Foreign,
+    /// A wrappy wrapper without a record, e.g. from a lost state file
+    Untracked,
+}
+
+impl WrapperState {
+    /// Modified and missing bindings can be recreated from their container.
+    pub fn is_repairable(&self) -> bool {
+        matches!(self, Self::Modified | Self::Missing)
+    }
+}
+
+impl fmt::Display for WrapperState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::Consistent => "consistent",
+            Self::Modified => "modified",
+            Self::Missing => "missing",
+            Self::Foreign => "foreign",
+            Self::Untracked => "untracked",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Reconciliation result for one name in the bin directory.
+#[derive(Debug, Clone)]
+pub struct WrapperReport {
+    pub name: String,
+    pub path: PathBuf,
+    pub container: Option<String>,
+    pub state: WrapperState,
+    /// What is wrong, or why it cannot be repaired
+    pub detail: Option<String>,
+    /// Container and binding the file would be recreated from
+    repair: Option<(Container, ExecutableBinding)>,
+}
+
+impl WrapperReport {
+    pub fn can_repair(&self) -> bool {
+        self.state.is_repairable() && self.repair.is_some()
+    }
+}
+
+/// Compares the bin directory with the binding state and the wrappers enable would
+/// generate today. Hand edits, deletions and other tools' scripts make the two diverge.
+pub struct ReconcileService;
+
+impl ReconcileService {
+    /// Classifies every recorded executable binding in the bin directories and every
+    /// wrappy wrapper found there, sorted by name. Unrelated files are left out.
+    pub fn scan(manager: &BindingManager) -> ContainerResult<Vec<WrapperReport>> {
+        let bin_dirs = manager.bin_dirs()?;
+        let mut recorded: BTreeMap<PathBuf, BindingRecord> = BTreeMap::new();
+        for record in manager.recorded_bindings()? {
+            let path = manager.installed_path(&record);
+            if path.parent().is_some_and(|parent| bin_dirs.iter().any(|bin_dir| bin_dir == parent)) {
+                recorded.insert(path, record);
+            }
+        }
+
+        let mut reports = Vec::new();
+        for (path, record) in &recorded {
+            reports.push(Self::check_recorded(manager, path, record));
+        }
+        let mut entries = Vec::new();
+        for bin_dir in &bin_dirs {
+            entries.extend(Self::bin_entries(bin_dir)?);
+        }
+        for path in entries {
+            if recorded.contains_key(&path) {
+                continue;
+            }
+            if let Some(metadata) = WrapperGenerator::read_metadata(&path) {
+                reports.push(WrapperReport {
+                    name: Self::file_name(&path),
+                    detail: Some(format!("runs {}", metadata.executable_path.display())),
+                    container: Some(metadata.container),
+                    path,
+                    state: WrapperState::Untracked,
+                    repair: None,
+                });
+            }
+        }
+
+        reports.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(reports)
+    }
+
+    /// Regenerates a modified wrapper or reinstalls a missing binding.
+    pub fn repair(manager: &BindingManager, report: &WrapperReport) -> ContainerResult<()> {
+        match (&report.repair, report.state.is_repairable()) {
+            (Some((container, executable)), true) => manager.reinstall_executable(container, executable).map(|_| ()),
+            _ => Err(ContainerError::InvalidPath {
+                path: report.path.clone(),
+                reason: format!("a {} binding cannot be repaired automatically", report.state),
+            }),
+        }
+    }
+
+    fn check_recorded(manager: &BindingManager, path: &Path, record: &BindingRecord) -> WrapperReport {
+        let repair = Self::find_binding(manager, path, record);
+        let expected = repair.as_ref().map(|(container, executable)| Self::expected(path, record, container, executable));
+
+        let (state, detail) = if path.symlink_metadata().is_err() {
+            (WrapperState::Missing, Some("recorded but not on disk".to_string()))
+        } else {
+            match &record.binding_type {
+                BindingType::Wrapper if WrapperGenerator::read_metadata(path).is_none() => {
+                    (WrapperState::Foreign, Some("not a wrappy wrapper; left alone".to_string()))
+                }
+                BindingType::Symlink if !BindingManager::is_binding_active(path, &record.binding_type, &record.source_path) => (
+                    WrapperState::Foreign,
+                    Some(format!("does not link to {}; left alone", record.source_path.display())),
+                ),
+                BindingType::Symlink => (WrapperState::Consistent, None),
+                BindingType::Wrapper | BindingType::Copy => match expected {
+                    Some(Ok(expected)) if fs::read(path).is_ok_and(|content| content == expected) => (WrapperState::Consistent, None),
+                    Some(Ok(_)) => (
+                        WrapperState::Modified,
+                        Some("differs from what enable would generate".to_string()),
+                    ),
+                    Some(Err(error)) => (WrapperState::Modified, Some(format!("cannot build the expected file: {}", error))),
+                    // Without its container the expected content is unknown
+                    None => (WrapperState::Modified, None),
+                },
+            }
+        };
+
+        let detail = match (&repair, state.is_repairable()) {
+            (None, true) => Some(format!(
+                "container '{}' is gone or no longer declares this binding; cannot repair",
+                record.container
+            )),
+            _ => detail,
+        };
+
+        WrapperReport {
+            name: Self::file_name(path),
+            path: path.to_path_buf(),
+            container: Some(record.container.clone()),
+            state,
+            detail,
+            repair,
+        }
+    }
+
+    /// Content enable would write: the generated wrapper, or the source itself for copies.
+    fn expected(path: &Path, record: &BindingRecord, container: &Container, executable: &ExecutableBinding) -> ContainerResult<Vec<u8>> {
+        match record.binding_type {
+            BindingType::Copy => fs::read(&record.source_path).map_err(|e| ContainerError::IoError {
+                path: record.source_path.clone(),
+                source: e,
+            }),
+            _ => WrapperGenerator::render_wrapper(
+                &Self::file_name(path),
+                container.name(),
+                &container.path,
+                executable,
+                &record.source_path,
+            )
+            .map(String::into_bytes),
+        }
+    }
+
+    /// The installed container the record's source belongs to and its binding for `path`,
+    /// with the bin directory the record was enabled into so a repair puts it back there.
+    fn find_binding(manager: &BindingManager, path: &Path, record: &BindingRecord) -> Option<(Container, ExecutableBinding)> {
+        let mut container = ContainerService::load_from_directory(Self::container_root(&record.source_path)?).ok()?;
+        if let Some(bin_dir) = &record.bin_dir {
+            container.manifest.bindings.bin_dir = Some(bin_dir.to_string_lossy().into_owned());
+        }
+        let executable = container
+            .manifest
+            .bindings
+            .executables
+            .iter()
+            .find(|executable| {
+                manager.executable_target_path(&container, executable).is_ok_and(|target| target == path)
+                    && container.path.join(&executable.source) == record.source_path
+            })?
+            .clone();
+        Some((container, executable))
+    }
+
+    /// Nearest ancestor of a binding source that holds a manifest.
+    fn container_root(source_path: &Path) -> Option<&Path> {
+        source_path.ancestors().skip(1).find(|ancestor| ancestor.join("manifest.json").is_file())
+    }
+
+    fn bin_entries(bin_dir: &Path) -> ContainerResult<Vec<PathBuf>> {
+        let Ok(entries) = fs::read_dir(bin_dir) else {
+            return Ok(Vec::new());
+        };
+        let mut paths = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| ContainerError::IoError {
+                path: bin_dir.to_path_buf(),
+                source: e,
+            })?;
+            if entry.file_type().is_ok_and(|file_type| file_type.is_file()) {
+                paths.push(entry.path());
+            }
+        }
+        Ok(paths)
+    }
+
+    fn file_name(path: &Path) -> String {
+        path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::setup::SetupService;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, write_executable, TempHome};
+
+    /// `app` installed in the store with its `app-tool` wrapper enabled; returns the wrapper path.
+    fn enabled(home: &TempHome) -> PathBuf {
+        SetupService::initialize(&home.paths()).unwrap();
+        let dir = home.paths().default_store_dir().join("app/1.0.0");
+        fixture_container_at(&dir, fixture_manifest("app", "1.0.0"));
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+        BindingManager::new().unwrap().install_bindings(&container).unwrap();
+        home.paths().bin_dir.join("app-tool")
+    }
+
+    fn scan() -> Vec<WrapperReport> {
+        ReconcileService::scan(&BindingManager::new().unwrap()).unwrap()
+    }
+
+    fn states(reports: &[WrapperReport]) -> Vec<(String, WrapperState)> {
+        reports.iter().map(|report| (report.name.clone(), report.state)).collect()
+    }
+
+    #[test]
+    fn a_freshly_enabled_wrapper_is_consistent_and_unrelated_files_are_left_out() {
+        let home = TempHome::enter();
+        let wrapper = enabled(&home);
+        write_executable(&wrapper.with_file_name("unrelated"), "#!/bin/sh\n");
+
+        assert_eq!(states(&scan()), [("app-tool".to_string(), WrapperState::Consistent)]);
+    }
+
+    #[test]
+    fn an_edited_wrapper_is_modified_and_repair_regenerates_it() {
+        let home = TempHome::enter();
+        let wrapper = enabled(&home);
+        let original = fs::read_to_string(&wrapper).unwrap();
+        fs::write(&wrapper, format!("{}echo edited\n", original)).unwrap();
+
+        let reports = scan();
+        assert_eq!(states(&reports), [("app-tool".to_string(), WrapperState::Modified)]);
+        assert!(reports[0].can_repair());
+        ReconcileService::repair(&BindingManager::new().unwrap(), &reports[0]).unwrap();
+
+        assert_eq!(fs::read_to_string(&wrapper).unwrap(), original);
+        assert_eq!(states(&scan()), [("app-tool".to_string(), WrapperState::Consistent)]);
+    }
+
+    #[test]
+    fn a_deleted_wrapper_is_missing_and_repair_reinstalls_it() {
+        let home = TempHome::enter();
+        let wrapper = enabled(&home);
+        fs::remove_file(&wrapper).unwrap();
+
+        let reports = scan();
+        assert_eq!(states(&reports), [("app-tool".to_string(), WrapperState::Missing)]);
+        ReconcileService::repair(&BindingManager::new().unwrap(), &reports[0]).unwrap();
+
+        assert!(wrapper.is_file());
+        assert_eq!(states(&scan()), [("app-tool".to_string(), WrapperState::Consistent)]);
+    }
+
+    #[test]
+    fn another_tools_file_under_a_recorded_name_is_foreign_and_left_alone() {
+        let home = TempHome::enter();
+        let wrapper = enabled(&home);
+        write_executable(&wrapper, "#!/bin/sh\necho other tool\n");
+
+        let reports = scan();
+        assert_eq!(states(&reports), [("app-tool".to_string(), WrapperState::Foreign)]);
+        assert!(!reports[0].can_repair());
+        assert!(ReconcileService::repair(&BindingManager::new().unwrap(), &reports[0]).is_err());
+
+        assert_eq!(fs::read_to_string(&wrapper).unwrap(), "#!/bin/sh\necho other tool\n");
+    }
+
+    #[test]
+    fn a_wrappy_wrapper_without_a_record_is_untracked() {
+        let home = TempHome::enter();
+        let wrapper = enabled(&home);
+        fs::copy(&wrapper, wrapper.with_file_name("copied-tool")).unwrap();
+
+        let reports = scan();
+
+        assert_eq!(
+            states(&reports),
+            [("app-tool".to_string(), WrapperState::Consistent), ("copied-tool".to_string(), WrapperState::Untracked)]
+        );
+        assert_eq!(reports[1].container.as_deref(), Some("app"));
+    }
+
+    #[test]
+    fn a_missing_wrapper_of_a_removed_container_cannot_be_repaired() {
+        let home = TempHome::enter();
+        let wrapper = enabled(&home);
+        fs::remove_file(&wrapper).unwrap();
+        fs::remove_dir_all(home.paths().default_store_dir().join("app")).unwrap();
+
+        let reports = scan();
+
+        assert_eq!(states(&reports), [("app-tool".to_string(), WrapperState::Missing)]);
+        assert!(!reports[0].can_repair());
+        assert!(reports[0].detail.as_deref().unwrap().contains("cannot repair"));
+    }
+}
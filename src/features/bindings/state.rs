@@ -0,0 +1,349 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::features::bindings::{ActiveBinding, BindingCategory, BindingType};
+use crate::shared::clock;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::lock_file::LockFile;
+use crate::shared::paths::WrappyPaths;
+
+/// Format version written by this wrappy. Bump it together with a new
+/// `upgrade_from` arm whenever a change to the records breaks older files.
+pub const BINDING_STATE_VERSION: u32 = 1;
+
+/// One binding wrappy created on the host.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BindingRecord {
+    pub container: String,
+    pub source_path: PathBuf,
+    pub target_path: PathBuf,
+    pub binding_type: BindingType,
+    /// Manifest section of the binding; unset in records written before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<BindingCategory>,
+    /// Bin directory of a container with a `bin_dir` override or enabled with `--bin-dir`;
+    /// unset for the configured bin directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bin_dir: Option<PathBuf>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&ActiveBinding> for BindingRecord {
+    fn from(binding: &ActiveBinding) -> Self {
+        Self {
+            container: binding.container_name.clone(),
+            source_path: binding.source_path.clone(),
+            target_path: binding.target_path.clone(),
+            binding_type: binding.binding_type.clone(),
+            category: Some(binding.category),
+            bin_dir: binding.bin_dir.clone(),
+            created_at: binding.created_at.into(),
+        }
+    }
+}
+
+/// Contents of `bindings.json` in the current format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BindingState {
+    pub version: u32,
+    #[serde(default)]
+    pub bindings: Vec<BindingRecord>,
+    /// Records this wrappy cannot read, such as binding types added by a newer
+    /// wrappy; written back unchanged after `bindings`
+    #[serde(skip)]
+    pub unknown_bindings: Vec<Value>,
+    /// Top-level fields this wrappy does not know, written back unchanged
+    #[serde(flatten)]
+    pub unknown: BTreeMap<String, Value>,
+}
+
+impl Default for BindingState {
+    fn default() -> Self {
+        Self {
+            version: BINDING_STATE_VERSION,
+            bindings: Vec::new(),
+            unknown_bindings: Vec::new(),
+            unknown: BTreeMap::new(),
+        }
+    }
+}
+
+/// Record of the bindings wrappy created, kept so they can be listed without
+/// scanning the host. Older formats are upgraded on load and written back, so
+/// a wrappy update never makes existing bindings look like they do not exist.
+pub struct BindingStateFile {
+    path: PathBuf,
+}
+
+impl BindingStateFile {
+    pub fn new(paths: &WrappyPaths) -> Self {
+        Self::at(paths.binding_state_file())
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the state, migrating an older format first. A missing file is an empty state,
+    /// and so is a corrupt one, after it is moved aside; see `quarantine`.
+    pub fn load(&self) -> ContainerResult<BindingState> {
+        if !self.path.is_file() {
+            return Ok(BindingState::default());
+        }
+
+        let content = fs::read_to_string(&self.path).map_err(|e| ContainerError::IoError {
+            path: self.path.clone(),
+            source: e,
+        })?;
+        match self.decode(&content) {
+            Err(ContainerError::InvalidConfig { reason, .. }) => self.quarantine(&reason),
+            result => result,
+        }
+    }
+
+    fn decode(&self, content: &str) -> ContainerResult<BindingState> {
+        let value: Value = serde_json::from_str(content).map_err(|e| self.invalid(e.to_string()))?;
+
+        // Files from before the format was versioned have no version field and are version 1
+        let versioned = value.get("version").is_some();
+        let version = match value.get("version") {
+            None => 1,
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| self.invalid(format!("invalid format version {}", version)))?,
+        };
+
+        if version > BINDING_STATE_VERSION {
+            return Err(ContainerError::StateFileTooNew {
+                path: self.path.clone(),
+                version,
+                supported: BINDING_STATE_VERSION,
+            });
+        }
+
+        let mut value = value;
+        for from in version..BINDING_STATE_VERSION {
+            value = self.upgrade_from(from, value)?;
+        }
+
+        let mut unknown_bindings = Vec::new();
+        if let Some(object) = value.as_object_mut() {
+            object.insert("version".to_string(), Value::from(BINDING_STATE_VERSION));
+            if let Some(Value::Array(records)) = object.get_mut("bindings") {
+                let (known, unknown): (Vec<Value>, Vec<Value>) = records
+                    .drain(..)
+                    .partition(|record| serde_json::from_value::<BindingRecord>(record.clone()).is_ok());
+                *records = known;
+                unknown_bindings = unknown;
+            }
+        }
+        let mut state: BindingState = serde_json::from_value(value).map_err(|e| self.invalid(e.to_string()))?;
+        state.unknown_bindings = unknown_bindings;
+
+        if !versioned || version < BINDING_STATE_VERSION {
+            self.save(&state)?;
+            let from = if versioned { format!("format version {}", version) } else { "the unversioned format".to_string() };
+            println!(
+                "🔄 Migrated binding state {} from {} to format version {}",
+                self.path.display(),
+                from,
+                BINDING_STATE_VERSION
+            );
+        }
+        Ok(state)
+    }
+
+    /// Moves a state file that cannot be read to `bindings.json.corrupt-<time>` and starts
+    /// over with an empty state. Bindings it recorded are still on disk; disabling a
+    /// container finds the declared ones there, and enabling it records them again.
+    fn quarantine(&self, reason: &str) -> ContainerResult<BindingState> {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".corrupt-{}", clock::now().format("%Y%m%dT%H%M%S")));
+        let moved = self.path.with_file_name(name);
+        fs::rename(&self.path, &moved).map_err(|e| ContainerError::IoError {
+            path: self.path.clone(),
+            source: e,
+        })?;
+        eprintln!(
+            "⚠️  Binding state {} is corrupt ({}); moved it to {} and started a new one",
+            self.path.display(),
+            reason,
+            moved.display()
+        );
+        eprintln!("   Run 'wrappy bindings enable <container>' to record existing bindings again");
+        Ok(BindingState::default())
+    }
+
+    pub fn save(&self, state: &BindingState) -> ContainerResult<()> {
+        if let Some(parent) = self.path.parent() {
+            filesystem::create_state_dir(parent)?;
+        }
+        let content = if state.unknown_bindings.is_empty() {
+            serde_json::to_vec_pretty(state)?
+        } else {
+            let mut value = serde_json::to_value(state)?;
+            if let Some(Value::Array(records)) = value.get_mut("bindings") {
+                records.extend(state.unknown_bindings.iter().cloned());
+            }
+            serde_json::to_vec_pretty(&value)?
+        };
+        filesystem::atomic_write(&self.path, &content, Some(filesystem::STATE_FILE_MODE))
+    }
+
+    /// Adds bindings, replacing older records for the same target.
+    pub fn record(&self, bindings: &[ActiveBinding]) -> ContainerResult<()> {
+        if bindings.is_empty() {
+            return Ok(());
+        }
+
+        let _lock = self.lock()?;
+        let mut state = self.load()?;
+        for binding in bindings {
+            state.bindings.retain(|record| record.target_path != binding.target_path);
+            state.bindings.push(BindingRecord::from(binding));
+        }
+        self.save(&state)
+    }
+
+    /// Drops the records of `container` at the given targets.
+    pub fn forget(&self, container: &str, targets: &[PathBuf]) -> ContainerResult<()> {
+        let _lock = self.lock()?;
+        let mut state = self.load()?;
+        let before = state.bindings.len();
+        state
+            .bindings
+            .retain(|record| record.container != container || !targets.contains(&record.target_path));
+        if state.bindings.len() != before {
+            self.save(&state)?;
+        }
+        Ok(())
+    }
+
+    /// Held around every read-modify-write of the file, so concurrent enables and
+    /// disables of different containers do not drop each other's records.
+    fn lock(&self) -> ContainerResult<LockFile> {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".lock");
+        LockFile::acquire(&self.path.with_file_name(name), "the binding state")
+    }
+
+    /// Converts a document of format version `from` to version `from + 1`.
+    /// Version 1 is the first format, so no older layout exists yet.
+    fn upgrade_from(&self, from: u32, _value: Value) -> ContainerResult<Value> {
+        Err(self.invalid(format!("unknown format version {}", from)))
+    }
+
+    fn invalid(&self, reason: String) -> ContainerError {
+        ContainerError::InvalidConfig {
+            path: self.path.clone(),
+            reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const UNVERSIONED: &str = include_str!("../../../tests/fixtures/binding_state/unversioned.json");
+    const VERSION_1: &str = include_str!("../../../tests/fixtures/binding_state/v1.json");
+
+    fn state_file(dir: &TempDir, content: &str) -> BindingStateFile {
+        let path = dir.path().join("bindings.json");
+        fs::write(&path, content).unwrap();
+        BindingStateFile::at(path)
+    }
+
+    fn stored(file: &BindingStateFile) -> Value {
+        serde_json::from_str(&fs::read_to_string(file.path()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn an_unversioned_file_is_migrated_and_written_back() {
+        let dir = TempDir::new().unwrap();
+        let file = state_file(&dir, UNVERSIONED);
+
+        let state = file.load().unwrap();
+
+        assert_eq!(state.bindings.len(), 1);
+        assert_eq!(state.bindings[0].target_path, PathBuf::from("/home/user/.local/bin/node"));
+        assert_eq!(state.bindings[0].category, None);
+        let written = stored(&file);
+        assert_eq!(written["version"], BINDING_STATE_VERSION);
+        assert_eq!(written["bindings"][0]["container"], "node");
+    }
+
+    #[test]
+    fn a_version_1_file_loads_as_is_and_keeps_what_it_does_not_know() {
+        let dir = TempDir::new().unwrap();
+        let file = state_file(&dir, VERSION_1);
+
+        let state = file.load().unwrap();
+        file.save(&state).unwrap();
+
+        let targets: Vec<&Path> = state.bindings.iter().map(|record| record.target_path.as_path()).collect();
+        assert_eq!(targets, [Path::new("/home/user/.local/bin/node"), Path::new("/home/user/.config/nvim")]);
+        assert_eq!(state.bindings[1].bin_dir, Some(PathBuf::from("/opt/bin")));
+        assert_eq!(state.unknown_bindings.len(), 1);
+        assert_eq!(stored(&file), serde_json::from_str::<Value>(VERSION_1).unwrap());
+    }
+
+    #[test]
+    fn forgetting_a_record_keeps_records_and_fields_of_a_newer_wrappy() {
+        let dir = TempDir::new().unwrap();
+        let file = state_file(&dir, VERSION_1);
+
+        file.forget("node", &[PathBuf::from("/home/user/.local/bin/node")]).unwrap();
+
+        let written = stored(&file);
+        let types: Vec<&str> = written["bindings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|record| record["binding_type"].as_str().unwrap())
+            .collect();
+        assert_eq!(types, ["symlink", "overlay"]);
+        assert_eq!(written["written_by"], "a newer wrappy");
+    }
+
+    #[test]
+    fn a_file_from_a_newer_wrappy_is_refused_and_left_alone() {
+        let dir = TempDir::new().unwrap();
+        let content = VERSION_1.replacen("\"version\": 1", "\"version\": 99", 1);
+        let file = state_file(&dir, &content);
+
+        let error = file.load().unwrap_err();
+
+        assert!(matches!(error, ContainerError::StateFileTooNew { version: 99, .. }), "{:?}", error);
+        assert!(error.to_string().contains("newer"), "{}", error);
+        assert_eq!(fs::read_to_string(file.path()).unwrap(), content);
+    }
+
+    #[test]
+    fn a_corrupt_file_is_moved_aside_and_an_empty_state_starts() {
+        let dir = TempDir::new().unwrap();
+        let file = state_file(&dir, "{\"version\": 1, \"bindings\": [");
+
+        let state = file.load().unwrap();
+
+        assert!(state.bindings.is_empty());
+        assert!(!file.path().exists());
+        let moved: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(moved.len(), 1);
+        assert!(moved[0].starts_with("bindings.json.corrupt-"), "{:?}", moved);
+    }
+}
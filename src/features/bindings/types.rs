@@ -1,36 +1,156 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::path::PathBuf;
 
+use crate::features::manifest::serialize_sorted;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::portability;
+use crate::shared::shell_quote::has_control_characters;
+
 /// Defines how container resources are bound to the host system.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum BindingType {
     /// Direct symbolic link to container resource
     Symlink,
     /// Wrapper script that intercepts execution
+    #[default]
     Wrapper,
     /// Copy resource to host location
     Copy,
 }
 
-impl Default for BindingType {
-    fn default() -> Self {
-        Self::Wrapper
+impl fmt::Display for BindingType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BindingType::Symlink => "symlink",
+            BindingType::Wrapper => "wrapper",
+            BindingType::Copy => "copy",
+        };
+        write!(f, "{}", name)
     }
 }
 
+/// Controls what the wrapped program sees as argv[0].
+/// Multi-call binaries (busybox style) pick their behavior from the invoked name,
+/// so they need the wrapper's name rather than the path inside the container.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Argv0Mode {
+    /// Program sees the name it was invoked as through the wrapper
+    Preserve,
+    /// Program sees its own path inside the container
+    #[default]
+    Target,
+}
+
 /// Configuration for binding executable files from container to host.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutableBinding {
     /// Path to executable within container (relative to container root)
     pub source: String,
-    /// Target path on host system (supports ~ expansion)
+    /// Target path on host system (supports ~ expansion), or a bare name placed in the bin directory
     pub target: String,
-    /// How the binding should be created
-    #[serde(default)]
-    pub binding_type: BindingType,
+    /// How the binding should be created; unset falls back to the config policy, then the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding_type: Option<BindingType>,
     /// Optional display name for console output
     pub display_name: Option<String>,
+    /// Which name the executable receives as argv[0] (wrapper bindings only)
+    #[serde(default)]
+    pub argv0: Argv0Mode,
+    /// Start the container's service on first use of the wrapper (wrapper bindings only)
+    #[serde(default)]
+    pub lazy: bool,
+    /// Service started when `lazy` is set
+    #[serde(default, skip_serializing_if = "LazyService::is_default")]
+    pub service: LazyService,
+    /// Arguments passed before the user's own (wrapper bindings only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args_prepend: Vec<String>,
+    /// Environment exported by the wrapper before running the executable (wrapper bindings only)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty", serialize_with = "serialize_sorted")]
+    pub env: HashMap<String, String>,
+}
+
+impl ExecutableBinding {
+    pub fn binding_type(&self) -> BindingType {
+        self.binding_type.clone().unwrap_or_default()
+    }
+
+    /// A target without a `/`, such as `rg`, names a file in the bin directory.
+    pub fn is_bare_name(&self) -> bool {
+        !self.target.contains('/')
+    }
+
+    /// Rejects values the wrapper cannot carry: newlines would split the generated
+    /// shell line and NUL cannot appear in argv or the environment at all.
+    pub fn validate(&self) -> ContainerResult<()> {
+        let invalid = |reason: String| {
+            ContainerError::ManifestValidation(format!("Executable binding '{}': {}", self.target, reason))
+        };
+
+        let names = [("source", Some(&self.source)), ("target", Some(&self.target)), ("display name", self.display_name.as_ref())];
+        for (what, value) in names {
+            if value.is_some_and(|value| has_control_characters(value)) {
+                return Err(invalid(format!("{} contains a control character such as a newline", what)));
+            }
+        }
+
+        for arg in &self.args_prepend {
+            if arg.contains(['\n', '\r', '\0']) {
+                return Err(invalid(format!("argument {:?} contains a newline or NUL", arg)));
+            }
+        }
+        for (key, value) in &self.env {
+            let valid_key = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid_key {
+                return Err(invalid(format!("'{}' is not a valid environment variable name", key)));
+            }
+            if value.contains(['\n', '\r', '\0']) {
+                return Err(invalid(format!("value of '{}' contains a newline or NUL", key)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Background service a lazy wrapper starts before running its executable.
+/// `wrappy container idle-reaper` stops it again once the wrapper has not been used for a while.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LazyService {
+    /// Manifest script that runs the service in the foreground
+    #[serde(default = "LazyService::default_script")]
+    pub script: String,
+    /// Seconds without wrapper use before the service is stopped
+    #[serde(default = "LazyService::default_idle_timeout")]
+    pub idle_timeout_secs: u64,
+}
+
+impl LazyService {
+    fn default_script() -> String {
+        "service".to_string()
+    }
+
+    fn default_idle_timeout() -> u64 {
+        900
+    }
+
+    fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+impl Default for LazyService {
+    fn default() -> Self {
+        Self {
+            script: Self::default_script(),
+            idle_timeout_secs: Self::default_idle_timeout(),
+        }
+    }
 }
 
 /// Configuration for binding configuration directories.
@@ -40,12 +160,25 @@ pub struct ConfigBinding {
     pub source: String,
     /// Target config path on host system
     pub target: String,
-    /// How the binding should be created
-    #[serde(default)]
-    pub binding_type: BindingType,
+    /// How the binding should be created; unset falls back to the config policy, then the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding_type: Option<BindingType>,
     /// Whether to backup existing target before binding
     #[serde(default)]
     pub backup_existing: bool,
+    /// Name used to select this binding in commands and messages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl ConfigBinding {
+    pub fn binding_type(&self) -> BindingType {
+        self.binding_type.clone().unwrap_or_default()
+    }
+
+    pub fn label(&self) -> String {
+        effective_label(self.label.as_deref(), &self.target)
+    }
 }
 
 /// Configuration for binding data directories.
@@ -55,12 +188,75 @@ pub struct DataBinding {
     pub source: String,
     /// Target data path on host system  
     pub target: String,
-    /// How the binding should be created
-    #[serde(default)]
-    pub binding_type: BindingType,
+    /// How the binding should be created; unset falls back to the config policy, then the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding_type: Option<BindingType>,
     /// Whether to backup existing target before binding
     #[serde(default)]
     pub backup_existing: bool,
+    /// Name used to select this binding in commands and messages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl DataBinding {
+    pub fn binding_type(&self) -> BindingType {
+        self.binding_type.clone().unwrap_or_default()
+    }
+
+    pub fn label(&self) -> String {
+        effective_label(self.label.as_deref(), &self.target)
+    }
+}
+
+/// Starts an executable binding with the desktop session through an XDG autostart
+/// entry in `~/.config/autostart/`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutostartBinding {
+    /// Target of the executable binding to start, exactly as written in `executables`
+    pub executable: String,
+    /// Name session managers show; defaults to the executable name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Seconds to wait after login before starting (`X-GNOME-Autostart-Delay`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay_secs: Option<u32>,
+}
+
+impl AutostartBinding {
+    /// File name of the referenced executable, e.g. `clipman` for `~/.local/bin/clipman`.
+    pub fn executable_name(&self) -> String {
+        self.executable
+            .rsplit('/')
+            .find(|part| !part.is_empty())
+            .unwrap_or(&self.executable)
+            .to_string()
+    }
+}
+
+/// Explicit label, or one derived from the target (`~/.config/app` -> `config-app`).
+/// Derived labels only depend on the target, so unlabeled entries keep the same
+/// label across manifest edits that merely reorder them.
+fn effective_label(label: Option<&str>, target: &str) -> String {
+    if let Some(label) = label {
+        return label.to_string();
+    }
+
+    let target = target.strip_prefix("~/").unwrap_or(target);
+    let mut derived = String::new();
+    for c in target.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            derived.push(c.to_ascii_lowercase());
+        } else if !derived.is_empty() && !derived.ends_with('-') {
+            derived.push('-');
+        }
+    }
+    derived.trim_end_matches('-').to_string()
+}
+
+/// Labels may be typed on the command line, so they stay shell-friendly.
+pub fn is_valid_label(label: &str) -> bool {
+    !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
 }
 
 /// Complete bindings configuration for a container.
@@ -75,6 +271,16 @@ pub struct BindingsConfig {
     /// Data directory bindings
     #[serde(default)]
     pub data: Vec<DataBinding>,
+    /// Executable bindings started with the desktop session
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub autostart: Vec<AutostartBinding>,
+    /// Directory for this container's wrappers and bare-name targets instead of the configured bin directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bin_dir: Option<String>,
+    /// Sections this wrappy does not know (e.g. from a newer wrappy), kept
+    /// as written so saving the manifest does not drop them
+    #[serde(flatten)]
+    pub unknown: BTreeMap<String, Value>,
 }
 
 impl BindingsConfig {
@@ -94,9 +300,226 @@ impl BindingsConfig {
         self.data.push(binding);
     }
 
+    pub fn add_autostart(&mut self, binding: AutostartBinding) {
+        self.autostart.push(binding);
+    }
+
+    /// Autostart entries whose executable binding is among `executables`.
+    pub fn autostart_of_executables(&self) -> Vec<&AutostartBinding> {
+        self.autostart
+            .iter()
+            .filter(|autostart| self.executables.iter().any(|executable| executable.target == autostart.executable))
+            .collect()
+    }
+
+    /// Every autostart entry must start a declared executable binding, once.
+    pub fn validate_autostart(&self) -> ContainerResult<()> {
+        let mut seen = std::collections::HashSet::new();
+        for autostart in &self.autostart {
+            if !self.executables.iter().any(|executable| executable.target == autostart.executable) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Autostart entry '{}' does not name the target of an executable binding",
+                    autostart.executable
+                )));
+            }
+            if !seen.insert(autostart.executable_name()) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Executable '{}' has more than one autostart entry",
+                    autostart.executable
+                )));
+            }
+            if autostart.name.as_deref().is_some_and(has_control_characters) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Name of the autostart entry '{}' contains a control character such as a newline",
+                    autostart.executable
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The bin directory override must be a path, not a bare name.
+    pub fn validate_bin_dir(&self) -> ContainerResult<()> {
+        let Some(bin_dir) = &self.bin_dir else {
+            return Ok(());
+        };
+        if has_control_characters(bin_dir) {
+            return Err(ContainerError::ManifestValidation(
+                "Bindings 'bin_dir' contains a control character such as a newline".to_string(),
+            ));
+        }
+        if bin_dir.contains(':') {
+            return Err(ContainerError::ManifestValidation(format!(
+                "Bindings 'bin_dir' '{}' contains ':', which separates PATH entries",
+                bin_dir
+            )));
+        }
+        if !(bin_dir.starts_with('/') || bin_dir.starts_with("~/") || bin_dir.starts_with("${")) {
+            return Err(ContainerError::ManifestValidation(format!(
+                "Bindings 'bin_dir' '{}' must be an absolute path or start with ~/",
+                bin_dir
+            )));
+        }
+        Ok(())
+    }
+
+    /// Every binding as its section name, source and target.
+    fn source_target_pairs(&self) -> Vec<(&'static str, &str, &str)> {
+        let executables = self.executables.iter().map(|binding| ("Executable", &binding.source, &binding.target));
+        let configs = self.configs.iter().map(|binding| ("Config", &binding.source, &binding.target));
+        let data = self.data.iter().map(|binding| ("Data", &binding.source, &binding.target));
+        executables
+            .chain(configs)
+            .chain(data)
+            .map(|(kind, source, target)| (kind, source.as_str(), target.as_str()))
+            .collect()
+    }
+
+    /// Sources are relative to the container, so `bin\tool` from a manifest written on
+    /// Windows can only mean `bin/tool`. Absolute Windows paths are left for `validate_paths`.
+    pub fn normalize_sources(&mut self) {
+        let sources = self
+            .executables
+            .iter_mut()
+            .map(|binding| &mut binding.source)
+            .chain(self.configs.iter_mut().map(|binding| &mut binding.source))
+            .chain(self.data.iter_mut().map(|binding| &mut binding.source));
+        for source in sources {
+            if source.contains('\\') && !portability::is_windows_absolute(source) {
+                *source = source.replace('\\', "/");
+            }
+        }
+    }
+
+    /// Windows paths pass as plain strings but cannot work on unix: a drive or UNC
+    /// path is not absolute here, and a backslash is part of a file name, not a separator.
+    pub fn validate_paths(&self) -> ContainerResult<()> {
+        for (kind, source, target) in self.source_target_pairs() {
+            let invalid = |reason: String| {
+                ContainerError::ManifestValidation(format!("{} binding '{}': {}", kind, target, reason))
+            };
+            if portability::is_windows_absolute(source) {
+                return Err(invalid(format!(
+                    "source '{}' is a Windows path; sources are relative to the container, e.g. bin/tool",
+                    source
+                )));
+            }
+            if portability::is_windows_absolute(target) {
+                return Err(invalid(
+                    "target is a Windows path, which means nothing on unix; use an absolute path or one starting with ~/"
+                        .to_string(),
+                ));
+            }
+            if target.contains('\\') {
+                return Err(invalid(
+                    "target contains a backslash, which unix keeps as part of a file name; separate directories with /"
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Targets that go through a directory only another platform has, such as `AppData`,
+    /// with that directory and the platform. Valid here, so `validate` only warns.
+    pub fn platform_specific_targets(&self) -> Vec<(String, String, &'static str)> {
+        self.source_target_pairs()
+            .into_iter()
+            .filter_map(|(_, _, target)| {
+                portability::platform_specific_segment(target).map(|(segment, platform)| (target.to_string(), segment, platform))
+            })
+            .collect()
+    }
+
+    /// True when there is nothing this wrappy can bind; unknown sections do not count.
     pub fn is_empty(&self) -> bool {
         self.executables.is_empty() && self.configs.is_empty() && self.data.is_empty()
     }
+
+    /// Names of the sections this wrappy ignores, e.g. `sockets`.
+    pub fn unknown_sections(&self) -> Vec<&str> {
+        self.unknown.keys().map(String::as_str).collect()
+    }
+
+    /// Explicit labels must be shell-friendly, and every config and data binding,
+    /// labeled or not, must end up with a label no other one has.
+    pub fn validate_labels(&self) -> ContainerResult<()> {
+        let explicit = self
+            .configs
+            .iter()
+            .map(|config| config.label.as_deref())
+            .chain(self.data.iter().map(|data| data.label.as_deref()))
+            .flatten();
+        for label in explicit {
+            if !is_valid_label(label) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Binding label '{}' may only contain alphanumeric characters, hyphens, and underscores",
+                    label
+                )));
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        let labels = self
+            .configs
+            .iter()
+            .map(ConfigBinding::label)
+            .chain(self.data.iter().map(DataBinding::label));
+        for label in labels {
+            if !seen.insert(label.clone()) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Binding label '{}' is used more than once; give the bindings distinct labels",
+                    label
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Binding section of the manifest, used to narrow operations to one kind of binding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BindingCategory {
+    Executables,
+    Configs,
+    Data,
+}
+
+impl BindingCategory {
+    pub const ALL: [BindingCategory; 3] = [Self::Executables, Self::Configs, Self::Data];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Executables => "executables",
+            Self::Configs => "configs",
+            Self::Data => "data",
+        }
+    }
+}
+
+/// Selects a subset of a container's bindings so they can be disabled individually.
+#[derive(Debug, Clone, Default)]
+pub struct BindingFilter {
+    pub only: Option<BindingCategory>,
+    /// Target path as written by the user; compared after `~` expansion
+    pub target: Option<String>,
+    /// Label of a single config or data binding
+    pub label: Option<String>,
+}
+
+impl BindingFilter {
+    pub fn is_empty(&self) -> bool {
+        self.only.is_none() && self.target.is_none() && self.label.is_none()
+    }
+
+    pub fn includes(&self, category: BindingCategory) -> bool {
+        // Only config and data bindings carry labels
+        if self.label.is_some() && category == BindingCategory::Executables {
+            return false;
+        }
+        self.only.is_none_or(|only| only == category)
+    }
 }
 
 /// Represents an active binding on the host system.
@@ -106,5 +529,135 @@ pub struct ActiveBinding {
     pub source_path: PathBuf,
     pub target_path: PathBuf,
     pub binding_type: BindingType,
+    /// Manifest section the binding was declared in
+    pub category: BindingCategory,
+    /// Bin directory the binding went to, when it is not the configured one
+    pub bin_dir: Option<PathBuf>,
     pub created_at: std::time::SystemTime,
-}
\ No newline at end of file
+}
+
+/// One binding as `bindings list` shows it: a record from the state file, or a
+/// wrappy wrapper found in a bin directory without one.
+#[derive(Debug, Clone, Serialize)]
+pub struct BindingListing {
+    pub container: String,
+    /// `None` for records written before the section was recorded, unless it can be inferred
+    pub kind: Option<BindingCategory>,
+    pub binding_type: BindingType,
+    /// Path on the host; for wrappers the generated script in its bin directory
+    pub target: PathBuf,
+    pub source: PathBuf,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// False for wrappers found on disk that the state file does not know about
+    pub recorded: bool,
+    pub status: ListingStatus,
+}
+
+/// Whether a listed binding is still usable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListingStatus {
+    Ok,
+    /// The target is gone
+    Missing,
+    /// The target is there but a symlink dangles or a wrapper points at a deleted executable
+    Broken,
+}
+
+impl fmt::Display for ListingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Ok => "ok",
+            Self::Missing => "missing",
+            Self::Broken => "broken",
+        };
+        write!(f, "{}", name)
+    }
+}
+/// One host path more than one container would bind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetConflict {
+    pub target: PathBuf,
+    pub containers: Vec<String>,
+}
+
+impl fmt::Display for TargetConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is bound by {}", self.target.display(), self.containers.join(" and "))
+    }
+}
+
+/// What currently sits at a binding's target on the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetState {
+    /// Nothing there; enable creates the binding
+    Free,
+    /// Nothing there and the parent directory is missing; enable creates it
+    ParentMissing,
+    /// Another file or directory is in the way
+    Occupied,
+    /// The binding itself is already installed
+    Bound,
+}
+
+impl fmt::Display for TargetState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            TargetState::Free => "free",
+            TargetState::ParentMissing => "free, parent directory will be created",
+            TargetState::Occupied => "occupied by another file",
+            TargetState::Bound => "already bound",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// A binding's paths as `bindings enable` would use them, with what is on the host now.
+#[derive(Debug)]
+pub struct ResolvedBinding {
+    pub source_path: PathBuf,
+    pub source_exists: bool,
+    /// Kept per binding so one bad target does not hide the others
+    pub target: ContainerResult<(PathBuf, TargetState)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn executable(extra: serde_json::Value) -> ExecutableBinding {
+        let mut declaration = json!({ "source": "bin/app", "target": "app" });
+        declaration.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(declaration).unwrap()
+    }
+
+    #[test]
+    fn validate_accepts_quotes_and_dollars_in_arguments_and_environment() {
+        let binding = executable(json!({
+            "args_prepend": ["-jar", "app with spaces.jar", "it's $HOME `id`"],
+            "env": { "JAVA_OPTS": "-Xmx1g \"quoted\" $(id)" },
+        }));
+
+        assert!(binding.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_newlines_and_nul_in_arguments() {
+        for arg in ["one\ntwo", "one\rtwo", "one\0two"] {
+            let binding = executable(json!({ "args_prepend": [arg] }));
+
+            assert!(matches!(binding.validate(), Err(ContainerError::ManifestValidation(_))), "{:?}", arg);
+        }
+    }
+
+    #[test]
+    fn validate_rejects_bad_environment_names_and_newline_or_nul_values() {
+        let envs = [json!({ "1ST": "x" }), json!({ "A-B": "x" }), json!({ "": "x" }), json!({ "OK": "a\0b" }), json!({ "OK": "a\nb" })];
+        for env in envs {
+            let binding = executable(json!({ "env": env.clone() }));
+
+            assert!(matches!(binding.validate(), Err(ContainerError::ManifestValidation(_))), "{}", env);
+        }
+    }
+}
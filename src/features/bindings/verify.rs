@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::features::bindings::{
+    BindingCategory, BindingManager, BindingRecord, BindingType, ReconcileService, WrapperGenerator, WrapperReport, WrapperState,
+};
+use crate::shared::error::ContainerResult;
+
+/// How a recorded binding can be put back.
+#[derive(Debug, Clone)]
+enum Repair {
+    /// Regenerated or reinstalled from its container, as `reconcile --fix` does
+    Executable(Box<WrapperReport>),
+    /// Linked or copied again from its source
+    Directory(BindingRecord),
+}
+
+/// Result of checking one recorded binding against the host.
+#[derive(Debug, Clone)]
+pub struct BindingVerification {
+    pub container: String,
+    pub kind: Option<BindingCategory>,
+    pub binding_type: BindingType,
+    pub target: PathBuf,
+    /// What is wrong; `None` for a working binding
+    pub problem: Option<String>,
+    repair: Option<Repair>,
+}
+
+impl BindingVerification {
+    pub fn is_broken(&self) -> bool {
+        self.problem.is_some()
+    }
+
+    pub fn can_repair(&self) -> bool {
+        self.is_broken() && self.repair.is_some()
+    }
+}
+
+/// Checks that every recorded binding still works: its source exists, symlinks point
+/// at it, wrappers still run the executable they were generated for and copies are
+/// still there. Bindings in bin directories are checked by `ReconcileService`.
+pub struct VerifyService;
+
+impl VerifyService {
+    /// Checks the recorded bindings, of one container only when `container` is set,
+    /// sorted by container and target.
+    pub fn check(manager: &BindingManager, container: Option<&str>) -> ContainerResult<Vec<BindingVerification>> {
+        let mut records: BTreeMap<PathBuf, BindingRecord> = manager
+            .recorded_bindings()?
+            .into_iter()
+            .filter(|record| container.is_none_or(|container| record.container == container))
+            .map(|record| (manager.installed_path(&record), record))
+            .collect();
+
+        let mut checks = Vec::new();
+        for report in ReconcileService::scan(manager)? {
+            let Some(record) = records.remove(&report.path) else {
+                // Untracked wrappers are no known binding, and other containers' are filtered out
+                continue;
+            };
+            checks.push(Self::check_executable(record, report));
+        }
+        for (target, record) in records {
+            checks.push(Self::check_directory(target, record));
+        }
+
+        checks.sort_by(|a, b| (&a.container, &a.target).cmp(&(&b.container, &b.target)));
+        Ok(checks)
+    }
+
+    /// Repairs a broken binding where its container still provides the source.
+    pub fn repair(manager: &BindingManager, check: &BindingVerification) -> ContainerResult<()> {
+        match &check.repair {
+            Some(Repair::Executable(report)) => ReconcileService::repair(manager, report),
+            Some(Repair::Directory(record)) => manager.reinstall_directory(record),
+            None => Ok(()),
+        }
+    }
+
+    fn check_executable(record: BindingRecord, report: WrapperReport) -> BindingVerification {
+        let problem = match report.state {
+            // A wrapper generated as enable would still fails when its executable is gone
+            WrapperState::Consistent if !record.source_path.exists() => {
+                Some(format!("source {} no longer exists; cannot repair", record.source_path.display()))
+            }
+            WrapperState::Consistent => None,
+            state => {
+                let detail = report.detail.clone().or_else(|| Self::wrapper_problem(&report.path));
+                Some(detail.map_or_else(|| state.to_string(), |detail| format!("{}: {}", state, detail)))
+            }
+        };
+        BindingVerification {
+            container: record.container,
+            kind: Some(record.category.unwrap_or(BindingCategory::Executables)),
+            binding_type: record.binding_type,
+            target: report.path.clone(),
+            problem,
+            repair: report.can_repair().then(|| Repair::Executable(Box::new(report))),
+        }
+    }
+
+    /// Why a wrapper that differs from what enable would generate does not work,
+    /// when its container is gone and the expected content is unknown.
+    fn wrapper_problem(path: &Path) -> Option<String> {
+        let metadata = WrapperGenerator::read_metadata(path)?;
+        (!metadata.executable_path.exists())
+            .then(|| format!("runs {}, which no longer exists", metadata.executable_path.display()))
+    }
+
+    fn check_directory(target: PathBuf, record: BindingRecord) -> BindingVerification {
+        let source = &record.source_path;
+        let (problem, repairable) = if !source.exists() {
+            (Some(format!("source {} no longer exists; cannot repair", source.display())), false)
+        } else if target.symlink_metadata().is_err() {
+            (Some("missing: recorded but not on disk".to_string()), true)
+        } else if record.binding_type == BindingType::Symlink
+            && !BindingManager::is_binding_active(&target, &record.binding_type, source)
+        {
+            (Some(format!("does not link to {}; left alone", source.display())), false)
+        } else {
+            (None, false)
+        };
+
+        BindingVerification {
+            container: record.container.clone(),
+            kind: record.category,
+            binding_type: record.binding_type.clone(),
+            target,
+            problem,
+            repair: repairable.then_some(Repair::Directory(record)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::ContainerService;
+    use crate::features::setup::SetupService;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, TempHome};
+    use serde_json::json;
+    use std::fs;
+    use std::process::Command;
+
+    /// `app` with its `app-tool` wrapper, a linked config and a copied data directory enabled.
+    fn enabled(home: &TempHome) -> PathBuf {
+        SetupService::initialize(&home.paths()).unwrap();
+        let dir = home.paths().default_store_dir().join("app/1.0.0");
+        let mut manifest = fixture_manifest("app", "1.0.0");
+        manifest["bindings"]["configs"] = json!([{ "source": "config/app", "target": "~/.config/app", "binding_type": "symlink" }]);
+        manifest["bindings"]["data"] = json!([{ "source": "content/data", "target": "~/.local/share/app", "binding_type": "copy" }]);
+        fixture_container_at(&dir, manifest);
+        fs::create_dir_all(dir.join("config/app")).unwrap();
+        fs::write(dir.join("config/app/settings.ini"), "theme=dark\n").unwrap();
+        fs::create_dir_all(dir.join("content/data")).unwrap();
+        fs::write(dir.join("content/data/seed.db"), "rows").unwrap();
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+        BindingManager::new().unwrap().install_bindings(&container).unwrap();
+        dir
+    }
+
+    fn broken(checks: &[BindingVerification]) -> Vec<(&Path, bool)> {
+        checks
+            .iter()
+            .filter(|check| check.is_broken())
+            .map(|check| (check.target.as_path(), check.can_repair()))
+            .collect()
+    }
+
+    #[test]
+    fn freshly_enabled_bindings_all_verify() {
+        let home = TempHome::enter();
+        enabled(&home);
+
+        let checks = VerifyService::check(&BindingManager::new().unwrap(), None).unwrap();
+
+        assert_eq!(checks.len(), 3);
+        assert!(broken(&checks).is_empty(), "{:?}", checks);
+    }
+
+    #[test]
+    fn a_broken_wrapper_symlink_and_copy_are_reported_and_repaired() {
+        let home = TempHome::enter();
+        let dir = enabled(&home);
+        let wrapper = home.paths().bin_dir.join("app-tool");
+        let config = home.path().join(".config/app");
+        let data = home.path().join(".local/share/app");
+        let content = fs::read_to_string(&wrapper).unwrap();
+        let executable = dir.join("bin/tool").to_string_lossy().into_owned();
+        fs::write(&wrapper, content.replace(&executable, "/nowhere/tool")).unwrap();
+        fs::remove_file(&config).unwrap();
+        fs::remove_dir_all(&data).unwrap();
+        let manager = BindingManager::new().unwrap();
+
+        let before = VerifyService::check(&manager, Some("app")).unwrap();
+        for check in &before {
+            VerifyService::repair(&manager, check).unwrap();
+        }
+        let after = VerifyService::check(&manager, Some("app")).unwrap();
+
+        let mut expected = vec![(wrapper.as_path(), true), (config.as_path(), true), (data.as_path(), true)];
+        expected.sort();
+        let mut found = broken(&before);
+        found.sort();
+        assert_eq!(found, expected);
+        assert!(broken(&after).is_empty(), "{:?}", after);
+        assert_eq!(fs::read_link(&config).unwrap(), dir.join("config/app"));
+        assert_eq!(fs::read_to_string(data.join("seed.db")).unwrap(), "rows");
+        let output = Command::new(&wrapper).arg("x").env_remove("WRAPPY_ACTIVE_APP_TOOL").output().unwrap();
+        assert!(String::from_utf8_lossy(&output.stdout).contains("tool x"));
+    }
+
+    #[test]
+    fn a_binding_whose_source_is_gone_is_broken_but_not_repaired() {
+        let home = TempHome::enter();
+        let dir = enabled(&home);
+        fs::remove_dir_all(dir.join("content/data")).unwrap();
+        let manager = BindingManager::new().unwrap();
+
+        let checks = VerifyService::check(&manager, None).unwrap();
+
+        let data = home.path().join(".local/share/app");
+        assert_eq!(broken(&checks), [(data.as_path(), false)]);
+        let check = checks.iter().find(|check| check.is_broken()).unwrap();
+        assert!(check.problem.as_deref().unwrap().contains("cannot repair"));
+    }
+}
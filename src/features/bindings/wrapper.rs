@@ -1,8 +1,22 @@
 use std::fs;
-use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 
+use crate::features::bindings::{Argv0Mode, ExecutableBinding, LazyService};
 use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::path_sanity;
+use crate::shared::shell_quote::{has_control_characters, posix_quote, posix_unquote};
+
+/// Marker line every generated wrapper carries in its header.
+const WRAPPER_MARKER: &str = "# Wrappy container wrapper";
+
+/// What a generated wrapper's header says about the executable it runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrapperMetadata {
+    pub container: String,
+    pub display_name: String,
+    pub executable_path: PathBuf,
+}
 
 /// Generates wrapper scripts for container executables with execution tracking.
 pub struct WrapperGenerator {
@@ -34,42 +48,83 @@ impl WrapperGenerator {
     }
 
     /// Generates wrapper script for executable with console output tracking.
+    /// Everything configurable comes from the binding, so regenerating a wrapper
+    /// from the manifest always reproduces the same script.
     pub fn create_wrapper(
         &self,
         executable_name: &str,
         container_name: &str,
+        container_path: &Path,
+        executable: &ExecutableBinding,
         executable_path: &Path,
-        display_name: Option<&str>,
     ) -> ContainerResult<PathBuf> {
+        path_sanity::check_path_entry(&self.target_dir)?;
         let wrapper_path = self.target_dir.join(executable_name);
-        let display = display_name.unwrap_or(executable_name);
+        let script_content = Self::render_wrapper(executable_name, container_name, container_path, executable, executable_path)?;
 
-        let script_content = self.generate_wrapper_script(
+        // Atomic replace so a concurrently running wrapper never reads a half-written script
+        filesystem::atomic_write(&wrapper_path, script_content.as_bytes(), Some(0o755))?;
+
+        Ok(wrapper_path)
+    }
+
+    /// The exact script `create_wrapper` writes, so an installed wrapper can be compared against it.
+    pub fn render_wrapper(
+        executable_name: &str,
+        container_name: &str,
+        container_path: &Path,
+        executable: &ExecutableBinding,
+        executable_path: &Path,
+    ) -> ContainerResult<String> {
+        // Bindings restored from state never went through manifest validation
+        executable.validate()?;
+        let display = executable.display_name.as_deref().unwrap_or(executable_name);
+        Self::check_printable(container_name, executable_name, display, container_path, executable_path)?;
+
+        let mut prelude = if executable.lazy {
+            Self::service_start_line(container_path, &executable.service)?
+        } else {
+            String::new()
+        };
+        prelude.push_str(&Self::injection_lines(executable));
+
+        Ok(Self::generate_wrapper_script(
             container_name,
             executable_path,
             display,
-        );
-
-        // Write wrapper script
-        fs::write(&wrapper_path, script_content).map_err(|e| ContainerError::IoError {
-            path: wrapper_path.clone(),
-            source: e,
-        })?;
-
-        // Make executable
-        let mut perms = fs::metadata(&wrapper_path)
-            .map_err(|e| ContainerError::IoError {
-                path: wrapper_path.clone(),
-                source: e,
-            })?
-            .permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(&wrapper_path, perms).map_err(|e| ContainerError::IoError {
-            path: wrapper_path.clone(),
-            source: e,
-        })?;
+            &executable.argv0,
+            &Self::recursion_guard(executable_name, executable_path),
+            &prelude,
+        ))
+    }
 
-        Ok(wrapper_path)
+    /// Every interpolated value is quoted, but a control character such as a newline
+    /// would still split a line of the script (or its header), so such names are refused.
+    fn check_printable(
+        container_name: &str,
+        executable_name: &str,
+        display_name: &str,
+        container_path: &Path,
+        executable_path: &Path,
+    ) -> ContainerResult<()> {
+        for (what, name) in [("container name", container_name), ("wrapper name", executable_name), ("display name", display_name)] {
+            if has_control_characters(name) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "{} {:?} contains a control character such as a newline",
+                    what, name
+                )));
+            }
+        }
+        for path in [container_path, executable_path] {
+            if has_control_characters(&path.to_string_lossy()) {
+                return Err(ContainerError::InvalidPath {
+                    path: path.to_path_buf(),
+                    reason: "contains a control character such as a newline, which a wrapper cannot carry".to_string(),
+                });
+            }
+        }
+        // The recursion guard puts the directory of the executable on PATH
+        path_sanity::check_path_entry(executable_path.parent().unwrap_or(executable_path))
     }
 
     /// Removes wrapper script from target directory.
@@ -86,22 +141,129 @@ impl WrapperGenerator {
         Ok(())
     }
 
+    /// Shell line that starts the container's service if needed and records the use.
+    /// Liveness and locking live in `wrappy container start`, so the wrapper stays trivial.
+    fn service_start_line(container_path: &Path, service: &LazyService) -> ContainerResult<String> {
+        let wrappy = std::env::current_exe().map_err(|e| ContainerError::IoError {
+            path: PathBuf::from("wrappy"),
+            source: e,
+        })?;
+
+        let container_path = container_path.canonicalize().unwrap_or_else(|_| container_path.to_path_buf());
+
+        Ok(format!(
+            r#"
+# Start the container service on first use; idle-reaper stops it after idling
+{wrappy} container start {container} --script {script} --idle-timeout {timeout} --quiet \
+    || echo "⚠️  Could not start the service of $CONTAINER_NAME" >&2
+"#,
+            wrappy = posix_quote(&wrappy.to_string_lossy()),
+            container = posix_quote(&container_path.to_string_lossy()),
+            script = posix_quote(&service.script),
+            timeout = service.idle_timeout_secs,
+        ))
+    }
+
+    /// Variable a wrapper sets while it runs, e.g. `WRAPPY_ACTIVE_NODE` for `node`.
+    pub fn active_variable(executable_name: &str) -> String {
+        let name: String = executable_name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+            .collect();
+        format!("WRAPPY_ACTIVE_{}", name)
+    }
+
+    /// A program that runs its own command name (a `node` shim calling `node`) would
+    /// find this wrapper again through PATH and recurse until the shell dies. Nested calls
+    /// therefore skip the bookkeeping and hand over to the next `<name>` on PATH outside the
+    /// wrapper's and the executable's directories, which is what such a program means to run.
+    /// Running the executable itself again, the usual guard, would only move the loop into it.
+    /// The executable's directory is searched first so internal lookups reach the container's
+    /// own binaries, unless it holds the executable under the command name itself: that file
+    /// would find itself again before ever reaching the guard.
+    fn recursion_guard(executable_name: &str, executable_path: &Path) -> String {
+        let variable = Self::active_variable(executable_name);
+        let executable_dir = posix_quote(&executable_path.parent().unwrap_or(executable_path).to_string_lossy());
+        let shadows_itself = executable_path.file_name().is_some_and(|name| name == executable_name);
+        let search_first = if shadows_itself {
+            String::new()
+        } else {
+            format!("export PATH={}:\"$PATH\"\n", executable_dir)
+        };
+        format!(
+            r#"
+# Nested invocation through PATH: run the next {name} without bookkeeping
+if [ -n "${{{variable}:-}}" ]; then
+    WRAPPER_DIR="${{0%/*}}"
+    NEXT_PATH=
+    IFS=: read -r -a PATH_ENTRIES <<< "$PATH"
+    for entry in "${{PATH_ENTRIES[@]}}"; do
+        case "${{entry%/}}" in
+            "${{WRAPPER_DIR%/}}"|{executable_dir}) ;;
+            *) NEXT_PATH="${{NEXT_PATH:+$NEXT_PATH:}}$entry" ;;
+        esac
+    done
+    export PATH="$NEXT_PATH"
+    NEXT_COMMAND={name}
+    if ! command -v "$NEXT_COMMAND" >/dev/null; then
+        echo "❌ $CONTAINER_NAME runs $NEXT_COMMAND, but there is no other $NEXT_COMMAND on PATH" >&2
+        exit 127
+    fi
+    exec "$NEXT_COMMAND" "$@"
+fi
+export {variable}=1
+{search_first}"#,
+            variable = variable,
+            name = posix_quote(executable_name),
+            executable_dir = executable_dir,
+            search_first = search_first,
+        )
+    }
+
+    /// Exports the binding's environment and puts its fixed arguments ahead of the user's.
+    /// Values are single-quoted, so nothing in them is expanded by the wrapper shell.
+    fn injection_lines(executable: &ExecutableBinding) -> String {
+        if executable.args_prepend.is_empty() && executable.env.is_empty() {
+            return String::new();
+        }
+
+        let mut lines = String::from("\n# Arguments and environment from the binding declaration\n");
+        let mut env: Vec<_> = executable.env.iter().collect();
+        env.sort();
+        for (key, value) in env {
+            lines.push_str(&format!("export {}={}\n", key, posix_quote(value)));
+        }
+        if !executable.args_prepend.is_empty() {
+            let args: Vec<String> = executable.args_prepend.iter().map(|arg| posix_quote(arg)).collect();
+            lines.push_str(&format!("set -- {} \"$@\"\n", args.join(" ")));
+        }
+        lines
+    }
+
     /// Generates the actual wrapper script content with execution tracking.
     fn generate_wrapper_script(
-        &self,
         container_name: &str,
         executable_path: &Path,
         display_name: &str,
+        argv0: &Argv0Mode,
+        recursion_guard: &str,
+        prelude: &str,
     ) -> String {
+        // The subshell keeps exec from replacing the wrapper, so the finish banner still runs
+        let exec_line = match argv0 {
+            Argv0Mode::Preserve => r#"(exec -a "$0" "$EXECUTABLE_PATH" "$@")"#,
+            Argv0Mode::Target => r#""$EXECUTABLE_PATH" "$@""#,
+        };
+
         format!(
             r#"#!/bin/bash
 # Wrappy container wrapper for {container_name}/{display_name}
 # Generated automatically - do not modify
 
-CONTAINER_NAME="{container_name}"
-DISPLAY_NAME="{display_name}"
-EXECUTABLE_PATH="{executable_path}"
-
+CONTAINER_NAME={quoted_container_name}
+DISPLAY_NAME={quoted_display_name}
+EXECUTABLE_PATH={quoted_executable_path}
+{recursion_guard}
 # Function to get current timestamp
 get_timestamp() {{
     date '+%Y-%m-%d %H:%M:%S'
@@ -122,6 +284,7 @@ calculate_duration() {{
     fi
 }}
 
+{prelude}
 # Record start time
 START_TIME=$(date +%s)
 TIMESTAMP=$(get_timestamp)
@@ -130,7 +293,7 @@ TIMESTAMP=$(get_timestamp)
 echo "🚀 [$TIMESTAMP] Starting $CONTAINER_NAME/$DISPLAY_NAME"
 
 # Execute the actual command with all arguments
-"$EXECUTABLE_PATH" "$@"
+{exec_line}
 EXIT_CODE=$?
 
 # Record end time and calculate duration
@@ -149,10 +312,36 @@ exit $EXIT_CODE
 "#,
             container_name = container_name,
             display_name = display_name,
-            executable_path = executable_path.display()
+            quoted_container_name = posix_quote(container_name),
+            quoted_display_name = posix_quote(display_name),
+            quoted_executable_path = posix_quote(&executable_path.to_string_lossy()),
+            exec_line = exec_line,
+            recursion_guard = recursion_guard,
+            prelude = prelude
         )
     }
 
+    /// Reads the header of a wrapper; `None` for files wrappy did not generate.
+    pub fn read_metadata(path: &Path) -> Option<WrapperMetadata> {
+        let content = fs::read_to_string(path).ok()?;
+        if !content.contains(WRAPPER_MARKER) {
+            return None;
+        }
+
+        // Wrappers written before values were single-quoted use double quotes
+        let value = |key: &str| {
+            content.lines().find_map(|line| {
+                let word = line.strip_prefix(key)?.strip_prefix('=')?;
+                posix_unquote(word).or_else(|| word.strip_prefix('"')?.strip_suffix('"').map(str::to_string))
+            })
+        };
+        Some(WrapperMetadata {
+            container: value("CONTAINER_NAME")?,
+            display_name: value("DISPLAY_NAME").unwrap_or_default(),
+            executable_path: PathBuf::from(value("EXECUTABLE_PATH")?),
+        })
+    }
+
     /// Lists all wrapper scripts in the target directory.
     pub fn list_wrappers(&self) -> ContainerResult<Vec<String>> {
         if !self.target_dir.exists() {
@@ -176,7 +365,7 @@ exit $EXIT_CODE
             })?.is_file() {
                 // Check if it's a wrappy wrapper by reading first few lines
                 if let Ok(content) = fs::read_to_string(entry.path()) {
-                    if content.contains("# Wrappy container wrapper") {
+                    if content.contains(WRAPPER_MARKER) {
                         if let Some(name) = entry.file_name().to_str() {
                             wrappers.push(name.to_string());
                         }
@@ -189,3 +378,336 @@ exit $EXIT_CODE
         Ok(wrappers)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn binding(declaration: serde_json::Value) -> ExecutableBinding {
+        serde_json::from_value(declaration).unwrap()
+    }
+
+    /// Runs a wrapper and returns what the program printed, without the start and finish banners.
+    fn run(wrapper: &Path, args: &[&str]) -> Vec<String> {
+        let output = Command::new(wrapper).args(args).env_remove("WRAPPY_ACTIVE_TOOL").output().unwrap();
+        run_output(&output.stdout)
+    }
+
+    fn run_output(stdout: &[u8]) -> Vec<String> {
+        String::from_utf8_lossy(stdout)
+            .lines()
+            .filter(|line| !line.starts_with(['🚀', '✅', '❌']))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// argv[0] of the process bash becomes, read back from /proc since `$0` of a script
+    /// is always its path.
+    const PRINT_ARGV0: &str = r#"tr '\0' '\n' < /proc/$$/cmdline | head -n 1"#;
+
+    #[test]
+    fn preserve_mode_passes_the_wrapper_name_as_argv0() {
+        let dir = TempDir::new().unwrap();
+        let generator = WrapperGenerator::new(dir.path().to_path_buf());
+        let executable = binding(json!({ "source": "bin/bash", "target": "tool", "argv0": "preserve" }));
+
+        let wrapper = generator.create_wrapper("tool", "demo", dir.path(), &executable, Path::new("/bin/bash")).unwrap();
+
+        assert_eq!(run(&wrapper, &["-c", PRINT_ARGV0]), vec![wrapper.to_string_lossy().to_string()]);
+    }
+
+    #[test]
+    fn target_mode_passes_the_executable_path_as_argv0() {
+        let dir = TempDir::new().unwrap();
+        let generator = WrapperGenerator::new(dir.path().to_path_buf());
+        let executable = binding(json!({ "source": "bin/bash", "target": "tool" }));
+
+        let wrapper = generator.create_wrapper("tool", "demo", dir.path(), &executable, Path::new("/bin/bash")).unwrap();
+
+        assert_eq!(run(&wrapper, &["-c", PRINT_ARGV0]), vec!["/bin/bash".to_string()]);
+    }
+
+    #[test]
+    fn both_modes_keep_the_exit_code_of_the_program() {
+        let dir = TempDir::new().unwrap();
+        let generator = WrapperGenerator::new(dir.path().to_path_buf());
+        for mode in ["preserve", "target"] {
+            let executable = binding(json!({ "source": "bin/bash", "target": "tool", "argv0": mode }));
+            let wrapper = generator.create_wrapper("tool", "demo", dir.path(), &executable, Path::new("/bin/bash")).unwrap();
+
+            let status = Command::new(&wrapper).args(["-c", "exit 7"]).output().unwrap().status;
+
+            assert_eq!(status.code(), Some(7), "argv0 mode {}", mode);
+        }
+    }
+
+    /// Binding whose fixed arguments make bash print every argument it receives and `$GREETING`.
+    fn printing_binding() -> ExecutableBinding {
+        binding(json!({
+            "source": "bin/bash",
+            "target": "tool",
+            "args_prepend": ["-c", "printf '[%s]\\n' \"$@\"; printf 'env=[%s]\\n' \"$GREETING\"", "bash", "--profile-dir", "a b"],
+            "env": { "GREETING": "it's $HOME `id` \"quoted\"" },
+        }))
+    }
+
+    #[test]
+    fn injected_arguments_and_environment_reach_the_program_exactly() {
+        let dir = TempDir::new().unwrap();
+        let generator = WrapperGenerator::new(dir.path().to_path_buf());
+        let wrapper = generator.create_wrapper("tool", "demo", dir.path(), &printing_binding(), Path::new("/bin/bash")).unwrap();
+
+        let printed = run(&wrapper, &["two  spaces", "it's", "$HOME", "`id`", "\"q\"", ""]);
+
+        assert_eq!(
+            printed,
+            [
+                "[--profile-dir]",
+                "[a b]",
+                "[two  spaces]",
+                "[it's]",
+                "[$HOME]",
+                "[`id`]",
+                "[\"q\"]",
+                "[]",
+                "env=[it's $HOME `id` \"quoted\"]",
+            ]
+        );
+    }
+
+    #[test]
+    fn regenerating_from_the_serialized_binding_keeps_the_injection() {
+        let executable = printing_binding();
+        let reloaded: ExecutableBinding = serde_json::from_value(serde_json::to_value(&executable).unwrap()).unwrap();
+        let render = |executable: &ExecutableBinding| {
+            WrapperGenerator::render_wrapper("tool", "demo", Path::new("/opt/demo"), executable, Path::new("/bin/bash")).unwrap()
+        };
+
+        let script = render(&reloaded);
+
+        assert_eq!(script, render(&executable));
+        assert!(script.contains("export GREETING='it'\\''s $HOME `id` \"quoted\"'\n"), "{}", script);
+        assert!(script.contains("set -- '-c' "), "{}", script);
+    }
+
+    fn executable_script(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+        fs::set_permissions(path, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+    }
+
+    /// Runs `wrapper` with only `path` and the system directories on PATH, killing it
+    /// when it has not finished after ten seconds, as a recursing wrapper never does.
+    fn run_with_path(wrapper: &Path, path: &Path) -> (Option<i32>, Vec<String>) {
+        let mut child = Command::new(wrapper)
+            .arg("a b")
+            .env("PATH", format!("{}:/usr/bin:/bin", path.display()))
+            .env_remove("WRAPPY_ACTIVE_TOOL")
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while child.try_wait().unwrap().is_none() {
+            if std::time::Instant::now() > deadline {
+                child.kill().unwrap();
+                panic!("the wrapper is still running after 10s, probably recursing");
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        let output = child.wait_with_output().unwrap();
+        (output.status.code(), run_output(&output.stdout))
+    }
+
+    #[test]
+    fn a_script_calling_its_own_command_name_runs_the_next_one_on_path() {
+        let dir = TempDir::new().unwrap();
+        let bin = dir.path().join("bin");
+        let system = dir.path().join("system");
+        let executable = dir.path().join("container/bin/tool");
+        executable_script(&executable, "#!/bin/sh\necho \"shim $1\"\nexec tool \"$@\"\n");
+        executable_script(&system.join("tool"), "#!/bin/sh\necho \"system $1\"\n");
+        fs::create_dir_all(&bin).unwrap();
+        let generator = WrapperGenerator::new(bin.clone());
+        let wrapper = generator
+            .create_wrapper("tool", "demo", dir.path(), &binding(json!({ "source": "bin/tool", "target": "tool" })), &executable)
+            .unwrap();
+
+        let (code, printed) = run_with_path(&wrapper, &PathBuf::from(format!("{}:{}", bin.display(), system.display())));
+
+        assert_eq!(code, Some(0));
+        assert_eq!(printed, ["shim a b", "system a b"]);
+    }
+
+    #[test]
+    fn internal_lookups_find_the_container_binaries_before_the_wrapper() {
+        let dir = TempDir::new().unwrap();
+        let bin = dir.path().join("bin");
+        let container_bin = dir.path().join("container/bin");
+        executable_script(&container_bin.join("tool-main"), "#!/bin/sh\nexec helper \"$@\"\n");
+        executable_script(&container_bin.join("helper"), "#!/bin/sh\necho \"container helper $1\"\n");
+        executable_script(&bin.join("helper"), "#!/bin/sh\necho \"other helper $1\"\n");
+        let generator = WrapperGenerator::new(bin.clone());
+        let executable = binding(json!({ "source": "bin/tool-main", "target": "tool" }));
+        let wrapper = generator
+            .create_wrapper("tool", "demo", dir.path(), &executable, &container_bin.join("tool-main"))
+            .unwrap();
+
+        let (code, printed) = run_with_path(&wrapper, &bin);
+
+        assert_eq!(code, Some(0));
+        assert_eq!(printed, ["container helper a b"]);
+    }
+
+    #[test]
+    fn a_nested_call_without_another_command_on_path_fails_instead_of_recursing() {
+        let dir = TempDir::new().unwrap();
+        let bin = dir.path().join("bin");
+        let executable = dir.path().join("container/bin/tool");
+        executable_script(&executable, "#!/bin/sh\nexec tool \"$@\"\n");
+        fs::create_dir_all(&bin).unwrap();
+        let generator = WrapperGenerator::new(bin.clone());
+        let wrapper = generator
+            .create_wrapper("tool", "demo", dir.path(), &binding(json!({ "source": "bin/tool", "target": "tool" })), &executable)
+            .unwrap();
+
+        let (code, _) = run_with_path(&wrapper, &bin);
+
+        assert_eq!(code, Some(127));
+    }
+
+    #[test]
+    fn a_nested_call_skips_the_wrapper_and_executable_directories_however_they_are_spelled() {
+        let dir = TempDir::new().unwrap();
+        let bin = dir.path().join("bin");
+        let container_bin = dir.path().join("container/bin");
+        let system = dir.path().join("system");
+        let executable = container_bin.join("tool-main");
+        executable_script(&executable, "#!/bin/sh\necho \"container $1\"\n");
+        executable_script(&container_bin.join("tool"), "#!/bin/sh\necho \"container tool $1\"\n");
+        executable_script(&system.join("tool"), "#!/bin/sh\necho \"system $1\"\n");
+        fs::create_dir_all(&bin).unwrap();
+        let generator = WrapperGenerator::new(bin.clone());
+        let wrapper = generator
+            .create_wrapper("tool", "demo", dir.path(), &binding(json!({ "source": "bin/tool-main", "target": "tool" })), &executable)
+            .unwrap();
+        let path = format!("{}/:{}:{}/:/usr/bin:/bin", bin.display(), container_bin.display(), system.display());
+
+        // As a program started by the wrapper would run it: the variable is already set
+        let output = Command::new(&wrapper).arg("a b").env("PATH", path).env("WRAPPY_ACTIVE_TOOL", "1").output().unwrap();
+
+        assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "system a b\n", "no banner and neither the executable nor its neighbour");
+    }
+
+    #[test]
+    fn a_wrapper_rewritten_while_running_is_always_a_complete_version() {
+        let dir = TempDir::new().unwrap();
+        let programs = TempDir::new().unwrap();
+        let versions: Vec<PathBuf> = ["old", "new"]
+            .iter()
+            .map(|version| {
+                let path = programs.path().join(version);
+                fs::write(&path, format!("#!/bin/sh\necho {}\n", version)).unwrap();
+                fs::set_permissions(&path, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+                path
+            })
+            .collect();
+        let generator = WrapperGenerator::new(dir.path().to_path_buf());
+        let executable = binding(json!({ "source": "bin/tool", "target": "tool" }));
+        let wrapper = generator.create_wrapper("tool", "demo", dir.path(), &executable, &versions[0]).unwrap();
+        // A separate process runs the wrapper, as a user's shell would: threads of this
+        // process would inherit the writer's temp file across fork and hit ETXTBSY
+        let stop = programs.path().join("stop");
+        let reader = Command::new("/bin/sh")
+            .args(["-c", r#"n=0; while [ ! -e "$2" ] || [ $n -lt 20 ]; do "$1"; echo "exit $?"; n=$((n+1)); done"#, "sh"])
+            .arg(&wrapper)
+            .arg(&stop)
+            .env_remove("WRAPPY_ACTIVE_TOOL")
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        for round in 0..300 {
+            generator.create_wrapper("tool", "demo", dir.path(), &executable, &versions[round % 2]).unwrap();
+        }
+        fs::write(&stop, "").unwrap();
+        let output = reader.wait_with_output().unwrap();
+
+        let lines = run_output(&output.stdout);
+        let runs: Vec<&[String]> = lines.split_inclusive(|line| line.starts_with("exit ")).collect();
+        assert!(runs.len() >= 20, "only {} runs", runs.len());
+        for run in runs {
+            assert!(
+                run == ["old", "exit 0"] || run == ["new", "exit 0"],
+                "a run saw a partial wrapper: {:?}",
+                run
+            );
+        }
+
+        let left: Vec<_> = fs::read_dir(dir.path()).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(left, vec![std::ffi::OsString::from("tool")], "temp files left behind");
+    }
+
+    /// Spaces, both quotes, command substitutions and UTF-8, none of which may be expanded.
+    const HOSTILE: &str = "sp ace 'single' \"double\" $HOME $(touch pwned) `touch pwned` ünïcødé €";
+
+    #[test]
+    fn hostile_names_paths_arguments_and_environment_reach_the_program_verbatim() {
+        let dir = TempDir::new().unwrap();
+        let bin = dir.path().join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        let container = dir.path().join(HOSTILE);
+        let executable_path = container.join("bin").join(HOSTILE);
+        executable_script(&executable_path, "#!/bin/sh\nprintf '[%s]\\n' \"$@\"\nprintf 'env=[%s]\\n' \"$GREETING\"\n");
+        let executable = binding(json!({
+            "source": format!("bin/{}", HOSTILE),
+            "target": "tool",
+            "args_prepend": [HOSTILE],
+            "env": { "GREETING": HOSTILE },
+        }));
+        let wrapper_name = "tööl $(touch pwned)";
+        let generator = WrapperGenerator::new(bin);
+        let wrapper = generator.create_wrapper(wrapper_name, HOSTILE, &container, &executable, &executable_path).unwrap();
+
+        let output = Command::new(&wrapper).arg(HOSTILE).current_dir(dir.path()).output().unwrap();
+
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let expected = format!("[{}]", HOSTILE);
+        assert_eq!(run_output(&output.stdout), [expected.clone(), expected.clone(), format!("env={}", expected)]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains(&format!("Starting {}/{}", HOSTILE, wrapper_name)), "{}", stdout);
+        assert!(!dir.path().join("pwned").exists(), "a command substitution ran");
+        let metadata = WrapperGenerator::read_metadata(&wrapper).unwrap();
+        assert_eq!(metadata.container, HOSTILE);
+        assert_eq!(metadata.executable_path, executable_path);
+    }
+
+    #[test]
+    fn newlines_anywhere_in_a_wrapper_are_refused_before_writing() {
+        let dir = TempDir::new().unwrap();
+        let generator = WrapperGenerator::new(dir.path().to_path_buf());
+        let plain = binding(json!({ "source": "bin/tool", "target": "tool" }));
+        let path = Path::new("/opt/demo/bin/tool");
+        let with_arg = binding(json!({ "source": "bin/tool", "target": "tool", "args_prepend": ["a\nb"] }));
+        let with_env = binding(json!({ "source": "bin/tool", "target": "tool", "env": { "GREETING": "a\nb" } }));
+        let with_display = binding(json!({ "source": "bin/tool", "target": "tool", "display_name": "a\nb" }));
+        let attempts = [
+            generator.create_wrapper("tool", "de\nmo", Path::new("/opt/demo"), &plain, path),
+            generator.create_wrapper("to\nol", "demo", Path::new("/opt/demo"), &plain, path),
+            generator.create_wrapper("tool", "demo", Path::new("/opt/de\nmo"), &plain, path),
+            generator.create_wrapper("tool", "demo", Path::new("/opt/demo"), &plain, Path::new("/opt/demo/bin/to\nol")),
+            generator.create_wrapper("tool", "demo", Path::new("/opt/demo"), &with_arg, path),
+            generator.create_wrapper("tool", "demo", Path::new("/opt/demo"), &with_env, path),
+            generator.create_wrapper("tool", "demo", Path::new("/opt/demo"), &with_display, path),
+        ];
+
+        for (index, attempt) in attempts.into_iter().enumerate() {
+            assert!(attempt.is_err(), "attempt {} was accepted", index);
+        }
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0, "a wrapper was written");
+    }
+}
@@ -0,0 +1,147 @@
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::features::changelog::{Changelog, ChangelogEntry};
+use crate::features::Version;
+use crate::shared::error::{ContainerError, ContainerResult};
+
+/// File name looked up in the container root.
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+pub struct ChangelogService;
+
+impl ChangelogService {
+    /// Loads `CHANGELOG.md` from a container directory; a missing file is not an error.
+    pub fn load(container_path: &Path) -> ContainerResult<Option<Changelog>> {
+        let path = container_path.join(CHANGELOG_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| ContainerError::IoError {
+            path: path.clone(),
+            source: e,
+        })?;
+        Ok(Some(Self::parse(&content)))
+    }
+
+    /// Splits Markdown into sections at headings that name a version
+    /// (`## 1.2.0`, `## [v1.2.0] - 2024-01-01`). Other headings such as
+    /// `# Changelog` or `## Unreleased` close the current section and are skipped,
+    /// and duplicate versions keep their first section. Lines such as `#123 fixed`
+    /// are not headings and stay in the body.
+    pub fn parse(markdown: &str) -> Changelog {
+        let any_heading_regex = Regex::new(r"^#{1,6}(\s|$)").expect("heading regex is valid");
+        let heading_regex = Regex::new(r"^#{1,6}\s+\[?v?(\d+\.\d+\.\d+)\]?")
+            .expect("changelog heading regex is valid");
+
+        let mut entries: Vec<ChangelogEntry> = Vec::new();
+        let mut current: Option<ChangelogEntry> = None;
+
+        for line in markdown.lines() {
+            if !any_heading_regex.is_match(line) {
+                if let Some(entry) = current.as_mut() {
+                    entry.body.push_str(line);
+                    entry.body.push('\n');
+                }
+                continue;
+            }
+
+            entries.extend(current.take());
+            current = heading_regex
+                .captures(line)
+                .and_then(|captures| Version::new(&captures[1]).ok())
+                .map(|version| ChangelogEntry {
+                    version,
+                    heading: line.trim_start_matches('#').trim().to_string(),
+                    body: String::new(),
+                });
+        }
+        entries.extend(current);
+
+        for entry in &mut entries {
+            entry.body = entry.body.trim().to_string();
+        }
+        entries.sort_by(|a, b| b.version.cmp(&a.version));
+        entries.dedup_by(|a, b| a.version == b.version);
+
+        Changelog { entries }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn version(value: &str) -> Version {
+        Version::new(value).unwrap()
+    }
+
+    fn versions(entries: &[&ChangelogEntry]) -> Vec<String> {
+        entries.iter().map(|entry| entry.version.to_string()).collect()
+    }
+
+    const OUT_OF_ORDER: &str = "# Changelog\n\
+        \n\
+        ## Unreleased\n\
+        - not shipped yet\n\
+        \n\
+        ## 1.1.0\n\
+        - second\n\
+        \n\
+        ## [v2.0.0] - 2024-05-01\n\
+        - breaking\n\
+        #42 fixed along the way\n\
+        \n\
+        ### Notes\n\
+        - not part of 2.0.0\n\
+        \n\
+        ## 1.0.0\n\
+        - first\n\
+        \n\
+        ## 1.1.0\n\
+        - duplicate, ignored\n";
+
+    #[test]
+    fn sections_are_ordered_newest_first_whatever_the_file_order() {
+        let changelog = ChangelogService::parse(OUT_OF_ORDER);
+
+        let entries: Vec<&ChangelogEntry> = changelog.entries.iter().collect();
+        assert_eq!(versions(&entries), ["2.0.0", "1.1.0", "1.0.0"]);
+        assert_eq!(changelog.latest().unwrap().heading, "[v2.0.0] - 2024-05-01");
+        assert_eq!(changelog.entries[0].body, "- breaking\n#42 fixed along the way");
+        assert_eq!(changelog.entries[1].body, "- second");
+    }
+
+    #[test]
+    fn between_skips_versions_without_a_section() {
+        let changelog = ChangelogService::parse(OUT_OF_ORDER);
+
+        assert_eq!(versions(&changelog.between(&version("1.0.0"), &version("2.0.0"))), ["2.0.0", "1.1.0"]);
+        assert_eq!(versions(&changelog.between(&version("1.0.5"), &version("1.9.0"))), ["1.1.0"]);
+        assert!(changelog.between(&version("2.0.0"), &version("3.0.0")).is_empty());
+        assert!(changelog.between(&version("1.2.0"), &version("1.9.0")).is_empty());
+    }
+
+    #[test]
+    fn a_changelog_without_version_headings_is_empty() {
+        let changelog = ChangelogService::parse("# Changelog\n\nJust prose.\n## Unreleased\n- soon\n");
+
+        assert!(changelog.is_empty());
+        assert!(changelog.latest().is_none());
+    }
+
+    #[test]
+    fn load_reads_the_file_and_tolerates_its_absence() {
+        let dir = TempDir::new().unwrap();
+        assert!(ChangelogService::load(dir.path()).unwrap().is_none());
+
+        fs::write(dir.path().join(CHANGELOG_FILE), "## 0.1.0\n- hello\n").unwrap();
+        let changelog = ChangelogService::load(dir.path()).unwrap().unwrap();
+
+        assert_eq!(changelog.latest().unwrap().body, "- hello");
+    }
+}
@@ -0,0 +1,34 @@
+use crate::features::Version;
+
+/// Changelog section for one version: the heading line and the text under it.
+#[derive(Debug, Clone)]
+pub struct ChangelogEntry {
+    pub version: Version,
+    pub heading: String,
+    pub body: String,
+}
+
+/// Parsed changelog with entries ordered newest first, whatever order the file used.
+#[derive(Debug, Clone, Default)]
+pub struct Changelog {
+    pub entries: Vec<ChangelogEntry>,
+}
+
+impl Changelog {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn latest(&self) -> Option<&ChangelogEntry> {
+        self.entries.first()
+    }
+
+    /// Entries newer than `installed` up to and including `target`, newest first.
+    /// Versions without a section are simply absent from the result.
+    pub fn between(&self, installed: &Version, target: &Version) -> Vec<&ChangelogEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| &entry.version > installed && &entry.version <= target)
+            .collect()
+    }
+}
@@ -0,0 +1,485 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+use crate::features::container::{Container, ExportService};
+use crate::shared::clock;
+use crate::shared::digest::Sha256;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::host;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::staging::StagingArea;
+
+/// Attestation `container export --attest` embeds next to the container's own files.
+pub const ATTESTATION_FILE: &str = ".wrappy-attestation.json";
+
+/// Version 1 was signed with a symmetric key and is no longer accepted.
+const ATTESTATION_FORMAT_VERSION: u32 = 2;
+
+/// Ed25519 signatures in the SSH signature format, made and checked by `ssh-keygen -Y`.
+const SIGNATURE_ALGORITHM: &str = "sshsig-ed25519";
+
+/// Namespace of the SSH signature, so a signature made for something else never passes.
+const SIGNATURE_NAMESPACE: &str = "wrappy-attestation";
+
+/// What an attestation vouches for. The content digest covers exactly what an
+/// export archives, so it does not depend on modes, owners or timestamps.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttestationSubject {
+    pub name: String,
+    pub version: String,
+    pub content_sha256: String,
+}
+
+/// The signed part of an attestation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttestationStatement {
+    pub format_version: u32,
+    pub subject: AttestationSubject,
+    /// `user@host` that exported the container
+    pub builder: String,
+    /// `HEAD` of the git repository the container was exported from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_commit: Option<String>,
+    pub wrappy_version: String,
+    pub attested_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttestationSignature {
+    pub algorithm: String,
+    /// Public key of the signer as in an OpenSSH `.pub` file, without the comment
+    pub public_key: String,
+    /// First 16 hex digits of the SHA-256 of `public_key`
+    pub key_id: String,
+    /// Armored SSH signature over the statement
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attestation {
+    pub statement: AttestationStatement,
+    pub signature: AttestationSignature,
+}
+
+/// What checking the attestation of a container directory found.
+#[derive(Debug, Clone)]
+pub enum AttestationCheck {
+    Verified(Box<Attestation>),
+    Missing,
+    /// The reason it cannot be trusted
+    Invalid(String),
+}
+
+/// Signs and verifies attestations. The statement, serialized as compact JSON with
+/// sorted keys, is signed with the Ed25519 key in `~/.config/wrappy/keys/attestation`.
+/// Verifying needs only public keys: the local `attestation.pub` and the keys listed
+/// in `~/.config/wrappy/keys/trusted_signers`, one OpenSSH public key per line.
+pub struct AttestationService;
+
+impl AttestationService {
+    /// Attests the current content of `container`, creating the signing key on first use.
+    pub fn attest(container: &Container, paths: &WrappyPaths) -> ContainerResult<Attestation> {
+        let public_key = Self::signing_key(paths)?;
+        let statement = AttestationStatement {
+            format_version: ATTESTATION_FORMAT_VERSION,
+            subject: AttestationSubject {
+                name: container.name().to_string(),
+                version: container.version().to_string(),
+                content_sha256: Self::content_digest(container)?,
+            },
+            builder: Self::builder(),
+            source_commit: Self::source_commit(&container.path),
+            wrappy_version: env!("CARGO_PKG_VERSION").to_string(),
+            attested_at: clock::now(),
+        };
+        let signed = serde_json::to_vec(&serde_json::to_value(&statement)?)?;
+        let key_file = paths.attestation_key_file();
+        let args = ["-Y".as_ref(), "sign".as_ref(), "-n".as_ref(), SIGNATURE_NAMESPACE.as_ref(), "-f".as_ref(), key_file.as_os_str()];
+        let output = Self::ssh_keygen(&args, &signed)?;
+        let signature = AttestationSignature {
+            algorithm: SIGNATURE_ALGORITHM.to_string(),
+            key_id: Self::key_id(&public_key),
+            public_key,
+            value: String::from_utf8_lossy(&output.stdout).into_owned(),
+        };
+        Ok(Attestation { statement, signature })
+    }
+
+    /// Checks the attestation in the directory of `container` against its
+    /// content and the trusted keys. Never fails: problems come back as `Invalid`.
+    pub fn verify(container: &Container, paths: &WrappyPaths) -> AttestationCheck {
+        let path = container.path.join(ATTESTATION_FILE);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return AttestationCheck::Missing,
+            Err(e) => return AttestationCheck::Invalid(format!("cannot read {}: {}", path.display(), e)),
+        };
+        match Self::check(container, paths, &content) {
+            Ok(attestation) => AttestationCheck::Verified(Box::new(attestation)),
+            Err(reason) => AttestationCheck::Invalid(reason),
+        }
+    }
+
+    /// SHA-256 over the sorted paths an export would archive, with the content of
+    /// files, whether they are executable and the target of links. Persistent data is
+    /// never covered, so exporting it with `--include-data` keeps the attestation valid.
+    pub fn content_digest(container: &Container) -> ContainerResult<String> {
+        let mut entries = ExportService::collect_entries(&container.path, &container.persistent_paths())?;
+        entries.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+
+        let mut hasher = Sha256::new();
+        for (relative, path) in entries {
+            let Some(path) = path else {
+                continue;
+            };
+            let io_error = |source: io::Error| ContainerError::IoError {
+                path: path.clone(),
+                source,
+            };
+            let metadata = fs::symlink_metadata(&path).map_err(io_error)?;
+            let payload = if metadata.file_type().is_symlink() {
+                format!("l\0{}", fs::read_link(&path).map_err(io_error)?.to_string_lossy())
+            } else if metadata.is_dir() {
+                "d\0".to_string()
+            } else {
+                let mut file_hasher = Sha256::new();
+                io::copy(&mut File::open(&path).map_err(io_error)?, &mut file_hasher).map_err(io_error)?;
+                let kind = if metadata.mode() & 0o111 != 0 { "x" } else { "f" };
+                format!("{}\0{}", kind, file_hasher.finish_hex())
+            };
+            hasher.update(relative.as_bytes());
+            hasher.update(b"\0");
+            hasher.update(payload.as_bytes());
+            hasher.update(b"\n");
+        }
+        Ok(hasher.finish_hex())
+    }
+
+    fn check(container: &Container, paths: &WrappyPaths, content: &str) -> Result<Attestation, String> {
+        let document: Value = serde_json::from_str(content).map_err(|e| format!("not valid JSON: {}", e))?;
+        let format_version = document["statement"]["format_version"].as_u64().unwrap_or_default();
+        if format_version != u64::from(ATTESTATION_FORMAT_VERSION) {
+            return Err(format!("format version {} is not supported; export it again with --attest", format_version));
+        }
+        let attestation: Attestation =
+            serde_json::from_value(document.clone()).map_err(|e| format!("not an attestation: {}", e))?;
+        let signature = &attestation.signature;
+        if signature.algorithm != SIGNATURE_ALGORITHM {
+            return Err(format!("signature algorithm '{}' is not supported", signature.algorithm));
+        }
+
+        let public_key = Self::key_text(&signature.public_key).ok_or("the signature has no public key")?;
+        let trusted = Self::trusted_keys(paths).map_err(|e| e.to_string())?;
+        if !trusted.contains(&public_key) {
+            return Err(format!(
+                "signed with key {}, which is neither the local key nor listed in {}",
+                Self::key_id(&public_key),
+                paths.trusted_signers_file().display()
+            ));
+        }
+        // Verify the statement as written, so fields this wrappy does not know are covered too
+        let signed = serde_json::to_vec(&document["statement"]).map_err(|e| e.to_string())?;
+        Self::check_signature(paths, &public_key, &signature.value, &signed)?;
+
+        let content_sha256 = Self::content_digest(container).map_err(|e| e.to_string())?;
+        if content_sha256 != attestation.statement.subject.content_sha256 {
+            return Err("the files changed since the container was attested".to_string());
+        }
+        Ok(attestation)
+    }
+
+    /// Lets `ssh-keygen` check `signature` over `signed` against `public_key` alone.
+    fn check_signature(paths: &WrappyPaths, public_key: &str, signature: &str, signed: &[u8]) -> Result<(), String> {
+        let scratch = StagingArea::create_in(&paths.cache_dir.join("attestation")).map_err(|e| e.to_string())?;
+        let allowed = scratch.join("allowed_signers");
+        let signature_file = scratch.join("signature");
+        fs::write(&allowed, format!("{} {}\n", SIGNATURE_NAMESPACE, public_key))
+            .and_then(|_| fs::write(&signature_file, signature))
+            .map_err(|e| e.to_string())?;
+        let args = [
+            "-Y".as_ref(),
+            "verify".as_ref(),
+            "-n".as_ref(),
+            SIGNATURE_NAMESPACE.as_ref(),
+            "-I".as_ref(),
+            SIGNATURE_NAMESPACE.as_ref(),
+            "-f".as_ref(),
+            allowed.as_os_str(),
+            "-s".as_ref(),
+            signature_file.as_os_str(),
+        ];
+        match Self::ssh_keygen(&args, signed) {
+            Ok(_) => Ok(()),
+            Err(ContainerError::Runtime { .. }) => Err("the signature does not match the statement".to_string()),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+
+    /// The local public key, creating the key pair with `ssh-keygen` if there is none yet.
+    fn signing_key(paths: &WrappyPaths) -> ContainerResult<String> {
+        let path = paths.attestation_key_file();
+        if !path.exists() {
+            if let Some(parent) = path.parent() {
+                filesystem::create_state_dir(parent)?;
+            }
+            let args = [
+                "-q".as_ref(),
+                "-t".as_ref(),
+                "ed25519".as_ref(),
+                "-N".as_ref(),
+                "".as_ref(),
+                "-C".as_ref(),
+                SIGNATURE_NAMESPACE.as_ref(),
+                "-f".as_ref(),
+                path.as_os_str(),
+            ];
+            Self::ssh_keygen(&args, &[])?;
+        }
+        Self::local_public_key(paths)?.ok_or_else(|| ContainerError::InvalidConfig {
+            path: paths.attestation_public_key_file(),
+            reason: "expected an OpenSSH public key".to_string(),
+        })
+    }
+
+    fn local_public_key(paths: &WrappyPaths) -> ContainerResult<Option<String>> {
+        let path = paths.attestation_public_key_file();
+        match fs::read_to_string(&path) {
+            Ok(content) => Ok(Self::key_text(&content)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ContainerError::IoError { path, source: e }),
+        }
+    }
+
+    /// The local public key and every key in `trusted_signers`; blank lines and `#` comments are skipped.
+    fn trusted_keys(paths: &WrappyPaths) -> ContainerResult<Vec<String>> {
+        let mut keys: Vec<String> = Self::local_public_key(paths)?.into_iter().collect();
+        let path = paths.trusted_signers_file();
+        match fs::read_to_string(&path) {
+            Ok(content) => keys.extend(
+                content
+                    .lines()
+                    .filter(|line| !line.trim_start().starts_with('#'))
+                    .filter_map(Self::key_text),
+            ),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(ContainerError::IoError { path, source: e }),
+        }
+        Ok(keys)
+    }
+
+    /// `<type> <base64>` of an OpenSSH public key line, dropping its comment.
+    fn key_text(line: &str) -> Option<String> {
+        let mut fields = line.split_whitespace();
+        match (fields.next(), fields.next()) {
+            (Some(key_type), Some(key)) if key_type.starts_with("ssh-") => Some(format!("{} {}", key_type, key)),
+            _ => None,
+        }
+    }
+
+    fn key_id(public_key: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(public_key.as_bytes());
+        hasher.finish_hex()[..16].to_string()
+    }
+
+    /// Runs `ssh-keygen` with `input` on stdin; a non-zero exit is a `Runtime` error with its stderr.
+    fn ssh_keygen(args: &[&std::ffi::OsStr], input: &[u8]) -> ContainerResult<Output> {
+        let spawn_error = |e| ContainerError::IoError {
+            path: "ssh-keygen".into(),
+            source: e,
+        };
+        let mut child = Command::new("ssh-keygen")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input).map_err(spawn_error)?;
+        }
+        let output = child.wait_with_output().map_err(spawn_error)?;
+        if !output.status.success() {
+            return Err(ContainerError::Runtime {
+                message: format!("ssh-keygen failed: {}", String::from_utf8_lossy(&output.stderr).trim()),
+            });
+        }
+        Ok(output)
+    }
+
+    fn builder() -> String {
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("LOGNAME"))
+            .unwrap_or_else(|_| "unknown".to_string());
+        format!("{}@{}", user, host::hostname())
+    }
+
+    /// `HEAD` of the git work tree `root` is in; `None` without git or a repository.
+    fn source_commit(root: &Path) -> Option<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        let commit = String::from_utf8(output.stdout).ok()?.trim().to_string();
+        (output.status.success() && !commit.is_empty()).then_some(commit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::ContainerService;
+    use crate::shared::test_support::{fixture_container, TempHome};
+
+    fn attested(home: &TempHome) -> (Container, Attestation) {
+        let container = ContainerService::load_from_directory(fixture_container(home.path(), "web", "1.0.0")).unwrap();
+        let attestation = AttestationService::attest(&container, &home.paths()).unwrap();
+        write(&container, &serde_json::to_value(&attestation).unwrap());
+        (container, attestation)
+    }
+
+    fn write(container: &Container, attestation: &Value) {
+        fs::write(container.path.join(ATTESTATION_FILE), serde_json::to_string_pretty(attestation).unwrap()).unwrap();
+    }
+
+    fn invalid_reason(check: AttestationCheck) -> String {
+        match check {
+            AttestationCheck::Invalid(reason) => reason,
+            AttestationCheck::Verified(_) => panic!("the attestation was accepted"),
+            AttestationCheck::Missing => panic!("the attestation is missing"),
+        }
+    }
+
+    #[test]
+    fn an_attestation_round_trips_with_the_public_key_only() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        let (container, attestation) = attested(&home);
+        let private_key_mode = fs::metadata(paths.attestation_key_file()).unwrap().mode();
+        fs::remove_file(paths.attestation_key_file()).unwrap();
+
+        let check = AttestationService::verify(&container, &paths);
+
+        let AttestationCheck::Verified(verified) = check else {
+            panic!("not verified: {:?}", check);
+        };
+        assert_eq!(verified.statement.subject, attestation.statement.subject);
+        assert_eq!(attestation.signature.algorithm, SIGNATURE_ALGORITHM);
+        assert!(attestation.signature.public_key.starts_with("ssh-ed25519 "));
+        assert!(attestation.signature.value.starts_with("-----BEGIN SSH SIGNATURE-----"));
+        assert_eq!(private_key_mode & 0o077, 0, "the private key is readable by others");
+    }
+
+    #[test]
+    fn a_tampered_statement_or_signature_is_rejected() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        let (container, attestation) = attested(&home);
+        let original = serde_json::to_value(&attestation).unwrap();
+        let tampered = |edit: &dyn Fn(&mut Value)| {
+            let mut document = original.clone();
+            edit(&mut document);
+            write(&container, &document);
+            invalid_reason(AttestationService::verify(&container, &paths))
+        };
+
+        let builder = tampered(&|document| document["statement"]["builder"] = "mallory@elsewhere".into());
+        let digest = tampered(&|document| {
+            let digest = AttestationService::content_digest(&container).unwrap();
+            let flipped = if digest.starts_with('0') { '1' } else { '0' };
+            document["statement"]["subject"]["content_sha256"] = format!("{}{}", flipped, &digest[1..]).into()
+        });
+        let unknown_field = tampered(&|document| document["statement"]["note"] = "added later".into());
+        let signature = tampered(&|document| {
+            let value = document["signature"]["value"].as_str().unwrap().replacen("U1NIU0lH", "U1NIU0lI", 1);
+            document["signature"]["value"] = value.into();
+        });
+
+        for reason in [builder, digest, unknown_field, signature] {
+            assert_eq!(reason, "the signature does not match the statement");
+        }
+        write(&container, &original);
+        assert!(matches!(AttestationService::verify(&container, &paths), AttestationCheck::Verified(_)));
+    }
+
+    #[test]
+    fn changed_files_fail_even_with_a_valid_signature() {
+        let home = TempHome::enter();
+        let (container, _) = attested(&home);
+        fs::write(container.path.join("bin/tool"), "#!/bin/sh\necho replaced\n").unwrap();
+
+        let reason = invalid_reason(AttestationService::verify(&container, &home.paths()));
+
+        assert_eq!(reason, "the files changed since the container was attested");
+    }
+
+    #[test]
+    fn a_foreign_key_is_only_trusted_when_listed_in_trusted_signers() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        let (container, attestation) = attested(&home);
+        // Another machine: its own key pair, none of ours
+        fs::remove_file(paths.attestation_key_file()).unwrap();
+        fs::remove_file(paths.attestation_public_key_file()).unwrap();
+        let other = ContainerService::load_from_directory(fixture_container(&home.path().join("other"), "api", "1.0.0")).unwrap();
+        AttestationService::attest(&other, &paths).unwrap();
+
+        let untrusted = invalid_reason(AttestationService::verify(&container, &paths));
+        fs::write(
+            paths.trusted_signers_file(),
+            format!("# build server\n\n{} builder@ci\n", attestation.signature.public_key),
+        )
+        .unwrap();
+        let trusted = AttestationService::verify(&container, &paths);
+
+        assert!(untrusted.contains(&attestation.signature.key_id) && untrusted.contains("trusted_signers"), "{}", untrusted);
+        assert!(matches!(trusted, AttestationCheck::Verified(_)), "{:?}", trusted);
+    }
+
+    #[test]
+    fn a_key_swapped_for_a_trusted_one_does_not_verify() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        let (container, attestation) = attested(&home);
+        fs::rename(paths.attestation_key_file(), home.path().join("first")).unwrap();
+        fs::rename(paths.attestation_public_key_file(), home.path().join("first.pub")).unwrap();
+        let other = ContainerService::load_from_directory(fixture_container(&home.path().join("other"), "api", "1.0.0")).unwrap();
+        let second = AttestationService::attest(&other, &paths).unwrap();
+        let mut document = serde_json::to_value(&attestation).unwrap();
+        // Claims the trusted local key, but the signature was made with the first one
+        document["signature"]["public_key"] = second.signature.public_key.clone().into();
+        write(&container, &document);
+
+        let reason = invalid_reason(AttestationService::verify(&container, &paths));
+
+        assert_eq!(reason, "the signature does not match the statement");
+    }
+
+    #[test]
+    fn symmetric_attestations_and_missing_files_are_reported() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        let (container, attestation) = attested(&home);
+        let mut document = serde_json::to_value(&attestation).unwrap();
+        document["statement"]["format_version"] = 1.into();
+        document["signature"]["algorithm"] = "hmac-sha256".into();
+        write(&container, &document);
+
+        let old = invalid_reason(AttestationService::verify(&container, &paths));
+        fs::remove_file(container.path.join(ATTESTATION_FILE)).unwrap();
+
+        assert!(old.starts_with("format version 1 is not supported"), "{}", old);
+        assert!(matches!(AttestationService::verify(&container, &paths), AttestationCheck::Missing));
+    }
+}
@@ -1,58 +1,2218 @@
-use clap::Subcommand;
+use clap::{Args, Subcommand};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::features::container::{Container, ContainerService};
-use crate::shared::error::ContainerError;
+use crate::features::bindings::{BindingFilter, BindingManager};
+use crate::features::changelog::ChangelogService;
+use crate::features::manifest::{ContainerManifest, ContainerType, ManifestFormat};
+use crate::features::container::{
+    create_missing_config_files, executor, missing_config_files, AttestationCheck, AttestationService, Container,
+    ContainerRegistry, ContainerResolver, ContainerService, ContainerValidationResult, DependencyCheck,
+    DependencyOutcome, EnvironmentConfig, ExportService, InstallMetadata, InstallService, InstalledStatus,
+    InterpreterCheck, MigrationService, OutputMode, PermissionsConfig, PlanFormat, RegistryEntry, ReportFormat,
+    ResolutionCandidate, ScaffoldService, SealService, ServiceRuntime, StateLocation, StateStash, ValidationReport,
+    VariableStore, VerifyMode, VerifyOptions,
+};
+use crate::features::profile::ProfileService;
+use crate::features::query;
+use crate::shared::clock;
+use crate::shared::config::Config;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::path_list;
+use crate::shared::path_sanity::{self, PathIssue};
+use crate::shared::paths::WrappyPaths;
+use crate::shared::portability;
+use crate::shared::prompt;
+use crate::shared::redact;
+use crate::shared::summary::{Reporter, SummaryFormat};
+use crate::shared::{CancellationToken, PrivilegeGuard};
+
+/// Flags of `container run`, shared with the top-level `wrappy run`.
+#[derive(Args)]
+pub struct RunArgs {
+    /// Container name, name@version or path
+    pub container: String,
+
+    /// Script to run
+    #[arg(short, long, default_value = "default")]
+    pub script: String,
+
+    /// Run a script of a declared dependency instead, as `toolchain:compile` (`toolchain` runs its default script)
+    #[arg(long, value_name = "DEPENDENCY:SCRIPT", conflicts_with_all = ["script", "watch"])]
+    pub dep: Option<String>,
+
+    /// Re-run the script when files under these paths change
+    /// (relative paths are resolved against the container's content directory)
+    #[arg(short, long)]
+    pub watch: Vec<PathBuf>,
+
+    /// Kill a still-running script when a change arrives (default in watch mode)
+    #[arg(long, requires = "watch", conflicts_with = "queue")]
+    pub restart: bool,
+
+    /// Let a still-running script finish and run again afterwards
+    #[arg(long, requires = "watch")]
+    pub queue: bool,
+
+    /// Start from an empty environment, passing through only allowed host variables
+    #[arg(long)]
+    pub no_inherit_env: bool,
+
+    /// Print the environment the script would get, with the source of each variable, and exit
+    #[arg(long)]
+    pub show_env: bool,
+
+    /// Print what would be executed (script, interpreter, environment, isolation) without running it
+    #[arg(long, conflicts_with_all = ["watch", "show_env"])]
+    pub dry_run: bool,
+
+    /// Run a script that declares mounts although runs are not sandboxed; the mounts are not applied
+    #[arg(long)]
+    pub allow_unsandboxed_mounts: bool,
+
+    /// Format of the --dry-run plan
+    #[arg(long, value_enum, default_value = "text", requires = "dry_run")]
+    pub format: PlanFormat,
+
+    /// Print secret-looking values in --show-env and --dry-run output instead of masking them
+    #[arg(long)]
+    pub show_secrets: bool,
+
+    /// Where script output goes: `tee:<file>` (terminal and file), `json` (one JSON
+    /// record per line with stream and timestamp) or `quiet` (only the exit status)
+    #[arg(long, value_name = "MODE", default_value = "inherit")]
+    pub output: OutputMode,
+
+    /// Arguments passed to the script
+    #[arg(last = true)]
+    pub args: Vec<String>,
+}
+
+/// Flags of `container exec`, shared with the top-level `wrappy x`.
+#[derive(Args)]
+pub struct ExecArgs {
+    /// Container name, name@version or path
+    pub container: String,
+
+    /// Start from an empty environment, passing through only allowed host variables
+    #[arg(long)]
+    pub no_inherit_env: bool,
+
+    /// Command to run and its arguments
+    #[arg(last = true, required = true)]
+    pub command: Vec<String>,
+}
 
 #[derive(Subcommand)]
 pub enum ContainerCommands {
-    /// Validate container structure in the current or specified directory
+    /// Validate container structure in the current or specified directories
     Validate {
-        /// Directory path to validate (defaults to current directory)
+        /// Container directories to validate (defaults to current directory);
+        /// `-` reads newline-delimited directories from stdin
+        paths: Vec<PathBuf>,
+
+        /// Directories read from stdin are NUL-delimited, e.g. from `find -print0`
+        #[arg(long = "null", short = '0')]
+        null: bool,
+
+        /// Directory path to validate (same as a positional path)
         #[arg(short, long)]
         path: Option<PathBuf>,
-        
-        /// Enable verbose output
-        #[arg(short, long)]
-        verbose: bool,
-    },
-}
 
-pub struct ContainerHandler;
+        /// Enable verbose output
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Treat unmet host requirements as validation failures
+        #[arg(long)]
+        strict: bool,
+
+        /// Apply safe fixes such as materializing inline scripts
+        #[arg(long)]
+        fix: bool,
+
+        /// Skip the scan for names that collide on case-insensitive or normalizing filesystems
+        #[arg(long)]
+        no_portability_checks: bool,
+
+        /// Also validate installed dependency containers, transitively
+        #[arg(long)]
+        with_dependencies: bool,
+
+        /// Write the full report to this file and print only a summary line
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Format of the --output file
+        #[arg(long, value_enum, default_value = "json", requires = "output")]
+        output_format: ReportFormat,
+
+        /// Write a JUnit XML report with one test case per container
+        #[arg(long)]
+        junit: Option<PathBuf>,
+
+        /// Print nothing; only the exit code reports the result
+        #[arg(short, long, conflicts_with = "verbose")]
+        quiet: bool,
+    },
+    /// Run a script of a container, optionally re-running it when files change (also `wrappy run`)
+    Run(RunArgs),
+    /// Run any command in the environment a script of the container gets (also `wrappy x`)
+    Exec(ExecArgs),
+    /// From inside a running script, run a script of one of the container's declared dependencies
+    RunDep {
+        /// Dependency and script, as `toolchain:compile` (`toolchain` runs its default script)
+        target: String,
+
+        /// Arguments passed to the script
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Start a container's service in the background unless it already runs
+    Start {
+        /// Container name, name@version or path
+        container: String,
+
+        /// Script that runs the service in the foreground
+        #[arg(short, long, default_value = "service")]
+        script: String,
+
+        /// Seconds without use after which idle-reaper stops the service
+        #[arg(long, default_value_t = 900)]
+        idle_timeout: u64,
+
+        /// Print nothing on success (used by lazy wrappers)
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Show whether a container is running, with its last exit code and errors
+    Status {
+        /// Container name, name@version or path
+        container: String,
+
+        /// Print the status as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Stop a container's running script and its background service
+    Stop {
+        /// Container name, name@version or path
+        container: String,
+
+        /// Seconds to wait after SIGTERM before sending SIGKILL
+        #[arg(long, default_value_t = 10)]
+        timeout: u64,
+    },
+    /// Stop services that have not been used for longer than their idle timeout
+    IdleReaper {
+        /// Only report which services would be stopped
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Upgrade a container using an older layout or manifest shape
+    Migrate {
+        /// Container directory (defaults to current directory)
+        path: Option<PathBuf>,
+    },
+    /// Scaffold a new container, or show where an existing one keeps its state; for git working trees optionally write a .gitignore
+    Init {
+        /// Container directory (defaults to current directory)
+        path: Option<PathBuf>,
+
+        /// Container name for a new manifest (defaults to the directory name)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Container type for a new manifest
+        #[arg(long = "type", value_enum, default_value = "application")]
+        container_type: ContainerType,
+
+        /// Scaffold into a directory that is not empty; existing files are kept
+        #[arg(long)]
+        force: bool,
+
+        /// Add wrappy's state files and the persistent directories to the container's .gitignore
+        #[arg(long)]
+        gitignore: bool,
+    },
+    /// Show container details and host requirement status
+    Info {
+        /// Container name, name@version or path (defaults to the current directory)
+        #[arg(default_value = ".")]
+        container: String,
+
+        /// Print environment values that look like secrets instead of masking them
+        #[arg(long)]
+        show_secrets: bool,
+    },
+    /// List installed and adopted containers
+    List {
+        /// Print the list as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Only list containers whose name contains this text
+        #[arg(long)]
+        filter: Option<String>,
+    },
+    /// Copy a container directory into the store
+    Install {
+        /// Container directory to install
+        path: PathBuf,
+
+        /// Name to install it under (defaults to the manifest name)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Replace an installed container with the same name and version
+        #[arg(long)]
+        force: bool,
+
+        /// Refuse a container without a valid attestation from `container export --attest`
+        #[arg(long)]
+        require_attestation: bool,
+
+        /// Install even when the store path contains ':', which keeps wrappers off PATH
+        #[arg(long)]
+        allow_weird_paths: bool,
+
+        /// Restore the state `remove --keep-state` stashed for this name without asking
+        #[arg(long)]
+        restore_state: bool,
+    },
+    /// Register a container directory where it is, without copying it into the store
+    Adopt {
+        /// Container directory to register
+        path: PathBuf,
+
+        /// Name commands accept for it (defaults to the manifest name)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Point the adoption of the same name and version at this directory after it moved
+        #[arg(long)]
+        relocate: bool,
+
+        /// Adopt even when the path contains ':', which keeps wrappers off PATH
+        #[arg(long)]
+        allow_weird_paths: bool,
+    },
+    /// Remove an installed container and its bindings; adopted containers are only deregistered
+    Remove {
+        /// Container name, name@version or path
+        container: String,
+
+        /// Also delete the directory of an adopted container
+        #[arg(long)]
+        delete_files: bool,
+
+        /// Leave the container's active bindings in place
+        #[arg(long)]
+        keep_bindings: bool,
+
+        /// Also remove the containers that require it, dependents first
+        #[arg(long, conflicts_with = "force")]
+        cascade: bool,
+
+        /// Remove it even though other containers require it; they are marked degraded
+        #[arg(long)]
+        force: bool,
+
+        /// Delete the persistent data directories too instead of keeping them in orphaned-data/
+        #[arg(long)]
+        purge: bool,
+
+        /// Stash the runtime id, run history and active bindings for a later install of this name
+        #[arg(long)]
+        keep_state: bool,
+
+        /// Remove without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Pack a container into a tar archive
+    Export {
+        /// Container name, name@version or path
+        container: String,
+
+        /// Archive to write (defaults to <name>-<version>.tar in the current directory)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Byte-identical output for identical content: fixed mtimes, no owners, normalized modes
+        #[arg(long)]
+        reproducible: bool,
+
+        /// Export even if names collide on case-insensitive or normalizing filesystems
+        #[arg(long)]
+        no_portability_checks: bool,
+
+        /// Also pack the persistent data directories, which are left out by default
+        #[arg(long)]
+        include_data: bool,
+
+        /// Embed an attestation signed with the local Ed25519 key (~/.config/wrappy/keys/attestation)
+        #[arg(long)]
+        attest: bool,
+    },
+    /// Print the SHA-256 a reproducible export would have, without writing it
+    Digest {
+        /// Container name, name@version or path
+        container: String,
+    },
+    /// List the files export, seal and dedup include after applying .wrappyignore
+    Files {
+        /// Container name, name@version or path
+        container: String,
+
+        /// List what the ignore rules leave out instead, with the rule that matched
+        #[arg(long)]
+        ignored: bool,
+    },
+    /// Record the SHA-256, size and mtime of every file, for `container verify`
+    Seal {
+        /// Container name, name@version or path
+        container: String,
+    },
+    /// Check a container against its seal and report changed, missing and added files
+    Verify {
+        /// Container name, name@version or path
+        container: String,
+
+        /// Trust files whose size and modification time match the seal instead of hashing them
+        #[arg(long)]
+        fast: bool,
+
+        /// Continue an interrupted run, skipping the files it already verified
+        #[arg(long)]
+        resume: bool,
+
+        /// Hash every file, ignoring modification times and earlier progress
+        #[arg(long, conflicts_with_all = ["fast", "resume"])]
+        paranoid: bool,
+
+        /// Progress lines and a summary table, or only the summary as JSON
+        #[arg(long, value_enum, default_value = "text")]
+        format: SummaryFormat,
+    },
+    /// Manifest file commands
+    Manifest {
+        #[command(subcommand)]
+        action: ManifestCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ManifestCommands {
+    /// Write a manifest in another format
+    Convert {
+        /// Manifest file or container directory
+        path: PathBuf,
+        /// Target format
+        #[arg(long, value_enum)]
+        to: ManifestFormat,
+        /// File to write (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Flags of `container validate` shared by every validated path
+struct ValidateOptions {
+    verbose: bool,
+    strict: bool,
+    fix: bool,
+    portability_checks: bool,
+    with_dependencies: bool,
+    output: Option<PathBuf>,
+    output_format: ReportFormat,
+    junit: Option<PathBuf>,
+    quiet: bool,
+}
+
+impl ValidateOptions {
+    /// Detailed terminal output is replaced by a summary when reports go to files
+    fn prints_details(&self) -> bool {
+        !self.quiet && self.output.is_none() && self.junit.is_none()
+    }
+}
+
+impl RunArgs {
+    /// Whether the run prints JSON for another program to read instead of the script's own output.
+    pub fn is_machine_readable(&self) -> bool {
+        self.output == OutputMode::Json || (self.dry_run && self.format == PlanFormat::Json)
+    }
+}
+
+impl ContainerCommands {
+    /// Whether the command prints JSON for another program, or nothing at all with `validate --quiet`.
+    pub fn is_machine_readable(&self) -> bool {
+        match self {
+            ContainerCommands::Run(args) => args.is_machine_readable(),
+            ContainerCommands::Validate { quiet, .. } => *quiet,
+            ContainerCommands::Status { json, .. } | ContainerCommands::List { json, .. } => *json,
+            ContainerCommands::Verify { format, .. } => *format == SummaryFormat::Json,
+            _ => false,
+        }
+    }
+}
+
+/// Flags of `container remove`
+struct RemoveOptions {
+    delete_files: bool,
+    keep_bindings: bool,
+    cascade: bool,
+    force: bool,
+    purge: bool,
+    keep_state: bool,
+    yes: bool,
+}
+
+/// One container `container remove` is about to delete or forget.
+struct RemovalTarget {
+    container: Container,
+    /// Name other containers require it by: the registered name, else the manifest name
+    name: String,
+    selector: String,
+    /// Registry entry of an adopted container, which is only forgotten unless `--delete-files`
+    adopted: Option<RegistryEntry>,
+    /// Targets of its active bindings
+    active_bindings: Vec<String>,
+}
+
+impl RemovalTarget {
+    fn new(container: Container, registered: Option<RegistryEntry>) -> Self {
+        let (name, selector) = match &registered {
+            Some(entry) => (entry.name.clone(), entry.selector()),
+            None => (container.name().to_string(), format!("{}@{}", container.name(), container.version())),
+        };
+        let active_bindings = query::bindings_of(&container.path.to_string_lossy())
+            .into_iter()
+            .filter(|binding| binding.active)
+            .map(|binding| binding.target)
+            .collect();
+        Self {
+            container,
+            name,
+            selector,
+            adopted: registered.filter(|entry| entry.adopted),
+            active_bindings,
+        }
+    }
+}
+
+pub struct ContainerHandler;
+
+impl ContainerHandler {
+
+    /// Routes and executes the appropriate command
+    pub fn execute_command(command: ContainerCommands) -> i32 {
+        match command {
+            ContainerCommands::Validate {
+                mut paths,
+                path,
+                null,
+                verbose,
+                strict,
+                fix,
+                no_portability_checks,
+                with_dependencies,
+                output,
+                output_format,
+                junit,
+                quiet,
+            } => {
+                paths.extend(path);
+                let options = ValidateOptions {
+                    verbose,
+                    strict,
+                    fix,
+                    portability_checks: !no_portability_checks,
+                    with_dependencies,
+                    output,
+                    output_format,
+                    junit,
+                    quiet,
+                };
+                Self::handle_validate_command(paths, null, options)
+            }
+            ContainerCommands::Run(args) => Self::handle_run_command(args),
+            ContainerCommands::RunDep { target, args } => Self::handle_run_dep_command(target, args),
+            ContainerCommands::Exec(ExecArgs {
+                container,
+                no_inherit_env,
+                command,
+            }) => Self::handle_exec_command(container, !no_inherit_env, command),
+            ContainerCommands::Start { container, script, idle_timeout, quiet } => {
+                Self::handle_start_command(container, script, idle_timeout, quiet)
+            }
+            ContainerCommands::Stop { container, timeout } => Self::handle_stop_command(container, timeout),
+            ContainerCommands::IdleReaper { dry_run } => Self::handle_idle_reaper_command(dry_run),
+            ContainerCommands::Migrate { path } => Self::handle_migrate_command(path),
+            ContainerCommands::Init {
+                path,
+                name,
+                container_type,
+                force,
+                gitignore,
+            } => Self::handle_init_command(path, name, container_type, force, gitignore),
+            ContainerCommands::Info { container, show_secrets } => Self::handle_info_command(container, show_secrets),
+            ContainerCommands::List { json, filter } => Self::handle_list_command(json, filter),
+            ContainerCommands::Status { container, json } => Self::handle_status_command(container, json),
+            ContainerCommands::Install {
+                path,
+                name,
+                force,
+                require_attestation,
+                allow_weird_paths,
+                restore_state,
+            } => Self::handle_install_command(path, name, force, require_attestation, allow_weird_paths, restore_state),
+            ContainerCommands::Adopt {
+                path,
+                name,
+                relocate,
+                allow_weird_paths,
+            } => Self::handle_adopt_command(path, name, relocate, allow_weird_paths),
+            ContainerCommands::Remove {
+                container,
+                delete_files,
+                keep_bindings,
+                cascade,
+                force,
+                purge,
+                keep_state,
+                yes,
+            } => Self::handle_remove_command(
+                container,
+                RemoveOptions {
+                    delete_files,
+                    keep_bindings,
+                    cascade,
+                    force,
+                    purge,
+                    keep_state,
+                    yes,
+                },
+            ),
+            ContainerCommands::Export {
+                container,
+                output,
+                reproducible,
+                no_portability_checks,
+                include_data,
+                attest,
+            } => Self::handle_export_command(container, output, reproducible, !no_portability_checks, include_data, attest),
+            ContainerCommands::Digest { container } => Self::handle_digest_command(container),
+            ContainerCommands::Files { container, ignored } => Self::handle_files_command(container, ignored),
+            ContainerCommands::Seal { container } => Self::handle_seal_command(container),
+            ContainerCommands::Verify {
+                container,
+                fast,
+                resume,
+                paranoid,
+                format,
+            } => {
+                let mode = match (fast, paranoid) {
+                    (_, true) => VerifyMode::Paranoid,
+                    (true, false) => VerifyMode::Fast,
+                    (false, false) => VerifyMode::Full,
+                };
+                Self::handle_verify_command(container, VerifyOptions { mode, resume }, format)
+            }
+            ContainerCommands::Manifest {
+                action: ManifestCommands::Convert { path, to, output },
+            } => Self::handle_convert_command(path, to, output),
+        }
+    }
+
+    /// Handles the validate command execution
+    fn handle_validate_command(paths: Vec<PathBuf>, null: bool, options: ValidateOptions) -> i32 {
+        let (paths, from_stdin) = match path_list::expand_stdin(paths, null) {
+            Ok(expanded) => expanded,
+            Err(error) => {
+                eprintln!("❌ Failed to read container directories from stdin: {}", error);
+                return 1;
+            }
+        };
+        if from_stdin && paths.is_empty() && !options.quiet {
+            println!("ℹ️  No container directories on stdin, nothing to validate");
+        }
+
+        // An empty list from stdin stays empty instead of meaning the current directory
+        let paths = if paths.is_empty() && !from_stdin {
+            match Self::resolve_container_path(None) {
+                Ok(path) => vec![path],
+                Err(exit_code) => return exit_code,
+            }
+        } else {
+            paths
+        };
+
+        let mut report = ValidationReport::default();
+        for path in &paths {
+            report.containers.push(Self::validate_one(path, &options));
+        }
+
+        if let Err(error) = Self::write_report_files(&report, &options) {
+            eprintln!("❌ Failed to write validation report: {}", error);
+            return 1;
+        }
+
+        // A list from stdin can be long, so it always ends with the combined result
+        let list_summary = from_stdin && !report.containers.is_empty();
+        if !options.quiet && (!options.prints_details() || list_summary) {
+            println!("{}", report.summary());
+        }
+
+        if report.is_valid() { 0 } else { 1 }
+    }
+
+    /// Validates `paths` like `container validate --quiet` and returns the report instead
+    /// of printing anything; for embedders such as the C interface.
+    pub fn validation_report(paths: &[PathBuf], strict: bool) -> ValidationReport {
+        let options = ValidateOptions {
+            verbose: false,
+            strict,
+            fix: false,
+            portability_checks: true,
+            with_dependencies: false,
+            output: None,
+            output_format: ReportFormat::Json,
+            junit: None,
+            quiet: true,
+        };
+        ValidationReport {
+            containers: paths.iter().map(|path| Self::validate_one(path, &options)).collect(),
+        }
+    }
+
+    /// Validates one container directory, printing details only in interactive mode
+    fn validate_one(path: &Path, options: &ValidateOptions) -> ContainerValidationResult {
+        let details = options.prints_details();
+        let mut result = ContainerValidationResult::start(path);
+
+        if details {
+            Self::print_validation_start(path, options.verbose);
+        }
+
+        let validated = Self::validate_container_at_path(path).and_then(|container| {
+            if options.fix {
+                Self::apply_fixes(&container, details)?;
+            }
+            result.warnings.extend(Self::check_config_files(&container, options.strict, details)?);
+            result.warnings.extend(Self::check_host_requirements(&container, options.strict, details)?);
+            result.warnings.extend(Self::check_interpreters(&container, options.strict, details)?);
+            result.warnings.extend(Self::check_line_endings(&container, options.strict, details)?);
+            result.warnings.extend(Self::check_broken_symlinks(&container, options.strict, details)?);
+            result.warnings.extend(Self::check_escaping_symlinks(&container, options.strict, details)?);
+            if options.portability_checks {
+                result.warnings.extend(Self::check_name_collisions(&container, options.strict, details)?);
+            }
+            result.warnings.extend(Self::check_unknown_bindings(&container, details));
+            result.warnings.extend(Self::check_platform_targets(&container, details));
+            result.warnings.extend(Self::check_in_tree_state(&container, details));
+            Ok(container)
+        });
+
+        let container = match validated {
+            Ok(container) => {
+                if details {
+                    Self::print_validation_success(&container, options.verbose);
+                }
+                container
+            }
+            Err(error) => {
+                if details {
+                    Self::print_validation_error(&error, options.verbose);
+                }
+                result.push_error(error.code(), error.to_string());
+                return result.finish();
+            }
+        };
+
+        result.name = Some(container.name().to_string());
+        result.version = Some(container.version().to_string());
+
+        if options.with_dependencies {
+            Self::validate_dependencies(&container, options, &mut result);
+        }
+        result.finish()
+    }
+
+    /// Writes the requested report files; nothing is written when neither option is given
+    fn write_report_files(report: &ValidationReport, options: &ValidateOptions) -> Result<(), ContainerError> {
+        if let Some(output) = &options.output {
+            let content = report.render(options.output_format)?;
+            filesystem::atomic_write(output, content.as_bytes(), None)?;
+        }
+        if let Some(junit) = &options.junit {
+            filesystem::atomic_write(junit, report.to_junit().as_bytes(), None)?;
+        }
+        Ok(())
+    }
+
+    /// Validates the dependency tree; only strict mode turns dependency problems into errors
+    fn validate_dependencies(container: &Container, options: &ValidateOptions, result: &mut ContainerValidationResult) {
+        let details = options.prints_details();
+        let resolver = match ContainerResolver::new() {
+            Ok(resolver) => resolver,
+            Err(error) => {
+                if details {
+                    eprintln!("❌ Failed to open the container store: {}", error);
+                }
+                result.push_error(error.code(), format!("failed to open the container store: {}", error));
+                return;
+            }
+        };
+
+        let reports = ContainerService::validate_dependency_tree(container, &resolver, options.strict);
+        if reports.is_empty() {
+            if details {
+                println!("📦 No dependencies to validate");
+            }
+            return;
+        }
+
+        let mut problems = 0;
+        for report in &reports {
+            if details {
+                println!("\n📦 Dependencies of {}:", report.container);
+            }
+            for check in &report.checks {
+                if details {
+                    Self::print_dependency_check(check, options.strict, options.verbose);
+                }
+                if let Some((code, problem)) = Self::dependency_problem(&report.container, check) {
+                    problems += 1;
+                    if options.strict && check.is_error(true) {
+                        result.push_error(code, problem);
+                    } else {
+                        result.warnings.push(problem);
+                    }
+                }
+            }
+        }
+
+        problems += Self::check_dependency_binding_conflicts(container, &resolver, options, result);
+
+        if details && problems > 0 {
+            eprintln!("\n❌ {} dependency problem(s) found", problems);
+        }
+    }
+
+    /// Containers of one dependency tree that bind the same host path would fail at enable
+    /// time on whichever comes second, so the conflicts are reported now: as warnings, or as
+    /// errors in strict mode. Returns how many were found.
+    fn check_dependency_binding_conflicts(
+        container: &Container,
+        resolver: &ContainerResolver,
+        options: &ValidateOptions,
+        result: &mut ContainerValidationResult,
+    ) -> usize {
+        let details = options.prints_details();
+        let closure = ContainerService::dependency_closure(container, resolver);
+        if closure.len() < 2 {
+            return 0;
+        }
+
+        let conflicts = BindingManager::open()
+            .and_then(|manager| manager.target_owners(&closure))
+            .map(BindingManager::conflicts);
+        let conflicts = match conflicts {
+            Ok(conflicts) => conflicts,
+            Err(error) => {
+                if details {
+                    eprintln!("❌ Failed to collect the binding targets of the dependencies: {}", error);
+                }
+                result.push_error(error.code(), format!("failed to collect binding targets of dependencies: {}", error));
+                return 1;
+            }
+        };
+
+        if details && !conflicts.is_empty() {
+            println!("\n🔗 Binding conflicts between dependencies:");
+        }
+        for conflict in &conflicts {
+            if details {
+                let marker = if options.strict { "❌" } else { "⚠️ " };
+                println!("  {} {}", marker, conflict);
+            }
+            let problem = format!("binding conflict in the dependency tree: {}", conflict);
+            if options.strict {
+                let error = ContainerError::BindingConflict {
+                    conflicts: vec![conflict.to_string()],
+                };
+                result.push_error(error.code(), problem);
+            } else {
+                result.warnings.push(problem);
+            }
+        }
+        conflicts.len()
+    }
+
+    /// Describes a failed dependency check for reports with its error code; optional missing dependencies are not problems
+    fn dependency_problem(parent: &str, check: &DependencyCheck) -> Option<(&'static str, String)> {
+        match &check.outcome {
+            DependencyOutcome::Valid { .. } | DependencyOutcome::Missing { optional: true, .. } => None,
+            DependencyOutcome::Invalid { path, error } => Some((
+                error.code(),
+                format!(
+                    "dependency '{}' of {} at {} is invalid: {}",
+                    check.name,
+                    parent,
+                    path.display(),
+                    error
+                ),
+            )),
+            DependencyOutcome::TypeMismatch { version, expected, found, .. } => Some((
+                "E_INVALID_DEPENDENCY",
+                format!(
+                    "dependency '{}@{}' of {} has type {}, expected {} (declare \"type\": \"{}\" if intended)",
+                    check.name, version, parent, found, expected, found
+                ),
+            )),
+            DependencyOutcome::Missing { requirement, .. } => Some((
+                "E_PACKAGE_NOT_FOUND",
+                format!("dependency '{}' {} of {} is not installed", check.name, requirement, parent),
+            )),
+        }
+    }
+
+    /// Prints one line per dependency, with the failure reason underneath
+    fn print_dependency_check(check: &DependencyCheck, strict: bool, verbose: bool) {
+        match &check.outcome {
+            DependencyOutcome::Valid { version, path } => {
+                println!("  ✅ {}@{}", check.name, version);
+                if verbose {
+                    println!("     {}", path.display());
+                }
+            }
+            DependencyOutcome::TypeMismatch { version, path, expected, found } => {
+                let marker = if strict { "❌" } else { "⚠️ " };
+                println!("  {} {}@{} has type {}, expected {}", marker, check.name, version, found, expected);
+                if verbose {
+                    println!("     {}", path.display());
+                }
+                println!("     Declare \"type\": \"{}\" on the dependency if this is intended", found);
+            }
+            DependencyOutcome::Invalid { path, error } => {
+                println!("  ❌ {} ({})", check.name, path.display());
+                println!("     {}", error);
+            }
+            DependencyOutcome::Missing { requirement, optional: true } => {
+                println!("  ℹ️  {} {} is optional and not installed", check.name, requirement);
+            }
+            DependencyOutcome::Missing { requirement, .. } => {
+                let marker = if strict { "❌" } else { "⚠️ " };
+                println!("  {} {} {} is not installed", marker, check.name, requirement);
+            }
+        }
+    }
+
+    /// Handles the export command execution
+    fn handle_export_command(
+        input: String,
+        output: Option<PathBuf>,
+        reproducible: bool,
+        portability_checks: bool,
+        include_data: bool,
+        attest: bool,
+    ) -> i32 {
+        let container = match ContainerResolver::new().and_then(|resolver| resolver.resolve_container(&input)) {
+            Ok(container) => container,
+            Err(error) => {
+                eprintln!("❌ {}", error);
+                return 1;
+            }
+        };
+
+        // The archive may be unpacked on macOS or Windows, where these pairs become one file
+        if portability_checks {
+            match portability::find_collisions(&container.path) {
+                Ok(collisions) if collisions.is_empty() => {}
+                Ok(collisions) => {
+                    eprintln!("❌ Cannot export '{}': some names collide on case-insensitive or normalizing filesystems", input);
+                    for collision in &collisions {
+                        eprintln!("  {}", collision);
+                    }
+                    eprintln!("  ℹ️  Rename one file of each pair, or pass --no-portability-checks");
+                    return 1;
+                }
+                Err(error) => {
+                    eprintln!("❌ Failed to export '{}': {}", input, error);
+                    return 1;
+                }
+            }
+        }
+
+        let attestation = match attest {
+            true => match WrappyPaths::new().and_then(|paths| AttestationService::attest(&container, &paths)) {
+                Ok(attestation) => Some(attestation),
+                Err(error) => {
+                    eprintln!("❌ Failed to attest '{}': {}", input, error);
+                    return 1;
+                }
+            },
+            false => None,
+        };
+
+        let output = output.unwrap_or_else(|| PathBuf::from(ExportService::default_file_name(&container)));
+        match ExportService::export_to_file(&container, &output, reproducible, include_data, attestation.as_ref()) {
+            Ok(summary) => {
+                println!(
+                    "📦 Exported {}@{} to {} ({} entries, {})",
+                    container.name(),
+                    container.version(),
+                    output.display(),
+                    summary.entries,
+                    filesystem::format_size(summary.size)
+                );
+                println!("  sha256: {}", summary.sha256);
+                if let Some(attestation) = &attestation {
+                    println!(
+                        "  🔏 Attested by {} with key {}",
+                        attestation.statement.builder, attestation.signature.key_id
+                    );
+                }
+                if !reproducible {
+                    println!("  ℹ️  Use --reproducible for an archive whose hash depends on the content only");
+                }
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to export '{}': {}", input, error);
+                1
+            }
+        }
+    }
+
+    /// Handles the digest command execution
+    fn handle_digest_command(input: String) -> i32 {
+        let digest = ContainerResolver::new()
+            .and_then(|resolver| resolver.resolve_container(&input))
+            .and_then(|container| ExportService::digest(&container));
+
+        match digest {
+            Ok(summary) => {
+                println!("sha256:{}", summary.sha256);
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to compute digest of '{}': {}", input, error);
+                1
+            }
+        }
+    }
+
+    /// Handles the list command execution
+    fn handle_list_command(json: bool, filter: Option<String>) -> i32 {
+        let listed = WrappyPaths::new().and_then(|paths| {
+            let store_dir = Config::load_or_default(&paths)?.store_dir(&paths);
+            ContainerRegistry::new(&paths).installed(&store_dir)
+        });
+        let mut listed = match listed {
+            Ok(listed) => listed,
+            Err(error) => {
+                eprintln!("❌ Failed to list containers: {}", error);
+                return 1;
+            }
+        };
+        if let Some(filter) = &filter {
+            listed.retain(|row| row.name.contains(filter.as_str()));
+        }
+
+        if json {
+            return match serde_json::to_string_pretty(&listed) {
+                Ok(json) => {
+                    println!("{}", json);
+                    0
+                }
+                Err(error) => {
+                    eprintln!("❌ Failed to list containers: {}", error);
+                    1
+                }
+            };
+        }
+
+        if listed.is_empty() {
+            match filter {
+                Some(filter) => println!("No installed container matches '{}'", filter),
+                None => {
+                    println!("No containers installed yet.");
+                    println!("Use 'wrappy container install <path>' to install one.");
+                }
+            }
+            return 0;
+        }
+
+        let rows: Vec<[String; 5]> = listed
+            .iter()
+            .map(|row| {
+                let status = match (row.status, row.adopted) {
+                    (InstalledStatus::Ok, true) => "✅ ok (adopted)".to_string(),
+                    (InstalledStatus::Ok, false) => "✅ ok".to_string(),
+                    (InstalledStatus::Degraded, _) => "⚠️  degraded".to_string(),
+                    (status, _) => format!("❌ {}", status),
+                };
+                [
+                    row.name.clone(),
+                    row.version.clone(),
+                    row.container_type.as_ref().map(ToString::to_string).unwrap_or_else(|| "-".to_string()),
+                    row.installed_at
+                        .map(|at| at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                    status,
+                ]
+            })
+            .collect();
+        let headers = ["NAME", "VERSION", "TYPE", "INSTALLED", "STATUS"];
+        let widths: Vec<usize> = (0..4)
+            .map(|column| rows.iter().map(|row| row[column].chars().count()).chain([headers[column].len()]).max().unwrap_or(0))
+            .collect();
+        let line = |cells: [&str; 5]| {
+            let mut line = String::from(" ");
+            for (column, width) in widths.iter().enumerate() {
+                line.push_str(&format!(" {:<width$} ", cells[column], width = width));
+            }
+            line.push(' ');
+            line.push_str(cells[4]);
+            line
+        };
+        println!("📦 Installed containers:");
+        println!("{}", line(headers));
+        for (row, listed) in rows.iter().zip(&listed) {
+            println!("{}", line([&row[0], &row[1], &row[2], &row[3], &row[4]]));
+            if let Some(error) = &listed.error {
+                println!("      {}", error);
+            }
+        }
+        0
+    }
+
+    /// Handles the install command execution
+    fn handle_install_command(
+        path: PathBuf,
+        name: Option<String>,
+        force: bool,
+        require_attestation: bool,
+        allow_weird_paths: bool,
+        restore_state: bool,
+    ) -> i32 {
+        if !Self::check_valid(&path) {
+            return 1;
+        }
+
+        executor::install_interrupt_handler();
+        let cancellation = CancellationToken::linked_to(executor::interrupted);
+        let result = WrappyPaths::new().and_then(|paths| {
+            let resolver = ContainerResolver::new()?;
+            // Names are plain words and versions semver, so only the store can bring in odd characters
+            Self::check_path_sanity(resolver.store_dir(), allow_weird_paths)?;
+            InstallService::install(&path, name, force, require_attestation, &paths, &resolver, &cancellation)
+        });
+        match result {
+            Ok(installation) => {
+                let verb = if installation.replaced { "Reinstalled" } else { "Installed" };
+                println!(
+                    "📦 {} {} to {} ({} file(s), {})",
+                    verb,
+                    installation.entry.selector(),
+                    installation.entry.path.display(),
+                    installation.files,
+                    filesystem::format_size(installation.size)
+                );
+                if !installation.kept_data.is_empty() {
+                    println!("  📁 Kept persistent data: {}", installation.kept_data.join(", "));
+                }
+                match &installation.dedup {
+                    Some(summary) if summary.cross_device => println!(
+                        "  ℹ️  The store is on another filesystem than the object store; files stay plain copies"
+                    ),
+                    Some(summary) if summary.files_linked > 0 => println!(
+                        "  🔗 {} file(s) linked to shared objects, {} saved",
+                        summary.files_linked,
+                        filesystem::format_size(summary.bytes_saved)
+                    ),
+                    _ => {}
+                }
+                let orphaned = WrappyPaths::new()
+                    .ok()
+                    .map(|paths| paths.orphaned_data_dir(&installation.entry.name))
+                    .filter(|orphaned| !installation.replaced && orphaned.is_dir());
+                if let Some(orphaned) = orphaned {
+                    println!(
+                        "  ℹ️  Data kept when an earlier {} was removed is in {}; move it into {} to use it again",
+                        installation.entry.name,
+                        orphaned.display(),
+                        installation.entry.path.display()
+                    );
+                }
+                match &installation.attestation {
+                    AttestationCheck::Verified(attestation) => println!(
+                        "  🔏 Attestation verified: exported by {} with wrappy {}{}",
+                        attestation.statement.builder,
+                        attestation.statement.wrappy_version,
+                        attestation
+                            .statement
+                            .source_commit
+                            .as_ref()
+                            .map(|commit| format!(" from commit {}", commit))
+                            .unwrap_or_default()
+                    ),
+                    AttestationCheck::Missing => println!("  ⚠️  Not attested; pass --require-attestation to refuse such containers"),
+                    AttestationCheck::Invalid(reason) => println!("  ⚠️  Attestation is not valid: {}", reason),
+                }
+                Self::offer_stashed_state(&installation.entry, restore_state);
+                0
+            }
+            Err(error @ ContainerError::ContainerExists { .. }) => {
+                eprintln!("❌ Failed to install {}: {}", path.display(), error);
+                eprintln!("   Pass --force to replace it");
+                error.exit_code()
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to install {}: {}", path.display(), error);
+                error.exit_code()
+            }
+        }
+    }
+
+    /// Puts back the state `remove --keep-state` stashed for the installed name, with
+    /// `--restore-state` or when the user agrees on a terminal. A stash from an incompatible
+    /// version is only restored after a second confirmation; otherwise it stays for later.
+    fn offer_stashed_state(entry: &RegistryEntry, restore_state: bool) {
+        let Ok(paths) = WrappyPaths::new() else {
+            return;
+        };
+        let stash = StateStash::for_container(&paths, &entry.name);
+        let Ok(metadata) = stash.metadata() else {
+            return;
+        };
+        let stashed = format!("{}@{}", metadata.name, metadata.version);
+        let question = format!(
+            "  State stashed when {} was removed on {} is available. Restore it?",
+            stashed,
+            metadata.stashed_at.format("%Y-%m-%d %H:%M")
+        );
+        if !restore_state && !prompt::confirm(&question, false) {
+            println!(
+                "  ℹ️  State stashed when {} was removed is kept in {}; pass --restore-state to restore it",
+                stashed,
+                stash.dir().display()
+            );
+            return;
+        }
+
+        let restored = ContainerService::load_from_directory(&entry.path).and_then(|container| {
+            match stash.restore(&container, false) {
+                Err(error @ ContainerError::VersionConflict { .. }) => {
+                    eprintln!("  ⚠️  {}", error);
+                    if prompt::confirm("  Restore it anyway?", false) {
+                        stash.restore(&container, true)
+                    } else {
+                        Err(error)
+                    }
+                }
+                result => result,
+            }
+            .map(|restored| (container, restored))
+        });
+        let (container, restored) = match restored {
+            Ok(restored) => restored,
+            Err(error) => {
+                eprintln!("  ❌ Did not restore the stashed state: {}", error);
+                eprintln!("     It stays in {}", stash.dir().display());
+                return;
+            }
+        };
+        println!(
+            "  📦 Restored the state stashed from {}: {}{} run record(s)",
+            stashed,
+            if restored.metadata.has_runtime { "runtime id, " } else { "" },
+            restored.history_records_restored
+        );
+
+        let targets = &restored.metadata.active_bindings;
+        if targets.is_empty() {
+            return;
+        }
+        match Self::enable_bindings(&container, targets) {
+            Ok(()) => println!("  🔗 Enabled the bindings that were active before: {}", targets.join(", ")),
+            Err(error) => eprintln!(
+                "  ⚠️  Failed to enable the bindings that were active before ({}): {}",
+                targets.join(", "),
+                error
+            ),
+        }
+    }
+
+    /// Enables the bindings of `container` whose manifest targets are listed.
+    fn enable_bindings(container: &Container, targets: &[String]) -> Result<(), ContainerError> {
+        let manager = BindingManager::new()?;
+        let bindings = &container.manifest.bindings;
+        for target in targets {
+            let filter = BindingFilter {
+                only: None,
+                target: Some(target.clone()),
+                label: None,
+            };
+            let mut filtered = container.clone();
+            filtered.manifest.bindings = manager.filter_bindings(container, bindings, &filter)?;
+            manager.install_bindings(&filtered)?;
+        }
+        Ok(())
+    }
+
+    /// Validates `path` without output and prints the errors when it is not a container.
+    fn check_valid(path: &Path) -> bool {
+        let options = ValidateOptions {
+            verbose: false,
+            strict: false,
+            fix: false,
+            portability_checks: false,
+            with_dependencies: false,
+            output: None,
+            output_format: ReportFormat::Json,
+            junit: None,
+            quiet: true,
+        };
+        let result = Self::validate_one(path, &options);
+        if !result.valid {
+            eprintln!("❌ {} is not a valid container:", path.display());
+            for error in &result.errors {
+                eprintln!("   {}", error);
+            }
+        }
+        result.valid
+    }
+
+    /// Refuses a container path wrappers, desktop entries or state files cannot carry,
+    /// and warns about the issues `--allow-weird-paths` lets through.
+    fn check_path_sanity(path: &Path, allow_weird_paths: bool) -> ContainerResult<()> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        for issue in path_sanity::check_container_path(&path, allow_weird_paths)? {
+            println!("⚠️  {} {}", path.display(), issue);
+            if issue == PathIssue::Colon {
+                println!("   Executable bindings of this container will be refused");
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles the adopt command execution
+    fn handle_adopt_command(path: PathBuf, name: Option<String>, relocate: bool, allow_weird_paths: bool) -> i32 {
+        if !Self::check_valid(&path) {
+            return 1;
+        }
+        if let Err(error) = Self::check_path_sanity(&path, allow_weird_paths) {
+            eprintln!("❌ Failed to adopt {}: {}", path.display(), error);
+            return error.exit_code();
+        }
+
+        match Self::adopt(&path, name, relocate) {
+            Ok((entry, None)) => {
+                println!("📦 Adopted {} at {}", entry.selector(), entry.path.display());
+                println!("   wrappy will not copy, update or delete it; `container remove` only forgets it");
+                0
+            }
+            Ok((entry, Some(previous))) => {
+                println!(
+                    "📦 {} now points at {} (was {})",
+                    entry.selector(),
+                    entry.path.display(),
+                    previous.path.display()
+                );
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to adopt {}: {}", path.display(), error);
+                1
+            }
+        }
+    }
+
+    /// Records `path` in the registry. Returns the entry and, when relocating, the one it replaced.
+    fn adopt(path: &Path, name: Option<String>, relocate: bool) -> ContainerResult<(RegistryEntry, Option<RegistryEntry>)> {
+        let path = path.canonicalize().map_err(|e| ContainerError::IoError {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        let container = ContainerService::load_from_directory(&path)?;
+        let resolver = ContainerResolver::new()?;
+        if resolver.is_in_store(&path) {
+            return Err(ContainerError::InvalidPath {
+                path,
+                reason: "is already in the store".to_string(),
+            });
+        }
+
+        let name = name.unwrap_or_else(|| container.name().to_string());
+        RegistryEntry::check_name(&name)?;
+        let entry = RegistryEntry {
+            name,
+            version: container.version().clone(),
+            path,
+            adopted: true,
+            registered_at: clock::now(),
+            attestation: None,
+            dependencies: Some(RegistryEntry::required_dependencies(&container.manifest)),
+            missing_dependencies: Vec::new(),
+        };
+
+        let same_version: Vec<ResolutionCandidate> = resolver
+            .installed_versions(&entry.name)
+            .into_iter()
+            .filter(|candidate| candidate.version == entry.version)
+            .collect();
+        if same_version.iter().any(|candidate| !candidate.adopted) {
+            return Err(ContainerError::ContainerExists { name: entry.selector() });
+        }
+
+        let registry = ContainerRegistry::new(&WrappyPaths::new()?);
+        // Disappeared directories are not candidates, so look in the registry itself
+        let previous = registry
+            .adopted()?
+            .into_iter()
+            .find(|existing| existing.name == entry.name && existing.version == entry.version && existing.path != entry.path);
+        match (previous, relocate) {
+            (Some(previous), false) => Err(ContainerError::InvalidPath {
+                path: entry.path,
+                reason: format!(
+                    "{} is already adopted at {}; pass --relocate if it moved here",
+                    previous.selector(),
+                    previous.path.display()
+                ),
+            }),
+            (None, true) => Err(ContainerError::ContainerNotFound {
+                name: entry.selector(),
+                suggestions: Vec::new(),
+            }),
+            (previous, _) => {
+                registry.register(entry.clone())?;
+                Ok((entry, previous))
+            }
+        }
+    }
+
+    /// Handles the remove command execution. Containers that require the one removed
+    /// block it unless `--cascade` removes them first or `--force` leaves them degraded.
+    fn handle_remove_command(input: String, options: RemoveOptions) -> i32 {
+        let resolved = ContainerResolver::new().and_then(|resolver| {
+            let container = resolver.resolve_container(&input)?;
+            let registered = resolver.registered_entry(&container.path).cloned();
+            let in_store = resolver.is_in_store(&container.path);
+            Ok((resolver, container, registered, in_store))
+        });
+        let (resolver, container, registered, in_store) = match resolved {
+            Ok(resolved) => resolved,
+            Err(error @ ContainerError::ContainerNotFound { .. }) => return Self::forget_missing_adoption(&input, error),
+            Err(error) => {
+                eprintln!("❌ Failed to remove '{}': {}", input, error);
+                return 1;
+            }
+        };
+        let target = RemovalTarget::new(container, registered);
+        if target.adopted.is_none() && !in_store {
+            eprintln!(
+                "❌ {} is neither installed nor adopted; wrappy does not delete other directories",
+                target.container.path.display()
+            );
+            return 1;
+        }
+
+        let registry = match WrappyPaths::new() {
+            Ok(paths) => ContainerRegistry::new(&paths),
+            Err(error) => {
+                eprintln!("❌ Failed to remove {}: {}", target.selector, error);
+                return 1;
+            }
+        };
+        let (dependents, cascade) = match Self::dependents(&resolver, &registry, &target, options.cascade) {
+            Ok(dependents) => dependents,
+            Err(error) => {
+                eprintln!("❌ Failed to remove {}: {}", target.selector, error);
+                return 1;
+            }
+        };
+        if !dependents.is_empty() && !options.cascade && !options.force {
+            let error = ContainerError::HasDependents {
+                name: target.selector.clone(),
+                dependents,
+            };
+            eprintln!("❌ Failed to remove '{}': {}", input, error);
+            eprintln!("   Pass --cascade to remove them too, or --force to remove it anyway and leave them degraded");
+            return 1;
+        }
+
+        // Dependents go first, so no container is left behind without what it requires
+        let removing: Vec<&RemovalTarget> = cascade.iter().chain([&target]).collect();
+        for removal in &removing {
+            if let Some(pid) = Self::running_pid(&removal.container) {
+                eprintln!("❌ {} is running (pid {})", removal.selector, pid);
+                eprintln!("   Stop it first with `wrappy container stop {}`", removal.selector);
+                return 1;
+            }
+        }
+
+        let degraded: &[String] = if options.force { &dependents } else { &[] };
+        if !options.yes && !prompt::confirm(&Self::removal_question(&removing, degraded, &options), false) {
+            eprintln!("❌ Not removing {}; pass --yes to remove it without asking", target.selector);
+            return 1;
+        }
+
+        for (position, removal) in removing.iter().enumerate() {
+            if !Self::remove_target(removal, &options) {
+                let removed: Vec<&str> = removing[..position].iter().map(|done| done.selector.as_str()).collect();
+                if !removed.is_empty() {
+                    eprintln!("   Already removed: {}", removed.join(", "));
+                }
+                return 1;
+            }
+        }
+
+        if !degraded.is_empty() {
+            if let Err(error) = registry.mark_missing_dependency(degraded, &target.name) {
+                eprintln!("⚠️  Could not mark {} as degraded: {}", degraded.join(", "), error);
+            }
+            println!(
+                "⚠️  {} required {} and {} degraded until it is installed again",
+                degraded.join(", "),
+                target.name,
+                if degraded.len() == 1 { "is" } else { "are" }
+            );
+        }
+        0
+    }
+
+    /// Names of the containers that directly require `target`, and with `cascade` every
+    /// direct or indirect dependent to remove before it. Nothing depends on `target`
+    /// while another installed version of it remains.
+    fn dependents(
+        resolver: &ContainerResolver,
+        registry: &ContainerRegistry,
+        target: &RemovalTarget,
+        cascade: bool,
+    ) -> ContainerResult<(Vec<String>, Vec<RemovalTarget>)> {
+        let same = |path: &Path| path.canonicalize().ok() == target.container.path.canonicalize().ok();
+        if resolver.installed_versions(&target.name).iter().any(|candidate| !same(&candidate.path)) {
+            return Ok((Vec::new(), Vec::new()));
+        }
+
+        let index = registry.dependency_index()?;
+        let direct = index.dependents_of(&target.name);
+        if !cascade || direct.is_empty() {
+            return Ok((direct, Vec::new()));
+        }
+        let entries = registry.entries()?;
+        let mut removals = Vec::new();
+        for name in index.cascade_removal_order(&target.name) {
+            for entry in entries.iter().filter(|entry| entry.name == name) {
+                let container = ContainerService::load_from_directory(&entry.path)?;
+                removals.push(RemovalTarget::new(container, Some(entry.clone())));
+            }
+        }
+        Ok((direct, removals))
+    }
+
+    /// One confirmation for everything `remove` is about to do; `removing` ends with the
+    /// container that was asked for.
+    fn removal_question(removing: &[&RemovalTarget], degraded: &[String], options: &RemoveOptions) -> String {
+        let Some((target, dependents)) = removing.split_last() else {
+            return String::new();
+        };
+        let mut question = match dependents {
+            [] if target.adopted.is_some() && !options.delete_files => format!("Forget {}", target.selector),
+            [] => format!("Remove {} and delete {}", target.selector, target.container.path.display()),
+            _ => format!(
+                "Remove {} and the {} container(s) that depend on it: {}",
+                target.selector,
+                dependents.len(),
+                dependents.iter().map(|dependent| dependent.selector.as_str()).collect::<Vec<_>>().join(", ")
+            ),
+        };
+        let bindings: usize = removing.iter().map(|removal| removal.active_bindings.len()).sum();
+        if bindings > 0 && !options.keep_bindings {
+            question.push_str(&format!(" with {} active binding(s)", bindings));
+        }
+        if !degraded.is_empty() {
+            question.push_str(&format!(", leaving {} degraded", degraded.join(", ")));
+        }
+        question.push('?');
+        question
+    }
+
+    /// Disables the bindings of one container, then deletes or forgets it. Reports what
+    /// happened and returns whether it was removed.
+    fn remove_target(target: &RemovalTarget, options: &RemoveOptions) -> bool {
+        let container = &target.container;
+        let selector = &target.selector;
+        // Stashed first: the stash records which bindings are still active
+        if options.keep_state {
+            match WrappyPaths::new().and_then(|paths| StateStash::for_container(&paths, &target.name).stash(container)) {
+                Ok(metadata) => println!(
+                    "📦 Stashed the state of {} ({} run record(s), {} active binding(s)); installing {} again offers to restore it",
+                    selector,
+                    metadata.history_records,
+                    metadata.active_bindings.len(),
+                    target.name
+                ),
+                Err(error) => {
+                    eprintln!("❌ Failed to stash the state of {}: {}", selector, error);
+                    eprintln!("   {} was not removed", selector);
+                    return false;
+                }
+            }
+        }
+
+        let active = &target.active_bindings;
+        if !active.is_empty() {
+            if options.keep_bindings {
+                println!("⚠️  Keeping {} active binding(s): {}", active.len(), active.join(", "));
+            } else if let Err(error) = BindingManager::new().and_then(|manager| manager.remove_bindings(container)) {
+                eprintln!("❌ Failed to disable the bindings of {}: {}", selector, error);
+                eprintln!("   Nothing was removed; pass --keep-bindings to remove it anyway");
+                return false;
+            }
+        }
+
+        let delete_files = options.delete_files;
+        let kept_data = match target.adopted.is_none() || delete_files {
+            true if !options.purge => {
+                match WrappyPaths::new().and_then(|paths| ContainerService::orphan_persistent_data(container, &target.name, &paths)) {
+                    Ok(kept) => kept,
+                    Err(error) => {
+                        eprintln!("❌ Failed to keep the data of {}: {}", selector, error);
+                        eprintln!("   {} was not removed; pass --purge to delete its data with it", selector);
+                        return false;
+                    }
+                }
+            }
+            _ => None,
+        };
+        let result = match &target.adopted {
+            Some(entry) => WrappyPaths::new()
+                .and_then(|paths| ContainerRegistry::new(&paths).deregister(&entry.path))
+                .and_then(|_| match delete_files {
+                    true => Self::remove_directory(&entry.path),
+                    false => Ok(()),
+                }),
+            None => {
+                let canonical = container.path.canonicalize().unwrap_or_else(|_| container.path.clone());
+                Self::remove_directory(&container.path).and_then(|_| {
+                    WrappyPaths::new().and_then(|paths| ContainerRegistry::new(&paths).deregister(&canonical).map(|_| ()))
+                })
+            }
+        };
+        if let Err(error) = result {
+            eprintln!("❌ Failed to remove {}: {}", selector, error);
+            return false;
+        }
+
+        match (&target.adopted, delete_files) {
+            (Some(entry), false) => println!(
+                "🗑️  Forgot {}; its files stay in {} (pass --delete-files to delete them)",
+                selector,
+                entry.path.display()
+            ),
+            (Some(entry), true) => println!("🗑️  Forgot {} and deleted {}", selector, entry.path.display()),
+            (None, _) => {
+                // The per-name directory goes too once its last version is gone
+                if let Some(parent) = container.path.parent() {
+                    let _ = std::fs::remove_dir(parent);
+                }
+                println!("🗑️  Removed {} from {}", selector, container.path.display());
+            }
+        }
+        if let Some(kept) = kept_data {
+            println!("  📁 Kept its persistent data in {} (pass --purge to delete it)", kept.display());
+        }
+        true
+    }
+
+    /// Pid of a running service of `container`, started from this very directory.
+    fn running_pid(container: &Container) -> Option<u32> {
+        if let (true, Some(pid)) = (container.is_running(), container.runtime.pid) {
+            if container.runtime.is_process_alive() {
+                return Some(pid);
+            }
+        }
+        let paths = WrappyPaths::new().ok()?;
+        let state = ServiceRuntime::new(&paths, container.name()).running()?;
+        let same = |a: &Path, b: &Path| a.canonicalize().ok().is_some_and(|a| b.canonicalize().is_ok_and(|b| a == b));
+        same(&state.container_path, &container.path).then_some(state.pid)
+    }
+
+    /// Adopted directories that disappeared cannot be resolved, but can still be forgotten by name.
+    fn forget_missing_adoption(input: &str, not_found: ContainerError) -> i32 {
+        let registry = match WrappyPaths::new() {
+            Ok(paths) => ContainerRegistry::new(&paths),
+            Err(error) => {
+                eprintln!("❌ Failed to remove '{}': {}", input, error);
+                return 1;
+            }
+        };
+        let missing: Vec<RegistryEntry> = registry
+            .adopted()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|entry| (entry.name == input || entry.selector() == input) && !entry.path.is_dir())
+            .collect();
+        let entry = match missing.as_slice() {
+            [entry] => entry,
+            [] => {
+                eprintln!("❌ Failed to remove '{}': {}", input, not_found);
+                return 1;
+            }
+            _ => {
+                let candidates = missing.iter().map(RegistryEntry::selector).collect();
+                let error = ContainerError::AmbiguousContainer {
+                    name: input.to_string(),
+                    candidates,
+                };
+                eprintln!("❌ Failed to remove '{}': {}", input, error);
+                return 1;
+            }
+        };
+
+        match registry.deregister(&entry.path) {
+            Ok(_) => {
+                println!("🗑️  Forgot {}, whose directory {} no longer exists", entry.selector(), entry.path.display());
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to remove {}: {}", entry.selector(), error);
+                1
+            }
+        }
+    }
+
+    fn remove_directory(path: &Path) -> ContainerResult<()> {
+        std::fs::remove_dir_all(path).map_err(|e| ContainerError::IoError {
+            path: path.to_path_buf(),
+            source: e,
+        })
+    }
+
+    /// Handles the files command execution
+    fn handle_files_command(input: String, ignored: bool) -> i32 {
+        let listing = ContainerResolver::new()
+            .and_then(|resolver| resolver.resolve_container(&input))
+            .and_then(|container| ContainerService::list_files(&container));
+        let listing = match listing {
+            Ok(listing) => listing,
+            Err(error) => {
+                eprintln!("❌ Failed to list files of '{}': {}", input, error);
+                return 1;
+            }
+        };
+
+        if ignored {
+            for path in &listing.ignored {
+                let suffix = if path.is_dir { "/" } else { "" };
+                println!("{}{}  ({}, {})", path.path, suffix, filesystem::format_size(path.size), path.rule);
+            }
+        } else {
+            for file in &listing.included {
+                println!("{}", file.path);
+            }
+        }
+        eprintln!(
+            "📁 {} file(s) included ({}), {} path(s) ignored ({})",
+            listing.included.len(),
+            filesystem::format_size(listing.included_size()),
+            listing.ignored.len(),
+            filesystem::format_size(listing.ignored_size())
+        );
+        0
+    }
+
+    /// Handles the seal command execution
+    fn handle_seal_command(input: String) -> i32 {
+        executor::install_interrupt_handler();
+        let cancellation = CancellationToken::linked_to(executor::interrupted);
+        let result = ContainerResolver::new()
+            .and_then(|resolver| resolver.resolve_container(&input))
+            .and_then(|container| SealService::seal(&container, &cancellation).map(|seal| (container, seal)));
+
+        match result {
+            Ok((container, seal)) => {
+                println!(
+                    "🔏 Sealed {}@{}: {} file(s), {}",
+                    container.name(),
+                    container.version(),
+                    seal.files.len(),
+                    filesystem::format_size(seal.total_size())
+                );
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to seal '{}': {}", input, error);
+                error.exit_code()
+            }
+        }
+    }
+
+    /// Handles the verify command execution
+    fn handle_verify_command(input: String, options: VerifyOptions, format: SummaryFormat) -> i32 {
+        let reporter = Reporter::new(format!("verify {}", input), format);
+        executor::install_interrupt_handler();
+        let cancellation = CancellationToken::linked_to(executor::interrupted);
+        let result = ContainerResolver::new()
+            .and_then(|resolver| resolver.resolve_container(&input))
+            .and_then(|container| SealService::verify(&container, options, &cancellation, &reporter));
+
+        let cancelled = match result {
+            Ok(summary) => {
+                let mut line = format!(
+                    "🔍 Checked {} file(s): {} hashed ({})",
+                    summary.files,
+                    summary.hashed,
+                    filesystem::format_size(summary.hashed_bytes)
+                );
+                if summary.trusted > 0 {
+                    line.push_str(&format!(", {} trusted by size and mtime", summary.trusted));
+                }
+                if summary.resumed > 0 {
+                    line.push_str(&format!(", {} verified by the earlier run", summary.resumed));
+                }
+                reporter.info(line);
+                if summary.mismatches == 0 {
+                    reporter.info("✅ Container matches its seal");
+                }
+                None
+            }
+            Err(error @ ContainerError::Cancelled { .. }) => {
+                reporter.failed(
+                    &input,
+                    &error,
+                    "⏹️  Verification was interrupted; run it again with --resume to continue",
+                );
+                Some(error.exit_code())
+            }
+            Err(error) => {
+                reporter.failed(&input, &error, format!("❌ Failed to verify '{}': {}", input, error));
+                None
+            }
+        };
+        let summary = reporter.finish();
+        cancelled.unwrap_or(summary.exit_code())
+    }
+
+    /// Handles the init command execution
+    fn handle_init_command(
+        path: Option<PathBuf>,
+        name: Option<String>,
+        container_type: ContainerType,
+        force: bool,
+        gitignore: bool,
+    ) -> i32 {
+        let container_path = match Self::resolve_container_path(path) {
+            Ok(path) => path,
+            Err(exit_code) => return exit_code,
+        };
+
+        // An existing container is left as it is; init then only reports where its state lives
+        if force || ManifestFormat::locate(&container_path).is_err() {
+            match ScaffoldService::scaffold(&container_path, name, container_type, force) {
+                Ok(scaffold) => {
+                    println!("📦 Scaffolded container '{}' in {}", scaffold.name, container_path.display());
+                    for path in &scaffold.created {
+                        println!("   + {}", path.strip_prefix(&container_path).unwrap_or(path).display());
+                    }
+                    println!("   Check it with `wrappy container validate {}`", container_path.display());
+                }
+                Err(error) => {
+                    eprintln!("❌ Failed to scaffold {}: {}", container_path.display(), error);
+                    return error.exit_code();
+                }
+            }
+        } else if name.is_some() {
+            println!("ℹ️  {} already has a manifest; --name only applies to new containers", container_path.display());
+        }
 
-impl ContainerHandler {
+        if StateLocation::is_version_controlled(&container_path) {
+            println!(
+                "📦 {} is a git working tree; wrappy keeps its state in {}",
+                container_path.display(),
+                StateLocation::resolve(&container_path).display()
+            );
+            let in_tree = StateLocation::in_tree_state(&container_path);
+            if !in_tree.is_empty() {
+                println!(
+                    "⚠️  {} state file(s) still in the working tree; move them with `wrappy container validate --fix`",
+                    in_tree.len()
+                );
+            }
+        } else {
+            println!("ℹ️  {} is not the root of a git working tree; state stays in the container", container_path.display());
+        }
 
-    /// Routes and executes the appropriate command
-    pub fn execute_command(command: ContainerCommands) -> i32 {
-        match command {
-            ContainerCommands::Validate { path, verbose } => {
-                Self::handle_validate_command(path, verbose)
+        if !gitignore {
+            return 0;
+        }
+        // Persistent directories hold user data and are ignored too when the manifest declares any
+        let persistent = ContainerService::load_from_directory(&container_path)
+            .map(|container| container.manifest.persistent)
+            .unwrap_or_default();
+        match StateLocation::write_gitignore(&container_path, &StateLocation::gitignore_entries(&persistent)) {
+            Ok(added) if added.is_empty() => println!("✅ .gitignore already covers wrappy's state"),
+            Ok(added) => {
+                println!("✅ Added to {}:", container_path.join(".gitignore").display());
+                for entry in added {
+                    println!("   {}", entry);
+                }
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to write .gitignore: {}", error);
+                return 1;
             }
         }
+        0
     }
 
-    /// Handles the validate command execution
-    pub fn handle_validate_command(path: Option<PathBuf>, verbose: bool) -> i32 {
+    fn handle_migrate_command(path: Option<PathBuf>) -> i32 {
         let container_path = match Self::resolve_container_path(path) {
             Ok(path) => path,
             Err(exit_code) => return exit_code,
         };
 
-        Self::print_validation_start(&container_path, verbose);
+        let report = match MigrationService::migrate_layout(&container_path) {
+            Ok(report) => report,
+            Err(error) => {
+                eprintln!("❌ Migration failed: {}", error);
+                return 1;
+            }
+        };
+
+        if report.applied.is_empty() {
+            println!("✅ No legacy layout detected, nothing to migrate");
+        } else {
+            if let Some(backup) = &report.backup {
+                println!("📦 Original manifest saved to {}", backup.display());
+            }
+            for migration in &report.applied {
+                println!("🔧 [{}] {}", migration.id, migration.description);
+            }
+            // The layout is now the one this wrappy writes
+            if let Err(error) = InstallMetadata::record_installed_with(&container_path) {
+                eprintln!("⚠️  Failed to record the wrappy version: {}", error);
+            }
+        }
 
         match Self::validate_container_at_path(&container_path) {
             Ok(container) => {
-                Self::print_validation_success(&container, verbose);
+                println!("✅ Container '{}' (v{}) is valid", container.name(), container.version());
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Container is still invalid after migration: {}", error);
+                1
+            }
+        }
+    }
+
+    /// Handles the manifest convert command execution
+    fn handle_convert_command(path: PathBuf, to: ManifestFormat, output: Option<PathBuf>) -> i32 {
+        let converted = (|| {
+            let manifest_path = if path.is_dir() { ManifestFormat::locate(&path)? } else { path.clone() };
+            ContainerManifest::from_file(&manifest_path)?.to_string_as(to)
+        })();
+
+        let content = match converted {
+            Ok(content) => content,
+            Err(error) => {
+                eprintln!("❌ Failed to convert manifest: {}", error);
+                return 1;
+            }
+        };
+
+        match output {
+            Some(output) => match filesystem::atomic_write(&output, content.as_bytes(), None) {
+                Ok(()) => {
+                    println!("✅ Wrote {}", output.display());
+                    0
+                }
+                Err(error) => {
+                    eprintln!("❌ Failed to write manifest: {}", error);
+                    1
+                }
+            },
+            None => {
+                println!("{}", content);
+                0
+            }
+        }
+    }
+
+    /// Handles the info command execution
+    fn handle_info_command(input: String, show_secrets: bool) -> i32 {
+        match ContainerResolver::new().and_then(|resolver| resolver.resolve_container(&input)) {
+            Ok(container) => {
+                Self::print_container_details(&container, show_secrets);
+                Self::print_install_metadata_warning(&container);
+                Self::print_persistent_info(&container);
+                Self::print_host_requirements_info(&container);
+                Self::print_interpreters_info(&container);
+                Self::print_latest_changelog_entry(&container);
                 0
             }
             Err(error) => {
-                Self::print_validation_error(&error, verbose);
+                eprintln!("❌ Failed to load container '{}': {}", input, error);
                 1
             }
         }
     }
 
+    /// Applies safe automatic fixes and reports each one when printing details
+    fn apply_fixes(container: &Container, details: bool) -> Result<(), ContainerError> {
+        PrivilegeGuard::ensure_owner(&container.path, "fix a container")?;
+        for path in StateLocation::migrate(&container.path)? {
+            if details {
+                let relative = path.strip_prefix(&container.path).unwrap_or(&path);
+                println!("🔧 Moved {} to {}", relative.display(), StateLocation::resolve(&container.path).display());
+            }
+        }
+        for script in ContainerService::materialize_inline_scripts(&container.path, &container.manifest)? {
+            if details {
+                println!("🔧 Materialized inline script '{}'", script);
+            }
+        }
+        for directory in ContainerService::ensure_persistent_dirs(container)? {
+            if details {
+                println!("🔧 Created persistent directory {}", directory.display());
+            }
+        }
+        for file in create_missing_config_files(&container.path)? {
+            if details {
+                let relative = file.strip_prefix(&container.path).unwrap_or(&file);
+                println!("🔧 Created {}", relative.display());
+            }
+        }
+        for script in ContainerService::convert_scripts_to_lf(&container.path, &container.manifest)? {
+            if details {
+                println!("🔧 Converted script '{}' to LF line endings", script);
+            }
+        }
+        Ok(())
+    }
+
+    /// Config files must parse. Missing ones read as empty, so they only fail validation in strict mode
+    fn check_config_files(container: &Container, strict: bool, details: bool) -> Result<Vec<String>, ContainerError> {
+        let allowlist = PermissionsConfig::load(&container.path)?.network_allowlist(&container.path)?;
+        EnvironmentConfig::load(&container.path)?;
+
+        let mut warnings = Vec::new();
+        if !allowlist.is_empty() {
+            let warning = format!(
+                "network allowlist of {} host(s) is not enforced, since runs are not sandboxed yet",
+                allowlist.rules().len()
+            );
+            if details {
+                eprintln!("⚠️  {}", warning);
+            }
+            warnings.push(warning);
+        }
+        for file in missing_config_files(&container.path) {
+            if strict {
+                return Err(ContainerError::InvalidStructure(format!("{} not found", file)));
+            }
+            if details {
+                eprintln!("⚠️  {} not found, read as empty; `wrappy container validate --fix` creates it", file);
+            }
+            warnings.push(format!("{} not found", file));
+        }
+        Ok(warnings)
+    }
+
+    /// Host requirements fail validation only in strict mode, otherwise they are returned as warnings
+    fn check_host_requirements(container: &Container, strict: bool, details: bool) -> Result<Vec<String>, ContainerError> {
+        if strict {
+            ContainerService::ensure_host_requirements(&container.manifest)?;
+            return Ok(Vec::new());
+        }
+
+        let mut warnings = Vec::new();
+        for status in ContainerService::check_host_requirements(&container.manifest) {
+            if !status.is_satisfied() {
+                if details {
+                    eprintln!("⚠️  Host requirement {}", status);
+                }
+                warnings.push(format!("host requirement {}", status));
+            }
+        }
+        Ok(warnings)
+    }
+
+    /// Checks the interpreter of every script and records the result for installed containers.
+    /// Missing interpreters fail validation only in strict mode, otherwise they are returned as warnings
+    fn check_interpreters(container: &Container, strict: bool, details: bool) -> Result<Vec<String>, ContainerError> {
+        let checks = InterpreterCheck::check_container(&container.path, &container.manifest);
+        let installed = ContainerResolver::new().is_ok_and(|resolver| resolver.is_installed(&container.path));
+        if installed {
+            if let Err(error) = InstallMetadata::record_interpreters(&container.path, &checks) {
+                eprintln!("⚠️  Failed to record interpreter checks: {}", error);
+            }
+        }
+
+        let mut warnings = Vec::new();
+        for check in checks.iter().filter(|check| !check.available) {
+            if strict {
+                check.ensure_available()?;
+            }
+            if details {
+                eprintln!("⚠️  Interpreter: {}", check);
+            }
+            warnings.push(format!("interpreter: {}", check));
+        }
+        Ok(warnings)
+    }
+
+    /// Scripts saved with CRLF line endings (usually on Windows) fail to start on Linux,
+    /// because the kernel looks for an interpreter whose name ends in `\r`.
+    /// They fail validation only in strict mode; `--fix` converts them to LF
+    fn check_line_endings(container: &Container, strict: bool, details: bool) -> Result<Vec<String>, ContainerError> {
+        let scripts = ContainerService::crlf_scripts(&container.path, &container.manifest);
+        if strict && !scripts.is_empty() {
+            let paths: Vec<&str> = scripts.iter().map(|(_, relative)| relative.as_str()).collect();
+            return Err(ContainerError::InvalidStructure(format!(
+                "scripts with CRLF line endings: {}",
+                paths.join(", ")
+            )));
+        }
+
+        let mut warnings = Vec::new();
+        for (name, relative) in scripts {
+            let warning = format!("script '{}' ({}) has CRLF line endings; fix with `validate --fix`", name, relative);
+            if details {
+                eprintln!("⚠️  Portability: {}", warning);
+            }
+            warnings.push(format!("portability: {}", warning));
+        }
+        Ok(warnings)
+    }
+
+    /// Dangling and cyclic symlinks are kept as they are by copies, so they only
+    /// fail validation in strict mode, otherwise they are returned as warnings
+    fn check_broken_symlinks(container: &Container, strict: bool, details: bool) -> Result<Vec<String>, ContainerError> {
+        let broken: Vec<String> = filesystem::broken_symlinks(&container.path)?
+            .iter()
+            .map(|link| {
+                let relative = link.strip_prefix(&container.path).unwrap_or(link);
+                match std::fs::read_link(link) {
+                    Ok(target) => format!("{} -> {}", relative.display(), target.display()),
+                    Err(_) => relative.display().to_string(),
+                }
+            })
+            .collect();
+
+        if strict && !broken.is_empty() {
+            return Err(ContainerError::InvalidStructure(format!(
+                "broken symlinks: {}",
+                broken.join(", ")
+            )));
+        }
+
+        let mut warnings = Vec::new();
+        for link in broken {
+            if details {
+                eprintln!("⚠️  Broken symlink {}", link);
+            }
+            warnings.push(format!("broken symlink {}", link));
+        }
+        Ok(warnings)
+    }
+
+    /// Symlinks leaving the container work once installed but cannot be exported,
+    /// so they fail validation only in strict mode, otherwise they are returned as warnings
+    fn check_escaping_symlinks(container: &Container, strict: bool, details: bool) -> Result<Vec<String>, ContainerError> {
+        let escaping: Vec<String> = filesystem::escaping_symlinks(&container.path)?
+            .iter()
+            .map(|(link, target)| {
+                let relative = link.strip_prefix(&container.path).unwrap_or(link);
+                format!("{} -> {}", relative.display(), target.display())
+            })
+            .collect();
+
+        if strict && !escaping.is_empty() {
+            return Err(ContainerError::InvalidStructure(format!(
+                "symlinks leaving the container: {}",
+                escaping.join(", ")
+            )));
+        }
+
+        let mut warnings = Vec::new();
+        for link in escaping {
+            if details {
+                eprintln!("⚠️  Symlink {} leaves the container and cannot be exported", link);
+            }
+            warnings.push(format!("symlink {} leaves the container", link));
+        }
+        Ok(warnings)
+    }
+
+    /// Names that only differ in case or Unicode normalization are fine here but become
+    /// one file on macOS or Windows, so they fail validation only in strict mode
+    fn check_name_collisions(container: &Container, strict: bool, details: bool) -> Result<Vec<String>, ContainerError> {
+        let collisions = portability::find_collisions(&container.path)?;
+
+        if strict && !collisions.is_empty() {
+            let pairs: Vec<String> = collisions.iter().map(ToString::to_string).collect();
+            return Err(ContainerError::InvalidStructure(format!(
+                "colliding names: {}",
+                pairs.join(", ")
+            )));
+        }
+
+        let mut warnings = Vec::new();
+        for collision in collisions {
+            if details {
+                eprintln!("⚠️  Colliding names: {}", collision);
+            }
+            warnings.push(format!("colliding names {}", collision));
+        }
+        Ok(warnings)
+    }
+
+    /// State written into a git working tree before wrappy kept it outside shows up as diff noise
+    fn check_in_tree_state(container: &Container, details: bool) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for path in StateLocation::in_tree_state(&container.path) {
+            let relative = path.strip_prefix(&container.path).unwrap_or(&path).display().to_string();
+            if details {
+                eprintln!("⚠️  State file {} is inside the git working tree; `--fix` moves it out", relative);
+            }
+            warnings.push(format!("state file {} is inside the git working tree", relative));
+        }
+        warnings
+    }
+
+    /// Binding sections from a newer wrappy are kept in the manifest but not acted on
+    fn check_unknown_bindings(container: &Container, details: bool) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for section in container.manifest.bindings.unknown_sections() {
+            if details {
+                eprintln!("⚠️  Unknown bindings section '{}' is ignored by this wrappy", section);
+            }
+            warnings.push(format!("unknown bindings section '{}' is ignored by this wrappy", section));
+        }
+        warnings
+    }
+
+    /// Targets through `AppData` or `Library/Application Support` work here, but
+    /// usually come from a manifest written for another platform
+    fn check_platform_targets(container: &Container, details: bool) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for (target, segment, platform) in container.manifest.bindings.platform_specific_targets() {
+            let warning = format!("binding target '{}' goes through '{}', a {} directory", target, segment, platform);
+            if details {
+                eprintln!("⚠️  Portability: {}", warning);
+            }
+            warnings.push(format!("portability: {}", warning));
+        }
+        warnings
+    }
+
     /// Resolves the container path from optional input or current directory
     fn resolve_container_path(path: Option<PathBuf>) -> Result<PathBuf, i32> {
         match path {
@@ -65,14 +2225,14 @@ impl ContainerHandler {
     }
 
     /// Prints validation start message if verbose mode is enabled
-    fn print_validation_start(path: &PathBuf, verbose: bool) {
+    fn print_validation_start(path: &Path, verbose: bool) {
         if verbose {
             println!("Validating container at: {}", path.display());
         }
     }
 
     /// Validates container at the specified path using service
-    fn validate_container_at_path(path: &PathBuf) -> Result<Container, ContainerError> {
+    fn validate_container_at_path(path: &Path) -> Result<Container, ContainerError> {
         ContainerService::load_from_directory(path)
     }
 
@@ -81,29 +2241,78 @@ impl ContainerHandler {
         println!(" Container validation successful!");
         
         if verbose {
-            Self::print_container_details(container);
+            Self::print_container_details(container, false);
         } else {
             println!("Container '{}' (v{}) is valid", container.name(), container.version());
         }
     }
 
     /// Prints detailed container information
-    fn print_container_details(container: &Container) {
+    fn print_container_details(container: &Container, show_secrets: bool) {
         println!("Container details:");
         println!("  Name: {}", container.name());
         println!("  Version: {}", container.version());
         println!("  Path: {}", container.path.display());
+        println!("  Installed with: {}", InstallMetadata::installed_with_label(&container.path));
         
         Self::print_scripts_info(container);
         Self::print_dependencies_info(container);
+        Self::print_environment_info(container, show_secrets);
+        Self::print_variables_info(container, show_secrets);
+        Self::print_profiles_info(container);
+    }
+
+    /// Prints the config profiles the container belongs to
+    fn print_profiles_info(container: &Container) {
+        let profiles = ProfileService::new()
+            .map(|service| service.profiles_of(container.name()))
+            .unwrap_or_default();
+        if !profiles.is_empty() {
+            println!("  Profiles: {}", profiles.join(", "));
+        }
+    }
+
+    /// Prints declared manifest variables with the values chosen at install time
+    fn print_variables_info(container: &Container, show_secrets: bool) {
+        if container.manifest.variables.is_empty() {
+            return;
+        }
+
+        let chosen = WrappyPaths::new()
+            .and_then(|paths| VariableStore::for_container(&paths, container.name()).load())
+            .unwrap_or_default();
+
+        println!("  Variables:");
+        for variable in &container.manifest.variables {
+            let value = match chosen.get(&variable.name) {
+                Some(_) if !show_secrets && redact::is_secret_key(&variable.name) => redact::REDACTED.to_string(),
+                Some(value) => value.clone(),
+                None if variable.required => "<not set, required>".to_string(),
+                None => "<not set>".to_string(),
+            };
+            println!("    {}={}", variable.name, value);
+            if !variable.description.is_empty() {
+                println!("      {}", variable.description);
+            }
+        }
+    }
+
+    /// Prints the manifest environment; secret-looking values stay masked unless requested
+    fn print_environment_info(container: &Container, show_secrets: bool) {
+        if !container.manifest.environment.is_empty() {
+            println!("  Environment:");
+            for line in redact::environment_lines(&container.manifest.environment, show_secrets) {
+                println!("    {}", line);
+            }
+        }
     }
 
     /// Prints container scripts information
     fn print_scripts_info(container: &Container) {
         if !container.manifest.scripts.is_empty() {
             println!("  Scripts:");
-            for (name, path) in &container.manifest.scripts {
-                println!("    {}: {}", name, path);
+            for (name, script) in &container.manifest.scripts {
+                println!("    {}: {}", name, script);
             }
         }
     }
@@ -118,6 +2327,81 @@ impl ContainerHandler {
         }
     }
 
+    /// Prints persistent data directories with their current size
+    /// Warns when a newer wrappy laid out the container
+    fn print_install_metadata_warning(container: &Container) {
+        if let Some(metadata) = InstallMetadata::read(&container.path).filter(InstallMetadata::is_from_newer_wrappy) {
+            println!(
+                "  ⚠️  Installed by wrappy {}, newer than this wrappy {}; some features may not work",
+                metadata.installed_with.unwrap_or_default(),
+                env!("CARGO_PKG_VERSION")
+            );
+        }
+    }
+
+    fn print_persistent_info(container: &Container) {
+        if container.manifest.persistent.is_empty() {
+            return;
+        }
+
+        println!("  Persistent data:");
+        for (entry, directory) in container.manifest.persistent.iter().zip(container.persistent_paths()) {
+            match filesystem::directory_size(&directory) {
+                Ok(size) => println!("    {}: {}", entry, filesystem::format_size(size)),
+                Err(_) => println!("    {}: not created yet", entry),
+            }
+        }
+    }
+
+    /// Prints script interpreters as recorded by the last validate or run; info does not re-check them
+    fn print_interpreters_info(container: &Container) {
+        let Some(metadata) = InstallMetadata::read(&container.path) else {
+            return;
+        };
+        if metadata.interpreters.is_empty() {
+            return;
+        }
+
+        println!("  Interpreters (checked {}):", metadata.recorded_at.format("%Y-%m-%d %H:%M"));
+        for check in &metadata.interpreters {
+            let marker = if check.available { "✅" } else { "❌" };
+            println!("    {} {}", marker, check);
+        }
+    }
+
+    /// Prints host requirements with their current satisfaction status
+    fn print_host_requirements_info(container: &Container) {
+        let statuses = ContainerService::check_host_requirements(&container.manifest);
+        if statuses.is_empty() {
+            return;
+        }
+
+        println!("  Host requirements:");
+        for status in statuses {
+            let marker = if status.is_satisfied() { "✅" } else { "❌" };
+            println!("    {} {}", marker, status);
+        }
+    }
+
+    /// Prints the newest CHANGELOG.md section, if the container ships one
+    fn print_latest_changelog_entry(container: &Container) {
+        let changelog = match ChangelogService::load(&container.path) {
+            Ok(Some(changelog)) => changelog,
+            Ok(None) => return,
+            Err(error) => {
+                eprintln!("⚠️  Could not read changelog: {}", error);
+                return;
+            }
+        };
+
+        if let Some(entry) = changelog.latest() {
+            println!("  Latest changes ({}):", entry.heading);
+            for line in entry.body.lines() {
+                println!("    {}", line);
+            }
+        }
+    }
+
     /// Prints validation error message and suggestions
     fn print_validation_error(error: &ContainerError, verbose: bool) {
         eprintln!("L Container validation failed: {}", error);
@@ -144,9 +2428,382 @@ impl ContainerHandler {
                 eprintln!("\nSuggestion: Ensure the default script exists in the scripts directory");
             }
             ContainerError::ScriptNotFound { script, .. } => {
-                eprintln!("\nSuggestion: Ensure script '{}' exists in the scripts directory", script);
+                eprintln!("\nSuggestion: Ensure script '{}' exists in the scripts directory or fix its path in manifest.json", script);
+            }
+            ContainerError::ScriptNotExecutable { path } => {
+                eprintln!("\nSuggestion: Make the script executable with 'chmod +x {}'", path.display());
+            }
+            ContainerError::HostRequirementsUnmet { .. } => {
+                eprintln!("\nSuggestion: Install the missing host tools or update them to the required versions");
+            }
+            ContainerError::MissingVariables { container, .. } => {
+                eprintln!("\nSuggestion: Choose values with 'wrappy bindings enable {} --set NAME=value'", container);
             }
             _ => {}
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::Executor;
+    use crate::features::setup::SetupService;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, symlink_fixture, TempHome};
+    use crate::shared::CancellationToken;
+    use serde_json::json;
+
+    /// Installs `name` requiring `dependencies` and returns its store directory.
+    fn install(home: &TempHome, name: &str, dependencies: &[&str]) -> PathBuf {
+        let mut manifest = fixture_manifest(name, "1.0.0");
+        manifest["dependencies"] = dependencies
+            .iter()
+            .map(|dependency| json!({ "name": dependency, "version": "1.0.0" }))
+            .collect();
+        let source = home.path().join("src").join(name);
+        fixture_container_at(&source, manifest);
+        let paths = home.paths();
+        let resolver = ContainerResolver::new().unwrap();
+        InstallService::install(&source, None, false, false, &paths, &resolver, &CancellationToken::new())
+            .unwrap()
+            .entry
+            .path
+    }
+
+    /// tool -> app -> lib
+    fn installed_chain(home: &TempHome) -> [PathBuf; 3] {
+        SetupService::initialize(&home.paths()).unwrap();
+        [install(home, "lib", &[]), install(home, "app", &["lib"]), install(home, "tool", &["app"])]
+    }
+
+    fn remove(name: &str, cascade: bool, force: bool) -> i32 {
+        remove_with(name, RemoveOptions { cascade, force, ..options() })
+    }
+
+    fn options() -> RemoveOptions {
+        RemoveOptions {
+            delete_files: false,
+            keep_bindings: false,
+            cascade: false,
+            force: false,
+            purge: false,
+            keep_state: false,
+            yes: true,
+        }
+    }
+
+    fn remove_with(name: &str, options: RemoveOptions) -> i32 {
+        ContainerHandler::handle_remove_command(name.to_string(), options)
+    }
+
+    fn install_with_data(home: &TempHome) -> PathBuf {
+        SetupService::initialize(&home.paths()).unwrap();
+        let mut manifest = fixture_manifest("app", "1.0.0");
+        manifest["persistent"] = json!(["data"]);
+        let source = home.path().join("src/app");
+        fixture_container_at(&source, manifest);
+        let installed = InstallService::install(
+            &source,
+            None,
+            false,
+            false,
+            &home.paths(),
+            &ContainerResolver::new().unwrap(),
+            &CancellationToken::new(),
+        )
+        .unwrap()
+        .entry
+        .path;
+        std::fs::write(installed.join("data/state"), "kept").unwrap();
+        installed
+    }
+
+    #[test]
+    fn remove_keeps_persistent_data_in_orphaned_data() {
+        let home = TempHome::enter();
+        let installed = install_with_data(&home);
+
+        assert_eq!(remove_with("app", options()), 0);
+
+        assert!(!installed.exists());
+        let kept = home.paths().orphaned_data_dir("app").join("data/state");
+        assert_eq!(std::fs::read_to_string(kept).unwrap(), "kept");
+    }
+
+    #[test]
+    fn remove_with_purge_deletes_the_data_too() {
+        let home = TempHome::enter();
+        let installed = install_with_data(&home);
+
+        assert_eq!(remove_with("app", RemoveOptions { purge: true, ..options() }), 0);
+
+        assert!(!installed.exists());
+        assert!(!home.paths().orphaned_data_dir("app").exists());
+    }
+
+    #[test]
+    fn keep_state_survives_a_reinstall_with_restore_state() {
+        let home = TempHome::enter();
+        SetupService::initialize(&home.paths()).unwrap();
+        let installed = install(&home, "app", &[]);
+        let container = ContainerService::load_from_directory(&installed).unwrap();
+        container.save_runtime().unwrap();
+        BindingManager::new().unwrap().install_bindings(&container).unwrap();
+
+        assert_eq!(remove_with("app", RemoveOptions { keep_state: true, ..options() }), 0);
+        assert!(!installed.exists());
+        assert!(query::bindings_of("app").is_empty());
+        let source = home.path().join("src/app");
+        assert_eq!(
+            ContainerHandler::handle_install_command(source, None, false, false, false, true),
+            0
+        );
+
+        let reinstalled = ContainerService::load_from_directory(&installed).unwrap();
+        assert_eq!(reinstalled.runtime.id, container.runtime.id);
+        let active: Vec<String> = query::bindings_of("app")
+            .into_iter()
+            .filter(|binding| binding.active)
+            .map(|binding| binding.target)
+            .collect();
+        assert_eq!(active, ["app-tool"]);
+        assert!(!StateStash::for_container(&home.paths(), "app").exists());
+    }
+
+    #[test]
+    fn remove_refuses_while_other_containers_require_it() {
+        let home = TempHome::enter();
+        let [lib, app, tool] = installed_chain(&home);
+
+        assert_eq!(remove("lib", false, false), 1);
+
+        assert!(lib.is_dir() && app.is_dir() && tool.is_dir());
+    }
+
+    #[test]
+    fn cascade_removes_every_dependent_too() {
+        let home = TempHome::enter();
+        let [lib, app, tool] = installed_chain(&home);
+
+        assert_eq!(remove("lib", true, false), 0);
+
+        assert!(!lib.exists() && !app.exists() && !tool.exists());
+        assert!(ContainerRegistry::new(&home.paths()).entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn force_removes_it_and_marks_direct_dependents_degraded() {
+        let home = TempHome::enter();
+        let [lib, app, tool] = installed_chain(&home);
+
+        assert_eq!(remove("lib", false, true), 0);
+
+        assert!(!lib.exists() && app.is_dir() && tool.is_dir());
+        let registry = ContainerRegistry::new(&home.paths());
+        let listed = registry.installed(&home.paths().default_store_dir()).unwrap();
+        let status = |name: &str| listed.iter().find(|row| row.name == name).unwrap().status;
+        assert_eq!(status("app"), InstalledStatus::Degraded);
+        assert_eq!(status("tool"), InstalledStatus::Ok);
+    }
+
+    #[test]
+    fn a_container_nothing_requires_is_removed_as_before() {
+        let home = TempHome::enter();
+        let [lib, app, tool] = installed_chain(&home);
+
+        assert_eq!(remove("tool", false, false), 0);
+
+        assert!(lib.is_dir() && app.is_dir() && !tool.exists());
+    }
+
+    fn validate_options(output: Option<PathBuf>, junit: Option<PathBuf>, quiet: bool) -> ValidateOptions {
+        ValidateOptions {
+            verbose: false,
+            strict: false,
+            fix: false,
+            portability_checks: true,
+            with_dependencies: false,
+            output,
+            output_format: ReportFormat::Json,
+            junit,
+            quiet,
+        }
+    }
+
+    #[test]
+    fn validate_checks_every_path_and_writes_json_and_junit_reports() {
+        let home = TempHome::enter();
+        let good = home.path().join("good");
+        fixture_container_at(&good, fixture_manifest("good", "1.0.0"));
+        let bad = home.path().join("bad");
+        fixture_container_at(&bad, fixture_manifest("bad", "1.0.0"));
+        std::fs::write(bad.join("manifest.json"), "{ not json").unwrap();
+        let json_report = home.path().join("report.json");
+        let junit_report = home.path().join("junit.xml");
+
+        let status = ContainerHandler::handle_validate_command(
+            vec![good.clone(), bad.clone()],
+            false,
+            validate_options(Some(json_report.clone()), Some(junit_report.clone()), false),
+        );
+
+        assert_eq!(status, 1);
+        let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&json_report).unwrap()).unwrap();
+        assert_eq!(report["containers"].as_array().unwrap().len(), 2);
+        assert_eq!(report["containers"][0]["valid"], true);
+        assert_eq!(report["containers"][1]["valid"], false);
+        assert_eq!(report["containers"][1]["path"], json!(bad));
+        let junit = std::fs::read_to_string(&junit_report).unwrap();
+        assert_eq!(junit.matches("<testcase ").count(), 2);
+        assert_eq!(junit.matches("<failure ").count(), 1);
+    }
+
+    #[test]
+    fn validate_succeeds_quietly_when_every_path_is_valid() {
+        let home = TempHome::enter();
+        let paths: Vec<PathBuf> = ["a", "b"]
+            .iter()
+            .map(|name| {
+                let dir = home.path().join(name);
+                fixture_container_at(&dir, fixture_manifest(name, "1.0.0"));
+                dir
+            })
+            .collect();
+
+        assert_eq!(ContainerHandler::handle_validate_command(paths.clone(), false, validate_options(None, None, true)), 0);
+        let report = ContainerHandler::validation_report(&paths, false);
+        let labels: Vec<String> = report.containers.iter().map(ContainerValidationResult::label).collect();
+        assert_eq!(labels, ["a@1.0.0", "b@1.0.0"]);
+    }
+
+    #[test]
+    fn an_unknown_bindings_section_warns_and_survives_an_edit_and_save() {
+        let home = TempHome::enter();
+        let dir = home.path().join("app");
+        let mut manifest = fixture_manifest("app", "1.0.0");
+        let sockets = json!([{ "source": "run/app.sock", "target": "~/.local/run/app.sock", "activation": "lazy" }]);
+        manifest["bindings"]["sockets"] = sockets.clone();
+        fixture_container_at(&dir, manifest);
+
+        let report = ContainerHandler::validation_report(std::slice::from_ref(&dir), false);
+        let mut loaded = ContainerManifest::from_file(dir.join("manifest.json")).unwrap();
+        loaded.bindings.add_executable(serde_json::from_value(json!({ "source": "bin/tool", "target": "second" })).unwrap());
+        let saved: serde_json::Value = serde_json::from_str(&loaded.to_string_as(ManifestFormat::Json).unwrap()).unwrap();
+
+        let result = &report.containers[0];
+        assert!(result.valid, "{:?}", result.errors);
+        assert!(result.warnings.iter().any(|warning| warning.contains("'sockets'")), "{:?}", result.warnings);
+        assert_eq!(loaded.bindings.unknown_sections(), ["sockets"]);
+        assert_eq!(saved["bindings"]["sockets"], sockets);
+        assert_eq!(saved["bindings"]["executables"][1]["target"], "second");
+    }
+
+    /// Adopts a container at `home/<dir_name>` with the executable binding `odd-tool`.
+    fn adoptable(home: &TempHome, dir_name: &str) -> PathBuf {
+        SetupService::initialize(&home.paths()).unwrap();
+        let dir = home.path().join(dir_name);
+        let mut manifest = fixture_manifest("odd", "1.0.0");
+        manifest["bindings"]["executables"] = json!([{ "source": "bin/tool", "target": "odd-tool" }]);
+        fixture_container_at(&dir, manifest);
+        dir
+    }
+
+    #[test]
+    fn a_path_with_spaces_and_quotes_is_adopted_enabled_and_run() {
+        let home = TempHome::enter();
+        let dir = adoptable(&home, "my \"odd\" 'app' dir");
+
+        assert_eq!(ContainerHandler::handle_adopt_command(dir.clone(), None, false, false), 0);
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+        BindingManager::new().unwrap().install_bindings(&container).unwrap();
+        let output = std::process::Command::new(home.paths().bin_dir.join("odd-tool"))
+            .arg("it's $HOME")
+            .env_remove("WRAPPY_ACTIVE_ODD_TOOL")
+            .output()
+            .unwrap();
+        let run = Executor::new(container).output(OutputMode::Quiet).run("default", &[]);
+
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("tool it's $HOME"));
+        assert_eq!(run.unwrap(), 0);
+    }
+
+    #[test]
+    fn a_colon_path_is_only_adopted_with_the_flag_and_never_put_on_path() {
+        let home = TempHome::enter();
+        let dir = adoptable(&home, "app:1");
+
+        let refused = ContainerHandler::handle_adopt_command(dir.clone(), None, false, false);
+        let allowed = ContainerHandler::handle_adopt_command(dir.clone(), None, false, true);
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+        let bindings = BindingManager::new().unwrap().install_bindings(&container);
+
+        assert_ne!(refused, 0);
+        assert_eq!(allowed, 0);
+        assert!(bindings.is_err_and(|e| e.to_string().contains("PATH")));
+        assert!(!home.paths().bin_dir.join("odd-tool").exists());
+    }
+
+    #[test]
+    fn an_initialized_container_passes_validate_strictly() {
+        let home = TempHome::enter();
+        let dir = home.path().join("fresh");
+        let report = home.path().join("report.json");
+        let mut options = validate_options(Some(report.clone()), None, true);
+        options.strict = true;
+
+        let init = ContainerHandler::handle_init_command(Some(dir.clone()), None, ContainerType::Application, false, false);
+        let validate = ContainerHandler::handle_validate_command(vec![dir.clone()], false, options);
+
+        assert_eq!((init, validate), (0, 0));
+        let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&report).unwrap()).unwrap();
+        assert_eq!(report["containers"][0]["valid"], true, "{}", report);
+    }
+
+    #[test]
+    fn validate_warns_about_dangling_cyclic_and_escaping_symlinks_and_strict_refuses_them() {
+        let home = TempHome::enter();
+        let cases: [(&str, &[&str]); 3] = [
+            ("dangling", &["broken symlink content/link -> missing.txt"]),
+            ("cyclic", &["broken symlink content/link -> loop-b", "broken symlink content/loop-b -> link"]),
+            ("absolute", &["symlink content/link -> /etc leaves the container"]),
+        ];
+        let report_of = |path: &Path| -> serde_json::Value {
+            let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+            report["containers"][0].clone()
+        };
+
+        for (kind, expected) in cases {
+            let lenient_report = home.path().join(format!("{}.json", kind));
+            let strict_report = home.path().join(format!("{}-strict.json", kind));
+            let mut strict = validate_options(Some(strict_report.clone()), None, true);
+            strict.strict = true;
+
+            let lenient = ContainerHandler::handle_validate_command(
+                vec![symlink_fixture(kind)],
+                false,
+                validate_options(Some(lenient_report.clone()), None, true),
+            );
+            let strict = ContainerHandler::handle_validate_command(vec![symlink_fixture(kind)], false, strict);
+
+            assert_eq!((lenient, strict), (0, 1), "{}", kind);
+            assert_eq!(report_of(&lenient_report)["warnings"], json!(expected), "{}", kind);
+            let errors = report_of(&strict_report)["errors"].to_string();
+            assert!(errors.contains("content/link ->"), "{}: {}", kind, errors);
+        }
+    }
+
+    #[test]
+    fn info_finds_an_adopted_container_by_name_and_version() {
+        let home = TempHome::enter();
+        let dir = adoptable(&home, "checkout");
+        assert_eq!(ContainerHandler::handle_adopt_command(dir.clone(), None, false, false), 0);
+
+        let by_name = ContainerHandler::handle_info_command("odd".into(), false);
+        let by_version = ContainerHandler::handle_info_command("odd@1.0.0".into(), false);
+        let by_path = ContainerHandler::handle_info_command(dir.to_string_lossy().into_owned(), false);
+        let unknown = ContainerHandler::handle_info_command("odd-typo".into(), false);
+
+        assert_eq!((by_name, by_version, by_path, unknown), (0, 0, 0, 1));
+    }
+}
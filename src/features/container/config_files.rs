@@ -0,0 +1,174 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::features::netguard::HostAllowlist;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+
+pub const PERMISSIONS_FILE: &str = "config/permissions.json";
+pub const ENVIRONMENT_FILE: &str = "config/environment.json";
+
+/// Config files every container is expected to ship. A missing one reads as empty;
+/// `validate` warns about it, `--strict` fails and `--fix` writes the skeleton.
+pub const CONFIG_FILES: [&str; 2] = [PERMISSIONS_FILE, ENVIRONMENT_FILE];
+
+/// Contents of `config/permissions.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PermissionsConfig {
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Host paths the container may access
+    #[serde(default)]
+    pub filesystem: Vec<String>,
+    /// Hosts the container may connect to, as `host:port` entries
+    #[serde(default)]
+    pub network: Vec<String>,
+    /// Keys this wrappy does not know, kept when the file is rewritten
+    #[serde(flatten)]
+    pub unknown: BTreeMap<String, Value>,
+}
+
+/// Contents of `config/environment.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentConfig {
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    /// Keys this wrappy does not know, kept when the file is rewritten
+    #[serde(flatten)]
+    pub unknown: BTreeMap<String, Value>,
+}
+
+impl PermissionsConfig {
+    pub const SCHEMA: &'static str = "wrappy:permissions:1";
+
+    /// Reads the file of the container at `root`; a missing file is an empty config.
+    pub fn load(root: &Path) -> ContainerResult<Self> {
+        load_config(&root.join(PERMISSIONS_FILE))
+    }
+
+    /// What `validate --fix` writes: the schema key and empty sections to fill in.
+    pub fn skeleton() -> Self {
+        Self {
+            schema: Some(Self::SCHEMA.to_string()),
+            ..Self::default()
+        }
+    }
+}
+
+impl PermissionsConfig {
+    /// The `network` entries as rules; malformed entries fail `validate` with this error.
+    pub fn network_allowlist(&self, root: &Path) -> ContainerResult<HostAllowlist> {
+        HostAllowlist::parse(&self.network).map_err(|errors| ContainerError::InvalidConfig {
+            path: root.join(PERMISSIONS_FILE),
+            reason: errors.join("; "),
+        })
+    }
+}
+
+impl EnvironmentConfig {
+    pub const SCHEMA: &'static str = "wrappy:environment:1";
+
+    /// Reads the file of the container at `root`; a missing file is an empty config.
+    pub fn load(root: &Path) -> ContainerResult<Self> {
+        load_config(&root.join(ENVIRONMENT_FILE))
+    }
+
+    /// What `validate --fix` writes: the schema key and empty sections to fill in.
+    pub fn skeleton() -> Self {
+        Self {
+            schema: Some(Self::SCHEMA.to_string()),
+            ..Self::default()
+        }
+    }
+}
+
+/// Config files the container at `root` does not have, relative to it.
+pub fn missing_config_files(root: &Path) -> Vec<&'static str> {
+    CONFIG_FILES.iter().copied().filter(|file| !root.join(file).exists()).collect()
+}
+
+/// Writes the skeleton of every missing config file and returns the paths written.
+pub fn create_missing_config_files(root: &Path) -> ContainerResult<Vec<PathBuf>> {
+    let mut created = Vec::new();
+    for file in missing_config_files(root) {
+        let skeleton = match file {
+            PERMISSIONS_FILE => serde_json::to_vec_pretty(&PermissionsConfig::skeleton())?,
+            _ => serde_json::to_vec_pretty(&EnvironmentConfig::skeleton())?,
+        };
+        let path = root.join(file);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| ContainerError::IoError {
+                path: parent.to_path_buf(),
+                source: e,
+            })?;
+        }
+        let mut content = skeleton;
+        content.push(b'\n');
+        filesystem::atomic_write(&path, &content, None)?;
+        created.push(path);
+    }
+    Ok(created)
+}
+
+fn load_config<T: DeserializeOwned + Default>(path: &Path) -> ContainerResult<T> {
+    let content = match filesystem::read_json_file(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => {
+            return Err(ContainerError::IoError {
+                path: path.to_path_buf(),
+                source: e,
+            })
+        }
+    };
+    serde_json::from_str(&content).map_err(|e| ContainerError::InvalidConfig {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn permissions(dir: &TempDir, network: &[&str]) -> PermissionsConfig {
+        fs::create_dir_all(dir.path().join("config")).unwrap();
+        let content = serde_json::json!({ "$schema": PermissionsConfig::SCHEMA, "network": network });
+        fs::write(dir.path().join(PERMISSIONS_FILE), content.to_string()).unwrap();
+        PermissionsConfig::load(dir.path()).unwrap()
+    }
+
+    #[test]
+    fn the_network_list_becomes_an_allowlist() {
+        let dir = TempDir::new().unwrap();
+
+        let allowlist = permissions(&dir, &["api.example.com:443", "[::1]:*"]).network_allowlist(dir.path()).unwrap();
+
+        assert_eq!(allowlist.rules().len(), 2);
+        assert!(allowlist.allows("::1", 9000));
+        assert!(PermissionsConfig::default().network_allowlist(dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed_network_entries_are_all_named_in_one_config_error() {
+        let dir = TempDir::new().unwrap();
+
+        let error = permissions(&dir, &["ok.example.com:443", "nohost", "::1:80"]).network_allowlist(dir.path()).unwrap_err();
+
+        match error {
+            ContainerError::InvalidConfig { path, reason } => {
+                assert_eq!(path, dir.path().join(PERMISSIONS_FILE));
+                assert!(reason.contains("'nohost'") && reason.contains("'::1:80'"), "{}", reason);
+                assert!(!reason.contains("ok.example.com"), "{}", reason);
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+}
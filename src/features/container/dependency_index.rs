@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::features::ContainerManifest;
+
+/// Reverse-dependency index answering "who depends on this container?".
+/// Maintained incrementally as containers are registered and removed, so removal
+/// protection never has to re-read every installed manifest.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DependencyIndex {
+    /// Container name -> names it declares as dependencies
+    #[serde(default)]
+    dependencies: BTreeMap<String, BTreeSet<String>>,
+    /// Dependency name -> containers that declare it
+    #[serde(default)]
+    dependents: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl DependencyIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a container's declared dependencies, replacing any previous entry for it.
+    pub fn register(&mut self, manifest: &ContainerManifest) {
+        self.unregister(&manifest.name);
+        self.add(
+            &manifest.name,
+            manifest.dependencies.iter().map(|dependency| dependency.name.clone()),
+        );
+    }
+
+    /// Adds dependencies of `name` to those already recorded, as for another installed version.
+    pub fn add(&mut self, name: &str, dependencies: impl IntoIterator<Item = String>) {
+        let recorded = self.dependencies.entry(name.to_string()).or_default();
+        for dependency in dependencies {
+            self.dependents
+                .entry(dependency.clone())
+                .or_default()
+                .insert(name.to_string());
+            recorded.insert(dependency);
+        }
+    }
+
+    pub fn unregister(&mut self, name: &str) {
+        let Some(dependencies) = self.dependencies.remove(name) else {
+            return;
+        };
+
+        for dependency in dependencies {
+            if let Some(dependents) = self.dependents.get_mut(&dependency) {
+                dependents.remove(name);
+                if dependents.is_empty() {
+                    self.dependents.remove(&dependency);
+                }
+            }
+        }
+    }
+
+    /// Containers that directly declare `name` as a dependency.
+    pub fn dependents_of(&self, name: &str) -> Vec<String> {
+        self.dependents
+            .get(name)
+            .map(|dependents| dependents.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// All direct and indirect dependents of `name` in safe removal order:
+    /// every container appears before anything it depends on, so a cascading
+    /// remove never leaves a container whose dependency is already gone.
+    pub fn cascade_removal_order(&self, name: &str) -> Vec<String> {
+        let mut order = Vec::new();
+        let mut visited = BTreeSet::new();
+        self.visit_dependents(name, &mut visited, &mut order);
+        order.retain(|dependent| dependent != name);
+        order
+    }
+
+    /// Orders `names` so every container comes after the ones it depends on.
+    /// Only the given names are returned; otherwise their input order is kept.
+    pub fn install_order(&self, names: &[String]) -> Vec<String> {
+        let wanted: BTreeSet<&String> = names.iter().collect();
+        let mut order = Vec::new();
+        let mut visited = BTreeSet::new();
+        for name in names {
+            self.visit_dependencies(name, &mut visited, &mut order);
+        }
+        order.retain(|name| wanted.contains(name));
+        order
+    }
+
+    fn visit_dependencies(&self, name: &str, visited: &mut BTreeSet<String>, order: &mut Vec<String>) {
+        if !visited.insert(name.to_string()) {
+            return;
+        }
+
+        for dependency in self.dependencies.get(name).into_iter().flatten() {
+            self.visit_dependencies(dependency, visited, order);
+        }
+        order.push(name.to_string());
+    }
+
+    /// Post-order walk over dependents; cycles are cut by the visited set
+    fn visit_dependents(&self, name: &str, visited: &mut BTreeSet<String>, order: &mut Vec<String>) {
+        if !visited.insert(name.to_string()) {
+            return;
+        }
+
+        for dependent in self.dependents_of(name) {
+            self.visit_dependents(&dependent, visited, order);
+        }
+        order.push(name.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// tool -> app -> lib, and cli -> lib
+    fn index() -> DependencyIndex {
+        let mut index = DependencyIndex::new();
+        index.add("lib", Vec::new());
+        index.add("app", ["lib".to_string()]);
+        index.add("tool", ["app".to_string()]);
+        index.add("cli", ["lib".to_string()]);
+        index
+    }
+
+    #[test]
+    fn dependents_are_the_direct_ones_only() {
+        assert_eq!(index().dependents_of("lib"), vec!["app", "cli"]);
+        assert!(index().dependents_of("tool").is_empty());
+    }
+
+    #[test]
+    fn cascade_order_removes_every_dependent_before_what_it_requires() {
+        let order = index().cascade_removal_order("lib");
+
+        assert_eq!(order.len(), 3);
+        let position = |name: &str| order.iter().position(|entry| entry == name).unwrap();
+        assert!(position("tool") < position("app"));
+        assert!(!order.contains(&"lib".to_string()));
+    }
+
+    #[test]
+    fn versions_add_up_and_unregister_forgets_them_all() {
+        let mut index = index();
+        index.add("app", ["cache".to_string()]);
+        assert_eq!(index.dependents_of("cache"), vec!["app"]);
+        assert_eq!(index.dependents_of("lib"), vec!["app", "cli"]);
+
+        index.unregister("app");
+
+        assert!(index.dependents_of("cache").is_empty());
+        assert_eq!(index.dependents_of("lib"), vec!["cli"]);
+    }
+
+    #[test]
+    fn cycles_do_not_loop() {
+        let mut index = DependencyIndex::new();
+        index.add("a", ["b".to_string()]);
+        index.add("b", ["a".to_string()]);
+
+        assert_eq!(index.cascade_removal_order("a"), vec!["b"]);
+    }
+}
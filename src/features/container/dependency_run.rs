@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::path::PathBuf;
+
+use crate::features::container::{Container, ContainerResolver, ContainerService};
+use crate::shared::error::{ContainerError, ContainerResult};
+
+/// The wrappy executable, so scripts can call `"$WRAPPY_BIN" container run-dep ...`.
+pub const WRAPPY_BIN_VAR: &str = "WRAPPY_BIN";
+pub const CONTAINER_NAME_VAR: &str = "WRAPPY_CONTAINER_NAME";
+pub const CONTAINER_VERSION_VAR: &str = "WRAPPY_CONTAINER_VERSION";
+pub const CONTAINER_PATH_VAR: &str = "WRAPPY_CONTAINER_PATH";
+/// Names of the containers whose scripts led to this run, outermost first and separated by commas.
+pub const CONTAINER_CHAIN_VAR: &str = "WRAPPY_CONTAINER_CHAIN";
+/// The first persistent directory of the manifest; not set when it declares none.
+pub const DATA_DIR_VAR: &str = "WRAPPY_DATA_DIR";
+
+/// Dependency runs nested deeper than this are refused, even without a cycle.
+pub const MAX_DEPENDENCY_RUN_DEPTH: usize = 8;
+
+/// The container a script was started for, read back from the variables the executor exports.
+#[derive(Debug, Clone)]
+pub struct RunContext {
+    pub container_path: PathBuf,
+    /// Container names from the outermost run to the current one
+    pub chain: Vec<String>,
+}
+
+impl RunContext {
+    /// `None` outside a script started by wrappy.
+    pub fn from_env() -> Option<Self> {
+        let container_path = env::var_os(CONTAINER_PATH_VAR).filter(|path| !path.is_empty())?;
+        let chain = env::var(CONTAINER_CHAIN_VAR)
+            .unwrap_or_default()
+            .split(',')
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        Some(Self {
+            container_path: PathBuf::from(container_path),
+            chain,
+        })
+    }
+
+    /// Variables a run of `container` exports; `callers` are the containers that led to it.
+    pub fn variables(container: &Container, callers: &[String]) -> BTreeMap<String, String> {
+        let mut chain = callers.to_vec();
+        chain.push(container.name().to_string());
+
+        let mut variables = BTreeMap::new();
+        if let Ok(wrappy) = env::current_exe() {
+            variables.insert(WRAPPY_BIN_VAR.to_string(), wrappy.to_string_lossy().into_owned());
+        }
+        variables.insert(CONTAINER_NAME_VAR.to_string(), container.name().to_string());
+        variables.insert(CONTAINER_VERSION_VAR.to_string(), container.version().to_string());
+        variables.insert(CONTAINER_PATH_VAR.to_string(), container.path.to_string_lossy().into_owned());
+        variables.insert(CONTAINER_CHAIN_VAR.to_string(), chain.join(","));
+        if let Some(data_dir) = container.persistent_paths().first() {
+            variables.insert(DATA_DIR_VAR.to_string(), data_dir.to_string_lossy().into_owned());
+        }
+        variables
+    }
+}
+
+/// Runs scripts of a container's declared dependencies, as `container run --dep` and
+/// `container run-dep` do.
+pub struct DependencyRun;
+
+impl DependencyRun {
+    /// Splits `toolchain:compile` into the dependency and the script; without a script it is `default`.
+    pub fn parse_target(target: &str) -> (&str, &str) {
+        match target.split_once(':') {
+            Some((dependency, script)) if !script.is_empty() => (dependency, script),
+            Some((dependency, _)) => (dependency, "default"),
+            None => (target, "default"),
+        }
+    }
+
+    /// The installed container `dependency` of `dependent` resolves to. It must be declared
+    /// in the manifest of `dependent`, and must not already be in `chain`, the containers
+    /// whose scripts are running, nor make the chain longer than `MAX_DEPENDENCY_RUN_DEPTH`.
+    pub fn resolve(
+        dependent: &Container,
+        dependency: &str,
+        chain: &[String],
+        resolver: &ContainerResolver,
+    ) -> ContainerResult<Container> {
+        let declared = dependent
+            .manifest
+            .dependencies
+            .iter()
+            .find(|declared| declared.name == dependency)
+            .ok_or_else(|| ContainerError::InvalidDependency {
+                package: dependency.to_string(),
+                reason: format!("'{}' does not declare it in its dependencies", dependent.name()),
+            })?;
+
+        if chain.iter().any(|name| name == dependency) {
+            let mut cycle = chain.to_vec();
+            cycle.push(dependency.to_string());
+            return Err(ContainerError::CircularDependency {
+                chain: cycle.join(" -> "),
+            });
+        }
+        if chain.len() >= MAX_DEPENDENCY_RUN_DEPTH {
+            return Err(ContainerError::InvalidDependency {
+                package: dependency.to_string(),
+                reason: format!("dependency runs are nested more than {} deep", MAX_DEPENDENCY_RUN_DEPTH),
+            });
+        }
+
+        let candidate = resolver
+            .resolve_dependency(declared)
+            .ok_or_else(|| ContainerError::InvalidDependency {
+                package: dependency.to_string(),
+                reason: format!("no installed version matches '{}'", declared.version),
+            })?;
+        ContainerService::load_from_directory(&candidate.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest};
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn runs_get_the_first_persistent_directory_as_data_dir() {
+        let dir = TempDir::new().unwrap();
+        let mut manifest = fixture_manifest("app", "1.0.0");
+        manifest["persistent"] = json!(["data", "cache"]);
+        fixture_container_at(dir.path(), manifest);
+        let container = ContainerService::load_from_directory(dir.path()).unwrap();
+
+        let variables = RunContext::variables(&container, &["caller".to_string()]);
+
+        assert_eq!(variables[DATA_DIR_VAR], dir.path().join("data").to_string_lossy());
+        assert_eq!(variables[CONTAINER_CHAIN_VAR], "caller,app");
+    }
+
+    #[test]
+    fn no_data_dir_without_persistent_directories() {
+        let dir = TempDir::new().unwrap();
+        fixture_container_at(dir.path(), fixture_manifest("app", "1.0.0"));
+        let container = ContainerService::load_from_directory(dir.path()).unwrap();
+
+        assert!(!RunContext::variables(&container, &[]).contains_key(DATA_DIR_VAR));
+    }
+}
@@ -0,0 +1,208 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::features::ContainerManifest;
+
+pub use crate::shared::glob::glob_matches;
+
+/// Host variables passed into containers that do not inherit the host environment,
+/// covering what a shell and GUI toolkits need to find the session, display and locale.
+pub const DEFAULT_ENVIRONMENT_PASSTHROUGH: [&str; 14] = [
+    "HOME",
+    "USER",
+    "PATH",
+    "TERM",
+    "LANG",
+    "LANGUAGE",
+    "LC_*",
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XAUTHORITY",
+    "XDG_RUNTIME_DIR",
+    "XDG_SESSION_TYPE",
+    "DBUS_SESSION_BUS_ADDRESS",
+    "PULSE_SERVER",
+];
+
+/// Where a variable in the child environment came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EnvironmentSource {
+    /// Inherited because the host environment is passed through as a whole
+    Inherited,
+    /// Allowed by a passthrough pattern while the host environment is not inherited
+    Passthrough { pattern: String },
+    /// Set by the manifest, overriding any host value
+    Manifest,
+    /// Set in the container's `config/environment.json`, overriding the manifest
+    ConfigFile,
+    /// Describes the run itself (`WRAPPY_BIN`, `WRAPPY_CONTAINER_*`); nothing overrides these
+    Wrappy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvironmentEntry {
+    pub key: String,
+    pub value: String,
+    pub source: EnvironmentSource,
+}
+
+/// Builds the environment of a script run. Host variables come first, either all of them
+/// or only those matching the passthrough list. Manifest values are applied over them, then
+/// the variables of `config/environment.json`, so the local config of a container wins over
+/// the manifest. The variables describing the run come last.
+pub struct EnvironmentBuilder<'a> {
+    manifest: &'a ContainerManifest,
+    inherit_host: bool,
+    config_variables: BTreeMap<String, String>,
+    run_variables: BTreeMap<String, String>,
+}
+
+impl<'a> EnvironmentBuilder<'a> {
+    pub fn new(manifest: &'a ContainerManifest) -> Self {
+        Self {
+            manifest,
+            inherit_host: true,
+            config_variables: BTreeMap::new(),
+            run_variables: BTreeMap::new(),
+        }
+    }
+
+    pub fn inherit_host(mut self, inherit_host: bool) -> Self {
+        self.inherit_host = inherit_host;
+        self
+    }
+
+    /// Variables from `config/environment.json`.
+    pub fn config_variables(mut self, variables: BTreeMap<String, String>) -> Self {
+        self.config_variables = variables;
+        self
+    }
+
+    /// Variables wrappy sets for the run, applied after everything else.
+    pub fn run_variables(mut self, variables: BTreeMap<String, String>) -> Self {
+        self.run_variables = variables;
+        self
+    }
+
+    /// Returns the final environment sorted by variable name.
+    pub fn build<I>(&self, host: I) -> Vec<EnvironmentEntry>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let passthrough = self.manifest.environment_passthrough();
+        let mut entries: BTreeMap<String, EnvironmentEntry> = BTreeMap::new();
+
+        for (key, value) in host {
+            let source = if self.inherit_host {
+                EnvironmentSource::Inherited
+            } else {
+                match passthrough.iter().find(|pattern| glob_matches(pattern, &key)) {
+                    Some(pattern) => EnvironmentSource::Passthrough {
+                        pattern: pattern.clone(),
+                    },
+                    None => continue,
+                }
+            };
+            entries.insert(key.clone(), EnvironmentEntry { key, value, source });
+        }
+
+        let overrides = self
+            .manifest
+            .environment
+            .iter()
+            .map(|entry| (entry, EnvironmentSource::Manifest))
+            .chain(self.config_variables.iter().map(|entry| (entry, EnvironmentSource::ConfigFile)))
+            .chain(self.run_variables.iter().map(|entry| (entry, EnvironmentSource::Wrappy)));
+        for ((key, value), source) in overrides {
+            entries.insert(
+                key.clone(),
+                EnvironmentEntry {
+                    key: key.clone(),
+                    value: value.clone(),
+                    source,
+                },
+            );
+        }
+
+        entries.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn manifest(extra: serde_json::Value) -> ContainerManifest {
+        let mut value = json!({ "name": "app", "version": "1.0.0", "type": "package" });
+        value.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn host() -> Vec<(String, String)> {
+        [("DISPLAY", ":0"), ("LC_TIME", "C"), ("LANG", "en"), ("SSH_AUTH_SOCK", "/tmp/agent"), ("MODE", "host")]
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn find<'e>(entries: &'e [EnvironmentEntry], key: &str) -> Option<&'e EnvironmentEntry> {
+        entries.iter().find(|entry| entry.key == key)
+    }
+
+    #[test]
+    fn without_inheritance_only_the_default_passthrough_reaches_the_script() {
+        let manifest = manifest(json!({}));
+
+        let entries = EnvironmentBuilder::new(&manifest).inherit_host(false).build(host());
+
+        let keys: Vec<&str> = entries.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(keys, ["DISPLAY", "LANG", "LC_TIME"]);
+        assert_eq!(
+            find(&entries, "LC_TIME").unwrap().source,
+            EnvironmentSource::Passthrough { pattern: "LC_*".to_string() }
+        );
+    }
+
+    #[test]
+    fn a_manifest_passthrough_list_replaces_the_defaults() {
+        let manifest = manifest(json!({
+            "isolation": { "enabled": true, "network": "none", "filesystem": "sandboxed",
+                           "environment_passthrough": ["SSH_*"] }
+        }));
+
+        let entries = EnvironmentBuilder::new(&manifest).inherit_host(false).build(host());
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "SSH_AUTH_SOCK");
+    }
+
+    #[test]
+    fn inheriting_passes_every_host_variable() {
+        let manifest = manifest(json!({}));
+
+        let entries = EnvironmentBuilder::new(&manifest).build(host());
+
+        assert_eq!(entries.len(), 5);
+        assert!(entries.iter().all(|entry| entry.source == EnvironmentSource::Inherited));
+    }
+
+    #[test]
+    fn manifest_beats_passthrough_config_beats_manifest_and_run_variables_beat_all() {
+        let manifest = manifest(json!({ "environment": { "DISPLAY": ":9", "MODE": "manifest", "WRAPPY_BIN": "x" } }));
+
+        let entries = EnvironmentBuilder::new(&manifest)
+            .inherit_host(false)
+            .config_variables(BTreeMap::from([("MODE".to_string(), "config".to_string())]))
+            .run_variables(BTreeMap::from([("WRAPPY_BIN".to_string(), "/usr/bin/wrappy".to_string())]))
+            .build(host());
+
+        let display = find(&entries, "DISPLAY").unwrap();
+        assert_eq!((display.value.as_str(), &display.source), (":9", &EnvironmentSource::Manifest));
+        let mode = find(&entries, "MODE").unwrap();
+        assert_eq!((mode.value.as_str(), &mode.source), ("config", &EnvironmentSource::ConfigFile));
+        let bin = find(&entries, "WRAPPY_BIN").unwrap();
+        assert_eq!((bin.value.as_str(), &bin.source), ("/usr/bin/wrappy", &EnvironmentSource::Wrappy));
+    }
+}
@@ -0,0 +1,654 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::features::container::{Container, ContainerService, EnvironmentBuilder, EnvironmentConfig, EnvironmentEntry, ExecutionPlan, InstallMetadata, InterpreterCheck, OutputCapture, OutputMode, PermissionsConfig, RunContext};
+use crate::features::history::{RunHistory, RunRecord};
+use crate::shared::clock;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::portability;
+
+pub use crate::shared::error::INTERRUPTED_EXIT_CODE;
+
+/// How often a waiting executor checks the child and the interrupt flag.
+const CHILD_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn handle_interrupt(_signal: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Keeps wrappy alive on Ctrl-C / SIGTERM so it can stop its child and record the run
+/// instead of dying and leaving the history incomplete.
+pub fn install_interrupt_handler() {
+    let handler = handle_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    // SAFETY: the handler only stores into an atomic, which is async-signal-safe.
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+    }
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// A script started by the executor that has not been recorded yet.
+pub struct RunningScript {
+    child: Child,
+    /// Reader of the child's pipes when its output is captured
+    output: Option<OutputCapture>,
+    script: String,
+    started_at: DateTime<Utc>,
+    started: Instant,
+}
+
+impl RunningScript {
+    pub fn script(&self) -> &str {
+        &self.script
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.child.id()
+    }
+}
+
+/// Runs manifest scripts of one container and records every finished run in the history.
+/// Starting and finishing a run also updates the runtime state of the container.
+pub struct Executor {
+    container: Container,
+    history: Option<RunHistory>,
+    inherit_env: bool,
+    allow_unsandboxed_mounts: bool,
+    /// Containers whose scripts started this run through `run-dep`, outermost first
+    callers: Vec<String>,
+    output: OutputMode,
+}
+
+impl Executor {
+    pub fn new(container: Container) -> Self {
+        let history = WrappyPaths::new().ok().map(|paths| RunHistory::new(&paths));
+        Self {
+            container,
+            history,
+            inherit_env: true,
+            allow_unsandboxed_mounts: false,
+            callers: Vec::new(),
+            output: OutputMode::Inherit,
+        }
+    }
+
+    /// Without host inheritance only the manifest's passthrough variables reach the script.
+    pub fn inherit_env(mut self, inherit_env: bool) -> Self {
+        self.inherit_env = inherit_env;
+        self
+    }
+
+    /// Runs scripts that declare mounts even though runs are not sandboxed and the mounts are dropped.
+    pub fn allow_unsandboxed_mounts(mut self, allow: bool) -> Self {
+        self.allow_unsandboxed_mounts = allow;
+        self
+    }
+
+    /// Records the containers whose scripts led to this run, for the cycle and depth checks of `run-dep`.
+    pub fn callers(mut self, callers: Vec<String>) -> Self {
+        self.callers = callers;
+        self
+    }
+
+    /// Where the output of started scripts goes. A relative tee file is resolved
+    /// against the current directory, so the history records where it really is.
+    pub fn output(mut self, output: OutputMode) -> Self {
+        self.output = match output {
+            OutputMode::Tee(path) => OutputMode::Tee(std::path::absolute(&path).unwrap_or(path)),
+            output => output,
+        };
+        self
+    }
+
+    /// The environment a script started now would receive; a malformed
+    /// `config/environment.json` is an error rather than silently left out.
+    pub fn environment(&self) -> ContainerResult<Vec<EnvironmentEntry>> {
+        let config = EnvironmentConfig::load(&self.container.path)?;
+        Ok(EnvironmentBuilder::new(&self.container.manifest)
+            .inherit_host(self.inherit_env)
+            .config_variables(config.variables)
+            .run_variables(RunContext::variables(&self.container, &self.callers))
+            .build(std::env::vars()))
+    }
+
+    pub fn container(&self) -> &Container {
+        &self.container
+    }
+
+    /// Decides how `script` would run with `args`, without writing or starting anything.
+    pub fn plan(&self, script: &str, args: &[String]) -> ContainerResult<ExecutionPlan> {
+        ContainerService::ensure_variables_resolved(&self.container.manifest)?;
+        let script_path = self.container.get_script_path(script)?;
+        let working_directory = env::current_dir().map_err(|e| ContainerError::IoError {
+            path: ".".into(),
+            source: e,
+        })?;
+
+        Ok(ExecutionPlan {
+            container: self.container.name().to_string(),
+            version: self.container.version().to_string(),
+            script: script.to_string(),
+            interpreter: ExecutionPlan::read_interpreter(&script_path),
+            script_path,
+            args: args.to_vec(),
+            working_directory,
+            inherit_host_environment: self.inherit_env,
+            environment: self.environment()?,
+            isolation: self.container.manifest.isolation.clone(),
+            mounts: self
+                .container
+                .manifest
+                .scripts
+                .get(script)
+                .map(|spec| spec.mounts().to_vec())
+                .unwrap_or_default(),
+            network_allowlist: self.network_allowlist(),
+        })
+    }
+
+    /// Allowed hosts in normalized form; `validate` reports a malformed list.
+    fn network_allowlist(&self) -> Vec<String> {
+        let path = &self.container.path;
+        PermissionsConfig::load(path)
+            .and_then(|permissions| permissions.network_allowlist(path))
+            .map(|allowlist| allowlist.rules().iter().map(ToString::to_string).collect())
+            .unwrap_or_default()
+    }
+
+    /// Runs are not sandboxed, so declared mounts cannot be applied; running anyway
+    /// would silently give the script a different filesystem than its author intended.
+    fn check_mounts(&self, plan: &ExecutionPlan) -> ContainerResult<()> {
+        if plan.mounts.is_empty() || self.allow_unsandboxed_mounts {
+            return Ok(());
+        }
+        Err(ContainerError::UnsandboxedMounts {
+            script: plan.script.clone(),
+            mounts: plan.mounts.iter().map(ToString::to_string).collect(),
+        })
+    }
+
+    /// Pre-flight check of the script's interpreter. The result replaces the recorded
+    /// one of installed containers, so the record follows what the host really has.
+    fn check_interpreter(&self, plan: &ExecutionPlan) -> ContainerResult<()> {
+        let Some(shebang) = &plan.interpreter else {
+            return Ok(());
+        };
+        let check = InterpreterCheck::from_shebang(&plan.script, shebang);
+        if InstallMetadata::read(&self.container.path).is_some() {
+            let _ = InstallMetadata::record_interpreter(&self.container.path, &check);
+        }
+        check.ensure_available()
+    }
+
+    /// Starts `script` with `args` without waiting for it.
+    pub fn spawn(&mut self, script: &str, args: &[String]) -> ContainerResult<RunningScript> {
+        ContainerService::ensure_variables_resolved(&self.container.manifest)?;
+        ContainerService::materialize_inline_scripts(&self.container.path, &self.container.manifest)?;
+        // WRAPPY_DATA_DIR must exist, also for adopted containers that were never installed
+        ContainerService::ensure_persistent_dirs(&self.container)?;
+        let plan = self.plan(script, args)?;
+        self.check_mounts(&plan)?;
+        self.check_interpreter(&plan)?;
+        ContainerService::ensure_host_requirements(&self.container.manifest)?;
+
+        let mut command = plan.command();
+        self.redirect_output(&mut command);
+        let mut child = command.spawn().map_err(|e| Self::spawn_error(&plan, e))?;
+        let output = self.capture_output(&mut child)?;
+        self.container.mark_running(child.id());
+        self.save_runtime();
+
+        Ok(RunningScript {
+            child,
+            output,
+            script: script.to_string(),
+            started_at: clock::now(),
+            started: Instant::now(),
+        })
+    }
+
+    /// Starts `program` with `args` in the environment a script would get, from the
+    /// current directory and without waiting for it. The run is recorded as `exec:<program>`.
+    pub fn spawn_command(&mut self, program: &str, args: &[String]) -> ContainerResult<RunningScript> {
+        ContainerService::ensure_variables_resolved(&self.container.manifest)?;
+        ContainerService::ensure_persistent_dirs(&self.container)?;
+        let environment = self.environment()?;
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .env_clear()
+            .envs(environment.iter().map(|entry| (&entry.key, &entry.value)));
+        self.redirect_output(&mut command);
+        let mut child = command.spawn().map_err(|e| ContainerError::Runtime {
+            message: format!("failed to start '{}': {}", program, e),
+        })?;
+        let output = self.capture_output(&mut child)?;
+        self.container.mark_running(child.id());
+        self.save_runtime();
+
+        Ok(RunningScript {
+            child,
+            output,
+            script: format!("exec:{}", program),
+            started_at: clock::now(),
+            started: Instant::now(),
+        })
+    }
+
+    fn redirect_output(&self, command: &mut Command) {
+        match self.output {
+            OutputMode::Inherit => {}
+            OutputMode::Quiet => {
+                command.stdout(Stdio::null()).stderr(Stdio::null());
+            }
+            OutputMode::Tee(_) | OutputMode::Json => {
+                command.stdout(Stdio::piped()).stderr(Stdio::piped());
+            }
+        }
+    }
+
+    /// Starts reading the pipes `redirect_output` gave the child. A capture that
+    /// cannot start stops the child, which would otherwise block on a full pipe.
+    fn capture_output(&self, child: &mut Child) -> ContainerResult<Option<OutputCapture>> {
+        if !matches!(self.output, OutputMode::Tee(_) | OutputMode::Json) {
+            return Ok(None);
+        }
+        OutputCapture::new(self.output.clone(), child).map(Some).inspect_err(|_| {
+            let _ = child.kill();
+            let _ = child.wait();
+        })
+    }
+
+    /// Starts `script` in its own process group with output appended to `log_path`
+    /// and returns its pid. Detached runs outlive wrappy and are not recorded in the history.
+    pub fn spawn_detached(&self, script: &str, log_path: &Path) -> ContainerResult<u32> {
+        ContainerService::ensure_variables_resolved(&self.container.manifest)?;
+        ContainerService::materialize_inline_scripts(&self.container.path, &self.container.manifest)?;
+        let plan = self.plan(script, &[])?;
+        self.check_mounts(&plan)?;
+        self.check_interpreter(&plan)?;
+        ContainerService::ensure_host_requirements(&self.container.manifest)?;
+
+        let log = filesystem::state_file_options()
+            .create(true)
+            .append(true)
+            .open(log_path)
+            .map_err(|e| ContainerError::IoError {
+                path: log_path.to_path_buf(),
+                source: e,
+            })?;
+        let log_err = log.try_clone().map_err(|e| ContainerError::IoError {
+            path: log_path.to_path_buf(),
+            source: e,
+        })?;
+
+        let child = plan
+            .command()
+            .stdin(Stdio::null())
+            .stdout(log)
+            .stderr(log_err)
+            .process_group(0)
+            .spawn()
+            .map_err(|e| ContainerError::IoError {
+                path: plan.script_path.clone(),
+                source: e,
+            })?;
+
+        Ok(child.id())
+    }
+
+    /// Runs `script` to completion; an interrupt stops the child and reports 130.
+    pub fn run(&mut self, script: &str, args: &[String]) -> ContainerResult<i32> {
+        let running = self.spawn(script, args)?;
+        self.wait(running)
+    }
+
+    /// Runs `program` like `spawn_command` and waits for it like `run`.
+    pub fn exec(&mut self, program: &str, args: &[String]) -> ContainerResult<i32> {
+        let running = self.spawn_command(program, args)?;
+        self.wait(running)
+    }
+
+    fn wait(&mut self, mut running: RunningScript) -> ContainerResult<i32> {
+        loop {
+            if let Some(exit_code) = self.try_finish(&mut running)? {
+                return Ok(exit_code);
+            }
+            if interrupted() {
+                self.stop(running)?;
+                return Ok(INTERRUPTED_EXIT_CODE);
+            }
+            match running.output.as_mut() {
+                Some(output) => output.pump(CHILD_POLL_INTERVAL),
+                None => thread::sleep(CHILD_POLL_INTERVAL),
+            }
+        }
+    }
+
+    /// Returns the exit code once the script has finished, recording the run.
+    pub fn try_finish(&mut self, running: &mut RunningScript) -> ContainerResult<Option<i32>> {
+        // Reading first keeps a child that fills its pipe from blocking forever
+        if let Some(output) = running.output.as_mut() {
+            output.pump(Duration::ZERO);
+        }
+        let status = running.child.try_wait().map_err(|e| ContainerError::Runtime {
+            message: format!("failed to wait for script '{}': {}", running.script, e),
+        })?;
+
+        Ok(status.map(|status| self.record(running, status)))
+    }
+
+    /// Kills a script that is still running, waits for it and records the run.
+    pub fn stop(&mut self, mut running: RunningScript) -> ContainerResult<i32> {
+        // The child may have exited on its own in the meantime; wait() below reports either way.
+        let _ = running.child.kill();
+        let status = running.child.wait().map_err(|e| ContainerError::Runtime {
+            message: format!("failed to stop script '{}': {}", running.script, e),
+        })?;
+
+        Ok(self.record(&mut running, status))
+    }
+
+    fn record(&mut self, running: &mut RunningScript, status: ExitStatus) -> i32 {
+        if let Some(output) = running.output.as_mut() {
+            output.finish();
+        }
+        let exit_code = exit_code_of(status);
+        self.container.mark_stopped(exit_code);
+        self.save_runtime();
+
+        if let Some(history) = &self.history {
+            let record = RunRecord {
+                container: self.container.name().to_string(),
+                script: running.script.clone(),
+                started_at: running.started_at,
+                duration_ms: running.started.elapsed().as_millis() as u64,
+                exit_code,
+                output_mode: self.output.history_name().map(str::to_string),
+                output_file: self.output.file().map(Path::to_path_buf),
+            };
+            if let Err(error) = history.append(&record) {
+                eprintln!("⚠️  Failed to record run in history: {}", error);
+            }
+        }
+
+        exit_code
+    }
+
+    /// A CRLF `#!` line names an interpreter ending in `\r`, which exec reports as the
+    /// script itself not existing; say what is really wrong instead.
+    fn spawn_error(plan: &ExecutionPlan, error: io::Error) -> ContainerError {
+        let crlf = error.kind() == io::ErrorKind::NotFound
+            && fs::read(&plan.script_path).is_ok_and(|content| portability::has_crlf(&content));
+        if crlf {
+            return ContainerError::Runtime {
+                message: format!(
+                    "script '{}' has CRLF line endings, so its #! line names an interpreter that does not exist; convert it with `wrappy container validate --fix`",
+                    plan.script
+                ),
+            };
+        }
+        ContainerError::IoError {
+            path: plan.script_path.clone(),
+            source: error,
+        }
+    }
+
+    /// A run goes ahead even when its state cannot be saved, e.g. in a read-only container.
+    fn save_runtime(&self) {
+        if let Err(error) = self.container.save_runtime() {
+            eprintln!("⚠️  Failed to save runtime state: {}", error);
+        }
+    }
+}
+
+/// Maps a child's status to a shell-style exit code, so signals show up as 128 + signal.
+fn exit_code_of(status: ExitStatus) -> i32 {
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::ContainerStatus;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, write_executable, TempHome};
+    use serde_json::json;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn run_refuses_a_script_while_host_requirements_are_unmet() {
+        let home = TempHome::enter();
+        let dir = home.path().join("needy");
+        let mut manifest = fixture_manifest("needy", "1.0.0");
+        manifest["host_requirements"] = json!([{ "command": "wrappy-test-no-such-tool" }]);
+        manifest["scripts"]["default"] = json!("scripts/marker.sh");
+        fixture_container_at(&dir, manifest);
+        write_executable(&dir.join("scripts/marker.sh"), "#!/bin/sh\ntouch \"$0.ran\"\n");
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+
+        let result = Executor::new(container).output(OutputMode::Quiet).run("default", &[]);
+
+        assert!(matches!(result, Err(ContainerError::HostRequirementsUnmet { .. })), "{:?}", result);
+        assert!(!dir.join("scripts/marker.sh.ran").exists());
+    }
+
+    /// Container whose default script records that it ran and prints its argument, `$GREETING` and its directory.
+    fn planned_container(home: &TempHome) -> std::path::PathBuf {
+        let dir = home.path().join("planned");
+        let mut manifest = fixture_manifest("planned", "2.1.0");
+        manifest["environment"] = json!({ "GREETING": "hello", "API_TOKEN": "s3cret" });
+        manifest["scripts"]["default"] = json!({
+            "path": "scripts/marker.sh",
+            "mounts": [{ "host": "/srv/projects", "container": "/work" }],
+        });
+        manifest["isolation"] = json!({ "enabled": true, "network": "none", "filesystem": "readonly" });
+        fixture_container_at(&dir, manifest);
+        write_executable(
+            &dir.join("scripts/marker.sh"),
+            "#!/bin/sh\ntouch \"$0.ran\"\necho \"$1 $GREETING $(pwd)\"\n",
+        );
+        dir
+    }
+
+    #[test]
+    fn the_plan_describes_the_run_without_starting_or_writing_anything() {
+        let home = TempHome::enter();
+        let dir = planned_container(&home);
+        std::env::set_var("WRAPPY_TEST_HOST_ONLY", "1");
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+
+        let plan = Executor::new(container.clone()).inherit_env(false).plan("default", &["a b".to_string()]);
+        let inheriting = Executor::new(container).plan("default", &[]);
+        std::env::remove_var("WRAPPY_TEST_HOST_ONLY");
+        let plan = plan.unwrap();
+
+        assert!(!dir.join("scripts/marker.sh.ran").exists());
+        assert_eq!((plan.container.as_str(), plan.version.as_str()), ("planned", "2.1.0"));
+        assert_eq!(plan.script_path, dir.join("scripts/marker.sh"));
+        assert_eq!(plan.interpreter.as_deref(), Some("/bin/sh"));
+        assert_eq!(plan.args, ["a b"]);
+        assert_eq!(plan.working_directory, env::current_dir().unwrap());
+        assert_eq!(plan.mounts.len(), 1);
+        assert_eq!(plan.isolation.as_ref().map(|isolation| isolation.network.as_str()), Some("none"));
+        let value = |key: &str| plan.environment.iter().find(|entry| entry.key == key).map(|entry| entry.value.clone());
+        assert_eq!(value("GREETING").as_deref(), Some("hello"));
+        assert_eq!(value("WRAPPY_TEST_HOST_ONLY"), None);
+        assert!(!plan.inherit_host_environment);
+        assert!(inheriting.unwrap().environment.iter().any(|entry| entry.key == "WRAPPY_TEST_HOST_ONLY"));
+        assert_eq!(plan.redacted().environment.iter().find(|entry| entry.key == "API_TOKEN").unwrap().value, crate::shared::redact::REDACTED);
+    }
+
+    #[test]
+    fn the_command_of_a_plan_runs_exactly_what_it_describes() {
+        let home = TempHome::enter();
+        let dir = planned_container(&home);
+        let executor = Executor::new(ContainerService::load_from_directory(&dir).unwrap());
+        let plan = executor.plan("default", &["a b".to_string()]).unwrap();
+
+        let output = plan.command().output().unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim_end(),
+            format!("a b hello {}", plan.working_directory.display())
+        );
+    }
+
+    #[test]
+    fn declared_mounts_stop_a_real_run_unless_allowed() {
+        let home = TempHome::enter();
+        let dir = planned_container(&home);
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+
+        let refused = Executor::new(container.clone()).output(OutputMode::Quiet).run("default", &[]);
+
+        assert!(matches!(refused, Err(ContainerError::UnsandboxedMounts { .. })), "{:?}", refused);
+        assert!(!dir.join("scripts/marker.sh.ran").exists());
+        let allowed = Executor::new(container).allow_unsandboxed_mounts(true).output(OutputMode::Quiet).run("default", &[]);
+        assert!(allowed.is_ok(), "{:?}", allowed);
+        assert!(dir.join("scripts/marker.sh.ran").exists());
+    }
+
+    /// Container whose default script writes its arguments and `$MODE` to `out` and exits with `$1`.
+    fn reporting_container(home: &TempHome) -> std::path::PathBuf {
+        let dir = home.path().join("reporting");
+        let mut manifest = fixture_manifest("reporting", "1.0.0");
+        manifest["environment"] = json!({ "MODE": "manifest", "KEPT": "from the manifest" });
+        manifest["scripts"]["default"] = json!("scripts/report.sh");
+        fixture_container_at(&dir, manifest);
+        write_executable(
+            &dir.join("scripts/report.sh"),
+            "#!/bin/sh\nprintf '[%s]\\n' \"$@\" \"$MODE\" \"$KEPT\" > \"${0%/*}/../out\"\nexit \"$1\"\n",
+        );
+        fs::write(
+            dir.join("config/environment.json"),
+            json!({ "$schema": EnvironmentConfig::SCHEMA, "variables": { "MODE": "config" } }).to_string(),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn a_run_gets_the_config_environment_and_the_arguments_and_records_its_exit_code() {
+        let home = TempHome::enter();
+        let dir = reporting_container(&home);
+        let mut executor = Executor::new(ContainerService::load_from_directory(&dir).unwrap()).output(OutputMode::Quiet);
+        let args: Vec<String> = ["3", "two words", "--flag=$HOME", ""].iter().map(|arg| arg.to_string()).collect();
+
+        let exit_code = executor.run("default", &args).unwrap();
+
+        assert_eq!(exit_code, 3);
+        assert_eq!(
+            fs::read_to_string(dir.join("out")).unwrap(),
+            "[3]\n[two words]\n[--flag=$HOME]\n[]\n[config]\n[from the manifest]\n"
+        );
+        let runtime = &executor.container().runtime;
+        assert_eq!((&runtime.status, runtime.pid, runtime.exit_code), (&ContainerStatus::Stopped, None, Some(3)));
+        let reloaded = ContainerService::load_from_directory(&dir).unwrap();
+        assert_eq!(reloaded.runtime.exit_code, Some(3));
+        assert!(reloaded.runtime.started_at.is_some() && reloaded.runtime.stopped_at.is_some());
+    }
+
+    #[test]
+    fn a_malformed_environment_config_stops_the_plan_and_the_run() {
+        let home = TempHome::enter();
+        let dir = reporting_container(&home);
+        fs::write(dir.join("config/environment.json"), "{ \"variables\": [").unwrap();
+        let mut executor = Executor::new(ContainerService::load_from_directory(&dir).unwrap()).output(OutputMode::Quiet);
+
+        let plan = executor.plan("default", &[]);
+        let run = executor.run("default", &["0".to_string()]);
+
+        assert!(plan.is_err());
+        assert!(run.is_err());
+        assert!(!dir.join("out").exists());
+    }
+
+    #[test]
+    fn a_spawned_script_is_recorded_as_running_until_it_is_stopped() {
+        let home = TempHome::enter();
+        let dir = home.path().join("sleeper");
+        let mut manifest = fixture_manifest("sleeper", "1.0.0");
+        manifest["scripts"]["default"] = json!("scripts/sleep.sh");
+        fixture_container_at(&dir, manifest);
+        write_executable(&dir.join("scripts/sleep.sh"), "#!/bin/sh\nexec sleep 30\n");
+        let mut executor = Executor::new(ContainerService::load_from_directory(&dir).unwrap()).output(OutputMode::Quiet);
+
+        let running = executor.spawn("default", &[]).unwrap();
+        let while_running = ContainerService::load_from_directory(&dir).unwrap().runtime;
+        let was_alive = while_running.is_process_alive();
+        let exit_code = executor.stop(running).unwrap();
+        let after = ContainerService::load_from_directory(&dir).unwrap().runtime;
+
+        assert_eq!(while_running.status, ContainerStatus::Running);
+        assert!(while_running.pid.is_some());
+        assert!(was_alive, "the recorded pid is the running script");
+        assert_eq!(exit_code, 128 + libc::SIGKILL);
+        assert_eq!((after.status, after.pid, after.exit_code), (ContainerStatus::Stopped, None, Some(128 + libc::SIGKILL)));
+    }
+
+    #[test]
+    fn a_windows_edited_container_loads_and_its_crlf_script_runs_once_converted() {
+        let home = TempHome::enter();
+        let dir = reporting_container(&home);
+        for file in ["manifest.json", "config/environment.json"] {
+            let content = fs::read_to_string(dir.join(file)).unwrap().replace('\n', "\r\n");
+            fs::write(dir.join(file), format!("\u{feff}{}", content)).unwrap();
+        }
+        let script = dir.join("scripts/report.sh");
+        let crlf = fs::read_to_string(&script).unwrap().replace('\n', "\r\n");
+        fs::write(&script, crlf).unwrap();
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+
+        let before = Executor::new(container.clone()).output(OutputMode::Quiet).run("default", &["0".to_string()]);
+        let converted = ContainerService::convert_scripts_to_lf(&dir, &container.manifest).unwrap();
+        let after = Executor::new(container.clone()).output(OutputMode::Quiet).run("default", &["0".to_string()]);
+
+        assert!(before.as_ref().is_err_and(|e| e.to_string().contains("CRLF")), "{:?}", before);
+        assert_eq!(converted, ["default"]);
+        assert!(ContainerService::crlf_scripts(&dir, &container.manifest).is_empty());
+        assert_eq!(fs::metadata(&script).unwrap().permissions().mode() & 0o777, 0o755);
+        assert_eq!(after.unwrap(), 0);
+        assert_eq!(fs::read_to_string(dir.join("out")).unwrap(), "[0]\n[config]\n[from the manifest]\n");
+    }
+
+    #[test]
+    fn the_output_mode_and_file_are_recorded_in_the_run_history() {
+        let home = TempHome::enter();
+        let dir = reporting_container(&home);
+        let log = home.path().join("run.log");
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+
+        let quiet = Executor::new(container.clone()).output(OutputMode::Quiet).run("default", &["3".to_string()]);
+        let tee = Executor::new(container).output(OutputMode::Tee(log.clone())).run("default", &["0".to_string()]);
+
+        assert_eq!((quiet.unwrap(), tee.unwrap()), (3, 0));
+        let records = RunHistory::new(&home.paths()).read_all().unwrap().records;
+        let recorded: Vec<(Option<&str>, Option<&Path>, i32)> = records
+            .iter()
+            .map(|record| (record.output_mode.as_deref(), record.output_file.as_deref(), record.exit_code))
+            .collect();
+        assert_eq!(recorded, [(Some("quiet"), None, 3), (Some("tee"), Some(log.as_path()), 0)]);
+        assert!(log.is_file());
+    }
+}
@@ -0,0 +1,604 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::os::unix::fs::{self as unix_fs, MetadataExt, PermissionsExt};
+use std::path::{Component, Path, PathBuf};
+
+use crate::features::container::{Attestation, Container, ATTESTATION_FILE, METADATA_FILE};
+use crate::shared::archive::{EntryHeader, EntryKind, TarReader, TarWriter};
+use crate::shared::clock;
+use crate::shared::digest::DigestWriter;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::ignore::IgnoreRules;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::portability::CollisionDetector;
+use crate::shared::staging::StagingArea;
+use crate::shared::traversal::Walk;
+
+/// Describes the export inside the archive, next to the container's own files.
+pub const EXPORT_METADATA_FILE: &str = ".wrappy-export.json";
+
+/// Embedded in every export. Reproducible exports leave out the timestamp,
+/// so the same container always yields the same bytes.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportMetadata {
+    pub name: String,
+    pub version: String,
+    pub exported_with: String,
+    pub reproducible: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<DateTime<Utc>>,
+}
+
+/// Result of writing one archive.
+#[derive(Debug, Clone)]
+pub struct ExportSummary {
+    /// Lowercase hex SHA-256 of the archive bytes
+    pub sha256: String,
+    pub size: u64,
+    pub entries: usize,
+}
+
+/// Packs a container directory into an uncompressed ustar archive under `<name>-<version>/`.
+///
+/// Entries are always sorted by their UTF-8 path. A reproducible export also
+/// zeroes mtimes and owners and normalizes modes to 0644/0755, so two exports
+/// of the same content are byte-identical and their hash can identify it.
+pub struct ExportService;
+
+impl ExportService {
+    /// `<name>-<version>.tar`, used when no output path is given.
+    pub fn default_file_name(container: &Container) -> String {
+        format!("{}-{}.tar", container.name(), container.version())
+    }
+
+    /// Writes the archive to `output` through a file in a staging area, so a failed
+    /// export never leaves a truncated archive behind. An `attestation` is embedded
+    /// as `.wrappy-attestation.json`. Persistent data is packed only with `include_data`.
+    pub fn export_to_file(
+        container: &Container,
+        output: &Path,
+        reproducible: bool,
+        include_data: bool,
+        attestation: Option<&Attestation>,
+    ) -> ContainerResult<ExportSummary> {
+        let staging = StagingArea::create(&WrappyPaths::new()?, output.parent().unwrap_or(Path::new(".")))?;
+        let partial = staging.join(Self::default_file_name(container));
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source: io::Error| ContainerError::IoError { path, source }
+        };
+
+        let file = File::create(&partial).map_err(io_error(&partial))?;
+        let (file, summary) = Self::write_archive(container, file, reproducible, include_data, attestation)?;
+        file.sync_all().map_err(io_error(&partial))?;
+        fs::rename(&partial, output).map_err(io_error(output))?;
+        Ok(summary)
+    }
+
+    /// SHA-256 of the reproducible export without data, computed without writing the archive anywhere.
+    pub fn digest(container: &Container) -> ContainerResult<ExportSummary> {
+        Self::write_archive(container, io::sink(), true, false, None).map(|(_, summary)| summary)
+    }
+
+    /// Streams the archive into `writer` and returns it with the archive's digest.
+    pub fn write_archive<W: Write>(
+        container: &Container,
+        writer: W,
+        reproducible: bool,
+        include_data: bool,
+        attestation: Option<&Attestation>,
+    ) -> ContainerResult<(W, ExportSummary)> {
+        let root = &container.path;
+        let prefix = format!("{}-{}", container.name(), container.version());
+        let archive_error = |source: io::Error| ContainerError::IoError {
+            path: root.clone(),
+            source,
+        };
+
+        let metadata = ExportMetadata {
+            name: container.name().to_string(),
+            version: container.version().to_string(),
+            exported_with: env!("CARGO_PKG_VERSION").to_string(),
+            reproducible,
+            exported_at: (!reproducible).then(clock::now),
+        };
+        let mut generated = vec![(EXPORT_METADATA_FILE, serde_json::to_string_pretty(&metadata)?)];
+        if let Some(attestation) = attestation {
+            generated.push((ATTESTATION_FILE, serde_json::to_string_pretty(attestation)?));
+        }
+
+        let skipped = if include_data { Vec::new() } else { container.persistent_paths() };
+        let mut entries = Self::collect_entries(root, &skipped)?;
+        entries.extend(generated.iter().map(|(name, _)| (name.to_string(), None)));
+        entries.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+
+        let mut tar = TarWriter::new(DigestWriter::new(writer));
+        let root_header = Self::header(&prefix, &fs::metadata(root).map_err(archive_error)?, EntryKind::Directory, reproducible);
+        tar.append(&root_header, io::empty()).map_err(archive_error)?;
+
+        for (relative, path) in &entries {
+            let archive_path = format!("{}/{}", prefix, relative);
+            let Some(path) = path else {
+                // The embedded metadata and attestation are generated, not read from disk
+                let content = generated
+                    .iter()
+                    .find(|(name, _)| name == relative)
+                    .map(|(_, content)| format!("{}\n", content))
+                    .unwrap_or_default();
+                let header = EntryHeader {
+                    size: content.len() as u64,
+                    ..Self::normalized_header(&archive_path, EntryKind::File, 0o644)
+                };
+                tar.append(&header, content.as_bytes()).map_err(archive_error)?;
+                continue;
+            };
+
+            let io_error = |source: io::Error| ContainerError::IoError {
+                path: path.clone(),
+                source,
+            };
+            let file_metadata = fs::symlink_metadata(path).map_err(io_error)?;
+            let file_type = file_metadata.file_type();
+            if file_type.is_symlink() {
+                let target = fs::read_link(path).map_err(io_error)?;
+                // Unpacking refuses such links, so the archive could never be restored
+                if filesystem::symlink_leaves_root(Path::new(relative), &target) {
+                    return Err(ContainerError::InvalidPath {
+                        path: path.clone(),
+                        reason: format!("is a symlink to '{}', outside the container, which exports cannot carry", target.display()),
+                    });
+                }
+                let target = Self::utf8(path, target.to_str())?.to_string();
+                let header = Self::header(&archive_path, &file_metadata, EntryKind::Symlink { target }, reproducible);
+                tar.append(&header, io::empty()).map_err(io_error)?;
+            } else if file_type.is_dir() {
+                let header = Self::header(&archive_path, &file_metadata, EntryKind::Directory, reproducible);
+                tar.append(&header, io::empty()).map_err(io_error)?;
+            } else if file_type.is_file() {
+                let header = Self::header(&archive_path, &file_metadata, EntryKind::File, reproducible);
+                tar.append(&header, File::open(path).map_err(io_error)?).map_err(io_error)?;
+            }
+        }
+
+        let (writer, sha256, size) = tar.finish().map_err(archive_error)?.finish();
+        Ok((
+            writer,
+            ExportSummary {
+                sha256,
+                size,
+                entries: entries.len() + 1,
+            },
+        ))
+    }
+
+    /// Unpacks an archive written by `write_archive` into `destination`, which must not
+    /// exist yet. The top-level `<name>-<version>/` directory and the export metadata are
+    /// dropped; entries that would land outside `destination` are refused, and so are
+    /// entries a case-insensitive or normalizing filesystem would write over an earlier one.
+    /// Symlinks may point anywhere inside the container, resolved from their own
+    /// directory, but not outside it, and no entry is ever written through a symlink
+    /// an earlier entry created.
+    /// Returns the number of entries unpacked.
+    pub fn unpack_archive(archive: &Path, destination: &Path) -> ContainerResult<usize> {
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source: io::Error| ContainerError::IoError { path, source }
+        };
+        let mut tar = TarReader::new(io::BufReader::new(File::open(archive).map_err(io_error(archive))?));
+        fs::create_dir(destination).map_err(io_error(destination))?;
+
+        let mut unpacked = 0;
+        let mut directories = Vec::new();
+        let mut links = Vec::new();
+        let mut names = CollisionDetector::new();
+        while let Some(header) = tar.next_entry().map_err(io_error(archive))? {
+            let relative = match header.path.split_once('/') {
+                Some((_, relative)) if !relative.is_empty() && relative != EXPORT_METADATA_FILE => Path::new(relative),
+                _ => continue,
+            };
+            if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+                return Err(ContainerError::InvalidPath {
+                    path: archive.to_path_buf(),
+                    reason: format!("entry '{}' points outside the container", header.path),
+                });
+            }
+
+            let path = destination.join(relative);
+            Self::refuse_symlinks_on_the_way(archive, destination, relative, &header.path)?;
+            // Only a filesystem that folds the pair finds the second name already there
+            if let Some(collision) = names.insert(relative) {
+                if fs::symlink_metadata(&path).is_ok() {
+                    return Err(ContainerError::InvalidPath {
+                        path: archive.to_path_buf(),
+                        reason: format!("{}, which this filesystem stores as one file", collision),
+                    });
+                }
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(io_error(parent))?;
+            }
+            match &header.kind {
+                EntryKind::Directory => {
+                    fs::create_dir_all(&path).map_err(io_error(&path))?;
+                    directories.push((path, header.mode));
+                }
+                EntryKind::Symlink { target } => {
+                    if filesystem::symlink_leaves_root(relative, Path::new(target)) {
+                        return Err(Self::escaping_link(archive, &header.path, target));
+                    }
+                    unix_fs::symlink(target, &path).map_err(io_error(&path))?;
+                    links.push((path, header.path.clone(), target.clone()));
+                }
+                EntryKind::File => {
+                    // Never opens an existing file, so nothing already there is followed or truncated
+                    let mut file = fs::OpenOptions::new()
+                        .write(true)
+                        .create_new(true)
+                        .open(&path)
+                        .map_err(io_error(&path))?;
+                    io::copy(&mut tar.data(), &mut file).map_err(io_error(&path))?;
+                    file.set_permissions(fs::Permissions::from_mode(header.mode & 0o7777))
+                        .map_err(io_error(&path))?;
+                }
+            }
+            unpacked += 1;
+        }
+
+        // `..` after a link that a later entry created resolves from where that link
+        // points, which the path alone does not show, so check where links really end up
+        let root = destination.canonicalize().map_err(io_error(destination))?;
+        for (path, entry, target) in links {
+            if path.canonicalize().is_ok_and(|resolved| !resolved.starts_with(&root)) {
+                return Err(Self::escaping_link(archive, &entry, &target));
+            }
+        }
+
+        // Modes last, so read-only directories could still be filled
+        for (path, mode) in directories {
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode & 0o7777)).map_err(io_error(&path))?;
+        }
+        Ok(unpacked)
+    }
+
+    fn escaping_link(archive: &Path, entry: &str, target: &str) -> ContainerError {
+        ContainerError::InvalidPath {
+            path: archive.to_path_buf(),
+            reason: format!("symlink '{}' points to '{}', outside the container", entry, target),
+        }
+    }
+
+    /// Refuses an entry when its path, or a directory on the way to it, is a symlink
+    /// that is already unpacked, since writing there would follow the link.
+    fn refuse_symlinks_on_the_way(archive: &Path, destination: &Path, relative: &Path, entry: &str) -> ContainerResult<()> {
+        let mut path = destination.to_path_buf();
+        for component in relative.components() {
+            path.push(component);
+            if fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+                return Err(ContainerError::InvalidPath {
+                    path: archive.to_path_buf(),
+                    reason: format!(
+                        "entry '{}' would be written through the symlink '{}'",
+                        entry,
+                        path.strip_prefix(destination).unwrap_or(&path).display()
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Every path under `root` relative to it, without following symlinks or what
+    /// `.wrappyignore` leaves out. wrappy's own metadata file describes this machine
+    /// and is left out too, and so are the metadata and attestation of an earlier export
+    /// and the `skipped` paths.
+    pub fn collect_entries(root: &Path, skipped: &[PathBuf]) -> ContainerResult<Vec<(String, Option<PathBuf>)>> {
+        let mut entries = Vec::new();
+        let mut walk = Walk::new(root).follow_root_link().ignoring(IgnoreRules::for_container(root)?);
+        while let Some(entry) = walk.next() {
+            let entry = entry?;
+            if entry.depth == 0 {
+                continue;
+            }
+            let relative = entry.path.strip_prefix(root).unwrap_or(&entry.path);
+            if [METADATA_FILE, EXPORT_METADATA_FILE, ATTESTATION_FILE]
+                .iter()
+                .any(|generated| relative == Path::new(generated))
+                || skipped.contains(&entry.path)
+            {
+                walk.skip_current_dir();
+                continue;
+            }
+            let relative = Self::utf8(&entry.path, relative.to_str())?.to_string();
+            entries.push((relative, Some(entry.path)));
+        }
+        Ok(entries)
+    }
+
+    fn header(path: &str, metadata: &fs::Metadata, kind: EntryKind, reproducible: bool) -> EntryHeader {
+        if reproducible {
+            let executable = kind == EntryKind::Directory || metadata.mode() & 0o111 != 0;
+            return EntryHeader {
+                size: metadata.len(),
+                ..Self::normalized_header(path, kind, if executable { 0o755 } else { 0o644 })
+            };
+        }
+        EntryHeader {
+            path: path.to_string(),
+            size: metadata.len(),
+            mode: metadata.mode(),
+            mtime: metadata.mtime().max(0) as u64,
+            uid: metadata.uid(),
+            gid: metadata.gid(),
+            user_name: String::new(),
+            group_name: String::new(),
+            kind,
+        }
+    }
+
+    /// Header with nothing taken from the host except the mode class.
+    fn normalized_header(path: &str, kind: EntryKind, mode: u32) -> EntryHeader {
+        EntryHeader {
+            path: path.to_string(),
+            kind,
+            mode,
+            size: 0,
+            mtime: 0,
+            uid: 0,
+            gid: 0,
+            user_name: String::new(),
+            group_name: String::new(),
+        }
+    }
+
+    /// Archive paths must be UTF-8 so their order is the same everywhere.
+    fn utf8<'a>(path: &Path, value: Option<&'a str>) -> ContainerResult<&'a str> {
+        value.ok_or_else(|| ContainerError::InvalidPath {
+            path: path.to_path_buf(),
+            reason: "not valid UTF-8, so it cannot be exported".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::ContainerService;
+    use crate::shared::archive::{EntryHeader, EntryKind, TarReader, TarWriter};
+    use crate::shared::clock::MockClock;
+    use crate::shared::digest::Sha256;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, symlink_fixture, TempHome};
+    use chrono::{Duration, TimeZone};
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::UNIX_EPOCH;
+    use tempfile::TempDir;
+
+    fn container_with_data(dir: &Path) -> Container {
+        let root = dir.join("app");
+        let mut manifest = fixture_manifest("app", "1.0.0");
+        manifest["persistent"] = json!(["data"]);
+        fixture_container_at(&root, manifest);
+        fs::create_dir_all(root.join("data")).unwrap();
+        fs::write(root.join("data/state"), "user data").unwrap();
+        ContainerService::load_from_directory(&root).unwrap()
+    }
+
+    fn archived_paths(archive: &[u8]) -> Vec<String> {
+        let mut reader = TarReader::new(archive);
+        let mut paths = Vec::new();
+        while let Some(header) = reader.next_entry().unwrap() {
+            paths.push(header.path);
+        }
+        paths
+    }
+
+    #[test]
+    fn persistent_data_is_only_exported_when_asked_for() {
+        let dir = TempDir::new().unwrap();
+        let container = container_with_data(dir.path());
+
+        let (without, _) = ExportService::write_archive(&container, Vec::new(), true, false, None).unwrap();
+        let (with, _) = ExportService::write_archive(&container, Vec::new(), true, true, None).unwrap();
+
+        let without = archived_paths(&without);
+        assert!(without.contains(&"app-1.0.0/scripts/default.sh".to_string()));
+        assert!(!without.iter().any(|path| path.starts_with("app-1.0.0/data")), "{:?}", without);
+        assert!(archived_paths(&with).contains(&"app-1.0.0/data/state".to_string()));
+    }
+
+    #[test]
+    fn the_digest_does_not_change_with_the_data() {
+        let dir = TempDir::new().unwrap();
+        let container = container_with_data(dir.path());
+        let before = ExportService::digest(&container).unwrap().sha256;
+
+        fs::write(container.path.join("data/state"), "changed by the app").unwrap();
+
+        assert_eq!(ExportService::digest(&container).unwrap().sha256, before);
+    }
+
+    fn touch(path: &Path, seconds: u64) {
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(seconds);
+        File::options().write(true).open(path).unwrap().set_modified(time).unwrap();
+    }
+
+    #[test]
+    fn exporting_twice_with_the_clock_advanced_gives_identical_bytes_and_digest() {
+        let home = TempHome::enter();
+        let container = container_with_data(home.path());
+        let clock = Arc::new(MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap()));
+        clock::set_clock(clock.clone());
+        let first = home.path().join("first.tar");
+        let second = home.path().join("second.tar");
+
+        let first_summary = ExportService::export_to_file(&container, &first, true, false, None);
+        clock.advance(Duration::days(3));
+        touch(&container.path.join("scripts/default.sh"), 1_234_567);
+        touch(&container.path.join("bin/tool"), 7_654_321);
+        let second_summary = ExportService::export_to_file(&container, &second, true, false, None);
+        let digest = ExportService::digest(&container);
+        clock::reset_clock();
+
+        let (first_summary, second_summary) = (first_summary.unwrap(), second_summary.unwrap());
+        let bytes = fs::read(&first).unwrap();
+        assert_eq!(bytes, fs::read(&second).unwrap());
+        assert_eq!(first_summary.sha256, second_summary.sha256);
+        assert_eq!(digest.unwrap().sha256, first_summary.sha256);
+        let mut hasher = Sha256::new();
+        hasher.update(&bytes);
+        assert_eq!(hasher.finish_hex(), first_summary.sha256);
+        assert_eq!(first_summary.size, bytes.len() as u64);
+    }
+
+    #[test]
+    fn a_reproducible_archive_carries_nothing_from_the_host() {
+        let dir = TempDir::new().unwrap();
+        let container = container_with_data(dir.path());
+
+        let (archive, _) = ExportService::write_archive(&container, Vec::new(), true, false, None).unwrap();
+
+        let mut reader = TarReader::new(archive.as_slice());
+        let mut paths = Vec::new();
+        while let Some(header) = reader.next_entry().unwrap() {
+            assert_eq!((header.mtime, header.uid, header.gid), (0, 0, 0), "{}", header.path);
+            assert!(header.user_name.is_empty() && header.group_name.is_empty(), "{}", header.path);
+            assert!([0o644, 0o755].contains(&header.mode), "{} {:o}", header.path, header.mode);
+            if header.path.ends_with(EXPORT_METADATA_FILE) {
+                let mut content = String::new();
+                std::io::Read::read_to_string(&mut reader.data(), &mut content).unwrap();
+                assert!(!content.contains("exported_at") && !content.contains('\r'), "{}", content);
+            }
+            paths.push(header.path);
+        }
+        let mut sorted = paths.clone();
+        sorted.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        assert_eq!(paths, sorted);
+    }
+
+    #[test]
+    fn a_regular_export_records_when_it_was_made() {
+        let home = TempHome::enter();
+        let container = container_with_data(home.path());
+        clock::set_clock(Arc::new(MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap())));
+
+        let exported = ExportService::write_archive(&container, Vec::new(), false, false, None);
+        clock::reset_clock();
+
+        let (archive, _) = exported.unwrap();
+        let text = String::from_utf8_lossy(&archive);
+        assert!(text.contains("\"exported_at\": \"2023-11-14T22:13:20Z\""), "no timestamp in the metadata");
+    }
+
+    /// Path, symlink target and file content of one archive entry.
+    type Entry<'a> = (&'a str, Option<&'a str>, &'a str);
+
+    /// An archive of `app-1.0.0/<path>` entries; a `Some` target makes a symlink, text a file.
+    fn malicious_archive(dir: &Path, entries: &[Entry]) -> PathBuf {
+        let mut writer = TarWriter::new(Vec::new());
+        for (path, target, content) in entries {
+            let kind = match target {
+                Some(target) => EntryKind::Symlink { target: target.to_string() },
+                None => EntryKind::File,
+            };
+            let header = EntryHeader {
+                path: format!("app-1.0.0/{}", path),
+                size: if target.is_some() { 0 } else { content.len() as u64 },
+                kind,
+                mode: 0o644,
+                mtime: 0,
+                uid: 0,
+                gid: 0,
+                user_name: String::new(),
+                group_name: String::new(),
+            };
+            writer.append(&header, content.as_bytes()).unwrap();
+        }
+        let archive = dir.join("malicious.tar");
+        fs::write(&archive, writer.finish().unwrap()).unwrap();
+        archive
+    }
+
+    #[test]
+    fn archives_that_escape_through_symlinks_are_refused() {
+        let dir = TempDir::new().unwrap();
+        let outside = dir.path().join("outside");
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("victim"), "original").unwrap();
+        let outside_text = outside.to_string_lossy().into_owned();
+        let cases: [(&str, Vec<Entry>, &str); 7] = [
+            ("absolute target", vec![("link", Some(outside_text.as_str()), "")], "outside the container"),
+            ("parent target", vec![("link", Some("../../outside"), "")], "outside the container"),
+            ("hidden parent", vec![("sub/link", Some("x/../../.."), "")], "outside the container"),
+            ("parent of a later link", vec![("up", Some("here/.."), ""), ("here", Some("."), "")], "'app-1.0.0/up' points to 'here/..'"),
+            ("file below a symlinked dir", vec![("dir", Some("sub"), ""), ("dir/victim", None, "pwned")], "through the symlink 'dir'"),
+            ("file over a symlink", vec![("link", Some("sub"), ""), ("link", None, "pwned")], "through the symlink 'link'"),
+            ("parent path", vec![("../escape", None, "pwned")], "points outside the container"),
+        ];
+
+        for (label, entries, expected) in cases {
+            let destination = dir.path().join(format!("unpacked-{}", label.replace(' ', "-")));
+            let archive = malicious_archive(dir.path(), &entries);
+
+            let result = ExportService::unpack_archive(&archive, &destination);
+
+            match result {
+                Err(ContainerError::InvalidPath { reason, .. }) => assert!(reason.contains(expected), "{}: {}", label, reason),
+                other => panic!("{}: expected a refusal, got {:?}", label, other),
+            }
+            assert_eq!(fs::read_to_string(outside.join("victim")).unwrap(), "original", "{}", label);
+            assert!(!dir.path().join("escape").exists(), "{}", label);
+        }
+    }
+
+    #[test]
+    fn relative_symlinks_inside_the_container_are_unpacked() {
+        let dir = TempDir::new().unwrap();
+        let archive = malicious_archive(
+            dir.path(),
+            &[
+                ("lib/real", None, "library"),
+                ("lib/alias", Some("real"), ""),
+                ("here", Some("./lib"), ""),
+                ("bin/tool", Some("../lib/real"), ""),
+            ],
+        );
+        let destination = dir.path().join("unpacked");
+
+        assert_eq!(ExportService::unpack_archive(&archive, &destination).unwrap(), 4);
+
+        assert_eq!(fs::read_link(destination.join("lib/alias")).unwrap(), Path::new("real"));
+        assert_eq!(fs::read_to_string(destination.join("lib/alias")).unwrap(), "library");
+        assert_eq!(fs::read_to_string(destination.join("bin/tool")).unwrap(), "library");
+    }
+
+    #[test]
+    fn dangling_and_cyclic_symlinks_survive_an_export_but_absolute_ones_are_refused() {
+        let dir = TempDir::new().unwrap();
+
+        for kind in ["dangling", "cyclic"] {
+            let container = ContainerService::load_from_directory(symlink_fixture(kind)).unwrap();
+            let archive = dir.path().join(format!("{}.tar", kind));
+            let destination = dir.path().join(kind);
+
+            let (bytes, _) = ExportService::write_archive(&container, Vec::new(), true, false, None).unwrap();
+            fs::write(&archive, bytes).unwrap();
+            ExportService::unpack_archive(&archive, &destination).unwrap();
+
+            assert_eq!(
+                fs::read_link(destination.join("content/link")).unwrap(),
+                fs::read_link(container.path.join("content/link")).unwrap(),
+                "{}",
+                kind
+            );
+        }
+
+        let absolute = ContainerService::load_from_directory(symlink_fixture("absolute")).unwrap();
+        match ExportService::write_archive(&absolute, Vec::new(), true, false, None) {
+            Err(ContainerError::InvalidPath { reason, .. }) => assert!(reason.contains("outside the container"), "{}", reason),
+            other => panic!("expected a refusal, got {:?}", other.map(|(_, summary)| summary)),
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use std::fs;
+use std::os::unix::fs as unix_fs;
+use std::path::Path;
+
+use crate::features::container::{
+    AttestationCheck, AttestationService, ContainerRegistry, ContainerResolver, ContainerService, InstallMetadata,
+    RegistryEntry, STATE_ENTRIES,
+};
+use crate::features::store::{DedupSummary, ObjectStore};
+use crate::shared::cancellation::CancellationToken;
+use crate::shared::clock;
+use crate::shared::config::Config;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::ignore::IgnoreRules;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::staging::StagingArea;
+use crate::shared::traversal::Walk;
+
+/// A container `container install` copied into the store.
+#[derive(Debug, Clone)]
+pub struct Installation {
+    pub entry: RegistryEntry,
+    /// An earlier install of the same name and version was replaced (`--force`)
+    pub replaced: bool,
+    pub files: usize,
+    pub size: u64,
+    /// What checking the attestation of the source found
+    pub attestation: AttestationCheck,
+    /// Persistent directories carried over from the replaced install
+    pub kept_data: Vec<String>,
+    /// What linking the files to shared objects did, when `store.dedup` is on
+    pub dedup: Option<DedupSummary>,
+}
+
+pub struct InstallService;
+
+impl InstallService {
+    /// Copies the container at `source` to `<store>/<name>/<version>` and records it in
+    /// the registry. The copy is made in a staging area and renamed into place,
+    /// so a failed or interrupted install leaves nothing half-copied behind.
+    /// With `require_attestation` a source without a valid attestation is refused.
+    pub fn install(
+        source: &Path,
+        name: Option<String>,
+        force: bool,
+        require_attestation: bool,
+        paths: &WrappyPaths,
+        resolver: &ContainerResolver,
+        cancellation: &CancellationToken,
+    ) -> ContainerResult<Installation> {
+        let source = source.canonicalize().map_err(|e| Self::io_error(source, e))?;
+        let container = ContainerService::load_from_directory(&source)?;
+        ContainerService::ensure_host_requirements(&container.manifest)?;
+        if resolver.is_in_store(&source) {
+            return Err(ContainerError::InvalidPath {
+                path: source,
+                reason: "is already in the store".to_string(),
+            });
+        }
+
+        let name = name.unwrap_or_else(|| container.name().to_string());
+        RegistryEntry::check_name(&name)?;
+        let selector = format!("{}@{}", name, container.version());
+        let existing: Vec<_> = resolver
+            .installed_versions(&name)
+            .into_iter()
+            .filter(|candidate| candidate.version == *container.version())
+            .collect();
+        // Replacing an adoption would delete files wrappy does not own
+        if let Some(adopted) = existing.iter().find(|candidate| candidate.adopted) {
+            return Err(ContainerError::InvalidPath {
+                path: adopted.path.clone(),
+                reason: format!("{} is adopted from here; remove it first with `wrappy container remove`", selector),
+            });
+        }
+
+        let store_dir = resolver.store_dir();
+        let destination = store_dir.join(&name).join(container.version().to_string());
+        let replaced = destination.exists() || !existing.is_empty();
+        if replaced && !force {
+            return Err(ContainerError::ContainerExists { name: selector });
+        }
+
+        let attestation = AttestationService::verify(&container, paths);
+        if require_attestation {
+            let reason = match &attestation {
+                AttestationCheck::Verified(_) => None,
+                AttestationCheck::Missing => Some("it has none".to_string()),
+                AttestationCheck::Invalid(reason) => Some(reason.clone()),
+            };
+            if let Some(reason) = reason {
+                return Err(ContainerError::AttestationRequired { path: source, reason });
+            }
+        }
+
+        let staging = StagingArea::create(paths, store_dir)?;
+        let (files, size) = Self::copy_container(&source, &staging.join("container"), cancellation)?;
+        // Linking the staged copy keeps a failed dedup from leaving a half-installed container
+        let dedup = if Config::load_or_default(paths)?.store.dedup {
+            let staged = ContainerService::load_from_directory(staging.join("container"))?;
+            Some(ObjectStore::at(paths.objects_dir(), store_dir.to_path_buf()).dedup_container(&staged)?)
+        } else {
+            None
+        };
+        let kept_data = Self::move_into_place(&staging, &destination, &container.manifest.persistent)?;
+        drop(staging);
+        ContainerService::ensure_persistent_dirs(&ContainerService::load_from_directory(&destination)?)?;
+
+        InstallMetadata::record_installed_with(&destination)?;
+        let entry = RegistryEntry {
+            name,
+            version: container.version().clone(),
+            path: destination.canonicalize().map_err(|e| Self::io_error(&destination, e))?,
+            adopted: false,
+            registered_at: clock::now(),
+            attestation: match &attestation {
+                AttestationCheck::Verified(attestation) => Some(attestation.statement.subject.clone()),
+                _ => None,
+            },
+            dependencies: Some(RegistryEntry::required_dependencies(&container.manifest)),
+            missing_dependencies: Vec::new(),
+        };
+        ContainerRegistry::new(paths).register(entry.clone())?;
+        Ok(Installation {
+            entry,
+            replaced,
+            files,
+            size,
+            attestation,
+            kept_data,
+            dedup,
+        })
+    }
+
+    /// Copies what belongs to the container: ignored paths and wrappy's own state stay behind.
+    /// Links are recreated as they are. Returns the number of files and their size.
+    fn copy_container(source: &Path, target: &Path, cancellation: &CancellationToken) -> ContainerResult<(usize, u64)> {
+        let mut walk = Walk::new(source).follow_root_link().ignoring(IgnoreRules::for_container(source)?);
+        let (mut files, mut size) = (0, 0);
+        while let Some(entry) = walk.next() {
+            let entry = entry?;
+            let relative = entry.path.strip_prefix(source).unwrap_or(&entry.path).to_path_buf();
+            if entry.depth == 1 && STATE_ENTRIES.iter().any(|state| relative == Path::new(state)) {
+                if entry.file_type.is_dir() {
+                    walk.skip_current_dir();
+                }
+                continue;
+            }
+            cancellation.check("installing the container")?;
+
+            let target_path = target.join(&relative);
+            if entry.file_type.is_symlink() {
+                let link = fs::read_link(&entry.path).map_err(|e| Self::io_error(&entry.path, e))?;
+                unix_fs::symlink(&link, &target_path).map_err(|e| Self::io_error(&target_path, e))?;
+            } else if entry.file_type.is_dir() {
+                fs::create_dir(&target_path).map_err(|e| Self::io_error(&target_path, e))?;
+            } else {
+                size += fs::copy(&entry.path, &target_path).map_err(|e| Self::io_error(&target_path, e))?;
+                files += 1;
+            }
+        }
+        Ok((files, size))
+    }
+
+    /// Renames the staged copy to `destination`. An install being replaced is moved
+    /// into the staging area first, put back if the rename fails and otherwise removed with it.
+    /// Its `persistent` directories replace those of the copy, so data survives the swap;
+    /// returns the ones carried over.
+    fn move_into_place(staging: &StagingArea, destination: &Path, persistent: &[String]) -> ContainerResult<Vec<String>> {
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).map_err(|e| Self::io_error(parent, e))?;
+        }
+        let copy = staging.join("container");
+        if !destination.exists() {
+            fs::rename(&copy, destination).map_err(|e| Self::io_error(destination, e))?;
+            return Ok(Vec::new());
+        }
+
+        let previous = staging.join("previous");
+        fs::rename(destination, &previous).map_err(|e| Self::io_error(destination, e))?;
+        let kept = match Self::carry_over(&previous, &copy, persistent) {
+            Ok(kept) => kept,
+            Err(error) => {
+                let _ = fs::rename(&previous, destination);
+                return Err(error);
+            }
+        };
+        if let Err(e) = fs::rename(&copy, destination) {
+            // Data goes back before the old install does, or it would be deleted with the staging area
+            for entry in &kept {
+                let _ = fs::rename(copy.join(entry), previous.join(entry));
+            }
+            let _ = fs::rename(&previous, destination);
+            return Err(Self::io_error(destination, e));
+        }
+        Ok(kept)
+    }
+
+    /// Moves the `persistent` directories of `previous` into `copy`, over what the source
+    /// had there. On failure the ones already moved go back.
+    fn carry_over(previous: &Path, copy: &Path, persistent: &[String]) -> ContainerResult<Vec<String>> {
+        let mut kept = Vec::new();
+        for entry in persistent {
+            let (from, to) = (previous.join(entry), copy.join(entry));
+            if !from.is_dir() {
+                continue;
+            }
+            let moved = (|| {
+                if to.symlink_metadata().is_ok() {
+                    fs::remove_dir_all(&to)?;
+                }
+                if let Some(parent) = to.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::rename(&from, &to)
+            })();
+            if let Err(e) = moved {
+                for entry in &kept {
+                    let _ = fs::rename(copy.join(entry), previous.join(entry));
+                }
+                return Err(Self::io_error(&to, e));
+            }
+            kept.push(entry.clone());
+        }
+        Ok(kept)
+    }
+
+    fn io_error(path: &Path, source: std::io::Error) -> ContainerError {
+        ContainerError::IoError {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::setup::SetupService;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, symlink_fixture, TempHome};
+    use serde_json::json;
+    use std::os::unix::fs::MetadataExt;
+
+    #[test]
+    fn install_refuses_a_container_whose_host_requirements_are_unmet() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        SetupService::initialize(&paths).unwrap();
+        let mut manifest = fixture_manifest("needy", "1.0.0");
+        manifest["host_requirements"] = json!([{ "command": "wrappy-test-no-such-tool" }]);
+        let source = home.path().join("src/needy");
+        fixture_container_at(&source, manifest);
+        let resolver = ContainerResolver::with_store(paths.default_store_dir());
+
+        let result = InstallService::install(&source, None, false, false, &paths, &resolver, &CancellationToken::new());
+
+        assert!(matches!(result, Err(ContainerError::HostRequirementsUnmet { .. })), "{:?}", result.map(|_| ()));
+        assert!(resolver.installed_versions("needy").is_empty());
+        assert!(!paths.default_store_dir().join("needy").exists());
+    }
+
+    #[test]
+    fn install_creates_persistent_directories_and_a_forced_reinstall_keeps_their_data() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        SetupService::initialize(&paths).unwrap();
+        let mut manifest = fixture_manifest("app", "1.0.0");
+        manifest["persistent"] = json!(["data"]);
+        let source = home.path().join("src/app");
+        fixture_container_at(&source, manifest);
+        let resolver = ContainerResolver::with_store(paths.default_store_dir());
+        let install = |force| InstallService::install(&source, None, force, false, &paths, &resolver, &CancellationToken::new());
+
+        let first = install(false).unwrap();
+        let data = first.entry.path.join("data");
+        assert!(data.is_dir());
+        fs::write(data.join("state"), "written by the app").unwrap();
+        fs::write(source.join("content/new"), "update").unwrap();
+
+        let second = install(true).unwrap();
+
+        assert!(second.replaced);
+        assert_eq!(second.kept_data, vec!["data"]);
+        assert_eq!(fs::read_to_string(data.join("state")).unwrap(), "written by the app");
+        assert!(second.entry.path.join("content/new").is_file());
+    }
+
+    #[test]
+    fn with_store_dedup_on_identical_files_of_a_second_install_are_linked_to_the_first() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        SetupService::initialize(&paths).unwrap();
+        let mut config = Config::load_or_default(&paths).unwrap();
+        config.store.dedup = true;
+        config.save(&paths).unwrap();
+        let resolver = ContainerResolver::with_store(paths.default_store_dir());
+        let install = |name: &str| {
+            let source = home.path().join("src").join(name);
+            fixture_container_at(&source, fixture_manifest(name, "1.0.0"));
+            InstallService::install(&source, None, false, false, &paths, &resolver, &CancellationToken::new()).unwrap()
+        };
+
+        let first = install("app");
+        let second = install("api");
+
+        let inode = |entry: &RegistryEntry| fs::metadata(entry.path.join("bin/tool")).unwrap().ino();
+        assert_eq!(inode(&first.entry), inode(&second.entry));
+        let summary = second.dedup.unwrap();
+        assert!(!summary.cross_device);
+        assert!(summary.files_linked >= 2, "{:?}", summary);
+        assert_eq!(first.dedup.unwrap().files_linked, 0);
+        // Manifests differ, so they keep their own contents
+        assert!(fs::read_to_string(second.entry.path.join("manifest.json")).unwrap().contains("\"api\""));
+    }
+
+    #[test]
+    fn with_store_dedup_off_installs_keep_plain_copies() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        SetupService::initialize(&paths).unwrap();
+        let resolver = ContainerResolver::with_store(paths.default_store_dir());
+        let source = home.path().join("src/app");
+        fixture_container_at(&source, fixture_manifest("app", "1.0.0"));
+
+        let installation = InstallService::install(&source, None, false, false, &paths, &resolver, &CancellationToken::new()).unwrap();
+
+        assert!(installation.dedup.is_none());
+        assert_eq!(fs::metadata(installation.entry.path.join("bin/tool")).unwrap().nlink(), 1);
+        assert!(!paths.objects_dir().exists());
+    }
+
+    #[test]
+    fn parallel_installs_stage_separately_and_leave_no_staging_behind() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        SetupService::initialize(&paths).unwrap();
+        let names = ["alpha", "beta", "gamma", "delta"];
+        for name in names {
+            fixture_container_at(&home.path().join("src").join(name), fixture_manifest(name, "1.0.0"));
+        }
+
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let installs: Vec<_> = names
+                .iter()
+                .map(|name| {
+                    let (paths, source) = (&paths, home.path().join("src").join(name));
+                    scope.spawn(move || {
+                        let resolver = ContainerResolver::with_store(paths.default_store_dir());
+                        InstallService::install(&source, None, false, false, paths, &resolver, &CancellationToken::new())
+                            .map(|installation| installation.entry.path)
+                    })
+                })
+                .collect();
+            installs.into_iter().map(|install| install.join().unwrap()).collect()
+        });
+
+        for (name, result) in names.iter().zip(results) {
+            assert_eq!(result.unwrap(), paths.default_store_dir().join(name).join("1.0.0"));
+        }
+        for root in StagingArea::roots(&paths, &paths.default_store_dir()) {
+            assert!(fs::read_dir(&root).map_or(true, |mut entries| entries.next().is_none()), "{} not empty", root.display());
+        }
+    }
+
+    #[test]
+    fn a_failed_install_removes_its_staging_directory() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        SetupService::initialize(&paths).unwrap();
+        let source = home.path().join("src/app");
+        fixture_container_at(&source, fixture_manifest("app", "1.0.0"));
+        let resolver = ContainerResolver::with_store(paths.default_store_dir());
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+
+        let result = InstallService::install(&source, None, false, false, &paths, &resolver, &cancellation);
+
+        assert!(matches!(result, Err(ContainerError::Cancelled { .. })), "{:?}", result.map(|_| ()));
+        assert!(fs::read_dir(paths.staging_dir()).map_or(true, |mut entries| entries.next().is_none()));
+        assert!(!paths.default_store_dir().join("app").exists());
+    }
+
+    #[test]
+    fn dangling_absolute_and_cyclic_symlinks_are_installed_as_they_are() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        SetupService::initialize(&paths).unwrap();
+        let resolver = ContainerResolver::with_store(paths.default_store_dir());
+
+        for kind in ["dangling", "absolute", "cyclic"] {
+            let source = symlink_fixture(kind);
+
+            let installed = InstallService::install(&source, None, false, false, &paths, &resolver, &CancellationToken::new())
+                .unwrap()
+                .entry
+                .path;
+
+            for link in ["content/link", "content/loop-b"] {
+                let Ok(expected) = fs::read_link(source.join(link)) else { continue };
+                assert!(installed.join(link).symlink_metadata().unwrap().file_type().is_symlink(), "{}: {}", kind, link);
+                assert_eq!(fs::read_link(installed.join(link)).unwrap(), expected, "{}: {}", kind, link);
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+use crate::features::container::ExecutionPlan;
+use crate::features::manifest::ContainerManifest;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::host;
+
+/// Interpreter named by a script's `#!` line and whether this machine has it.
+/// A container validated on its author's machine may rely on `python3` or `node`
+/// that is missing here, which otherwise only shows up as an exec error at run time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterpreterCheck {
+    pub script: String,
+    /// The whole `#!` line without the marker, e.g. `/usr/bin/env python3`
+    pub shebang: String,
+    /// What has to exist on the host: the program `env` looks up, or the absolute interpreter path
+    pub command: String,
+    pub available: bool,
+}
+
+impl InterpreterCheck {
+    /// Checks the interpreter of one shebang line against the host PATH.
+    pub fn from_shebang(script: &str, shebang: &str) -> Self {
+        let command = Self::command_of(shebang);
+        Self {
+            script: script.to_string(),
+            shebang: shebang.to_string(),
+            available: host::find_executable(&command).is_some(),
+            command,
+        }
+    }
+
+    /// Checks every script of the container that has a `#!` line, sorted by script name.
+    /// Inline scripts are read from the manifest, so they need not be materialized yet.
+    pub fn check_container(path: &Path, manifest: &ContainerManifest) -> Vec<Self> {
+        let mut names: Vec<&String> = manifest.scripts.keys().collect();
+        names.sort();
+
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let script = &manifest.scripts[name];
+                let shebang = match script.inline() {
+                    Some(inline) => inline
+                        .lines()
+                        .next()
+                        .and_then(|line| line.strip_prefix("#!"))
+                        .map(|shebang| shebang.trim().to_string())
+                        .filter(|shebang| !shebang.is_empty()),
+                    None => ExecutionPlan::read_interpreter(&path.join(script.relative_path(name))),
+                }?;
+                Some(Self::from_shebang(name, &shebang))
+            })
+            .collect()
+    }
+
+    /// Fails with the missing interpreter named, instead of the bare exec error a run would give.
+    pub fn ensure_available(&self) -> ContainerResult<()> {
+        if self.available {
+            return Ok(());
+        }
+        Err(ContainerError::InterpreterMissing {
+            script: self.script.clone(),
+            command: self.command.clone(),
+            shebang: self.shebang.clone(),
+        })
+    }
+
+    /// `/usr/bin/env [-S] [VAR=value] python3 -u` needs `python3`; any other
+    /// shebang needs its first word, usually an absolute path.
+    fn command_of(shebang: &str) -> String {
+        let mut words = shebang.split_whitespace();
+        let first = words.next().unwrap_or_default();
+        if Path::new(first).file_name().is_some_and(|name| name == "env") {
+            if let Some(program) = words.find(|word| !word.starts_with('-') && !word.contains('=')) {
+                return program.to_string();
+            }
+        }
+        first.to_string()
+    }
+}
+
+impl fmt::Display for InterpreterCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.available {
+            write!(f, "script '{}': {} (#!{})", self.script, self.command, self.shebang)
+        } else {
+            write!(
+                f,
+                "script '{}' needs '{}' (#!{}), which is not installed on this machine",
+                self.script, self.command, self.shebang
+            )
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::features::container::{InterpreterCheck, StateLocation};
+use crate::features::Version;
+use crate::shared::clock;
+use crate::shared::error::ContainerResult;
+use crate::shared::filesystem;
+
+/// Written by wrappy itself; not part of the container's content.
+pub const METADATA_FILE: &str = ".wrappy-meta.json";
+
+/// What wrappy knows about an installed container beyond its manifest: which
+/// wrappy last laid it out (layouts and wrapper templates change between releases,
+/// so bug reports need this to be reproducible) and whether its script interpreters exist here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallMetadata {
+    /// `None` when only the interpreters have been recorded so far
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installed_with: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+    /// Interpreter checks as of the last validate or run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interpreters: Vec<InterpreterCheck>,
+}
+
+impl InstallMetadata {
+    /// Changes the metadata of the container at `path`, creating it when missing.
+    pub fn update(path: &Path, change: impl FnOnce(&mut Self)) -> ContainerResult<()> {
+        let mut metadata = Self::read(path).unwrap_or_else(|| Self {
+            installed_with: None,
+            recorded_at: clock::now(),
+            interpreters: Vec::new(),
+        });
+        change(&mut metadata);
+        metadata.recorded_at = clock::now();
+        metadata.write(path)
+    }
+
+    /// Records that the running wrappy laid out the container.
+    pub fn record_installed_with(path: &Path) -> ContainerResult<()> {
+        Self::update(path, |metadata| metadata.installed_with = Some(env!("CARGO_PKG_VERSION").to_string()))
+    }
+
+    /// Records the result of checking every script interpreter.
+    pub fn record_interpreters(path: &Path, checks: &[InterpreterCheck]) -> ContainerResult<()> {
+        Self::update(path, |metadata| metadata.interpreters = checks.to_vec())
+    }
+
+    /// Replaces the recorded check of one script; writes nothing when it did not change.
+    pub fn record_interpreter(path: &Path, check: &InterpreterCheck) -> ContainerResult<()> {
+        let recorded = Self::read(path)
+            .map(|metadata| metadata.interpreters)
+            .unwrap_or_default();
+        if recorded.contains(check) {
+            return Ok(());
+        }
+        Self::update(path, |metadata| {
+            metadata.interpreters.retain(|recorded| recorded.script != check.script);
+            metadata.interpreters.push(check.clone());
+            metadata.interpreters.sort_by(|a, b| a.script.cmp(&b.script));
+        })
+    }
+
+    /// Metadata of the container at `path`; containers from before it was recorded have none.
+    pub fn read(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(StateLocation::resolve(path).join(METADATA_FILE)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn write(&self, path: &Path) -> ContainerResult<()> {
+        let content = serde_json::to_vec_pretty(self)?;
+        filesystem::atomic_write(&StateLocation::writable(path)?.join(METADATA_FILE), &content, None)
+    }
+
+    /// Version shown to users, "unknown" for old installs.
+    pub fn installed_with_label(path: &Path) -> String {
+        Self::read(path)
+            .and_then(|metadata| metadata.installed_with)
+            .map(|installed_with| format!("wrappy {}", installed_with))
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// True when a newer major or minor release wrote the container, which
+    /// may rely on layout this wrappy does not understand. Patch releases do not count.
+    pub fn is_from_newer_wrappy(&self) -> bool {
+        let release = |version: &str| {
+            let version = Version::new(version).ok()?;
+            Some((version.major().ok()?, version.minor().ok()?))
+        };
+        match (self.installed_with.as_deref().and_then(release), release(env!("CARGO_PKG_VERSION"))) {
+            (Some(installed), Some(running)) => installed > running,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::features::container::{create_missing_config_files, missing_config_files};
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::privilege::PrivilegeGuard;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Manifest file name used by early layouts.
+const LEGACY_MANIFEST_FILE: &str = "container.json";
+
+const REQUIRED_DIRECTORIES: [&str; 3] = ["scripts", "content", "config"];
+const DEFAULT_SCRIPT_PATH: &str = "scripts/default.sh";
+
+/// Manifest keys from early layouts and their current names.
+const RENAMED_FIELDS: [(&str, &str); 3] = [("env", "environment"), ("deps", "dependencies"), ("binds", "bindings")];
+
+/// Container directory and raw manifest being migrated; the manifest is kept as JSON
+/// because legacy shapes do not deserialize into `ContainerManifest`.
+pub struct MigrationContext {
+    pub path: PathBuf,
+    pub manifest: Value,
+    manifest_changed: bool,
+}
+
+impl MigrationContext {
+    fn manifest_object(&mut self) -> &mut Map<String, Value> {
+        self.manifest_changed = true;
+        if !self.manifest.is_object() {
+            self.manifest = Value::Object(Map::new());
+        }
+        self.manifest.as_object_mut().expect("manifest was just made an object")
+    }
+}
+
+/// One known legacy pattern: how to recognise it and how to bring it up to date.
+/// New layout changes are supported by appending an entry to `LAYOUT_MIGRATIONS`.
+pub struct LayoutMigration {
+    pub id: &'static str,
+    pub description: &'static str,
+    detect: fn(&MigrationContext) -> bool,
+    apply: fn(&mut MigrationContext) -> ContainerResult<()>,
+}
+
+/// Checked and applied in order; later entries may rely on earlier ones (e.g. directories exist before files).
+pub const LAYOUT_MIGRATIONS: &[LayoutMigration] = &[
+    LayoutMigration {
+        id: "legacy-manifest-name",
+        description: "rename container.json to manifest.json",
+        detect: |ctx| !ctx.path.join(MANIFEST_FILE).exists() && ctx.path.join(LEGACY_MANIFEST_FILE).exists(),
+        apply: |ctx| {
+            // The content is written as manifest.json at the end of the migration
+            ctx.manifest_changed = true;
+            remove_file(&ctx.path.join(LEGACY_MANIFEST_FILE))
+        },
+    },
+    LayoutMigration {
+        id: "renamed-fields",
+        description: "rename legacy manifest fields (env, deps, binds)",
+        detect: |ctx| RENAMED_FIELDS.iter().any(|(old, _)| ctx.manifest.get(old).is_some()),
+        apply: |ctx| {
+            let manifest = ctx.manifest_object();
+            for (old, new) in RENAMED_FIELDS {
+                if let Some(value) = manifest.remove(old) {
+                    manifest.entry(new).or_insert(value);
+                }
+            }
+            Ok(())
+        },
+    },
+    LayoutMigration {
+        id: "string-dependencies",
+        description: "convert \"name@version\" dependencies to objects",
+        detect: |ctx| {
+            ctx.manifest
+                .get("dependencies")
+                .and_then(Value::as_array)
+                .is_some_and(|deps| deps.iter().any(Value::is_string))
+        },
+        apply: |ctx| {
+            let manifest = ctx.manifest_object();
+            let Some(Value::Array(dependencies)) = manifest.get_mut("dependencies") else {
+                return Ok(());
+            };
+            for dependency in dependencies.iter_mut() {
+                let Some(spec) = dependency.as_str() else {
+                    continue;
+                };
+                let (name, version) = spec.split_once('@').ok_or_else(|| ContainerError::InvalidDependency {
+                    package: spec.to_string(),
+                    reason: "legacy dependency has no version, expected 'name@version'".to_string(),
+                })?;
+                *dependency = serde_json::json!({ "name": name, "version": version });
+            }
+            Ok(())
+        },
+    },
+    LayoutMigration {
+        id: "missing-default-script",
+        description: "register scripts/default.sh as the default script",
+        detect: |ctx| {
+            ctx.path.join(DEFAULT_SCRIPT_PATH).is_file()
+                && ctx.manifest.get("scripts").and_then(|scripts| scripts.get("default")).is_none()
+        },
+        apply: |ctx| {
+            let manifest = ctx.manifest_object();
+            let scripts = manifest
+                .entry("scripts")
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Some(scripts) = scripts.as_object_mut() {
+                scripts.insert("default".to_string(), Value::String(DEFAULT_SCRIPT_PATH.to_string()));
+            }
+            Ok(())
+        },
+    },
+    LayoutMigration {
+        id: "missing-directories",
+        description: "create missing scripts/, content/ and config/ directories",
+        detect: |ctx| REQUIRED_DIRECTORIES.iter().any(|dir| !ctx.path.join(dir).exists()),
+        apply: |ctx| {
+            for dir in REQUIRED_DIRECTORIES {
+                let dir_path = ctx.path.join(dir);
+                fs::create_dir_all(&dir_path).map_err(|e| ContainerError::IoError {
+                    path: dir_path,
+                    source: e,
+                })?;
+            }
+            Ok(())
+        },
+    },
+    LayoutMigration {
+        id: "missing-config-files",
+        description: "create skeleton config/permissions.json and config/environment.json",
+        detect: |ctx| !missing_config_files(&ctx.path).is_empty(),
+        apply: |ctx| create_missing_config_files(&ctx.path).map(|_| ()),
+    },
+];
+
+/// What `migrate_layout` did to a container directory.
+pub struct MigrationReport {
+    pub applied: Vec<&'static LayoutMigration>,
+    /// Copy of the original manifest, written before anything was changed
+    pub backup: Option<PathBuf>,
+}
+
+pub struct MigrationService;
+
+impl MigrationService {
+    /// Detects legacy patterns in `path` and applies every matching migration.
+    /// The original manifest is backed up before the first change.
+    pub fn migrate_layout(path: &Path) -> ContainerResult<MigrationReport> {
+        PrivilegeGuard::ensure_owner(path, "migrate a container")?;
+        let source = [MANIFEST_FILE, LEGACY_MANIFEST_FILE]
+            .iter()
+            .map(|name| path.join(name))
+            .find(|candidate| candidate.exists())
+            .ok_or_else(|| {
+                ContainerError::InvalidStructure(format!(
+                    "neither {} nor {} found in {}",
+                    MANIFEST_FILE,
+                    LEGACY_MANIFEST_FILE,
+                    path.display()
+                ))
+            })?;
+
+        let original = filesystem::read_json_file(&source).map_err(|e| ContainerError::IoError {
+            path: source.clone(),
+            source: e,
+        })?;
+        let manifest: Value =
+            serde_json::from_str(&original).map_err(|e| ContainerError::InvalidManifest(e.to_string()))?;
+
+        let mut context = MigrationContext {
+            path: path.to_path_buf(),
+            manifest,
+            manifest_changed: false,
+        };
+
+        // Detection runs right before each migration so it sees the result of earlier ones
+        let mut applied: Vec<&'static LayoutMigration> = Vec::new();
+        let mut backup = None;
+        for migration in LAYOUT_MIGRATIONS {
+            if !(migration.detect)(&context) {
+                continue;
+            }
+            if backup.is_none() {
+                backup = Some(Self::write_backup(&source, &original)?);
+            }
+            (migration.apply)(&mut context)?;
+            applied.push(migration);
+        }
+
+        if context.manifest_changed {
+            let content = serde_json::to_string_pretty(&context.manifest)?;
+            filesystem::atomic_write(&path.join(MANIFEST_FILE), content.as_bytes(), None)?;
+        }
+
+        Ok(MigrationReport { applied, backup })
+    }
+
+    fn write_backup(source: &Path, original: &str) -> ContainerResult<PathBuf> {
+        let mut backup = source.as_os_str().to_owned();
+        backup.push(".wrappy-backup");
+        let backup = PathBuf::from(backup);
+        filesystem::atomic_write(&backup, original.as_bytes(), None)?;
+        Ok(backup)
+    }
+}
+
+fn remove_file(path: &Path) -> ContainerResult<()> {
+    fs::remove_file(path).map_err(|e| ContainerError::IoError {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
@@ -1,6 +1,52 @@
+mod attestation;
 mod commands;
+mod config_files;
+mod dependency_index;
+mod dependency_run;
+mod environment;
+mod executor;
+mod export;
+mod install;
+mod interpreter;
+mod metadata;
+mod migration;
+mod output;
+mod plan;
+mod registry;
+mod report;
+mod resolver;
+mod run_commands;
+mod scaffold;
+mod seal;
 mod service;
+mod service_runtime;
+mod stash;
+mod state_location;
+mod types;
+mod variables;
 
+pub use attestation::*;
 pub use commands::*;
+pub use config_files::*;
+pub use dependency_index::*;
+pub use dependency_run::*;
+pub use environment::*;
+pub use executor::*;
+pub use export::*;
+pub use install::*;
+pub use interpreter::*;
+pub use metadata::*;
+pub use migration::*;
+pub use output::*;
+pub use plan::*;
+pub use registry::*;
+pub use report::*;
+pub use resolver::*;
+pub use scaffold::*;
+pub use seal::*;
 pub use service::*;
-
+pub use service_runtime::*;
+pub use stash::*;
+pub use state_location::*;
+pub use types::*;
+pub use variables::*;
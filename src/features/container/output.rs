@@ -0,0 +1,409 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, OwnedFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::shared::clock;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+
+/// Where the output of a script run goes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// The script writes straight to wrappy's terminal
+    #[default]
+    Inherit,
+    /// Output reaches the terminal and is also written, interleaved, to the file
+    Tee(PathBuf),
+    /// Every line becomes one JSON record on stdout, for log shippers
+    Json,
+    /// Output is discarded; only the exit status is reported
+    Quiet,
+}
+
+impl OutputMode {
+    /// Name recorded in the run history; `None` for the default mode.
+    pub fn history_name(&self) -> Option<&'static str> {
+        match self {
+            OutputMode::Inherit => None,
+            OutputMode::Tee(_) => Some("tee"),
+            OutputMode::Json => Some("json"),
+            OutputMode::Quiet => Some("quiet"),
+        }
+    }
+
+    pub fn file(&self) -> Option<&Path> {
+        match self {
+            OutputMode::Tee(path) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for OutputMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "inherit" => Ok(OutputMode::Inherit),
+            "json" => Ok(OutputMode::Json),
+            "quiet" => Ok(OutputMode::Quiet),
+            _ => match value.strip_prefix("tee:") {
+                Some("") => Err("tee needs a file, as tee:<file>".to_string()),
+                Some(file) => Ok(OutputMode::Tee(PathBuf::from(file))),
+                None => Err(format!("unknown output mode '{}'; use tee:<file>, json or quiet", value)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for OutputMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputMode::Inherit => write!(f, "inherit"),
+            OutputMode::Tee(path) => write!(f, "tee:{}", path.display()),
+            OutputMode::Json => write!(f, "json"),
+            OutputMode::Quiet => write!(f, "quiet"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+impl OutputStream {
+    fn index(self) -> usize {
+        match self {
+            OutputStream::Stdout => 0,
+            OutputStream::Stderr => 1,
+        }
+    }
+}
+
+/// One line of script output in `--output json` mode.
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputLine {
+    pub stream: OutputStream,
+    pub at: DateTime<Utc>,
+    pub line: String,
+}
+
+/// One pipe of the child, read without blocking.
+struct Pipe {
+    reader: Option<File>,
+    /// Bytes after the last newline, kept until the line is complete
+    partial: Vec<u8>,
+}
+
+impl Pipe {
+    fn new(fd: Option<OwnedFd>) -> io::Result<Self> {
+        let reader = fd.map(File::from);
+        if let Some(reader) = &reader {
+            set_nonblocking(reader.as_raw_fd())?;
+        }
+        Ok(Self {
+            reader,
+            partial: Vec::new(),
+        })
+    }
+
+    fn fd(&self) -> Option<RawFd> {
+        self.reader.as_ref().map(AsRawFd::as_raw_fd)
+    }
+
+    /// Reads what is available now; `None` once nothing is waiting, and the pipe is closed at EOF.
+    fn read_available(&mut self, buffer: &mut [u8]) -> Option<usize> {
+        let reader = self.reader.as_mut()?;
+        match reader.read(buffer) {
+            Ok(0) => {
+                self.reader = None;
+                None
+            }
+            Ok(read) => Some(read),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => Some(0),
+            // WouldBlock, or a broken pipe that will never deliver more
+            Err(e) => {
+                if e.kind() != io::ErrorKind::WouldBlock {
+                    self.reader = None;
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Reads the stdout and stderr pipes of a running script and forwards what arrives
+/// according to the output mode. Both pipes are non-blocking and polled together, so
+/// chunks are handled in the order they become readable and stdout/stderr keep their
+/// interleaving as closely as the two pipes allow.
+pub struct OutputCapture {
+    mode: OutputMode,
+    tee: Option<File>,
+    /// Indexed by `OutputStream::index`
+    pipes: [Pipe; 2],
+    /// Where output reaching the terminal goes, indexed like `pipes`
+    terminal: [Box<dyn Write + Send>; 2],
+}
+
+impl OutputCapture {
+    /// Takes the piped stdout and stderr of `child`. The tee file is truncated, as `tee` does.
+    pub fn new(mode: OutputMode, child: &mut Child) -> ContainerResult<Self> {
+        Self::with_terminal(mode, child, Box::new(io::stdout()), Box::new(io::stderr()))
+    }
+
+    /// Like `new`, with what would reach wrappy's stdout and stderr written to `stdout` and `stderr`.
+    pub fn with_terminal(
+        mode: OutputMode,
+        child: &mut Child,
+        stdout: Box<dyn Write + Send>,
+        stderr: Box<dyn Write + Send>,
+    ) -> ContainerResult<Self> {
+        let tee = match mode.file() {
+            Some(path) => Some(
+                filesystem::state_file_options()
+                    .create(true)
+                    .write(true)
+                    .truncate(true)
+                    .open(path)
+                    .map_err(|e| Self::io_error(path, e))?,
+            ),
+            None => None,
+        };
+        let piped_stdout = child.stdout.take().map(OwnedFd::from);
+        let piped_stderr = child.stderr.take().map(OwnedFd::from);
+        let pipes = Pipe::new(piped_stdout)
+            .and_then(|piped_stdout| Ok([piped_stdout, Pipe::new(piped_stderr)?]))
+            .map_err(|e| ContainerError::Runtime {
+                message: format!("failed to capture script output: {}", e),
+            })?;
+        Ok(Self {
+            mode,
+            tee,
+            pipes,
+            terminal: [stdout, stderr],
+        })
+    }
+
+    /// Waits up to `timeout` for output and forwards everything that is readable.
+    pub fn pump(&mut self, timeout: Duration) {
+        let mut fds: Vec<libc::pollfd> = self
+            .pipes
+            .iter()
+            .filter_map(Pipe::fd)
+            .map(|fd| libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            })
+            .collect();
+        if fds.is_empty() {
+            return;
+        }
+        // SAFETY: `fds` is a valid array of `fds.len()` pollfd entries for the duration of the call.
+        unsafe {
+            libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout.as_millis() as libc::c_int);
+        }
+        self.drain()
+    }
+
+    /// Forwards everything waiting in the pipes, then completes trailing partial lines.
+    /// Called once the script has exited; a pipe still held open by a process the script
+    /// left behind is not waited for.
+    pub fn finish(&mut self) {
+        self.drain();
+        for stream in [OutputStream::Stdout, OutputStream::Stderr] {
+            let partial = std::mem::take(&mut self.pipes[stream.index()].partial);
+            if !partial.is_empty() {
+                self.emit_json(stream, &partial);
+            }
+        }
+        if let Some(tee) = &mut self.tee {
+            let _ = tee.flush();
+        }
+    }
+
+    fn drain(&mut self) {
+        let mut buffer = [0u8; 8192];
+        loop {
+            let mut progressed = false;
+            for stream in [OutputStream::Stdout, OutputStream::Stderr] {
+                if let Some(read) = self.pipes[stream.index()].read_available(&mut buffer) {
+                    self.forward(stream, &buffer[..read]);
+                    progressed = true;
+                }
+            }
+            if !progressed {
+                return;
+            }
+        }
+    }
+
+    fn forward(&mut self, stream: OutputStream, chunk: &[u8]) {
+        if chunk.is_empty() {
+            return;
+        }
+        match self.mode {
+            OutputMode::Json => {
+                let pipe = &mut self.pipes[stream.index()];
+                pipe.partial.extend_from_slice(chunk);
+                let Some(end) = pipe.partial.iter().rposition(|&byte| byte == b'\n') else {
+                    return;
+                };
+                let complete: Vec<u8> = pipe.partial.drain(..=end).collect();
+                for line in complete[..end].split(|&byte| byte == b'\n') {
+                    self.emit_json(stream, line);
+                }
+            }
+            _ => {
+                // Terminal output failing (e.g. a closed pager) must not stop the script
+                let terminal = &mut self.terminal[stream.index()];
+                let _ = terminal.write_all(chunk).and_then(|_| terminal.flush());
+                // Neither does a full disk: the run goes on without the file
+                if let (Some(tee), Some(path)) = (&mut self.tee, self.mode.file()) {
+                    if let Err(error) = tee.write_all(chunk) {
+                        eprintln!("⚠️  Stopped writing output to {}: {}", path.display(), error);
+                        self.tee = None;
+                    }
+                }
+            }
+        }
+    }
+
+    fn emit_json(&mut self, stream: OutputStream, line: &[u8]) {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let record = OutputLine {
+            stream,
+            at: clock::now(),
+            line: String::from_utf8_lossy(line).into_owned(),
+        };
+        if let Ok(json) = serde_json::to_string(&record) {
+            let stdout = &mut self.terminal[OutputStream::Stdout.index()];
+            let _ = writeln!(stdout, "{}", json).and_then(|_| stdout.flush());
+        }
+    }
+
+    fn io_error(path: &Path, source: io::Error) -> ContainerError {
+        ContainerError::IoError {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+fn set_nonblocking(fd: RawFd) -> io::Result<()> {
+    // SAFETY: fcntl on a file descriptor owned by the child's pipe handle.
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags < 0 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::write_executable;
+    use std::process::{Command, Stdio};
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    /// A terminal stream the test reads back.
+    #[derive(Clone, Default)]
+    struct Terminal(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Terminal {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Terminal {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    /// Runs a script alternating between stdout and stderr, ending in a CRLF line
+    /// and an unterminated one, and returns what reached the terminal's stdout and stderr.
+    fn capture(dir: &TempDir, mode: OutputMode) -> (String, String) {
+        let script = dir.path().join("chatty.sh");
+        write_executable(
+            &script,
+            "#!/bin/sh\necho out1\nsleep 0.05\necho err1 >&2\nsleep 0.05\nprintf 'out2\\r\\n'\nsleep 0.05\nprintf partial >&2\n",
+        );
+        let mut child = Command::new(&script).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().unwrap();
+        let (stdout, stderr) = (Terminal::default(), Terminal::default());
+        let mut capture =
+            OutputCapture::with_terminal(mode, &mut child, Box::new(stdout.clone()), Box::new(stderr.clone())).unwrap();
+
+        while child.try_wait().unwrap().is_none() {
+            capture.pump(Duration::from_millis(10));
+        }
+        capture.finish();
+        (stdout.text(), stderr.text())
+    }
+
+    #[test]
+    fn tee_writes_both_streams_interleaved_to_the_file_and_each_to_its_terminal_stream() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("run.log");
+
+        let (stdout, stderr) = capture(&dir, OutputMode::Tee(file.clone()));
+
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "out1\nerr1\nout2\r\npartial");
+        assert_eq!(stdout, "out1\nout2\r\n");
+        assert_eq!(stderr, "err1\npartial");
+    }
+
+    #[test]
+    fn json_frames_every_line_with_its_stream_and_time_on_stdout() {
+        let dir = TempDir::new().unwrap();
+
+        let (stdout, stderr) = capture(&dir, OutputMode::Json);
+
+        let records: Vec<serde_json::Value> = stdout.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        let framed: Vec<(&str, &str)> = records
+            .iter()
+            .map(|record| (record["stream"].as_str().unwrap(), record["line"].as_str().unwrap()))
+            .collect();
+        assert_eq!(framed, [("stdout", "out1"), ("stderr", "err1"), ("stdout", "out2"), ("stderr", "partial")]);
+        for record in &records {
+            assert_eq!(record.as_object().unwrap().len(), 3);
+            assert!(record["at"].as_str().unwrap().parse::<DateTime<Utc>>().is_ok(), "{}", record);
+        }
+        assert_eq!(stderr, "");
+    }
+
+    #[test]
+    fn modes_parse_from_and_print_as_their_flag_values() {
+        for (value, mode) in [
+            ("inherit", OutputMode::Inherit),
+            ("json", OutputMode::Json),
+            ("quiet", OutputMode::Quiet),
+            ("tee:/tmp/run log", OutputMode::Tee(PathBuf::from("/tmp/run log"))),
+        ] {
+            assert_eq!(value.parse::<OutputMode>().unwrap(), mode);
+            assert_eq!(mode.to_string(), value);
+        }
+        assert!("tee:".parse::<OutputMode>().is_err());
+        assert!("loud".parse::<OutputMode>().is_err());
+        assert_eq!(OutputMode::Inherit.history_name(), None);
+        assert_eq!(OutputMode::Tee(PathBuf::from("x")).history_name(), Some("tee"));
+    }
+}
@@ -0,0 +1,114 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::features::container::EnvironmentEntry;
+use crate::features::manifest::{IsolationConfig, ScriptMount};
+use crate::shared::redact;
+
+/// Output formats of `container run --dry-run`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PlanFormat {
+    Text,
+    Json,
+}
+
+/// Everything the executor decided about a script run before starting it.
+/// Running a script means building the plan and executing it, so a dry run
+/// shows exactly what a real run would do.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionPlan {
+    pub container: String,
+    pub version: String,
+    pub script: String,
+    pub script_path: PathBuf,
+    /// Interpreter from the script's `#!` line; `None` runs the file directly
+    pub interpreter: Option<String>,
+    pub args: Vec<String>,
+    pub working_directory: PathBuf,
+    pub inherit_host_environment: bool,
+    /// Final environment sorted by name; manifest values win over host ones
+    pub environment: Vec<EnvironmentEntry>,
+    /// Isolation as declared by the manifest; wrappy does not sandbox runs yet
+    pub isolation: Option<IsolationConfig>,
+    /// Extra host paths the script declares; they only apply inside a sandbox
+    pub mounts: Vec<ScriptMount>,
+    /// `host:port` entries of `config/permissions.json`; like mounts, only a sandbox could enforce them
+    pub network_allowlist: Vec<String>,
+}
+
+impl ExecutionPlan {
+    /// Copy safe to show or log, with secret-looking values masked.
+    pub fn redacted(&self) -> Self {
+        let mut plan = self.clone();
+        for entry in &mut plan.environment {
+            if redact::is_secret_key(&entry.key) {
+                entry.value = redact::REDACTED.to_string();
+            }
+        }
+        plan
+    }
+
+    /// Command that carries out the plan; the caller adds stdio and process group settings.
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.script_path);
+        command
+            .args(&self.args)
+            .current_dir(&self.working_directory)
+            .env_clear()
+            .envs(self.environment.iter().map(|entry| (&entry.key, &entry.value)));
+        command
+    }
+
+    /// Reads the `#!` line of `script_path`; scripts that do not exist yet have none.
+    pub fn read_interpreter(script_path: &Path) -> Option<String> {
+        let file = File::open(script_path).ok()?;
+        let mut first_line = String::new();
+        BufReader::new(file).read_line(&mut first_line).ok()?;
+        first_line
+            .strip_prefix("#!")
+            .map(|interpreter| interpreter.trim().to_string())
+            .filter(|interpreter| !interpreter.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::EnvironmentSource;
+
+    fn entry(key: &str, value: &str) -> EnvironmentEntry {
+        EnvironmentEntry {
+            key: key.to_string(),
+            value: value.to_string(),
+            source: EnvironmentSource::Manifest,
+        }
+    }
+
+    #[test]
+    fn redacted_plan_masks_secret_values_in_text_and_json() {
+        let plan = ExecutionPlan {
+            container: "web".to_string(),
+            version: "1.0.0".to_string(),
+            script: "default".to_string(),
+            script_path: PathBuf::from("/tmp/default.sh"),
+            interpreter: None,
+            args: Vec::new(),
+            working_directory: PathBuf::from("/tmp"),
+            inherit_host_environment: false,
+            environment: vec![entry("DB_PASSWORD", "hunter2"), entry("LANG", "C")],
+            isolation: None,
+            mounts: Vec::new(),
+            network_allowlist: Vec::new(),
+        };
+
+        let redacted = plan.redacted();
+
+        assert_eq!(redacted.environment[0].value, redact::REDACTED);
+        assert_eq!(redacted.environment[1].value, "C");
+        assert!(!serde_json::to_string(&redacted).unwrap().contains("hunter2"));
+        assert_eq!(plan.environment[0].value, "hunter2");
+    }
+}
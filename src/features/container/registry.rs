@@ -0,0 +1,430 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::features::container::{AttestationSubject, ContainerService, DependencyIndex};
+use crate::features::manifest::{ContainerManifest, ContainerType};
+use crate::features::Version;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::lock_file::LockFile;
+use crate::shared::paths::WrappyPaths;
+
+/// Format version written by this wrappy.
+pub const REGISTRY_VERSION: u32 = 1;
+
+/// One installed or adopted container.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    /// Name commands accept for this container; the manifest name unless installed or adopted with `--name`
+    pub name: String,
+    pub version: Version,
+    /// Canonical path of the container directory
+    pub path: PathBuf,
+    /// Kept where the user put it; wrappy never updates or deletes it on its own
+    #[serde(default)]
+    pub adopted: bool,
+    pub registered_at: DateTime<Utc>,
+    /// What the verified attestation of an installed export vouched for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<AttestationSubject>,
+    /// Names of the containers it requires, so removal finds dependents without reading
+    /// manifests; `None` for entries registered before wrappy recorded them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<Vec<String>>,
+    /// Required containers removed with `remove --force`; it is degraded until they are back
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing_dependencies: Vec<String>,
+}
+
+impl RegistryEntry {
+    pub fn selector(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+
+    /// Names work as directory names in the store and as selectors on the command line.
+    pub fn check_name(name: &str) -> ContainerResult<()> {
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+            return Err(ContainerError::ManifestValidation(format!(
+                "Container name '{}' can only contain alphanumeric characters, hyphens, and underscores",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Names of the non-optional dependencies `manifest` declares.
+    pub fn required_dependencies(manifest: &ContainerManifest) -> Vec<String> {
+        let mut names: Vec<String> = manifest
+            .dependencies
+            .iter()
+            .filter(|dependency| !dependency.optional)
+            .map(|dependency| dependency.name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Why the recorded path no longer holds this container, if it does not.
+    pub fn problem(&self) -> Option<String> {
+        if !self.path.is_dir() {
+            return Some("directory no longer exists".to_string());
+        }
+        match ContainerService::load_from_directory(&self.path) {
+            Ok(container) if container.manifest.version != self.version => Some(format!(
+                "directory now holds version {} instead of {}",
+                container.manifest.version, self.version
+            )),
+            Ok(_) => None,
+            Err(error) => Some(format!("directory is no longer a valid container: {}", error)),
+        }
+    }
+}
+
+/// Contents of `registry.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryState {
+    pub version: u32,
+    #[serde(default)]
+    pub containers: Vec<RegistryEntry>,
+}
+
+impl Default for RegistryState {
+    fn default() -> Self {
+        Self {
+            version: REGISTRY_VERSION,
+            containers: Vec::new(),
+        }
+    }
+}
+
+/// Whether a listed container can be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstalledStatus {
+    Ok,
+    /// The directory exists but does not load as a container
+    Error,
+    /// Registered, but the directory is gone
+    Missing,
+    /// Loads, but a container it requires was removed with `remove --force`
+    Degraded,
+}
+
+impl fmt::Display for InstalledStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            InstalledStatus::Ok => "ok",
+            InstalledStatus::Error => "error",
+            InstalledStatus::Missing => "missing",
+            InstalledStatus::Degraded => "degraded",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One row of `container list`.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstalledContainer {
+    pub name: String,
+    /// As recorded in the store layout or registry, since a broken manifest has none
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_type: Option<ContainerType>,
+    /// When it was registered, or when its store directory was last changed for older installs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installed_at: Option<DateTime<Utc>>,
+    pub path: PathBuf,
+    pub adopted: bool,
+    pub status: InstalledStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Index of the containers wrappy manages: those `container install` copied into
+/// the store and directories adopted from a git checkout or an external drive.
+pub struct ContainerRegistry {
+    path: PathBuf,
+}
+
+impl ContainerRegistry {
+    pub fn new(paths: &WrappyPaths) -> Self {
+        Self::at(paths.registry_file())
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Reads the registry; a missing file is an empty registry.
+    pub fn load(&self) -> ContainerResult<RegistryState> {
+        if !self.path.is_file() {
+            return Ok(RegistryState::default());
+        }
+        let content = fs::read_to_string(&self.path).map_err(|e| ContainerError::IoError {
+            path: self.path.clone(),
+            source: e,
+        })?;
+        let state: RegistryState = serde_json::from_str(&content).map_err(|e| ContainerError::InvalidConfig {
+            path: self.path.clone(),
+            reason: e.to_string(),
+        })?;
+        if state.version > REGISTRY_VERSION {
+            return Err(ContainerError::StateFileTooNew {
+                path: self.path.clone(),
+                version: state.version,
+                supported: REGISTRY_VERSION,
+            });
+        }
+        Ok(state)
+    }
+
+    pub fn save(&self, state: &RegistryState) -> ContainerResult<()> {
+        if let Some(parent) = self.path.parent() {
+            filesystem::create_state_dir(parent)?;
+        }
+        let content = serde_json::to_vec_pretty(state)?;
+        filesystem::atomic_write(&self.path, &content, Some(filesystem::STATE_FILE_MODE))
+    }
+
+    /// Every registered container, installed or adopted, in the order they were registered.
+    pub fn entries(&self) -> ContainerResult<Vec<RegistryEntry>> {
+        Ok(self.load()?.containers)
+    }
+
+    /// Adopted containers, in the order they were registered.
+    pub fn adopted(&self) -> ContainerResult<Vec<RegistryEntry>> {
+        Ok(self.load()?.containers.into_iter().filter(|entry| entry.adopted).collect())
+    }
+
+    /// Every version directory in `store_dir` and every registered directory, sorted by
+    /// name and version. Containers that no longer load are listed with their error
+    /// instead of being left out, so one broken manifest does not hide the rest.
+    pub fn installed(&self, store_dir: &Path) -> ContainerResult<Vec<InstalledContainer>> {
+        let entries = self.entries()?;
+        let mut listed = Vec::new();
+        for name_dir in Self::subdirectories(store_dir) {
+            let Some(name) = name_dir.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            // Dot directories hold staging areas, not containers
+            if name.starts_with('.') {
+                continue;
+            }
+            for version_dir in Self::subdirectories(&name_dir) {
+                let version = version_dir.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                let canonical = version_dir.canonicalize().unwrap_or_else(|_| version_dir.clone());
+                let entry = entries.iter().find(|entry| entry.path == canonical);
+                listed.push(Self::describe(name, &version, &version_dir, entry));
+            }
+        }
+
+        let seen: Vec<PathBuf> = listed.iter().filter_map(|row| row.path.canonicalize().ok()).collect();
+        for entry in entries.iter().filter(|entry| !seen.contains(&entry.path)) {
+            listed.push(Self::describe(&entry.name, entry.version.as_str(), &entry.path, Some(entry)));
+        }
+
+        listed.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| Self::version_order(&a.version, &b.version)));
+        Ok(listed)
+    }
+
+    fn describe(name: &str, version: &str, path: &Path, entry: Option<&RegistryEntry>) -> InstalledContainer {
+        let mut row = InstalledContainer {
+            name: name.to_string(),
+            version: version.to_string(),
+            container_type: None,
+            installed_at: entry.map(|entry| entry.registered_at),
+            path: path.to_path_buf(),
+            adopted: entry.is_some_and(|entry| entry.adopted),
+            status: InstalledStatus::Ok,
+            error: None,
+        };
+        if !path.is_dir() {
+            row.status = InstalledStatus::Missing;
+            row.error = Some("directory no longer exists".to_string());
+            return row;
+        }
+        if row.installed_at.is_none() {
+            row.installed_at = fs::metadata(path).and_then(|metadata| metadata.modified()).ok().map(DateTime::from);
+        }
+
+        match ContainerService::load_from_directory(path) {
+            Ok(container) => {
+                row.container_type = Some(container.manifest.container_type.clone());
+                // The store layout is <manifest name>/<version> unless the registry renamed it
+                let problem = if entry.is_none() && container.name() != name {
+                    Some(format!("manifest names it '{}'", container.name()))
+                } else if container.version().to_string() != version {
+                    Some(format!("manifest says version {}", container.version()))
+                } else {
+                    None
+                };
+                if let Some(problem) = problem {
+                    row.status = InstalledStatus::Error;
+                    row.error = Some(problem);
+                }
+            }
+            Err(error) => {
+                row.status = InstalledStatus::Error;
+                row.error = Some(error.to_string());
+            }
+        }
+        if let Some(entry) = entry.filter(|entry| row.status == InstalledStatus::Ok && !entry.missing_dependencies.is_empty()) {
+            row.status = InstalledStatus::Degraded;
+            row.error = Some(format!("requires removed {}", entry.missing_dependencies.join(", ")));
+        }
+        row
+    }
+
+    fn subdirectories(path: &Path) -> Vec<PathBuf> {
+        let mut directories: Vec<PathBuf> = fs::read_dir(path)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        directories.sort();
+        directories
+    }
+
+    /// Held around every read-modify-write of the registry.
+    fn lock(&self) -> ContainerResult<LockFile> {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".lock");
+        LockFile::acquire(&self.path.with_file_name(name), "the container registry")
+    }
+
+    /// Newest first, like `installed_versions`; unparsable versions go last.
+    fn version_order(a: &str, b: &str) -> std::cmp::Ordering {
+        match (Version::new(a), Version::new(b)) {
+            (Ok(a), Ok(b)) => b.cmp(&a),
+            (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+            (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        }
+    }
+
+    /// Adds `entry`, replacing earlier entries with the same name and version or the same path.
+    /// Containers degraded by the removal of `entry`'s name are whole again.
+    pub fn register(&self, entry: RegistryEntry) -> ContainerResult<()> {
+        let _lock = self.lock()?;
+        let mut state = self.load()?;
+        state.containers.retain(|existing| {
+            !(existing.name == entry.name && existing.version == entry.version) && existing.path != entry.path
+        });
+        for existing in &mut state.containers {
+            existing.missing_dependencies.retain(|missing| *missing != entry.name);
+        }
+        state.containers.push(entry);
+        self.save(&state)
+    }
+
+    /// Which registered containers require which, by name. Built from the dependencies
+    /// recorded at registration; only entries registered before those were recorded
+    /// have their manifest read.
+    pub fn dependency_index(&self) -> ContainerResult<DependencyIndex> {
+        let mut index = DependencyIndex::new();
+        for entry in self.entries()? {
+            let dependencies = match entry.dependencies {
+                Some(dependencies) => dependencies,
+                None => match ContainerService::load_from_directory(&entry.path) {
+                    Ok(container) => RegistryEntry::required_dependencies(&container.manifest),
+                    Err(_) => continue,
+                },
+            };
+            index.add(&entry.name, dependencies);
+        }
+        Ok(index)
+    }
+
+    /// Marks every entry named in `dependents` as missing its dependency `removed`.
+    pub fn mark_missing_dependency(&self, dependents: &[String], removed: &str) -> ContainerResult<()> {
+        let _lock = self.lock()?;
+        let mut state = self.load()?;
+        for entry in state.containers.iter_mut().filter(|entry| dependents.contains(&entry.name)) {
+            if !entry.missing_dependencies.iter().any(|missing| missing == removed) {
+                entry.missing_dependencies.push(removed.to_string());
+            }
+        }
+        self.save(&state)
+    }
+
+    /// Drops the entry registered at `path` and returns it.
+    pub fn deregister(&self, path: &Path) -> ContainerResult<Option<RegistryEntry>> {
+        let _lock = self.lock()?;
+        let mut state = self.load()?;
+        let Some(position) = state.containers.iter().position(|entry| entry.path == path) else {
+            return Ok(None);
+        };
+        let entry = state.containers.remove(position);
+        self.save(&state)?;
+        Ok(Some(entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::fixture_container;
+    use tempfile::TempDir;
+
+    fn entry(name: &str, path: &Path, dependencies: Option<&[&str]>) -> RegistryEntry {
+        RegistryEntry {
+            name: name.to_string(),
+            version: Version::new("1.0.0").unwrap(),
+            path: path.to_path_buf(),
+            adopted: true,
+            registered_at: crate::shared::clock::now(),
+            attestation: None,
+            dependencies: dependencies.map(|names| names.iter().map(|name| name.to_string()).collect()),
+            missing_dependencies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn dependency_index_uses_recorded_dependencies_and_reads_older_entries_from_the_manifest() {
+        let dir = TempDir::new().unwrap();
+        let registry = ContainerRegistry::at(dir.path().join("registry.json"));
+        let legacy = fixture_container(dir.path(), "legacy", "1.0.0");
+        let mut manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(legacy.join("manifest.json")).unwrap()).unwrap();
+        manifest["dependencies"] = serde_json::json!([
+            { "name": "lib", "version": "1.0.0" },
+            { "name": "extra", "version": "1.0.0", "optional": true }
+        ]);
+        crate::shared::test_support::write_manifest(&legacy, &manifest);
+        registry.register(entry("app", &dir.path().join("gone"), Some(&["lib"]))).unwrap();
+        registry.register(entry("legacy", &legacy, None)).unwrap();
+
+        let index = registry.dependency_index().unwrap();
+
+        assert_eq!(index.dependents_of("lib"), vec!["app", "legacy"]);
+        assert!(index.dependents_of("extra").is_empty());
+    }
+
+    #[test]
+    fn a_forced_removal_degrades_dependents_until_the_dependency_is_registered_again() {
+        let dir = TempDir::new().unwrap();
+        let registry = ContainerRegistry::at(dir.path().join("registry.json"));
+        let store = dir.path().join("store");
+        let app = fixture_container(dir.path(), "app", "1.0.0");
+        let lib = fixture_container(dir.path(), "lib", "1.0.0");
+        registry.register(entry("app", &app, Some(&["lib"]))).unwrap();
+
+        registry.mark_missing_dependency(&["app".to_string()], "lib").unwrap();
+        registry.mark_missing_dependency(&["app".to_string()], "lib").unwrap();
+
+        let listed = registry.installed(&store).unwrap();
+        assert_eq!(listed[0].status, InstalledStatus::Degraded);
+        assert_eq!(listed[0].error.as_deref(), Some("requires removed lib"));
+        assert_eq!(registry.entries().unwrap()[0].missing_dependencies, vec!["lib"]);
+
+        registry.register(entry("lib", &lib, Some(&[]))).unwrap();
+
+        let listed = registry.installed(&store).unwrap();
+        assert!(listed.iter().all(|row| row.status == InstalledStatus::Ok), "{:?}", listed);
+    }
+}
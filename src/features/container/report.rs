@@ -0,0 +1,300 @@
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// File formats `container validate --output` can write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// The full validation report as JSON
+    Json,
+    /// GitHub Actions workflow commands (::error / ::warning)
+    Github,
+}
+
+/// Validation outcome of one container directory, in a shape CI tooling can consume.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContainerValidationResult {
+    pub path: PathBuf,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub valid: bool,
+    pub errors: Vec<String>,
+    /// Stable code of each entry in `errors`, in the same order
+    pub error_codes: Vec<String>,
+    pub warnings: Vec<String>,
+    pub duration_ms: u64,
+    #[serde(skip)]
+    started: Option<Instant>,
+}
+
+impl ContainerValidationResult {
+    pub fn start(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            name: None,
+            version: None,
+            valid: false,
+            errors: Vec::new(),
+            error_codes: Vec::new(),
+            warnings: Vec::new(),
+            duration_ms: 0,
+            started: Some(Instant::now()),
+        }
+    }
+
+    /// Records an error together with its stable code (see `ContainerError::code`).
+    pub fn push_error(&mut self, code: &str, message: String) {
+        self.errors.push(message);
+        self.error_codes.push(code.to_string());
+    }
+
+    /// Fixes the verdict and duration once every check has run.
+    pub fn finish(mut self) -> Self {
+        self.valid = self.errors.is_empty();
+        if let Some(started) = self.started.take() {
+            self.duration_ms = started.elapsed().as_millis() as u64;
+        }
+        self
+    }
+
+    /// `name@version` when the manifest could be read, otherwise the path.
+    pub fn label(&self) -> String {
+        match (&self.name, &self.version) {
+            (Some(name), Some(version)) => format!("{}@{}", name, version),
+            _ => self.path.display().to_string(),
+        }
+    }
+}
+
+/// Results of one `container validate` invocation over one or more containers.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationReport {
+    pub containers: Vec<ContainerValidationResult>,
+}
+
+impl ValidationReport {
+    pub fn passed(&self) -> usize {
+        self.containers.iter().filter(|container| container.valid).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.containers.len() - self.passed()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.failed() == 0
+    }
+
+    /// One line for stdout when the details go to files.
+    pub fn summary(&self) -> String {
+        let marker = if self.is_valid() { "✅" } else { "❌" };
+        format!(
+            "{} {}/{} containers valid, {} failed",
+            marker,
+            self.passed(),
+            self.containers.len(),
+            self.failed()
+        )
+    }
+
+    pub fn render(&self, format: ReportFormat) -> serde_json::Result<String> {
+        match format {
+            ReportFormat::Json => serde_json::to_string_pretty(self),
+            ReportFormat::Github => Ok(self.to_github_annotations()),
+        }
+    }
+
+    /// GitHub Actions annotations attached to each container's manifest.
+    pub fn to_github_annotations(&self) -> String {
+        let mut out = String::new();
+        for container in &self.containers {
+            let file = github_property(&container.path.join("manifest.json").display().to_string());
+            let title = github_property(&format!("wrappy validate {}", container.label()));
+            for (error, code) in container.errors.iter().zip(&container.error_codes) {
+                let error_title = github_property(&format!("wrappy validate {} [{}]", container.label(), code));
+                let _ = writeln!(out, "::error file={},title={}::{}", file, error_title, github_data(error));
+            }
+            for warning in &container.warnings {
+                let _ = writeln!(out, "::warning file={},title={}::{}", file, title, github_data(warning));
+            }
+        }
+        out
+    }
+
+    /// JUnit XML with one test case per container; errors become the failure
+    /// and warnings go to system-out so CI UIs still show them.
+    pub fn to_junit(&self) -> String {
+        // Summed in milliseconds: an empty f64 sum is -0.0 and would print as "-0.000"
+        let total_ms: u64 = self.containers.iter().map(|container| container.duration_ms).sum();
+        let total_seconds = total_ms as f64 / 1000.0;
+        let counts = format!(
+            "tests=\"{}\" failures=\"{}\" errors=\"0\" time=\"{:.3}\"",
+            self.containers.len(),
+            self.failed(),
+            total_seconds
+        );
+
+        let mut out = String::new();
+        let _ = writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+        let _ = writeln!(out, "<testsuites name=\"wrappy validate\" {}>", counts);
+        let _ = writeln!(out, "  <testsuite name=\"wrappy validate\" {}>", counts);
+
+        for container in &self.containers {
+            let _ = write!(
+                out,
+                "    <testcase classname=\"wrappy.validate\" name=\"{}\" file=\"{}\" time=\"{:.3}\"",
+                xml_escape(&container.label()),
+                xml_escape(&container.path.display().to_string()),
+                container.duration_ms as f64 / 1000.0
+            );
+
+            if container.errors.is_empty() && container.warnings.is_empty() {
+                let _ = writeln!(out, "/>");
+                continue;
+            }
+            let _ = writeln!(out, ">");
+
+            if let Some(first) = container.errors.first() {
+                let _ = writeln!(
+                    out,
+                    "      <failure type=\"ValidationError\" message=\"{}\">{}</failure>",
+                    xml_escape(first),
+                    xml_escape(&container.errors.join("\n"))
+                );
+            }
+            if !container.warnings.is_empty() {
+                let _ = writeln!(
+                    out,
+                    "      <system-out>{}</system-out>",
+                    xml_escape(&container.warnings.join("\n"))
+                );
+            }
+            let _ = writeln!(out, "    </testcase>");
+        }
+
+        let _ = writeln!(out, "  </testsuite>");
+        let _ = writeln!(out, "</testsuites>");
+        out
+    }
+}
+
+/// Escapes text for XML attributes and content; control characters XML 1.0 cannot represent are dropped.
+pub fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes the message part of a GitHub workflow command.
+fn github_data(value: &str) -> String {
+    value.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escapes a `key=value` property of a GitHub workflow command.
+fn github_property(value: &str) -> String {
+    github_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, errors: &[(&str, &str)], warnings: &[&str], duration_ms: u64) -> ContainerValidationResult {
+        let mut result = ContainerValidationResult::start(Path::new(&format!("/repo/{}", name)));
+        result.name = Some(name.to_string());
+        result.version = Some("1.0.0".to_string());
+        for (code, message) in errors {
+            result.push_error(code, message.to_string());
+        }
+        result.warnings = warnings.iter().map(|warning| warning.to_string()).collect();
+        let mut result = result.finish();
+        result.duration_ms = duration_ms;
+        result
+    }
+
+    fn report() -> ValidationReport {
+        ValidationReport {
+            containers: vec![
+                result("ok", &[], &[], 250),
+                result("bad", &[("E_MANIFEST", "missing <name> & \"version\""), ("E_SCRIPT", "second")], &[], 1000),
+                result("warned", &[], &["it's\u{1b}[31m odd"], 5),
+            ],
+        }
+    }
+
+    #[test]
+    fn junit_has_one_test_case_per_container_with_failures_and_warnings() {
+        let expected = concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<testsuites name=\"wrappy validate\" tests=\"3\" failures=\"1\" errors=\"0\" time=\"1.255\">\n",
+            "  <testsuite name=\"wrappy validate\" tests=\"3\" failures=\"1\" errors=\"0\" time=\"1.255\">\n",
+            "    <testcase classname=\"wrappy.validate\" name=\"ok@1.0.0\" file=\"/repo/ok\" time=\"0.250\"/>\n",
+            "    <testcase classname=\"wrappy.validate\" name=\"bad@1.0.0\" file=\"/repo/bad\" time=\"1.000\">\n",
+            "      <failure type=\"ValidationError\" message=\"missing &lt;name&gt; &amp; &quot;version&quot;\">",
+            "missing &lt;name&gt; &amp; &quot;version&quot;\nsecond</failure>\n",
+            "    </testcase>\n",
+            "    <testcase classname=\"wrappy.validate\" name=\"warned@1.0.0\" file=\"/repo/warned\" time=\"0.005\">\n",
+            "      <system-out>it&apos;s[31m odd</system-out>\n",
+            "    </testcase>\n",
+            "  </testsuite>\n",
+            "</testsuites>\n",
+        );
+
+        assert_eq!(report().to_junit(), expected);
+    }
+
+    #[test]
+    fn an_empty_report_is_a_valid_empty_suite() {
+        let junit = ValidationReport::default().to_junit();
+
+        assert!(junit.contains("<testsuite name=\"wrappy validate\" tests=\"0\" failures=\"0\" errors=\"0\" time=\"0.000\">\n  </testsuite>"));
+    }
+
+    #[test]
+    fn xml_escape_covers_markup_and_drops_invalid_control_characters() {
+        assert_eq!(xml_escape("<a href=\"x\">'&'</a>"), "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;");
+        assert_eq!(xml_escape("tab\tnew\nline\r"), "tab\tnew\nline\r");
+        assert_eq!(xml_escape("bell\u{7}null\u{0}esc\u{1b}"), "bellnullesc");
+        assert_eq!(xml_escape("ünïcödé ✅"), "ünïcödé ✅");
+    }
+
+    #[test]
+    fn github_annotations_escape_data_and_properties() {
+        let mut report = report();
+        report.containers[1].errors[1] = "100% broken\nnext line".to_string();
+        report.containers[1].path = PathBuf::from("/repo/a,b:c");
+
+        let annotations = report.to_github_annotations();
+
+        let lines: Vec<&str> = annotations.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            "::error file=/repo/a%2Cb%3Ac/manifest.json,title=wrappy validate bad@1.0.0 [E_SCRIPT]::100%25 broken%0Anext line"
+        );
+        assert!(lines[2].starts_with("::warning file=/repo/warned/manifest.json,title=wrappy validate warned@1.0.0::"));
+    }
+
+    #[test]
+    fn summary_and_json_describe_every_container() {
+        let report = report();
+
+        assert_eq!(report.summary(), "❌ 2/3 containers valid, 1 failed");
+        let json: serde_json::Value = serde_json::from_str(&report.render(ReportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["containers"][1]["error_codes"], serde_json::json!(["E_MANIFEST", "E_SCRIPT"]));
+        assert_eq!(json["containers"][0]["valid"], true);
+        assert!(json["containers"][0].get("started").is_none());
+    }
+}
@@ -0,0 +1,417 @@
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+use crate::features::container::{Container, ContainerRegistry, ContainerService, RegistryEntry};
+use crate::features::manifest::{Dependency, ManifestCache};
+use crate::features::{Version, VersionReq};
+use crate::shared::config::Config;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::paths::WrappyPaths;
+use crate::shared::prompt;
+
+/// How many names a "did you mean" hint lists at most.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Names further than this many edits from the input are not suggested.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// One installed container that matches a requested name.
+#[derive(Debug, Clone)]
+pub struct ResolutionCandidate {
+    pub name: String,
+    pub version: Version,
+    pub path: PathBuf,
+    /// Registered with `container adopt` rather than kept in the store
+    pub adopted: bool,
+}
+
+impl ResolutionCandidate {
+    /// Exact `name@version` syntax that selects this candidate unambiguously.
+    pub fn selector(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+/// Outcome of resolving user input to a container, shared by every command
+/// so ambiguity is reported and disambiguated the same way everywhere.
+#[derive(Debug, Clone)]
+pub enum Resolution {
+    Path(PathBuf),
+    Unique(ResolutionCandidate),
+    Ambiguous {
+        name: String,
+        candidates: Vec<ResolutionCandidate>,
+    },
+    NotFound {
+        name: String,
+    },
+}
+
+/// Resolves container names and paths against the filesystem, the store and the registry.
+pub struct ContainerResolver {
+    store_dir: PathBuf,
+    registered: Vec<RegistryEntry>,
+}
+
+impl ContainerResolver {
+    pub fn new() -> ContainerResult<Self> {
+        let paths = WrappyPaths::new()?;
+        let config = Config::load_or_default(&paths)?;
+        let registered = ContainerRegistry::new(&paths).entries()?;
+        Ok(Self::with_store(config.store_dir(&paths)).with_registered(registered))
+    }
+
+    /// Resolves against `store_dir` alone, without registered containers.
+    pub fn with_store(store_dir: PathBuf) -> Self {
+        Self {
+            store_dir,
+            registered: Vec::new(),
+        }
+    }
+
+    /// Adds registry entries: adopted directories and store installs under another name.
+    pub fn with_registered(mut self, registered: Vec<RegistryEntry>) -> Self {
+        self.registered = registered;
+        self
+    }
+
+    pub fn store_dir(&self) -> &Path {
+        &self.store_dir
+    }
+
+    /// Resolves `input` without side effects.
+    /// Existing directories win over names; `name@version` pins a single installed version.
+    pub fn resolve(&self, input: &str) -> Resolution {
+        let path = PathBuf::from(input);
+        if path.is_dir() {
+            return Resolution::Path(path);
+        }
+
+        let (name, version) = match input.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (input, None),
+        };
+
+        let mut candidates: Vec<ResolutionCandidate> = self
+            .installed_versions(name)
+            .into_iter()
+            .filter(|candidate| version.is_none_or(|v| Self::version_matches(&candidate.version, v)))
+            .collect();
+
+        match candidates.len() {
+            0 => Resolution::NotFound {
+                name: input.to_string(),
+            },
+            1 => Resolution::Unique(candidates.remove(0)),
+            _ => Resolution::Ambiguous {
+                name: name.to_string(),
+                candidates,
+            },
+        }
+    }
+
+    /// Resolves `input` to a loaded container, prompting on a terminal when several match
+    /// and otherwise failing with the candidates and the syntax that picks one.
+    pub fn resolve_container(&self, input: &str) -> ContainerResult<Container> {
+        match self.resolve(input) {
+            Resolution::Path(path) => ContainerService::load_from_directory(path),
+            Resolution::Unique(candidate) => ContainerService::load_from_directory(&candidate.path),
+            Resolution::Ambiguous { name, candidates } => {
+                let candidate = Self::choose_candidate(&name, candidates, std::io::stdin().is_terminal())?;
+                ContainerService::load_from_directory(&candidate.path)
+            }
+            Resolution::NotFound { name } => Err(ContainerError::ContainerNotFound {
+                suggestions: self.similar_names(&name),
+                name,
+            }),
+        }
+    }
+
+    /// Installed names close to the name in `input`, for "did you mean" hints: names it is
+    /// a prefix of or that are a prefix of it, then names a typo or two away. Paths get none.
+    pub fn similar_names(&self, input: &str) -> Vec<String> {
+        let name = input.split_once('@').map_or(input, |(name, _)| name);
+        if name.is_empty() || name.contains('/') {
+            return Vec::new();
+        }
+        let name = name.to_lowercase();
+        let max_distance = (name.chars().count() / 3).clamp(1, MAX_SUGGESTION_DISTANCE);
+
+        let mut scored: Vec<(usize, String)> = self
+            .installed_names()
+            .into_iter()
+            .filter_map(|candidate| {
+                let lowercase = candidate.to_lowercase();
+                let score = if lowercase.starts_with(&name) || name.starts_with(&lowercase) {
+                    0
+                } else {
+                    let distance = edit_distance(&name, &lowercase);
+                    (distance <= max_distance).then_some(distance)?
+                };
+                Some((score, candidate))
+            })
+            .collect();
+        scored.sort();
+        scored.into_iter().take(MAX_SUGGESTIONS).map(|(_, candidate)| candidate).collect()
+    }
+
+    /// True when `path` is an installed container, in the store or adopted, rather than a source directory.
+    pub fn is_installed(&self, path: &Path) -> bool {
+        self.is_in_store(path) || self.adopted_entry(path).is_some()
+    }
+
+    /// True when `path` lies inside the store.
+    pub fn is_in_store(&self, path: &Path) -> bool {
+        match (path.canonicalize(), self.store_dir.canonicalize()) {
+            (Ok(path), Ok(store_dir)) => path.starts_with(store_dir),
+            _ => false,
+        }
+    }
+
+    /// Registry entry of the adopted container at `path`.
+    pub fn adopted_entry(&self, path: &Path) -> Option<&RegistryEntry> {
+        self.registered_entry(path).filter(|entry| entry.adopted)
+    }
+
+    /// Registry entry of the container at `path`, installed or adopted.
+    pub fn registered_entry(&self, path: &Path) -> Option<&RegistryEntry> {
+        let path = path.canonicalize().ok()?;
+        self.registered.iter().find(|entry| entry.path == path)
+    }
+
+    /// Names of all containers present in the store or registered, sorted.
+    pub fn installed_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(&self.store_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| !name.starts_with('.'))
+            .chain(self.registered.iter().map(|entry| entry.name.clone()))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Lists every installed version of `name`, newest first. Registered directories that
+    /// disappeared are left out; `wrappy setup` reports them.
+    pub fn installed_versions(&self, name: &str) -> Vec<ResolutionCandidate> {
+        let mut cache = ManifestCache::open();
+        let mut candidates: Vec<ResolutionCandidate> = fs::read_dir(self.store_dir.join(name))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| Self::candidate_from_dir(&mut cache, &entry.path()))
+            .filter(|candidate| candidate.name == name)
+            .collect();
+        // Store installs under the manifest name were found above; `--name` ones only through the registry
+        let registered: Vec<ResolutionCandidate> = self
+            .registered
+            .iter()
+            .filter(|entry| entry.name == name)
+            .filter(|entry| {
+                !candidates
+                    .iter()
+                    .any(|candidate| candidate.path.canonicalize().is_ok_and(|path| path == entry.path))
+            })
+            .filter_map(|entry| {
+                let mut candidate = Self::candidate_from_dir(&mut cache, &entry.path)?;
+                candidate.name = entry.name.clone();
+                candidate.adopted = entry.adopted;
+                Some(candidate)
+            })
+            .collect();
+        candidates.extend(registered);
+        // A cache that cannot be written only costs speed on the next run
+        let _ = cache.save();
+
+        candidates.sort_by(|a, b| b.version.cmp(&a.version));
+        candidates
+    }
+
+    /// Picks the newest installed container satisfying a manifest dependency.
+    /// A bare dependency version is read as a caret requirement, like everywhere else.
+    pub fn resolve_dependency(&self, dependency: &Dependency) -> Option<ResolutionCandidate> {
+        let requirement: VersionReq = dependency.version.parse().ok()?;
+        self.installed_versions(&dependency.name)
+            .into_iter()
+            .find(|candidate| requirement.matches(&candidate.version))
+    }
+
+    fn candidate_from_dir(cache: &mut ManifestCache, path: &Path) -> Option<ResolutionCandidate> {
+        let manifest = cache.get_or_load(&path.join("manifest.json")).ok()?;
+        Some(ResolutionCandidate {
+            name: manifest.name,
+            version: manifest.version,
+            path: path.to_path_buf(),
+            adopted: false,
+        })
+    }
+
+    /// `name@v1.2.3` pasted from a git tag selects version 1.2.3
+    fn version_matches(version: &Version, input: &str) -> bool {
+        match Version::parse_lenient(input) {
+            Ok(requested) => &requested == version,
+            Err(_) => version.as_str() == input,
+        }
+    }
+
+    /// Asks which candidate to use when `interactive`, otherwise fails with their selectors.
+    fn choose_candidate(name: &str, candidates: Vec<ResolutionCandidate>, interactive: bool) -> ContainerResult<ResolutionCandidate> {
+        let labels: Vec<String> = candidates
+            .iter()
+            .map(|candidate| format!("{} ({})", candidate.selector(), candidate.path.display()))
+            .collect();
+
+        if interactive {
+            let question = format!("Several installed containers match '{}':", name);
+            if let Some(index) = prompt::select(&question, &labels) {
+                return Ok(candidates[index].clone());
+            }
+        }
+
+        Err(ContainerError::AmbiguousContainer {
+            name: name.to_string(),
+            candidates: candidates.iter().map(ResolutionCandidate::selector).collect(),
+        })
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::{fixture_container, fixture_container_at, fixture_manifest, TempHome};
+    use assert_matches::assert_matches;
+
+    /// A store with node 18.0.0 and 20.1.0 and python 3.12.0, laid out as `<name>/<version>`.
+    fn store(home: &TempHome) -> PathBuf {
+        let store = home.path().join("store");
+        for (name, version) in [("node", "18.0.0"), ("node", "20.1.0"), ("python", "3.12.0")] {
+            fixture_container_at(&store.join(name).join(version), fixture_manifest(name, version));
+        }
+        store
+    }
+
+    fn adopted(name: &str, path: &Path) -> RegistryEntry {
+        let container = ContainerService::load_from_directory(path).unwrap();
+        RegistryEntry {
+            name: name.to_string(),
+            version: container.manifest.version,
+            path: path.canonicalize().unwrap(),
+            adopted: true,
+            registered_at: crate::shared::clock::now(),
+            attestation: None,
+            dependencies: None,
+            missing_dependencies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn a_name_with_one_installed_version_is_unique() {
+        let home = TempHome::enter();
+        let resolver = ContainerResolver::with_store(store(&home));
+
+        assert_matches!(resolver.resolve("python"), Resolution::Unique(candidate) if candidate.version.as_str() == "3.12.0");
+    }
+
+    #[test]
+    fn a_name_with_several_versions_is_ambiguous_newest_first() {
+        let home = TempHome::enter();
+        let resolver = ContainerResolver::with_store(store(&home));
+
+        let Resolution::Ambiguous { name, candidates } = resolver.resolve("node") else {
+            panic!("expected an ambiguity");
+        };
+
+        assert_eq!(name, "node");
+        let selectors: Vec<String> = candidates.iter().map(ResolutionCandidate::selector).collect();
+        assert_eq!(selectors, vec!["node@20.1.0", "node@18.0.0"]);
+    }
+
+    #[test]
+    fn name_at_version_pins_one_also_with_a_v_prefix() {
+        let home = TempHome::enter();
+        let resolver = ContainerResolver::with_store(store(&home));
+
+        assert_matches!(resolver.resolve("node@18.0.0"), Resolution::Unique(candidate) if candidate.version.as_str() == "18.0.0");
+        assert_matches!(resolver.resolve("node@v20.1.0"), Resolution::Unique(candidate) if candidate.version.as_str() == "20.1.0");
+        assert_matches!(resolver.resolve("node@19.0.0"), Resolution::NotFound { name } if name == "node@19.0.0");
+    }
+
+    #[test]
+    fn an_existing_directory_wins_over_an_installed_name() {
+        let home = TempHome::enter();
+        let resolver = ContainerResolver::with_store(store(&home));
+        let local = fixture_container(home.path(), "node", "21.0.0");
+
+        assert_matches!(resolver.resolve(local.to_str().unwrap()), Resolution::Path(path) if path == local);
+    }
+
+    #[test]
+    fn without_a_terminal_ambiguity_fails_with_the_exact_selectors() {
+        let home = TempHome::enter();
+        let resolver = ContainerResolver::with_store(store(&home));
+        let Resolution::Ambiguous { name, candidates } = resolver.resolve("node") else {
+            panic!("expected an ambiguity");
+        };
+
+        let error = ContainerResolver::choose_candidate(&name, candidates, false).unwrap_err();
+
+        assert_matches!(error, ContainerError::AmbiguousContainer { name, candidates }
+            if name == "node" && candidates == vec!["node@20.1.0", "node@18.0.0"]);
+    }
+
+    #[test]
+    fn adopted_containers_resolve_under_their_registered_name() {
+        let home = TempHome::enter();
+        let checkout = fixture_container(home.path(), "node", "22.0.0");
+        let resolver = ContainerResolver::with_store(store(&home)).with_registered(vec![adopted("node-dev", &checkout)]);
+
+        assert_matches!(resolver.resolve("node-dev"), Resolution::Unique(candidate) if candidate.adopted && candidate.name == "node-dev");
+        assert_eq!(resolver.installed_names(), vec!["node", "node-dev", "python"]);
+    }
+
+    #[test]
+    fn unknown_names_fail_with_close_suggestions() {
+        let home = TempHome::enter();
+        let resolver = ContainerResolver::with_store(store(&home));
+
+        let error = resolver.resolve_container("nod").unwrap_err();
+
+        assert_matches!(error, ContainerError::ContainerNotFound { name, suggestions }
+            if name == "nod" && suggestions == vec!["node"]);
+        assert_eq!(resolver.similar_names("pyhton"), vec!["python"]);
+        assert!(resolver.similar_names("./node").is_empty());
+    }
+
+    #[test]
+    fn dependencies_resolve_to_the_newest_matching_version() {
+        let home = TempHome::enter();
+        let resolver = ContainerResolver::with_store(store(&home));
+        let dependency = |version: &str| -> Dependency {
+            serde_json::from_value(serde_json::json!({ "name": "node", "version": version })).unwrap()
+        };
+
+        assert_eq!(resolver.resolve_dependency(&dependency("18")).unwrap().version.as_str(), "18.0.0");
+        assert_eq!(resolver.resolve_dependency(&dependency(">=18")).unwrap().version.as_str(), "20.1.0");
+        assert!(resolver.resolve_dependency(&dependency("19")).is_none());
+    }
+}
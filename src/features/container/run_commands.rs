@@ -0,0 +1,584 @@
+use std::env;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use crate::features::container::{
+    executor, ContainerHandler, ContainerResolver, ContainerService, ContainerStatus, DependencyRun, EnvironmentConfig,
+    EnvironmentEntry, EnvironmentSource, Executor, OutputMode, PlanFormat, RunArgs, RunContext, RunStop, RunningScript,
+    ServiceRuntime, ServiceStart, CONTAINER_PATH_VAR, ENVIRONMENT_FILE,
+};
+use crate::shared::error::ContainerError;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::redact;
+use crate::shared::watcher::{WatchEvent, WatchOptions, Watcher, WATCH_POLL_INTERVAL};
+
+/// How many of the errors kept in the runtime state `container status` shows.
+const STATUS_RECENT_ERRORS: usize = 5;
+
+/// Flags of `container run` that shape how the script is executed
+struct RunOptions {
+    watch: Vec<PathBuf>,
+    queue: bool,
+    inherit_env: bool,
+    show_env: bool,
+    /// Print the values --show-env and --dry-run would mask
+    show_secrets: bool,
+    /// Print the execution plan in this format instead of running
+    dry_run: Option<PlanFormat>,
+    allow_unsandboxed_mounts: bool,
+    output: OutputMode,
+}
+
+impl ContainerHandler {
+    /// Handles the run command execution
+    pub fn handle_run_command(args: RunArgs) -> i32 {
+        let RunArgs {
+            container,
+            script,
+            dep,
+            watch,
+            restart: _,
+            queue,
+            no_inherit_env,
+            show_env,
+            dry_run,
+            allow_unsandboxed_mounts,
+            format,
+            show_secrets,
+            output,
+            args,
+        } = args;
+        let options = RunOptions {
+            watch,
+            queue,
+            inherit_env: !no_inherit_env,
+            show_env,
+            show_secrets,
+            dry_run: dry_run.then_some(format),
+            allow_unsandboxed_mounts,
+            output,
+        };
+        Self::run_script(container, script, dep, options, args)
+    }
+
+    /// Runs the script of the container, or of the dependency `dep` names, unless asked to only show its environment or plan
+    fn run_script(input: String, script: String, dep: Option<String>, options: RunOptions, args: Vec<String>) -> i32 {
+        let resolved = ContainerResolver::new().and_then(|resolver| {
+            let container = resolver.resolve_container(&input)?;
+            match &dep {
+                Some(target) => {
+                    let (dependency, dep_script) = DependencyRun::parse_target(target);
+                    let callers = vec![container.name().to_string()];
+                    let dependency = DependencyRun::resolve(&container, dependency, &callers, &resolver)?;
+                    Ok((dependency, dep_script.to_string(), callers))
+                }
+                None => Ok((container, script, Vec::new())),
+            }
+        });
+        let (container, script, callers) = match resolved {
+            Ok(resolved) => resolved,
+            Err(error) => {
+                eprintln!("❌ {}", error);
+                return 1;
+            }
+        };
+
+        let mut executor = Executor::new(container)
+            .callers(callers)
+            .inherit_env(options.inherit_env)
+            .allow_unsandboxed_mounts(options.allow_unsandboxed_mounts)
+            .output(options.output.clone());
+        if options.show_env {
+            return Self::print_environment(&executor, options.show_secrets);
+        }
+        if let Some(format) = options.dry_run {
+            return Self::print_execution_plan(&executor, &script, &args, format, options.show_secrets);
+        }
+
+        executor::install_interrupt_handler();
+        let result = if options.watch.is_empty() {
+            let quiet = options.output == OutputMode::Quiet;
+            executor
+                .run(&script, &args)
+                .inspect(|&exit_code| if quiet { Self::print_run_status(&script, exit_code) })
+        } else {
+            Self::run_watching(&mut executor, &script, &args, options.watch, options.queue)
+        };
+
+        match result {
+            Ok(exit_code) => exit_code,
+            Err(error) => {
+                eprintln!("❌ Failed to run script '{}': {}", script, error);
+                1
+            }
+        }
+    }
+
+    /// Handles `container run-dep`: runs a script of a dependency of the container whose script
+    /// called it, with the dependency's own environment, and passes its exit code on
+    pub fn handle_run_dep_command(target: String, args: Vec<String>) -> i32 {
+        let Some(context) = RunContext::from_env() else {
+            eprintln!("❌ `run-dep` only works inside a script started by wrappy ({} is not set)", CONTAINER_PATH_VAR);
+            return 2;
+        };
+        let (dependency, script) = DependencyRun::parse_target(&target);
+        let resolved = ContainerService::load_from_directory(&context.container_path).and_then(|caller| {
+            // The caller's own variables were exported into this process; the dependency gets its own
+            let config = EnvironmentConfig::load(&caller.path)?;
+            for key in caller.manifest.environment.keys().chain(config.variables.keys()) {
+                env::remove_var(key);
+            }
+            DependencyRun::resolve(&caller, dependency, &context.chain, &ContainerResolver::new()?)
+        });
+        let container = match resolved {
+            Ok(container) => container,
+            Err(error) => {
+                eprintln!("❌ Failed to run '{}': {}", target, error);
+                return 1;
+            }
+        };
+
+        let mut executor = Executor::new(container).callers(context.chain);
+        executor::install_interrupt_handler();
+        match executor.run(script, &args) {
+            Ok(exit_code) => exit_code,
+            Err(error) => {
+                eprintln!("❌ Failed to run script '{}' of '{}': {}", script, dependency, error);
+                1
+            }
+        }
+    }
+
+    /// Handles `container exec`: runs an arbitrary command with the container's environment
+    pub fn handle_exec_command(input: String, inherit_env: bool, command: Vec<String>) -> i32 {
+        let container = match ContainerResolver::new().and_then(|resolver| resolver.resolve_container(&input)) {
+            Ok(container) => container,
+            Err(error) => {
+                eprintln!("❌ {}", error);
+                return 1;
+            }
+        };
+        let Some((program, args)) = command.split_first() else {
+            eprintln!("❌ No command given; pass it after --");
+            return 1;
+        };
+
+        let mut executor = Executor::new(container).inherit_env(inherit_env);
+        executor::install_interrupt_handler();
+        match executor.exec(program, args) {
+            Ok(exit_code) => exit_code,
+            Err(error) => {
+                eprintln!("❌ Failed to run '{}' in '{}': {}", program, input, error);
+                1
+            }
+        }
+    }
+
+    /// Prints the run environment; secret-looking values are masked unless `show_secrets` is set
+    fn print_environment(executor: &Executor, show_secrets: bool) -> i32 {
+        let environment = match executor.environment() {
+            Ok(environment) => environment,
+            Err(error) => {
+                eprintln!("❌ {}", error);
+                return 1;
+            }
+        };
+        println!("🌱 Environment for container '{}':", executor.container().name());
+        Self::print_environment_entries(&environment, "  ", show_secrets);
+        0
+    }
+
+    fn print_environment_entries(environment: &[EnvironmentEntry], indent: &str, show_secrets: bool) {
+        for entry in environment {
+            let masked = !show_secrets && redact::is_secret_key(&entry.key);
+            let value = if masked { redact::REDACTED } else { entry.value.as_str() };
+            let source = match &entry.source {
+                EnvironmentSource::Inherited => "inherited from host".to_string(),
+                EnvironmentSource::Passthrough { pattern } => format!("passed through by '{}'", pattern),
+                EnvironmentSource::Manifest => "set by manifest".to_string(),
+                EnvironmentSource::ConfigFile => format!("set in {}", ENVIRONMENT_FILE),
+                EnvironmentSource::Wrappy => "set by wrappy for the run".to_string(),
+            };
+            println!("{}{}={}  ({})", indent, entry.key, value, source);
+        }
+    }
+
+    /// Prints the plan of a run without executing it; secrets are masked in both formats
+    /// unless `show_secrets` is set
+    fn print_execution_plan(executor: &Executor, script: &str, args: &[String], format: PlanFormat, show_secrets: bool) -> i32 {
+        let plan = match executor.plan(script, args) {
+            Ok(plan) if show_secrets => plan,
+            Ok(plan) => plan.redacted(),
+            Err(error) => {
+                eprintln!("❌ Failed to plan script '{}': {}", script, error);
+                return 1;
+            }
+        };
+
+        match format {
+            PlanFormat::Json => match serde_json::to_string_pretty(&plan) {
+                Ok(json) => println!("{}", json),
+                Err(error) => {
+                    eprintln!("❌ Failed to serialize execution plan: {}", error);
+                    return 1;
+                }
+            },
+            PlanFormat::Text => {
+                println!("📋 Execution plan for {}@{} script '{}':", plan.container, plan.version, plan.script);
+                println!("  Script: {}", plan.script_path.display());
+                match &plan.interpreter {
+                    Some(interpreter) => println!("  Interpreter: {}", interpreter),
+                    None => println!("  Interpreter: none (executed directly)"),
+                }
+                if !plan.args.is_empty() {
+                    println!("  Arguments: {}", plan.args.join(" "));
+                }
+                println!("  Working directory: {}", plan.working_directory.display());
+                match &plan.isolation {
+                    Some(isolation) if isolation.enabled => println!(
+                        "  Isolation: declared (network: {}, filesystem: {}), not enforced by this wrappy",
+                        isolation.network, isolation.filesystem
+                    ),
+                    _ => println!("  Isolation: none"),
+                }
+                if !plan.network_allowlist.is_empty() {
+                    println!(
+                        "  Allowed hosts (not enforced, runs are not sandboxed): {}",
+                        plan.network_allowlist.join(", ")
+                    );
+                }
+                if !plan.mounts.is_empty() {
+                    println!("  Mounts (not applied, runs are not sandboxed):");
+                    for mount in &plan.mounts {
+                        println!("    {}", mount);
+                    }
+                }
+                let inherit = if plan.inherit_host_environment { "host environment inherited" } else { "passthrough only" };
+                println!("  Environment ({}):", inherit);
+                Self::print_environment_entries(&plan.environment, "    ", show_secrets);
+            }
+        }
+        0
+    }
+
+    /// Runs the script, then again after every debounced change until interrupted.
+    /// A change during a run either restarts it or queues one more run.
+    fn run_watching(
+        executor: &mut Executor,
+        script: &str,
+        args: &[String],
+        watch: Vec<PathBuf>,
+        queue: bool,
+    ) -> Result<i32, ContainerError> {
+        let content_path = executor.container().content_path();
+        let roots: Vec<PathBuf> = watch.into_iter().map(|path| content_path.join(path)).collect();
+        let mut watcher = Watcher::new(roots, WatchOptions::default());
+
+        println!("👀 Watching:");
+        for root in watcher.roots() {
+            println!("   {}", root.display());
+        }
+
+        let mut running = Self::start_watched_run(executor, script, args);
+        let mut queued: Option<WatchEvent> = None;
+
+        loop {
+            if executor::interrupted() {
+                if let Some(current) = running.take() {
+                    executor.stop(current)?;
+                }
+                println!("\n👋 Stopped watching");
+                return Ok(executor::INTERRUPTED_EXIT_CODE);
+            }
+
+            if let Some(current) = running.as_mut() {
+                if let Some(exit_code) = executor.try_finish(current)? {
+                    Self::print_run_finished(script, exit_code);
+                    running = None;
+                }
+            }
+
+            if running.is_none() {
+                if let Some(trigger) = queued.take() {
+                    Self::print_trigger_separator(&trigger, script);
+                    running = Self::start_watched_run(executor, script, args);
+                }
+            }
+
+            if let Some(trigger) = watcher.next_event(executor::interrupted) {
+                match running.take() {
+                    Some(current) if queue => {
+                        running = Some(current);
+                        queued = Some(trigger);
+                    }
+                    current => {
+                        if let Some(current) = current {
+                            executor.stop(current)?;
+                        }
+                        Self::print_trigger_separator(&trigger, script);
+                        running = Self::start_watched_run(executor, script, args);
+                    }
+                }
+            }
+
+            thread::sleep(WATCH_POLL_INTERVAL);
+        }
+    }
+
+    /// Start failures (e.g. a script mid-edit) are reported and watching continues
+    fn start_watched_run(executor: &mut Executor, script: &str, args: &[String]) -> Option<RunningScript> {
+        match executor.spawn(script, args) {
+            Ok(running) => Some(running),
+            Err(error) => {
+                eprintln!("❌ Failed to start script '{}': {}", script, error);
+                None
+            }
+        }
+    }
+
+    fn print_trigger_separator(trigger: &WatchEvent, script: &str) {
+        println!("\n━━━━━━━━ 🔁 {} changed, running '{}' ━━━━━━━━", trigger, script);
+    }
+
+    fn print_run_finished(script: &str, exit_code: i32) {
+        Self::print_run_status(script, exit_code);
+        println!("👀 Waiting for changes...");
+    }
+
+    fn print_run_status(script: &str, exit_code: i32) {
+        if exit_code == 0 {
+            println!("✅ Script '{}' finished", script);
+        } else {
+            println!("❌ Script '{}' exited with code {}", script, exit_code);
+        }
+    }
+
+    /// Handles the start command execution
+    pub fn handle_start_command(input: String, script: String, idle_timeout: u64, quiet: bool) -> i32 {
+        let result = WrappyPaths::new().and_then(|paths| {
+            let container = ContainerResolver::new()?.resolve_container(&input)?;
+            let runtime = ServiceRuntime::new(&paths, container.name());
+            let outcome = runtime.start(&Executor::new(container), &script, idle_timeout)?;
+            Ok((runtime, outcome))
+        });
+
+        match result {
+            Ok((runtime, outcome)) => {
+                if !quiet {
+                    match outcome {
+                        ServiceStart::Started(pid) => println!(
+                            "🚀 Started service of '{}' (pid {}, log: {})",
+                            runtime.container_name(),
+                            pid,
+                            runtime.log_file().display()
+                        ),
+                        ServiceStart::AlreadyRunning(pid) => {
+                            println!("ℹ️  Service of '{}' is already running (pid {})", runtime.container_name(), pid)
+                        }
+                    }
+                }
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to start service: {}", error);
+                1
+            }
+        }
+    }
+
+    /// Handles the stop command execution: the recorded run first, then the service
+    pub fn handle_stop_command(input: String, timeout: u64) -> i32 {
+        let timeout = Duration::from_secs(timeout);
+        let result = WrappyPaths::new().and_then(|paths| {
+            let mut container = ContainerResolver::new()?.resolve_container(&input)?;
+            let run = container.stop_run(timeout)?;
+            let service = ServiceRuntime::new(&paths, container.name()).stop_within(timeout)?;
+            Ok((run, service, container))
+        });
+        let (run, service, container) = match result {
+            Ok(result) => result,
+            Err(error) => {
+                eprintln!("❌ Failed to stop '{}': {}", input, error);
+                return 1;
+            }
+        };
+
+        let name = container.name();
+        match run {
+            RunStop::Stopped { pid, killed, exit_code } => {
+                let exit_code = exit_code.map(|code| format!(", exit code {}", code)).unwrap_or_default();
+                match killed {
+                    true => println!(
+                        "🛑 Killed '{}' (pid {}{}) after it ignored SIGTERM for {}s",
+                        name,
+                        pid,
+                        exit_code,
+                        timeout.as_secs()
+                    ),
+                    false => println!("🛑 Stopped '{}' (pid {}{})", name, pid, exit_code),
+                }
+            }
+            RunStop::Stale(pid) => println!(
+                "⚠️  Process {} recorded for '{}' is gone or was reused; did not signal it and recorded the run as stopped",
+                pid, name
+            ),
+            RunStop::NotRunning => {}
+        }
+        if let Some(pid) = service {
+            println!("🛑 Stopped service of '{}' (pid {})", name, pid);
+        }
+        if run == RunStop::NotRunning && service.is_none() {
+            println!("ℹ️  '{}' is not running", name);
+        }
+        0
+    }
+
+    /// Handles the status command execution. A `Running` state whose process is gone is
+    /// corrected and saved first. Exits with 3 when the last run ended in an error.
+    pub fn handle_status_command(input: String, json: bool) -> i32 {
+        let result = ContainerResolver::new()
+            .and_then(|resolver| resolver.resolve_container(&input))
+            .and_then(|mut container| Ok((container.clear_stale_run()?, container)));
+        let (stale_pid, container) = match result {
+            Ok(result) => result,
+            Err(error) => {
+                eprintln!("❌ Failed to read status of '{}': {}", input, error);
+                return 1;
+            }
+        };
+        let runtime = &container.runtime;
+        let exit_code = match runtime.status {
+            ContainerStatus::Error => 3,
+            _ => 0,
+        };
+
+        if json {
+            let mut status = match serde_json::to_value(runtime) {
+                Ok(status) => status,
+                Err(error) => {
+                    eprintln!("❌ Failed to read status of '{}': {}", input, error);
+                    return 1;
+                }
+            };
+            status["name"] = container.name().into();
+            status["version"] = container.version().to_string().into();
+            status["stale"] = stale_pid.is_some().into();
+            println!("{}", serde_json::to_string_pretty(&status).unwrap_or_default());
+            return exit_code;
+        }
+
+        let local = |at: &chrono::DateTime<chrono::Utc>| at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string();
+        let icon = match runtime.status {
+            ContainerStatus::Running => "▶️ ",
+            ContainerStatus::Error => "❌",
+            ContainerStatus::Installing | ContainerStatus::Removing => "⏳",
+            ContainerStatus::Ready | ContainerStatus::Stopped => "⏹️ ",
+        };
+        println!("{} {} {}: {}", icon, container.name(), container.version(), runtime.status);
+        if let Some(pid) = stale_pid {
+            println!("⚠️  The state said running, but process {} is gone or was reused; recorded it as stopped", pid);
+        }
+        if let Some(pid) = runtime.pid {
+            println!("  PID:            {}", pid);
+        }
+        if let Some(started_at) = &runtime.started_at {
+            println!("  Started:        {}", local(started_at));
+        }
+        if let (false, Some(stopped_at)) = (container.is_running(), &runtime.stopped_at) {
+            println!("  Stopped:        {}", local(stopped_at));
+        }
+        if let Some(code) = runtime.exit_code {
+            println!("  Last exit code: {}", code);
+        }
+        if !runtime.errors.is_empty() {
+            println!("  Recent errors:");
+            let skip = runtime.errors.len().saturating_sub(STATUS_RECENT_ERRORS);
+            for error in &runtime.errors[skip..] {
+                println!("    - {}", error);
+            }
+        }
+        exit_code
+    }
+
+    /// Handles the idle-reaper command execution; meant to be run from a user timer
+    pub fn handle_idle_reaper_command(dry_run: bool) -> i32 {
+        let paths = match WrappyPaths::new() {
+            Ok(paths) => paths,
+            Err(error) => {
+                eprintln!("❌ {}", error);
+                return 1;
+            }
+        };
+
+        let mut exit_code = 0;
+        for runtime in ServiceRuntime::all(&paths) {
+            let Some(state) = runtime.running() else {
+                continue;
+            };
+            let idle = runtime.idle_for().unwrap_or(Duration::MAX);
+            if idle < Duration::from_secs(state.idle_timeout_secs) {
+                continue;
+            }
+
+            if dry_run {
+                println!(
+                    "💤 Would stop '{}' (pid {}, idle {}s)",
+                    runtime.container_name(),
+                    state.pid,
+                    idle.as_secs()
+                );
+                continue;
+            }
+
+            match runtime.stop() {
+                Ok(Some(pid)) => println!(
+                    "💤 Stopped idle service of '{}' (pid {}, idle {}s)",
+                    runtime.container_name(),
+                    pid,
+                    idle.as_secs()
+                ),
+                Ok(None) => {}
+                Err(error) => {
+                    eprintln!("❌ Failed to stop service of '{}': {}", runtime.container_name(), error);
+                    exit_code = 1;
+                }
+            }
+        }
+        exit_code
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::InstallService;
+    use crate::features::setup::SetupService;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, TempHome};
+    use crate::shared::CancellationToken;
+
+    #[test]
+    fn stop_ends_the_recorded_run_of_an_installed_container_and_is_a_no_op_afterwards() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        SetupService::initialize(&paths).unwrap();
+        let source = home.path().join("src/app");
+        fixture_container_at(&source, fixture_manifest("app", "1.0.0"));
+        let resolver = ContainerResolver::new().unwrap();
+        let installed = InstallService::install(&source, None, false, false, &paths, &resolver, &CancellationToken::new());
+        let dir = installed.unwrap().entry.path;
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let mut container = ContainerService::load_from_directory(&dir).unwrap();
+        container.mark_running(child.id());
+        container.save_runtime().unwrap();
+        let reaper = thread::spawn(move || child.wait().unwrap());
+
+        assert_eq!(ContainerHandler::handle_stop_command("app".into(), 5), 0);
+        assert!(!reaper.join().unwrap().success());
+        let stopped = ContainerService::load_from_directory(&dir).unwrap();
+        assert!(!stopped.is_running());
+        assert_eq!(stopped.runtime.exit_code, Some(128 + libc::SIGTERM));
+
+        assert_eq!(ContainerHandler::handle_stop_command("app".into(), 5), 0);
+    }
+}
@@ -0,0 +1,163 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::features::container::{create_missing_config_files, RegistryEntry};
+use crate::features::manifest::{ContainerManifest, ContainerType, ManifestFormat};
+use crate::features::version::Version;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+
+/// Version a scaffolded manifest starts at.
+const INITIAL_VERSION: &str = "0.1.0";
+
+const DEFAULT_SCRIPT_PATH: &str = "scripts/default.sh";
+
+/// What `container init` created; files that already existed are not listed.
+#[derive(Debug, Clone, Default)]
+pub struct Scaffold {
+    pub name: String,
+    pub created: Vec<PathBuf>,
+}
+
+pub struct ScaffoldService;
+
+impl ScaffoldService {
+    /// Creates the directories and files `validate_structure` requires in `path`, which
+    /// is created when missing. A non-empty directory is refused unless `force` is set,
+    /// and even then existing files are kept. `name` defaults to the directory name.
+    /// The result passes `validate` as it is.
+    pub fn scaffold(
+        path: &Path,
+        name: Option<String>,
+        container_type: ContainerType,
+        force: bool,
+    ) -> ContainerResult<Scaffold> {
+        let path = std::path::absolute(path).map_err(|e| Self::io_error(path, e))?;
+        let non_empty = match fs::read_dir(&path) {
+            Ok(mut entries) => entries.next().is_some(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+            Err(e) => return Err(Self::io_error(&path, e)),
+        };
+        if non_empty && !force {
+            return Err(ContainerError::InvalidPath {
+                path,
+                reason: "is not empty; pass --force to scaffold into it".to_string(),
+            });
+        }
+
+        // With --force an existing manifest is kept, and so is its name
+        let existing = ManifestFormat::locate(&path).ok();
+        let name = match (name, &existing) {
+            (Some(name), _) => name,
+            (None, Some(manifest)) => ContainerManifest::from_file(manifest)?.name,
+            (None, None) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        RegistryEntry::check_name(&name).map_err(|_| {
+            ContainerError::ManifestValidation(format!(
+                "'{}' is not a container name; pass --name with letters, digits, hyphens and underscores",
+                name
+            ))
+        })?;
+        fs::create_dir_all(&path).map_err(|e| Self::io_error(&path, e))?;
+
+        let mut created = Vec::new();
+        for dir in ["scripts", "content", "config"] {
+            let dir = path.join(dir);
+            if !dir.exists() {
+                fs::create_dir(&dir).map_err(|e| Self::io_error(&dir, e))?;
+                created.push(dir);
+            }
+        }
+
+        let manifest_path = path.join(ManifestFormat::Json.file_name());
+        if existing.is_none() && !manifest_path.exists() {
+            let mut manifest = ContainerManifest::new(name.clone(), Version::new(INITIAL_VERSION)?);
+            manifest.container_type = container_type;
+            manifest.to_file(&manifest_path)?;
+            created.push(manifest_path);
+        }
+
+        let script = path.join(DEFAULT_SCRIPT_PATH);
+        if !script.exists() {
+            let content = format!(
+                "#!/bin/sh\n# Default script of {name}; `wrappy container run {name}` starts it\nset -e\n\necho \"Hello from {name}\"\n",
+                name = name
+            );
+            filesystem::atomic_write(&script, content.as_bytes(), Some(0o755))?;
+            created.push(script);
+        }
+
+        created.extend(create_missing_config_files(&path)?);
+        Ok(Scaffold { name, created })
+    }
+
+    fn io_error(path: &Path, source: std::io::Error) -> ContainerError {
+        ContainerError::IoError {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::{ContainerService, Executor, OutputMode};
+    use crate::shared::test_support::{write_executable, TempHome};
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn a_scaffold_has_every_required_part_and_its_default_script_runs() {
+        let home = TempHome::enter();
+        let dir = home.path().join("hello-app");
+
+        let scaffold = ScaffoldService::scaffold(&dir, None, ContainerType::Application, false).unwrap();
+
+        assert_eq!(scaffold.name, "hello-app");
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+        ContainerService::validate_structure(&dir, &container.manifest).unwrap();
+        assert_eq!(container.manifest.container_type, ContainerType::Application);
+        assert_eq!(container.manifest.version.to_string(), INITIAL_VERSION);
+        for file in ["config/permissions.json", "config/environment.json"] {
+            assert!(scaffold.created.contains(&dir.join(file)), "{} was not created", file);
+        }
+        let mode = fs::metadata(dir.join(DEFAULT_SCRIPT_PATH)).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert_eq!(Executor::new(container).output(OutputMode::Quiet).run("default", &[]).unwrap(), 0);
+    }
+
+    #[test]
+    fn a_non_empty_directory_needs_force_and_keeps_its_files() {
+        let home = TempHome::enter();
+        let dir = home.path().join("existing");
+        write_executable(&dir.join("scripts/default.sh"), "#!/bin/sh\necho mine\n");
+        fs::write(dir.join("notes.txt"), "keep").unwrap();
+
+        let refused = ScaffoldService::scaffold(&dir, None, ContainerType::Package, false);
+        let forced = ScaffoldService::scaffold(&dir, Some("mine".to_string()), ContainerType::Package, true).unwrap();
+
+        assert!(matches!(refused, Err(ContainerError::InvalidPath { .. })));
+        assert_eq!(forced.name, "mine");
+        assert!(!forced.created.contains(&dir.join(DEFAULT_SCRIPT_PATH)));
+        assert_eq!(fs::read_to_string(dir.join("scripts/default.sh")).unwrap(), "#!/bin/sh\necho mine\n");
+        assert_eq!(fs::read_to_string(dir.join("notes.txt")).unwrap(), "keep");
+        assert_eq!(ContainerService::load_from_directory(&dir).unwrap().name(), "mine");
+    }
+
+    #[test]
+    fn a_directory_name_that_is_no_container_name_needs_a_name() {
+        let home = TempHome::enter();
+        let dir = home.path().join("my app");
+
+        let refused = ScaffoldService::scaffold(&dir, None, ContainerType::Package, false);
+        let created_anything = dir.exists();
+        let named = ScaffoldService::scaffold(&dir, Some("my-app".to_string()), ContainerType::Package, false);
+
+        assert!(matches!(refused, Err(ContainerError::ManifestValidation(_))));
+        assert!(!created_anything);
+        assert_eq!(named.unwrap().name, "my-app");
+    }
+}
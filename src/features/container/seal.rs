@@ -0,0 +1,601 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::features::container::{Container, STATE_ENTRIES};
+use crate::shared::cancellation::CancellationToken;
+use crate::shared::clock;
+use crate::shared::digest::Sha256;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::ignore::IgnoreRules;
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::summary::Reporter;
+use crate::shared::traversal::Walk;
+
+/// Checksum manifest `container seal` writes into the container root.
+pub const SEAL_FILE: &str = ".wrappy-seal.json";
+
+const SEAL_FORMAT_VERSION: u32 = 1;
+
+/// Bytes hashed between cancellation checks, so even one huge file can be interrupted.
+const HASH_CHUNK_SIZE: usize = 1 << 20;
+
+/// Longest verify runs between two saves of its progress.
+const PROGRESS_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Verify prints a progress line each time this share of the bytes is done.
+const PROGRESS_STEP_PERCENT: u64 = 10;
+
+/// Smaller runs finish too quickly for progress lines to be worth printing.
+const PROGRESS_MIN_BYTES: u64 = 64 * 1024 * 1024;
+
+/// What a seal recorded about one path, relative to the container root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SealedEntry {
+    File {
+        sha256: String,
+        size: u64,
+        /// Modification time in seconds and nanoseconds, for `verify --fast`
+        mtime: i64,
+        mtime_nsec: i64,
+    },
+    Symlink {
+        target: String,
+    },
+}
+
+/// Per-file hashes of a container at the time it was sealed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealManifest {
+    pub format_version: u32,
+    pub name: String,
+    pub version: String,
+    pub sealed_at: DateTime<Utc>,
+    pub files: BTreeMap<String, SealedEntry>,
+}
+
+impl SealManifest {
+    /// Bytes of all sealed files.
+    pub fn total_size(&self) -> u64 {
+        self.files
+            .values()
+            .map(|entry| match entry {
+                SealedEntry::File { size, .. } => *size,
+                SealedEntry::Symlink { .. } => 0,
+            })
+            .sum()
+    }
+}
+
+/// How much `verify` trusts what it sees on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyMode {
+    /// Hash every file not verified by the run being resumed
+    Full,
+    /// Trust files whose size and modification time match the seal
+    Fast,
+    /// Hash every file, ignoring earlier progress and modification times
+    Paranoid,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct VerifyOptions {
+    pub mode: VerifyMode,
+    /// Continue the last interrupted run instead of starting over
+    pub resume: bool,
+}
+
+/// What a verify run did; mismatches are reported through the reporter.
+#[derive(Debug, Clone, Default)]
+pub struct VerifySummary {
+    pub files: usize,
+    pub hashed: usize,
+    pub hashed_bytes: u64,
+    /// Files `--fast` accepted by size and modification time
+    pub trusted: usize,
+    /// Files an earlier, interrupted run had already verified
+    pub resumed: usize,
+    pub mismatches: usize,
+}
+
+/// Progress of a verify run, kept in the cache directory until the run completes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VerifyProgress {
+    /// SHA-256 of the seal file; progress against another seal is discarded
+    seal_sha256: String,
+    /// Last path the run got to, in the seal's order
+    position: Option<String>,
+    verified: BTreeSet<String>,
+}
+
+/// Seals containers with per-file checksums and verifies them against the seal.
+pub struct SealService;
+
+impl SealService {
+    /// Hashes every file of `container` and writes the seal. State files, persistent
+    /// directories and the seal itself change while the container is used, so they are left out.
+    pub fn seal(container: &Container, cancellation: &CancellationToken) -> ContainerResult<SealManifest> {
+        let mut files = BTreeMap::new();
+        for (relative, path, metadata) in Self::current_entries(container)? {
+            cancellation.check("sealing the container")?;
+            let entry = if metadata.file_type().is_symlink() {
+                SealedEntry::Symlink {
+                    target: Self::link_target(&path)?,
+                }
+            } else {
+                SealedEntry::File {
+                    sha256: Self::hash_file(&path, cancellation, |_| {})?,
+                    size: metadata.len(),
+                    mtime: metadata.mtime(),
+                    mtime_nsec: metadata.mtime_nsec(),
+                }
+            };
+            files.insert(relative, entry);
+        }
+
+        let seal = SealManifest {
+            format_version: SEAL_FORMAT_VERSION,
+            name: container.name().to_string(),
+            version: container.version().to_string(),
+            sealed_at: clock::now(),
+            files,
+        };
+        let mut content = serde_json::to_string_pretty(&seal)?;
+        content.push('\n');
+        filesystem::atomic_write(&container.path.join(SEAL_FILE), content.as_bytes(), Some(0o644))?;
+        Ok(seal)
+    }
+
+    pub fn read_seal(container: &Container) -> ContainerResult<SealManifest> {
+        let path = container.path.join(SEAL_FILE);
+        let content = fs::read_to_string(&path).map_err(|e| ContainerError::IoError { path, source: e })?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Compares `container` with its seal, in the seal's path order. Every missing, changed
+    /// or unsealed file is reported as a failed item. Progress is saved to the cache directory
+    /// as the run goes, so an interrupted run can continue with `resume`.
+    pub fn verify(
+        container: &Container,
+        options: VerifyOptions,
+        cancellation: &CancellationToken,
+        reporter: &Reporter,
+    ) -> ContainerResult<VerifySummary> {
+        let seal_path = container.path.join(SEAL_FILE);
+        let seal_bytes = fs::read(&seal_path).map_err(|e| ContainerError::IoError {
+            path: seal_path.clone(),
+            source: e,
+        })?;
+        let seal: SealManifest = serde_json::from_slice(&seal_bytes)?;
+        let mut hasher = Sha256::new();
+        hasher.update(&seal_bytes);
+        let seal_sha256 = hasher.finish_hex();
+
+        let progress_file = Self::progress_file(container)?;
+        let mut progress = match options.resume && options.mode != VerifyMode::Paranoid {
+            true => Self::load_progress(&progress_file, &seal_sha256, reporter),
+            false => VerifyProgress::default(),
+        };
+        progress.seal_sha256 = seal_sha256;
+        if let Some(position) = &progress.position {
+            reporter.info(format!("⏩ Resuming after {} ({} file(s) already verified)", position, progress.verified.len()));
+        }
+
+        let mut current: BTreeMap<String, (PathBuf, fs::Metadata)> = Self::current_entries(container)?
+            .into_iter()
+            .map(|(relative, path, metadata)| (relative, (path, metadata)))
+            .collect();
+        let mut summary = VerifySummary {
+            files: seal.files.len(),
+            ..VerifySummary::default()
+        };
+
+        let pending_bytes: u64 = seal
+            .files
+            .iter()
+            .filter(|(relative, _)| !progress.verified.contains(*relative))
+            .map(|(_, entry)| match entry {
+                SealedEntry::File { size, .. } => *size,
+                SealedEntry::Symlink { .. } => 0,
+            })
+            .sum();
+        let mut done_bytes = 0;
+        let mut next_step = 1;
+        let mut saved = Instant::now();
+
+        for (relative, sealed) in &seal.files {
+            let on_disk = current.remove(relative).or_else(|| {
+                // Sealed before `.wrappyignore` left it out, so it is still checked
+                let path = container.path.join(relative);
+                fs::symlink_metadata(&path).ok().map(|metadata| (path, metadata))
+            });
+            if progress.verified.contains(relative) {
+                summary.resumed += 1;
+                continue;
+            }
+            if let Err(error) = cancellation.check("verifying the container") {
+                Self::save_progress(&progress_file, &progress)?;
+                return Err(error);
+            }
+
+            let problem = match (sealed, on_disk) {
+                (_, None) => Some("missing".to_string()),
+                (SealedEntry::Symlink { target }, Some((path, metadata))) => {
+                    match metadata.file_type().is_symlink() {
+                        true if Self::link_target(&path)? == *target => None,
+                        true => Some(format!("symlink no longer points to {}", target)),
+                        false => Some("was a symlink when sealed".to_string()),
+                    }
+                }
+                (SealedEntry::File { sha256, size, mtime, mtime_nsec }, Some((path, metadata))) => {
+                    let unchanged_stamp = metadata.mtime() == *mtime && metadata.mtime_nsec() == *mtime_nsec;
+                    if !metadata.file_type().is_file() {
+                        Some("is no longer a regular file".to_string())
+                    } else if metadata.len() != *size {
+                        Some(format!("size changed from {} to {} bytes", size, metadata.len()))
+                    } else if options.mode == VerifyMode::Fast && unchanged_stamp {
+                        summary.trusted += 1;
+                        None
+                    } else {
+                        let base = done_bytes;
+                        let hashed = Self::hash_file(&path, cancellation, |bytes| {
+                            Self::report_progress(reporter, base + bytes, pending_bytes, &mut next_step)
+                        });
+                        let hashed = match hashed {
+                            Ok(hashed) => hashed,
+                            Err(error @ ContainerError::Cancelled { .. }) => {
+                                Self::save_progress(&progress_file, &progress)?;
+                                return Err(error);
+                            }
+                            Err(error) => return Err(error),
+                        };
+                        summary.hashed += 1;
+                        summary.hashed_bytes += size;
+                        match hashed == *sha256 {
+                            true => None,
+                            false => Some("content differs from the seal".to_string()),
+                        }
+                    }
+                }
+            };
+            if let SealedEntry::File { size, .. } = sealed {
+                done_bytes += size;
+                Self::report_progress(reporter, done_bytes, pending_bytes, &mut next_step);
+            }
+
+            match problem {
+                None => {
+                    progress.verified.insert(relative.clone());
+                }
+                Some(reason) => {
+                    summary.mismatches += 1;
+                    Self::report_mismatch(reporter, container, relative, reason);
+                }
+            }
+            progress.position = Some(relative.clone());
+            if saved.elapsed() >= PROGRESS_SAVE_INTERVAL {
+                Self::save_progress(&progress_file, &progress)?;
+                saved = Instant::now();
+            }
+        }
+
+        for relative in current.keys() {
+            summary.mismatches += 1;
+            Self::report_mismatch(reporter, container, relative, "not in the seal".to_string());
+        }
+
+        // A finished run leaves nothing to resume
+        let _ = fs::remove_file(&progress_file);
+        Ok(summary)
+    }
+
+    fn report_mismatch(reporter: &Reporter, container: &Container, relative: &str, reason: String) {
+        let line = format!("❌ {}: {}", relative, reason);
+        let error = ContainerError::IntegrityMismatch {
+            path: container.path.join(relative),
+            reason,
+        };
+        reporter.failed(relative, &error, line);
+    }
+
+    fn report_progress(reporter: &Reporter, done: u64, total: u64, next_step: &mut u64) {
+        if total < PROGRESS_MIN_BYTES || *next_step * PROGRESS_STEP_PERCENT >= 100 {
+            return;
+        }
+        let percent = done.saturating_mul(100) / total;
+        if percent >= *next_step * PROGRESS_STEP_PERCENT {
+            reporter.info(format!(
+                "⏳ Verified {} of {} ({}%)",
+                filesystem::format_size(done),
+                filesystem::format_size(total),
+                percent
+            ));
+            *next_step = percent / PROGRESS_STEP_PERCENT + 1;
+        }
+    }
+
+    /// Files and symlinks of the container as the seal covers them, sorted by relative path.
+    /// `.wrappyignore` applies as it does to export.
+    fn current_entries(container: &Container) -> ContainerResult<Vec<(String, PathBuf, fs::Metadata)>> {
+        let root = &container.path;
+        let skipped: Vec<PathBuf> = STATE_ENTRIES
+            .iter()
+            .chain([SEAL_FILE].iter())
+            .map(|entry| root.join(entry))
+            .chain(container.persistent_paths())
+            .collect();
+
+        let mut entries = Vec::new();
+        let mut walk = Walk::new(root).follow_root_link().ignoring(IgnoreRules::for_container(root)?);
+        while let Some(entry) = walk.next() {
+            let entry = entry?;
+            if skipped.contains(&entry.path) {
+                walk.skip_current_dir();
+                continue;
+            }
+            if !entry.file_type.is_file() && !entry.file_type.is_symlink() {
+                continue;
+            }
+            let relative = entry.path.strip_prefix(root).unwrap_or(&entry.path);
+            let relative = relative.to_str().ok_or_else(|| ContainerError::InvalidPath {
+                path: entry.path.clone(),
+                reason: "not valid UTF-8, so it cannot be sealed".to_string(),
+            })?;
+            let metadata = fs::symlink_metadata(&entry.path).map_err(|e| ContainerError::IoError {
+                path: entry.path.clone(),
+                source: e,
+            })?;
+            entries.push((relative.to_string(), entry.path, metadata));
+        }
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(entries)
+    }
+
+    /// Streams the file through SHA-256 in chunks, telling `progress` the bytes read so far.
+    fn hash_file(path: &Path, cancellation: &CancellationToken, mut progress: impl FnMut(u64)) -> ContainerResult<String> {
+        let io_error = |e| ContainerError::IoError {
+            path: path.to_path_buf(),
+            source: e,
+        };
+        let mut file = File::open(path).map_err(io_error)?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; HASH_CHUNK_SIZE];
+        let mut read_total = 0;
+        loop {
+            cancellation.check("verifying the container")?;
+            let read = file.read(&mut buffer).map_err(io_error)?;
+            if read == 0 {
+                return Ok(hasher.finish_hex());
+            }
+            hasher.update(&buffer[..read]);
+            read_total += read as u64;
+            progress(read_total);
+        }
+    }
+
+    fn link_target(path: &Path) -> ContainerResult<String> {
+        let target = fs::read_link(path).map_err(|e| ContainerError::IoError {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        Ok(target.to_string_lossy().into_owned())
+    }
+
+    /// `~/.cache/wrappy/verify/<name>-<hash of the path>.json`, one per container directory.
+    fn progress_file(container: &Container) -> ContainerResult<PathBuf> {
+        let paths = WrappyPaths::new()?;
+        let canonical = fs::canonicalize(&container.path).unwrap_or_else(|_| container.path.clone());
+        let mut hasher = Sha256::new();
+        hasher.update(canonical.to_string_lossy().as_bytes());
+        Ok(paths
+            .cache_dir
+            .join("verify")
+            .join(format!("{}-{}.json", container.name(), &hasher.finish_hex()[..16])))
+    }
+
+    fn load_progress(path: &Path, seal_sha256: &str, reporter: &Reporter) -> VerifyProgress {
+        let Some(progress) = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<VerifyProgress>(&content).ok())
+        else {
+            reporter.info("ℹ️  No interrupted verify run to resume, starting from the beginning");
+            return VerifyProgress::default();
+        };
+        if progress.seal_sha256 != seal_sha256 {
+            reporter.warn("The saved progress belongs to an older seal, starting from the beginning");
+            return VerifyProgress::default();
+        }
+        progress
+    }
+
+    fn save_progress(path: &Path, progress: &VerifyProgress) -> ContainerResult<()> {
+        if let Some(parent) = path.parent() {
+            filesystem::create_state_dir(parent)?;
+        }
+        let content = serde_json::to_string(progress)?;
+        filesystem::atomic_write(path, content.as_bytes(), Some(filesystem::STATE_FILE_MODE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::ContainerService;
+    use crate::shared::summary::SummaryFormat;
+    use crate::shared::test_support::{fixture_container, TempHome};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Sealed fixture container with five more content files.
+    fn sealed(home: &TempHome) -> Container {
+        let dir = fixture_container(home.path(), "web", "1.0.0");
+        for name in ["a", "b", "c", "d", "e"] {
+            fs::write(dir.join("content").join(name), format!("content of {}", name)).unwrap();
+        }
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+        SealService::seal(&container, &CancellationToken::new()).unwrap();
+        container
+    }
+
+    fn verify(container: &Container, mode: VerifyMode, resume: bool) -> (VerifySummary, Vec<String>) {
+        let reporter = Reporter::new("verify", SummaryFormat::Json);
+        let summary = SealService::verify(container, VerifyOptions { mode, resume }, &CancellationToken::new(), &reporter).unwrap();
+        let failed = reporter.finish().items.into_iter().map(|item| item.subject).collect();
+        (summary, failed)
+    }
+
+    #[test]
+    fn an_untouched_container_verifies_and_the_seal_leaves_out_state() {
+        let home = TempHome::enter();
+        let container = sealed(&home);
+        container.save_runtime().unwrap();
+
+        let seal = SealService::read_seal(&container).unwrap();
+        let (summary, failed) = verify(&container, VerifyMode::Full, false);
+
+        assert!(seal.files.contains_key("content/a") && seal.files.contains_key("bin/tool"));
+        assert!(!seal.files.contains_key(SEAL_FILE));
+        assert!(seal.files.keys().all(|path| !path.starts_with(".wrappy-runtime")), "{:?}", seal.files.keys());
+        let on_disk: u64 = seal.files.keys().map(|path| fs::metadata(container.path.join(path)).unwrap().len()).sum();
+        assert_eq!(seal.total_size(), on_disk);
+        assert!(failed.is_empty(), "{:?}", failed);
+        assert_eq!((summary.files, summary.hashed, summary.mismatches), (seal.files.len(), seal.files.len(), 0));
+    }
+
+    #[test]
+    fn changed_missing_added_and_retargeted_entries_are_each_reported() {
+        let home = TempHome::enter();
+        let dir = fixture_container(home.path(), "web", "1.0.0");
+        std::os::unix::fs::symlink("tool", dir.join("bin/alias")).unwrap();
+        let container = ContainerService::load_from_directory(&dir).unwrap();
+        SealService::seal(&container, &CancellationToken::new()).unwrap();
+        fs::write(dir.join("bin/tool"), "#!/bin/sh\necho changed\n").unwrap();
+        fs::remove_file(dir.join("scripts/default.sh")).unwrap();
+        fs::write(dir.join("content/new"), "added").unwrap();
+        fs::remove_file(dir.join("bin/alias")).unwrap();
+        std::os::unix::fs::symlink("elsewhere", dir.join("bin/alias")).unwrap();
+
+        let reporter = Reporter::new("verify", SummaryFormat::Json);
+        let options = VerifyOptions { mode: VerifyMode::Full, resume: false };
+        let summary = SealService::verify(&container, options, &CancellationToken::new(), &reporter).unwrap();
+        let report = reporter.finish();
+
+        let failed: Vec<(&str, &str)> = report
+            .items
+            .iter()
+            .map(|item| (item.subject.as_str(), item.code.unwrap_or_default()))
+            .collect();
+        assert_eq!(
+            failed,
+            [
+                ("bin/alias", "E_INTEGRITY_MISMATCH"),
+                ("bin/tool", "E_INTEGRITY_MISMATCH"),
+                ("scripts/default.sh", "E_INTEGRITY_MISMATCH"),
+                ("content/new", "E_INTEGRITY_MISMATCH"),
+            ]
+        );
+        assert_eq!(summary.mismatches, 4);
+        assert_eq!(report.exit_code(), 1);
+    }
+
+    /// Calls of the cancellation source before it reports Ctrl-C.
+    static CHECKS_BEFORE_INTERRUPT: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+    fn interrupted() -> bool {
+        CHECKS_BEFORE_INTERRUPT
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| Some(left.saturating_sub(1)))
+            .unwrap()
+            == 0
+    }
+
+    #[test]
+    fn resume_continues_an_interrupted_run_where_it_stopped() {
+        let home = TempHome::enter();
+        let container = sealed(&home);
+        let files = SealService::read_seal(&container).unwrap().files.len();
+        // Each file is checked once before and twice while hashing; the third file is cut off
+        CHECKS_BEFORE_INTERRUPT.store(7, Ordering::SeqCst);
+        let options = VerifyOptions { mode: VerifyMode::Full, resume: false };
+
+        let first = SealService::verify(&container, options, &CancellationToken::linked_to(interrupted), &Reporter::default());
+        CHECKS_BEFORE_INTERRUPT.store(usize::MAX, Ordering::SeqCst);
+        let (resumed, failed) = verify(&container, VerifyMode::Full, true);
+        let (again, _) = verify(&container, VerifyMode::Full, true);
+
+        assert!(matches!(first, Err(ContainerError::Cancelled { .. })), "{:?}", first.map(|summary| summary.hashed));
+        assert!(failed.is_empty(), "{:?}", failed);
+        assert_eq!(resumed.resumed, 2);
+        assert_eq!(resumed.hashed, files - 2);
+        // The finished run removed its progress, so the next one starts over
+        assert_eq!((again.resumed, again.hashed), (0, files));
+    }
+
+    #[test]
+    fn resume_ignores_progress_of_another_seal_and_paranoid_ignores_it_altogether() {
+        let home = TempHome::enter();
+        let container = sealed(&home);
+        let files = SealService::read_seal(&container).unwrap().files.len();
+        let progress_file = SealService::progress_file(&container).unwrap();
+        let seal_sha256 = {
+            let mut hasher = Sha256::new();
+            hasher.update(&fs::read(container.path.join(SEAL_FILE)).unwrap());
+            hasher.finish_hex()
+        };
+        let progress = |seal_sha256: &str| VerifyProgress {
+            seal_sha256: seal_sha256.to_string(),
+            position: Some("content/a".to_string()),
+            verified: ["bin/tool".to_string(), "content/a".to_string()].into(),
+        };
+
+        SealService::save_progress(&progress_file, &progress(&seal_sha256)).unwrap();
+        let (paranoid, _) = verify(&container, VerifyMode::Paranoid, true);
+        SealService::save_progress(&progress_file, &progress("an older seal")).unwrap();
+        let (stale, _) = verify(&container, VerifyMode::Full, true);
+        SealService::save_progress(&progress_file, &progress(&seal_sha256)).unwrap();
+        let (resumed, _) = verify(&container, VerifyMode::Full, true);
+
+        assert_eq!((paranoid.resumed, paranoid.hashed), (0, files));
+        assert_eq!((stale.resumed, stale.hashed), (0, files));
+        assert_eq!((resumed.resumed, resumed.hashed), (2, files - 2));
+    }
+
+    #[test]
+    fn fast_trusts_a_preserved_mtime_but_full_and_paranoid_catch_the_edit() {
+        let home = TempHome::enter();
+        let container = sealed(&home);
+        let path = container.path.join("content/c");
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        fs::write(&path, "CONTENT OF c").unwrap();
+        fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+
+        let (fast, fast_failed) = verify(&container, VerifyMode::Fast, false);
+        let (full, full_failed) = verify(&container, VerifyMode::Full, false);
+        let (paranoid, paranoid_failed) = verify(&container, VerifyMode::Paranoid, false);
+
+        assert!(fast_failed.is_empty());
+        assert_eq!((fast.trusted, fast.hashed, fast.mismatches), (fast.files, 0, 0));
+        assert_eq!(full_failed, ["content/c"]);
+        assert_eq!(paranoid_failed, ["content/c"]);
+        assert_eq!((full.mismatches, paranoid.mismatches, paranoid.trusted), (1, 1, 0));
+    }
+
+    #[test]
+    fn a_sealed_file_ignored_later_is_still_verified() {
+        let home = TempHome::enter();
+        let container = sealed(&home);
+        fs::write(container.path.join(".wrappyignore"), "content/b\n").unwrap();
+        fs::write(container.path.join("content/b"), "changed content").unwrap();
+
+        let (summary, failed) = verify(&container, VerifyMode::Full, false);
+
+        // The ignore file itself is new, so it is reported as unsealed
+        assert_eq!(failed, ["content/b", ".wrappyignore"]);
+        assert_eq!(summary.mismatches, 2);
+    }
+}
@@ -1,11 +1,31 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use regex::Regex;
 
-use crate::features::{ContainerManifest, Version};
+use crate::features::container::{
+    ContainerResolver, DependencyCheck, DependencyOutcome, DependencyReport, FileListing, HostRequirementState,
+    HostRequirementStatus, IgnoredPath, IncludedFile, StateLocation, VariableStore, RUNTIME_FILE,
+};
+use crate::features::bindings::BindingPolicy;
+use crate::features::manifest::{ManifestFormat, HostRequirement, ScriptSpec, GENERATED_SCRIPTS_DIR};
+use crate::features::{ContainerManifest, Version, VersionMismatch, VersionReq};
+use crate::shared::clock;
 use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::host;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::portability;
+use crate::shared::ignore::IgnoreRules;
+use crate::shared::traversal::{TraversalLimits, Walk};
 
 /// Tracks container lifecycle for execution monitoring and user feedback.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -19,6 +39,44 @@ pub enum ContainerStatus {
     Removing,
 }
 
+/// How often `stop_run` checks whether the process has exited.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long `stop_run` waits for a process to disappear after SIGKILL.
+const STOP_KILL_PERIOD: Duration = Duration::from_secs(5);
+
+/// How long `stop_run` gives a `container run` waiting for the process to record its exit.
+const STOP_RECORD_PERIOD: Duration = Duration::from_secs(1);
+
+/// What `Container::stop_run` did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStop {
+    NotRunning,
+    /// The recorded pid is gone or belongs to another process; it was not signalled
+    /// and the state was corrected
+    Stale(u32),
+    Stopped {
+        pid: u32,
+        /// SIGTERM was ignored until the timeout, so the process was killed
+        killed: bool,
+        exit_code: Option<i32>,
+    },
+}
+
+impl fmt::Display for ContainerStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            Self::Ready => "ready",
+            Self::Running => "running",
+            Self::Stopped => "stopped",
+            Self::Error => "error",
+            Self::Installing => "installing",
+            Self::Removing => "removing",
+        };
+        f.write_str(status)
+    }
+}
+
 /// Tracks container runtime state for lifecycle management and user reporting.
 /// Enables monitoring execution status, process information, and error history.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +88,24 @@ pub struct ContainerRuntime {
     pub stopped_at: Option<DateTime<Utc>>,
     pub exit_code: Option<i32>,
     pub errors: Vec<String>,
+    /// Start time of `pid` as `process_start_time` reported it, so a reused pid is not mistaken for the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_start_time: Option<u64>,
+}
+
+impl ContainerRuntime {
+    /// Whether the recorded pid still is the process that was started. Runtimes
+    /// saved without a start time only check that the pid exists.
+    pub fn is_process_alive(&self) -> bool {
+        let Some(pid) = self.pid else {
+            return false;
+        };
+        match (host::process_start_time(pid), self.process_start_time) {
+            (Some(current), Some(recorded)) => current == recorded,
+            (current, None) => current.is_some(),
+            (None, Some(_)) => false,
+        }
+    }
 }
 
 impl Default for ContainerRuntime {
@@ -42,10 +118,20 @@ impl Default for ContainerRuntime {
             stopped_at: None,
             exit_code: None,
             errors: Vec::new(),
+            process_start_time: None,
         }
     }
 }
 
+/// Contents of `runtime.json`: the runtime of the last run and when the container was last used.
+#[derive(Debug, Serialize, Deserialize)]
+struct RuntimeRecord {
+    #[serde(flatten)]
+    runtime: ContainerRuntime,
+    #[serde(default)]
+    last_accessed: Option<DateTime<Utc>>,
+}
+
 /// Core abstraction for isolated application environments in the container file system.
 /// Encapsulates application lifecycle, deployment validation, and runtime management.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,7 +153,7 @@ impl ContainerService {
         Self::validate_manifest(&manifest)?;
         Self::validate_structure(&path, &manifest)?;
 
-        let now = Utc::now();
+        let now = clock::now();
         
         Ok(Container {
             manifest,
@@ -85,15 +171,68 @@ impl ContainerService {
         
         Self::validate_path_exists(&path)?;
         
-        let manifest = Self::load_manifest(&path)?;
-        Self::create_container(manifest, path)
+        let mut manifest = Self::load_manifest(&path)?;
+        Self::apply_stored_variables(&mut manifest)?;
+        BindingPolicy::load()?.apply_defaults(&mut manifest.bindings);
+        let mut container = Self::create_container(manifest, path)?;
+        Self::load_runtime(&mut container);
+        Ok(container)
+    }
+
+    /// Restores the runtime saved by an earlier wrappy process. Without a `runtime.json`
+    /// the container starts out `Ready`; an unreadable one is reported and ignored,
+    /// since losing the last run's state is no reason to refuse the container.
+    fn load_runtime(container: &mut Container) {
+        let path = StateLocation::resolve(&container.path).join(RUNTIME_FILE);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+            Err(e) => {
+                eprintln!("⚠️  Ignoring runtime state {}: {}", path.display(), e);
+                return;
+            }
+        };
+        match serde_json::from_str::<RuntimeRecord>(&content) {
+            Ok(record) => {
+                container.runtime = record.runtime;
+                if let Some(last_accessed) = record.last_accessed {
+                    container.last_accessed = last_accessed;
+                }
+            }
+            Err(e) => eprintln!("⚠️  Ignoring corrupt runtime state {}: {}", path.display(), e),
+        }
+    }
+
+    /// Fills `${install:NAME}` placeholders with the values chosen for this container.
+    /// Placeholders nobody chose a value for yet stay in place and block binding or running.
+    fn apply_stored_variables(manifest: &mut ContainerManifest) -> ContainerResult<()> {
+        if manifest.variables.is_empty() {
+            return Ok(());
+        }
+        let paths = WrappyPaths::new()?;
+        let values = VariableStore::for_container(&paths, &manifest.name).load()?;
+        manifest.apply_install_variables(&values);
+        Ok(())
+    }
+
+    /// Fails when the manifest still holds placeholders without a chosen value.
+    pub fn ensure_variables_resolved(manifest: &ContainerManifest) -> ContainerResult<()> {
+        let unresolved = manifest.unresolved_install_variables();
+        if unresolved.is_empty() {
+            Ok(())
+        } else {
+            Err(ContainerError::MissingVariables {
+                container: manifest.name.clone(),
+                variables: unresolved,
+            })
+        }
     }
 
     /// Validates that path exists and is a directory
-    fn validate_path_exists(path: &PathBuf) -> ContainerResult<()> {
+    fn validate_path_exists(path: &Path) -> ContainerResult<()> {
         if !path.exists() {
             return Err(ContainerError::InvalidPath { 
-                path: path.clone(), 
+                path: path.to_path_buf(), 
                 reason: "Path does not exist".to_string() 
             });
         }
@@ -108,9 +247,8 @@ impl ContainerService {
     }
 
     /// Loads and validates manifest from directory
-    fn load_manifest(path: &PathBuf) -> ContainerResult<ContainerManifest> {
-        let manifest_path = path.join("manifest.json");
-        ContainerManifest::from_file(&manifest_path)
+    fn load_manifest(path: &Path) -> ContainerResult<ContainerManifest> {
+        ContainerManifest::from_file(ManifestFormat::locate(path)?)
     }
 
     /// Validates manifest data
@@ -121,11 +259,10 @@ impl ContainerService {
     /// Validates container directory structure to ensure proper deployment.
     /// Prevents runtime failures by catching missing dependencies early.
     pub fn validate_structure(path: &Path, manifest: &ContainerManifest) -> ContainerResult<()> {
-        Self::validate_path_exists(&path.to_path_buf())?;
+        Self::validate_path_exists(path)?;
         Self::validate_required_directories(path)?;
         Self::validate_manifest_file_exists(path)?;
         Self::validate_scripts_exist(path, manifest)?;
-        Self::validate_config_files_exist(path)?;
 
         Ok(())
     }
@@ -146,13 +283,7 @@ impl ContainerService {
 
     /// Validates manifest file exists
     fn validate_manifest_file_exists(path: &Path) -> ContainerResult<()> {
-        let manifest_path = path.join("manifest.json");
-        if !manifest_path.exists() {
-            return Err(ContainerError::InvalidStructure(
-                "manifest.json not found".to_string(),
-            ));
-        }
-        Ok(())
+        ManifestFormat::locate(path).map(|_| ())
     }
 
     /// Validates all script files exist
@@ -164,42 +295,198 @@ impl ContainerService {
 
     /// Validates default script exists
     fn validate_default_script_exists(path: &Path, manifest: &ContainerManifest) -> ContainerResult<()> {
-        let default_script_path = path.join(manifest.default_script()?);
-        if !default_script_path.exists() {
-            return Err(ContainerError::MissingDefaultScript);
+        // Inline scripts live in the manifest and are materialized on demand
+        if manifest.scripts.get("default").is_some_and(ScriptSpec::is_inline) {
+            return Ok(());
+        }
+
+        match Self::resolve_script_path(path, manifest, "default") {
+            Err(ContainerError::ScriptNotFound { .. }) => Err(ContainerError::MissingDefaultScript),
+            result => result.map(|_| ()),
         }
-        Ok(())
     }
 
     /// Validates all referenced scripts exist
     fn validate_all_scripts_exist(path: &Path, manifest: &ContainerManifest) -> ContainerResult<()> {
-        for (script_name, script_path) in &manifest.scripts {
-            let full_script_path = path.join(script_path);
-            if !full_script_path.exists() {
+        for (script_name, script) in &manifest.scripts {
+            if script.is_inline() {
+                continue;
+            }
+
+            Self::resolve_script_path(path, manifest, script_name)?;
+        }
+        Ok(())
+    }
+
+    /// Resolves a manifest script to its path and checks it can be executed right now:
+    /// it exists, is a regular file (symlinks are followed) and has an execute bit.
+    pub fn resolve_script_path(path: &Path, manifest: &ContainerManifest, script_name: &str) -> ContainerResult<PathBuf> {
+        // Inline scripts are materialized with the rest of the container's state
+        let base = match manifest.scripts.get(script_name).is_some_and(ScriptSpec::is_inline) {
+            true => StateLocation::resolve(path),
+            false => path.to_path_buf(),
+        };
+        let script_path = base.join(manifest.get_script(script_name)?);
+
+        let metadata = match fs::metadata(&script_path) {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => {
                 return Err(ContainerError::ScriptNotFound {
                     container: manifest.name.clone(),
-                    script: script_name.clone(),
-                });
+                    script: script_name.to_string(),
+                })
             }
+        };
+
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err(ContainerError::ScriptNotExecutable { path: script_path });
         }
-        Ok(())
+
+        Ok(script_path)
     }
 
-    /// Validates required configuration files exist
-    fn validate_config_files_exist(path: &Path) -> ContainerResult<()> {
-        let permissions_path = path.join("config/permissions.json");
-        let environment_path = path.join("config/environment.json");
-        
-        if !permissions_path.exists() {
-            return Err(ContainerError::InvalidStructure(
-                "config/permissions.json not found".to_string(),
-            ));
+    /// Creates any declared persistent directory that does not exist yet.
+    /// Returns the directories that were created.
+    pub fn ensure_persistent_dirs(container: &Container) -> ContainerResult<Vec<PathBuf>> {
+        let mut created = Vec::new();
+        for directory in container.persistent_paths() {
+            if !directory.exists() {
+                fs::create_dir_all(&directory).map_err(|e| ContainerError::IoError {
+                    path: directory.clone(),
+                    source: e,
+                })?;
+                created.push(directory);
+            }
         }
+        Ok(created)
+    }
 
-        if !environment_path.exists() {
-            return Err(ContainerError::InvalidStructure(
-                "config/environment.json not found".to_string(),
-            ));
+    /// Moves the persistent directories of `container` that hold anything to
+    /// `orphaned-data/<name>/` before the container is deleted. Nothing moves while data
+    /// an earlier removal kept there is in the way. Returns where the data went, if any.
+    pub fn orphan_persistent_data(container: &Container, name: &str, paths: &WrappyPaths) -> ContainerResult<Option<PathBuf>> {
+        let orphaned = paths.orphaned_data_dir(name);
+        let kept: Vec<(PathBuf, PathBuf)> = container
+            .manifest
+            .persistent
+            .iter()
+            .zip(container.persistent_paths())
+            .filter(|(_, directory)| fs::read_dir(directory).is_ok_and(|mut entries| entries.next().is_some()))
+            .map(|(entry, directory)| (directory, orphaned.join(entry)))
+            .collect();
+        if kept.is_empty() {
+            return Ok(None);
+        }
+        if let Some((_, target)) = kept.iter().find(|(_, target)| target.symlink_metadata().is_ok()) {
+            return Err(ContainerError::InvalidPath {
+                path: target.clone(),
+                reason: "still holds data kept from an earlier removal; move it away first".to_string(),
+            });
+        }
+
+        for (directory, target) in kept {
+            if let Some(parent) = target.parent() {
+                filesystem::create_state_dir(parent)?;
+            }
+            filesystem::move_dir(&directory, &target)?;
+        }
+        Ok(Some(orphaned))
+    }
+
+    /// Writes inline scripts to `scripts/.generated/` of the state location with the executable
+    /// bit and removes generated files whose inline definition is gone. Unchanged files are left
+    /// untouched so repeated runs are cheap. Returns the names of scripts that were (re)written.
+    pub fn materialize_inline_scripts(path: &Path, manifest: &ContainerManifest) -> ContainerResult<Vec<String>> {
+        let state = StateLocation::resolve(path);
+        let generated_dir = state.join(GENERATED_SCRIPTS_DIR);
+        let mut written = Vec::new();
+
+        for (script_name, script) in &manifest.scripts {
+            let Some(inline) = script.inline() else {
+                continue;
+            };
+
+            let script_path = state.join(ScriptSpec::generated_path(script_name));
+            let is_current = fs::read(&script_path)
+                .map(|existing| existing == inline.as_bytes())
+                .unwrap_or(false);
+            if is_current {
+                continue;
+            }
+
+            fs::create_dir_all(&generated_dir).map_err(|e| ContainerError::IoError {
+                path: generated_dir.clone(),
+                source: e,
+            })?;
+            filesystem::atomic_write(&script_path, inline.as_bytes(), Some(0o755))?;
+            written.push(script_name.clone());
+        }
+
+        Self::remove_stale_generated_scripts(&generated_dir, manifest)?;
+        written.sort();
+        Ok(written)
+    }
+
+    /// Script files with CRLF line endings, as `(script name, relative path)` sorted by name.
+    /// Inline scripts and missing files are left to other checks.
+    pub fn crlf_scripts(path: &Path, manifest: &ContainerManifest) -> Vec<(String, String)> {
+        let mut found: Vec<(String, String)> = manifest
+            .scripts
+            .iter()
+            .filter(|(_, script)| !script.is_inline())
+            .map(|(name, script)| (name.clone(), script.relative_path(name)))
+            .filter(|(_, relative)| fs::read(path.join(relative)).is_ok_and(|content| portability::has_crlf(&content)))
+            .collect();
+        found.sort();
+        found
+    }
+
+    /// Rewrites script files with CRLF line endings to LF, keeping their mode.
+    /// Returns the names of the scripts that were converted.
+    pub fn convert_scripts_to_lf(path: &Path, manifest: &ContainerManifest) -> ContainerResult<Vec<String>> {
+        let mut converted = Vec::new();
+        for (name, relative) in Self::crlf_scripts(path, manifest) {
+            let script_path = path.join(&relative);
+            let io_error = |e| ContainerError::IoError {
+                path: script_path.clone(),
+                source: e,
+            };
+            let content = fs::read(&script_path).map_err(io_error)?;
+            let mode = fs::metadata(&script_path).map_err(io_error)?.permissions().mode() & 0o7777;
+            filesystem::atomic_write(&script_path, &portability::crlf_to_lf(&content), Some(mode))?;
+            converted.push(name);
+        }
+        Ok(converted)
+    }
+
+    /// Removes materialized scripts that no longer have an inline definition
+    fn remove_stale_generated_scripts(generated_dir: &Path, manifest: &ContainerManifest) -> ContainerResult<()> {
+        if !generated_dir.is_dir() {
+            return Ok(());
+        }
+
+        let entries = fs::read_dir(generated_dir).map_err(|e| ContainerError::IoError {
+            path: generated_dir.to_path_buf(),
+            source: e,
+        })?;
+
+        for entry in entries.flatten() {
+            let file_path = entry.path();
+            let script_name = file_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let still_inline = manifest
+                .scripts
+                .get(&script_name)
+                .is_some_and(ScriptSpec::is_inline);
+
+            if !still_inline {
+                fs::remove_file(&file_path).map_err(|e| ContainerError::IoError {
+                    path: file_path.clone(),
+                    source: e,
+                })?;
+            }
         }
 
         Ok(())
@@ -242,6 +529,235 @@ impl ContainerService {
         Ok(())
     }
 
+    /// Checks every host tool the manifest declares against the current PATH.
+    /// Lets validate and info report exactly which tools are missing or too old.
+    pub fn check_host_requirements(manifest: &ContainerManifest) -> Vec<HostRequirementStatus> {
+        manifest
+            .host_requirements
+            .iter()
+            .map(|requirement| HostRequirementStatus {
+                requirement: requirement.clone(),
+                state: Self::check_host_requirement(requirement),
+            })
+            .collect()
+    }
+
+    /// Validates the dependency tree of `root` against the store, breadth first.
+    /// Each container is visited once per `name@version`, which also stops dependency cycles,
+    /// and chains deeper than the traversal depth limit are reported instead of followed.
+    /// Host requirements of dependencies are only enforced in strict mode.
+    pub fn validate_dependency_tree(
+        root: &Container,
+        resolver: &ContainerResolver,
+        strict: bool,
+    ) -> Vec<DependencyReport> {
+        let mut visited = HashSet::from([format!("{}@{}", root.name(), root.version())]);
+        let limits = TraversalLimits::default();
+        let mut queue = VecDeque::from([(root.clone(), 0)]);
+        let mut reports = Vec::new();
+
+        while let Some((container, depth)) = queue.pop_front() {
+            let mut checks = Vec::new();
+
+            for dependency in &container.manifest.dependencies {
+                let outcome = match resolver.resolve_dependency(dependency) {
+                    None => DependencyOutcome::Missing {
+                        requirement: dependency.version.clone(),
+                        optional: dependency.optional,
+                    },
+                    Some(candidate) => {
+                        let loaded = Self::load_from_directory(&candidate.path).and_then(|loaded| {
+                            if strict {
+                                Self::ensure_host_requirements(&loaded.manifest)?;
+                            }
+                            Ok(loaded)
+                        });
+
+                        match loaded {
+                            Ok(_) if depth + 1 >= limits.max_depth => DependencyOutcome::Invalid {
+                                error: limits.depth_exceeded(&candidate.path),
+                                path: candidate.path,
+                            },
+                            Ok(loaded) => {
+                                let found = loaded.manifest.container_type.clone();
+                                if visited.insert(candidate.selector()) {
+                                    queue.push_back((loaded, depth + 1));
+                                }
+                                if dependency.accepts_type(&found) {
+                                    DependencyOutcome::Valid {
+                                        version: candidate.version,
+                                        path: candidate.path,
+                                    }
+                                } else {
+                                    DependencyOutcome::TypeMismatch {
+                                        version: candidate.version,
+                                        path: candidate.path,
+                                        expected: dependency.expected_type.clone(),
+                                        found,
+                                    }
+                                }
+                            }
+                            Err(error) => DependencyOutcome::Invalid {
+                                path: candidate.path,
+                                error,
+                            },
+                        }
+                    }
+                };
+
+                checks.push(DependencyCheck {
+                    name: dependency.name.clone(),
+                    outcome,
+                });
+            }
+
+            if !checks.is_empty() {
+                reports.push(DependencyReport {
+                    container: format!("{}@{}", container.name(), container.version()),
+                    checks,
+                });
+            }
+        }
+
+        reports
+    }
+
+    /// `root` and every installed container it depends on, transitively, each version once.
+    /// Dependencies that are missing or fail to load are left out; `validate_dependency_tree`
+    /// reports those.
+    pub fn dependency_closure(root: &Container, resolver: &ContainerResolver) -> Vec<Container> {
+        let mut visited = HashSet::from([format!("{}@{}", root.name(), root.version())]);
+        let limits = TraversalLimits::default();
+        let mut queue = VecDeque::from([(root.clone(), 0)]);
+        let mut closure = Vec::new();
+
+        while let Some((container, depth)) = queue.pop_front() {
+            if depth + 1 < limits.max_depth {
+                for dependency in &container.manifest.dependencies {
+                    let Some(candidate) = resolver.resolve_dependency(dependency) else {
+                        continue;
+                    };
+                    if visited.insert(candidate.selector()) {
+                        if let Ok(loaded) = Self::load_from_directory(&candidate.path) {
+                            queue.push_back((loaded, depth + 1));
+                        }
+                    }
+                }
+            }
+            closure.push(container);
+        }
+        closure
+    }
+
+    /// Files of the container split by its ignore rules, sorted by path. An ignored
+    /// directory is listed once, with the size of everything below it.
+    pub fn list_files(container: &Container) -> ContainerResult<FileListing> {
+        let root = &container.path;
+        let rules = IgnoreRules::for_container(root)?;
+        let mut listing = FileListing::default();
+
+        let mut walk = Walk::new(root).follow_root_link();
+        while let Some(entry) = walk.next() {
+            let entry = entry?;
+            if entry.depth == 0 {
+                continue;
+            }
+            let relative = entry.path.strip_prefix(root).unwrap_or(&entry.path);
+            let is_dir = entry.file_type.is_dir();
+            let path = relative.to_string_lossy().into_owned();
+
+            if let Some(rule) = rules.excluded_by(relative, is_dir) {
+                let rule = rule.source.clone();
+                let size = match is_dir {
+                    true => {
+                        walk.skip_current_dir();
+                        filesystem::directory_size(&entry.path)?
+                    }
+                    false => Self::entry_size(&entry.path)?,
+                };
+                listing.ignored.push(IgnoredPath { path, is_dir, size, rule });
+            } else if !is_dir {
+                let size = Self::entry_size(&entry.path)?;
+                listing.included.push(IncludedFile { path, size });
+            }
+        }
+
+        listing.included.sort_by(|a, b| a.path.cmp(&b.path));
+        listing.ignored.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(listing)
+    }
+
+    fn entry_size(path: &Path) -> ContainerResult<u64> {
+        fs::symlink_metadata(path)
+            .map(|metadata| metadata.len())
+            .map_err(|e| ContainerError::IoError {
+                path: path.to_path_buf(),
+                source: e,
+            })
+    }
+
+    /// Fails with a combined report when any host requirement is not satisfied.
+    pub fn ensure_host_requirements(manifest: &ContainerManifest) -> ContainerResult<()> {
+        let unmet: Vec<String> = Self::check_host_requirements(manifest)
+            .iter()
+            .filter(|status| !status.is_satisfied())
+            .map(|status| status.to_string())
+            .collect();
+
+        if unmet.is_empty() {
+            return Ok(());
+        }
+
+        Err(ContainerError::HostRequirementsUnmet {
+            details: unmet.join("; "),
+        })
+    }
+
+    /// Locates a single host command and compares its reported version
+    fn check_host_requirement(requirement: &HostRequirement) -> HostRequirementState {
+        let Some(path) = host::find_executable(&requirement.command) else {
+            return HostRequirementState::Missing;
+        };
+
+        let Some(version_req) = requirement
+            .version_req
+            .as_deref()
+            .and_then(|req| req.parse::<VersionReq>().ok())
+        else {
+            return HostRequirementState::Satisfied { path, version: None };
+        };
+
+        match Self::detect_host_version(&path, &requirement.version_args()) {
+            Some(found) => match version_req.mismatch(&found) {
+                None => HostRequirementState::Satisfied {
+                    path,
+                    version: Some(found),
+                },
+                Some(VersionMismatch::TooOld) => HostRequirementState::TooOld { path, found },
+                Some(VersionMismatch::TooNew) => HostRequirementState::TooNew { path, found },
+            },
+            None => HostRequirementState::VersionUnknown { path },
+        }
+    }
+
+    /// Runs the command with its version arguments and extracts the first version-like token
+    fn detect_host_version(path: &Path, args: &[String]) -> Option<Version> {
+        let output = Command::new(path).args(args).output().ok()?;
+        let text = format!(
+            "{}\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let version_regex = Regex::new(r"(\d+)\.(\d+)(?:\.(\d+))?").ok()?;
+        let captures = version_regex.captures(&text)?;
+        let component = |index: usize| -> Option<u32> {
+            captures.get(index).map_or(Some(0), |m| m.as_str().parse().ok())
+        };
+
+        Version::from_parts(component(1)?, component(2)?, component(3)?).ok()
+    }
+
     /// Detects circular dependencies to prevent infinite dependency loops.
     /// Critical for safe container installation and dependency resolution.
     pub fn check_circular_dependencies(
@@ -254,6 +770,10 @@ impl ContainerService {
                 chain: visited.join(" -> "),
             });
         }
+        let limits = TraversalLimits::default();
+        if visited.len() >= limits.max_depth {
+            return Err(limits.depth_exceeded(Path::new(current)));
+        }
 
         if let Some(container) = containers.get(current) {
             visited.push(current.to_string());
@@ -292,8 +812,77 @@ impl Container {
         self.runtime.status == ContainerStatus::Running
     }
 
-    /// Resolves script name to absolute filesystem path for execution.
+    /// Corrects a `Running` state whose process is gone, e.g. because wrappy was killed
+    /// before it could record the end of the run. The exit code of such a run is unknown.
+    /// Returns the pid of the stale record, after saving the corrected state.
+    pub fn clear_stale_run(&mut self) -> ContainerResult<Option<u32>> {
+        if !self.is_running() || self.runtime.is_process_alive() {
+            return Ok(None);
+        }
+        let pid = self.runtime.pid.take();
+        self.runtime.status = ContainerStatus::Stopped;
+        self.runtime.process_start_time = None;
+        self.runtime.exit_code = None;
+        self.save_runtime()?;
+        Ok(pid)
+    }
+
+    /// Stops the recorded run with SIGTERM, then SIGKILL if it is still alive after `timeout`.
+    /// A `container run` still waiting for the process records its exit code itself; without
+    /// one the run is recorded as ended by the signal. A reused pid is never signalled.
+    pub fn stop_run(&mut self, timeout: Duration) -> ContainerResult<RunStop> {
+        if let Some(pid) = self.clear_stale_run()? {
+            return Ok(RunStop::Stale(pid));
+        }
+        let (true, Some(pid)) = (self.is_running(), self.runtime.pid) else {
+            return Ok(RunStop::NotRunning);
+        };
+
+        signal_process(pid, libc::SIGTERM);
+        let started = Instant::now();
+        let mut killed = false;
+        while self.runtime.is_process_alive() {
+            let waited = started.elapsed();
+            if !killed && waited >= timeout {
+                signal_process(pid, libc::SIGKILL);
+                killed = true;
+            } else if killed && waited >= timeout + STOP_KILL_PERIOD {
+                return Err(ContainerError::Runtime {
+                    message: format!("process {} did not exit after SIGKILL", pid),
+                });
+            }
+            thread::sleep(STOP_POLL_INTERVAL);
+        }
+
+        let signal = if killed { libc::SIGKILL } else { libc::SIGTERM };
+        let recorded = Instant::now();
+        loop {
+            ContainerService::load_runtime(self);
+            if self.runtime.pid != Some(pid) || !self.is_running() {
+                break;
+            }
+            if recorded.elapsed() >= STOP_RECORD_PERIOD {
+                self.mark_stopped(128 + signal);
+                self.save_runtime()?;
+                break;
+            }
+            thread::sleep(STOP_POLL_INTERVAL);
+        }
+        Ok(RunStop::Stopped {
+            pid,
+            killed,
+            exit_code: self.runtime.exit_code,
+        })
+    }
+
+    /// Resolves script name to a path that is executable at call time,
+    /// so a script deleted or chmod-ed after load is reported instead of failing at spawn.
     pub fn get_script_path(&self, script_name: &str) -> ContainerResult<PathBuf> {
+        ContainerService::resolve_script_path(&self.path, &self.manifest, script_name)
+    }
+
+    /// Joins the script path without touching the filesystem.
+    pub fn get_script_path_unchecked(&self, script_name: &str) -> ContainerResult<PathBuf> {
         let script_relative_path = self.manifest.get_script(script_name)?;
         Ok(self.path.join(script_relative_path))
     }
@@ -302,9 +891,22 @@ impl Container {
         self.get_script_path("default")
     }
 
+    /// Writes the runtime and the last access time to `runtime.json` in the state location,
+    /// atomically, so the next wrappy process sees how the last run went.
+    pub fn save_runtime(&self) -> ContainerResult<()> {
+        let record = RuntimeRecord {
+            runtime: self.runtime.clone(),
+            last_accessed: Some(self.last_accessed),
+        };
+        let mut content = serde_json::to_vec_pretty(&record)?;
+        content.push(b'\n');
+        let path = StateLocation::writable(&self.path)?.join(RUNTIME_FILE);
+        filesystem::atomic_write(&path, &content, Some(filesystem::STATE_FILE_MODE))
+    }
+
     /// Updates access timestamp for usage tracking and cleanup decisions.
     pub fn update_last_accessed(&mut self) {
-        self.last_accessed = Utc::now();
+        self.last_accessed = clock::now();
     }
 
     /// Updates runtime state when container execution begins.
@@ -312,7 +914,8 @@ impl Container {
     pub fn mark_running(&mut self, pid: u32) {
         self.runtime.status = ContainerStatus::Running;
         self.runtime.pid = Some(pid);
-        self.runtime.started_at = Some(Utc::now());
+        self.runtime.process_start_time = host::process_start_time(pid);
+        self.runtime.started_at = Some(clock::now());
         self.update_last_accessed();
     }
 
@@ -321,7 +924,8 @@ impl Container {
     pub fn mark_stopped(&mut self, exit_code: i32) {
         self.runtime.status = ContainerStatus::Stopped;
         self.runtime.pid = None;
-        self.runtime.stopped_at = Some(Utc::now());
+        self.runtime.process_start_time = None;
+        self.runtime.stopped_at = Some(clock::now());
         self.runtime.exit_code = Some(exit_code);
     }
 
@@ -330,7 +934,7 @@ impl Container {
     pub fn mark_error(&mut self, error: String) {
         self.runtime.status = ContainerStatus::Error;
         self.runtime.errors.push(error);
-        self.runtime.stopped_at = Some(Utc::now());
+        self.runtime.stopped_at = Some(clock::now());
     }
 
     pub fn content_path(&self) -> PathBuf {
@@ -345,6 +949,15 @@ impl Container {
         self.path.join("scripts")
     }
 
+    /// Absolute paths of the directories declared as persistent in the manifest.
+    pub fn persistent_paths(&self) -> Vec<PathBuf> {
+        self.manifest
+            .persistent
+            .iter()
+            .map(|entry| self.path.join(entry))
+            .collect()
+    }
+
     /// Validates dependencies using service
     pub fn validate_dependencies(&self, available_packages: &HashMap<String, Version>) -> ContainerResult<()> {
         ContainerService::validate_dependencies(self, available_packages)
@@ -367,3 +980,277 @@ impl Container {
         serde_json::from_str(json).map_err(|e| ContainerError::JsonError { source: e })
     }
 }
+
+fn signal_process(pid: u32, signal: libc::c_int) {
+    // SAFETY: kill has no memory-safety preconditions; callers checked the pid is still the run.
+    unsafe {
+        libc::kill(pid as libc::pid_t, signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, write_executable, TempHome};
+    use crate::shared::traversal::DEFAULT_MAX_DEPTH;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    fn manifest_requiring(requirements: serde_json::Value) -> ContainerManifest {
+        serde_json::from_value(json!({ "name": "demo", "version": "1.0.0", "host_requirements": requirements })).unwrap()
+    }
+
+    /// A `faketool` reporting version 2.5.1, first on PATH while `home` lives.
+    fn install_fake_tool(home: &TempHome) {
+        let bin = home.path().join("host-bin");
+        write_executable(&bin.join("faketool"), "#!/bin/sh\necho 'faketool version 2.5.1'\n");
+        home.prepend_path(&bin);
+    }
+
+    fn state_of(requirement: serde_json::Value) -> HostRequirementState {
+        let manifest = manifest_requiring(json!([requirement]));
+        ContainerService::check_host_requirements(&manifest).remove(0).state
+    }
+
+    #[test]
+    fn host_requirements_report_missing_old_new_and_satisfied_tools() {
+        let home = TempHome::enter();
+        install_fake_tool(&home);
+
+        assert_matches!(state_of(json!({ "command": "wrappy-test-no-such-tool" })), HostRequirementState::Missing);
+        assert_matches!(
+            state_of(json!({ "command": "faketool", "version_req": ">=2.0" })),
+            HostRequirementState::Satisfied { version: Some(version), .. } if version.as_str() == "2.5.1"
+        );
+        assert_matches!(
+            state_of(json!({ "command": "faketool", "version_req": ">=3" })),
+            HostRequirementState::TooOld { .. }
+        );
+        assert_matches!(
+            state_of(json!({ "command": "faketool", "version_req": "<2" })),
+            HostRequirementState::TooNew { .. }
+        );
+    }
+
+    #[test]
+    fn ensure_host_requirements_names_every_unmet_tool() {
+        let home = TempHome::enter();
+        install_fake_tool(&home);
+        let manifest = manifest_requiring(json!([
+            { "command": "faketool", "version_req": "<2" },
+            { "command": "faketool", "version_req": ">=2" },
+            { "command": "wrappy-test-no-such-tool" }
+        ]));
+
+        let error = ContainerService::ensure_host_requirements(&manifest).unwrap_err();
+
+        let ContainerError::HostRequirementsUnmet { details } = error else {
+            panic!("unexpected error: {}", error);
+        };
+        assert!(details.contains("newer than allowed"), "{}", details);
+        assert!(details.contains("wrappy-test-no-such-tool"), "{}", details);
+        assert!(!details.contains(">=2"), "{}", details);
+    }
+
+    fn container_with_data(home: &TempHome) -> Container {
+        let dir = home.path().join("app");
+        let mut manifest = fixture_manifest("app", "1.0.0");
+        manifest["persistent"] = json!(["data", "cache"]);
+        fixture_container_at(&dir, manifest);
+        fs::create_dir_all(dir.join("data/sub")).unwrap();
+        fs::write(dir.join("data/sub/state"), "kept").unwrap();
+        fs::create_dir_all(dir.join("cache")).unwrap();
+        ContainerService::load_from_directory(&dir).unwrap()
+    }
+
+    #[test]
+    fn orphaning_moves_the_persistent_directories_that_hold_anything() {
+        let home = TempHome::enter();
+        let container = container_with_data(&home);
+
+        let kept = ContainerService::orphan_persistent_data(&container, "app", &home.paths()).unwrap();
+
+        let orphaned = home.paths().orphaned_data_dir("app");
+        assert_eq!(kept, Some(orphaned.clone()));
+        assert_eq!(fs::read_to_string(orphaned.join("data/sub/state")).unwrap(), "kept");
+        assert!(!orphaned.join("cache").exists(), "empty directories are not kept");
+        assert!(!container.path.join("data").exists());
+    }
+
+    #[test]
+    fn orphaning_refuses_to_mix_with_data_of_an_earlier_removal() {
+        let home = TempHome::enter();
+        let container = container_with_data(&home);
+        let earlier = home.paths().orphaned_data_dir("app").join("data");
+        fs::create_dir_all(&earlier).unwrap();
+
+        let result = ContainerService::orphan_persistent_data(&container, "app", &home.paths());
+
+        assert_matches!(result, Err(ContainerError::InvalidPath { path, .. }) if path == earlier);
+        assert!(container.path.join("data/sub/state").is_file());
+    }
+
+    #[test]
+    fn nothing_is_orphaned_without_data() {
+        let home = TempHome::enter();
+        let container = container_with_data(&home);
+        fs::remove_dir_all(container.path.join("data")).unwrap();
+
+        assert_eq!(ContainerService::orphan_persistent_data(&container, "app", &home.paths()).unwrap(), None);
+    }
+
+    /// Containers `c0` to `c<count-1>` where each depends on the next; `c<last>` on `c<back_to>` when given.
+    fn dependency_chain(home: &TempHome, count: usize, back_to: Option<usize>) -> HashMap<String, Container> {
+        let dir = home.path().join("chain");
+        fixture_container_at(&dir, fixture_manifest("c0", "1.0.0"));
+        (0..count)
+            .map(|index| {
+                let next = match index + 1 {
+                    next if next < count => Some(next),
+                    _ => back_to,
+                };
+                let mut manifest = fixture_manifest(&format!("c{}", index), "1.0.0");
+                manifest["dependencies"] = json!(next.map(|next| vec![json!({ "name": format!("c{}", next), "version": "1.0.0" })]).unwrap_or_default());
+                let container = ContainerService::create_container(serde_json::from_value(manifest).unwrap(), dir.clone()).unwrap();
+                (format!("c{}", index), container)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_dependency_chain_deeper_than_the_limit_is_refused_and_a_cycle_named() {
+        let home = TempHome::enter();
+
+        let deep = dependency_chain(&home, DEFAULT_MAX_DEPTH + 10, None);
+        let cyclic = dependency_chain(&home, 3, Some(0));
+        let fine = dependency_chain(&home, 10, None);
+
+        let deep_result = ContainerService::check_circular_dependencies(&deep, &mut Vec::new(), "c0");
+        assert_matches!(deep_result, Err(ContainerError::TraversalLimit { .. }));
+        let cyclic_result = ContainerService::check_circular_dependencies(&cyclic, &mut Vec::new(), "c0");
+        assert_matches!(cyclic_result, Err(ContainerError::CircularDependency { chain }) if chain == "c0 -> c1 -> c2");
+        assert!(ContainerService::check_circular_dependencies(&fine, &mut Vec::new(), "c0").is_ok());
+    }
+
+    #[test]
+    fn every_consumer_leaves_out_what_wrappyignore_lists() {
+        use crate::features::container::{ExportService, SealService};
+        use crate::features::store::ObjectStore;
+        use crate::shared::archive::TarReader;
+        use crate::shared::cancellation::CancellationToken;
+        use std::os::unix::fs::MetadataExt;
+
+        let home = TempHome::enter();
+        let root = home.path().join("app");
+        fixture_container_at(&root, fixture_manifest("app", "1.0.0"));
+        fs::write(root.join(".wrappyignore"), "# build output\n*.log\nbuild/\n!content/keep.log\n").unwrap();
+        for (path, content) in [("content/run.log", "log"), ("content/keep.log", "kept"), ("build/out/bin", "binary"), (".git/HEAD", "ref")] {
+            fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+            fs::write(root.join(path), content).unwrap();
+        }
+        let container = ContainerService::load_from_directory(&root).unwrap();
+        let ignored = ["content/run.log", "build/out/bin", ".git/HEAD"];
+
+        let listing = ContainerService::list_files(&container).unwrap();
+        let (archive, _) = ExportService::write_archive(&container, Vec::new(), true, false, None).unwrap();
+        let mut reader = TarReader::new(archive.as_slice());
+        let mut archived = Vec::new();
+        while let Some(header) = reader.next_entry().unwrap() {
+            archived.push(header.path);
+        }
+        let seal = SealService::seal(&container, &CancellationToken::new()).unwrap();
+        ObjectStore::at(home.path().join("objects"), home.path().to_path_buf()).dedup_container(&container).unwrap();
+
+        let listed_ignored: Vec<(&str, &str)> = listing.ignored.iter().map(|path| (path.path.as_str(), path.rule.as_str())).collect();
+        assert_eq!(listed_ignored, [(".git", ".git/"), ("build", "build/"), ("content/run.log", "*.log")]);
+        assert!(listing.included.iter().any(|file| file.path == "content/keep.log"));
+        assert!(archived.iter().any(|path| path.ends_with("content/keep.log")), "{:?}", archived);
+        for path in ignored {
+            assert!(!archived.iter().any(|archived| archived.ends_with(path)), "{} exported", path);
+            assert!(!seal.files.contains_key(path), "{} sealed", path);
+            assert_eq!(fs::metadata(root.join(path)).unwrap().nlink(), 1, "{} deduplicated", path);
+        }
+        assert!(seal.files.contains_key("content/keep.log"));
+        assert_eq!(fs::metadata(root.join("content/keep.log")).unwrap().nlink(), 2);
+    }
+
+    /// Starts `script` under `sh` as the recorded run of a fresh container. A thread reaps the
+    /// process once it exits, as a waiting `container run` would, so it does not linger as a zombie.
+    fn running(home: &TempHome, script: &str) -> (Container, u32, thread::JoinHandle<()>) {
+        let dir = home.path().join("app");
+        fixture_container_at(&dir, fixture_manifest("app", "1.0.0"));
+        let mut container = ContainerService::load_from_directory(&dir).unwrap();
+        let mut child = Command::new("sh").arg("-c").arg(script).spawn().unwrap();
+        let pid = child.id();
+        let reaper = thread::spawn(move || {
+            child.wait().unwrap();
+        });
+        while host::process_start_time(pid).is_none() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        container.mark_running(pid);
+        container.save_runtime().unwrap();
+        (container, pid, reaper)
+    }
+
+    fn recorded(container: &Container) -> (ContainerStatus, Option<u32>, Option<i32>) {
+        let mut reloaded = ContainerService::load_from_directory(&container.path).unwrap();
+        ContainerService::load_runtime(&mut reloaded);
+        (reloaded.runtime.status, reloaded.runtime.pid, reloaded.runtime.exit_code)
+    }
+
+    #[test]
+    fn a_run_that_honours_sigterm_stops_gracefully_and_records_the_signal() {
+        let home = TempHome::enter();
+        let (mut container, pid, reaper) = running(&home, "exec sleep 30");
+
+        let stop = container.stop_run(Duration::from_secs(5)).unwrap();
+
+        reaper.join().unwrap();
+        assert_matches!(stop, RunStop::Stopped { pid: stopped, killed: false, exit_code: Some(143) } if stopped == pid);
+        assert_eq!(recorded(&container), (ContainerStatus::Stopped, None, Some(128 + libc::SIGTERM)));
+    }
+
+    #[test]
+    fn a_run_that_ignores_sigterm_is_killed_after_the_timeout() {
+        let home = TempHome::enter();
+        // The ignored disposition survives exec, so sleep itself ignores TERM
+        let (mut container, pid, reaper) = running(&home, "trap '' TERM; exec sleep 30");
+
+        let started = Instant::now();
+        let stop = container.stop_run(Duration::from_millis(300)).unwrap();
+
+        reaper.join().unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        assert_matches!(stop, RunStop::Stopped { pid: stopped, killed: true, exit_code: Some(137) } if stopped == pid);
+        assert_eq!(recorded(&container), (ContainerStatus::Stopped, None, Some(128 + libc::SIGKILL)));
+    }
+
+    #[test]
+    fn stopping_a_container_without_a_run_changes_nothing() {
+        let home = TempHome::enter();
+        let dir = home.path().join("app");
+        fixture_container_at(&dir, fixture_manifest("app", "1.0.0"));
+        let mut container = ContainerService::load_from_directory(&dir).unwrap();
+
+        assert_matches!(container.stop_run(Duration::from_secs(1)).unwrap(), RunStop::NotRunning);
+        assert_eq!(recorded(&container), (ContainerStatus::Ready, None, None));
+    }
+
+    #[test]
+    fn a_reused_pid_is_cleared_as_stale_and_never_signalled() {
+        let home = TempHome::enter();
+        let (mut container, pid, reaper) = running(&home, "exec sleep 30");
+        // Same pid, different start time: the recorded run ended and another process took the pid
+        container.runtime.process_start_time = container.runtime.process_start_time.map(|start| start + 1);
+        container.save_runtime().unwrap();
+
+        let stop = container.stop_run(Duration::from_millis(100)).unwrap();
+
+        assert_matches!(stop, RunStop::Stale(stale) if stale == pid);
+        assert!(host::process_start_time(pid).is_some(), "the unrelated process was signalled");
+        assert_eq!(recorded(&container), (ContainerStatus::Stopped, None, None));
+        signal_process(pid, libc::SIGKILL);
+        reaper.join().unwrap();
+    }
+}
@@ -0,0 +1,265 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::features::container::Executor;
+use crate::shared::clock;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::host;
+use crate::shared::lock_file::LockFile;
+use crate::shared::paths::WrappyPaths;
+
+const STATE_FILE: &str = "service.json";
+const ACTIVITY_FILE: &str = "last-activity";
+const LOCK_FILE: &str = "start.lock";
+const LOG_FILE: &str = "service.log";
+
+const LOCK_WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Grace period between SIGTERM and SIGKILL when stopping a service.
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Persisted description of a running service.
+/// The process start time guards against the pid being reused after the service died.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceState {
+    pub pid: u32,
+    pub process_start_time: u64,
+    pub script: String,
+    pub container_path: PathBuf,
+    pub idle_timeout_secs: u64,
+    pub started_at: DateTime<Utc>,
+}
+
+impl ServiceState {
+    /// Alive means the pid exists and is still the process we started.
+    pub fn is_alive(&self) -> bool {
+        host::process_start_time(self.pid) == Some(self.process_start_time)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServiceStart {
+    AlreadyRunning(u32),
+    Started(u32),
+}
+
+/// Run directory of one container's lazily started service:
+/// `~/.local/share/wrappy/run/<container>/`.
+pub struct ServiceRuntime {
+    container_name: String,
+    dir: PathBuf,
+}
+
+impl ServiceRuntime {
+    pub fn new(paths: &WrappyPaths, container_name: &str) -> Self {
+        Self {
+            container_name: container_name.to_string(),
+            dir: paths.services_run_dir().join(container_name),
+        }
+    }
+
+    /// Every container that has a run directory, running or not.
+    pub fn all(paths: &WrappyPaths) -> Vec<Self> {
+        let Ok(entries) = fs::read_dir(paths.services_run_dir()) else {
+            return Vec::new();
+        };
+
+        let mut runtimes: Vec<Self> = entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .map(|name| Self::new(paths, &name))
+            .collect();
+        runtimes.sort_by(|a, b| a.container_name.cmp(&b.container_name));
+        runtimes
+    }
+
+    pub fn container_name(&self) -> &str {
+        &self.container_name
+    }
+
+    pub fn log_file(&self) -> PathBuf {
+        self.dir.join(LOG_FILE)
+    }
+
+    /// State of the running service; a stale state file is removed on the way.
+    pub fn running(&self) -> Option<ServiceState> {
+        let content = fs::read_to_string(self.dir.join(STATE_FILE)).ok()?;
+        match serde_json::from_str::<ServiceState>(&content) {
+            Ok(state) if state.is_alive() => Some(state),
+            _ => {
+                let _ = fs::remove_file(self.dir.join(STATE_FILE));
+                None
+            }
+        }
+    }
+
+    /// Records a use of the service; idle-reaper measures idleness from this stamp.
+    pub fn touch_activity(&self) -> ContainerResult<()> {
+        self.ensure_dir()?;
+        let path = self.dir.join(ACTIVITY_FILE);
+        let file = filesystem::state_file_options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| io_error(&path, e))?;
+        file.set_modified(clock::system_now()).map_err(|e| io_error(&path, e))
+    }
+
+    pub fn last_activity(&self) -> Option<SystemTime> {
+        fs::metadata(self.dir.join(ACTIVITY_FILE)).ok()?.modified().ok()
+    }
+
+    /// Starts `script` detached unless the service already runs.
+    /// Concurrent starters serialize on a lock file, so simultaneous first uses start it once.
+    pub fn start(&self, executor: &Executor, script: &str, idle_timeout_secs: u64) -> ContainerResult<ServiceStart> {
+        self.touch_activity()?;
+        if let Some(state) = self.running() {
+            return Ok(ServiceStart::AlreadyRunning(state.pid));
+        }
+
+        let Some(_lock) = self.acquire_start_lock()? else {
+            // Another starter held the lock until it finished; use whatever it started
+            return match self.running() {
+                Some(state) => Ok(ServiceStart::AlreadyRunning(state.pid)),
+                None => Err(ContainerError::Runtime {
+                    message: format!("service of '{}' did not start", self.container_name),
+                }),
+            };
+        };
+
+        if let Some(state) = self.running() {
+            return Ok(ServiceStart::AlreadyRunning(state.pid));
+        }
+
+        let pid = executor.spawn_detached(script, &self.log_file())?;
+        let state = ServiceState {
+            pid,
+            process_start_time: host::process_start_time(pid).unwrap_or_default(),
+            script: script.to_string(),
+            container_path: executor.container().path.clone(),
+            idle_timeout_secs,
+            started_at: clock::now(),
+        };
+        let content = serde_json::to_vec_pretty(&state)?;
+        filesystem::atomic_write(&self.dir.join(STATE_FILE), &content, Some(filesystem::STATE_FILE_MODE))?;
+
+        Ok(ServiceStart::Started(pid))
+    }
+
+    /// Stops the running service: SIGTERM to its process group, SIGKILL after a grace period.
+    /// Returns the pid that was stopped, or `None` when nothing was running.
+    pub fn stop(&self) -> ContainerResult<Option<u32>> {
+        self.stop_within(STOP_GRACE_PERIOD)
+    }
+
+    /// Like `stop`, with `grace` instead of the default grace period before SIGKILL.
+    pub fn stop_within(&self, grace: Duration) -> ContainerResult<Option<u32>> {
+        let Some(state) = self.running() else {
+            return Ok(None);
+        };
+
+        signal_group(state.pid, libc::SIGTERM);
+        let mut waited = Duration::ZERO;
+        while state.is_alive() && waited < grace {
+            thread::sleep(STOP_POLL_INTERVAL);
+            waited += STOP_POLL_INTERVAL;
+        }
+        if state.is_alive() {
+            signal_group(state.pid, libc::SIGKILL);
+        }
+
+        let state_file = self.dir.join(STATE_FILE);
+        if state_file.exists() {
+            fs::remove_file(&state_file).map_err(|e| io_error(&state_file, e))?;
+        }
+        Ok(Some(state.pid))
+    }
+
+    /// Seconds since the last recorded use, if any use was recorded.
+    pub fn idle_for(&self) -> Option<Duration> {
+        clock::system_now().duration_since(self.last_activity()?).ok()
+    }
+
+    /// Returns `None` when another process held the lock and released it while we waited.
+    fn acquire_start_lock(&self) -> ContainerResult<Option<LockFile>> {
+        let path = self.dir.join(LOCK_FILE);
+        let what = format!("starting '{}'", self.container_name);
+        let mut waited = Duration::ZERO;
+
+        loop {
+            if let Some(lock) = LockFile::try_acquire(&path, &what)? {
+                return Ok(Some(lock));
+            }
+            if waited >= LOCK_WAIT_TIMEOUT {
+                let holder = LockFile::holder(&path).map(|holder| format!(" (held by {})", holder)).unwrap_or_default();
+                return Err(ContainerError::Runtime {
+                    message: format!("timed out waiting for another start of '{}'{}", self.container_name, holder),
+                });
+            }
+            thread::sleep(LOCK_POLL_INTERVAL);
+            waited += LOCK_POLL_INTERVAL;
+
+            if !path.exists() {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn ensure_dir(&self) -> ContainerResult<()> {
+        filesystem::create_state_dir(&self.dir)
+    }
+}
+
+
+fn signal_group(pid: u32, signal: libc::c_int) {
+    // SAFETY: kill has no memory-safety preconditions; services run in their own process group.
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), signal);
+    }
+}
+
+fn io_error(path: &Path, source: io::Error) -> ContainerError {
+    ContainerError::IoError {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::clock::MockClock;
+    use crate::shared::test_support::TempHome;
+    use chrono::TimeZone;
+    use std::sync::Arc;
+
+    #[test]
+    fn idleness_is_measured_against_the_clock() {
+        let home = TempHome::enter();
+        let service = ServiceRuntime::new(&home.paths(), "web");
+        let clock = Arc::new(MockClock::new(chrono::Utc.timestamp_opt(1_700_000_000, 0).unwrap()));
+        clock::set_clock(clock.clone());
+
+        let never_used = service.idle_for();
+        service.touch_activity().unwrap();
+        let just_used = service.idle_for();
+        clock.advance(chrono::Duration::days(90));
+        let after_90_days = service.idle_for();
+        clock::reset_clock();
+
+        assert_eq!(never_used, None);
+        assert_eq!(just_used, Some(Duration::ZERO));
+        assert_eq!(after_90_days, Some(Duration::from_secs(90 * 24 * 60 * 60)));
+    }
+}
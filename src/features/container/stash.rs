@@ -0,0 +1,304 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::features::container::{Container, StateLocation, RUNTIME_FILE};
+use crate::features::history::{RunHistory, RunRecord};
+use crate::features::query;
+use crate::features::Version;
+use crate::shared::clock;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+
+const METADATA_FILE: &str = "stash.json";
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// What a stash holds besides the files themselves.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashMetadata {
+    pub name: String,
+    pub version: Version,
+    pub stashed_at: DateTime<Utc>,
+    pub has_runtime: bool,
+    pub history_records: usize,
+    /// Manifest targets of the bindings that were active when stashing
+    pub active_bindings: Vec<String>,
+}
+
+/// Outcome of putting a stash back onto a reinstalled container.
+#[derive(Debug, Clone)]
+pub struct RestoredState {
+    pub metadata: StashMetadata,
+    /// History records that were missing from the run history and appended again
+    pub history_records_restored: usize,
+}
+
+/// Runtime state of one container kept aside while it is removed, so a
+/// reinstall for troubleshooting keeps its runtime id, run history and bindings.
+pub struct StateStash {
+    name: String,
+    dir: PathBuf,
+    history: RunHistory,
+}
+
+impl StateStash {
+    pub fn for_container(paths: &WrappyPaths, name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            dir: paths.stash_dir().join(name),
+            history: RunHistory::new(paths),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn exists(&self) -> bool {
+        self.dir.join(METADATA_FILE).is_file()
+    }
+
+    pub fn metadata(&self) -> ContainerResult<StashMetadata> {
+        let path = self.dir.join(METADATA_FILE);
+        let content = fs::read_to_string(&path).map_err(|e| ContainerError::IoError {
+            path: path.clone(),
+            source: e,
+        })?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Copies the container's state into the stash, replacing an older stash of the same name.
+    pub fn stash(&self, container: &Container) -> ContainerResult<StashMetadata> {
+        self.discard()?;
+        filesystem::create_state_dir(&self.dir)?;
+
+        let runtime = StateLocation::resolve(&container.path).join(RUNTIME_FILE);
+        let has_runtime = runtime.is_file();
+        if has_runtime {
+            let content = fs::read(&runtime).map_err(|e| ContainerError::IoError {
+                path: runtime.clone(),
+                source: e,
+            })?;
+            filesystem::atomic_write(&self.dir.join(RUNTIME_FILE), &content, Some(filesystem::STATE_FILE_MODE))?;
+        }
+
+        let records = self.own_history()?;
+        let mut lines = String::new();
+        for record in &records {
+            lines.push_str(&serde_json::to_string(record)?);
+            lines.push('\n');
+        }
+        filesystem::atomic_write(&self.dir.join(HISTORY_FILE), lines.as_bytes(), Some(filesystem::STATE_FILE_MODE))?;
+
+        let metadata = StashMetadata {
+            name: self.name.clone(),
+            version: container.manifest.version.clone(),
+            stashed_at: clock::now(),
+            has_runtime,
+            history_records: records.len(),
+            active_bindings: query::bindings_of(&container.path.to_string_lossy())
+                .into_iter()
+                .filter(|binding| binding.active)
+                .map(|binding| binding.target)
+                .collect(),
+        };
+        let content = serde_json::to_vec_pretty(&metadata)?;
+        filesystem::atomic_write(&self.dir.join(METADATA_FILE), &content, Some(filesystem::STATE_FILE_MODE))?;
+        Ok(metadata)
+    }
+
+    /// Puts the stash back onto `container` and deletes it.
+    /// State from another major version or a newer release than the one reinstalled
+    /// is refused unless `allow_mismatch` is set, since its runtime may not fit.
+    pub fn restore(&self, container: &Container, allow_mismatch: bool) -> ContainerResult<RestoredState> {
+        let metadata = self.metadata()?;
+        let installed = &container.manifest.version;
+        if !allow_mismatch && !installed.is_compatible_with(&metadata.version) {
+            return Err(ContainerError::VersionConflict {
+                conflict: format!(
+                    "stashed state of '{}' is from {}, reinstalled version is {}",
+                    self.name, metadata.version, installed
+                ),
+            });
+        }
+
+        if metadata.has_runtime {
+            let content = fs::read(self.dir.join(RUNTIME_FILE)).map_err(|e| ContainerError::IoError {
+                path: self.dir.join(RUNTIME_FILE),
+                source: e,
+            })?;
+            let runtime = StateLocation::writable(&container.path)?.join(RUNTIME_FILE);
+            filesystem::atomic_write(&runtime, &content, Some(filesystem::STATE_FILE_MODE))?;
+        }
+
+        let history_records_restored = self.restore_history()?;
+        self.discard()?;
+
+        Ok(RestoredState {
+            metadata,
+            history_records_restored,
+        })
+    }
+
+    pub fn discard(&self) -> ContainerResult<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir).map_err(|e| ContainerError::IoError {
+                path: self.dir.clone(),
+                source: e,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Deletes stashes older than `max_age` and returns the container names they belonged to.
+    /// Unreadable stashes are left alone so a half-written one is never lost to a race.
+    pub fn prune(paths: &WrappyPaths, max_age: Duration) -> ContainerResult<Vec<String>> {
+        let Ok(entries) = fs::read_dir(paths.stash_dir()) else {
+            return Ok(Vec::new());
+        };
+
+        let cutoff = clock::now() - max_age;
+        let mut pruned = Vec::new();
+        for name in entries.flatten().filter_map(|entry| entry.file_name().into_string().ok()) {
+            let stash = Self::for_container(paths, &name);
+            if stash.metadata().is_ok_and(|metadata| metadata.stashed_at < cutoff) {
+                stash.discard()?;
+                pruned.push(name);
+            }
+        }
+        pruned.sort();
+        Ok(pruned)
+    }
+
+    fn own_history(&self) -> ContainerResult<Vec<RunRecord>> {
+        Ok(self
+            .history
+            .read_all()?
+            .records
+            .into_iter()
+            .filter(|record| record.container == self.name)
+            .collect())
+    }
+
+    /// Appends stashed records the run history no longer has, e.g. after it was purged.
+    fn restore_history(&self) -> ContainerResult<usize> {
+        let path = self.dir.join(HISTORY_FILE);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Ok(0);
+        };
+
+        let present: HashSet<(DateTime<Utc>, String)> = self
+            .own_history()?
+            .into_iter()
+            .map(|record| (record.started_at, record.script))
+            .collect();
+
+        let mut restored = 0;
+        for record in content.lines().filter_map(|line| serde_json::from_str::<RunRecord>(line).ok()) {
+            if !present.contains(&(record.started_at, record.script.clone())) {
+                self.history.append(&record)?;
+                restored += 1;
+            }
+        }
+        Ok(restored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::ContainerService;
+    use crate::shared::clock::MockClock;
+    use crate::shared::test_support::{fixture_container, TempHome};
+    use chrono::TimeZone;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    fn record(script: &str, seconds: i64) -> RunRecord {
+        RunRecord {
+            container: "web".to_string(),
+            script: script.to_string(),
+            started_at: Utc.timestamp_opt(1_700_000_000 + seconds, 0).unwrap(),
+            duration_ms: 5,
+            exit_code: 0,
+            output_mode: None,
+            output_file: None,
+        }
+    }
+
+    fn installed(root: &Path, version: &str) -> Container {
+        ContainerService::load_from_directory(fixture_container(root, "web", version)).unwrap()
+    }
+
+    #[test]
+    fn restore_keeps_the_runtime_id_and_puts_purged_history_back() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        let history = RunHistory::new(&paths);
+        history.append(&record("default", 0)).unwrap();
+        history.append(&record("build", 1)).unwrap();
+        let container = installed(home.path(), "1.2.0");
+        container.save_runtime().unwrap();
+        let stash = StateStash::for_container(&paths, "web");
+
+        let metadata = stash.stash(&container).unwrap();
+        fs::remove_dir_all(&container.path).unwrap();
+        fs::remove_file(paths.history_file()).unwrap();
+        history.append(&record("default", 0)).unwrap();
+        let reinstalled = installed(home.path(), "1.2.0");
+        assert_ne!(reinstalled.runtime.id, container.runtime.id);
+        let restored = stash.restore(&reinstalled, false).unwrap();
+
+        assert!(metadata.has_runtime);
+        assert_eq!(metadata.history_records, 2);
+        assert_eq!(restored.history_records_restored, 1);
+        let reloaded = ContainerService::load_from_directory(&reinstalled.path).unwrap();
+        assert_eq!(reloaded.runtime.id, container.runtime.id);
+        let scripts: Vec<String> = history.read_all().unwrap().records.into_iter().map(|r| r.script).collect();
+        assert_eq!(scripts, ["default", "build"]);
+        assert!(!stash.exists());
+    }
+
+    #[test]
+    fn state_from_another_major_version_is_only_restored_when_allowed() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        let container = installed(home.path(), "1.4.0");
+        container.save_runtime().unwrap();
+        let stash = StateStash::for_container(&paths, "web");
+        stash.stash(&container).unwrap();
+        let reinstalled = installed(home.path(), "2.0.0");
+
+        let refused = stash.restore(&reinstalled, false);
+
+        assert!(matches!(refused, Err(ContainerError::VersionConflict { .. })));
+        assert!(stash.exists());
+        stash.restore(&reinstalled, true).unwrap();
+        let reloaded = ContainerService::load_from_directory(&reinstalled.path).unwrap();
+        assert_eq!(reloaded.runtime.id, container.runtime.id);
+    }
+
+    #[test]
+    fn prune_removes_only_stashes_older_than_the_max_age() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        let clock = Arc::new(MockClock::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap()));
+        clock::set_clock(clock.clone());
+        let old = installed(home.path(), "1.0.0");
+        StateStash::for_container(&paths, "web").stash(&old).unwrap();
+        clock.advance(Duration::days(20));
+        let recent = ContainerService::load_from_directory(fixture_container(home.path(), "api", "1.0.0")).unwrap();
+        StateStash::for_container(&paths, "api").stash(&recent).unwrap();
+        clock.advance(Duration::days(20));
+
+        let pruned = StateStash::prune(&paths, Duration::days(30));
+        clock::reset_clock();
+
+        assert_eq!(pruned.unwrap(), ["web"]);
+        assert!(!StateStash::for_container(&paths, "web").exists());
+        assert!(StateStash::for_container(&paths, "api").exists());
+    }
+}
@@ -0,0 +1,185 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::features::container::METADATA_FILE;
+use crate::features::manifest::{ContainerManifest, ManifestFormat, GENERATED_SCRIPTS_DIR};
+use crate::shared::digest::Sha256;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::traversal::Walk;
+
+/// Runtime identity of a container, kept across runs and restored from a stash.
+pub const RUNTIME_FILE: &str = "runtime.json";
+
+/// Everything wrappy rewrites in a container, relative to its state location.
+pub const STATE_ENTRIES: [&str; 3] = [METADATA_FILE, RUNTIME_FILE, GENERATED_SCRIPTS_DIR];
+
+/// Where the mutable state of a container lives. Normally that is the container itself;
+/// a container at the root of a git working tree keeps it under
+/// `~/.local/share/wrappy/container-state/<name>-<hash>` instead, so running it leaves
+/// no diff behind. Every reader and writer of state goes through `resolve`.
+pub struct StateLocation;
+
+impl StateLocation {
+    /// Whether `path` is the root of a git working tree (a `.git` directory or worktree file).
+    pub fn is_version_controlled(path: &Path) -> bool {
+        path.join(".git").exists()
+    }
+
+    /// Directory the state of the container at `path` is read from and written to.
+    pub fn resolve(path: &Path) -> PathBuf {
+        if !Self::is_version_controlled(path) {
+            return path.to_path_buf();
+        }
+        Self::external_dir(path).unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// Like `resolve`, creating the external directory when it is used.
+    pub fn writable(path: &Path) -> ContainerResult<PathBuf> {
+        let location = Self::resolve(path);
+        if location != path {
+            filesystem::create_state_dir(&location)?;
+        }
+        Ok(location)
+    }
+
+    /// External state directory of the container at `path`, keyed by its manifest name (the
+    /// directory name without a readable manifest) and a hash of its canonical path, so two
+    /// checkouts of one container never share state. `None` without a home directory.
+    pub fn external_dir(path: &Path) -> Option<PathBuf> {
+        let paths = WrappyPaths::new().ok()?;
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let mut hasher = Sha256::new();
+        hasher.update(canonical.to_string_lossy().as_bytes());
+        let name = ManifestFormat::locate(path)
+            .and_then(ContainerManifest::from_file)
+            .map(|manifest| manifest.name)
+            .ok()
+            .or_else(|| canonical.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "container".to_string());
+        Some(paths.container_state_dir().join(format!("{}-{}", name, &hasher.finish_hex()[..16])))
+    }
+
+    /// State entries left inside a version-controlled container; empty for other containers.
+    pub fn in_tree_state(path: &Path) -> Vec<PathBuf> {
+        if Self::resolve(path) == path {
+            return Vec::new();
+        }
+        STATE_ENTRIES
+            .iter()
+            .map(|entry| path.join(entry))
+            .filter(|entry| entry.symlink_metadata().is_ok())
+            .collect()
+    }
+
+    /// Moves in-tree state of a version-controlled container to its external location.
+    /// An entry already present there is newer, so the in-tree copy is dropped instead.
+    /// Returns the in-tree paths that were moved or dropped.
+    pub fn migrate(path: &Path) -> ContainerResult<Vec<PathBuf>> {
+        let found = Self::in_tree_state(path);
+        if found.is_empty() {
+            return Ok(found);
+        }
+        let location = Self::writable(path)?;
+        for source in &found {
+            let relative = source.strip_prefix(path).unwrap_or(source);
+            let target = location.join(relative);
+            if target.symlink_metadata().is_ok() {
+                Self::remove(source)?;
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                filesystem::create_state_dir(parent)?;
+            }
+            if fs::rename(source, &target).is_err() {
+                // Another filesystem: copy, then drop the original
+                Self::copy(source, &target)?;
+                Self::remove(source)?;
+            }
+        }
+        Ok(found)
+    }
+
+    /// `.gitignore` lines that keep wrappy state and the given persistent directories untracked.
+    pub fn gitignore_entries(persistent: &[String]) -> Vec<String> {
+        STATE_ENTRIES
+            .iter()
+            .map(|entry| {
+                if *entry == GENERATED_SCRIPTS_DIR {
+                    format!("/{}/", entry)
+                } else {
+                    format!("/{}", entry)
+                }
+            })
+            .chain(persistent.iter().map(|entry| format!("/{}/", entry.trim_matches('/'))))
+            .collect()
+    }
+
+    /// Adds the missing `entries` to the `.gitignore` of `path`, keeping what it already has.
+    /// Returns the lines that were added.
+    pub fn write_gitignore(path: &Path, entries: &[String]) -> ContainerResult<Vec<String>> {
+        let file = path.join(".gitignore");
+        let existing = match fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(ContainerError::IoError { path: file, source: error }),
+        };
+        let present: Vec<&str> = existing.lines().map(str::trim).collect();
+        let added: Vec<String> = entries
+            .iter()
+            .filter(|entry| !present.contains(&entry.as_str()))
+            .cloned()
+            .collect();
+        if added.is_empty() {
+            return Ok(added);
+        }
+
+        let mut content = existing.clone();
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        content.push_str("# Written by wrappy; see `wrappy container init`\n");
+        for entry in &added {
+            content.push_str(entry);
+            content.push('\n');
+        }
+        filesystem::atomic_write(&file, content.as_bytes(), None)?;
+        Ok(added)
+    }
+
+    fn copy(source: &Path, target: &Path) -> ContainerResult<()> {
+        for entry in Walk::new(source) {
+            let entry = entry?;
+            let destination = match entry.path.strip_prefix(source) {
+                Ok(relative) if relative.as_os_str().is_empty() => target.to_path_buf(),
+                Ok(relative) => target.join(relative),
+                Err(_) => continue,
+            };
+            let result = if entry.file_type.is_dir() {
+                fs::create_dir_all(&destination)
+            } else {
+                fs::copy(&entry.path, &destination).map(|_| ())
+            };
+            result.map_err(|e| ContainerError::IoError {
+                path: destination,
+                source: e,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn remove(path: &Path) -> ContainerResult<()> {
+        let result = match path.is_dir() {
+            true => fs::remove_dir_all(path),
+            false => fs::remove_file(path),
+        };
+        result.map_err(|e| ContainerError::IoError {
+            path: path.to_path_buf(),
+            source: e,
+        })
+    }
+}
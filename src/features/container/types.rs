@@ -0,0 +1,141 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::features::manifest::{ContainerType, HostRequirement};
+use crate::features::Version;
+use crate::shared::error::ContainerError;
+
+/// Outcome of checking one host requirement against the current machine.
+#[derive(Debug, Clone)]
+pub enum HostRequirementState {
+    Satisfied { path: PathBuf, version: Option<Version> },
+    Missing,
+    /// Below the required range, e.g. 2.20 for `>=2.30`
+    TooOld { path: PathBuf, found: Version },
+    /// Above the allowed range, e.g. 3.0 for `<3` or `^2.30`
+    TooNew { path: PathBuf, found: Version },
+    /// Command exists but its version output could not be parsed
+    VersionUnknown { path: PathBuf },
+}
+
+#[derive(Debug, Clone)]
+pub struct HostRequirementStatus {
+    pub requirement: HostRequirement,
+    pub state: HostRequirementState,
+}
+
+impl HostRequirementStatus {
+    pub fn is_satisfied(&self) -> bool {
+        matches!(self.state, HostRequirementState::Satisfied { .. })
+    }
+}
+
+impl fmt::Display for HostRequirementStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let command = &self.requirement.command;
+        let required = self
+            .requirement
+            .version_req
+            .as_deref()
+            .map(|req| format!(" ({})", req))
+            .unwrap_or_default();
+
+        match &self.state {
+            HostRequirementState::Satisfied { path, version: Some(version) } => {
+                write!(f, "{}{}: found {} at {}", command, required, version, path.display())
+            }
+            HostRequirementState::Satisfied { path, version: None } => {
+                write!(f, "{}{}: found at {}", command, required, path.display())
+            }
+            HostRequirementState::Missing => write!(f, "{}{}: not found on PATH", command, required),
+            HostRequirementState::TooOld { found, .. } => {
+                write!(f, "{}{}: found {}, which is too old", command, required, found)
+            }
+            HostRequirementState::TooNew { found, .. } => {
+                write!(f, "{}{}: found {}, which is newer than allowed", command, required, found)
+            }
+            HostRequirementState::VersionUnknown { path } => write!(
+                f,
+                "{}{}: found at {} but its version could not be determined",
+                command,
+                required,
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Outcome of validating one declared dependency against the store.
+#[derive(Debug)]
+pub enum DependencyOutcome {
+    Valid { version: Version, path: PathBuf },
+    /// Installed and valid, but not the container type the dependent expects
+    TypeMismatch {
+        version: Version,
+        path: PathBuf,
+        expected: ContainerType,
+        found: ContainerType,
+    },
+    Invalid { path: PathBuf, error: ContainerError },
+    Missing { requirement: String, optional: bool },
+}
+
+#[derive(Debug)]
+pub struct DependencyCheck {
+    pub name: String,
+    pub outcome: DependencyOutcome,
+}
+
+impl DependencyCheck {
+    /// Broken dependencies always fail; missing required ones and type mismatches only fail in strict mode.
+    pub fn is_error(&self, strict: bool) -> bool {
+        match &self.outcome {
+            DependencyOutcome::Valid { .. } => false,
+            DependencyOutcome::Invalid { .. } => true,
+            DependencyOutcome::TypeMismatch { .. } => strict,
+            DependencyOutcome::Missing { optional, .. } => strict && !optional,
+        }
+    }
+}
+
+/// Dependency checks declared by a single container of the validated tree.
+#[derive(Debug)]
+pub struct DependencyReport {
+    pub container: String,
+    pub checks: Vec<DependencyCheck>,
+}
+
+/// A file `container files` lists as part of the container.
+#[derive(Debug, Clone)]
+pub struct IncludedFile {
+    pub path: String,
+    pub size: u64,
+}
+
+/// A path the ignore rules leave out; for a directory, everything below it too.
+#[derive(Debug, Clone)]
+pub struct IgnoredPath {
+    pub path: String,
+    pub is_dir: bool,
+    /// Bytes of regular files below a directory, or of the file itself
+    pub size: u64,
+    /// Rule that excluded it, as written
+    pub rule: String,
+}
+
+/// What export, seal and dedup see of a container after applying its ignore rules.
+#[derive(Debug, Clone, Default)]
+pub struct FileListing {
+    pub included: Vec<IncludedFile>,
+    pub ignored: Vec<IgnoredPath>,
+}
+
+impl FileListing {
+    pub fn included_size(&self) -> u64 {
+        self.included.iter().map(|file| file.size).sum()
+    }
+
+    pub fn ignored_size(&self) -> u64 {
+        self.ignored.iter().map(|path| path.size).sum()
+    }
+}
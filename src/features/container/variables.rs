@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::features::ContainerManifest;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::prompt;
+
+/// On-disk form of the values chosen for one container.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct StoredVariables {
+    values: BTreeMap<String, String>,
+}
+
+/// Values of a container's manifest variables, chosen once and kept outside
+/// the store so updates and regenerated bindings reuse them without asking again.
+pub struct VariableStore {
+    path: PathBuf,
+}
+
+impl VariableStore {
+    pub fn for_container(paths: &WrappyPaths, name: &str) -> Self {
+        Self {
+            path: paths.variables_dir().join(format!("{}.json", name)),
+        }
+    }
+
+    /// Stored values; a container nobody chose values for yet has none.
+    pub fn load(&self) -> ContainerResult<HashMap<String, String>> {
+        if !self.path.is_file() {
+            return Ok(HashMap::new());
+        }
+        let content = fs::read_to_string(&self.path).map_err(|e| ContainerError::IoError {
+            path: self.path.clone(),
+            source: e,
+        })?;
+        let stored: StoredVariables = serde_json::from_str(&content)?;
+        Ok(stored.values.into_iter().collect())
+    }
+
+    pub fn save(&self, values: &HashMap<String, String>) -> ContainerResult<()> {
+        if let Some(parent) = self.path.parent() {
+            filesystem::create_state_dir(parent)?;
+        }
+        let stored = StoredVariables {
+            values: values.iter().map(|(name, value)| (name.clone(), value.clone())).collect(),
+        };
+        let content = serde_json::to_vec_pretty(&stored)?;
+        filesystem::atomic_write(&self.path, &content, Some(filesystem::STATE_FILE_MODE))
+    }
+
+    /// Chooses a value for every declared variable: `--set` overrides first, then the
+    /// stored value, then a prompt on a terminal, then the manifest default.
+    /// Required variables left without a value fail with all their names at once.
+    pub fn resolve(
+        &self,
+        manifest: &ContainerManifest,
+        overrides: &HashMap<String, String>,
+    ) -> ContainerResult<HashMap<String, String>> {
+        if let Some(unknown) = overrides
+            .keys()
+            .find(|name| !manifest.variables.iter().any(|variable| &variable.name == *name))
+        {
+            return Err(ContainerError::ManifestValidation(format!(
+                "Container '{}' declares no variable '{}'",
+                manifest.name, unknown
+            )));
+        }
+
+        let stored = self.load()?;
+        let mut values = HashMap::new();
+        let mut missing = Vec::new();
+
+        for variable in &manifest.variables {
+            let question = if variable.description.is_empty() {
+                variable.name.clone()
+            } else {
+                format!("{} ({})", variable.name, variable.description)
+            };
+
+            let value = overrides
+                .get(&variable.name)
+                .or_else(|| stored.get(&variable.name))
+                .cloned()
+                .or_else(|| prompt::input(&question, variable.default.as_deref()))
+                .or_else(|| variable.default.clone());
+
+            match value {
+                Some(value) if !(variable.required && value.is_empty()) => {
+                    values.insert(variable.name.clone(), value);
+                }
+                _ if variable.required => missing.push(variable.name.clone()),
+                _ => {
+                    values.insert(variable.name.clone(), String::new());
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            return Err(ContainerError::MissingVariables {
+                container: manifest.name.clone(),
+                variables: missing,
+            });
+        }
+        Ok(values)
+    }
+}
+
+/// Parses `--set NAME=value` arguments.
+pub fn parse_variable_overrides(assignments: &[String]) -> ContainerResult<HashMap<String, String>> {
+    assignments
+        .iter()
+        .map(|assignment| {
+            assignment
+                .split_once('=')
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .ok_or_else(|| {
+                    ContainerError::ManifestValidation(format!(
+                        "Invalid variable assignment '{}', expected NAME=value",
+                        assignment
+                    ))
+                })
+        })
+        .collect()
+}
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use crate::features::container::install_interrupt_handler;
+use crate::features::daemon::{DaemonServer, PROTOCOL_VERSION};
+
+pub struct DaemonHandler;
+
+impl DaemonHandler {
+    /// Handles the daemon command execution; blocks until interrupted
+    pub fn execute_command(socket: PathBuf) -> i32 {
+        install_interrupt_handler();
+
+        let server = match DaemonServer::bind(&socket) {
+            Ok(server) => server,
+            Err(error) => {
+                eprintln!("❌ Failed to start daemon: {}", error);
+                return 1;
+            }
+        };
+
+        println!(
+            "📡 Listening on {} (protocol {}), press Ctrl-C to stop",
+            server.socket_path().display(),
+            PROTOCOL_VERSION
+        );
+        match server.serve() {
+            Ok(()) => {
+                println!("🛑 Daemon stopped");
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Daemon failed: {}", error);
+                1
+            }
+        }
+    }
+}
@@ -0,0 +1,9 @@
+mod commands;
+mod server;
+mod service;
+mod types;
+
+pub use commands::*;
+pub use server::*;
+pub use service::*;
+pub use types::*;
@@ -0,0 +1,220 @@
+use serde_json::json;
+use std::fs::{self, Permissions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::features::container::interrupted;
+use crate::features::daemon::{DaemonService, ProgressNotification, RpcResponse};
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Unix socket listener speaking newline-delimited JSON-RPC, one thread per client.
+/// The socket is private to the user like the rest of wrappy's state.
+pub struct DaemonServer {
+    socket_path: PathBuf,
+    listener: UnixListener,
+    service: Arc<DaemonService>,
+}
+
+impl DaemonServer {
+    /// Binds `socket_path`, replacing a stale socket left by a daemon that died.
+    pub fn bind(socket_path: &Path) -> ContainerResult<Self> {
+        let io_error = |e: io::Error| ContainerError::IoError {
+            path: socket_path.to_path_buf(),
+            source: e,
+        };
+
+        if socket_path.exists() {
+            if UnixStream::connect(socket_path).is_ok() {
+                return Err(ContainerError::Runtime {
+                    message: format!("a daemon is already listening on '{}'", socket_path.display()),
+                });
+            }
+            fs::remove_file(socket_path).map_err(io_error)?;
+        }
+        if let Some(parent) = socket_path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            filesystem::create_state_dir(parent)?;
+        }
+
+        let listener = UnixListener::bind(socket_path).map_err(io_error)?;
+        fs::set_permissions(socket_path, Permissions::from_mode(filesystem::STATE_FILE_MODE)).map_err(io_error)?;
+        listener.set_nonblocking(true).map_err(io_error)?;
+
+        Ok(Self {
+            socket_path: socket_path.to_path_buf(),
+            listener,
+            service: Arc::new(DaemonService::new()),
+        })
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Accepts clients until SIGINT/SIGTERM, then removes the socket.
+    pub fn serve(self) -> ContainerResult<()> {
+        let result = loop {
+            if interrupted() {
+                break Ok(());
+            }
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    let service = Arc::clone(&self.service);
+                    thread::spawn(move || {
+                        // A client that disconnects mid-request only ends its own thread
+                        let _ = Self::handle_client(stream, &service);
+                    });
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_POLL_INTERVAL),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    break Err(ContainerError::IoError {
+                        path: self.socket_path.clone(),
+                        source: e,
+                    })
+                }
+            }
+        };
+
+        let _ = fs::remove_file(&self.socket_path);
+        result
+    }
+
+    fn handle_client(stream: UnixStream, service: &DaemonService) -> io::Result<()> {
+        stream.set_nonblocking(false)?;
+        let mut writer = stream.try_clone()?;
+        let reader = BufReader::new(stream);
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut write_error = None;
+            let response = service.handle_line(&line, &mut |notification: ProgressNotification| {
+                let message = json!({ "jsonrpc": "2.0", "method": "progress", "params": notification });
+                if let Err(e) = Self::write_line(&mut writer, &message) {
+                    write_error.get_or_insert(e);
+                }
+            });
+            if let Some(e) = write_error {
+                return Err(e);
+            }
+            if let Some(response) = response {
+                Self::write_response(&mut writer, &response)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_response(writer: &mut UnixStream, response: &RpcResponse) -> io::Result<()> {
+        Self::write_line(writer, &serde_json::to_value(response)?)
+    }
+
+    fn write_line(writer: &mut UnixStream, value: &serde_json::Value) -> io::Result<()> {
+        let mut line = serde_json::to_string(value)?;
+        line.push('\n');
+        writer.write_all(line.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::daemon::PROTOCOL_VERSION;
+    use crate::features::setup::SetupService;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, TempHome};
+    use serde_json::Value;
+
+    struct Client {
+        writer: UnixStream,
+        reader: BufReader<UnixStream>,
+    }
+
+    impl Client {
+        /// Sends one request and returns the progress messages before its response, then the response.
+        fn call(&mut self, id: u64, method: &str, params: Value) -> (Vec<String>, Value) {
+            let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params, "protocol": PROTOCOL_VERSION });
+            self.writer.write_all(format!("{}\n", request).as_bytes()).unwrap();
+
+            let mut progress = Vec::new();
+            loop {
+                let mut line = String::new();
+                self.reader.read_line(&mut line).unwrap();
+                let message: Value = serde_json::from_str(&line).unwrap();
+                if message["method"] == "progress" {
+                    assert_eq!(message["params"]["id"], id);
+                    progress.push(message["params"]["message"].as_str().unwrap().to_string());
+                } else {
+                    assert_eq!(message["id"], id);
+                    return (progress, message);
+                }
+            }
+        }
+    }
+
+    fn active_targets(response: &Value) -> Vec<String> {
+        response["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|status| status["active"] == true)
+            .map(|status| status["target"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn a_client_drives_list_enable_status_and_disable_over_the_socket() {
+        let home = TempHome::enter();
+        SetupService::initialize(&home.paths()).unwrap();
+        fixture_container_at(&home.paths().default_store_dir().join("app/1.0.0"), fixture_manifest("app", "1.0.0"));
+        let (client, server) = UnixStream::pair().unwrap();
+        let service = DaemonService::new();
+        let handler = thread::spawn(move || DaemonServer::handle_client(server, &service));
+        let mut client = Client {
+            writer: client.try_clone().unwrap(),
+            reader: BufReader::new(client),
+        };
+
+        let (_, listed) = client.call(1, "list", Value::Null);
+        let (progress, enabled) = client.call(2, "bindings.enable", json!({ "container": "app" }));
+        let (_, status) = client.call(3, "bindings.status", json!({ "container": "app" }));
+        let wrapper = home.path().join(".local/bin/app-tool");
+        let wrapper_was_written = wrapper.exists();
+        let (_, disabled) = client.call(4, "bindings.disable", json!({ "container": "app" }));
+        drop(client);
+
+        assert_eq!(listed["result"][0]["name"], "app");
+        assert_eq!(listed["result"][0]["version"], "1.0.0");
+        assert_eq!(progress, ["Enabling executables of 'app'"]);
+        assert_eq!(active_targets(&enabled), ["app-tool"]);
+        assert_eq!(active_targets(&status), ["app-tool"]);
+        assert!(wrapper_was_written);
+        assert!(active_targets(&disabled).is_empty());
+        assert!(!wrapper.exists());
+        handler.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn bind_refuses_a_live_socket_and_replaces_a_stale_one() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let socket = dir.path().join("run/wrappy.sock");
+
+        let first = DaemonServer::bind(&socket).unwrap();
+        let refused = DaemonServer::bind(&socket);
+        drop(first);
+        let replaced = DaemonServer::bind(&socket);
+
+        assert!(matches!(refused, Err(ContainerError::Runtime { .. })));
+        assert!(replaced.is_ok());
+        assert_eq!(fs::metadata(&socket).unwrap().permissions().mode() & 0o777, filesystem::STATE_FILE_MODE);
+    }
+}
@@ -0,0 +1,268 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::features::bindings::{BindingCategory, BindingFilter, BindingManager};
+use crate::features::container::{
+    Container, ContainerService, Executor, Resolution, ServiceRuntime, ServiceStart,
+};
+use crate::features::daemon::{error_codes, ProgressNotification, RpcError, RpcRequest, RpcResponse, PROTOCOL_VERSION};
+use crate::features::query;
+use crate::shared::error::ContainerError;
+use crate::shared::paths::WrappyPaths;
+
+/// Answers daemon requests with the same services the CLI uses.
+/// Mutating methods run one at a time, like CLI invocations serialized by the user.
+pub struct DaemonService {
+    mutations: Mutex<()>,
+}
+
+impl Default for DaemonService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DaemonService {
+    pub fn new() -> Self {
+        Self {
+            mutations: Mutex::new(()),
+        }
+    }
+
+    /// Handles one request line. Requests without an id are notifications and get no response.
+    pub fn handle_line(&self, line: &str, progress: &mut dyn FnMut(ProgressNotification)) -> Option<RpcResponse> {
+        let request: RpcRequest = match serde_json::from_str::<Value>(line) {
+            Err(e) => {
+                return Some(RpcResponse::failure(Value::Null, RpcError::new(error_codes::PARSE_ERROR, e.to_string())))
+            }
+            Ok(value) => match serde_json::from_value(value) {
+                Ok(request) => request,
+                Err(e) => {
+                    return Some(RpcResponse::failure(
+                        Value::Null,
+                        RpcError::new(error_codes::INVALID_REQUEST, e.to_string()),
+                    ))
+                }
+            },
+        };
+
+        let id = request.id.clone();
+        let result = self.handle(&request, &mut |message: &str| {
+            if let Some(id) = &id {
+                progress(ProgressNotification {
+                    id: id.clone(),
+                    message: message.to_string(),
+                });
+            }
+        });
+
+        let id = id?;
+        Some(match result {
+            Ok(value) => RpcResponse::success(id, value),
+            Err(error) => RpcResponse::failure(id, error),
+        })
+    }
+
+    fn handle(&self, request: &RpcRequest, progress: &mut dyn FnMut(&str)) -> Result<Value, RpcError> {
+        match request.protocol {
+            Some(PROTOCOL_VERSION) => {}
+            Some(other) => {
+                return Err(RpcError::new(
+                    error_codes::UNSUPPORTED_PROTOCOL,
+                    format!("protocol version {} is not supported, this daemon speaks {}", other, PROTOCOL_VERSION),
+                ))
+            }
+            None => {
+                return Err(RpcError::new(
+                    error_codes::INVALID_REQUEST,
+                    format!("request must name its protocol version (current: {})", PROTOCOL_VERSION),
+                ))
+            }
+        }
+
+        let params = &request.params;
+        match request.method.as_str() {
+            "list" => to_value(query::installed_containers()),
+            "info" => Self::info(params),
+            "bindings.status" => to_value(query::bindings_of(&Self::container_path(params)?.to_string_lossy())),
+            "bindings.enable" => self.set_bindings(params, true, progress),
+            "bindings.disable" => self.set_bindings(params, false, progress),
+            "run" => self.run(params, progress),
+            "stop" => self.stop(params),
+            other => Err(RpcError {
+                code: error_codes::METHOD_NOT_FOUND,
+                message: format!("unknown method '{}'", other),
+                data: Some(json!({
+                    "methods": ["list", "info", "bindings.status", "bindings.enable", "bindings.disable", "run", "stop"]
+                })),
+            }),
+        }
+    }
+
+    fn info(params: &Value) -> Result<Value, RpcError> {
+        let container = Self::load_container(params)?;
+        let manifest = &container.manifest;
+        Ok(json!({
+            "name": manifest.name,
+            "version": manifest.version,
+            "container_type": manifest.container_type,
+            "description": manifest.description,
+            "path": container.path,
+            "scripts": query::scripts_of(&container.path.to_string_lossy()),
+        }))
+    }
+
+    fn set_bindings(&self, params: &Value, enable: bool, progress: &mut dyn FnMut(&str)) -> Result<Value, RpcError> {
+        let only: Option<BindingCategory> = match params.get("only") {
+            None | Some(Value::Null) => None,
+            Some(value) => Some(
+                serde_json::from_value(value.clone())
+                    .map_err(|e| RpcError::new(error_codes::INVALID_PARAMS, format!("invalid 'only': {}", e)))?,
+            ),
+        };
+        let container = Self::load_container(params)?;
+
+        let _guard = self.mutations.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let manager = BindingManager::new().map_err(operation_failed)?;
+        for category in BindingCategory::ALL.into_iter().filter(|category| only.is_none_or(|only| only == *category)) {
+            let filter = BindingFilter {
+                only: Some(category),
+                ..BindingFilter::default()
+            };
+            let mut filtered = container.clone();
+            filtered.manifest.bindings = manager
+                .filter_bindings(&container, &container.manifest.bindings, &filter)
+                .map_err(operation_failed)?;
+            if filtered.manifest.bindings.is_empty() {
+                continue;
+            }
+
+            let verb = if enable { "Enabling" } else { "Disabling" };
+            progress(&format!("{} {} of '{}'", verb, category.label(), container.name()));
+            if enable {
+                manager.install_bindings(&filtered).map_err(operation_failed)?;
+            } else {
+                manager
+                    .remove_selected_bindings(&container, &filtered.manifest.bindings)
+                    .map_err(operation_failed)?;
+            }
+        }
+
+        to_value(query::bindings_of(&container.path.to_string_lossy()))
+    }
+
+    fn run(&self, params: &Value, progress: &mut dyn FnMut(&str)) -> Result<Value, RpcError> {
+        let container = Self::load_container(params)?;
+        let script = params.get("script").and_then(Value::as_str).unwrap_or("service").to_string();
+        let idle_timeout = params.get("idle_timeout").and_then(Value::as_u64).unwrap_or(900);
+
+        let _guard = self.mutations.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        progress(&format!("Starting '{}' of '{}'", script, container.name()));
+        let paths = WrappyPaths::new().map_err(operation_failed)?;
+        let runtime = ServiceRuntime::new(&paths, container.name());
+        let outcome = runtime
+            .start(&Executor::new(container), &script, idle_timeout)
+            .map_err(operation_failed)?;
+
+        Ok(match outcome {
+            ServiceStart::Started(pid) => json!({ "pid": pid, "already_running": false, "log": runtime.log_file() }),
+            ServiceStart::AlreadyRunning(pid) => json!({ "pid": pid, "already_running": true, "log": runtime.log_file() }),
+        })
+    }
+
+    fn stop(&self, params: &Value) -> Result<Value, RpcError> {
+        let container = Self::load_container(params)?;
+
+        let _guard = self.mutations.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let paths = WrappyPaths::new().map_err(operation_failed)?;
+        let stopped = ServiceRuntime::new(&paths, container.name()).stop().map_err(operation_failed)?;
+        Ok(json!({ "stopped_pid": stopped }))
+    }
+
+    fn load_container(params: &Value) -> Result<Container, RpcError> {
+        ContainerService::load_from_directory(Self::container_path(params)?).map_err(operation_failed)
+    }
+
+    /// Resolves the `container` parameter without prompting; ambiguity is an error listing the candidates.
+    fn container_path(params: &Value) -> Result<PathBuf, RpcError> {
+        let input = params
+            .get("container")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::new(error_codes::INVALID_PARAMS, "missing string parameter 'container'"))?;
+
+        match query::resolve(input) {
+            Resolution::Path(path) => Ok(path),
+            Resolution::Unique(candidate) => Ok(candidate.path),
+            Resolution::Ambiguous { name, candidates } => Err(operation_failed(ContainerError::AmbiguousContainer {
+                name,
+                candidates: candidates.iter().map(|candidate| candidate.selector()).collect(),
+            })),
+            Resolution::NotFound { name } => Err(operation_failed(ContainerError::ContainerNotFound {
+                suggestions: query::similar_names(&name),
+                name,
+            })),
+        }
+    }
+}
+
+fn operation_failed(error: ContainerError) -> RpcError {
+    RpcError {
+        code: error_codes::OPERATION_FAILED,
+        message: error.to_string(),
+        data: Some(json!({ "code": error.code() })),
+    }
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::new(error_codes::OPERATION_FAILED, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn respond(line: &str) -> Option<Value> {
+        DaemonService::new()
+            .handle_line(line, &mut |_| panic!("no progress expected"))
+            .map(|response| serde_json::to_value(response).unwrap())
+    }
+
+    #[test]
+    fn unknown_methods_return_a_structured_error_listing_the_methods() {
+        let response = respond(r#"{"id": 7, "method": "frobnicate", "protocol": 1}"#).unwrap();
+
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["error"]["code"], error_codes::METHOD_NOT_FOUND);
+        assert!(response["error"]["data"]["methods"].as_array().unwrap().contains(&json!("bindings.enable")));
+        assert!(response.get("result").is_none());
+    }
+
+    #[test]
+    fn requests_must_name_a_supported_protocol_version() {
+        let missing = respond(r#"{"id": 1, "method": "list"}"#).unwrap();
+        let newer = respond(r#"{"id": 2, "method": "list", "protocol": 2}"#).unwrap();
+
+        assert_eq!(missing["error"]["code"], error_codes::INVALID_REQUEST);
+        assert_eq!(newer["error"]["code"], error_codes::UNSUPPORTED_PROTOCOL);
+    }
+
+    #[test]
+    fn malformed_lines_get_an_error_with_a_null_id_and_notifications_no_response() {
+        let unparsable = respond("{not json").unwrap();
+        let not_a_request = respond(r#"{"id": 3}"#).unwrap();
+
+        assert_eq!(unparsable["error"]["code"], error_codes::PARSE_ERROR);
+        assert_eq!(unparsable["id"], Value::Null);
+        assert_eq!(not_a_request["error"]["code"], error_codes::INVALID_REQUEST);
+        assert!(respond(r#"{"method": "frobnicate", "protocol": 1}"#).is_none());
+    }
+
+    #[test]
+    fn a_missing_container_parameter_is_invalid_params() {
+        let response = respond(r#"{"id": 4, "method": "info", "params": {}, "protocol": 1}"#).unwrap();
+
+        assert_eq!(response["error"]["code"], error_codes::INVALID_PARAMS);
+    }
+}
@@ -0,0 +1,5 @@
+mod service;
+mod types;
+
+pub use service::*;
+pub use types::*;
@@ -0,0 +1,118 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::features::history::{HistoryReadResult, RunRecord};
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+
+/// Append-only run history stored as JSON lines, one record per execution.
+pub struct RunHistory {
+    path: PathBuf,
+}
+
+impl RunHistory {
+    pub fn new(paths: &WrappyPaths) -> Self {
+        Self::at(paths.history_file())
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn append(&self, record: &RunRecord) -> ContainerResult<()> {
+        if let Some(parent) = self.path.parent() {
+            filesystem::create_state_dir(parent)?;
+        }
+
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let mut file = filesystem::state_file_options()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| ContainerError::IoError {
+                path: self.path.clone(),
+                source: e,
+            })?;
+        file.write_all(line.as_bytes()).map_err(|e| ContainerError::IoError {
+            path: self.path.clone(),
+            source: e,
+        })
+    }
+
+    /// Reads every record, skipping malformed lines so one bad write never hides the rest.
+    pub fn read_all(&self) -> ContainerResult<HistoryReadResult> {
+        if !self.path.exists() {
+            return Ok(HistoryReadResult::default());
+        }
+
+        let content = fs::read_to_string(&self.path).map_err(|e| ContainerError::IoError {
+            path: self.path.clone(),
+            source: e,
+        })?;
+
+        let mut result = HistoryReadResult::default();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<RunRecord>(line) {
+                Ok(record) => result.records.push(record),
+                Err(_) => result.skipped_lines.push(index + 1),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use tempfile::TempDir;
+
+    fn record(script: &str) -> RunRecord {
+        RunRecord {
+            container: "web".to_string(),
+            script: script.to_string(),
+            started_at: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+            duration_ms: 5,
+            exit_code: 0,
+            output_mode: None,
+            output_file: None,
+        }
+    }
+
+    #[test]
+    fn read_all_skips_malformed_lines_and_reports_their_numbers() {
+        let dir = TempDir::new().unwrap();
+        let history = RunHistory::at(dir.path().join("state/history.jsonl"));
+        history.append(&record("first")).unwrap();
+        let mut file = fs::OpenOptions::new().append(true).open(history.path()).unwrap();
+        file.write_all(b"{\"container\":\"web\",\"scr\n\nnot json\n").unwrap();
+        history.append(&record("second")).unwrap();
+
+        let read = history.read_all().unwrap();
+
+        let scripts: Vec<&str> = read.records.iter().map(|r| r.script.as_str()).collect();
+        assert_eq!(scripts, ["first", "second"]);
+        assert_eq!(read.skipped_lines, [2, 4]);
+    }
+
+    #[test]
+    fn a_missing_history_reads_as_empty() {
+        let dir = TempDir::new().unwrap();
+        let read = RunHistory::at(dir.path().join("history.jsonl")).read_all().unwrap();
+
+        assert!(read.records.is_empty());
+        assert!(read.skipped_lines.is_empty());
+    }
+}
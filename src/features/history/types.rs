@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// One finished script execution, appended to the run history for stats and troubleshooting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub container: String,
+    pub script: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub exit_code: i32,
+    /// `tee`, `json` or `quiet` when the output did not go straight to the terminal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_mode: Option<String>,
+    /// File the output was teed to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_file: Option<PathBuf>,
+}
+
+impl RunRecord {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == 0
+    }
+}
+
+/// Result of reading the history file; malformed lines are counted instead of failing the read.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryReadResult {
+    pub records: Vec<RunRecord>,
+    pub skipped_lines: Vec<usize>,
+}
@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::features::ContainerManifest;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+
+/// Bumped whenever the cache layout or the manifest schema changes; older caches are dropped.
+const MANIFEST_CACHE_VERSION: u32 = 1;
+
+/// Cleared by `--no-cache` so every manifest is parsed from disk.
+static CACHE_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Set by commands that must not write anything, such as `wrappy env` in shell startup.
+static CACHE_READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Identifies one revision of a manifest file without reading it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct FileStamp {
+    mtime: i64,
+    mtime_nsec: i64,
+    size: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> ContainerResult<Self> {
+        let metadata = fs::metadata(path).map_err(|e| ContainerError::IoError {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        Ok(Self {
+            mtime: metadata.mtime(),
+            mtime_nsec: metadata.mtime_nsec(),
+            size: metadata.size(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedManifest {
+    stamp: FileStamp,
+    manifest: ContainerManifest,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    entries: HashMap<PathBuf, CachedManifest>,
+}
+
+/// On-disk cache of parsed and validated manifests, keyed by manifest path and
+/// revalidated by mtime and size, so commands that scan the whole store skip re-parsing.
+pub struct ManifestCache {
+    path: Option<PathBuf>,
+    entries: HashMap<PathBuf, CachedManifest>,
+    dirty: bool,
+    hits: usize,
+    misses: usize,
+}
+
+impl ManifestCache {
+    /// Opens the cache in the wrappy cache directory; a missing, corrupt or outdated
+    /// cache file silently starts empty.
+    pub fn open() -> Self {
+        match WrappyPaths::new() {
+            Ok(paths) if Self::is_enabled() => Self::at(paths.cache_dir.join("manifests.json")),
+            _ => Self::disabled(),
+        }
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CacheFile>(&content).ok())
+            .filter(|cache| cache.version == MANIFEST_CACHE_VERSION)
+            .map(|cache| cache.entries)
+            .unwrap_or_default();
+
+        Self {
+            path: Some(path),
+            entries,
+            dirty: false,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// A cache that always parses and never writes.
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            entries: HashMap::new(),
+            dirty: false,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn set_enabled(enabled: bool) {
+        CACHE_ENABLED.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled() -> bool {
+        CACHE_ENABLED.load(Ordering::Relaxed)
+    }
+
+    /// Keeps serving cached manifests but stops `save` from writing the cache file.
+    pub fn set_read_only(read_only: bool) {
+        CACHE_READ_ONLY.store(read_only, Ordering::Relaxed);
+    }
+
+    /// Returns the cached manifest when the file is unchanged, otherwise parses and caches it.
+    pub fn get_or_load(&mut self, manifest_path: &Path) -> ContainerResult<ContainerManifest> {
+        if self.path.is_none() {
+            self.misses += 1;
+            return ContainerManifest::from_file(manifest_path);
+        }
+
+        let stamp = FileStamp::of(manifest_path)?;
+        if let Some(cached) = self.entries.get(manifest_path) {
+            if cached.stamp == stamp {
+                self.hits += 1;
+                return Ok(cached.manifest.clone());
+            }
+        }
+
+        self.misses += 1;
+        let manifest = ContainerManifest::from_file(manifest_path)?;
+        self.entries.insert(
+            manifest_path.to_path_buf(),
+            CachedManifest {
+                stamp,
+                manifest: manifest.clone(),
+            },
+        );
+        self.dirty = true;
+        Ok(manifest)
+    }
+
+    /// Number of manifests served from the cache / parsed from disk so far.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Writes the cache back if anything changed, dropping entries whose manifest is gone.
+    pub fn save(&mut self) -> ContainerResult<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.dirty || CACHE_READ_ONLY.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        self.entries.retain(|manifest_path, _| manifest_path.exists());
+        if let Some(parent) = path.parent() {
+            filesystem::create_state_dir(parent)?;
+        }
+
+        let cache = CacheFile {
+            version: MANIFEST_CACHE_VERSION,
+            entries: std::mem::take(&mut self.entries),
+        };
+        let result = serde_json::to_vec(&cache)
+            .map_err(ContainerError::from)
+            .and_then(|content| filesystem::atomic_write(path, &content, Some(filesystem::STATE_FILE_MODE)));
+        self.entries = cache.entries;
+        self.dirty = false;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::{self, fixture_container, TempHome};
+    use tempfile::TempDir;
+
+    fn fixtures(count: usize) -> (TempDir, Vec<PathBuf>) {
+        let dir = TempDir::new().unwrap();
+        let manifests = (0..count)
+            .map(|index| fixture_container(dir.path(), &format!("app{}", index), "1.0.0").join("manifest.json"))
+            .collect();
+        (dir, manifests)
+    }
+
+    fn load_all(cache: &mut ManifestCache, manifests: &[PathBuf]) {
+        for manifest in manifests {
+            cache.get_or_load(manifest).unwrap();
+        }
+    }
+
+    #[test]
+    fn a_second_run_over_300_containers_parses_nothing() {
+        let (_dir, manifests) = fixtures(300);
+        let cache_dir = TempDir::new().unwrap();
+        let cache_path = cache_dir.path().join("manifests.json");
+
+        let mut first = ManifestCache::at(cache_path.clone());
+        load_all(&mut first, &manifests);
+        first.save().unwrap();
+        assert_eq!((first.hits(), first.misses()), (0, 300));
+
+        let mut second = ManifestCache::at(cache_path);
+        load_all(&mut second, &manifests);
+        assert_eq!((second.hits(), second.misses()), (300, 0));
+        assert_eq!(second.get_or_load(&manifests[7]).unwrap().name, "app7");
+    }
+
+    #[test]
+    fn a_changed_manifest_is_parsed_again() {
+        let (dir, manifests) = fixtures(2);
+        let cache_path = dir.path().join("manifests.json");
+        let mut cache = ManifestCache::at(cache_path.clone());
+        load_all(&mut cache, &manifests);
+        cache.save().unwrap();
+
+        let mut changed = test_support::fixture_manifest("app0", "1.0.0");
+        changed["description"] = "now with a description".into();
+        test_support::write_manifest(manifests[0].parent().unwrap(), &changed);
+
+        let mut cache = ManifestCache::at(cache_path);
+        let manifest = cache.get_or_load(&manifests[0]).unwrap();
+        cache.get_or_load(&manifests[1]).unwrap();
+        assert_eq!(manifest.description, "now with a description");
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+    }
+
+    #[test]
+    fn corrupt_or_outdated_cache_files_are_dropped_silently() {
+        let (dir, manifests) = fixtures(1);
+        let cache_path = dir.path().join("manifests.json");
+        let mut cache = ManifestCache::at(cache_path.clone());
+        load_all(&mut cache, &manifests);
+        cache.save().unwrap();
+        let saved = fs::read_to_string(&cache_path).unwrap();
+
+        for content in [
+            "{not json".to_string(),
+            saved.replacen(&format!("\"version\":{}", MANIFEST_CACHE_VERSION), "\"version\":0", 1),
+        ] {
+            fs::write(&cache_path, content).unwrap();
+            let mut cache = ManifestCache::at(cache_path.clone());
+            load_all(&mut cache, &manifests);
+            assert_eq!((cache.hits(), cache.misses()), (0, 1));
+            cache.save().unwrap();
+        }
+
+        let mut cache = ManifestCache::at(cache_path);
+        load_all(&mut cache, &manifests);
+        assert_eq!(cache.hits(), 1);
+    }
+
+    #[test]
+    fn no_cache_parses_every_time_and_writes_nothing() {
+        let home = TempHome::enter();
+        let (_dir, manifests) = fixtures(3);
+
+        ManifestCache::set_enabled(false);
+        let mut cache = ManifestCache::open();
+        load_all(&mut cache, &manifests);
+        load_all(&mut cache, &manifests);
+        cache.save().unwrap();
+        ManifestCache::set_enabled(true);
+
+        assert_eq!((cache.hits(), cache.misses()), (0, 6));
+        assert!(!home.paths().cache_dir.join("manifests.json").exists());
+    }
+
+    #[test]
+    fn a_read_only_cache_serves_hits_but_never_writes() {
+        let _home = TempHome::enter();
+        let (dir, manifests) = fixtures(2);
+        let cache_path = dir.path().join("manifests.json");
+        let mut cache = ManifestCache::at(cache_path.clone());
+        cache.get_or_load(&manifests[0]).unwrap();
+        cache.save().unwrap();
+        let saved = fs::read(&cache_path).unwrap();
+
+        ManifestCache::set_read_only(true);
+        let mut cache = ManifestCache::at(cache_path.clone());
+        load_all(&mut cache, &manifests);
+        let result = cache.save();
+        ManifestCache::set_read_only(false);
+
+        result.unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+        assert_eq!(fs::read(&cache_path).unwrap(), saved);
+    }
+}
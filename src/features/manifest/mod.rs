@@ -1,20 +1,42 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
+mod cache;
 
-use crate::features::Version;
+pub use cache::*;
+
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::features::{Version, VersionReq};
 use crate::features::bindings::BindingsConfig;
+use crate::features::container::DEFAULT_ENVIRONMENT_PASSTHROUGH;
 use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::portability;
+use crate::shared::redact::Redact;
+use crate::shared::template::{self, INSTALL_NAMESPACE};
 
 /// Defines container category for isolation and deployment strategies.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
 #[serde(rename_all = "lowercase")]
 pub enum ContainerType {
+    #[default]
     Application,
     Package,
     System,
 }
 
+impl fmt::Display for ContainerType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ContainerType::Application => "application",
+            ContainerType::Package => "package",
+            ContainerType::System => "system",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 /// Controls container security boundaries and resource access.
 /// Balances security isolation with functional requirements.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +44,9 @@ pub struct IsolationConfig {
     pub enabled: bool,
     pub network: String,
     pub filesystem: String,
+    /// Host variables (glob patterns such as `LC_*`) still passed in when the host environment is not inherited
+    #[serde(default = "IsolationConfig::default_environment_passthrough")]
+    pub environment_passthrough: Vec<String>,
 }
 
 impl Default for IsolationConfig {
@@ -30,70 +55,433 @@ impl Default for IsolationConfig {
             enabled: true,
             network: "restricted".to_string(),
             filesystem: "sandboxed".to_string(),
+            environment_passthrough: Self::default_environment_passthrough(),
         }
     }
 }
 
+impl IsolationConfig {
+    pub fn default_environment_passthrough() -> Vec<String> {
+        DEFAULT_ENVIRONMENT_PASSTHROUGH.iter().map(|key| key.to_string()).collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Dependency {
     pub name: String,
     pub version: String,
     #[serde(default)]
     pub optional: bool,
+    /// Container type the dependent expects; anything but a package must be declared explicitly
+    #[serde(rename = "type", default = "Dependency::default_expected_type")]
+    pub expected_type: ContainerType,
+}
+
+impl Dependency {
+    fn default_expected_type() -> ContainerType {
+        ContainerType::Package
+    }
+
+    /// Whether a container of `found` type satisfies this dependency's type expectation.
+    pub fn accepts_type(&self, found: &ContainerType) -> bool {
+        &self.expected_type == found
+    }
+}
+
+/// File formats a manifest can be written in. Only JSON can be parsed by this build;
+/// the others are recognized so a container shipping one gets a clear error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ManifestFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ManifestFormat {
+    pub const ALL: [ManifestFormat; 3] = [Self::Json, Self::Toml, Self::Yaml];
+
+    /// Name of the manifest file in this format inside a container directory.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Json => "manifest.json",
+            Self::Toml => "manifest.toml",
+            Self::Yaml => "manifest.yaml",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Finds the manifest of a container directory, refusing directories with
+    /// several variants since it would be unclear which one is authoritative.
+    pub fn locate(container_dir: &Path) -> ContainerResult<PathBuf> {
+        let found: Vec<PathBuf> = Self::ALL
+            .iter()
+            .map(|format| container_dir.join(format.file_name()))
+            .filter(|path| path.is_file())
+            .collect();
+
+        match found.as_slice() {
+            [] => Err(ContainerError::InvalidStructure("manifest.json not found".to_string())),
+            [path] => Ok(path.clone()),
+            _ => Err(ContainerError::InvalidStructure(format!(
+                "More than one manifest file found ({}); keep exactly one",
+                found
+                    .iter()
+                    .filter_map(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))),
+        }
+    }
+
+    fn unsupported(self) -> ContainerError {
+        ContainerError::InvalidManifest(format!(
+            "{} manifests are not supported by this build of wrappy; use manifest.json",
+            format!("{:?}", self).to_uppercase()
+        ))
+    }
+}
+
+/// Writes a map in key order, so serializing the same manifest always gives the same bytes.
+pub fn serialize_sorted<S: Serializer, V: Serialize>(map: &HashMap<String, V>, serializer: S) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// Largest inline script accepted in a manifest; anything bigger belongs in `scripts/`.
+pub const MAX_INLINE_SCRIPT_BYTES: usize = 4096;
+
+/// Directory (relative to the container's state location) where inline scripts are materialized.
+pub const GENERATED_SCRIPTS_DIR: &str = "scripts/.generated";
+
+/// Structured script definition as written in the manifest.
+/// Plain strings stay supported so existing manifests keep working unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScriptSpec {
+    Path(String),
+    Detailed(ScriptDetails),
+}
+
+/// Object form of a script entry; exactly one of `path` or `inline` must be set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Script body stored directly in the manifest, materialized into `scripts/.generated/`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline: Option<String>,
+    /// Extra host paths only this script gets, on top of the container's isolation settings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mounts: Vec<ScriptMount>,
+}
+
+/// Whether a script may write through a mount.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MountMode {
+    #[default]
+    Ro,
+    Rw,
+}
+
+impl std::fmt::Display for MountMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MountMode::Ro => write!(f, "ro"),
+            MountMode::Rw => write!(f, "rw"),
+        }
+    }
+}
+
+/// Host path bound into the script's sandbox at `container`, e.g. a project directory or a device.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptMount {
+    pub host: String,
+    pub container: String,
+    #[serde(default)]
+    pub mode: MountMode,
+}
+
+impl std::fmt::Display for ScriptMount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> {} ({})", self.host, self.container, self.mode)
+    }
+}
+
+impl ScriptSpec {
+    pub fn inline(&self) -> Option<&str> {
+        match self {
+            ScriptSpec::Detailed(details) => details.inline.as_deref(),
+            ScriptSpec::Path(_) => None,
+        }
+    }
+
+    pub fn is_inline(&self) -> bool {
+        self.inline().is_some()
+    }
+
+    pub fn mounts(&self) -> &[ScriptMount] {
+        match self {
+            ScriptSpec::Detailed(details) => &details.mounts,
+            ScriptSpec::Path(_) => &[],
+        }
+    }
+
+    /// Path of the script relative to the container root.
+    /// Inline scripts resolve to their materialized file so callers never special-case them.
+    pub fn relative_path(&self, script_name: &str) -> String {
+        match self {
+            ScriptSpec::Path(path) => path.clone(),
+            ScriptSpec::Detailed(details) => match (&details.path, &details.inline) {
+                (Some(path), _) => path.clone(),
+                (None, _) => Self::generated_path(script_name),
+            },
+        }
+    }
+
+    pub fn generated_path(script_name: &str) -> String {
+        format!("{}/{}.sh", GENERATED_SCRIPTS_DIR, script_name)
+    }
+
+    fn validate(&self, script_name: &str) -> ContainerResult<()> {
+        Self::validate_mounts(script_name, self.mounts())?;
+        match self {
+            ScriptSpec::Path(path) if path.is_empty() => Err(ContainerError::ManifestValidation(
+                format!("Script '{}' has empty path", script_name),
+            )),
+            ScriptSpec::Path(_) => Ok(()),
+            ScriptSpec::Detailed(details) => match (&details.path, &details.inline) {
+                (Some(_), Some(_)) | (None, None) => Err(ContainerError::ManifestValidation(format!(
+                    "Script '{}' must define exactly one of 'path' or 'inline'",
+                    script_name
+                ))),
+                (Some(path), None) if path.is_empty() => Err(ContainerError::ManifestValidation(
+                    format!("Script '{}' has empty path", script_name),
+                )),
+                (Some(_), None) => Ok(()),
+                (None, Some(inline)) => Self::validate_inline(script_name, inline),
+            },
+        }
+    }
+
+    /// Both sides of a mount must be absolute, and no container path may equal or
+    /// contain another one, since the later bind would hide the earlier. `..` is refused
+    /// in container paths, because it would hide an overlap from the comparison.
+    fn validate_mounts(script_name: &str, mounts: &[ScriptMount]) -> ContainerResult<()> {
+        for (index, mount) in mounts.iter().enumerate() {
+            for (side, path) in [("host", &mount.host), ("container", &mount.container)] {
+                if !Path::new(path).is_absolute() {
+                    return Err(ContainerError::ManifestValidation(format!(
+                        "Script '{}' mount {} path '{}' must be absolute",
+                        script_name, side, path
+                    )));
+                }
+            }
+
+            let container = Path::new(&mount.container);
+            if container.components().any(|component| component == std::path::Component::ParentDir) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Script '{}' mount container path '{}' must not contain '..'",
+                    script_name, mount.container
+                )));
+            }
+            if let Some(other) = mounts[..index].iter().find(|other| {
+                let other = Path::new(&other.container);
+                container.starts_with(other) || other.starts_with(container)
+            }) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Script '{}' mounts '{}' and '{}' overlap inside the container",
+                    script_name, other.container, mount.container
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_inline(script_name: &str, inline: &str) -> ContainerResult<()> {
+        if inline.len() > MAX_INLINE_SCRIPT_BYTES {
+            return Err(ContainerError::ManifestValidation(format!(
+                "Inline script '{}' is {} bytes, the limit is {} bytes; move it to the scripts directory",
+                script_name,
+                inline.len(),
+                MAX_INLINE_SCRIPT_BYTES
+            )));
+        }
+
+        if !inline.starts_with("#!") {
+            return Err(ContainerError::ManifestValidation(format!(
+                "Inline script '{}' must start with a shebang line (e.g. #!/bin/sh)",
+                script_name
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ScriptSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self, self.inline()) {
+            (_, Some(inline)) => write!(f, "<inline, {} bytes>", inline.len()),
+            (ScriptSpec::Path(path), None) => write!(f, "{}", path),
+            (ScriptSpec::Detailed(details), None) => {
+                write!(f, "{}", details.path.as_deref().unwrap_or_default())
+            }
+        }
+    }
+}
+
+/// Host tool a container relies on instead of packaging it as another container.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostRequirement {
+    pub command: String,
+    /// Arguments that make the command print its version (defaults to `--version`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub version_args: Vec<String>,
+    /// Version constraint checked against the command output, e.g. `>=2.30`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_req: Option<String>,
+}
+
+impl HostRequirement {
+    pub fn version_args(&self) -> Vec<String> {
+        if self.version_args.is_empty() {
+            vec!["--version".to_string()]
+        } else {
+            self.version_args.clone()
+        }
+    }
+}
+
+/// Value chosen by the user when installing, referenced as `${install:NAME}`
+/// from binding targets and environment values so manifests stay shareable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    #[serde(default)]
+    pub required: bool,
 }
 
 /// Core container configuration defining deployment behavior and requirements.
 /// Central metadata store for container lifecycle management and validation.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ContainerManifest {
     pub name: String,
     pub version: Version,
+    #[serde(rename = "type", default)]
+    pub container_type: ContainerType,
     #[serde(default)]
     pub description: String,
     #[serde(default)]
     pub author: String,
-    #[serde(default)]
-    pub scripts: HashMap<String, String>,
+    #[serde(default, serialize_with = "serialize_sorted")]
+    pub scripts: HashMap<String, ScriptSpec>,
     #[serde(default)]
     pub dependencies: Vec<Dependency>,
-    #[serde(default)]
+    #[serde(default, serialize_with = "serialize_sorted")]
     pub environment: HashMap<String, String>,
     #[serde(default)]
     pub bindings: BindingsConfig,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub host_requirements: Vec<HostRequirement>,
+    /// Directories (relative to the container root) holding app state that must survive updates and removal
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub persistent: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isolation: Option<IsolationConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<TemplateVariable>,
+}
+
+/// Hand-written so `{:?}` of a manifest never prints environment secrets.
+impl fmt::Debug for ContainerManifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContainerManifest")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field("container_type", &self.container_type)
+            .field("description", &self.description)
+            .field("author", &self.author)
+            .field("scripts", &self.scripts)
+            .field("dependencies", &self.dependencies)
+            .field("environment", &self.environment.redacted())
+            .field("bindings", &self.bindings)
+            .field("host_requirements", &self.host_requirements)
+            .field("persistent", &self.persistent)
+            .field("isolation", &self.isolation)
+            .field("variables", &self.variables)
+            .finish()
+    }
 }
 
 impl ContainerManifest {
     /// Initializes manifest with default configuration and required default script.
     pub fn new(name: String, version: Version) -> Self {
         let mut scripts = HashMap::new();
-        scripts.insert("default".to_string(), "scripts/default.sh".to_string());
+        scripts.insert(
+            "default".to_string(),
+            ScriptSpec::Path("scripts/default.sh".to_string()),
+        );
 
         Self {
             name,
             version,
+            container_type: ContainerType::default(),
             description: String::new(),
             author: String::new(),
             scripts,
             dependencies: Vec::new(),
             environment: HashMap::new(),
             bindings: BindingsConfig::new(),
+            host_requirements: Vec::new(),
+            persistent: Vec::new(),
+            isolation: None,
+            variables: Vec::new(),
         }
     }
 
     /// Deserializes manifest from filesystem with validation.
     pub fn from_file<P: AsRef<Path>>(path: P) -> ContainerResult<Self> {
-        let content = std::fs::read_to_string(&path).map_err(|e| ContainerError::IoError {
-            path: path.as_ref().to_path_buf(),
-            source: e,
-        })?;
+        match ManifestFormat::from_path(path.as_ref()) {
+            Some(format @ (ManifestFormat::Toml | ManifestFormat::Yaml)) => return Err(format.unsupported()),
+            Some(ManifestFormat::Json) | None => {}
+        }
+
+        let content = filesystem::retry_on_stale(|| filesystem::read_json_file(path.as_ref()))
+            .map_err(|e| ContainerError::IoError {
+                path: path.as_ref().to_path_buf(),
+                source: e,
+            })?;
 
-        let manifest: ContainerManifest = serde_json::from_str(&content)
+        let mut manifest: ContainerManifest = serde_json::from_str(&content)
             .map_err(|e| ContainerError::InvalidManifest(e.to_string()))?;
+        manifest.bindings.normalize_sources();
 
         manifest.validate()?;
         Ok(manifest)
     }
 
+    /// Renders the validated manifest in `format`.
+    pub fn to_string_as(&self, format: ManifestFormat) -> ContainerResult<String> {
+        self.validate()?;
+        match format {
+            ManifestFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ManifestFormat::Toml | ManifestFormat::Yaml => Err(format.unsupported()),
+        }
+    }
+
     /// Serializes validated manifest to filesystem for deployment.
     pub fn to_file<P: AsRef<Path>>(&self, path: P) -> ContainerResult<()> {
         self.validate()?;
@@ -138,14 +526,9 @@ impl ContainerManifest {
             return Err(ContainerError::MissingDefaultScript);
         }
 
-        // Validate all script paths are non-empty
-        for (script_name, script_path) in &self.scripts {
-            if script_path.is_empty() {
-                return Err(ContainerError::ManifestValidation(format!(
-                    "Script '{}' has empty path",
-                    script_name
-                )));
-            }
+        // Validate script definitions (paths non-empty, inline scripts bounded with shebang)
+        for (script_name, script) in &self.scripts {
+            script.validate(script_name)?;
         }
 
         // Validate dependencies
@@ -173,18 +556,194 @@ impl ContainerManifest {
             }
         }
 
+        for executable in &self.bindings.executables {
+            executable.validate()?;
+        }
+        self.bindings.validate_labels()?;
+        self.bindings.validate_autostart()?;
+        self.bindings.validate_bin_dir()?;
+        self.bindings.validate_paths()?;
+
+        self.validate_host_requirements()?;
+        self.validate_environment()?;
+        self.validate_persistent_dirs()?;
+        self.validate_variables()?;
+
+        Ok(())
+    }
+
+    /// Variables need placeholder-friendly unique names, and every `${install:NAME}`
+    /// must be declared so a typo is caught before anyone is asked for a value.
+    fn validate_variables(&self) -> ContainerResult<()> {
+        let mut declared = std::collections::HashSet::new();
+        for variable in &self.variables {
+            if !template::is_valid_name(&variable.name) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Variable name '{}' may only contain alphanumeric characters and underscores",
+                    variable.name
+                )));
+            }
+            if !declared.insert(variable.name.as_str()) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Variable '{}' is declared more than once",
+                    variable.name
+                )));
+            }
+        }
+
+        for value in self.templated_values() {
+            for name in template::names_in(value, INSTALL_NAMESPACE) {
+                if !declared.contains(name.as_str()) {
+                    return Err(ContainerError::ManifestValidation(format!(
+                        "'{}' references undeclared variable '{}'; add it to 'variables'",
+                        value, name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Manifest strings that may contain `${install:NAME}` placeholders.
+    fn templated_values(&self) -> impl Iterator<Item = &String> {
+        self.bindings
+            .executables
+            .iter()
+            .map(|executable| &executable.target)
+            .chain(self.bindings.configs.iter().map(|config| &config.target))
+            .chain(self.bindings.data.iter().map(|data| &data.target))
+            .chain(self.bindings.bin_dir.iter())
+            .chain(self.environment.values())
+    }
+
+    /// Fills `${install:NAME}` placeholders with the chosen values; unknown ones stay as they are.
+    pub fn apply_install_variables(&mut self, values: &HashMap<String, String>) {
+        let fill = |input: &mut String| {
+            *input = template::substitute(input, |placeholder| {
+                if placeholder.namespace.as_deref() == Some(INSTALL_NAMESPACE) {
+                    values.get(&placeholder.name).cloned()
+                } else {
+                    None
+                }
+            });
+        };
+
+        self.bindings.executables.iter_mut().for_each(|executable| fill(&mut executable.target));
+        self.bindings.configs.iter_mut().for_each(|config| fill(&mut config.target));
+        self.bindings.data.iter_mut().for_each(|data| fill(&mut data.target));
+        self.bindings.bin_dir.iter_mut().for_each(fill);
+        self.environment.values_mut().for_each(fill);
+    }
+
+    /// Names of `${install:NAME}` placeholders still left in the manifest, sorted and deduplicated.
+    pub fn unresolved_install_variables(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .templated_values()
+            .flat_map(|value| template::names_in(value, INSTALL_NAMESPACE))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Values are passed to unix processes as they are, so a Windows path such as
+    /// `C:\Tools` would only ever name a relative file called that.
+    fn validate_environment(&self) -> ContainerResult<()> {
+        let mut names: Vec<&String> = self.environment.keys().collect();
+        names.sort();
+        for name in names {
+            let value = &self.environment[name];
+            if portability::is_windows_absolute(value) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Environment variable '{}' is the Windows path '{}', which means nothing on unix",
+                    name, value
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Persistent directories must stay inside the container and away from managed directories,
+    /// otherwise an update would preserve (or removal would keep) files wrappy owns.
+    fn validate_persistent_dirs(&self) -> ContainerResult<()> {
+        const MANAGED_DIRS: [&str; 3] = ["scripts", "content", "config"];
+
+        for entry in &self.persistent {
+            let path = Path::new(entry);
+            let escapes = path.is_absolute()
+                || path.components().any(|c| matches!(c, std::path::Component::ParentDir));
+
+            if entry.is_empty() || escapes {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Persistent directory '{}' must be a relative path inside the container",
+                    entry
+                )));
+            }
+
+            let first_component = path
+                .components()
+                .next()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if MANAGED_DIRS.contains(&first_component.as_str()) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Persistent directory '{}' cannot be inside the managed '{}' directory",
+                    entry, first_component
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks host requirement shape only; availability is checked at validate/run time.
+    fn validate_host_requirements(&self) -> ContainerResult<()> {
+        for requirement in &self.host_requirements {
+            if requirement.command.trim().is_empty() {
+                return Err(ContainerError::ManifestValidation(
+                    "Host requirement command cannot be empty".to_string(),
+                ));
+            }
+
+            if requirement.command.chars().any(char::is_whitespace) {
+                return Err(ContainerError::ManifestValidation(format!(
+                    "Host requirement command '{}' must be a single command name",
+                    requirement.command
+                )));
+            }
+
+            if let Some(version_req) = &requirement.version_req {
+                version_req.parse::<VersionReq>().map_err(|_| {
+                    ContainerError::ManifestValidation(format!(
+                        "Host requirement '{}' has invalid version requirement '{}'",
+                        requirement.command, version_req
+                    ))
+                })?;
+            }
+        }
         Ok(())
     }
 
-    pub fn default_script(&self) -> ContainerResult<&String> {
+    /// Relative path of the default script (materialized location for inline scripts).
+    pub fn default_script(&self) -> ContainerResult<String> {
         self.scripts
             .get("default")
+            .map(|script| script.relative_path("default"))
             .ok_or(ContainerError::MissingDefaultScript)
     }
 
-    pub fn get_script(&self, name: &str) -> ContainerResult<&String> {
+    /// Host variables allowed through when the host environment is not inherited.
+    pub fn environment_passthrough(&self) -> Vec<String> {
+        self.isolation
+            .as_ref()
+            .map(|isolation| isolation.environment_passthrough.clone())
+            .unwrap_or_else(IsolationConfig::default_environment_passthrough)
+    }
+
+    /// Relative path of the named script (materialized location for inline scripts).
+    pub fn get_script(&self, name: &str) -> ContainerResult<String> {
         self.scripts
             .get(name)
+            .map(|script| script.relative_path(name))
             .ok_or(ContainerError::ScriptNotFound {
                 container: self.name.clone(),
                 script: name.to_string(),
@@ -192,7 +751,7 @@ impl ContainerManifest {
     }
 
     pub fn add_script(&mut self, name: String, path: String) {
-        self.scripts.insert(name, path);
+        self.scripts.insert(name, ScriptSpec::Path(path));
     }
 
     pub fn add_dependency(&mut self, dependency: Dependency) {
@@ -200,3 +759,212 @@ impl ContainerManifest {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::{fixture_manifest, write_manifest};
+    use serde_json::{json, Value};
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Mixed plain and structured scripts and every kind of binding array.
+    fn rich_manifest() -> Value {
+        let mut manifest = fixture_manifest("app", "1.2.3");
+        manifest["description"] = json!("An app");
+        manifest["scripts"]["build"] = json!({ "inline": "#!/bin/sh\necho build\n" });
+        manifest["scripts"]["serve"] = json!({ "path": "scripts/serve.sh" });
+        manifest["bindings"]["executables"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "source": "bin/other", "target": "other", "binding_type": "symlink", "args_prepend": ["--x"] }));
+        manifest["bindings"]["configs"] = json!([{ "source": "config/app", "target": "~/.config/app" }]);
+        manifest["bindings"]["data"] = json!([{ "source": "content", "target": "~/.local/share/app", "binding_type": "copy" }]);
+        manifest["dependencies"] = json!([{ "name": "lib", "version": "1.0.0" }]);
+        manifest
+    }
+
+    fn parse(dir: &TempDir, content: &str) -> ContainerManifest {
+        let path = dir.path().join("manifest.json");
+        fs::write(&path, content).unwrap();
+        ContainerManifest::from_file(&path).unwrap()
+    }
+
+    #[test]
+    fn converting_to_json_and_back_is_lossless() {
+        let dir = TempDir::new().unwrap();
+        let original = parse(&dir, &rich_manifest().to_string());
+
+        let converted = original.to_string_as(ManifestFormat::Json).unwrap();
+        let reparsed = parse(&dir, &converted);
+
+        assert_eq!(serde_json::to_value(&reparsed).unwrap(), serde_json::to_value(&original).unwrap());
+        assert_eq!(reparsed.to_string_as(ManifestFormat::Json).unwrap(), converted);
+        let scripts = &serde_json::from_str::<Value>(&converted).unwrap()["scripts"];
+        assert_eq!(scripts["default"], "scripts/default.sh");
+        assert_eq!(scripts["build"]["inline"], "#!/bin/sh\necho build\n");
+    }
+
+    #[test]
+    fn the_field_order_is_stable_and_maps_are_written_in_key_order() {
+        let dir = TempDir::new().unwrap();
+        let converted = parse(&dir, &rich_manifest().to_string()).to_string_as(ManifestFormat::Json).unwrap();
+
+        let position = |field: &str| converted.find(&format!("\"{}\":", field)).unwrap();
+
+        assert!(position("name") < position("version"));
+        assert!(position("version") < position("scripts"));
+        assert!(position("scripts") < position("bindings"));
+        assert!(position("build") < position("default") && position("default") < position("serve"));
+    }
+
+    #[test]
+    fn toml_and_yaml_are_recognized_but_refused_with_a_clear_error() {
+        let dir = TempDir::new().unwrap();
+        let manifest = parse(&dir, &rich_manifest().to_string());
+        fs::write(dir.path().join("manifest.toml"), "name = \"app\"\n").unwrap();
+
+        for format in [ManifestFormat::Toml, ManifestFormat::Yaml] {
+            let error = manifest.to_string_as(format).unwrap_err().to_string();
+            assert!(error.contains("not supported") && error.contains("manifest.json"), "{}", error);
+        }
+        let error = ContainerManifest::from_file(dir.path().join("manifest.toml")).err().unwrap().to_string();
+        assert!(error.contains("TOML manifests are not supported"), "{}", error);
+        assert_eq!(ManifestFormat::from_path(Path::new("manifest.yml")), Some(ManifestFormat::Yaml));
+    }
+
+    #[test]
+    fn locate_finds_the_one_variant_and_refuses_several() {
+        let dir = TempDir::new().unwrap();
+        assert!(ManifestFormat::locate(dir.path()).is_err());
+
+        write_manifest(dir.path(), &rich_manifest());
+        assert_eq!(ManifestFormat::locate(dir.path()).unwrap(), dir.path().join("manifest.json"));
+
+        fs::write(dir.path().join("manifest.yaml"), "name: app\n").unwrap();
+        let error = ManifestFormat::locate(dir.path()).unwrap_err().to_string();
+        assert!(error.contains("manifest.json, manifest.yaml"), "{}", error);
+    }
+
+    type Case = (&'static str, &'static [(&'static str, &'static str)], Option<&'static str>);
+
+    #[test]
+    fn mounts_need_absolute_paths_and_separate_container_paths() {
+        let cases: &[Case] = &[
+            ("one mount", &[("/srv/projects", "/work")], None),
+            ("siblings", &[("/srv/a", "/work"), ("/srv/b", "/data")], None),
+            ("shared prefix is not nesting", &[("/srv/a", "/work"), ("/srv/b", "/workspace")], None),
+            ("same host path twice", &[("/dev/video0", "/dev/cam"), ("/dev/video0", "/dev/cam2")], None),
+            ("relative host", &[("projects", "/work")], Some("mount host path 'projects' must be absolute")),
+            ("relative container", &[("/srv/a", "work")], Some("mount container path 'work' must be absolute")),
+            ("identical", &[("/srv/a", "/work"), ("/srv/b", "/work")], Some("mounts '/work' and '/work' overlap")),
+            ("nested", &[("/srv/a", "/work"), ("/srv/b", "/work/cache")], Some("mounts '/work' and '/work/cache' overlap")),
+            ("enclosing", &[("/srv/a", "/work/cache"), ("/srv/b", "/work")], Some("mounts '/work/cache' and '/work' overlap")),
+            ("trailing slash", &[("/srv/a", "/work/"), ("/srv/b", "/work")], Some("overlap")),
+            ("parent dir", &[("/srv/a", "/work"), ("/srv/b", "/data/../work")], Some("must not contain '..'")),
+        ];
+
+        for (label, mounts, expected) in cases {
+            let mounts: Vec<ScriptMount> = mounts
+                .iter()
+                .map(|(host, container)| ScriptMount {
+                    host: host.to_string(),
+                    container: container.to_string(),
+                    mode: MountMode::default(),
+                })
+                .collect();
+
+            match (ScriptSpec::validate_mounts("serve", &mounts), expected) {
+                (Ok(()), None) => {}
+                (Err(ContainerError::ManifestValidation(message)), Some(expected)) => {
+                    assert!(message.contains(expected), "{}: {}", label, message)
+                }
+                (result, expected) => panic!("{}: expected {:?}, got {:?}", label, expected, result),
+            }
+        }
+    }
+
+    #[test]
+    fn mounts_default_to_read_only_and_survive_a_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let mut manifest = fixture_manifest("app", "1.0.0");
+        manifest["scripts"]["serve"] = json!({
+            "path": "scripts/default.sh",
+            "mounts": [
+                { "host": "/srv/projects", "container": "/work" },
+                { "host": "/dev/video0", "container": "/dev/video0", "mode": "rw" },
+            ],
+        });
+
+        let parsed = parse(&dir, &manifest.to_string());
+        let mounts = parsed.scripts["serve"].mounts();
+
+        assert_eq!(mounts[0].mode, MountMode::Ro);
+        assert_eq!(mounts[1].mode, MountMode::Rw);
+        assert_eq!(mounts[0].to_string(), "/srv/projects -> /work (ro)");
+        assert!(parsed.scripts["default"].mounts().is_empty());
+        let reparsed = parse(&dir, &parsed.to_string_as(ManifestFormat::Json).unwrap());
+        assert_eq!(reparsed.scripts["serve"].mounts(), mounts);
+    }
+
+    #[test]
+    fn a_manifest_with_overlapping_mounts_is_rejected_as_a_whole() {
+        let dir = TempDir::new().unwrap();
+        let mut manifest = fixture_manifest("app", "1.0.0");
+        manifest["scripts"]["serve"] = json!({
+            "path": "scripts/default.sh",
+            "mounts": [{ "host": "/a", "container": "/work" }, { "host": "/b", "container": "/work/x" }],
+        });
+        write_manifest(dir.path(), &manifest);
+
+        let result = ContainerManifest::from_file(dir.path().join("manifest.json"));
+
+        assert!(matches!(result, Err(ContainerError::ManifestValidation(_))), "{:?}", result.map(|_| ()));
+    }
+
+    /// Saved in Notepad on Windows: BOM, CRLF and backslash-separated sources.
+    const WINDOWS_MANIFEST: &str = include_str!("../../../tests/fixtures/windows_manifest/manifest.json");
+
+    #[test]
+    fn a_windows_authored_manifest_loads_with_unix_sources() {
+        let dir = TempDir::new().unwrap();
+
+        let manifest = parse(&dir, WINDOWS_MANIFEST);
+
+        let bindings = &manifest.bindings;
+        assert_eq!(bindings.executables[0].source, "bin/wt");
+        assert_eq!(bindings.configs[0].source, "config/wt");
+        assert_eq!(bindings.data[0].source, "content/themes");
+        let flagged = bindings.platform_specific_targets();
+        assert_eq!(
+            flagged,
+            [("~/AppData/Roaming/wt/themes".to_string(), "AppData".to_string(), "Windows")]
+        );
+    }
+
+    #[test]
+    fn windows_paths_that_cannot_work_on_unix_are_refused_with_the_reason() {
+        let dir = TempDir::new().unwrap();
+        let original: Value = serde_json::from_str(WINDOWS_MANIFEST.trim_start_matches('\u{feff}')).unwrap();
+        type Edit = fn(&mut Value);
+        let cases: [(Edit, &str); 5] = [
+            (|m| m["bindings"]["configs"][0]["target"] = json!(r"C:\Users\me\cfg"), "target is a Windows path"),
+            (|m| m["bindings"]["configs"][0]["target"] = json!(r"~\.config\wt"), "backslash"),
+            (|m| m["bindings"]["executables"][0]["source"] = json!("D:/tools/wt.exe"), "sources are relative"),
+            (|m| m["bindings"]["data"][0]["source"] = json!(r"\\srv\share\themes"), "sources are relative"),
+            (|m| m["environment"]["WT_HOME"] = json!(r"C:\Tools\wt"), "Windows path"),
+        ];
+
+        for (edit, reason) in cases {
+            let mut manifest = original.clone();
+            edit(&mut manifest);
+            let path = dir.path().join("manifest.json");
+            fs::write(&path, manifest.to_string()).unwrap();
+
+            match ContainerManifest::from_file(&path) {
+                Err(ContainerError::ManifestValidation(message)) => assert!(message.contains(reason), "{}", message),
+                other => panic!("{} was accepted: {:?}", manifest, other.map(|m| m.name)),
+            }
+        }
+    }
+}
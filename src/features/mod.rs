@@ -1,9 +1,40 @@
+pub mod about;
+pub mod apply;
+pub mod backup;
 pub mod bindings;
+pub mod changelog;
 pub mod container;
+pub mod daemon;
+pub mod history;
 pub mod manifest;
+pub mod netguard;
+pub mod profile;
+pub mod query;
+pub mod settings;
+pub mod setup;
+pub mod shell;
+pub mod stats;
+pub mod store;
+#[cfg(feature = "tui")]
+pub mod ui;
 pub mod version;
 
+pub use about::*;
+pub use apply::*;
+pub use backup::*;
 pub use bindings::*;
+pub use changelog::*;
 pub use container::*;
+pub use daemon::*;
+pub use history::*;
 pub use manifest::*;
-pub use version::*;
\ No newline at end of file
+pub use netguard::*;
+pub use profile::*;
+pub use settings::*;
+pub use setup::*;
+pub use shell::*;
+pub use stats::*;
+pub use store::*;
+#[cfg(feature = "tui")]
+pub use ui::*;
+pub use version::*;
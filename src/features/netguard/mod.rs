@@ -0,0 +1,7 @@
+//! Which hosts a container may connect to, from the `network` list of its
+//! `config/permissions.json`. Only the rules live here: runs are not sandboxed
+//! yet, so nothing enforces the list at connection time.
+
+mod types;
+
+pub use types::*;
@@ -0,0 +1,238 @@
+use std::fmt;
+use std::net::IpAddr;
+
+/// Host part of an allowlist entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostPattern {
+    /// Exactly this name, lowercase and without a trailing dot
+    Name(String),
+    /// `*.example.com`: any name below the suffix, but not the suffix itself
+    Subdomains(String),
+    Address(IpAddr),
+}
+
+/// One `host:port` entry of the network allowlist. The port may be `*`;
+/// IPv6 addresses are written in brackets, as in `[::1]:8080`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostRule {
+    pub host: HostPattern,
+    /// `None` allows every port
+    pub port: Option<u16>,
+}
+
+impl HostRule {
+    /// Parses one entry; the error says what is wrong with it.
+    pub fn parse(entry: &str) -> Result<Self, String> {
+        let entry = entry.trim();
+        let (host, port) = match entry.strip_prefix('[') {
+            Some(rest) => {
+                let (address, port) = rest
+                    .split_once("]:")
+                    .ok_or_else(|| "IPv6 addresses need the form [address]:port".to_string())?;
+                let address: IpAddr = address
+                    .parse()
+                    .map_err(|_| format!("'{}' is not an IPv6 address", address))?;
+                (HostPattern::Address(address), port)
+            }
+            None => {
+                let (host, port) = entry
+                    .rsplit_once(':')
+                    .ok_or_else(|| "expected host:port, e.g. api.example.com:443".to_string())?;
+                (Self::parse_host(host)?, port)
+            }
+        };
+        let port = match port {
+            "*" => None,
+            port => Some(
+                port.parse::<u16>()
+                    .ok()
+                    .filter(|&port| port != 0)
+                    .ok_or_else(|| format!("'{}' is not a port between 1 and 65535 or *", port))?,
+            ),
+        };
+        Ok(Self { host, port })
+    }
+
+    /// Whether a connection to `host` on `port` is covered by this rule.
+    pub fn allows(&self, host: &str, port: u16) -> bool {
+        if self.port.is_some_and(|allowed| allowed != port) {
+            return false;
+        }
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        match &self.host {
+            HostPattern::Address(address) => host.parse::<IpAddr>().is_ok_and(|host| host == *address),
+            HostPattern::Name(name) => Self::normalize(host) == *name,
+            HostPattern::Subdomains(suffix) => Self::normalize(host)
+                .strip_suffix(suffix.as_str())
+                .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.')),
+        }
+    }
+
+    fn parse_host(host: &str) -> Result<HostPattern, String> {
+        match host.parse::<IpAddr>() {
+            Ok(IpAddr::V6(_)) => {
+                // `::1:80` could be an address or an address and a port
+                return Err(format!("IPv6 address '{}' needs brackets, as in [{}]:port", host, host));
+            }
+            Ok(address) => return Ok(HostPattern::Address(address)),
+            Err(_) => {}
+        }
+        let (wildcard, name) = match host.strip_prefix("*.") {
+            Some(name) => (true, name),
+            None => (false, host),
+        };
+        let name = Self::normalize(name);
+        let valid_label =
+            |label: &str| !label.is_empty() && label.len() <= 63 && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if name.is_empty() || !name.split('.').all(valid_label) {
+            return Err(format!("'{}' is not a host name", host));
+        }
+        Ok(match wildcard {
+            true => HostPattern::Subdomains(name),
+            false => HostPattern::Name(name),
+        })
+    }
+
+    fn normalize(host: &str) -> String {
+        host.trim_end_matches('.').to_ascii_lowercase()
+    }
+}
+
+impl fmt::Display for HostRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.host {
+            HostPattern::Name(name) => write!(f, "{}", name)?,
+            HostPattern::Subdomains(suffix) => write!(f, "*.{}", suffix)?,
+            HostPattern::Address(IpAddr::V6(address)) => write!(f, "[{}]", address)?,
+            HostPattern::Address(address) => write!(f, "{}", address)?,
+        }
+        match self.port {
+            Some(port) => write!(f, ":{}", port),
+            None => write!(f, ":*"),
+        }
+    }
+}
+
+/// The hosts a container may connect to. An empty list allows nothing beyond what
+/// the isolation mode of the manifest allows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostAllowlist {
+    rules: Vec<HostRule>,
+}
+
+impl HostAllowlist {
+    /// Parses every entry and reports all malformed ones at once.
+    pub fn parse(entries: &[String]) -> Result<Self, Vec<String>> {
+        let mut rules = Vec::new();
+        let mut errors = Vec::new();
+        for entry in entries {
+            match HostRule::parse(entry) {
+                Ok(rule) => rules.push(rule),
+                Err(error) => errors.push(format!("network entry '{}': {}", entry, error)),
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(Self { rules }),
+            false => Err(errors),
+        }
+    }
+
+    pub fn rules(&self) -> &[HostRule] {
+        &self.rules
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn allows(&self, host: &str, port: u16) -> bool {
+        self.rules.iter().any(|rule| rule.allows(host, port))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_names_wildcards_and_addresses_and_displays_them_back() {
+        let cases = [
+            ("api.example.com:443", "api.example.com:443"),
+            ("API.Example.COM.:443", "api.example.com:443"),
+            ("*.example.com:*", "*.example.com:*"),
+            ("10.0.0.1:5432", "10.0.0.1:5432"),
+            ("[::1]:8080", "[::1]:8080"),
+            ("[2001:db8::1]:*", "[2001:db8::1]:*"),
+            ("  localhost:65535 ", "localhost:65535"),
+        ];
+
+        for (entry, shown) in cases {
+            let rule = HostRule::parse(entry).unwrap();
+            assert_eq!(rule.to_string(), shown, "{}", entry);
+            assert_eq!(HostRule::parse(shown).unwrap(), rule, "{}", entry);
+        }
+    }
+
+    #[test]
+    fn parse_rejects_malformed_entries() {
+        let entries = [
+            "example.com",
+            "example.com:0",
+            "example.com:65536",
+            "example.com:http",
+            ":443",
+            "exa mple.com:443",
+            "bad..dots:443",
+            "*.:443",
+            "[::1:8080",
+            "[not-an-address]:80",
+            "::1:80",
+            "under_score.example.com:443",
+        ];
+
+        for entry in entries {
+            assert!(HostRule::parse(entry).is_err(), "{} was accepted", entry);
+        }
+    }
+
+    #[test]
+    fn rules_allow_only_their_host_and_port() {
+        let allowlist = HostAllowlist::parse(&[
+            "api.example.com:443".to_string(),
+            "*.cdn.example.com:*".to_string(),
+            "[::1]:8080".to_string(),
+        ])
+        .unwrap();
+        // (host, port, allowed)
+        let cases = [
+            ("api.example.com", 443, true),
+            ("API.example.com.", 443, true),
+            ("api.example.com", 80, false),
+            ("other.example.com", 443, false),
+            ("img.cdn.example.com", 8443, true),
+            ("a.b.cdn.example.com", 1, true),
+            ("cdn.example.com", 443, false),
+            ("evilcdn.example.com", 443, false),
+            ("::1", 8080, true),
+            ("[::1]", 8080, true),
+            ("0:0:0:0:0:0:0:1", 8080, true),
+            ("::2", 8080, false),
+        ];
+
+        for (host, port, allowed) in cases {
+            assert_eq!(allowlist.allows(host, port), allowed, "{}:{}", host, port);
+        }
+        assert!(!HostAllowlist::default().allows("localhost", 80));
+    }
+
+    #[test]
+    fn an_allowlist_reports_every_malformed_entry_at_once() {
+        let entries = ["ok.example.com:443".to_string(), "broken".to_string(), "also:broken".to_string()];
+
+        let errors = HostAllowlist::parse(&entries).unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("network entry 'broken':"), "{}", errors[0]);
+        assert!(errors[1].starts_with("network entry 'also:broken':"), "{}", errors[1]);
+    }
+}
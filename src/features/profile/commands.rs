@@ -0,0 +1,145 @@
+use clap::Subcommand;
+
+use crate::features::bindings::BindingManager;
+use crate::features::container::{install_interrupt_handler, interrupted};
+use crate::features::profile::{MemberState, ProfileService, ProfileSummary};
+use crate::shared::error::ContainerError;
+use crate::shared::CancellationToken;
+
+#[derive(Subcommand)]
+pub enum ProfileCommands {
+    /// Enable the bindings of every container in a profile
+    Enable {
+        /// Profile name from the config's "profiles" section
+        name: String,
+    },
+    /// Disable the bindings of every container in a profile
+    Disable {
+        /// Profile name from the config's "profiles" section
+        name: String,
+    },
+    /// Disable the containers of all other profiles, then enable this one
+    Switch {
+        /// Profile name from the config's "profiles" section
+        name: String,
+    },
+    /// Show the binding state of profile members
+    Status {
+        /// Only show this profile
+        name: Option<String>,
+    },
+}
+
+pub struct ProfileHandler;
+
+impl ProfileHandler {
+    /// Routes and executes the appropriate profile command
+    pub fn execute_command(command: ProfileCommands) -> i32 {
+        match command {
+            ProfileCommands::Enable { name } => {
+                Self::handle_bulk_command(&name, "enable", |service, manager| service.enable(&name, manager))
+            }
+            ProfileCommands::Disable { name } => {
+                Self::handle_bulk_command(&name, "disable", |service, manager| service.disable(&name, manager))
+            }
+            ProfileCommands::Switch { name } => {
+                Self::handle_bulk_command(&name, "switch to", |service, manager| service.switch(&name, manager))
+            }
+            ProfileCommands::Status { name } => Self::handle_status_command(name),
+        }
+    }
+
+    /// Runs one bulk operation with Ctrl-C handling and prints its summary
+    fn handle_bulk_command(
+        name: &str,
+        verb: &str,
+        operation: impl FnOnce(&ProfileService, &BindingManager) -> Result<ProfileSummary, ContainerError>,
+    ) -> i32 {
+        install_interrupt_handler();
+        let result = ProfileService::new().and_then(|service| {
+            let manager = BindingManager::new()?.with_cancellation(CancellationToken::linked_to(interrupted));
+            operation(&service, &manager)
+        });
+
+        match result {
+            Ok(summary) => {
+                Self::print_summary(name, &summary);
+                if summary.is_success() {
+                    0
+                } else {
+                    1
+                }
+            }
+            Err(error @ ContainerError::Cancelled { .. }) => {
+                eprintln!("⏹️  Cancelled; members handled before the interruption keep their new state");
+                error.exit_code()
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to {} profile '{}': {}", verb, name, error);
+                1
+            }
+        }
+    }
+
+    /// Handles the status command execution
+    fn handle_status_command(name: Option<String>) -> i32 {
+        let service = match ProfileService::new() {
+            Ok(service) => service,
+            Err(error) => {
+                eprintln!("❌ Failed to load profiles: {}", error);
+                return 1;
+            }
+        };
+
+        let names: Vec<String> = match name {
+            Some(name) => vec![name],
+            None => service.profiles().keys().cloned().collect(),
+        };
+        if names.is_empty() {
+            println!("ℹ️  No profiles configured.");
+            println!("   Add a \"profiles\" section to the config, e.g. \"profiles\": {{\"work\": [\"vpn-tool\"]}}");
+            return 0;
+        }
+
+        for name in names {
+            let statuses = match service.status(&name) {
+                Ok(statuses) => statuses,
+                Err(error) => {
+                    eprintln!("❌ {}", error);
+                    return 1;
+                }
+            };
+            println!("📁 {}", name);
+            for status in statuses {
+                let marker = match status.state {
+                    MemberState::Enabled => "✅",
+                    MemberState::Partial => "🟡",
+                    MemberState::Disabled | MemberState::NoBindings => "⚪",
+                    MemberState::Missing => "❓",
+                };
+                println!("  {} {} ({})", marker, status.name, status.state);
+            }
+        }
+        0
+    }
+
+    fn print_summary(name: &str, summary: &ProfileSummary) {
+        println!();
+        println!("📁 Profile '{}':", name);
+        if !summary.disabled.is_empty() {
+            println!("  🗑️  Disabled: {}", summary.disabled.join(", "));
+        }
+        if !summary.enabled.is_empty() {
+            println!("  🔗 Enabled: {}", summary.enabled.join(", "));
+        }
+        if !summary.missing.is_empty() {
+            println!("  ❓ Not installed: {}", summary.missing.join(", "));
+        }
+        for (container, reason) in &summary.failed {
+            println!("  ❌ {}: {}", container, reason);
+        }
+        if summary.enabled.is_empty() && summary.disabled.is_empty() && summary.missing.is_empty() && summary.is_success() {
+            println!("  ℹ️  Nothing to change");
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::features::bindings::BindingManager;
+use crate::features::container::{Container, ContainerResolver, ContainerService, Resolution};
+use crate::features::profile::{MemberState, MemberStatus, ProfileSummary};
+use crate::features::query;
+use crate::shared::config::Config;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::paths::WrappyPaths;
+
+/// Enables and disables the bindings of named groups of containers from the config.
+pub struct ProfileService {
+    profiles: BTreeMap<String, Vec<String>>,
+    resolver: ContainerResolver,
+}
+
+impl ProfileService {
+    pub fn new() -> ContainerResult<Self> {
+        let paths = WrappyPaths::new()?;
+        let config = Config::load_or_default(&paths)?;
+        Ok(Self::with_profiles(config.profiles, ContainerResolver::new()?))
+    }
+
+    pub fn with_profiles(profiles: BTreeMap<String, Vec<String>>, resolver: ContainerResolver) -> Self {
+        Self { profiles, resolver }
+    }
+
+    pub fn profiles(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.profiles
+    }
+
+    pub fn members(&self, profile: &str) -> ContainerResult<&[String]> {
+        self.profiles
+            .get(profile)
+            .map(Vec::as_slice)
+            .ok_or_else(|| ContainerError::ProfileNotFound {
+                name: profile.to_string(),
+            })
+    }
+
+    /// Profiles listing `container`, by bare name or as `name@version`.
+    pub fn profiles_of(&self, container: &str) -> Vec<String> {
+        self.profiles
+            .iter()
+            .filter(|(_, members)| members.iter().any(|member| Self::member_name(member) == container))
+            .map(|(profile, _)| profile.clone())
+            .collect()
+    }
+
+    /// Binding state of every member of `profile`.
+    pub fn status(&self, profile: &str) -> ContainerResult<Vec<MemberStatus>> {
+        Ok(self
+            .members(profile)?
+            .iter()
+            .map(|member| MemberStatus {
+                name: member.clone(),
+                state: self.member_state(member),
+            })
+            .collect())
+    }
+
+    /// Enables the bindings of every installed member. Nothing is changed when two
+    /// containers would bind the same path; a member that fails does not stop the others.
+    pub fn enable(&self, profile: &str, manager: &BindingManager) -> ContainerResult<ProfileSummary> {
+        let (containers, missing) = self.load_members(self.members(profile)?);
+        self.ensure_no_conflicts(&containers, &BTreeSet::new(), manager)?;
+        Self::enable_containers(&containers, missing, manager)
+    }
+
+    /// Disables the bindings of every installed member.
+    pub fn disable(&self, profile: &str, manager: &BindingManager) -> ContainerResult<ProfileSummary> {
+        let (containers, missing) = self.load_members(self.members(profile)?);
+        let mut summary = ProfileSummary {
+            missing,
+            ..ProfileSummary::default()
+        };
+        Self::disable_containers(&containers, manager, &mut summary);
+        Ok(summary)
+    }
+
+    /// Disables the members of every other profile that are not in `profile`, then enables `profile`.
+    /// Conflicts are checked first, ignoring bindings the switch is about to remove.
+    pub fn switch(&self, profile: &str, manager: &BindingManager) -> ContainerResult<ProfileSummary> {
+        let wanted: BTreeSet<&str> = self.members(profile)?.iter().map(|member| Self::member_name(member)).collect();
+        let others: Vec<String> = self
+            .profiles
+            .iter()
+            .filter(|(name, _)| name.as_str() != profile)
+            .flat_map(|(_, members)| members.iter())
+            .filter(|member| !wanted.contains(Self::member_name(member)))
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let (containers, missing) = self.load_members(self.members(profile)?);
+        let (leaving, _) = self.load_members(&others);
+        let leaving_names: BTreeSet<String> = leaving.iter().map(|container| container.name().to_string()).collect();
+        self.ensure_no_conflicts(&containers, &leaving_names, manager)?;
+
+        let mut summary = ProfileSummary::default();
+        Self::disable_containers(&leaving, manager, &mut summary);
+        let enabled = Self::enable_containers(&containers, missing, manager)?;
+        summary.enabled = enabled.enabled;
+        summary.missing = enabled.missing;
+        summary.failed.extend(enabled.failed);
+        Ok(summary)
+    }
+
+    fn enable_containers(containers: &[Container], missing: Vec<String>, manager: &BindingManager) -> ContainerResult<ProfileSummary> {
+        let mut summary = ProfileSummary {
+            missing,
+            ..ProfileSummary::default()
+        };
+        for container in containers {
+            match Self::bindings_state(&container.path.to_string_lossy()) {
+                MemberState::NoBindings => continue,
+                MemberState::Enabled => {
+                    summary.enabled.push(container.name().to_string());
+                    continue;
+                }
+                _ => {}
+            }
+            match manager.install_bindings(container) {
+                Ok(_) => summary.enabled.push(container.name().to_string()),
+                // Stop at once; the interrupted member already rolled itself back
+                Err(error @ ContainerError::Cancelled { .. }) => return Err(error),
+                Err(error) => summary.failed.push((container.name().to_string(), error.to_string())),
+            }
+        }
+        Ok(summary)
+    }
+
+    fn disable_containers(containers: &[Container], manager: &BindingManager, summary: &mut ProfileSummary) {
+        for container in containers {
+            if matches!(
+                Self::bindings_state(&container.path.to_string_lossy()),
+                MemberState::NoBindings | MemberState::Disabled
+            ) {
+                continue;
+            }
+            match manager.remove_bindings(container) {
+                Ok(()) => summary.disabled.push(container.name().to_string()),
+                Err(error) => summary.failed.push((container.name().to_string(), error.to_string())),
+            }
+        }
+    }
+
+    /// Fails with every path that two of `containers` would bind, or that a container
+    /// outside them (and not in `leaving`) already has bound.
+    fn ensure_no_conflicts(
+        &self,
+        containers: &[Container],
+        leaving: &BTreeSet<String>,
+        manager: &BindingManager,
+    ) -> ContainerResult<()> {
+        let mut owners = manager.target_owners(containers)?;
+        for record in manager.recorded_bindings()? {
+            if leaving.contains(&record.container) {
+                continue;
+            }
+            if let Some(names) = owners.get_mut(&record.target_path) {
+                names.insert(record.container);
+            }
+        }
+
+        let conflicts: Vec<String> = BindingManager::conflicts(owners)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(ContainerError::BindingConflict { conflicts })
+        }
+    }
+
+    /// Loads installed members; names that resolve to nothing are returned separately.
+    /// A bare name with several installed versions means the newest one.
+    fn load_members(&self, members: &[String]) -> (Vec<Container>, Vec<String>) {
+        let mut containers = Vec::new();
+        let mut missing = Vec::new();
+        for member in members {
+            let path = match self.resolver.resolve(member) {
+                Resolution::Path(path) => path,
+                Resolution::Unique(candidate) => candidate.path,
+                Resolution::Ambiguous { candidates, .. } => candidates[0].path.clone(),
+                Resolution::NotFound { .. } => {
+                    missing.push(member.clone());
+                    continue;
+                }
+            };
+            match ContainerService::load_from_directory(&path) {
+                Ok(container) => containers.push(container),
+                Err(_) => missing.push(member.clone()),
+            }
+        }
+        (containers, missing)
+    }
+
+    fn member_state(&self, member: &str) -> MemberState {
+        if matches!(self.resolver.resolve(member), Resolution::NotFound { .. }) {
+            return MemberState::Missing;
+        }
+        Self::bindings_state(member)
+    }
+
+    /// Enabling an already enabled container would trip over its own targets
+    fn bindings_state(name_or_path: &str) -> MemberState {
+        let bindings = query::bindings_of(name_or_path);
+        let active = bindings.iter().filter(|binding| binding.active).count();
+        match active {
+            _ if bindings.is_empty() => MemberState::NoBindings,
+            0 => MemberState::Disabled,
+            active if active == bindings.len() => MemberState::Enabled,
+            _ => MemberState::Partial,
+        }
+    }
+
+    fn member_name(member: &str) -> &str {
+        member.split_once('@').map_or(member, |(name, _)| name)
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt;
+
+/// How far the bindings of one profile member are installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberState {
+    /// Every binding of the member is active
+    Enabled,
+    /// Some bindings are active, some are not
+    Partial,
+    Disabled,
+    /// The manifest declares no bindings
+    NoBindings,
+    /// No version of the member is installed
+    Missing,
+}
+
+impl fmt::Display for MemberState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            MemberState::Enabled => "enabled",
+            MemberState::Partial => "partially enabled",
+            MemberState::Disabled => "disabled",
+            MemberState::NoBindings => "no bindings",
+            MemberState::Missing => "not installed",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MemberStatus {
+    pub name: String,
+    pub state: MemberState,
+}
+
+/// Outcome of enabling or disabling a whole profile; members fail independently.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileSummary {
+    pub enabled: Vec<String>,
+    pub disabled: Vec<String>,
+    pub missing: Vec<String>,
+    /// Members whose bindings could not be changed, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+impl ProfileSummary {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
@@ -0,0 +1,10 @@
+//! Cheap, read-only lookups over installed containers for front-ends such as
+//! shell completion or a TUI. Everything here is served from the store layout
+//! and the manifest cache without full validation, and is a stable API:
+//! fields are only ever added, never renamed or removed.
+
+mod service;
+mod types;
+
+pub use service::*;
+pub use types::*;
@@ -0,0 +1,272 @@
+use std::path::{Path, PathBuf};
+
+use crate::features::bindings::{BindingCategory, BindingManager, BindingType};
+use crate::features::container::{ContainerResolver, Resolution};
+use crate::features::manifest::{ContainerManifest, ManifestCache};
+use crate::features::query::{BindingStatus, ContainerSummary, ScriptSummary};
+
+/// Lists every installed container version, by name and newest version first.
+pub fn installed_containers() -> Vec<ContainerSummary> {
+    StoreQuery::open().map(|query| query.installed_containers()).unwrap_or_default()
+}
+
+/// Scripts of the container `name_or_path` resolves to, sorted by name.
+pub fn scripts_of(name_or_path: &str) -> Vec<ScriptSummary> {
+    StoreQuery::open().map(|query| query.scripts_of(name_or_path)).unwrap_or_default()
+}
+
+/// Bindings of the container `name_or_path` resolves to, in manifest order.
+pub fn bindings_of(name_or_path: &str) -> Vec<BindingStatus> {
+    StoreQuery::open().map(|query| query.bindings_of(name_or_path)).unwrap_or_default()
+}
+
+/// Resolves a container name, `name@version` or path the same way every command does.
+pub fn resolve(name_or_path: &str) -> Resolution {
+    match StoreQuery::open() {
+        Some(query) => query.resolve(name_or_path),
+        None => Resolution::NotFound {
+            name: name_or_path.to_string(),
+        },
+    }
+}
+
+/// Installed names close to `name`, for "did you mean" hints.
+pub fn similar_names(name: &str) -> Vec<String> {
+    StoreQuery::open().map(|query| query.resolver.similar_names(name)).unwrap_or_default()
+}
+
+/// Queries against one store. The free functions use the configured store;
+/// front-ends that manage their own store construct this directly.
+pub struct StoreQuery {
+    resolver: ContainerResolver,
+}
+
+impl StoreQuery {
+    /// Uses the store from the user config; `None` when no home directory can be found.
+    pub fn open() -> Option<Self> {
+        ContainerResolver::new().ok().map(|resolver| Self { resolver })
+    }
+
+    pub fn with_store(store_dir: PathBuf) -> Self {
+        Self {
+            resolver: ContainerResolver::with_store(store_dir),
+        }
+    }
+
+    pub fn installed_containers(&self) -> Vec<ContainerSummary> {
+        let candidates: Vec<_> = self
+            .resolver
+            .installed_names()
+            .iter()
+            .flat_map(|name| self.resolver.installed_versions(name))
+            .collect();
+
+        // Opened after resolving so it sees the entries the resolver just cached
+        let mut cache = ManifestCache::open();
+        let summaries = candidates
+            .into_iter()
+            .filter_map(|candidate| {
+                let manifest = Self::manifest_at(&mut cache, &candidate.path)?;
+                Some(ContainerSummary {
+                    name: candidate.name,
+                    version: candidate.version,
+                    container_type: manifest.container_type,
+                    description: manifest.description,
+                    path: candidate.path,
+                })
+            })
+            .collect();
+        // A cache that cannot be written only costs speed on the next run
+        let _ = cache.save();
+        summaries
+    }
+
+    pub fn scripts_of(&self, name_or_path: &str) -> Vec<ScriptSummary> {
+        let Some((path, manifest)) = self.load(name_or_path) else {
+            return Vec::new();
+        };
+
+        let mut scripts: Vec<ScriptSummary> = manifest
+            .scripts
+            .iter()
+            .map(|(name, spec)| ScriptSummary {
+                name: name.clone(),
+                path: path.join(spec.relative_path(name)),
+                inline: spec.is_inline(),
+            })
+            .collect();
+        scripts.sort_by(|a, b| a.name.cmp(&b.name));
+        scripts
+    }
+
+    pub fn bindings_of(&self, name_or_path: &str) -> Vec<BindingStatus> {
+        let Some((path, manifest)) = self.load(name_or_path) else {
+            return Vec::new();
+        };
+        let manager = BindingManager::open().ok();
+        let bindings = &manifest.bindings;
+        let bin_dir = manager.as_ref().and_then(|manager| manager.bin_dir_for(&manifest.name, bindings).ok());
+
+        let executables = bindings.executables.iter().map(|executable| {
+            let target_path = manager
+                .as_ref()
+                .zip(bin_dir.as_ref())
+                .and_then(|(manager, bin_dir)| manager.executable_path_in(bin_dir, executable).ok());
+            Self::binding_status(
+                BindingCategory::Executables,
+                None,
+                &executable.binding_type(),
+                path.join(&executable.source),
+                &executable.target,
+                target_path,
+            )
+        });
+        let directories = bindings
+            .configs
+            .iter()
+            .map(|config| (BindingCategory::Configs, config.label(), config.binding_type(), &config.source, &config.target))
+            .chain(
+                bindings
+                    .data
+                    .iter()
+                    .map(|data| (BindingCategory::Data, data.label(), data.binding_type(), &data.source, &data.target)),
+            )
+            .map(|(category, label, binding_type, source, target)| {
+                let target_path = manager
+                    .as_ref()
+                    .and_then(|manager| manager.directory_target_path(target).ok());
+                Self::binding_status(category, Some(label), &binding_type, path.join(source), target, target_path)
+            });
+
+        executables.chain(directories).collect()
+    }
+
+    pub fn resolve(&self, name_or_path: &str) -> Resolution {
+        self.resolver.resolve(name_or_path)
+    }
+
+    /// Ambiguous names pick the newest version, which is what completion wants to show.
+    fn load(&self, name_or_path: &str) -> Option<(PathBuf, ContainerManifest)> {
+        let path = match self.resolve(name_or_path) {
+            Resolution::Path(path) => path,
+            Resolution::Unique(candidate) => candidate.path,
+            Resolution::Ambiguous { candidates, .. } => candidates.into_iter().next()?.path,
+            Resolution::NotFound { .. } => return None,
+        };
+
+        let mut cache = ManifestCache::open();
+        let manifest = Self::manifest_at(&mut cache, &path)?;
+        let _ = cache.save();
+        Some((path, manifest))
+    }
+
+    fn manifest_at(cache: &mut ManifestCache, path: &Path) -> Option<ContainerManifest> {
+        cache.get_or_load(&path.join("manifest.json")).ok()
+    }
+
+    fn binding_status(
+        category: BindingCategory,
+        label: Option<String>,
+        binding_type: &BindingType,
+        source: PathBuf,
+        target: &str,
+        target_path: Option<PathBuf>,
+    ) -> BindingStatus {
+        let active = target_path
+            .as_deref()
+            .is_some_and(|target_path| BindingManager::is_binding_active(target_path, binding_type, &source));
+        BindingStatus {
+            category,
+            label,
+            binding_type: binding_type.clone(),
+            source,
+            target: target.to_string(),
+            target_path,
+            active,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::ContainerService;
+    use crate::features::setup::SetupService;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, TempHome};
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    /// node 18.0.0 and 20.1.0 (with an inline `build` script) and python 3.12.0 in the default store.
+    fn store(home: &TempHome) -> PathBuf {
+        SetupService::initialize(&home.paths()).unwrap();
+        let store = home.paths().default_store_dir();
+        for (name, version) in [("node", "18.0.0"), ("node", "20.1.0"), ("python", "3.12.0")] {
+            let mut manifest = fixture_manifest(name, version);
+            manifest["description"] = json!(format!("{} runtime", name));
+            if version == "20.1.0" {
+                manifest["scripts"]["build"] = json!({ "inline": "#!/bin/sh\necho build\n" });
+            }
+            fixture_container_at(&store.join(name).join(version), manifest);
+        }
+        store
+    }
+
+    #[test]
+    fn installed_containers_lists_every_version_by_name_newest_first() {
+        let home = TempHome::enter();
+        let store = store(&home);
+
+        let summaries = installed_containers();
+
+        let listed: Vec<String> = summaries
+            .iter()
+            .map(|summary| format!("{}@{}", summary.name, summary.version))
+            .collect();
+        assert_eq!(listed, ["node@20.1.0", "node@18.0.0", "python@3.12.0"]);
+        assert_eq!(summaries[2].description, "python runtime");
+        assert_eq!(summaries[2].path, store.join("python/3.12.0"));
+    }
+
+    #[test]
+    fn scripts_of_picks_the_newest_version_and_reports_inline_scripts() {
+        let home = TempHome::enter();
+        let store = store(&home);
+
+        let scripts = scripts_of("node");
+
+        let names: Vec<(&str, bool)> = scripts.iter().map(|script| (script.name.as_str(), script.inline)).collect();
+        assert_eq!(names, [("build", true), ("default", false)]);
+        assert_eq!(scripts[1].path, store.join("node/20.1.0/scripts/default.sh"));
+        assert_eq!(scripts_of("node@18.0.0").len(), 1);
+        assert!(scripts_of("missing").is_empty());
+    }
+
+    #[test]
+    fn bindings_of_reports_whether_each_binding_is_installed() {
+        let home = TempHome::enter();
+        let store = store(&home);
+
+        let before = bindings_of("python");
+        let container = ContainerService::load_from_directory(store.join("python/3.12.0")).unwrap();
+        BindingManager::open().unwrap().install_bindings(&container).unwrap();
+        let after = bindings_of("python");
+
+        assert_eq!(before.len(), 1);
+        assert_eq!(before[0].target, "python-tool");
+        assert_eq!(before[0].source, store.join("python/3.12.0/bin/tool"));
+        assert!(!before[0].active);
+        assert!(after[0].active);
+        assert!(after[0].target_path.as_ref().unwrap().exists());
+    }
+
+    #[test]
+    fn resolve_matches_the_command_resolver() {
+        let home = TempHome::enter();
+        let store = store(&home);
+
+        assert_matches!(resolve("python"), Resolution::Unique(candidate) if candidate.path == store.join("python/3.12.0"));
+        assert_matches!(resolve("node"), Resolution::Ambiguous { candidates, .. } if candidates.len() == 2);
+        assert_matches!(resolve("nope"), Resolution::NotFound { name } if name == "nope");
+        assert_eq!(similar_names("pyton"), ["python"]);
+    }
+}
@@ -0,0 +1,40 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::features::bindings::{BindingCategory, BindingType};
+use crate::features::manifest::ContainerType;
+use crate::features::Version;
+
+/// One installed version of a container.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContainerSummary {
+    pub name: String,
+    pub version: Version,
+    pub container_type: ContainerType,
+    pub description: String,
+    pub path: PathBuf,
+}
+
+/// One script declared in a container manifest.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptSummary {
+    pub name: String,
+    /// Absolute path of the script file; inline scripts point at their materialized file
+    pub path: PathBuf,
+    pub inline: bool,
+}
+
+/// One binding declared in a container manifest and whether it is installed.
+#[derive(Debug, Clone, Serialize)]
+pub struct BindingStatus {
+    pub category: BindingCategory,
+    /// Label of config and data bindings, explicit or derived from the target
+    pub label: Option<String>,
+    pub binding_type: BindingType,
+    pub source: PathBuf,
+    /// Target as written in the manifest
+    pub target: String,
+    /// Host path the binding occupies, `None` when the target cannot be expanded
+    pub target_path: Option<PathBuf>,
+    pub active: bool,
+}
@@ -0,0 +1,161 @@
+use clap::Subcommand;
+use serde_json::Value;
+
+use crate::features::settings::{ConfigService, ConfigSource};
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print the effective value of a key, e.g. store.dedup
+    Get {
+        /// Dotted key, e.g. store_dir or bindings.default_type
+        key: String,
+    },
+    /// Set a key in the config file; the value is parsed as JSON, or taken as a string
+    Set {
+        /// Dotted key, e.g. network.offline
+        key: String,
+        /// New value, e.g. true, 14, '["copy"]' or /mnt/apps
+        value: String,
+    },
+    /// Remove a key from the config file so its default applies again
+    Unset {
+        /// Dotted key, e.g. stash_max_age_days
+        key: String,
+    },
+    /// Show the keys set in the config file
+    List {
+        /// Show every key with its effective value and where it comes from
+        #[arg(long)]
+        defaults: bool,
+    },
+    /// Report unknown keys and invalid values in the config file
+    Validate,
+}
+
+pub struct ConfigHandler;
+
+impl ConfigHandler {
+    /// Routes and executes the appropriate config command
+    pub fn execute_command(command: ConfigCommands) -> i32 {
+        let service = match ConfigService::new() {
+            Ok(service) => service,
+            Err(error) => {
+                eprintln!("❌ {}", error);
+                return 1;
+            }
+        };
+
+        match command {
+            ConfigCommands::Get { key } => Self::handle_get_command(&service, &key),
+            ConfigCommands::Set { key, value } => Self::handle_set_command(&service, &key, &value),
+            ConfigCommands::Unset { key } => Self::handle_unset_command(&service, &key),
+            ConfigCommands::List { defaults } => Self::handle_list_command(&service, defaults),
+            ConfigCommands::Validate => Self::handle_validate_command(&service),
+        }
+    }
+
+    /// Handles the get command execution; strings are printed bare for use in scripts
+    fn handle_get_command(service: &ConfigService, key: &str) -> i32 {
+        match service.get(key) {
+            Ok(Some(entry)) => {
+                match entry.value {
+                    Value::String(text) => println!("{}", text),
+                    value => println!("{}", value),
+                }
+                0
+            }
+            Ok(None) => {
+                eprintln!("❌ Unknown config key '{}'", key);
+                1
+            }
+            Err(error) => {
+                eprintln!("❌ {}", error);
+                1
+            }
+        }
+    }
+
+    /// Handles the set command execution
+    fn handle_set_command(service: &ConfigService, key: &str, value: &str) -> i32 {
+        match service.set(key, value) {
+            Ok(value) => {
+                println!("✅ Set {} = {}", key, value);
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to set {}: {}", key, error);
+                1
+            }
+        }
+    }
+
+    /// Handles the unset command execution
+    fn handle_unset_command(service: &ConfigService, key: &str) -> i32 {
+        match service.unset(key) {
+            Ok(true) => {
+                println!("🗑️  Removed {}; its default applies again", key);
+                0
+            }
+            Ok(false) => {
+                println!("ℹ️  {} is not set in {}", key, service.config_file().display());
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to unset {}: {}", key, error);
+                1
+            }
+        }
+    }
+
+    /// Handles the list command execution
+    fn handle_list_command(service: &ConfigService, defaults: bool) -> i32 {
+        let entries = match service.entries(defaults) {
+            Ok(entries) => entries,
+            Err(error) => {
+                eprintln!("❌ {}", error);
+                return 1;
+            }
+        };
+
+        if entries.is_empty() {
+            println!("ℹ️  No keys are set in {}", service.config_file().display());
+            println!("   Use 'wrappy config list --defaults' to see the values in effect");
+            return 0;
+        }
+
+        println!("📋 Config ({}):", service.config_file().display());
+        for entry in entries {
+            match (defaults, entry.source) {
+                (true, source) => println!("  {} = {} ({})", entry.key, entry.value, source),
+                (false, ConfigSource::Cli) => println!("  {} = {} (cli)", entry.key, entry.value),
+                (false, _) => println!("  {} = {}", entry.key, entry.value),
+            }
+        }
+        0
+    }
+
+    /// Handles the validate command execution; unknown keys only warn
+    fn handle_validate_command(service: &ConfigService) -> i32 {
+        let report = match service.validate() {
+            Ok(report) => report,
+            Err(error) => {
+                eprintln!("❌ {}", error);
+                return 1;
+            }
+        };
+
+        for key in &report.unknown_keys {
+            println!("⚠️  Unknown key '{}' (kept in the file, ignored by this version)", key);
+        }
+        for error in &report.errors {
+            eprintln!("❌ {}", error);
+        }
+
+        if report.is_valid() {
+            println!("✅ {} is valid", service.config_file().display());
+            0
+        } else {
+            1
+        }
+    }
+}
@@ -0,0 +1,7 @@
+mod commands;
+mod service;
+mod types;
+
+pub use commands::*;
+pub use service::*;
+pub use types::*;
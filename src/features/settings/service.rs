@@ -0,0 +1,300 @@
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+use crate::features::bindings::BindingPolicy;
+use crate::features::settings::{ConfigEntry, ConfigReport, ConfigSource};
+use crate::shared::config::Config;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::network::NetworkPolicy;
+use crate::shared::paths::WrappyPaths;
+
+/// Reads and edits the config file as a JSON document addressed by dotted keys.
+/// Edits go through the document rather than `Config`, so keys written by newer
+/// versions survive; the result is still checked against `Config` before it is saved.
+pub struct ConfigService {
+    paths: WrappyPaths,
+}
+
+impl ConfigService {
+    pub fn new() -> ContainerResult<Self> {
+        Ok(Self::with_paths(WrappyPaths::new()?))
+    }
+
+    pub fn with_paths(paths: WrappyPaths) -> Self {
+        Self { paths }
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.paths.config_file()
+    }
+
+    /// Effective value of `key`, or `None` when the key is unknown and not in the file.
+    pub fn get(&self, key: &str) -> ContainerResult<Option<ConfigEntry>> {
+        let document = self.read_document()?;
+        let effective = self.effective(&document);
+        let Some(value) = Self::lookup(&effective, key) else {
+            return Ok(None);
+        };
+        let mut entry = ConfigEntry {
+            key: key.to_string(),
+            value: value.clone(),
+            source: Self::source_in(&document, key),
+        };
+        if let Some(cli) = self.cli_overrides(&document).into_iter().find(|cli| cli.key == key) {
+            entry = cli;
+        }
+        Ok(Some(entry))
+    }
+
+    /// Sets `key` in the file. `raw` is parsed as JSON (`true`, `30`, `["a"]`) and taken
+    /// as a string otherwise. Unknown keys and values `Config` would reject are refused.
+    pub fn set(&self, key: &str, raw: &str) -> ContainerResult<Value> {
+        if !self.is_known(key) {
+            return Err(self.invalid(format!("unknown key '{}'", key)));
+        }
+        let value = serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()));
+
+        let mut document = self.read_document()?;
+        Self::insert(&mut document, key, value.clone()).map_err(|reason| self.invalid(reason))?;
+        let errors = self.check(&document);
+        if !errors.is_empty() {
+            return Err(self.invalid(errors.join("; ")));
+        }
+        self.write_document(&document)?;
+        Ok(value)
+    }
+
+    /// Removes `key` from the file, and sections it leaves empty. Returns whether it was set.
+    /// Works for unknown keys too, so stale settings can be cleaned up.
+    pub fn unset(&self, key: &str) -> ContainerResult<bool> {
+        let mut document = self.read_document()?;
+        if !Self::remove(&mut document, key) {
+            return Ok(false);
+        }
+        self.write_document(&document)?;
+        Ok(true)
+    }
+
+    /// Keys set in the file or by a flag; with `defaults`, every known key as well.
+    pub fn entries(&self, defaults: bool) -> ContainerResult<Vec<ConfigEntry>> {
+        let document = self.read_document()?;
+        let overrides = self.cli_overrides(&document);
+
+        let mut leaves = Vec::new();
+        Self::leaves(&self.effective(&document), "", &mut leaves);
+        let entries = leaves
+            .into_iter()
+            .map(|(key, value)| match overrides.iter().find(|cli| cli.key == key) {
+                Some(cli) => cli.clone(),
+                None => ConfigEntry {
+                    source: Self::source_in(&document, &key),
+                    key,
+                    value,
+                },
+            })
+            .filter(|entry| defaults || entry.source != ConfigSource::Default)
+            .collect();
+        Ok(entries)
+    }
+
+    /// Unknown keys and invalid values of the file. Never fails on the file's content;
+    /// only an unreadable file is an error.
+    pub fn validate(&self) -> ContainerResult<ConfigReport> {
+        let mut report = ConfigReport::default();
+        let document = match self.read_document() {
+            Ok(document) => document,
+            Err(ContainerError::InvalidConfig { reason, .. }) => {
+                report.errors.push(reason);
+                return Ok(report);
+            }
+            Err(error) => return Err(error),
+        };
+
+        let mut leaves = Vec::new();
+        Self::leaves(&document, "", &mut leaves);
+        for (key, _) in leaves {
+            if let Some(unknown) = self.unknown_prefix(&key) {
+                if !report.unknown_keys.contains(&unknown) {
+                    report.unknown_keys.push(unknown);
+                }
+            }
+        }
+        report.errors = self.check(&document);
+        Ok(report)
+    }
+
+    /// Type errors from `Config` first, then values that parse but cannot work.
+    fn check(&self, document: &Value) -> Vec<String> {
+        let config: Config = match serde_json::from_value(document.clone()) {
+            Ok(config) => config,
+            Err(error) => return vec![error.to_string()],
+        };
+        let mut errors = config.problems();
+        match BindingPolicy::from_config(&config.bindings, &self.paths) {
+            Ok(_) => {}
+            Err(ContainerError::InvalidConfig { reason, .. }) => errors.push(reason),
+            Err(error) => errors.push(error.to_string()),
+        }
+        errors
+    }
+
+    /// Known keys are those of `Config::defaults`; below an empty default object any key is known.
+    fn is_known(&self, key: &str) -> bool {
+        let mut current = &Config::defaults(&self.paths);
+        for segment in key.split('.') {
+            match current {
+                Value::Object(map) if map.is_empty() => return true,
+                Value::Object(map) => match map.get(segment) {
+                    Some(next) => current = next,
+                    None => return false,
+                },
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Outermost part of `key` that is unknown, so a whole unknown section is reported once.
+    fn unknown_prefix(&self, key: &str) -> Option<String> {
+        let segments: Vec<&str> = key.split('.').collect();
+        (1..=segments.len())
+            .map(|length| segments[..length].join("."))
+            .find(|prefix| !self.is_known(prefix))
+    }
+
+    /// Defaults with the file laid over them.
+    fn effective(&self, document: &Value) -> Value {
+        let mut effective = Config::defaults(&self.paths);
+        Self::merge(&mut effective, document);
+        effective
+    }
+
+    /// `--offline` switches offline mode on regardless of the file.
+    fn cli_overrides(&self, document: &Value) -> Vec<ConfigEntry> {
+        let mut overrides = Vec::new();
+        if NetworkPolicy::is_offline() && Self::lookup(document, "network.offline") != Some(&Value::Bool(true)) {
+            overrides.push(ConfigEntry {
+                key: "network.offline".to_string(),
+                value: Value::Bool(true),
+                source: ConfigSource::Cli,
+            });
+        }
+        overrides
+    }
+
+    fn source_in(document: &Value, key: &str) -> ConfigSource {
+        match Self::lookup(document, key) {
+            Some(_) => ConfigSource::File,
+            None => ConfigSource::Default,
+        }
+    }
+
+    fn merge(base: &mut Value, overlay: &Value) {
+        match (base, overlay) {
+            (Value::Object(base), Value::Object(overlay)) => {
+                for (key, value) in overlay {
+                    match base.get_mut(key) {
+                        Some(existing) => Self::merge(existing, value),
+                        None => {
+                            base.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+            }
+            (base, overlay) => *base = overlay.clone(),
+        }
+    }
+
+    /// Dotted keys of every value that is not a non-empty object.
+    fn leaves(value: &Value, prefix: &str, leaves: &mut Vec<(String, Value)>) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (key, value) in map {
+                    let key = match prefix {
+                        "" => key.clone(),
+                        prefix => format!("{}.{}", prefix, key),
+                    };
+                    Self::leaves(value, &key, leaves);
+                }
+            }
+            value => leaves.push((prefix.to_string(), value.clone())),
+        }
+    }
+
+    fn lookup<'a>(document: &'a Value, key: &str) -> Option<&'a Value> {
+        key.split('.').try_fold(document, |current, segment| current.get(segment))
+    }
+
+    fn insert(document: &mut Value, key: &str, value: Value) -> Result<(), String> {
+        let segments: Vec<&str> = key.split('.').collect();
+        let (last, parents) = segments.split_last().ok_or_else(|| "empty key".to_string())?;
+        let mut current = document;
+        for (index, segment) in parents.iter().enumerate() {
+            let Value::Object(map) = current else {
+                return Err(format!("'{}' is not a section", segments[..index].join(".")));
+            };
+            current = map.entry(segment.to_string()).or_insert_with(|| Value::Object(Map::new()));
+        }
+        match current {
+            Value::Object(map) => {
+                map.insert(last.to_string(), value);
+                Ok(())
+            }
+            _ => Err(format!("'{}' is not a section", parents.join("."))),
+        }
+    }
+
+    fn remove(document: &mut Value, key: &str) -> bool {
+        let Value::Object(map) = document else {
+            return false;
+        };
+        match key.split_once('.') {
+            None => map.remove(key).is_some(),
+            Some((section, rest)) => {
+                let Some(child) = map.get_mut(section) else {
+                    return false;
+                };
+                let removed = Self::remove(child, rest);
+                if removed && child.as_object().is_some_and(Map::is_empty) {
+                    map.remove(section);
+                }
+                removed
+            }
+        }
+    }
+
+    /// The file as a document; a missing file is an empty one.
+    fn read_document(&self) -> ContainerResult<Value> {
+        let path = self.paths.config_file();
+        if !path.exists() {
+            return Ok(Value::Object(Map::new()));
+        }
+        let content = filesystem::read_json_file(&path).map_err(|e| ContainerError::IoError {
+            path: path.clone(),
+            source: e,
+        })?;
+        match serde_json::from_str(&content) {
+            Ok(document @ Value::Object(_)) => Ok(document),
+            Ok(_) => Err(self.invalid("the top level must be an object".to_string())),
+            Err(error) => Err(self.invalid(error.to_string())),
+        }
+    }
+
+    fn write_document(&self, document: &Value) -> ContainerResult<()> {
+        filesystem::create_state_dir(&self.paths.config_dir)?;
+        let content = serde_json::to_string_pretty(document)?;
+        filesystem::atomic_write(
+            &self.paths.config_file(),
+            content.as_bytes(),
+            Some(filesystem::STATE_FILE_MODE),
+        )
+    }
+
+    fn invalid(&self, reason: String) -> ContainerError {
+        ContainerError::InvalidConfig {
+            path: self.paths.config_file(),
+            reason,
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use serde_json::Value;
+use std::fmt;
+
+/// Where the effective value of a config key comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    File,
+    /// A global flag such as `--offline`
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ConfigSource::Default => "default",
+            ConfigSource::File => "file",
+            ConfigSource::Cli => "cli",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Effective value of one dotted key, e.g. `store.dedup`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: Value,
+    pub source: ConfigSource,
+}
+
+/// What `config validate` found; unknown keys are kept and ignored, errors make the file unusable.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigReport {
+    pub unknown_keys: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl ConfigReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
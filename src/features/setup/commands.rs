@@ -0,0 +1,179 @@
+use crate::features::container::ContainerRegistry;
+use crate::features::setup::{SetupService, SetupStatus};
+use crate::shared::error::ContainerError;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::prompt;
+use crate::shared::PrivilegeGuard;
+
+pub struct SetupHandler;
+
+impl SetupHandler {
+    /// Handles the setup command execution
+    pub fn execute_command(defaults: bool) -> i32 {
+        match Self::run_setup(defaults) {
+            Ok(()) => 0,
+            Err(error) => {
+                eprintln!("❌ Setup failed: {}", error);
+                1
+            }
+        }
+    }
+
+    /// Prints a hint to run setup when wrappy has not been initialized, once for each
+    /// set of missing components. Called centrally by the router so every command
+    /// reports a missing setup the same way.
+    pub fn print_setup_hint() {
+        let Ok(paths) = WrappyPaths::new() else {
+            return;
+        };
+
+        match SetupService::inspect(&paths) {
+            Ok(status) if !status.is_complete() && SetupService::take_setup_hint(&paths, &status.missing_components()) => {
+                eprintln!(
+                    "ℹ️  Wrappy is not fully set up (missing: {}).",
+                    status.missing_components().join(", ")
+                );
+                eprintln!("   Run 'wrappy setup' to initialize it.");
+            }
+            Ok(_) => {}
+            Err(error) => {
+                eprintln!("⚠️  Could not read wrappy configuration: {}", error);
+                eprintln!("   Run 'wrappy setup' to check your installation.");
+            }
+        }
+    }
+
+    /// Runs the guided first-run initialization
+    fn run_setup(defaults: bool) -> Result<(), ContainerError> {
+        let paths = WrappyPaths::new()?;
+        PrivilegeGuard::ensure_owner(&paths.home, "set up wrappy")?;
+        let status = SetupService::inspect(&paths)?;
+
+        println!("🛠️  Wrappy setup");
+        println!();
+
+        if status.is_complete() {
+            println!("  Config, data and store directories already exist.");
+        } else if defaults || prompt::confirm(
+            &format!("  Create missing {}?", status.missing_components().join(", ")),
+            true,
+        ) {
+            for created in SetupService::initialize(&paths)? {
+                println!("  ✅ Created {}", created.display());
+            }
+        } else {
+            println!("  Skipped creating {}.", status.missing_components().join(", "));
+        }
+
+        // Permissions of files owned by someone else cannot be repaired below
+        if !Self::report_foreign_owned(&paths, &status.bin_dir) {
+            return Ok(());
+        }
+        for restricted in SetupService::restrict_state_permissions(&paths)? {
+            println!("  🔒 Restricted permissions of {}", restricted.display());
+        }
+
+        let status = SetupService::inspect(&paths)?;
+        Self::check_path(&paths, &status, defaults)?;
+        Self::check_adopted(&paths)?;
+        Self::print_tools(&status);
+        Self::print_next_steps();
+
+        Ok(())
+    }
+
+    /// Lists files another user (usually root) owns with the command that gives them back.
+    /// Returns false when there were any, as setup cannot continue over them.
+    fn report_foreign_owned(paths: &WrappyPaths, bin_dir: &std::path::Path) -> bool {
+        let foreign = SetupService::foreign_owned_files(paths, bin_dir);
+        if foreign.is_empty() {
+            return true;
+        }
+
+        eprintln!("  ⚠️  {} file(s) belong to another user, probably from running wrappy with sudo:", foreign.len());
+        for path in foreign.iter().take(10) {
+            eprintln!("    {}", path.display());
+        }
+        if foreign.len() > 10 {
+            eprintln!("    ... and {} more", foreign.len() - 10);
+        }
+        eprintln!("  Give them back with:");
+        eprintln!(
+            "    sudo chown -R \"$(id -un):$(id -gn)\" {} {} {} {}",
+            paths.config_dir.display(),
+            paths.data_dir.display(),
+            paths.cache_dir.display(),
+            bin_dir.display()
+        );
+        false
+    }
+
+    /// Checks that the bin directory is on PATH and optionally fixes the shell profile
+    fn check_path(paths: &WrappyPaths, status: &SetupStatus, defaults: bool) -> Result<(), ContainerError> {
+        println!();
+        if status.bin_dir_in_path {
+            println!("  ✅ {} is on PATH", status.bin_dir.display());
+            return Ok(());
+        }
+
+        println!("  ⚠️  {} is not on PATH, wrapped commands will not be found", status.bin_dir.display());
+
+        // Editing shell profiles is never done implicitly with --defaults
+        if !defaults && prompt::confirm("  Add it to ~/.profile?", true) {
+            let profile = SetupService::add_bin_dir_to_profile(paths, &status.bin_dir)?;
+            println!("  ✅ Updated {} (open a new shell to apply)", profile.display());
+        } else {
+            println!("     Add this line to your shell profile:");
+            println!("     export PATH=\"{}:$PATH\"", status.bin_dir.display());
+        }
+
+        Ok(())
+    }
+
+    /// Flags adopted containers whose directory disappeared, moved or changed version
+    fn check_adopted(paths: &WrappyPaths) -> Result<(), ContainerError> {
+        let adopted = ContainerRegistry::new(paths).adopted()?;
+        if adopted.is_empty() {
+            return Ok(());
+        }
+
+        println!();
+        let problems: Vec<_> = adopted
+            .iter()
+            .filter_map(|entry| entry.problem().map(|problem| (entry, problem)))
+            .collect();
+        if problems.is_empty() {
+            println!("  ✅ {} adopted container(s) found where they were adopted", adopted.len());
+            return Ok(());
+        }
+        for (entry, problem) in problems {
+            println!("  ⚠️  Adopted {} at {}: {}", entry.selector(), entry.path.display(), problem);
+            println!(
+                "     If it moved, run `wrappy container adopt <new path> --relocate --name {}`;",
+                entry.name
+            );
+            println!("     if it is gone for good, `wrappy container remove {}`", entry.selector());
+        }
+        Ok(())
+    }
+
+    /// Prints which optional tools are available and the features they enable
+    fn print_tools(status: &SetupStatus) {
+        println!();
+        println!("  Optional tools:");
+        for tool in &status.tools {
+            match &tool.path {
+                Some(path) => println!("    ✅ {} ({}) - {} enabled", tool.command, path.display(), tool.feature),
+                None => println!("    ➖ {} not found - {} unavailable", tool.command, tool.feature),
+            }
+        }
+    }
+
+    fn print_next_steps() {
+        println!();
+        println!("  Next steps:");
+        println!("    wrappy container validate --path <dir>   check a container directory");
+        println!("    wrappy bindings show <dir>               inspect its host bindings");
+        println!("    wrappy bindings enable <dir>             expose its executables and configs");
+    }
+}
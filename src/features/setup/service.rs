@@ -0,0 +1,289 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::features::setup::{SetupStatus, ToolStatus};
+use crate::shared::config::Config;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::host;
+use crate::shared::paths::WrappyPaths;
+
+/// Optional host tools and the features they enable.
+const OPTIONAL_TOOLS: [(&str, &str); 3] = [
+    ("bwrap", "sandboxed execution"),
+    ("flatpak", "Flathub integration"),
+    ("mksquashfs", "squashfs export"),
+];
+
+/// Handles first-run initialization of config, store and data directories.
+pub struct SetupService;
+
+impl SetupService {
+    /// Inspects the current machine state without changing anything.
+    /// Cheap enough to run before every command to detect a missing setup.
+    pub fn inspect(paths: &WrappyPaths) -> ContainerResult<SetupStatus> {
+        let config = Config::load_or_default(paths)?;
+        let store_dir = config.store_dir(paths);
+        let bin_dir = config.bin_dir(paths);
+
+        Ok(SetupStatus {
+            config_file: paths.config_file(),
+            config_exists: paths.config_file().exists(),
+            store_exists: store_dir.is_dir(),
+            store_dir,
+            data_dir: paths.data_dir.clone(),
+            data_exists: paths.data_dir.is_dir(),
+            bin_dir_in_path: host::path_contains(&bin_dir),
+            bin_dir,
+            tools: Self::optional_tools(),
+        })
+    }
+
+    /// Creates the config file and every wrappy directory that is missing.
+    /// Returns the paths that were actually created so the CLI can report them.
+    pub fn initialize(paths: &WrappyPaths) -> ContainerResult<Vec<PathBuf>> {
+        let mut created = Vec::new();
+
+        let config = match Config::load(paths)? {
+            Some(config) => config,
+            None => {
+                let config = Config {
+                    store_dir: Some(paths.default_store_dir()),
+                    bin_dir: Some(paths.bin_dir.clone()),
+                    ..Config::default()
+                };
+                config.save(paths)?;
+                created.push(paths.config_file());
+                config
+            }
+        };
+
+        if !paths.data_dir.exists() {
+            filesystem::create_state_dir(&paths.data_dir)?;
+            created.push(paths.data_dir.clone());
+        }
+
+        let directories = [
+            config.store_dir(paths),
+            config.bin_dir(paths),
+        ];
+        for directory in directories {
+            if !directory.exists() {
+                fs::create_dir_all(&directory).map_err(|e| ContainerError::IoError {
+                    path: directory.clone(),
+                    source: e,
+                })?;
+                created.push(directory);
+            }
+        }
+
+        // Anything missing again later is worth a new hint
+        let _ = fs::remove_file(paths.setup_hint_file());
+        Ok(created)
+    }
+
+    /// Whether the hint about these missing components is due: it is shown once per set
+    /// of missing components, not before every command. The set is recorded on the way.
+    pub fn take_setup_hint(paths: &WrappyPaths, missing: &[&str]) -> bool {
+        let hint_file = paths.setup_hint_file();
+        let missing = missing.join(", ");
+        if fs::read_to_string(&hint_file).is_ok_and(|shown| shown == missing) {
+            return false;
+        }
+        // A hint that cannot be remembered, e.g. on a read-only home, is still shown
+        let _ = filesystem::create_state_dir(&paths.cache_dir).and_then(|_| {
+            filesystem::atomic_write(&hint_file, missing.as_bytes(), Some(filesystem::STATE_FILE_MODE))
+        });
+        true
+    }
+
+    /// Removes group and other access from wrappy's own state left by older versions.
+    /// The store is not touched: containers keep whatever modes their files ship with.
+    pub fn restrict_state_permissions(paths: &WrappyPaths) -> ContainerResult<Vec<PathBuf>> {
+        let mut changed = Vec::new();
+        // The data directory also holds the store, so only its own mode is fixed
+        for path in [
+            paths.config_dir.clone(),
+            paths.data_dir.clone(),
+            paths.history_file(),
+            paths.audit_log_file(),
+            paths.registry_file(),
+            paths.binding_state_file(),
+        ] {
+            if filesystem::restrict_permissions(&path)? {
+                changed.push(path);
+            }
+        }
+        for path in [
+            paths.config_file(),
+            paths.services_run_dir(),
+            paths.stash_dir(),
+            paths.variables_dir(),
+            paths.container_state_dir(),
+            paths.cache_dir.clone(),
+        ] {
+            changed.extend(filesystem::restrict_permissions_recursive(&path)?);
+        }
+        Ok(changed)
+    }
+
+    /// Files in wrappy's directories that belong to another user, typically left
+    /// by an earlier `sudo wrappy ...`; the current user cannot change or remove them.
+    pub fn foreign_owned_files(paths: &WrappyPaths, bin_dir: &Path) -> Vec<PathBuf> {
+        // SAFETY: geteuid has no preconditions and cannot fail.
+        let uid = unsafe { libc::geteuid() };
+        [paths.config_dir.as_path(), paths.data_dir.as_path(), paths.cache_dir.as_path(), bin_dir]
+            .into_iter()
+            .flat_map(|root| filesystem::not_owned_by(root, uid))
+            .collect()
+    }
+
+    /// Appends a PATH export for the bin directory to the user's `~/.profile`.
+    pub fn add_bin_dir_to_profile(paths: &WrappyPaths, bin_dir: &Path) -> ContainerResult<PathBuf> {
+        let profile = paths.home.join(".profile");
+        let line = format!(
+            "\n# Added by wrappy setup\nexport PATH=\"{}:$PATH\"\n",
+            bin_dir.display()
+        );
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&profile)
+            .map_err(|e| ContainerError::IoError {
+                path: profile.clone(),
+                source: e,
+            })?;
+        file.write_all(line.as_bytes()).map_err(|e| ContainerError::IoError {
+            path: profile.clone(),
+            source: e,
+        })?;
+
+        Ok(profile)
+    }
+
+    pub fn optional_tools() -> Vec<ToolStatus> {
+        OPTIONAL_TOOLS
+            .iter()
+            .map(|(command, feature)| ToolStatus {
+                command,
+                feature,
+                path: host::find_executable(command),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::bindings::{BindingState, BindingStateFile};
+    use crate::features::container::{ContainerRegistry, RegistryState};
+    use crate::features::history::{RunHistory, RunRecord};
+    use crate::shared::audit;
+    use crate::shared::test_support::TempHome;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn mode(path: &Path) -> u32 {
+        fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    /// Creates every kind of state file with a permissive umask, so only explicit modes protect them.
+    fn write_all_state(paths: &WrappyPaths) {
+        // SAFETY: umask only changes the process file creation mask; tests hold the process lock.
+        let previous = unsafe { libc::umask(0) };
+        SetupService::initialize(paths).unwrap();
+        RunHistory::new(paths)
+            .append(&RunRecord {
+                container: "app".to_string(),
+                script: "default".to_string(),
+                started_at: crate::shared::clock::now(),
+                duration_ms: 1,
+                exit_code: 0,
+                output_mode: None,
+                output_file: None,
+            })
+            .unwrap();
+        audit::record("test", serde_json::json!({})).unwrap();
+        ContainerRegistry::new(paths).save(&RegistryState::default()).unwrap();
+        BindingStateFile::new(paths).save(&BindingState::default()).unwrap();
+        SetupService::take_setup_hint(paths, &["store"]);
+        unsafe { libc::umask(previous) };
+    }
+
+    #[test]
+    fn setup_hint_is_due_once_per_set_of_missing_components() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+
+        assert!(SetupService::take_setup_hint(&paths, &["config file", "store"]));
+        assert!(!SetupService::take_setup_hint(&paths, &["config file", "store"]));
+        assert!(SetupService::take_setup_hint(&paths, &["store"]));
+        assert!(!SetupService::take_setup_hint(&paths, &["store"]));
+    }
+
+    #[test]
+    fn initialize_completes_setup_and_forgets_the_shown_hint() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        assert!(!SetupService::inspect(&paths).unwrap().is_complete());
+        SetupService::take_setup_hint(&paths, &["config file"]);
+
+        SetupService::initialize(&paths).unwrap();
+
+        let status = SetupService::inspect(&paths).unwrap();
+        assert!(status.config_exists && status.store_exists && status.data_exists);
+        assert!(!paths.setup_hint_file().exists());
+        assert!(SetupService::take_setup_hint(&paths, &["config file"]));
+    }
+
+    #[test]
+    fn fresh_state_files_are_private_whatever_the_umask() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+
+        write_all_state(&paths);
+
+        for dir in [&paths.config_dir, &paths.data_dir, &paths.cache_dir] {
+            assert_eq!(mode(dir), 0o700, "{}", dir.display());
+        }
+        for file in [
+            paths.config_file(),
+            paths.history_file(),
+            paths.audit_log_file(),
+            paths.registry_file(),
+            paths.binding_state_file(),
+            paths.setup_hint_file(),
+        ] {
+            assert_eq!(mode(&file), 0o600, "{}", file.display());
+        }
+    }
+
+    #[test]
+    fn restrict_state_permissions_fixes_old_state_but_not_the_store() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        write_all_state(&paths);
+        let stashed = paths.stash_dir().join("app/runtime.json");
+        fs::create_dir_all(stashed.parent().unwrap()).unwrap();
+        fs::write(&stashed, "{}").unwrap();
+        let store_file = paths.default_store_dir().join("app/1.0.0/content/readme");
+        fs::create_dir_all(store_file.parent().unwrap()).unwrap();
+        fs::write(&store_file, "shipped").unwrap();
+        for path in [paths.registry_file(), paths.audit_log_file(), stashed.clone(), store_file.clone()] {
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        }
+        fs::set_permissions(&paths.data_dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let changed = SetupService::restrict_state_permissions(&paths).unwrap();
+
+        for path in [paths.registry_file(), paths.audit_log_file(), stashed.clone()] {
+            assert_eq!(mode(&path), 0o600, "{}", path.display());
+            assert!(changed.contains(&path), "{}", path.display());
+        }
+        assert_eq!(mode(&paths.data_dir), 0o700);
+        assert_eq!(mode(&store_file), 0o644);
+        assert!(SetupService::restrict_state_permissions(&paths).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+/// Optional host tool that unlocks a wrappy feature when present.
+#[derive(Debug, Clone)]
+pub struct ToolStatus {
+    pub command: &'static str,
+    pub feature: &'static str,
+    pub path: Option<PathBuf>,
+}
+
+impl ToolStatus {
+    pub fn is_available(&self) -> bool {
+        self.path.is_some()
+    }
+}
+
+/// Snapshot of how far the first-run initialization has progressed.
+/// Shared by `wrappy setup` and the hint other commands print, so both agree on what is missing.
+#[derive(Debug, Clone)]
+pub struct SetupStatus {
+    pub config_file: PathBuf,
+    pub config_exists: bool,
+    pub store_dir: PathBuf,
+    pub store_exists: bool,
+    pub data_dir: PathBuf,
+    pub data_exists: bool,
+    pub bin_dir: PathBuf,
+    pub bin_dir_in_path: bool,
+    pub tools: Vec<ToolStatus>,
+}
+
+impl SetupStatus {
+    pub fn missing_components(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if !self.config_exists {
+            missing.push("config file");
+        }
+        if !self.data_exists {
+            missing.push("data directory");
+        }
+        if !self.store_exists {
+            missing.push("container store");
+        }
+        missing
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing_components().is_empty()
+    }
+}
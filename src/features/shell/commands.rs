@@ -0,0 +1,60 @@
+use crate::features::manifest::ManifestCache;
+use crate::features::shell::{Shell, ShellEnvService};
+
+pub struct ShellHandler;
+
+impl ShellHandler {
+    /// Prints shell integration code meant to be evaluated by the user's shell
+    pub fn execute_command(shell: Shell, containers: Vec<String>) -> i32 {
+        // Runs in every shell startup, so it must not even refresh the manifest cache
+        ManifestCache::set_read_only(true);
+        match ShellEnvService::collect(&containers) {
+            Ok(environment) => {
+                print!("{}", ShellEnvService::render(shell, &environment));
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to generate shell integration: {}", error);
+                1
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, TempHome};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn tree(root: &Path) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(&dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    pending.push(path.clone());
+                }
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn env_writes_nothing_not_even_the_manifest_cache() {
+        let home = TempHome::enter();
+        let store = home.paths().default_store_dir();
+        fixture_container_at(&store.join("node/20.1.0"), fixture_manifest("node", "20.1.0"));
+        let before = tree(home.path());
+
+        let status = ShellHandler::execute_command(Shell::Bash, Vec::new());
+        ManifestCache::set_read_only(false);
+
+        assert_eq!(status, 0);
+        assert_eq!(tree(home.path()), before);
+    }
+}
@@ -0,0 +1,244 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::features::container::{ContainerRegistry, ContainerResolver};
+use crate::features::shell::{Shell, ShellContainer, ShellEnvironment};
+use crate::shared::config::Config;
+use crate::shared::error::ContainerResult;
+use crate::shared::path_sanity;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::shell_quote;
+
+pub struct ShellEnvService;
+
+impl ShellEnvService {
+    /// Collects the bin dir and installed containers, optionally limited to `only`.
+    /// Reads config and the store but never writes, so it is safe in every shell startup.
+    pub fn collect(only: &[String]) -> ContainerResult<ShellEnvironment> {
+        let paths = WrappyPaths::new()?;
+        let config = Config::load_or_default(&paths)?;
+        let resolver =
+            ContainerResolver::with_store(config.store_dir(&paths)).with_registered(ContainerRegistry::new(&paths).entries()?);
+
+        let containers = resolver
+            .installed_names()
+            .into_iter()
+            .filter(|name| only.is_empty() || only.contains(name))
+            .filter_map(|name| {
+                let newest = resolver.installed_versions(&name).into_iter().next()?;
+                Some(ShellContainer {
+                    name,
+                    content_dir: newest.path.join("content"),
+                })
+            })
+            .collect();
+
+        let bin_dir = config.bin_dir(&paths);
+        path_sanity::check_path_entry(&bin_dir)?;
+        Ok(ShellEnvironment {
+            bin_dir,
+            containers,
+        })
+    }
+
+    pub fn render(shell: Shell, environment: &ShellEnvironment) -> String {
+        match shell {
+            Shell::Bash | Shell::Zsh => Self::render_posix(shell, environment),
+            Shell::Fish => Self::render_fish(environment),
+        }
+    }
+
+    fn render_posix(shell: Shell, environment: &ShellEnvironment) -> String {
+        let bin_dir = posix_quote(&environment.bin_dir);
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# wrappy shell integration for {}", shell_name(shell));
+        let _ = writeln!(out, "case \":$PATH:\" in");
+        let _ = writeln!(out, "  *:{}:*) ;;", bin_dir);
+        let _ = writeln!(out, "  *) export PATH={}\":$PATH\" ;;", bin_dir);
+        let _ = writeln!(out, "esac");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "wrappy-cd() {{");
+        let _ = writeln!(out, "  case \"$1\" in");
+        for container in &environment.containers {
+            let _ = writeln!(
+                out,
+                "    {}) cd {} ;;",
+                posix_quote(Path::new(&container.name)),
+                posix_quote(&container.content_dir)
+            );
+        }
+        let _ = writeln!(out, "    *) echo \"wrappy-cd: unknown container '$1'\" >&2; return 1 ;;");
+        let _ = writeln!(out, "  esac");
+        let _ = writeln!(out, "}}");
+        out
+    }
+
+    fn render_fish(environment: &ShellEnvironment) -> String {
+        let bin_dir = fish_quote(&environment.bin_dir);
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# wrappy shell integration for fish");
+        let _ = writeln!(out, "if not contains -- {} $PATH", bin_dir);
+        let _ = writeln!(out, "    set -gx PATH {} $PATH", bin_dir);
+        let _ = writeln!(out, "end");
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "function wrappy-cd --description 'Change to the content directory of a wrappy container'"
+        );
+        let _ = writeln!(out, "    switch $argv[1]");
+        for container in &environment.containers {
+            let _ = writeln!(out, "        case {}", fish_quote(Path::new(&container.name)));
+            let _ = writeln!(out, "            cd {}", fish_quote(&container.content_dir));
+        }
+        let _ = writeln!(out, "        case '*'");
+        let _ = writeln!(out, "            echo \"wrappy-cd: unknown container '$argv[1]'\" >&2");
+        let _ = writeln!(out, "            return 1");
+        let _ = writeln!(out, "    end");
+        let _ = writeln!(out, "end");
+        out
+    }
+}
+
+fn shell_name(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash => "bash",
+        Shell::Zsh => "zsh",
+        Shell::Fish => "fish",
+    }
+}
+
+fn posix_quote(value: &Path) -> String {
+    shell_quote::posix_quote(&value.to_string_lossy())
+}
+
+/// Single-quotes a value for fish, where only `\` and `'` need escaping inside quotes.
+fn fish_quote(value: &Path) -> String {
+    let escaped = value.to_string_lossy().replace('\\', r"\\").replace('\'', r"\'");
+    format!("'{}'", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, TempHome};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    fn environment(bin_dir: &str, containers: &[(&str, &str)]) -> ShellEnvironment {
+        ShellEnvironment {
+            bin_dir: PathBuf::from(bin_dir),
+            containers: containers
+                .iter()
+                .map(|(name, content_dir)| ShellContainer {
+                    name: name.to_string(),
+                    content_dir: PathBuf::from(content_dir),
+                })
+                .collect(),
+        }
+    }
+
+    fn sample() -> ShellEnvironment {
+        environment(
+            "/home/u/.local/bin",
+            &[("node", "/store/node/20.1.0/content"), ("it's", "/data/$HOME `x`/it's/content")],
+        )
+    }
+
+    #[test]
+    fn bash_and_zsh_code_snapshot() {
+        let expected = concat!(
+            "# wrappy shell integration for bash\n",
+            "case \":$PATH:\" in\n",
+            "  *:'/home/u/.local/bin':*) ;;\n",
+            "  *) export PATH='/home/u/.local/bin'\":$PATH\" ;;\n",
+            "esac\n",
+            "\n",
+            "wrappy-cd() {\n",
+            "  case \"$1\" in\n",
+            "    'node') cd '/store/node/20.1.0/content' ;;\n",
+            "    'it'\\''s') cd '/data/$HOME `x`/it'\\''s/content' ;;\n",
+            "    *) echo \"wrappy-cd: unknown container '$1'\" >&2; return 1 ;;\n",
+            "  esac\n",
+            "}\n",
+        );
+
+        assert_eq!(ShellEnvService::render(Shell::Bash, &sample()), expected);
+        assert_eq!(
+            ShellEnvService::render(Shell::Zsh, &sample()),
+            expected.replace("integration for bash", "integration for zsh")
+        );
+    }
+
+    #[test]
+    fn fish_code_snapshot() {
+        let expected = concat!(
+            "# wrappy shell integration for fish\n",
+            "if not contains -- '/home/u/.local/bin' $PATH\n",
+            "    set -gx PATH '/home/u/.local/bin' $PATH\n",
+            "end\n",
+            "\n",
+            "function wrappy-cd --description 'Change to the content directory of a wrappy container'\n",
+            "    switch $argv[1]\n",
+            "        case 'node'\n",
+            "            cd '/store/node/20.1.0/content'\n",
+            "        case 'it\\'s'\n",
+            "            cd '/data/$HOME `x`/it\\'s/content'\n",
+            "        case '*'\n",
+            "            echo \"wrappy-cd: unknown container '$argv[1]'\" >&2\n",
+            "            return 1\n",
+            "    end\n",
+            "end\n",
+        );
+
+        assert_eq!(ShellEnvService::render(Shell::Fish, &sample()), expected);
+    }
+
+    #[test]
+    fn bash_evaluates_the_code_into_path_and_a_working_wrappy_cd() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let content = dir.path().join("it's $odd `dir`");
+        fs::create_dir(&content).unwrap();
+        let bin_dir = dir.path().join("bin dir");
+        let code = ShellEnvService::render(
+            Shell::Bash,
+            &environment(bin_dir.to_str().unwrap(), &[("it's", content.to_str().unwrap())]),
+        );
+
+        let output = Command::new("bash")
+            .args(["--norc", "--noprofile", "-c"])
+            .arg("eval \"$1\"; eval \"$1\"; echo \"$PATH\"; wrappy-cd \"it's\" && pwd; wrappy-cd nope; echo \"status $?\"")
+            .arg("bash")
+            .arg(&code)
+            .env("PATH", "/usr/bin:/bin")
+            .output()
+            .unwrap();
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines[0], format!("{}:/usr/bin:/bin", bin_dir.display()));
+        assert_eq!(lines[1], content.to_str().unwrap());
+        assert_eq!(lines[2], "status 1");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("unknown container 'nope'"));
+    }
+
+    #[test]
+    fn collect_lists_the_newest_version_of_the_selected_containers() {
+        let home = TempHome::enter();
+        let store = home.paths().default_store_dir();
+        for (name, version) in [("node", "18.0.0"), ("node", "20.1.0"), ("python", "3.12.0")] {
+            fixture_container_at(&store.join(name).join(version), fixture_manifest(name, version));
+        }
+
+        let all = ShellEnvService::collect(&[]).unwrap();
+        let only = ShellEnvService::collect(&["node".to_string()]).unwrap();
+
+        let names: Vec<&str> = all.containers.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["node", "python"]);
+        assert_eq!(only.containers.len(), 1);
+        assert_eq!(only.containers[0].content_dir, store.join("node/20.1.0/content"));
+        assert_eq!(all.bin_dir, home.paths().bin_dir);
+    }
+}
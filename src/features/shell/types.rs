@@ -0,0 +1,23 @@
+use std::path::PathBuf;
+
+/// Shells `wrappy env` can emit integration code for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Installed container exposed to the shell, pointing at its newest version.
+#[derive(Debug, Clone)]
+pub struct ShellContainer {
+    pub name: String,
+    pub content_dir: PathBuf,
+}
+
+/// Everything the emitted shell code refers to, gathered up front so emitters stay pure.
+#[derive(Debug, Clone)]
+pub struct ShellEnvironment {
+    pub bin_dir: PathBuf,
+    pub containers: Vec<ShellContainer>,
+}
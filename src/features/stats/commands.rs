@@ -0,0 +1,128 @@
+use clap::Subcommand;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::features::history::RunHistory;
+use crate::features::stats::StatsService;
+use crate::shared::error::ContainerError;
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+
+const PROM_FILE_NAME: &str = "wrappy.prom";
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Subcommand)]
+pub enum StatsCommands {
+    /// Export run statistics as a Prometheus textfile
+    ExportProm {
+        /// Directory to write wrappy.prom into (prints to stdout when omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Keep running and rewrite the file whenever the run history changes
+        #[arg(long, requires = "output")]
+        watch: bool,
+    },
+}
+
+pub struct StatsHandler;
+
+impl StatsHandler {
+    /// Routes and executes the appropriate stats command
+    pub fn execute_command(command: StatsCommands) -> i32 {
+        match command {
+            StatsCommands::ExportProm { output, watch } => Self::handle_export_prom(output, watch),
+        }
+    }
+
+    /// Handles the export-prom command execution
+    fn handle_export_prom(output: Option<PathBuf>, watch: bool) -> i32 {
+        let result = match (output, watch) {
+            (Some(output), true) => Self::watch_and_export(&output),
+            (output, _) => Self::export_once(output.as_deref()),
+        };
+
+        match result {
+            Ok(()) => 0,
+            Err(error) => {
+                eprintln!("❌ Failed to export stats: {}", error);
+                1
+            }
+        }
+    }
+
+    fn export_once(output: Option<&Path>) -> Result<(), ContainerError> {
+        let history = RunHistory::new(&WrappyPaths::new()?);
+        let read = history.read_all()?;
+        for line in &read.skipped_lines {
+            eprintln!("⚠️  Skipping malformed run history record at line {}", line);
+        }
+
+        let rendered = StatsService::render_prometheus(&StatsService::aggregate(&read.records));
+
+        match output {
+            // Atomic rename keeps node-exporter from scraping a half-written file
+            Some(directory) => {
+                let target = directory.join(PROM_FILE_NAME);
+                filesystem::atomic_write(&target, rendered.as_bytes(), Some(0o644))?;
+                println!("📊 Wrote {}", target.display());
+            }
+            None => print!("{}", rendered),
+        }
+
+        Ok(())
+    }
+
+    /// Polls the history file and re-exports after every change
+    fn watch_and_export(output: &Path) -> Result<(), ContainerError> {
+        let history = RunHistory::new(&WrappyPaths::new()?);
+        let mut last_modified: Option<SystemTime> = None;
+
+        loop {
+            let modified = std::fs::metadata(history.path())
+                .and_then(|metadata| metadata.modified())
+                .ok();
+
+            if last_modified.is_none() || modified != last_modified {
+                Self::export_once(Some(output))?;
+                last_modified = modified.or(Some(SystemTime::UNIX_EPOCH));
+            }
+
+            thread::sleep(WATCH_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::TempHome;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    #[test]
+    fn export_writes_the_textfile_atomically_and_skips_malformed_records() {
+        let home = TempHome::enter();
+        let history = home.paths().history_file();
+        fs::create_dir_all(history.parent().unwrap()).unwrap();
+        fs::write(
+            &history,
+            "{\"container\":\"web\",\"script\":\"default\",\"started_at\":\"2023-11-14T22:13:20Z\",\"duration_ms\":1500,\"exit_code\":3}\n\
+             {broken\n",
+        )
+        .unwrap();
+        let output = TempDir::new().unwrap();
+        fs::write(output.path().join(PROM_FILE_NAME), "stale").unwrap();
+
+        StatsHandler::export_once(Some(output.path())).unwrap();
+
+        let target = output.path().join(PROM_FILE_NAME);
+        let written = fs::read_to_string(&target).unwrap();
+        assert!(written.contains("wrappy_container_failures_total{container=\"web\",script=\"default\"} 1\n"));
+        assert!(written.contains("wrappy_container_last_run_duration_seconds{container=\"web\",script=\"default\"} 1.500\n"));
+        assert_eq!(fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o644);
+        let names: Vec<_> = fs::read_dir(output.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(names, [PROM_FILE_NAME]);
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::features::history::RunRecord;
+use crate::features::stats::ScriptStats;
+
+/// Metric families exported for node-exporter's textfile collector.
+const METRIC_RUNS: &str = "wrappy_container_runs_total";
+const METRIC_FAILURES: &str = "wrappy_container_failures_total";
+const METRIC_LAST_RUN: &str = "wrappy_container_last_run_timestamp_seconds";
+const METRIC_LAST_DURATION: &str = "wrappy_container_last_run_duration_seconds";
+
+/// Aggregates run history and renders it in the Prometheus text exposition format.
+pub struct StatsService;
+
+impl StatsService {
+    /// Groups records by (container, script), ordered for stable output.
+    pub fn aggregate(records: &[RunRecord]) -> Vec<ScriptStats> {
+        let mut stats: BTreeMap<(String, String), ScriptStats> = BTreeMap::new();
+
+        for record in records {
+            let key = (record.container.clone(), record.script.clone());
+            let entry = stats.entry(key).or_insert_with(|| ScriptStats {
+                container: record.container.clone(),
+                script: record.script.clone(),
+                runs_total: 0,
+                failures_total: 0,
+                last_run_at: record.started_at,
+                last_duration_ms: record.duration_ms,
+            });
+
+            entry.runs_total += 1;
+            if !record.succeeded() {
+                entry.failures_total += 1;
+            }
+            if record.started_at >= entry.last_run_at {
+                entry.last_run_at = record.started_at;
+                entry.last_duration_ms = record.duration_ms;
+            }
+        }
+
+        stats.into_values().collect()
+    }
+
+    /// Renders one metric family per statistic with `container` and `script` labels.
+    pub fn render_prometheus(stats: &[ScriptStats]) -> String {
+        let mut output = String::new();
+
+        Self::render_family(&mut output, METRIC_RUNS, "counter", "Total script runs.", stats, |s| {
+            s.runs_total.to_string()
+        });
+        Self::render_family(&mut output, METRIC_FAILURES, "counter", "Script runs that exited non-zero.", stats, |s| {
+            s.failures_total.to_string()
+        });
+        Self::render_family(&mut output, METRIC_LAST_RUN, "gauge", "Unix time of the last run start.", stats, |s| {
+            s.last_run_at.timestamp().to_string()
+        });
+        Self::render_family(&mut output, METRIC_LAST_DURATION, "gauge", "Duration of the last run in seconds.", stats, |s| {
+            format!("{:.3}", s.last_duration_ms as f64 / 1000.0)
+        });
+
+        output
+    }
+
+    /// Escapes a label value as required by the exposition format (backslash, quote, newline).
+    pub fn escape_label_value(value: &str) -> String {
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    }
+
+    fn render_family<F>(output: &mut String, name: &str, kind: &str, help: &str, stats: &[ScriptStats], value: F)
+    where
+        F: Fn(&ScriptStats) -> String,
+    {
+        let _ = writeln!(output, "# HELP {} {}", name, help);
+        let _ = writeln!(output, "# TYPE {} {}", name, kind);
+        for entry in stats {
+            let _ = writeln!(
+                output,
+                "{}{{container=\"{}\",script=\"{}\"}} {}",
+                name,
+                Self::escape_label_value(&entry.container),
+                Self::escape_label_value(&entry.script),
+                value(entry)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn record(container: &str, script: &str, started_at: i64, duration_ms: u64, exit_code: i32) -> RunRecord {
+        RunRecord {
+            container: container.to_string(),
+            script: script.to_string(),
+            started_at: Utc.timestamp_opt(started_at, 0).unwrap(),
+            duration_ms,
+            exit_code,
+            output_mode: None,
+            output_file: None,
+        }
+    }
+
+    #[test]
+    fn aggregate_counts_runs_and_failures_and_keeps_the_latest_run() {
+        let records = [
+            record("web", "default", 200, 1500, 0),
+            record("web", "default", 100, 9000, 1),
+            record("db", "backup", 50, 250, 2),
+            record("web", "build", 10, 10, 0),
+        ];
+
+        let stats = StatsService::aggregate(&records);
+
+        let keys: Vec<(&str, &str)> = stats.iter().map(|s| (s.container.as_str(), s.script.as_str())).collect();
+        assert_eq!(keys, [("db", "backup"), ("web", "build"), ("web", "default")]);
+        let web = &stats[2];
+        assert_eq!((web.runs_total, web.failures_total), (2, 1));
+        assert_eq!(web.last_run_at.timestamp(), 200);
+        assert_eq!(web.last_duration_ms, 1500);
+        assert_eq!((stats[0].runs_total, stats[0].failures_total), (1, 1));
+    }
+
+    #[test]
+    fn render_prometheus_writes_every_family_with_help_type_and_labels() {
+        let stats = StatsService::aggregate(&[record("web", "default", 1_700_000_000, 1234, 0)]);
+
+        let rendered = StatsService::render_prometheus(&stats);
+
+        assert_eq!(
+            rendered,
+            "# HELP wrappy_container_runs_total Total script runs.\n\
+             # TYPE wrappy_container_runs_total counter\n\
+             wrappy_container_runs_total{container=\"web\",script=\"default\"} 1\n\
+             # HELP wrappy_container_failures_total Script runs that exited non-zero.\n\
+             # TYPE wrappy_container_failures_total counter\n\
+             wrappy_container_failures_total{container=\"web\",script=\"default\"} 0\n\
+             # HELP wrappy_container_last_run_timestamp_seconds Unix time of the last run start.\n\
+             # TYPE wrappy_container_last_run_timestamp_seconds gauge\n\
+             wrappy_container_last_run_timestamp_seconds{container=\"web\",script=\"default\"} 1700000000\n\
+             # HELP wrappy_container_last_run_duration_seconds Duration of the last run in seconds.\n\
+             # TYPE wrappy_container_last_run_duration_seconds gauge\n\
+             wrappy_container_last_run_duration_seconds{container=\"web\",script=\"default\"} 1.234\n"
+        );
+    }
+
+    #[test]
+    fn metric_names_follow_prometheus_conventions() {
+        let valid = regex::Regex::new("^[a-zA-Z_:][a-zA-Z0-9_:]*$").unwrap();
+        for name in [METRIC_RUNS, METRIC_FAILURES, METRIC_LAST_RUN, METRIC_LAST_DURATION] {
+            assert!(valid.is_match(name), "{}", name);
+            assert!(name.starts_with("wrappy_"), "{}", name);
+        }
+        for counter in [METRIC_RUNS, METRIC_FAILURES] {
+            assert!(counter.ends_with("_total"), "{}", counter);
+        }
+        assert!(METRIC_LAST_RUN.ends_with("_seconds"));
+        assert!(METRIC_LAST_DURATION.ends_with("_seconds"));
+    }
+
+    #[test]
+    fn label_values_escape_backslash_quote_and_newline() {
+        assert_eq!(StatsService::escape_label_value("plain"), "plain");
+        assert_eq!(StatsService::escape_label_value(r"C:\tools"), r"C:\\tools");
+        assert_eq!(StatsService::escape_label_value("say \"hi\""), "say \\\"hi\\\"");
+        assert_eq!(StatsService::escape_label_value("two\nlines"), "two\\nlines");
+        // The backslash is escaped first, so an escaped quote is never doubled
+        assert_eq!(StatsService::escape_label_value("\\\""), "\\\\\\\"");
+
+        let rendered = StatsService::render_prometheus(&StatsService::aggregate(&[record("a\"b", "x\ny", 0, 0, 0)]));
+        assert!(rendered.contains("{container=\"a\\\"b\",script=\"x\\ny\"} 1\n"));
+        assert_eq!(rendered.lines().count(), 12);
+    }
+}
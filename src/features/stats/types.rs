@@ -0,0 +1,12 @@
+use chrono::{DateTime, Utc};
+
+/// Run statistics for one container script, aggregated from the run history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptStats {
+    pub container: String,
+    pub script: String,
+    pub runs_total: u64,
+    pub failures_total: u64,
+    pub last_run_at: DateTime<Utc>,
+    pub last_duration_ms: u64,
+}
@@ -0,0 +1,245 @@
+use clap::Subcommand;
+
+use crate::features::container::{Container, ContainerResolver, StateStash};
+use crate::features::store::ObjectStore;
+use crate::shared::config::Config;
+use crate::shared::error::ContainerResult;
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::staging::{StagingArea, STALE_STAGING_AGE};
+
+#[derive(Subcommand)]
+pub enum StoreCommands {
+    /// Hard-link identical files of installed containers to shared objects (needs "store": {"dedup": true})
+    Dedup {
+        /// Containers to deduplicate (name, name@version or path); all installed ones when omitted
+        containers: Vec<String>,
+    },
+    /// Remove objects no container links to any more and staging directories left by crashed runs
+    Gc,
+    /// Show apparent and real store size and what deduplication saves
+    Stats,
+}
+
+pub struct StoreHandler;
+
+impl StoreHandler {
+    /// Routes and executes the appropriate store command
+    pub fn execute_command(command: StoreCommands) -> i32 {
+        match command {
+            StoreCommands::Dedup { containers } => Self::handle_dedup_command(containers),
+            StoreCommands::Gc => Self::handle_gc_command(),
+            StoreCommands::Stats => Self::handle_stats_command(),
+        }
+    }
+
+    /// Handles the dedup command execution
+    fn handle_dedup_command(inputs: Vec<String>) -> i32 {
+        let enabled = WrappyPaths::new()
+            .and_then(|paths| Config::load_or_default(&paths))
+            .map(|config| config.store.dedup);
+        match enabled {
+            Ok(true) => {}
+            Ok(false) => {
+                eprintln!("ℹ️  Deduplication is off. Enable it with \"store\": {{\"dedup\": true}} in the config.");
+                return 1;
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to load config: {}", error);
+                return 1;
+            }
+        }
+
+        let result = ObjectStore::new().and_then(|store| Ok((store, Self::load_containers(&inputs)?)));
+        let (store, containers) = match result {
+            Ok(result) => result,
+            Err(error) => {
+                eprintln!("❌ {}", error);
+                return 1;
+            }
+        };
+
+        let mut exit_code = 0;
+        for container in &containers {
+            match store.dedup_container(container) {
+                Ok(summary) if summary.cross_device => {
+                    println!(
+                        "ℹ️  {}@{} is on another filesystem than the object store; its files stay plain copies",
+                        container.name(),
+                        container.version()
+                    );
+                }
+                Ok(summary) => println!(
+                    "🔗 {}@{}: {} file(s) linked, {} new object(s), {} saved",
+                    container.name(),
+                    container.version(),
+                    summary.files_linked,
+                    summary.objects_created,
+                    filesystem::format_size(summary.bytes_saved)
+                ),
+                Err(error) => {
+                    eprintln!("❌ Failed to deduplicate {}@{}: {}", container.name(), container.version(), error);
+                    exit_code = 1;
+                }
+            }
+        }
+        exit_code
+    }
+
+    /// Handles the gc command execution
+    fn handle_gc_command() -> i32 {
+        let mut exit_code = 0;
+        match ObjectStore::new().and_then(|store| store.gc()) {
+            Ok(summary) => println!(
+                "🗑️  Removed {} unreferenced object(s), freed {}",
+                summary.removed,
+                filesystem::format_size(summary.bytes_freed)
+            ),
+            Err(error) => {
+                eprintln!("❌ Failed to collect objects: {}", error);
+                exit_code = 1;
+            }
+        }
+
+        match Self::sweep_staging() {
+            Ok((0, _)) => {}
+            Ok((removed, bytes)) => println!(
+                "🗑️  Removed {} stale staging area(s), freed {}",
+                removed,
+                filesystem::format_size(bytes)
+            ),
+            Err(error) => {
+                eprintln!("❌ Failed to remove stale staging directories: {}", error);
+                exit_code = 1;
+            }
+        }
+
+        match Self::prune_stashes() {
+            Ok(pruned) if pruned.is_empty() => {}
+            Ok(pruned) => println!(
+                "🗑️  Removed {} stashed state(s) older than stash_max_age_days: {}",
+                pruned.len(),
+                pruned.join(", ")
+            ),
+            Err(error) => {
+                eprintln!("❌ Failed to remove old stashed state: {}", error);
+                exit_code = 1;
+            }
+        }
+        exit_code
+    }
+
+    /// State stashed by `remove --keep-state` and never restored expires after the configured age.
+    fn prune_stashes() -> ContainerResult<Vec<String>> {
+        let paths = WrappyPaths::new()?;
+        let max_age = Config::load_or_default(&paths)?.stash_max_age();
+        StateStash::prune(&paths, max_age)
+    }
+
+    /// Staging directories older than a day belong to runs that crashed or were killed.
+    fn sweep_staging() -> ContainerResult<(usize, u64)> {
+        let paths = WrappyPaths::new()?;
+        let store_dir = Config::load_or_default(&paths)?.store_dir(&paths);
+        let (mut removed, mut bytes) = (0, 0);
+        for root in StagingArea::roots(&paths, &store_dir) {
+            let (root_removed, root_bytes) = StagingArea::sweep(&root, STALE_STAGING_AGE)?;
+            removed += root_removed;
+            bytes += root_bytes;
+        }
+        Ok((removed, bytes))
+    }
+
+    /// Handles the stats command execution
+    fn handle_stats_command() -> i32 {
+        match ObjectStore::new().and_then(|store| store.stats()) {
+            Ok(stats) => {
+                println!("📦 Store usage:");
+                println!("  Apparent size: {}", filesystem::format_size(stats.apparent_size));
+                println!("  Real size: {}", filesystem::format_size(stats.real_size));
+                println!("  Saved by deduplication: {}", filesystem::format_size(stats.savings()));
+                println!("  Objects: {} ({} unreferenced)", stats.objects, stats.unreferenced_objects);
+                if stats.unreferenced_objects > 0 {
+                    println!("  ℹ️  Run 'wrappy store gc' to remove unreferenced objects");
+                }
+                0
+            }
+            Err(error) => {
+                eprintln!("❌ Failed to measure the store: {}", error);
+                1
+            }
+        }
+    }
+
+    /// The named containers, or every installed version of every container in the store.
+    /// Adopted directories belong to the user and are never linked, even when named:
+    /// a hard link would let an edit in the checkout change every other container's copy.
+    fn load_containers(inputs: &[String]) -> ContainerResult<Vec<Container>> {
+        Self::load_containers_with(&ContainerResolver::new()?, inputs)
+    }
+
+    fn load_containers_with(resolver: &ContainerResolver, inputs: &[String]) -> ContainerResult<Vec<Container>> {
+        let mut containers = Vec::new();
+        if inputs.is_empty() {
+            for name in resolver.installed_names() {
+                for candidate in resolver.installed_versions(&name).into_iter().filter(|candidate| !candidate.adopted) {
+                    containers.push(resolver.resolve_container(&candidate.selector())?);
+                }
+            }
+            return Ok(containers);
+        }
+
+        for input in inputs {
+            let container = resolver.resolve_container(input)?;
+            if resolver.adopted_entry(&container.path).is_some() {
+                println!(
+                    "ℹ️  Skipping {}@{}: it is adopted, so its files belong to you",
+                    container.name(),
+                    container.version()
+                );
+                continue;
+            }
+            containers.push(container);
+        }
+        Ok(containers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::container::{ContainerService, RegistryEntry};
+    use crate::shared::clock;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, TempHome};
+
+    #[test]
+    fn adopted_containers_are_skipped_even_when_named() {
+        let home = TempHome::enter();
+        let store = home.path().join("store");
+        fixture_container_at(&store.join("app/1.0.0"), fixture_manifest("app", "1.0.0"));
+        let checkout = home.path().join("checkout");
+        fixture_container_at(&checkout, fixture_manifest("dev", "2.0.0"));
+        let adopted = RegistryEntry {
+            name: "dev".to_string(),
+            version: ContainerService::load_from_directory(&checkout).unwrap().manifest.version,
+            path: checkout.canonicalize().unwrap(),
+            adopted: true,
+            registered_at: clock::now(),
+            attestation: None,
+            dependencies: None,
+            missing_dependencies: Vec::new(),
+        };
+        let resolver = ContainerResolver::with_store(store).with_registered(vec![adopted]);
+        let names = |inputs: &[&str]| -> Vec<String> {
+            let inputs: Vec<String> = inputs.iter().map(|input| input.to_string()).collect();
+            StoreHandler::load_containers_with(&resolver, &inputs)
+                .unwrap()
+                .iter()
+                .map(|container| container.name().to_string())
+                .collect()
+        };
+
+        assert_eq!(names(&[]), ["app"]);
+        assert_eq!(names(&["dev", "app"]), ["app"]);
+        assert!(names(&["dev@2.0.0"]).is_empty());
+    }
+}
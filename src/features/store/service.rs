@@ -0,0 +1,213 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::features::container::{Container, STATE_ENTRIES};
+use crate::features::store::{DedupSummary, GcSummary, ObjectIndex, StoreStats};
+use crate::shared::config::Config;
+use crate::shared::digest::Sha256;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::ignore::IgnoreRules;
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::traversal::Walk;
+
+const INDEX_FILE: &str = "index.json";
+
+/// Content-addressed objects under `~/.local/share/wrappy/objects` that identical
+/// container files are hard-linked to, so a runtime bundled by several containers
+/// is stored once. Objects are keyed by SHA-256 and mode, because hard links share both.
+pub struct ObjectStore {
+    objects_dir: PathBuf,
+    store_dir: PathBuf,
+}
+
+impl ObjectStore {
+    pub fn new() -> ContainerResult<Self> {
+        let paths = WrappyPaths::new()?;
+        let config = Config::load_or_default(&paths)?;
+        Ok(Self::at(paths.objects_dir(), config.store_dir(&paths)))
+    }
+
+    pub fn at(objects_dir: PathBuf, store_dir: PathBuf) -> Self {
+        Self { objects_dir, store_dir }
+    }
+
+    /// Replaces every regular file of `container` with a hard link to its object,
+    /// creating objects for contents seen for the first time. Persistent data and
+    /// files wrappy rewrites are skipped, since a write to one link changes them all.
+    pub fn dedup_container(&self, container: &Container) -> ContainerResult<DedupSummary> {
+        filesystem::create_state_dir(&self.objects_dir)?;
+        let mut summary = DedupSummary::default();
+        if !self.same_device(&container.path)? {
+            summary.cross_device = true;
+            return Ok(summary);
+        }
+
+        // Files wrappy rewrites must never be shared
+        let skipped: Vec<PathBuf> = STATE_ENTRIES
+            .iter()
+            .map(|name| container.path.join(name))
+            .chain(container.persistent_paths())
+            .collect();
+        let label = format!("{}@{}", container.name(), container.version());
+        let mut index = self.load_index()?;
+
+        let rules = IgnoreRules::for_container(&container.path)?;
+        for file in Self::regular_files(&container.path, &skipped, rules)? {
+            let metadata = fs::symlink_metadata(&file).map_err(|e| Self::io_error(&file, e))?;
+            let key = format!("{}-{:o}", Self::hash_file(&file)?, metadata.mode() & 0o7777);
+            let object = self.objects_dir.join(&key);
+
+            match fs::symlink_metadata(&object) {
+                Ok(existing) if existing.ino() == metadata.ino() && existing.dev() == metadata.dev() => {}
+                Ok(_) => {
+                    Self::replace_with_link(&object, &file)?;
+                    summary.files_linked += 1;
+                    summary.bytes_saved += metadata.len();
+                }
+                Err(_) => {
+                    fs::hard_link(&file, &object).map_err(|e| Self::io_error(&object, e))?;
+                    summary.objects_created += 1;
+                }
+            }
+
+            let entry = index.objects.entry(key).or_default();
+            entry.size = metadata.len();
+            entry.containers.insert(label.clone());
+        }
+
+        self.save_index(&index)?;
+        Ok(summary)
+    }
+
+    /// Removes objects no container file links to any more (link count 1) and drops them from the index.
+    pub fn gc(&self) -> ContainerResult<GcSummary> {
+        let mut summary = GcSummary::default();
+        let mut index = self.load_index()?;
+        for (name, object, metadata) in self.objects()? {
+            if metadata.nlink() > 1 {
+                continue;
+            }
+            fs::remove_file(&object).map_err(|e| Self::io_error(&object, e))?;
+            index.objects.remove(&name);
+            summary.removed += 1;
+            summary.bytes_freed += metadata.len();
+        }
+        self.save_index(&index)?;
+        Ok(summary)
+    }
+
+    /// Apparent and real size of the store; every hard-linked inode counts once towards the real size.
+    pub fn stats(&self) -> ContainerResult<StoreStats> {
+        let mut stats = StoreStats::default();
+        let mut seen = HashSet::new();
+        // Everything on disk counts, including what containers ignore
+        for file in Self::regular_files(&self.store_dir, &[], IgnoreRules::default())? {
+            let metadata = fs::symlink_metadata(&file).map_err(|e| Self::io_error(&file, e))?;
+            stats.apparent_size += metadata.len();
+            if seen.insert((metadata.dev(), metadata.ino())) {
+                stats.real_size += metadata.len();
+            }
+        }
+        for (_, _, metadata) in self.objects()? {
+            stats.objects += 1;
+            if metadata.nlink() <= 1 {
+                stats.unreferenced_objects += 1;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Hard links only work within one filesystem; a store elsewhere keeps plain copies.
+    fn same_device(&self, container_path: &Path) -> ContainerResult<bool> {
+        let container = fs::metadata(container_path).map_err(|e| Self::io_error(container_path, e))?;
+        let objects = fs::metadata(&self.objects_dir).map_err(|e| Self::io_error(&self.objects_dir, e))?;
+        Ok(container.dev() == objects.dev())
+    }
+
+    /// Links `object` in place of `file` through a temporary name, so `file` is never missing.
+    fn replace_with_link(object: &Path, file: &Path) -> ContainerResult<()> {
+        let file_name = file.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let temp = file.with_file_name(format!(".{}.wrappy-dedup-{}", file_name, std::process::id()));
+        fs::hard_link(object, &temp).map_err(|e| Self::io_error(&temp, e))?;
+        fs::rename(&temp, file).map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            Self::io_error(file, e)
+        })
+    }
+
+    fn hash_file(path: &Path) -> ContainerResult<String> {
+        let mut file = File::open(path).map_err(|e| Self::io_error(path, e))?;
+        let mut hasher = Sha256::new();
+        io::copy(&mut file, &mut hasher).map_err(|e| Self::io_error(path, e))?;
+        Ok(hasher.finish_hex())
+    }
+
+    /// Regular files below `root`, without following symlinks, entering `skipped` paths
+    /// or including what `rules` ignore.
+    fn regular_files(root: &Path, skipped: &[PathBuf], rules: IgnoreRules) -> ContainerResult<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        if !root.is_dir() {
+            return Ok(files);
+        }
+        let mut walk = Walk::new(root).follow_root_link().ignoring(rules);
+        while let Some(entry) = walk.next() {
+            let entry = entry?;
+            if skipped.contains(&entry.path) {
+                walk.skip_current_dir();
+            } else if entry.file_type.is_file() {
+                files.push(entry.path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    fn objects(&self) -> ContainerResult<Vec<(String, PathBuf, fs::Metadata)>> {
+        let Ok(entries) = fs::read_dir(&self.objects_dir) else {
+            return Ok(Vec::new());
+        };
+        let mut objects = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| Self::io_error(&self.objects_dir, e))?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name == INDEX_FILE {
+                continue;
+            }
+            let path = entry.path();
+            let metadata = fs::symlink_metadata(&path).map_err(|e| Self::io_error(&path, e))?;
+            if metadata.is_file() {
+                objects.push((name, path, metadata));
+            }
+        }
+        Ok(objects)
+    }
+
+    fn load_index(&self) -> ContainerResult<ObjectIndex> {
+        let path = self.objects_dir.join(INDEX_FILE);
+        if !path.is_file() {
+            return Ok(ObjectIndex::default());
+        }
+        let content = fs::read_to_string(&path).map_err(|e| Self::io_error(&path, e))?;
+        serde_json::from_str(&content).map_err(|e| ContainerError::InvalidConfig {
+            path,
+            reason: e.to_string(),
+        })
+    }
+
+    fn save_index(&self, index: &ObjectIndex) -> ContainerResult<()> {
+        filesystem::create_state_dir(&self.objects_dir)?;
+        let content = serde_json::to_vec_pretty(index)?;
+        filesystem::atomic_write(&self.objects_dir.join(INDEX_FILE), &content, Some(filesystem::STATE_FILE_MODE))
+    }
+
+    fn io_error(path: &Path, source: io::Error) -> ContainerError {
+        ContainerError::IoError {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Contents of `objects/index.json`: which containers link to each object.
+/// Hard link counts stay authoritative; the index says who the users are.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObjectIndex {
+    #[serde(default)]
+    pub objects: BTreeMap<String, ObjectEntry>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObjectEntry {
+    pub size: u64,
+    /// `name@version` of every container that had a file linked to the object
+    #[serde(default)]
+    pub containers: BTreeSet<String>,
+}
+
+/// Outcome of deduplicating one container.
+#[derive(Debug, Clone, Default)]
+pub struct DedupSummary {
+    pub files_linked: usize,
+    pub objects_created: usize,
+    pub bytes_saved: u64,
+    /// The store and the object directory are on different filesystems, so files stayed plain copies
+    pub cross_device: bool,
+}
+
+/// Outcome of removing unreferenced objects.
+#[derive(Debug, Clone, Default)]
+pub struct GcSummary {
+    pub removed: usize,
+    pub bytes_freed: u64,
+}
+
+/// Disk usage of the store with hard links counted once.
+#[derive(Debug, Clone, Default)]
+pub struct StoreStats {
+    /// Sum of every file's size, as `du --count-links` would see it
+    pub apparent_size: u64,
+    /// Size of the distinct files actually on disk
+    pub real_size: u64,
+    pub objects: usize,
+    /// Objects no container links to any more, removed by `store gc`
+    pub unreferenced_objects: usize,
+}
+
+impl StoreStats {
+    pub fn savings(&self) -> u64 {
+        self.apparent_size.saturating_sub(self.real_size)
+    }
+}
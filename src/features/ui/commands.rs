@@ -0,0 +1,200 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+use crate::features::bindings::{BindingsCommands, BindingsHandler};
+use crate::features::container::{
+    ContainerCommands, ContainerHandler, ContainerResolver, Executor, OutputMode, PlanFormat, RunArgs, ServiceRuntime, ServiceStart,
+};
+use crate::features::query::StoreQuery;
+use crate::features::ui::{Dashboard, DashboardAction, Terminal, View};
+use crate::shared::error::ContainerResult;
+use crate::shared::paths::WrappyPaths;
+use crate::shared::summary::SummaryFormat;
+use crate::shared::watcher::{WatchOptions, Watcher, WATCH_POLL_INTERVAL};
+
+/// Script and idle timeout `s` starts a service with, the defaults of `container start`.
+const SERVICE_SCRIPT: &str = "service";
+const SERVICE_IDLE_TIMEOUT_SECS: u64 = 900;
+
+/// Script `r` runs, the default of `container run`.
+const RUN_SCRIPT: &str = "default";
+
+pub struct UiHandler;
+
+impl UiHandler {
+    /// Runs the dashboard until the user quits
+    pub fn execute_command() -> i32 {
+        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            eprintln!("❌ wrappy ui needs an interactive terminal");
+            return 1;
+        }
+
+        let (paths, query) = match WrappyPaths::new().map(|paths| (StoreQuery::open(), paths)) {
+            Ok((Some(query), paths)) => (paths, query),
+            Ok((None, _)) | Err(_) => {
+                eprintln!("❌ Could not determine the home directory");
+                return 1;
+            }
+        };
+
+        match Self::run(&paths, &query) {
+            Ok(()) => 0,
+            Err(error) => {
+                eprintln!("❌ Dashboard failed: {}", error);
+                1
+            }
+        }
+    }
+
+    fn run(paths: &WrappyPaths, query: &StoreQuery) -> io::Result<()> {
+        let mut dashboard = Dashboard::new(Dashboard::load_rows(query, paths));
+        let mut watcher = Watcher::new(Self::watched_paths(paths), WatchOptions::default());
+        let mut terminal = Terminal::enter()?;
+
+        loop {
+            if dashboard.needs_details() {
+                if let Some(row) = dashboard.selected_row() {
+                    let details = Dashboard::load_details(query, paths, row);
+                    dashboard.set_details(details);
+                }
+            }
+
+            // Sized on every frame, so a resized terminal is redrawn within one poll interval
+            let (width, height) = Terminal::size();
+            terminal.draw(&dashboard.render(width, height), width)?;
+
+            let Some(key) = Terminal::read_key(WATCH_POLL_INTERVAL)? else {
+                if watcher.next_event(|| false).is_some() {
+                    Self::reload(&mut dashboard, query, paths);
+                }
+                continue;
+            };
+            let Some(action) = dashboard.handle_key(key, height.saturating_sub(3)) else {
+                continue;
+            };
+
+            match action {
+                DashboardAction::Quit => return Ok(()),
+                DashboardAction::Reload => {}
+                DashboardAction::EnableBindings(selector) => {
+                    let command = BindingsCommands::Enable {
+                        container: selector.clone(),
+                        executables_only: false,
+                        configs_only: false,
+                        data_only: false,
+                        label: None,
+                        bin_dir: None,
+                        set: Vec::new(),
+                        override_policy: false,
+                        skip_preflight: false,
+                        force: false,
+                        format: SummaryFormat::Text,
+                    };
+                    let code = Self::suspended(&mut terminal, || BindingsHandler::execute_command(command))?;
+                    dashboard.set_status(Self::finished("Enabling bindings of", &selector, code));
+                }
+                DashboardAction::DisableBindings(selector) => {
+                    let command = BindingsCommands::Disable {
+                        container: selector.clone(),
+                        target: None,
+                        only: None,
+                        label: None,
+                        format: SummaryFormat::Text,
+                    };
+                    let code = Self::suspended(&mut terminal, || BindingsHandler::execute_command(command))?;
+                    dashboard.set_status(Self::finished("Disabling bindings of", &selector, code));
+                }
+                DashboardAction::RunScript(selector) => {
+                    let command = ContainerCommands::Run(RunArgs {
+                        container: selector.clone(),
+                        script: RUN_SCRIPT.to_string(),
+                        dep: None,
+                        watch: Vec::new(),
+                        restart: false,
+                        queue: false,
+                        no_inherit_env: false,
+                        show_env: false,
+                        dry_run: false,
+                        allow_unsandboxed_mounts: false,
+                        format: PlanFormat::Text,
+                        show_secrets: false,
+                        output: OutputMode::Inherit,
+                        args: Vec::new(),
+                    });
+                    let code = Self::suspended(&mut terminal, || ContainerHandler::execute_command(command))?;
+                    dashboard.set_status(Self::finished(&format!("Script '{}' of", RUN_SCRIPT), &selector, code));
+                }
+                DashboardAction::StartService(selector) => {
+                    let status = match Self::start_service(paths, &selector) {
+                        Ok(ServiceStart::Started(pid)) => format!("Started service of {} (pid {})", selector, pid),
+                        Ok(ServiceStart::AlreadyRunning(pid)) => {
+                            format!("Service of {} is already running (pid {})", selector, pid)
+                        }
+                        Err(error) => format!("Failed to start service of {}: {}", selector, error),
+                    };
+                    dashboard.set_status(status);
+                }
+                DashboardAction::StopService(selector) => {
+                    let status = match Self::stop_service(paths, &selector) {
+                        Ok(Some(pid)) => format!("Stopped service of {} (pid {})", selector, pid),
+                        Ok(None) => format!("Service of {} is not running", selector),
+                        Err(error) => format!("Failed to stop service of {}: {}", selector, error),
+                    };
+                    dashboard.set_status(status);
+                }
+            }
+            Self::reload(&mut dashboard, query, paths);
+        }
+    }
+
+    /// State files every action and every other wrappy process writes. The store itself
+    /// is not watched, since scanning container contents would be too slow; `R` rereads it.
+    fn watched_paths(paths: &WrappyPaths) -> Vec<PathBuf> {
+        vec![
+            paths.binding_state_file(),
+            paths.history_file(),
+            paths.services_run_dir(),
+            paths.config_file(),
+        ]
+    }
+
+    fn reload(dashboard: &mut Dashboard, query: &StoreQuery, paths: &WrappyPaths) {
+        dashboard.set_rows(Dashboard::load_rows(query, paths));
+        if dashboard.view() != View::List {
+            if let Some(row) = dashboard.selected_row() {
+                let details = Dashboard::load_details(query, paths, row);
+                dashboard.set_details(details);
+            }
+        }
+    }
+
+    /// Leaves the dashboard for a command that prints and may prompt, exactly as on the
+    /// command line, and comes back once the user has read its output.
+    fn suspended(terminal: &mut Terminal, command: impl FnOnce() -> i32) -> io::Result<i32> {
+        terminal.suspend()?;
+        let code = command();
+        print!("\nPress Enter to return to the dashboard ");
+        io::stdout().flush()?;
+        io::stdin().lock().read_line(&mut String::new())?;
+        terminal.resume()?;
+        Ok(code)
+    }
+
+    fn finished(what: &str, selector: &str, code: i32) -> String {
+        match code {
+            0 => format!("{} {} finished", what, selector),
+            code => format!("{} {} failed (exit code {})", what, selector, code),
+        }
+    }
+
+    fn start_service(paths: &WrappyPaths, selector: &str) -> ContainerResult<ServiceStart> {
+        let container = ContainerResolver::new()?.resolve_container(selector)?;
+        let runtime = ServiceRuntime::new(paths, container.name());
+        runtime.start(&Executor::new(container), SERVICE_SCRIPT, SERVICE_IDLE_TIMEOUT_SECS)
+    }
+
+    fn stop_service(paths: &WrappyPaths, selector: &str) -> ContainerResult<Option<u32>> {
+        let container = ContainerResolver::new()?.resolve_container(selector)?;
+        ServiceRuntime::new(paths, container.name()).stop()
+    }
+}
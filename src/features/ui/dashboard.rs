@@ -0,0 +1,575 @@
+use std::fs;
+
+use crate::features::bindings::BindingCategory;
+use crate::features::container::ServiceRuntime;
+use crate::features::history::{RunHistory, RunRecord};
+use crate::features::query::{BindingStatus, ContainerSummary, ScriptSummary, StoreQuery};
+use crate::shared::paths::WrappyPaths;
+
+/// Runs and service log lines kept for the logs view.
+const LOG_TAIL_LINES: usize = 200;
+const RECENT_RUNS: usize = 10;
+
+/// Smallest terminal the dashboard draws anything but a notice on.
+const MIN_WIDTH: usize = 24;
+const MIN_HEIGHT: usize = 4;
+
+/// A key the dashboard reacts to, already decoded from the terminal's byte sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Enter,
+    Escape,
+    Char(char),
+    /// Ctrl-C, which raw mode delivers as a key instead of a signal
+    Interrupt,
+}
+
+/// Something the dashboard cannot do by itself: it needs the terminal or changes state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DashboardAction {
+    Quit,
+    Reload,
+    EnableBindings(String),
+    DisableBindings(String),
+    RunScript(String),
+    StartService(String),
+    StopService(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    List,
+    Details,
+    Logs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineStyle {
+    Normal,
+    Title,
+    Heading,
+    Selected,
+    Dim,
+}
+
+/// One screen line; the terminal pads or cuts it to the width.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub text: String,
+    pub style: LineStyle,
+}
+
+impl Line {
+    fn new(text: impl Into<String>, style: LineStyle) -> Self {
+        Self { text: text.into(), style }
+    }
+}
+
+/// One installed container version as the list shows it.
+#[derive(Debug, Clone)]
+pub struct ContainerRow {
+    pub summary: ContainerSummary,
+    pub bindings: Vec<BindingStatus>,
+    /// Pid of the container's running background service
+    pub service_pid: Option<u32>,
+}
+
+impl ContainerRow {
+    /// `name@version`, which every command resolves to exactly this row.
+    pub fn selector(&self) -> String {
+        format!("{}@{}", self.summary.name, self.summary.version)
+    }
+
+    fn active_bindings(&self) -> usize {
+        self.bindings.iter().filter(|binding| binding.active).count()
+    }
+}
+
+/// What the details and logs views show about the selected container, loaded on demand.
+#[derive(Debug, Clone, Default)]
+pub struct ContainerDetails {
+    pub scripts: Vec<ScriptSummary>,
+    pub recent_runs: Vec<RunRecord>,
+    pub log_tail: Vec<String>,
+}
+
+/// State of `wrappy ui`. Rendering is a pure function of it, so the same state
+/// always draws the same lines whatever the terminal.
+pub struct Dashboard {
+    rows: Vec<ContainerRow>,
+    selected: usize,
+    view: View,
+    /// First line shown in the details and logs views
+    scroll: usize,
+    details: Option<ContainerDetails>,
+    status: Option<String>,
+}
+
+impl Dashboard {
+    pub fn new(rows: Vec<ContainerRow>) -> Self {
+        Self {
+            rows,
+            selected: 0,
+            view: View::List,
+            scroll: 0,
+            details: None,
+            status: None,
+        }
+    }
+
+    /// Every installed container version with its binding and service state, read through
+    /// the same query API completion uses.
+    pub fn load_rows(query: &StoreQuery, paths: &WrappyPaths) -> Vec<ContainerRow> {
+        query
+            .installed_containers()
+            .into_iter()
+            .map(|summary| ContainerRow {
+                bindings: query.bindings_of(&summary.path.to_string_lossy()),
+                service_pid: ServiceRuntime::new(paths, &summary.name).running().map(|state| state.pid),
+                summary,
+            })
+            .collect()
+    }
+
+    /// Scripts, recent runs and the tail of the service log of `row`.
+    pub fn load_details(query: &StoreQuery, paths: &WrappyPaths, row: &ContainerRow) -> ContainerDetails {
+        let runs = RunHistory::new(paths).read_all().map(|history| history.records).unwrap_or_default();
+        let mut recent_runs: Vec<RunRecord> = runs
+            .into_iter()
+            .filter(|record| record.container == row.summary.name)
+            .collect();
+        recent_runs.drain(..recent_runs.len().saturating_sub(RECENT_RUNS));
+
+        let log = fs::read_to_string(ServiceRuntime::new(paths, &row.summary.name).log_file()).unwrap_or_default();
+        let lines: Vec<&str> = log.lines().collect();
+        let log_tail = lines[lines.len().saturating_sub(LOG_TAIL_LINES)..]
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+
+        ContainerDetails {
+            scripts: query.scripts_of(&row.summary.path.to_string_lossy()),
+            recent_runs,
+            log_tail,
+        }
+    }
+
+    /// Replaces the rows after a reload, keeping the same container selected if it is still there.
+    pub fn set_rows(&mut self, rows: Vec<ContainerRow>) {
+        let selected = self.selected_row().map(ContainerRow::selector);
+        self.rows = rows;
+        self.selected = selected
+            .and_then(|selector| self.rows.iter().position(|row| row.selector() == selector))
+            .unwrap_or(self.selected)
+            .min(self.rows.len().saturating_sub(1));
+        if self.rows.is_empty() {
+            self.view = View::List;
+        }
+    }
+
+    pub fn set_details(&mut self, details: ContainerDetails) {
+        self.details = Some(details);
+    }
+
+    /// Message for the footer until the next key press.
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = Some(status.into());
+    }
+
+    pub fn selected_row(&self) -> Option<&ContainerRow> {
+        self.rows.get(self.selected)
+    }
+
+    pub fn view(&self) -> View {
+        self.view
+    }
+
+    /// The details and logs views read their content lazily; `true` until it was set.
+    pub fn needs_details(&self) -> bool {
+        self.view != View::List && self.details.is_none()
+    }
+
+    /// Moves through the views; returns what the caller has to do for keys that act.
+    pub fn handle_key(&mut self, key: Key, page: usize) -> Option<DashboardAction> {
+        self.status = None;
+        let selector = self.selected_row().map(ContainerRow::selector);
+        match (key, self.view) {
+            (Key::Char('q') | Key::Interrupt, _) => return Some(DashboardAction::Quit),
+            (Key::Char('R'), _) => return Some(DashboardAction::Reload),
+            (Key::Escape | Key::Left | Key::Char('h'), View::Details | View::Logs) => self.open(View::List),
+            (Key::Up | Key::Char('k'), View::List) => self.selected = self.selected.saturating_sub(1),
+            (Key::Down | Key::Char('j'), View::List) => {
+                self.selected = (self.selected + 1).min(self.rows.len().saturating_sub(1))
+            }
+            (Key::PageUp, View::List) => self.selected = self.selected.saturating_sub(page.max(1)),
+            (Key::PageDown, View::List) => {
+                self.selected = (self.selected + page.max(1)).min(self.rows.len().saturating_sub(1))
+            }
+            (Key::Up | Key::Char('k'), _) => self.scroll = self.scroll.saturating_sub(1),
+            (Key::Down | Key::Char('j'), _) => self.scroll = (self.scroll + 1).min(self.last_scroll(page)),
+            (Key::PageUp, _) => self.scroll = self.scroll.saturating_sub(page.max(1)),
+            (Key::PageDown, _) => self.scroll = (self.scroll + page.max(1)).min(self.last_scroll(page)),
+            (Key::Enter | Key::Right | Key::Char('l'), View::List) if selector.is_some() => self.open(View::Details),
+            (Key::Char('L'), _) if selector.is_some() => self.open(View::Logs),
+            (Key::Char('e'), _) => return selector.map(DashboardAction::EnableBindings),
+            (Key::Char('d'), _) => return selector.map(DashboardAction::DisableBindings),
+            (Key::Char('r'), _) => return selector.map(DashboardAction::RunScript),
+            (Key::Char('s'), _) => return selector.map(DashboardAction::StartService),
+            (Key::Char('x'), _) => return selector.map(DashboardAction::StopService),
+            _ => {}
+        }
+        None
+    }
+
+    /// Scroll position that shows the last line of the details or logs view at the bottom.
+    fn last_scroll(&self, page: usize) -> usize {
+        let lines = match self.view {
+            View::List => 0,
+            View::Details => self.details_lines().len(),
+            View::Logs => self.log_lines().len(),
+        };
+        lines.saturating_sub(page)
+    }
+
+    fn open(&mut self, view: View) {
+        self.view = view;
+        self.scroll = 0;
+        if view == View::List {
+            self.details = None;
+        }
+    }
+
+    /// Lines for a `width` x `height` terminal, never more than `height` of them.
+    pub fn render(&self, width: usize, height: usize) -> Vec<Line> {
+        if width < MIN_WIDTH || height < MIN_HEIGHT {
+            return vec![Line::new("Terminal too small", LineStyle::Dim)];
+        }
+
+        let (title, body) = match self.view {
+            View::List => (format!("wrappy - {} container version(s)", self.rows.len()), self.render_list(width, height - 2)),
+            View::Details => (self.selected_title("details"), self.scrolled(self.details_lines(), height - 2)),
+            View::Logs => (self.selected_title("logs"), self.scrolled(self.log_lines(), height - 2)),
+        };
+
+        let mut lines = vec![Line::new(title, LineStyle::Title)];
+        lines.extend(body);
+        lines.resize(height - 1, Line::new("", LineStyle::Normal));
+        let footer = self.status.clone().unwrap_or_else(|| self.key_help().to_string());
+        lines.push(Line::new(footer, LineStyle::Dim));
+        lines
+    }
+
+    fn selected_title(&self, view: &str) -> String {
+        match self.selected_row() {
+            Some(row) => format!("wrappy - {} {}", row.selector(), view),
+            None => "wrappy".to_string(),
+        }
+    }
+
+    fn key_help(&self) -> &'static str {
+        match self.view {
+            View::List => "j/k move  enter details  L logs  e/d enable/disable bindings  r run  s/x start/stop service  R reload  q quit",
+            View::Details | View::Logs => "esc back  j/k scroll  L logs  e/d enable/disable bindings  r run  s/x start/stop service  q quit",
+        }
+    }
+
+    /// Columns that fit `width`; narrow terminals drop the least important ones first.
+    fn render_list(&self, width: usize, height: usize) -> Vec<Line> {
+        if self.rows.is_empty() {
+            return vec![
+                Line::new("No containers installed.", LineStyle::Normal),
+                Line::new("Install one into the store and press R.", LineStyle::Dim),
+            ];
+        }
+
+        const COLUMNS: [(&str, usize, usize); 4] = [
+            ("VERSION", 12, 36),
+            ("BINDINGS", 9, 48),
+            ("SERVICE", 15, 64),
+            ("TYPE", 12, 80),
+        ];
+        let shown: Vec<_> = COLUMNS.iter().filter(|(_, _, min_width)| width >= *min_width).collect();
+        let name_width = width.saturating_sub(shown.iter().map(|(_, column, _)| column + 1).sum::<usize>()).max(8);
+
+        let cells = |name: &str, values: Vec<String>| {
+            let mut line = Self::cell(name, name_width);
+            for ((_, column, _), value) in shown.iter().zip(values) {
+                line.push(' ');
+                line.push_str(&Self::cell(&value, *column));
+            }
+            line
+        };
+
+        let headings = shown.iter().map(|(heading, _, _)| heading.to_string()).collect();
+        let mut lines = vec![Line::new(cells("NAME", headings), LineStyle::Heading)];
+
+        // Keep the selection on screen
+        let visible = height.saturating_sub(1).max(1);
+        let first = (self.selected + 1).saturating_sub(visible);
+        for (index, row) in self.rows.iter().enumerate().skip(first).take(visible) {
+            let values = shown
+                .iter()
+                .map(|(heading, _, _)| match *heading {
+                    "VERSION" => row.summary.version.to_string(),
+                    "BINDINGS" => format!("{}/{}", row.active_bindings(), row.bindings.len()),
+                    "SERVICE" => row.service_pid.map_or("-".to_string(), |pid| format!("running {}", pid)),
+                    _ => row.summary.container_type.to_string(),
+                })
+                .collect();
+            let style = if index == self.selected { LineStyle::Selected } else { LineStyle::Normal };
+            lines.push(Line::new(cells(&row.summary.name, values), style));
+        }
+        lines
+    }
+
+    fn details_lines(&self) -> Vec<Line> {
+        let Some(row) = self.selected_row() else {
+            return Vec::new();
+        };
+        let summary = &row.summary;
+        let mut lines = vec![
+            Line::new(format!("Type:        {}", summary.container_type), LineStyle::Normal),
+            Line::new(format!("Path:        {}", summary.path.display()), LineStyle::Normal),
+        ];
+        if !summary.description.is_empty() {
+            lines.push(Line::new(format!("Description: {}", summary.description), LineStyle::Normal));
+        }
+        lines.push(Line::new(
+            match row.service_pid {
+                Some(pid) => format!("Service:     running (pid {})", pid),
+                None => "Service:     not running".to_string(),
+            },
+            LineStyle::Normal,
+        ));
+
+        lines.push(Line::new("", LineStyle::Normal));
+        lines.push(Line::new("Scripts", LineStyle::Heading));
+        let scripts = self.details.as_ref().map(|details| details.scripts.as_slice()).unwrap_or_default();
+        if scripts.is_empty() {
+            lines.push(Line::new("  none", LineStyle::Dim));
+        }
+        for script in scripts {
+            let inline = if script.inline { " (inline)" } else { "" };
+            lines.push(Line::new(format!("  {}{}", script.name, inline), LineStyle::Normal));
+        }
+
+        lines.push(Line::new("", LineStyle::Normal));
+        lines.push(Line::new("Bindings", LineStyle::Heading));
+        if row.bindings.is_empty() {
+            lines.push(Line::new("  none", LineStyle::Dim));
+        }
+        for binding in &row.bindings {
+            let category = match binding.category {
+                BindingCategory::Executables => "executable",
+                BindingCategory::Configs => "config",
+                BindingCategory::Data => "data",
+            };
+            let state = if binding.active { "[on] " } else { "[off]" };
+            lines.push(Line::new(
+                format!("  {} {:<10} {} ({})", state, category, binding.target, binding.binding_type),
+                if binding.active { LineStyle::Normal } else { LineStyle::Dim },
+            ));
+        }
+        lines
+    }
+
+    fn log_lines(&self) -> Vec<Line> {
+        let Some(details) = &self.details else {
+            return Vec::new();
+        };
+        let mut lines = vec![Line::new("Recent runs", LineStyle::Heading)];
+        if details.recent_runs.is_empty() {
+            lines.push(Line::new("  none", LineStyle::Dim));
+        }
+        for run in details.recent_runs.iter().rev() {
+            lines.push(Line::new(
+                format!(
+                    "  {}  {:<12} exit {:<4} {} ms",
+                    run.started_at.format("%Y-%m-%d %H:%M:%S"),
+                    run.script,
+                    run.exit_code,
+                    run.duration_ms
+                ),
+                if run.succeeded() { LineStyle::Normal } else { LineStyle::Selected },
+            ));
+        }
+
+        lines.push(Line::new("", LineStyle::Normal));
+        lines.push(Line::new("Service log", LineStyle::Heading));
+        if details.log_tail.is_empty() {
+            lines.push(Line::new("  empty", LineStyle::Dim));
+        }
+        lines.extend(details.log_tail.iter().map(|line| Line::new(format!("  {}", line), LineStyle::Normal)));
+        lines
+    }
+
+    /// The part of `lines` the scroll position shows; scrolling stops at the last line.
+    fn scrolled(&self, lines: Vec<Line>, height: usize) -> Vec<Line> {
+        let first = self.scroll.min(lines.len().saturating_sub(height));
+        lines.into_iter().skip(first).take(height).collect()
+    }
+
+    /// `value` cut or padded to exactly `width` characters.
+    fn cell(value: &str, width: usize) -> String {
+        let count = value.chars().count();
+        if count <= width {
+            return format!("{}{}", value, " ".repeat(width - count));
+        }
+        let mut cut: String = value.chars().take(width.saturating_sub(1)).collect();
+        cut.push('~');
+        cut
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::bindings::BindingManager;
+    use crate::features::container::ContainerService;
+    use crate::features::setup::SetupService;
+    use crate::shared::test_support::{fixture_container_at, fixture_manifest, TempHome};
+    use serde_json::json;
+
+    /// Three containers in the store; `api` has its bindings enabled and a running service.
+    fn fixture_rows(home: &TempHome) -> Vec<ContainerRow> {
+        let paths = home.paths();
+        SetupService::initialize(&paths).unwrap();
+        let store = paths.default_store_dir();
+        let mut web = fixture_manifest("a-web-frontend-with-a-long-name", "2.1.0");
+        web["type"] = json!("package");
+        fixture_container_at(&store.join("a-web-frontend-with-a-long-name/2.1.0"), web);
+        fixture_container_at(&store.join("api/1.1.0"), fixture_manifest("api", "1.1.0"));
+        fixture_container_at(&store.join("db/16.2.0"), fixture_manifest("db", "16.2.0"));
+        let api = ContainerService::load_from_directory(store.join("api/1.1.0")).unwrap();
+        BindingManager::new().unwrap().install_bindings(&api).unwrap();
+
+        let mut rows = Dashboard::load_rows(&StoreQuery::with_store(store), &paths);
+        for row in &mut rows {
+            if row.selector() == "api@1.1.0" {
+                row.service_pid = Some(4242);
+            }
+        }
+        rows
+    }
+
+    fn text(lines: &[Line]) -> String {
+        lines.iter().map(|line| format!("{}\n", line.text.trim_end())).collect()
+    }
+
+    #[test]
+    fn the_list_matches_the_snapshot_on_a_wide_terminal() {
+        let home = TempHome::enter();
+        let dashboard = Dashboard::new(fixture_rows(&home));
+
+        let lines = dashboard.render(80, 8);
+
+        assert_eq!(text(&lines), include_str!("../../../tests/fixtures/ui_list_80x8.txt"));
+        assert_eq!(lines[2].style, LineStyle::Selected);
+        assert_eq!(lines[3].style, LineStyle::Normal);
+    }
+
+    #[test]
+    fn narrow_terminals_drop_columns_and_tiny_ones_show_a_notice() {
+        let home = TempHome::enter();
+        let dashboard = Dashboard::new(fixture_rows(&home));
+
+        let medium = dashboard.render(50, 6);
+        let narrow = dashboard.render(30, 6);
+        let tiny = dashboard.render(23, 6);
+
+        assert_eq!(medium[1].text.split_whitespace().collect::<Vec<_>>(), ["NAME", "VERSION", "BINDINGS"]);
+        assert_eq!(medium[3].text.split_whitespace().collect::<Vec<_>>(), ["api", "1.1.0", "1/1"]);
+        assert_eq!(narrow[1].text.trim_end(), "NAME");
+        assert_eq!(narrow[2].text, format!("{}~", "a-web-frontend-with-a-long-name".chars().take(29).collect::<String>()));
+        assert!(medium.iter().chain(&narrow).all(|line| line.text.chars().count() <= 50 || line.style == LineStyle::Dim));
+        assert_eq!(tiny, [Line::new("Terminal too small", LineStyle::Dim)]);
+        assert_eq!(dashboard.render(80, 3).len(), 1);
+    }
+
+    #[test]
+    fn the_selection_stays_on_screen_and_survives_a_reload() {
+        let home = TempHome::enter();
+        let rows = fixture_rows(&home);
+        let mut dashboard = Dashboard::new(rows.clone());
+
+        dashboard.handle_key(Key::Down, 1);
+        dashboard.handle_key(Key::Char('j'), 1);
+        dashboard.handle_key(Key::Down, 1);
+        let lines = dashboard.render(80, 4);
+
+        assert_eq!(dashboard.selected_row().unwrap().selector(), "db@16.2.0");
+        assert_eq!(lines.len(), 4);
+        assert!(lines[2].text.starts_with("db "), "{:?}", lines);
+        assert_eq!(lines[2].style, LineStyle::Selected);
+
+        dashboard.handle_key(Key::Up, 1);
+        dashboard.set_rows(rows[1..].to_vec());
+        assert_eq!(dashboard.selected_row().unwrap().selector(), "api@1.1.0");
+        dashboard.set_rows(Vec::new());
+        assert!(dashboard.selected_row().is_none());
+        assert_eq!(text(&dashboard.render(80, 5)[1..3]), "No containers installed.\nInstall one into the store and press R.\n");
+    }
+
+    #[test]
+    fn keys_open_views_and_return_actions_for_the_selected_container() {
+        let home = TempHome::enter();
+        let mut dashboard = Dashboard::new(fixture_rows(&home));
+        dashboard.handle_key(Key::Down, 10);
+
+        assert_eq!(dashboard.handle_key(Key::Char('e'), 10), Some(DashboardAction::EnableBindings("api@1.1.0".to_string())));
+        assert_eq!(dashboard.handle_key(Key::Char('x'), 10), Some(DashboardAction::StopService("api@1.1.0".to_string())));
+        assert_eq!(dashboard.handle_key(Key::Interrupt, 10), Some(DashboardAction::Quit));
+        assert_eq!(dashboard.handle_key(Key::Enter, 10), None);
+        assert_eq!(dashboard.view(), View::Details);
+        assert!(dashboard.needs_details());
+        dashboard.set_details(ContainerDetails::default());
+        assert!(!dashboard.needs_details());
+        let details = text(&dashboard.render(80, 20));
+        assert!(details.starts_with("wrappy - api@1.1.0 details\n"), "{}", details);
+        assert!(details.contains("Service:     running (pid 4242)"), "{}", details);
+        assert!(details.contains("  [on]  executable api-tool (wrapper)"), "{}", details);
+
+        dashboard.handle_key(Key::Escape, 10);
+        assert_eq!(dashboard.view(), View::List);
+        assert!(!dashboard.needs_details());
+        assert_eq!(Dashboard::new(Vec::new()).handle_key(Key::Char('r'), 10), None);
+    }
+
+    #[test]
+    fn the_logs_view_shows_the_newest_run_first_and_scrolls_to_the_last_line() {
+        let home = TempHome::enter();
+        let mut dashboard = Dashboard::new(fixture_rows(&home));
+        dashboard.handle_key(Key::Char('L'), 3);
+        let run = |script: &str, exit_code: i32| RunRecord {
+            container: "a-web-frontend-with-a-long-name".to_string(),
+            script: script.to_string(),
+            started_at: chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            duration_ms: 12,
+            exit_code,
+            output_mode: None,
+            output_file: None,
+        };
+        dashboard.set_details(ContainerDetails {
+            scripts: Vec::new(),
+            recent_runs: vec![run("build", 0), run("test", 1)],
+            log_tail: (1..=10).map(|n| format!("log line {}", n)).collect(),
+        });
+
+        let top = dashboard.render(80, 5);
+        for _ in 0..50 {
+            dashboard.handle_key(Key::PageDown, 3);
+        }
+        let bottom = dashboard.render(80, 5);
+
+        assert_eq!(top[1].text, "Recent runs");
+        assert!(top[2].text.contains(" test ") && top[2].style == LineStyle::Selected, "{:?}", top[2]);
+        assert!(top[3].text.contains(" build ") && top[3].style == LineStyle::Normal, "{:?}", top[3]);
+        assert_eq!(text(&bottom[1..4]), "  log line 8\n  log line 9\n  log line 10\n");
+    }
+}
@@ -0,0 +1,11 @@
+//! `wrappy ui`, an interactive terminal dashboard over the same APIs the CLI uses:
+//! the query module for the lists, and the command handlers and service runtime for
+//! actions. Built only with the `tui` feature.
+
+mod commands;
+mod dashboard;
+mod terminal;
+
+pub use commands::*;
+pub use dashboard::*;
+pub use terminal::*;
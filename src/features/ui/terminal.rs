@@ -0,0 +1,168 @@
+use std::io::{self, Read, Write};
+use std::os::fd::AsRawFd;
+use std::time::Duration;
+
+use crate::features::ui::{Key, Line, LineStyle};
+
+/// The controlling terminal in raw mode on the alternate screen. Dropping it restores
+/// the original mode and screen, also when the dashboard panics.
+pub struct Terminal {
+    original: libc::termios,
+    suspended: bool,
+}
+
+impl Terminal {
+    /// Switches stdin to raw mode and stdout to the alternate screen.
+    pub fn enter() -> io::Result<Self> {
+        let fd = io::stdin().as_raw_fd();
+        let mut original = unsafe { std::mem::zeroed::<libc::termios>() };
+        if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut terminal = Self {
+            original,
+            suspended: true,
+        };
+        terminal.resume()?;
+        Ok(terminal)
+    }
+
+    /// Hands the terminal back for a command that prints or prompts, like the CLI would.
+    pub fn suspend(&mut self) -> io::Result<()> {
+        self.suspended = true;
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x1b[0m\x1b[?25h\x1b[?1049l")?;
+        stdout.flush()?;
+        if unsafe { libc::tcsetattr(io::stdin().as_raw_fd(), libc::TCSANOW, &self.original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Raw input without echo, so keys arrive one by one and Ctrl-C is a key, not a signal.
+    pub fn resume(&mut self) -> io::Result<()> {
+        let mut raw = self.original;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+        raw.c_iflag &= !(libc::IXON | libc::ICRNL);
+        raw.c_cc[libc::VMIN] = 0;
+        raw.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(io::stdin().as_raw_fd(), libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x1b[?1049h\x1b[?25l")?;
+        stdout.flush()?;
+        self.suspended = false;
+        Ok(())
+    }
+
+    /// Columns and rows; 80x24 when the size cannot be read.
+    pub fn size() -> (usize, usize) {
+        let mut size = unsafe { std::mem::zeroed::<libc::winsize>() };
+        let read = unsafe { libc::ioctl(io::stdout().as_raw_fd(), libc::TIOCGWINSZ, &mut size) } == 0;
+        if read && size.ws_col > 0 && size.ws_row > 0 {
+            (size.ws_col as usize, size.ws_row as usize)
+        } else {
+            (80, 24)
+        }
+    }
+
+    /// Waits up to `timeout` for a key. Unknown escape sequences are dropped.
+    pub fn read_key(timeout: Duration) -> io::Result<Option<Key>> {
+        let mut poll = libc::pollfd {
+            fd: io::stdin().as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ready = unsafe { libc::poll(&mut poll, 1, timeout.as_millis() as libc::c_int) };
+        if ready < 0 {
+            let error = io::Error::last_os_error();
+            // A resize interrupts the wait; the caller redraws at the new size anyway
+            return if error.kind() == io::ErrorKind::Interrupted { Ok(None) } else { Err(error) };
+        }
+        if ready == 0 {
+            return Ok(None);
+        }
+
+        let mut buffer = [0u8; 16];
+        let read = io::stdin().lock().read(&mut buffer)?;
+        Ok(Self::decode(&buffer[..read]))
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Key> {
+        match bytes {
+            [] => None,
+            [0x1b] => Some(Key::Escape),
+            [0x1b, b'[', b'A'] | [0x1b, b'O', b'A'] => Some(Key::Up),
+            [0x1b, b'[', b'B'] | [0x1b, b'O', b'B'] => Some(Key::Down),
+            [0x1b, b'[', b'C'] | [0x1b, b'O', b'C'] => Some(Key::Right),
+            [0x1b, b'[', b'D'] | [0x1b, b'O', b'D'] => Some(Key::Left),
+            [0x1b, b'[', b'5', b'~'] => Some(Key::PageUp),
+            [0x1b, b'[', b'6', b'~'] => Some(Key::PageDown),
+            [0x1b, ..] => None,
+            [0x03] => Some(Key::Interrupt),
+            [b'\r'] | [b'\n'] => Some(Key::Enter),
+            _ => std::str::from_utf8(bytes).ok()?.chars().next().map(Key::Char),
+        }
+    }
+
+    /// Draws `lines` from the top-left corner, each padded or cut to `width` columns.
+    pub fn draw(&self, lines: &[Line], width: usize) -> io::Result<()> {
+        let mut frame = String::from("\x1b[H");
+        for (index, line) in lines.iter().enumerate() {
+            if index > 0 {
+                frame.push_str("\r\n");
+            }
+            frame.push_str(match line.style {
+                LineStyle::Normal => "",
+                LineStyle::Title => "\x1b[1;7m",
+                LineStyle::Heading => "\x1b[1m",
+                LineStyle::Selected => "\x1b[7m",
+                LineStyle::Dim => "\x1b[2m",
+            });
+            let text: String = line.text.chars().filter(|c| !c.is_control()).take(width).collect();
+            let padding = width.saturating_sub(text.chars().count());
+            frame.push_str(&text);
+            frame.push_str(&" ".repeat(padding));
+            frame.push_str("\x1b[0m");
+        }
+        frame.push_str("\x1b[J");
+
+        let mut stdout = io::stdout();
+        stdout.write_all(frame.as_bytes())?;
+        stdout.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if !self.suspended {
+            let _ = self.suspend();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_maps_escape_sequences_and_utf8_to_keys() {
+        let cases: [(&[u8], Option<Key>); 10] = [
+            (b"\x1b[A", Some(Key::Up)),
+            (b"\x1bOB", Some(Key::Down)),
+            (b"\x1b[5~", Some(Key::PageUp)),
+            (b"\x1b[6~", Some(Key::PageDown)),
+            (b"\x1b", Some(Key::Escape)),
+            (b"\x1b[99~", None),
+            (b"\x03", Some(Key::Interrupt)),
+            (b"\r", Some(Key::Enter)),
+            ("ї".as_bytes(), Some(Key::Char('ї'))),
+            (b"", None),
+        ];
+
+        for (bytes, key) in cases {
+            assert_eq!(Terminal::decode(bytes), key, "{:?}", bytes);
+        }
+    }
+}
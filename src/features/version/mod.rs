@@ -2,9 +2,15 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
 use regex::Regex;
+use std::sync::OnceLock;
 
 use crate::shared::error::{ContainerError, ContainerResult};
 
+fn semver_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"^(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)$").expect("valid semver pattern"))
+}
+
 /// Semantic version for containers following semver format (major.minor.patch)
 /// Stored as string to preserve exact format and enable flexible validation
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -14,15 +20,26 @@ pub struct Version {
 }
 
 impl Version {
-    /// Creates a new version from string format
+    /// Creates a new version from string format, ignoring surrounding whitespace
     pub fn new(version: &str) -> ContainerResult<Self> {
         let instance = Self {
-            version: version.to_string(),
+            version: version.trim().to_string(),
         };
         instance.validate()?;
         Ok(instance)
     }
 
+    /// Like `new`, but also accepts the `v1.2.3` form of git tags.
+    /// For user input only; manifests must spell versions out exactly.
+    pub fn parse_lenient(version: &str) -> ContainerResult<Self> {
+        let trimmed = version.trim();
+        let stripped = trimmed
+            .strip_prefix('v')
+            .or_else(|| trimmed.strip_prefix('V'))
+            .unwrap_or(trimmed);
+        Self::new(stripped)
+    }
+
     /// Creates version from individual components
     pub fn from_parts(major: u32, minor: u32, patch: u32) -> ContainerResult<Self> {
         let version_string = format!("{}.{}.{}", major, minor, patch);
@@ -36,12 +53,26 @@ impl Version {
 
     /// Validates version string format
     fn validate_version_format(version: &str) -> ContainerResult<()> {
-        let semver_regex = Regex::new(r"^(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)$")
-            .map_err(|_| ContainerError::InvalidVersion {
+        // `01.2.3` and `1.2.3` would sort as equal but name different directories
+        let parts: Vec<&str> = version.split('.').collect();
+        let numeric = parts.len() == 3
+            && parts.iter().all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+        if numeric && parts.iter().any(|part| part.len() > 1 && part.starts_with('0')) {
+            let normalized: Vec<&str> = parts
+                .iter()
+                .map(|part| match part.trim_start_matches('0') {
+                    "" => "0",
+                    digits => digits,
+                })
+                .collect();
+            return Err(ContainerError::VersionLeadingZeros {
                 version: version.to_string(),
-            })?;
+                normalized: normalized.join("."),
+            });
+        }
 
-        if !semver_regex.is_match(version) {
+        // A component past u32 could not be compared and would sort below every version
+        if !semver_pattern().is_match(version) || parts.iter().any(|part| part.parse::<u32>().is_err()) {
             return Err(ContainerError::InvalidVersion {
                 version: version.to_string(),
             });
@@ -149,4 +180,218 @@ impl Ord for Version {
     }
 }
 
+/// Comparison operator of a version requirement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionOp {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    /// Same major version and at least the given version (`^1.2.0`)
+    Caret,
+    /// Same major and minor version and at least the given version (`~1.2.0`)
+    Tilde,
+}
+
+/// Which side of a requirement a version falls on when it does not match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionMismatch {
+    TooOld,
+    TooNew,
+}
+
+/// Version constraint like `>=2.30.0` used to check tools that are not containers.
+/// Missing minor/patch components are treated as zero so `>=2.30` reads naturally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    op: VersionOp,
+    version: Version,
+}
+
+impl VersionReq {
+    pub fn matches(&self, version: &Version) -> bool {
+        let ordering = version.cmp(&self.version);
+        let same_major = version.major().ok() == self.version.major().ok();
+        let same_minor = same_major && version.minor().ok() == self.version.minor().ok();
+
+        match self.op {
+            VersionOp::Exact => ordering == std::cmp::Ordering::Equal,
+            VersionOp::Greater => ordering == std::cmp::Ordering::Greater,
+            VersionOp::GreaterEq => ordering != std::cmp::Ordering::Less,
+            VersionOp::Less => ordering == std::cmp::Ordering::Less,
+            VersionOp::LessEq => ordering != std::cmp::Ordering::Greater,
+            VersionOp::Caret => same_major && ordering != std::cmp::Ordering::Less,
+            VersionOp::Tilde => same_minor && ordering != std::cmp::Ordering::Less,
+        }
+    }
+
+    /// Whether `version` is below or above the allowed range; `None` when it matches.
+    /// `<2.0` rejects newer versions and `>=2.0` older ones; `^1.2` rejects both.
+    pub fn mismatch(&self, version: &Version) -> Option<VersionMismatch> {
+        if self.matches(version) {
+            return None;
+        }
+        match self.op {
+            VersionOp::Less | VersionOp::LessEq => Some(VersionMismatch::TooNew),
+            _ if version > &self.version => Some(VersionMismatch::TooNew),
+            _ => Some(VersionMismatch::TooOld),
+        }
+    }
+
+    /// Pads partial versions (`2`, `2.30`) to the full `major.minor.patch` form.
+    fn normalize(version: &str) -> String {
+        let parts = version.split('.').count();
+        match parts {
+            1 => format!("{}.0.0", version),
+            2 => format!("{}.0", version),
+            _ => version.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            VersionOp::Exact => "=",
+            VersionOp::Greater => ">",
+            VersionOp::GreaterEq => ">=",
+            VersionOp::Less => "<",
+            VersionOp::LessEq => "<=",
+            VersionOp::Caret => "^",
+            VersionOp::Tilde => "~",
+        };
+        write!(f, "{}{}", op, self.version)
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = ContainerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let (op, rest) = [
+            (">=", VersionOp::GreaterEq),
+            ("<=", VersionOp::LessEq),
+            (">", VersionOp::Greater),
+            ("<", VersionOp::Less),
+            ("=", VersionOp::Exact),
+            ("^", VersionOp::Caret),
+            ("~", VersionOp::Tilde),
+        ]
+        .iter()
+        .find_map(|(prefix, op)| trimmed.strip_prefix(prefix).map(|rest| (*op, rest)))
+        .unwrap_or((VersionOp::Caret, trimmed));
+
+        let version = Version::new(&Self::normalize(rest.trim())).map_err(|_| {
+            ContainerError::InvalidVersion {
+                version: s.to_string(),
+            }
+        })?;
+
+        Ok(Self { op, version })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mismatch(req: &str, version: &str) -> Option<VersionMismatch> {
+        req.parse::<VersionReq>().unwrap().mismatch(&Version::new(version).unwrap())
+    }
+
+    #[test]
+    fn lower_bounds_report_versions_below_them_as_too_old() {
+        assert_eq!(mismatch(">=2.30", "2.29.9"), Some(VersionMismatch::TooOld));
+        assert_eq!(mismatch(">1.2.3", "1.2.3"), Some(VersionMismatch::TooOld));
+        assert_eq!(mismatch(">=2.30", "2.30.0"), None);
+    }
+
+    #[test]
+    fn upper_bounds_report_versions_above_them_as_too_new() {
+        assert_eq!(mismatch("<3", "3.0.0"), Some(VersionMismatch::TooNew));
+        assert_eq!(mismatch("<=2.5", "2.5.1"), Some(VersionMismatch::TooNew));
+        assert_eq!(mismatch("=1.2.3", "1.2.4"), Some(VersionMismatch::TooNew));
+        assert_eq!(mismatch("<3", "2.9.0"), None);
+    }
 
+    /// Deterministic corpus of versions: small, boundary and pseudo-random components.
+    fn corpus() -> Vec<(u32, u32, u32)> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            match state % 4 {
+                0 => (state >> 8) as u32 % 10,
+                1 => (state >> 8) as u32 % 1000,
+                2 => (state >> 8) as u32,
+                _ => [0, 1, 9, 10, u32::MAX][(state >> 8) as usize % 5],
+            }
+        };
+        (0..2000).map(|_| (next(), next(), next())).collect()
+    }
+
+    #[test]
+    fn display_then_parse_gives_the_same_version() {
+        for (major, minor, patch) in corpus() {
+            let version = Version::from_parts(major, minor, patch).unwrap();
+
+            let reparsed = Version::new(&version.to_string()).unwrap();
+
+            assert_eq!(reparsed, version);
+            assert_eq!((reparsed.major().unwrap(), reparsed.minor().unwrap(), reparsed.patch().unwrap()), (major, minor, patch));
+            assert_eq!(Version::parse_lenient(&format!(" v{} ", version)).unwrap(), version);
+        }
+    }
+
+    #[test]
+    fn versions_order_by_their_numeric_components() {
+        let mut corpus = corpus();
+        let mut versions: Vec<Version> = corpus.iter().map(|&(a, b, c)| Version::from_parts(a, b, c).unwrap()).collect();
+
+        corpus.sort();
+        versions.sort();
+
+        let components: Vec<(u32, u32, u32)> = versions
+            .iter()
+            .map(|version| (version.major().unwrap(), version.minor().unwrap(), version.patch().unwrap()))
+            .collect();
+        assert_eq!(components, corpus);
+        assert!(Version::new("1.10.0").unwrap() > Version::new("1.9.0").unwrap());
+    }
+
+    #[test]
+    fn leading_zeros_are_rejected_with_the_normalized_form() {
+        for (input, normalized) in [("01.2.3", "1.2.3"), ("1.02.0", "1.2.0"), ("1.2.00", "1.2.0"), ("007.0.10", "7.0.10")] {
+            match Version::new(input) {
+                Err(ContainerError::VersionLeadingZeros { normalized: suggested, .. }) => assert_eq!(suggested, normalized),
+                other => panic!("{}: {:?}", input, other),
+            }
+            assert!(Version::parse_lenient(&format!("v{}", input)).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn strict_parsing_trims_whitespace_but_refuses_a_v_prefix() {
+        assert_eq!(Version::new("  1.2.3\n").unwrap().as_str(), "1.2.3");
+        assert!(matches!(Version::new("v1.2.3"), Err(ContainerError::InvalidVersion { .. })));
+        assert_eq!(Version::parse_lenient("V1.2.3").unwrap().as_str(), "1.2.3");
+    }
+
+    #[test]
+    fn malformed_and_overflowing_versions_are_invalid() {
+        for input in ["", "1", "1.2", "1.2.3.4", "1..3", "1.2.x", "-1.2.3", "1.2.3-beta", "vv1.2.3", "4294967296.0.0"] {
+            assert!(matches!(Version::parse_lenient(input), Err(ContainerError::InvalidVersion { .. })), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn caret_and_tilde_reject_both_sides() {
+        assert_eq!(mismatch("^1.2", "1.1.0"), Some(VersionMismatch::TooOld));
+        assert_eq!(mismatch("^1.2", "2.0.0"), Some(VersionMismatch::TooNew));
+        assert_eq!(mismatch("~1.2", "1.3.0"), Some(VersionMismatch::TooNew));
+        assert_eq!(mismatch("~1.2", "1.2.7"), None);
+    }
+}
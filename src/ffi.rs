@@ -0,0 +1,206 @@
+//! C interface for tools that embed wrappy instead of running the CLI and parsing its text.
+//!
+//! Ownership rules, shared by every function here:
+//! - Strings passed in are borrowed, NUL-terminated UTF-8 and only read during the call.
+//! - Strings returned are allocated by wrappy and owned by the caller, who releases them
+//!   with `wrappy_string_free`, never with `free()`.
+//! - A failed call returns NULL or a negative value and stores the error for the calling
+//!   thread, where `wrappy_last_error_json` picks it up.
+//!
+//! Panics never cross the boundary; they are reported as an `E_PANIC` error.
+//! The header is `include/wrappy.h`, regenerated with `cbindgen --config cbindgen.toml --output include/wrappy.h`.
+
+use serde_json::json;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+
+use crate::features::bindings::BindingManager;
+use crate::features::container::{ContainerHandler, ContainerService};
+use crate::shared::error::{ContainerError, ContainerResult};
+
+/// `wrappy_validate` flag: host requirements, interpreters and broken symlinks fail validation.
+pub const WRAPPY_VALIDATE_STRICT: c_int = 1;
+
+/// `wrappy_enable_bindings` flag: create bindings the binding policy would block.
+pub const WRAPPY_ENABLE_OVERRIDE_POLICY: c_int = 1;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Validates the container directory at `path` and returns the validation report as JSON,
+/// the same document `wrappy container validate --output <file>` writes. An invalid
+/// container still yields a report (with `"valid": false`); NULL means the call itself failed.
+///
+/// The returned string must be released with `wrappy_string_free`.
+///
+/// # Safety
+/// `path` must be NULL or point to a NUL-terminated string that stays valid for the call.
+#[no_mangle]
+pub unsafe extern "C" fn wrappy_validate(path: *const c_char, flags: c_int) -> *mut c_char {
+    let path = path_argument(path);
+    let result = guard(move || {
+        let report = ContainerHandler::validation_report(&[path?], flags & WRAPPY_VALIDATE_STRICT != 0);
+        Ok(serde_json::to_string(&report)?)
+    });
+    match result {
+        Some(json) => into_c_string(json),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Enables every binding of the container directory at `path`, like `wrappy bindings enable`.
+/// Returns 0 on success and -1 on failure. Progress is printed to stdout as in the CLI.
+///
+/// # Safety
+/// `path` must be NULL or point to a NUL-terminated string that stays valid for the call.
+#[no_mangle]
+pub unsafe extern "C" fn wrappy_enable_bindings(path: *const c_char, flags: c_int) -> c_int {
+    let path = path_argument(path);
+    let result = guard(move || {
+        let container = ContainerService::load_from_directory(&path?)?;
+        let manager = BindingManager::new()?.override_policy(flags & WRAPPY_ENABLE_OVERRIDE_POLICY != 0);
+        manager.install_bindings(&container)
+    });
+    match result {
+        Some(_) => 0,
+        None => -1,
+    }
+}
+
+/// The last error of the calling thread as `{"code": "E_...", "message": "..."}`, or NULL when
+/// no call on this thread has failed yet. Codes are the stable ones of `ContainerError::code`.
+///
+/// The returned string must be released with `wrappy_string_free`.
+#[no_mangle]
+pub extern "C" fn wrappy_last_error_json() -> *mut c_char {
+    match LAST_ERROR.with(|last| last.borrow().clone()) {
+        Some(json) => into_c_string(json),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Releases a string returned by wrappy. Passing NULL does nothing.
+///
+/// # Safety
+/// `value` must be NULL or a pointer returned by a wrappy function that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn wrappy_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// Runs `operation`, recording its error or panic for `wrappy_last_error_json`.
+/// Nothing the operation touched is used after a panic, so asserting unwind safety is sound.
+fn guard<T>(operation: impl FnOnce() -> ContainerResult<T>) -> Option<T> {
+    let error = match panic::catch_unwind(AssertUnwindSafe(operation)) {
+        Ok(Ok(value)) => return Some(value),
+        Ok(Err(error)) => json!({ "code": error.code(), "message": error.to_string() }),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            json!({ "code": "E_PANIC", "message": message })
+        }
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(error.to_string()));
+    None
+}
+
+/// Reads a path argument; NULL and non-UTF-8 input become errors reported through `guard`.
+unsafe fn path_argument(path: *const c_char) -> ContainerResult<PathBuf> {
+    if path.is_null() {
+        return Err(ContainerError::InvalidPath {
+            path: PathBuf::new(),
+            reason: "path is NULL".to_string(),
+        });
+    }
+    let path = CStr::from_ptr(path);
+    path.to_str().map(PathBuf::from).map_err(|_| ContainerError::InvalidPath {
+        path: PathBuf::from(path.to_string_lossy().into_owned()),
+        reason: "not valid UTF-8".to_string(),
+    })
+}
+
+/// Hands a string to C; interior NUL bytes cannot occur in JSON output, but are escaped to be safe.
+fn into_c_string(value: String) -> *mut c_char {
+    CString::new(value.replace('\0', "\\u0000")).unwrap_or_default().into_raw()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::{fixture_container, TempHome};
+    use serde_json::Value;
+    use std::os::unix::ffi::OsStrExt;
+
+    /// Takes ownership of a returned string, as a C caller would with `wrappy_string_free`.
+    fn take(value: *mut c_char) -> Option<Value> {
+        if value.is_null() {
+            return None;
+        }
+        let json = unsafe { CStr::from_ptr(value) }.to_str().unwrap().to_string();
+        unsafe { wrappy_string_free(value) };
+        Some(serde_json::from_str(&json).unwrap())
+    }
+
+    fn last_error() -> Value {
+        take(wrappy_last_error_json()).expect("an error was recorded")
+    }
+
+    #[test]
+    fn a_null_path_is_an_error_not_a_crash() {
+        assert!(unsafe { wrappy_validate(std::ptr::null(), 0) }.is_null());
+        assert_eq!(last_error()["code"], "E_INVALID_PATH");
+        assert!(last_error()["message"].as_str().unwrap().contains("path is NULL"));
+
+        assert_eq!(unsafe { wrappy_enable_bindings(std::ptr::null(), 0) }, -1);
+        assert_eq!(last_error()["code"], "E_INVALID_PATH");
+    }
+
+    #[test]
+    fn a_path_that_is_not_utf8_is_refused() {
+        let path = CString::new(b"/tmp/caf\xe9".to_vec()).unwrap();
+
+        assert!(unsafe { wrappy_validate(path.as_ptr(), 0) }.is_null());
+        let error = last_error();
+        assert_eq!(error["code"], "E_INVALID_PATH");
+        assert!(error["message"].as_str().unwrap().contains("not valid UTF-8"));
+        assert_eq!(unsafe { wrappy_enable_bindings(path.as_ptr(), 0) }, -1);
+    }
+
+    #[test]
+    fn validate_returns_a_report_and_strict_turns_warnings_into_errors() {
+        let home = TempHome::enter();
+        let container = fixture_container(home.path(), "app", "1.0.0");
+        let path = CString::new(container.as_os_str().as_bytes()).unwrap();
+        let validate = |flags| take(unsafe { wrappy_validate(path.as_ptr(), flags) }).expect("a report");
+
+        let lenient = validate(0);
+        let strict = validate(WRAPPY_VALIDATE_STRICT);
+
+        assert_eq!(lenient["containers"][0]["valid"], true, "{}", lenient);
+        assert_eq!(lenient["containers"][0]["name"], "app");
+        assert!(lenient["containers"][0]["warnings"][0].as_str().unwrap().contains("permissions.json"));
+        // An invalid container is still a report, not a failed call
+        assert_eq!(strict["containers"][0]["valid"], false, "{}", strict);
+        assert_eq!(strict["containers"][0]["error_codes"], json!(["E_INVALID_STRUCTURE"]));
+    }
+
+    #[test]
+    fn a_panic_becomes_an_error_and_freeing_null_does_nothing() {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let result: Option<()> = guard(|| panic!("boom"));
+        panic::set_hook(previous);
+
+        assert!(result.is_none());
+        assert_eq!(last_error(), json!({ "code": "E_PANIC", "message": "boom" }));
+        unsafe { wrappy_string_free(std::ptr::null_mut()) };
+    }
+}
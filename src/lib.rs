@@ -1,4 +1,6 @@
 pub mod cli;
+#[cfg(feature = "wrappy-ffi")]
+pub mod ffi;
 pub mod features;
 pub mod shared;
 
@@ -1,9 +1,14 @@
 use std::process;
 use wrappy::cli::{Cli, CommandRouter};
+use wrappy::features::ManifestCache;
+use wrappy::shared::{NetworkPolicy, PrivilegeGuard};
 use clap::Parser;
 
 fn main() {
     let cli = Cli::parse();
+    ManifestCache::set_enabled(!cli.no_cache);
+    NetworkPolicy::configure(cli.offline);
+    PrivilegeGuard::set_allow_root(cli.allow_root);
     let exit_code = CommandRouter::execute(cli.command);
     process::exit(exit_code);
 }
@@ -0,0 +1,380 @@
+use std::io::{self, Read, Write};
+
+const BLOCK_SIZE: usize = 512;
+
+/// Kind of a ustar entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Directory,
+    Symlink { target: String },
+}
+
+/// Header fields of one archive entry. Callers decide how much of the host
+/// (mtimes, owners) leaks into the archive; zeroed fields make it reproducible.
+#[derive(Debug, Clone)]
+pub struct EntryHeader {
+    pub path: String,
+    pub kind: EntryKind,
+    pub mode: u32,
+    pub size: u64,
+    pub mtime: u64,
+    pub uid: u32,
+    pub gid: u32,
+    pub user_name: String,
+    pub group_name: String,
+}
+
+/// Minimal ustar (POSIX.1-1988) writer: regular files, directories and symlinks.
+/// Output depends only on the headers and data given, nothing else is added.
+pub struct TarWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> TarWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    /// Appends one entry. Files stream exactly `header.size` bytes from `data`;
+    /// other kinds ignore it.
+    pub fn append(&mut self, header: &EntryHeader, data: impl Read) -> io::Result<()> {
+        self.inner.write_all(&Self::header_block(header)?)?;
+        if header.kind != EntryKind::File {
+            return Ok(());
+        }
+
+        let copied = io::copy(&mut data.take(header.size), &mut self.inner)?;
+        if copied != header.size {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("'{}' changed while it was archived", header.path),
+            ));
+        }
+        let padding = (BLOCK_SIZE as u64 - header.size % BLOCK_SIZE as u64) % BLOCK_SIZE as u64;
+        self.inner.write_all(&vec![0; padding as usize])?;
+        Ok(())
+    }
+
+    /// Writes the two zero blocks that end an archive and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.write_all(&[0; BLOCK_SIZE * 2])?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn header_block(header: &EntryHeader) -> io::Result<[u8; BLOCK_SIZE]> {
+        let mut block = [0u8; BLOCK_SIZE];
+        let mut path = header.path.clone();
+        if header.kind == EntryKind::Directory && !path.ends_with('/') {
+            path.push('/');
+        }
+        let (prefix, name) = Self::split_path(&path)?;
+
+        Self::put_bytes(&mut block[0..100], name.as_bytes());
+        Self::put_octal(&mut block[100..108], u64::from(header.mode & 0o7777))?;
+        Self::put_octal(&mut block[108..116], u64::from(header.uid))?;
+        Self::put_octal(&mut block[116..124], u64::from(header.gid))?;
+        let size = if header.kind == EntryKind::File { header.size } else { 0 };
+        Self::put_octal(&mut block[124..136], size)?;
+        Self::put_octal(&mut block[136..148], header.mtime)?;
+        block[148..156].fill(b' ');
+        block[156] = match header.kind {
+            EntryKind::File => b'0',
+            EntryKind::Symlink { .. } => b'2',
+            EntryKind::Directory => b'5',
+        };
+        if let EntryKind::Symlink { target } = &header.kind {
+            if target.len() > 100 {
+                return Err(Self::too_long("symlink target", target));
+            }
+            Self::put_bytes(&mut block[157..257], target.as_bytes());
+        }
+        block[257..263].copy_from_slice(b"ustar\0");
+        block[263..265].copy_from_slice(b"00");
+        Self::put_bytes(&mut block[265..297], &header.user_name.as_bytes()[..header.user_name.len().min(31)]);
+        Self::put_bytes(&mut block[297..329], &header.group_name.as_bytes()[..header.group_name.len().min(31)]);
+        Self::put_bytes(&mut block[345..500], prefix.as_bytes());
+
+        let checksum: u32 = block.iter().map(|&byte| u32::from(byte)).sum();
+        Self::put_bytes(&mut block[148..156], format!("{:06o}\0 ", checksum).as_bytes());
+        Ok(block)
+    }
+
+    /// Splits a path into the ustar prefix (up to 155 bytes) and name (up to 100 bytes) at a `/`.
+    fn split_path(path: &str) -> io::Result<(&str, &str)> {
+        if path.len() <= 100 {
+            return Ok(("", path));
+        }
+        path.char_indices()
+            .filter(|&(index, c)| c == '/' && index <= 155 && path.len() - index - 1 <= 100 && index + 1 < path.len())
+            .map(|(index, _)| (&path[..index], &path[index + 1..]))
+            .next()
+            .ok_or_else(|| Self::too_long("path", path))
+    }
+
+    fn put_bytes(field: &mut [u8], value: &[u8]) {
+        field[..value.len()].copy_from_slice(value);
+    }
+
+    /// Zero-padded octal terminated by NUL, as ustar numeric fields are.
+    fn put_octal(field: &mut [u8], value: u64) -> io::Result<()> {
+        let digits = format!("{:0width$o}", value, width = field.len() - 1);
+        if digits.len() >= field.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("value {} does not fit a tar header", value)));
+        }
+        Self::put_bytes(field, digits.as_bytes());
+        Ok(())
+    }
+
+    fn too_long(what: &str, value: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} '{}' is too long for a ustar archive", what, value),
+        )
+    }
+}
+
+/// Reader for the archives `TarWriter` produces: ustar regular files, directories
+/// and symlinks. Other entry types (hard links, devices, GNU long names) are refused.
+pub struct TarReader<R: Read> {
+    inner: R,
+    /// Unread data of the current entry
+    remaining: u64,
+    /// Padding after the current entry's data
+    padding: u64,
+}
+
+impl<R: Read> TarReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            remaining: 0,
+            padding: 0,
+        }
+    }
+
+    /// Header of the next entry, skipping whatever of the previous one was not read;
+    /// `None` at the end of the archive.
+    pub fn next_entry(&mut self) -> io::Result<Option<EntryHeader>> {
+        io::copy(&mut (&mut self.inner).take(self.remaining + self.padding), &mut io::sink())?;
+        self.remaining = 0;
+        self.padding = 0;
+
+        let mut block = [0u8; BLOCK_SIZE];
+        self.inner.read_exact(&mut block)?;
+        if block.iter().all(|&byte| byte == 0) {
+            return Ok(None);
+        }
+
+        let header = Self::parse_header(&block)?;
+        if header.kind == EntryKind::File {
+            self.remaining = header.size;
+            self.padding = (BLOCK_SIZE as u64 - header.size % BLOCK_SIZE as u64) % BLOCK_SIZE as u64;
+        }
+        Ok(Some(header))
+    }
+
+    /// Data of the current entry; empty for anything but files.
+    pub fn data(&mut self) -> impl Read + '_ {
+        EntryData { reader: self }
+    }
+
+    fn parse_header(block: &[u8; BLOCK_SIZE]) -> io::Result<EntryHeader> {
+        let stored = Self::octal(&block[148..156])?;
+        let computed: u64 = block
+            .iter()
+            .enumerate()
+            .map(|(index, &byte)| if (148..156).contains(&index) { u64::from(b' ') } else { u64::from(byte) })
+            .sum();
+        if stored != computed {
+            return Err(Self::invalid("header checksum mismatch".to_string()));
+        }
+
+        let name = Self::text(&block[0..100])?;
+        let prefix = Self::text(&block[345..500])?;
+        let mut path = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+        let kind = match block[156] {
+            b'0' | 0 => EntryKind::File,
+            b'5' => EntryKind::Directory,
+            b'2' => EntryKind::Symlink {
+                target: Self::text(&block[157..257])?,
+            },
+            other => return Err(Self::invalid(format!("unsupported entry type '{}' for '{}'", other as char, path))),
+        };
+        if kind == EntryKind::Directory {
+            path = path.trim_end_matches('/').to_string();
+        }
+
+        Ok(EntryHeader {
+            path,
+            kind,
+            mode: Self::octal(&block[100..108])? as u32,
+            size: Self::octal(&block[124..136])?,
+            mtime: Self::octal(&block[136..148])?,
+            uid: Self::octal(&block[108..116])? as u32,
+            gid: Self::octal(&block[116..124])? as u32,
+            user_name: Self::text(&block[265..297])?,
+            group_name: Self::text(&block[297..329])?,
+        })
+    }
+
+    /// NUL- or space-terminated octal number.
+    fn octal(field: &[u8]) -> io::Result<u64> {
+        let digits: String = field
+            .iter()
+            .take_while(|&&byte| byte != 0 && byte != b' ')
+            .map(|&byte| byte as char)
+            .collect();
+        let digits = digits.trim_start();
+        if digits.is_empty() {
+            return Ok(0);
+        }
+        u64::from_str_radix(digits, 8).map_err(|_| Self::invalid(format!("invalid number '{}' in a tar header", digits)))
+    }
+
+    fn text(field: &[u8]) -> io::Result<String> {
+        let end = field.iter().position(|&byte| byte == 0).unwrap_or(field.len());
+        String::from_utf8(field[..end].to_vec()).map_err(|_| Self::invalid("path is not valid UTF-8".to_string()))
+    }
+
+    fn invalid(message: String) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, message)
+    }
+}
+
+struct EntryData<'a, R: Read> {
+    reader: &'a mut TarReader<R>,
+}
+
+impl<R: Read> Read for EntryData<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let limit = buf.len().min(self.reader.remaining.min(usize::MAX as u64) as usize);
+        if limit == 0 {
+            return Ok(0);
+        }
+        let read = self.reader.inner.read(&mut buf[..limit])?;
+        if read == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "archive ends inside an entry"));
+        }
+        self.reader.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn header(path: &str, kind: EntryKind, size: u64) -> EntryHeader {
+        EntryHeader {
+            path: path.to_string(),
+            kind,
+            mode: 0o755,
+            size,
+            mtime: 1_700_000_000,
+            uid: 1000,
+            gid: 100,
+            user_name: "user".to_string(),
+            group_name: "users".to_string(),
+        }
+    }
+
+    /// A directory, a file needing padding, a symlink and a path that only fits with the ustar prefix.
+    fn sample() -> (Vec<u8>, String) {
+        let long_path = format!("app/{}/{}", "d".repeat(120), "f".repeat(90));
+        let mut tar = TarWriter::new(Vec::new());
+        tar.append(&header("app", EntryKind::Directory, 0), io::empty()).unwrap();
+        tar.append(&header("app/run.sh", EntryKind::File, 11), &b"echo hello\n"[..]).unwrap();
+        let link = EntryKind::Symlink { target: "run.sh".to_string() };
+        tar.append(&header("app/link", link, 0), io::empty()).unwrap();
+        tar.append(&header(&long_path, EntryKind::File, 0), io::empty()).unwrap();
+        (tar.finish().unwrap(), long_path)
+    }
+
+    #[test]
+    fn what_the_writer_packs_the_reader_returns() {
+        let (archive, long_path) = sample();
+        assert_eq!(archive.len() % BLOCK_SIZE, 0);
+
+        let mut reader = TarReader::new(archive.as_slice());
+        let mut entries = Vec::new();
+        while let Some(header) = reader.next_entry().unwrap() {
+            let mut data = Vec::new();
+            reader.data().read_to_end(&mut data).unwrap();
+            entries.push((header, data));
+        }
+
+        let paths: Vec<&str> = entries.iter().map(|(header, _)| header.path.as_str()).collect();
+        assert_eq!(paths, ["app", "app/run.sh", "app/link", long_path.as_str()]);
+        assert_eq!(entries[1].1, b"echo hello\n");
+        assert_eq!(entries[2].0.kind, EntryKind::Symlink { target: "run.sh".to_string() });
+        let file = &entries[1].0;
+        assert_eq!((file.mode, file.mtime, file.uid, file.gid), (0o755, 1_700_000_000, 1000, 100));
+        assert_eq!((file.user_name.as_str(), file.group_name.as_str()), ("user", "users"));
+    }
+
+    #[test]
+    fn system_tar_reads_the_archive() {
+        let (archive, long_path) = sample();
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("sample.tar"), &archive).unwrap();
+
+        let output = Command::new("tar").arg("-xf").arg("sample.tar").current_dir(dir.path()).output().unwrap();
+
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(std::fs::read_to_string(dir.path().join("app/run.sh")).unwrap(), "echo hello\n");
+        assert_eq!(std::fs::read_link(dir.path().join("app/link")).unwrap(), std::path::Path::new("run.sh"));
+        assert!(dir.path().join(long_path).is_file());
+    }
+
+    #[test]
+    fn a_corrupted_header_is_refused() {
+        let (mut archive, _) = sample();
+        archive[BLOCK_SIZE + 10] ^= 0x01;
+
+        let mut reader = TarReader::new(archive.as_slice());
+        reader.next_entry().unwrap();
+        let error = reader.next_entry().unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("checksum"), "{}", error);
+    }
+
+    #[test]
+    fn paths_and_targets_that_do_not_fit_are_refused() {
+        let mut tar = TarWriter::new(Vec::new());
+
+        let path = tar.append(&header(&"x".repeat(101), EntryKind::File, 0), io::empty()).unwrap_err();
+        let link = EntryKind::Symlink { target: "t".repeat(101) };
+        let target = tar.append(&header("link", link, 0), io::empty()).unwrap_err();
+
+        assert!(path.to_string().contains("too long"), "{}", path);
+        assert!(target.to_string().contains("symlink target"), "{}", target);
+    }
+
+    #[test]
+    fn a_file_shorter_than_its_header_says_is_an_error() {
+        let mut tar = TarWriter::new(Vec::new());
+
+        let error = tar.append(&header("short", EntryKind::File, 20), &b"only ten b"[..]).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn hard_links_and_other_entry_types_are_refused() {
+        let (mut archive, _) = sample();
+        // Turn the first entry into a hard link and fix up its checksum
+        archive[156] = b'1';
+        archive[148..156].fill(b' ');
+        let checksum: u32 = archive[..BLOCK_SIZE].iter().map(|&byte| u32::from(byte)).sum();
+        archive[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+
+        let error = TarReader::new(archive.as_slice()).next_entry().unwrap_err();
+
+        assert!(error.to_string().contains("unsupported entry type '1'"), "{}", error);
+    }
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+
+use crate::shared::clock;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+
+/// One line of `audit.jsonl`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    pub event: String,
+    pub details: Value,
+}
+
+/// Appends `event` to the audit log, for actions a user did not ask for explicitly
+/// and may want to trace later.
+pub fn record(event: &str, details: Value) -> ContainerResult<()> {
+    let path = WrappyPaths::new()?.audit_log_file();
+    if let Some(parent) = path.parent() {
+        filesystem::create_state_dir(parent)?;
+    }
+
+    let entry = AuditEntry {
+        at: clock::now(),
+        event: event.to_string(),
+        details,
+    };
+    let mut line = serde_json::to_string(&entry)?;
+    line.push('\n');
+
+    let io_error = |e| ContainerError::IoError {
+        path: path.clone(),
+        source: e,
+    };
+    let mut file = filesystem::state_file_options()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(io_error)?;
+    file.write_all(line.as_bytes()).map_err(io_error)
+}
@@ -0,0 +1,85 @@
+use std::process::{Command, Stdio};
+
+/// Version of this wrappy build.
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Commit the build came from, embedded by `build.rs`; `None` outside a git checkout.
+pub fn git_commit() -> Option<&'static str> {
+    option_env!("WRAPPY_GIT_COMMIT")
+}
+
+/// Cargo features this binary was compiled with.
+pub fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "tui") {
+        features.push("tui");
+    }
+    if cfg!(feature = "wrappy-ffi") {
+        features.push("wrappy-ffi");
+    }
+    features
+}
+
+/// `wrappy 0.1.0 (abc123def456)`, or without the commit when it is unknown.
+pub fn version_line() -> String {
+    match git_commit() {
+        Some(commit) => format!("wrappy {} ({})", CRATE_VERSION, commit),
+        None => format!("wrappy {}", CRATE_VERSION),
+    }
+}
+
+/// First non-empty line `command --version` prints, on stdout or stderr.
+/// `None` when the command fails to start or prints nothing.
+pub fn tool_version(command: &str) -> Option<String> {
+    let output = Command::new(command)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    [output.stdout, output.stderr].into_iter().find_map(|stream| {
+        let text = String::from_utf8_lossy(&stream).into_owned();
+        text.lines().map(str::trim).find(|line| !line.is_empty()).map(str::to_string)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::write_executable;
+    use tempfile::TempDir;
+
+    #[test]
+    fn the_version_line_names_the_crate_version_and_the_commit_when_known() {
+        let line = version_line();
+
+        assert!(line.starts_with(&format!("wrappy {}", CRATE_VERSION)));
+        match git_commit() {
+            Some(commit) => assert!(line.ends_with(&format!("({})", commit))),
+            None => assert_eq!(line, format!("wrappy {}", CRATE_VERSION)),
+        }
+    }
+
+    #[test]
+    fn features_follow_the_compiled_cfg() {
+        let features = enabled_features();
+
+        assert_eq!(features.contains(&"tui"), cfg!(feature = "tui"));
+        assert_eq!(features.contains(&"wrappy-ffi"), cfg!(feature = "wrappy-ffi"));
+    }
+
+    #[test]
+    fn tool_versions_take_the_first_printed_line_from_either_stream() {
+        let dir = TempDir::new().unwrap();
+        let on_stdout = dir.path().join("on-stdout");
+        write_executable(&on_stdout, "#!/bin/sh\necho\necho '  tool 1.2.3  '\necho more\n");
+        let on_stderr = dir.path().join("on-stderr");
+        write_executable(&on_stderr, "#!/bin/sh\necho 'other 4.5' >&2\n");
+        let silent = dir.path().join("silent");
+        write_executable(&silent, "#!/bin/sh\n");
+
+        assert_eq!(tool_version(&on_stdout.to_string_lossy()).as_deref(), Some("tool 1.2.3"));
+        assert_eq!(tool_version(&on_stderr.to_string_lossy()).as_deref(), Some("other 4.5"));
+        assert_eq!(tool_version(&silent.to_string_lossy()), None);
+        assert_eq!(tool_version(&dir.path().join("missing").to_string_lossy()), None);
+    }
+}
@@ -0,0 +1,47 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::shared::error::{ContainerError, ContainerResult};
+
+/// Lets an embedder stop a long-running operation without killing the process.
+/// Operations check it between items (files, bindings, containers) and undo
+/// their partial work before returning `ContainerError::Cancelled`.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    /// Extra cancellation source such as the CLI's Ctrl-C flag
+    linked: Option<fn() -> bool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Token that also counts as cancelled whenever `source` returns true.
+    pub fn linked_to(source: fn() -> bool) -> Self {
+        Self {
+            cancelled: Arc::default(),
+            linked: Some(source),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst) || self.linked.is_some_and(|source| source())
+    }
+
+    /// Returns `Cancelled` for `operation` once the token has been cancelled.
+    pub fn check(&self, operation: &str) -> ContainerResult<()> {
+        if self.is_cancelled() {
+            Err(ContainerError::Cancelled {
+                operation: operation.to_string(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
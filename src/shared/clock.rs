@@ -0,0 +1,110 @@
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+
+static CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+
+/// Source of the current time. Everything that records a timestamp or compares one
+/// against "now" (retention, idle and stale-lock timeouts) asks `clock::now()`, so a
+/// `MockClock` can move time without sleeping. Durations of work in progress still
+/// use `Instant`, which must stay monotonic.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::from(self.now())
+    }
+}
+
+/// The host clock, used unless another one is installed.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that only moves when told to.
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now: Mutex::new(now) }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.now.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *now += by;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Installs `clock` for the whole process.
+pub fn set_clock(clock: Arc<dyn Clock>) {
+    *CLOCK.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(clock);
+}
+
+/// Goes back to the host clock.
+pub fn reset_clock() {
+    *CLOCK.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Current time of the installed clock.
+pub fn now() -> DateTime<Utc> {
+    match CLOCK.read().unwrap_or_else(|poisoned| poisoned.into_inner()).as_ref() {
+        Some(clock) => clock.now(),
+        None => Utc::now(),
+    }
+}
+
+/// `now()` as a `SystemTime`, for comparing with file timestamps.
+pub fn system_now() -> SystemTime {
+    match CLOCK.read().unwrap_or_else(|poisoned| poisoned.into_inner()).as_ref() {
+        Some(clock) => clock.system_time(),
+        None => SystemTime::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::TempHome;
+    use chrono::TimeZone;
+
+    #[test]
+    fn an_installed_mock_clock_drives_now_and_system_now_until_reset() {
+        let _home = TempHome::enter();
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let clock = Arc::new(MockClock::new(start));
+        set_clock(clock.clone());
+
+        let first = now();
+        clock.advance(Duration::days(90));
+        let later = (now(), system_now());
+        clock.set(start);
+        let set_back = now();
+        reset_clock();
+
+        assert_eq!(first, start);
+        assert_eq!(later.0, start + Duration::days(90));
+        assert_eq!(later.1, SystemTime::from(start + Duration::days(90)));
+        assert_eq!(set_back, start);
+        assert!(now() > start + Duration::days(365));
+    }
+}
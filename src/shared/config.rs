@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::path_sanity;
+use crate::shared::paths::WrappyPaths;
+
+const DEFAULT_STASH_MAX_AGE_DAYS: u32 = 30;
+
+/// User-level wrappy configuration stored in `~/.config/wrappy/config.json`.
+/// Every field is optional so a missing or partial file falls back to defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store_dir: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bin_dir: Option<PathBuf>,
+    /// Days a stashed container state is kept before it is cleaned up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stash_max_age_days: Option<u32>,
+    #[serde(default, skip_serializing_if = "NetworkConfig::is_default")]
+    pub network: NetworkConfig,
+    #[serde(default, skip_serializing_if = "StoreConfig::is_default")]
+    pub store: StoreConfig,
+    #[serde(default, skip_serializing_if = "BindingsPolicyConfig::is_default")]
+    pub bindings: BindingsPolicyConfig,
+    /// Named groups of containers whose bindings are enabled and disabled together
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Vec<String>>,
+    /// Top-level keys this version does not know, kept so saving never drops settings of newer versions
+    #[serde(flatten)]
+    pub unknown: BTreeMap<String, Value>,
+}
+
+/// Machine-wide binding policy; types and roots are kept as written and
+/// interpreted by the bindings feature, which reports bad values by key.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BindingsPolicyConfig {
+    /// Binding type used when the manifest does not specify one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_type: Option<String>,
+    /// Back up existing config and data targets even when the manifest does not ask for it
+    #[serde(default)]
+    pub always_backup: bool,
+    /// Binding types that may not be created
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forbid_types: Vec<String>,
+    /// Directories binding targets must live under (supports ~); empty allows any target
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_target_roots: Vec<String>,
+}
+
+impl BindingsPolicyConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StoreConfig {
+    /// Share identical files between containers through hard-linked objects
+    #[serde(default)]
+    pub dedup: bool,
+}
+
+impl StoreConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Forbids every operation that would open a connection
+    #[serde(default)]
+    pub offline: bool,
+}
+
+impl NetworkConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Config {
+    /// Loads the config file, returning `None` when wrappy has not been set up yet.
+    pub fn load(paths: &WrappyPaths) -> ContainerResult<Option<Self>> {
+        let config_path = paths.config_file();
+        if !config_path.exists() {
+            return Ok(None);
+        }
+
+        let content = filesystem::read_json_file(&config_path).map_err(|e| ContainerError::IoError {
+            path: config_path.clone(),
+            source: e,
+        })?;
+
+        let config = serde_json::from_str(&content).map_err(|e| ContainerError::InvalidConfig {
+            path: config_path,
+            reason: e.to_string(),
+        })?;
+
+        Ok(Some(config))
+    }
+
+    pub fn load_or_default(paths: &WrappyPaths) -> ContainerResult<Self> {
+        Ok(Self::load(paths)?.unwrap_or_default())
+    }
+
+    pub fn save(&self, paths: &WrappyPaths) -> ContainerResult<()> {
+        filesystem::create_state_dir(&paths.config_dir)?;
+
+        let content = serde_json::to_string_pretty(self)?;
+        filesystem::atomic_write(
+            &paths.config_file(),
+            content.as_bytes(),
+            Some(filesystem::STATE_FILE_MODE),
+        )
+    }
+
+    pub fn store_dir(&self, paths: &WrappyPaths) -> PathBuf {
+        self.store_dir
+            .clone()
+            .unwrap_or_else(|| paths.default_store_dir())
+    }
+
+    pub fn stash_max_age(&self) -> chrono::Duration {
+        chrono::Duration::days(self.stash_max_age_days.unwrap_or(DEFAULT_STASH_MAX_AGE_DAYS).into())
+    }
+
+    pub fn bin_dir(&self, paths: &WrappyPaths) -> PathBuf {
+        self.bin_dir.clone().unwrap_or_else(|| paths.bin_dir.clone())
+    }
+
+    /// Every known key with the value used when the file does not set it.
+    /// An empty object is a map with free-form keys, like `profiles`.
+    pub fn defaults(paths: &WrappyPaths) -> Value {
+        json!({
+            "store_dir": paths.default_store_dir(),
+            "bin_dir": paths.bin_dir,
+            "stash_max_age_days": DEFAULT_STASH_MAX_AGE_DAYS,
+            "network": { "offline": false },
+            "store": { "dedup": false },
+            "bindings": {
+                "default_type": null,
+                "always_backup": false,
+                "forbid_types": [],
+                "allowed_target_roots": [],
+            },
+            "profiles": {},
+        })
+    }
+
+    /// Values that parse but cannot work, described by key.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (key, path) in [("store_dir", &self.store_dir), ("bin_dir", &self.bin_dir)] {
+            if let Some(path) = path.as_ref().filter(|path| !path.is_absolute()) {
+                problems.push(format!("{}: '{}' must be an absolute path", key, path.display()));
+            }
+        }
+        if let Some(Err(error)) = self.bin_dir.as_deref().map(path_sanity::check_path_entry) {
+            problems.push(format!("bin_dir: {}", error));
+        }
+        problems
+    }
+}
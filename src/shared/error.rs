@@ -14,6 +14,9 @@ pub enum ContainerError {
     #[error("Script '{script}' not found in container '{container}'")]
     ScriptNotFound { container: String, script: String },
 
+    #[error("Script '{path}' is not executable")]
+    ScriptNotExecutable { path: PathBuf },
+
     #[error("Invalid manifest format: {0}")]
     InvalidManifest(String),
 
@@ -32,6 +35,9 @@ pub enum ContainerError {
     #[error("Invalid container version format: {version}")]
     InvalidVersion { version: String },
 
+    #[error("Invalid version '{version}': components must not have leading zeros, write it as '{normalized}'")]
+    VersionLeadingZeros { version: String, normalized: String },
+
     #[error("Version conflict: {conflict}")]
     VersionConflict { conflict: String },
 
@@ -41,8 +47,17 @@ pub enum ContainerError {
     #[error("Container '{name}' already exists")]
     ContainerExists { name: String },
 
-    #[error("Container '{name}' not found")]
-    ContainerNotFound { name: String },
+    #[error("Container '{name}' not found{}", match suggestions.is_empty() {
+        true => String::new(),
+        false => format!("; did you mean {}?", suggestions.join(", ")),
+    })]
+    ContainerNotFound { name: String, suggestions: Vec<String> },
+
+    #[error("Container name '{name}' is ambiguous, specify one of: {}", candidates.join(", "))]
+    AmbiguousContainer { name: String, candidates: Vec<String> },
+
+    #[error("Container '{name}' is required by: {}", dependents.join(", "))]
+    HasDependents { name: String, dependents: Vec<String> },
 
     #[error("IO error at path '{path}': {source}")]
     IoError {
@@ -65,10 +80,124 @@ pub enum ContainerError {
 
     #[error("Runtime error: {message}")]
     Runtime { message: String },
+
+    #[error("Script '{script}' needs interpreter '{command}' (#!{shebang}), which is not installed on this machine; install it or put it on PATH")]
+    InterpreterMissing {
+        script: String,
+        command: String,
+        shebang: String,
+    },
+
+    #[error("Host requirements not satisfied: {details}")]
+    HostRequirementsUnmet { details: String },
+
+    #[error("Invalid config file '{path}': {reason}")]
+    InvalidConfig { path: PathBuf, reason: String },
+
+    #[error("Cannot {operation}: wrappy is in offline mode")]
+    OfflineMode { operation: String },
+
+    #[error("'{}' was created by a newer wrappy (format version {version}, this wrappy reads up to {supported}); upgrade wrappy", path.display())]
+    StateFileTooNew { path: PathBuf, version: u32, supported: u32 },
+
+    #[error("Cancelled: {operation}")]
+    Cancelled { operation: String },
+
+    #[error("Binding blocked by policy 'bindings.{key}': {details}")]
+    PolicyViolation { key: String, details: String },
+
+    #[error("Container '{container}' needs values for: {}", variables.join(", "))]
+    MissingVariables { container: String, variables: Vec<String> },
+
+    #[error("Refusing to {operation} as root: {details}. Run wrappy as your normal user, or pass --allow-root if this setup belongs to root")]
+    RunningAsRoot { operation: String, details: String },
+
+    #[error("Profile '{name}' not found in the config")]
+    ProfileNotFound { name: String },
+
+    #[error("Conflicting bindings: {}", conflicts.join("; "))]
+    BindingConflict { conflicts: Vec<String> },
+
+    #[error("Script '{script}' declares mounts ({}), but this run is not sandboxed, so they would not apply; pass --allow-unsandboxed-mounts to run it without them", mounts.join(", "))]
+    UnsandboxedMounts { script: String, mounts: Vec<String> },
+
+    #[error("Stopped walking '{}': {limit}", path.display())]
+    TraversalLimit { path: PathBuf, limit: String },
+
+    #[error("Enabling bindings of '{container}' would fail, nothing was changed:{}", problems.iter().map(|problem| format!("\n  - {}", problem)).collect::<String>())]
+    PreflightFailed { container: String, problems: Vec<String> },
+
+    #[error("'{}' does not match the seal: {reason}", path.display())]
+    IntegrityMismatch { path: PathBuf, reason: String },
+
+    #[error("'{}' has no valid attestation: {reason}", path.display())]
+    AttestationRequired { path: PathBuf, reason: String },
 }
 
 pub type ContainerResult<T> = Result<T, ContainerError>;
 
+/// Exit code reported when wrappy itself was interrupted, following the shell's 128 + SIGINT.
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+impl ContainerError {
+    /// Stable identifier of the error kind for scripts, JSON output and CI annotations.
+    ///
+    /// Codes never change once released, even when a variant is renamed or reshaped,
+    /// so match on the code rather than the variant or the message.
+    pub fn code(&self) -> &'static str {
+        self.describe().0
+    }
+
+    /// Process exit status for a command that failed with this error.
+    pub fn exit_code(&self) -> i32 {
+        self.describe().1
+    }
+
+    /// The one table behind `code` and `exit_code`, so the two cannot drift apart.
+    fn describe(&self) -> (&'static str, i32) {
+        match self {
+            Self::InvalidStructure(_) => ("E_INVALID_STRUCTURE", 1),
+            Self::MissingDefaultScript => ("E_MISSING_DEFAULT_SCRIPT", 1),
+            Self::ScriptNotFound { .. } => ("E_SCRIPT_NOT_FOUND", 1),
+            Self::ScriptNotExecutable { .. } => ("E_SCRIPT_NOT_EXECUTABLE", 1),
+            Self::InvalidManifest(_) => ("E_INVALID_MANIFEST", 1),
+            Self::ManifestValidation(_) => ("E_MANIFEST_VALIDATION", 1),
+            Self::InvalidDependency { .. } => ("E_INVALID_DEPENDENCY", 1),
+            Self::PackageNotFound { .. } => ("E_PACKAGE_NOT_FOUND", 1),
+            Self::CircularDependency { .. } => ("E_CIRCULAR_DEPENDENCY", 1),
+            Self::InvalidVersion { .. } => ("E_INVALID_VERSION", 1),
+            Self::VersionLeadingZeros { .. } => ("E_VERSION_LEADING_ZEROS", 1),
+            Self::VersionConflict { .. } => ("E_VERSION_CONFLICT", 1),
+            Self::PermissionDenied { .. } => ("E_PERMISSION_DENIED", 1),
+            Self::ContainerExists { .. } => ("E_CONTAINER_EXISTS", 1),
+            Self::ContainerNotFound { .. } => ("E_CONTAINER_NOT_FOUND", 1),
+            Self::AmbiguousContainer { .. } => ("E_AMBIGUOUS_CONTAINER", 1),
+            Self::HasDependents { .. } => ("E_HAS_DEPENDENTS", 1),
+            Self::IoError { .. } => ("E_IO", 1),
+            Self::JsonError { .. } => ("E_JSON", 1),
+            Self::InvalidPath { .. } => ("E_INVALID_PATH", 1),
+            Self::UnsupportedType { .. } => ("E_UNSUPPORTED_TYPE", 1),
+            Self::Runtime { .. } => ("E_RUNTIME", 1),
+            Self::InterpreterMissing { .. } => ("E_INTERPRETER_MISSING", 1),
+            Self::HostRequirementsUnmet { .. } => ("E_HOST_REQUIREMENTS_UNMET", 1),
+            Self::InvalidConfig { .. } => ("E_INVALID_CONFIG", 1),
+            Self::OfflineMode { .. } => ("E_OFFLINE_MODE", 1),
+            Self::StateFileTooNew { .. } => ("E_STATE_FILE_TOO_NEW", 1),
+            Self::Cancelled { .. } => ("E_CANCELLED", INTERRUPTED_EXIT_CODE),
+            Self::PolicyViolation { .. } => ("E_POLICY_VIOLATION", 1),
+            Self::MissingVariables { .. } => ("E_MISSING_VARIABLES", 1),
+            Self::RunningAsRoot { .. } => ("E_RUNNING_AS_ROOT", 1),
+            Self::ProfileNotFound { .. } => ("E_PROFILE_NOT_FOUND", 1),
+            Self::BindingConflict { .. } => ("E_BINDING_CONFLICT", 1),
+            Self::UnsandboxedMounts { .. } => ("E_UNSANDBOXED_MOUNTS", 1),
+            Self::TraversalLimit { .. } => ("E_TRAVERSAL_LIMIT", 1),
+            Self::PreflightFailed { .. } => ("E_PREFLIGHT_FAILED", 1),
+            Self::IntegrityMismatch { .. } => ("E_INTEGRITY_MISMATCH", 1),
+            Self::AttestationRequired { .. } => ("E_ATTESTATION_REQUIRED", 1),
+        }
+    }
+}
+
 impl From<std::io::Error> for ContainerError {
     fn from(error: std::io::Error) -> Self {
         ContainerError::IoError {
@@ -83,3 +212,127 @@ impl From<serde_json::Error> for ContainerError {
         ContainerError::JsonError { source: error }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// One error of every kind. `kind` has no wildcard arm, so a new variant does not
+    /// compile until it gets a sample here and a line in the snapshot.
+    fn samples() -> Vec<ContainerError> {
+        let text = || "x".to_string();
+        let path = PathBuf::from;
+        vec![
+            ContainerError::InvalidStructure(text()),
+            ContainerError::MissingDefaultScript,
+            ContainerError::ScriptNotFound { container: text(), script: text() },
+            ContainerError::ScriptNotExecutable { path: path("x") },
+            ContainerError::InvalidManifest(text()),
+            ContainerError::ManifestValidation(text()),
+            ContainerError::InvalidDependency { package: text(), reason: text() },
+            ContainerError::PackageNotFound { package: text() },
+            ContainerError::CircularDependency { chain: text() },
+            ContainerError::InvalidVersion { version: text() },
+            ContainerError::VersionLeadingZeros { version: text(), normalized: text() },
+            ContainerError::VersionConflict { conflict: text() },
+            ContainerError::PermissionDenied { operation: text() },
+            ContainerError::ContainerExists { name: text() },
+            ContainerError::ContainerNotFound { name: text(), suggestions: Vec::new() },
+            ContainerError::AmbiguousContainer { name: text(), candidates: Vec::new() },
+            ContainerError::HasDependents { name: text(), dependents: Vec::new() },
+            ContainerError::IoError { path: path("x"), source: std::io::Error::other("x") },
+            ContainerError::JsonError { source: serde_json::from_str::<u8>("x").unwrap_err() },
+            ContainerError::InvalidPath { path: path("x"), reason: text() },
+            ContainerError::UnsupportedType { container_type: text() },
+            ContainerError::Runtime { message: text() },
+            ContainerError::InterpreterMissing { script: text(), command: text(), shebang: text() },
+            ContainerError::HostRequirementsUnmet { details: text() },
+            ContainerError::InvalidConfig { path: path("x"), reason: text() },
+            ContainerError::OfflineMode { operation: text() },
+            ContainerError::StateFileTooNew { path: path("x"), version: 2, supported: 1 },
+            ContainerError::Cancelled { operation: text() },
+            ContainerError::PolicyViolation { key: text(), details: text() },
+            ContainerError::MissingVariables { container: text(), variables: Vec::new() },
+            ContainerError::RunningAsRoot { operation: text(), details: text() },
+            ContainerError::ProfileNotFound { name: text() },
+            ContainerError::BindingConflict { conflicts: Vec::new() },
+            ContainerError::UnsandboxedMounts { script: text(), mounts: Vec::new() },
+            ContainerError::TraversalLimit { path: path("x"), limit: text() },
+            ContainerError::PreflightFailed { container: text(), problems: Vec::new() },
+            ContainerError::IntegrityMismatch { path: path("x"), reason: text() },
+            ContainerError::AttestationRequired { path: path("x"), reason: text() },
+        ]
+    }
+
+    fn kind(error: &ContainerError) -> usize {
+        match error {
+            ContainerError::InvalidStructure(_) => 0,
+            ContainerError::MissingDefaultScript => 1,
+            ContainerError::ScriptNotFound { .. } => 2,
+            ContainerError::ScriptNotExecutable { .. } => 3,
+            ContainerError::InvalidManifest(_) => 4,
+            ContainerError::ManifestValidation(_) => 5,
+            ContainerError::InvalidDependency { .. } => 6,
+            ContainerError::PackageNotFound { .. } => 7,
+            ContainerError::CircularDependency { .. } => 8,
+            ContainerError::InvalidVersion { .. } => 9,
+            ContainerError::VersionLeadingZeros { .. } => 10,
+            ContainerError::VersionConflict { .. } => 11,
+            ContainerError::PermissionDenied { .. } => 12,
+            ContainerError::ContainerExists { .. } => 13,
+            ContainerError::ContainerNotFound { .. } => 14,
+            ContainerError::AmbiguousContainer { .. } => 15,
+            ContainerError::HasDependents { .. } => 16,
+            ContainerError::IoError { .. } => 17,
+            ContainerError::JsonError { .. } => 18,
+            ContainerError::InvalidPath { .. } => 19,
+            ContainerError::UnsupportedType { .. } => 20,
+            ContainerError::Runtime { .. } => 21,
+            ContainerError::InterpreterMissing { .. } => 22,
+            ContainerError::HostRequirementsUnmet { .. } => 23,
+            ContainerError::InvalidConfig { .. } => 24,
+            ContainerError::OfflineMode { .. } => 25,
+            ContainerError::StateFileTooNew { .. } => 26,
+            ContainerError::Cancelled { .. } => 27,
+            ContainerError::PolicyViolation { .. } => 28,
+            ContainerError::MissingVariables { .. } => 29,
+            ContainerError::RunningAsRoot { .. } => 30,
+            ContainerError::ProfileNotFound { .. } => 31,
+            ContainerError::BindingConflict { .. } => 32,
+            ContainerError::UnsandboxedMounts { .. } => 33,
+            ContainerError::TraversalLimit { .. } => 34,
+            ContainerError::PreflightFailed { .. } => 35,
+            ContainerError::IntegrityMismatch { .. } => 36,
+            ContainerError::AttestationRequired { .. } => 37,
+        }
+    }
+
+    #[test]
+    fn every_variant_has_a_sample() {
+        let kinds: Vec<usize> = samples().iter().map(kind).collect();
+        assert_eq!(kinds, (0..kinds.len()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn every_variant_has_its_own_well_formed_code() {
+        let mut seen = HashSet::new();
+        for error in samples() {
+            let code = error.code();
+            assert!(seen.insert(code), "{} is used by more than one variant", code);
+            assert!(code.starts_with("E_"), "{}", code);
+            assert!(code[2..].chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'), "{}", code);
+        }
+    }
+
+    #[test]
+    fn codes_and_exit_codes_match_the_snapshot() {
+        let listed: String = samples()
+            .iter()
+            .map(|error| format!("{} {}\n", error.code(), error.exit_code()))
+            .collect();
+
+        // Codes are a stable interface: change the snapshot only to add new ones
+        assert_eq!(listed, include_str!("../../tests/fixtures/error_codes.txt"));
+    }
+}
@@ -0,0 +1,424 @@
+use std::ffi::CString;
+use std::fs::{self, DirBuilder, File, OpenOptions, Permissions};
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::traversal::Walk;
+
+const STALE_RETRY_ATTEMPTS: u32 = 4;
+const STALE_RETRY_BASE_DELAY_MS: u64 = 50;
+
+/// Wrappy's own state (config, history, caches, service logs) records paths,
+/// origins and command lines, so it is private to the user. The umask still
+/// applies on top and can only narrow these further.
+pub const STATE_DIR_MODE: u32 = 0o700;
+pub const STATE_FILE_MODE: u32 = 0o600;
+
+/// Distinguishes temp files of concurrent writers within one process
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Fails before any partial work when `path` lives on a read-only mount.
+/// Mutating operations call this up front so NFS or remounted stores produce
+/// one clear error naming the mount instead of an EROFS halfway through.
+pub fn ensure_writable(path: &Path, operation: &str) -> ContainerResult<()> {
+    if is_read_only(path)? {
+        let mount = mount_point(path);
+        return Err(ContainerError::PermissionDenied {
+            operation: format!(
+                "{} (filesystem mounted at '{}' is read-only)",
+                operation,
+                mount.display()
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Reports whether the filesystem holding `path` is mounted read-only.
+/// Paths that do not exist yet are checked through their nearest existing ancestor.
+pub fn is_read_only(path: &Path) -> ContainerResult<bool> {
+    let existing = nearest_existing_ancestor(path);
+    statvfs_read_only(&existing).map_err(|e| ContainerError::IoError {
+        path: existing,
+        source: e,
+    })
+}
+
+/// Finds the mount point containing `path` by walking up until the device changes.
+pub fn mount_point(path: &Path) -> PathBuf {
+    let existing = nearest_existing_ancestor(path);
+    let device = match fs::metadata(&existing) {
+        Ok(metadata) => metadata.dev(),
+        Err(_) => return existing,
+    };
+
+    let mut mount = existing.clone();
+    for ancestor in existing.ancestors().skip(1) {
+        match fs::metadata(ancestor) {
+            Ok(metadata) if metadata.dev() == device => mount = ancestor.to_path_buf(),
+            _ => break,
+        }
+    }
+    mount
+}
+
+/// Replaces `path` atomically: content goes to a temp file in the same directory,
+/// gets its final mode while still private, and is renamed over the target.
+/// Readers such as a shell executing a wrapper see either the old or the new file, never a partial one.
+pub fn atomic_write(path: &Path, content: &[u8], mode: Option<u32>) -> ContainerResult<()> {
+    let directory = path.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp_path = directory.join(format!(
+        ".{}.wrappy-tmp-{}-{}",
+        file_name,
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let write_result = (|| -> io::Result<()> {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(content)?;
+        if let Some(mode) = mode {
+            file.set_permissions(Permissions::from_mode(mode))?;
+        }
+        file.sync_all()?;
+        // Executing a file that is still open for writing fails with ETXTBSY
+        drop(file);
+        fs::rename(&temp_path, path)
+    })();
+
+    write_result.map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        ContainerError::IoError {
+            path: path.to_path_buf(),
+            source: e,
+        }
+    })
+}
+
+/// Moves the directory `from` to `to`, whose parent must exist. Across filesystems the
+/// tree is copied, links as they are and modes kept, and the original removed afterwards.
+pub fn move_dir(from: &Path, to: &Path) -> ContainerResult<()> {
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source: io::Error| ContainerError::IoError { path, source }
+    };
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {}
+        result => return result.map_err(io_error(to)),
+    }
+
+    // Directory modes are set last, so a read-only directory still gets its children
+    let mut directories = Vec::new();
+    for entry in Walk::new(from) {
+        let entry = entry?;
+        let target = to.join(entry.path.strip_prefix(from).unwrap_or(&entry.path));
+        if entry.file_type.is_symlink() {
+            let link = fs::read_link(&entry.path).map_err(io_error(&entry.path))?;
+            std::os::unix::fs::symlink(&link, &target).map_err(io_error(&target))?;
+        } else if entry.file_type.is_dir() {
+            fs::create_dir(&target).map_err(io_error(&target))?;
+            directories.push((fs::metadata(&entry.path).map_err(io_error(&entry.path))?.permissions(), target));
+        } else {
+            fs::copy(&entry.path, &target).map_err(io_error(&target))?;
+        }
+    }
+    for (permissions, directory) in directories.into_iter().rev() {
+        fs::set_permissions(&directory, permissions).map_err(io_error(&directory))?;
+    }
+    fs::remove_dir_all(from).map_err(io_error(from))
+}
+
+/// Creates a state directory and its missing parents with `STATE_DIR_MODE`.
+/// Directories that already exist keep their mode; `restrict_permissions` fixes those.
+pub fn create_state_dir(path: &Path) -> ContainerResult<()> {
+    DirBuilder::new()
+        .recursive(true)
+        .mode(STATE_DIR_MODE)
+        .create(path)
+        .map_err(|e| ContainerError::IoError {
+            path: path.to_path_buf(),
+            source: e,
+        })
+}
+
+/// Open options for state files written in place (appends, lock files);
+/// a file created through them starts with `STATE_FILE_MODE`.
+pub fn state_file_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    options.mode(STATE_FILE_MODE);
+    options
+}
+
+/// Removes group and other access from `path` itself.
+/// Returns whether the mode changed; missing paths and symlinks are left alone.
+pub fn restrict_permissions(path: &Path) -> ContainerResult<bool> {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return Ok(false);
+    };
+    let mode = metadata.permissions().mode() & 0o7777;
+    if metadata.file_type().is_symlink() || mode & 0o077 == 0 {
+        return Ok(false);
+    }
+
+    fs::set_permissions(path, Permissions::from_mode(mode & !0o077)).map_err(|e| {
+        ContainerError::IoError {
+            path: path.to_path_buf(),
+            source: e,
+        }
+    })?;
+    Ok(true)
+}
+
+/// Applies `restrict_permissions` to `path` and everything below it, returning the changed paths.
+pub fn restrict_permissions_recursive(path: &Path) -> ContainerResult<Vec<PathBuf>> {
+    let mut changed = Vec::new();
+    if fs::symlink_metadata(path).is_err() {
+        return Ok(changed);
+    }
+    for entry in Walk::new(path) {
+        let entry = entry?;
+        if restrict_permissions(&entry.path)? {
+            changed.push(entry.path);
+        }
+    }
+    Ok(changed)
+}
+
+/// Total size in bytes of regular files under `path`; symlinks are counted but not followed.
+pub fn directory_size(path: &Path) -> ContainerResult<u64> {
+    let mut total = 0;
+    for entry in Walk::new(path) {
+        let entry = entry?;
+        if entry.file_type.is_dir() {
+            continue;
+        }
+        let metadata = fs::symlink_metadata(&entry.path).map_err(|e| ContainerError::IoError {
+            path: entry.path.clone(),
+            source: e,
+        })?;
+        total += metadata.len();
+    }
+    Ok(total)
+}
+
+/// Paths under `root` (itself included) not owned by `uid`; links are not followed.
+/// Unreadable parts, and whatever lies past the traversal limits, are left out.
+pub fn not_owned_by(root: &Path, uid: u32) -> Vec<PathBuf> {
+    Walk::new(root)
+        .flatten()
+        .filter(|entry| fs::symlink_metadata(&entry.path).is_ok_and(|metadata| metadata.uid() != uid))
+        .map(|entry| entry.path)
+        .collect()
+}
+
+/// Symlinks under `root` that point nowhere, either missing or part of a cycle.
+/// Links are never followed, so a cycle cannot make the walk loop.
+pub fn broken_symlinks(root: &Path) -> ContainerResult<Vec<PathBuf>> {
+    let mut broken = Vec::new();
+    for entry in Walk::new(root).follow_root_link() {
+        let entry = entry?;
+        if entry.depth > 0 && entry.file_type.is_symlink() && fs::metadata(&entry.path).is_err() {
+            broken.push(entry.path);
+        }
+    }
+    broken.sort();
+    Ok(broken)
+}
+
+/// Symlinks under `root` whose target lies outside it, each with that target.
+/// Exports refuse them, while copies and installs keep them as they are.
+pub fn escaping_symlinks(root: &Path) -> ContainerResult<Vec<(PathBuf, PathBuf)>> {
+    let mut escaping = Vec::new();
+    for entry in Walk::new(root).follow_root_link() {
+        let entry = entry?;
+        if entry.depth == 0 || !entry.file_type.is_symlink() {
+            continue;
+        }
+        let target = fs::read_link(&entry.path).map_err(|e| ContainerError::IoError {
+            path: entry.path.clone(),
+            source: e,
+        })?;
+        let relative = entry.path.strip_prefix(root).unwrap_or(&entry.path);
+        if symlink_leaves_root(relative, &target) {
+            escaping.push((entry.path, target));
+        }
+    }
+    escaping.sort();
+    Ok(escaping)
+}
+
+/// Whether a symlink at `link`, relative to the root of a tree, with `target` points
+/// outside that tree once resolved against the link's directory. Absolute targets
+/// always do. Only the path is looked at, so it applies to links not on disk yet.
+pub fn symlink_leaves_root(link: &Path, target: &Path) -> bool {
+    let mut depth = link.parent().map_or(0, |parent| parent.components().count());
+    for component in target.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return true,
+        }
+    }
+    target.as_os_str().is_empty()
+}
+
+/// Formats a byte count for humans (`12.3 MB`).
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Byte order mark Windows editors put at the start of UTF-8 files.
+pub const UTF8_BOM: char = '\u{feff}';
+
+/// Reads a JSON file as text without a leading UTF-8 BOM, which serde_json rejects
+/// as "expected value at line 1 column 1". Use it for every file people edit by hand.
+pub fn read_json_file(path: &Path) -> io::Result<String> {
+    let content = fs::read_to_string(path)?;
+    Ok(match content.strip_prefix(UTF8_BOM) {
+        Some(stripped) => stripped.to_string(),
+        None => content,
+    })
+}
+
+/// Retries operations failing with ESTALE, which network filesystems return
+/// transiently after the server replaced a file we had a handle to.
+pub fn retry_on_stale<T, F>(mut operation: F) -> io::Result<T>
+where
+    F: FnMut() -> io::Result<T>,
+{
+    let mut attempt = 0;
+    loop {
+        match operation() {
+            Err(e) if is_stale_handle(&e) && attempt + 1 < STALE_RETRY_ATTEMPTS => {
+                let delay = STALE_RETRY_BASE_DELAY_MS * 2u64.pow(attempt);
+                thread::sleep(Duration::from_millis(delay));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_stale_handle(error: &io::Error) -> bool {
+    error.raw_os_error() == Some(libc::ESTALE)
+}
+
+/// Nearest ancestor of `path` (itself included) that exists; `.` when none does.
+pub fn nearest_existing_ancestor(path: &Path) -> PathBuf {
+    path.ancestors()
+        .find(|ancestor| !ancestor.as_os_str().is_empty() && ancestor.exists())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn statvfs_read_only(path: &Path) -> io::Result<bool> {
+    Ok(statvfs(path)?.f_flag & libc::ST_RDONLY != 0)
+}
+
+/// Filesystem statistics of the filesystem holding `path`.
+pub fn statvfs(path: &Path) -> io::Result<libc::statvfs> {
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contains a NUL byte"))?;
+
+    // SAFETY: statvfs only writes into the zero-initialized struct we own and
+    // reads the NUL-terminated path that outlives the call.
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::statvfs(c_path.as_ptr(), &mut stats) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn move_dir_moves_the_tree_with_its_links() {
+        let dir = TempDir::new().unwrap();
+        let from = dir.path().join("from");
+        fs::create_dir_all(from.join("nested")).unwrap();
+        fs::write(from.join("nested/file"), "content").unwrap();
+        std::os::unix::fs::symlink("nested/file", from.join("link")).unwrap();
+        let to = dir.path().join("to");
+
+        move_dir(&from, &to).unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(to.join("nested/file")).unwrap(), "content");
+        assert_eq!(fs::read_link(to.join("link")).unwrap(), Path::new("nested/file"));
+    }
+
+    #[test]
+    fn atomic_write_replaces_the_file_with_the_given_mode() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, "old").unwrap();
+
+        atomic_write(&path, b"new", Some(0o640)).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o640);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn read_json_file_strips_only_a_leading_bom() {
+        let dir = TempDir::new().unwrap();
+        let leading = dir.path().join("leading.json");
+        fs::write(&leading, "\u{feff}{\"name\": \"web\"}\r\n").unwrap();
+        let inner = dir.path().join("inner.json");
+        fs::write(&inner, "{\"name\": \"\u{feff}web\"}").unwrap();
+
+        let content = read_json_file(&leading).unwrap();
+
+        assert_eq!(content, "{\"name\": \"web\"}\r\n");
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&content).unwrap()["name"], "web");
+        assert_eq!(read_json_file(&inner).unwrap(), "{\"name\": \"\u{feff}web\"}");
+    }
+
+    #[test]
+    fn symlinks_leave_the_root_only_when_resolved_above_it() {
+        let cases = [
+            ("bin/tool", "../content/tool", false),
+            ("bin/tool", "tool.real", false),
+            ("a/b/link", "../../c", false),
+            ("a/b/link", "./x/../../y", false),
+            ("link", "../outside", true),
+            ("bin/tool", "../../outside", true),
+            ("a/link", "x/../../..", true),
+            ("bin/tool", "/usr/bin/tool", true),
+            ("link", "", true),
+        ];
+
+        for (link, target, leaves) in cases {
+            assert_eq!(symlink_leaves_root(Path::new(link), Path::new(target)), leaves, "{} -> {}", link, target);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+/// Shell-style glob match supporting `*` (any run of characters) and `?` (one character).
+pub fn glob_matches(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+
+    let (mut p, mut v) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, v));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == value[v] => {
+                p += 1;
+                v += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character and retry
+                Some((star, matched)) => {
+                    p = star + 1;
+                    v = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal_patterns_match_only_themselves() {
+        assert!(glob_matches("DISPLAY", "DISPLAY"));
+        assert!(!glob_matches("DISPLAY", "WAYLAND_DISPLAY"));
+        assert!(!glob_matches("DISPLAY", "DISPLAY2"));
+        assert!(!glob_matches("", "X"));
+        assert!(glob_matches("", ""));
+    }
+
+    #[test]
+    fn star_matches_any_run_including_an_empty_one() {
+        assert!(glob_matches("LC_*", "LC_ALL"));
+        assert!(glob_matches("LC_*", "LC_"));
+        assert!(!glob_matches("LC_*", "LANG"));
+        assert!(glob_matches("*_DIR", "XDG_RUNTIME_DIR"));
+        assert!(glob_matches("X*_*_DIR", "XDG_RUNTIME_DIR"));
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("**", "anything"));
+        // Needs backtracking: the first `_` is not the one `_DIR` matches
+        assert!(glob_matches("*_DIR", "A_B_DIR"));
+        assert!(!glob_matches("*_DIR", "A_DIRX"));
+    }
+
+    #[test]
+    fn question_mark_matches_exactly_one_character() {
+        assert!(glob_matches("LC_?", "LC_X"));
+        assert!(!glob_matches("LC_?", "LC_"));
+        assert!(!glob_matches("LC_?", "LC_XY"));
+        assert!(glob_matches("?*", "A"));
+        assert!(!glob_matches("?*", ""));
+    }
+}
@@ -0,0 +1,52 @@
+use std::env;
+use std::ffi::CStr;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// Looks up a command on the host PATH the same way a shell would.
+/// Used to report optional tool availability and host-side requirements.
+pub fn find_executable(command: &str) -> Option<PathBuf> {
+    if command.contains('/') {
+        let path = PathBuf::from(command);
+        return is_executable(&path).then_some(path);
+    }
+
+    let search_path = env::var_os("PATH")?;
+    env::split_paths(&search_path)
+        .map(|dir| dir.join(command))
+        .find(|candidate| is_executable(candidate))
+}
+
+pub fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Checks whether `dir` is one of the PATH entries, so wrappers placed there are reachable.
+pub fn path_contains(dir: &Path) -> bool {
+    env::var_os("PATH")
+        .map(|search_path| env::split_paths(&search_path).any(|entry| entry == dir))
+        .unwrap_or(false)
+}
+
+/// Start time of a process in clock ticks since boot (field 22 of /proc/<pid>/stat),
+/// or `None` when the process does not exist.
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name may contain spaces, so fields are counted after its closing parenthesis
+    let after_name = &stat[stat.rfind(')')? + 1..];
+    after_name.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// Name of this machine, or `unknown` when it cannot be read.
+pub fn hostname() -> String {
+    let mut buffer = [0 as libc::c_char; 256];
+    // SAFETY: the buffer outlives the call and its length is passed along
+    match unsafe { libc::gethostname(buffer.as_mut_ptr(), buffer.len() - 1) } {
+        // SAFETY: the last byte is never written, so the name is NUL-terminated
+        0 => unsafe { CStr::from_ptr(buffer.as_ptr()) }.to_string_lossy().into_owned(),
+        _ => "unknown".to_string(),
+    }
+}
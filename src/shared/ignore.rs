@@ -0,0 +1,278 @@
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path};
+
+use crate::shared::error::{ContainerError, ContainerResult};
+
+/// File at the container root listing paths to leave out, in gitignore syntax.
+pub const IGNORE_FILE: &str = ".wrappyignore";
+
+/// Rules every container starts with; `.wrappyignore` can re-include them with `!`.
+pub const DEFAULT_IGNORE_RULES: [&str; 4] = [".git/", "*.swp", "runtime.json", "snapshots/"];
+
+/// One line of an ignore file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreRule {
+    /// The line as written, for showing which rule excluded a path
+    pub source: String,
+    pub negated: bool,
+    /// Written with a trailing `/`, so it only matches directories
+    pub directory_only: bool,
+    /// Path segments; unanchored rules start with `**`
+    segments: Vec<String>,
+}
+
+impl IgnoreRule {
+    /// Parses one line; blank lines and `#` comments have no rule.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end_matches(['\r', '\n']).trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (directory_only, pattern) = match pattern.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        // A slash anywhere but at the end ties the pattern to the container root
+        let anchored = pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+        if pattern.is_empty() {
+            return None;
+        }
+
+        let mut segments: Vec<String> = Vec::new();
+        if !anchored {
+            segments.push("**".to_string());
+        }
+        segments.extend(pattern.split('/').filter(|segment| !segment.is_empty()).map(str::to_string));
+        Some(Self {
+            source: line.to_string(),
+            negated,
+            directory_only,
+            segments,
+        })
+    }
+
+    /// Whether the rule matches `path` itself, ignoring what it says about the parents.
+    pub fn matches(&self, path: &[&str], is_dir: bool) -> bool {
+        (is_dir || !self.directory_only) && Self::match_segments(&self.segments, path)
+    }
+
+    fn match_segments(pattern: &[String], path: &[&str]) -> bool {
+        match pattern.split_first() {
+            None => path.is_empty(),
+            // A trailing `**` matches everything inside, but not the directory itself
+            Some((first, rest)) if first == "**" && rest.is_empty() => !path.is_empty(),
+            Some((first, rest)) if first == "**" => (0..=path.len()).any(|skip| Self::match_segments(rest, &path[skip..])),
+            Some((first, rest)) => match path.split_first() {
+                Some((name, path_rest)) => {
+                    let pattern: Vec<char> = first.chars().collect();
+                    let name: Vec<char> = name.chars().collect();
+                    segment_matches(&pattern, &name) && Self::match_segments(rest, path_rest)
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+impl fmt::Display for IgnoreRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// The ignore rules of one container: the defaults, then its `.wrappyignore`.
+/// Export, seal, verify, dedup and `container files` all ask this one matcher,
+/// so they agree on what belongs to a container.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    /// Parses gitignore-style text. Later lines win over earlier ones.
+    pub fn parse(text: &str) -> Self {
+        Self {
+            rules: text.lines().filter_map(IgnoreRule::parse).collect(),
+        }
+    }
+
+    pub fn defaults() -> Self {
+        Self::parse(&DEFAULT_IGNORE_RULES.join("\n"))
+    }
+
+    /// Defaults followed by the container's `.wrappyignore`, when it has one.
+    pub fn for_container(root: &Path) -> ContainerResult<Self> {
+        let mut rules = Self::defaults();
+        let path = root.join(IGNORE_FILE);
+        match fs::read_to_string(&path) {
+            Ok(text) => rules.rules.extend(Self::parse(&text).rules),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(ContainerError::IoError { path, source: e }),
+        }
+        Ok(rules)
+    }
+
+    pub fn rules(&self) -> &[IgnoreRule] {
+        &self.rules
+    }
+
+    /// Whether `relative` is left out. As in git, nothing inside an ignored directory
+    /// can be included again, so the parents are checked first.
+    pub fn is_ignored(&self, relative: &Path, is_dir: bool) -> bool {
+        self.excluded_by(relative, is_dir).is_some()
+    }
+
+    /// The rule that leaves `relative` out, either directly or through a parent.
+    pub fn excluded_by(&self, relative: &Path, is_dir: bool) -> Option<&IgnoreRule> {
+        let segments: Vec<&str> = relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => name.to_str(),
+                _ => None,
+            })
+            .collect();
+        (1..=segments.len()).find_map(|end| {
+            let entry_is_dir = end < segments.len() || is_dir;
+            self.decisive_rule(&segments[..end], entry_is_dir).filter(|rule| !rule.negated)
+        })
+    }
+
+    /// Last rule matching the path itself, which decides whether it is ignored.
+    fn decisive_rule(&self, path: &[&str], is_dir: bool) -> Option<&IgnoreRule> {
+        self.rules.iter().rev().find(|rule| rule.matches(path, is_dir))
+    }
+}
+
+/// Matches one path segment against a pattern with `*`, `?`, `[...]` classes and `\` escapes.
+fn segment_matches(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => {
+            let rest = &pattern[pattern.iter().take_while(|&&c| c == '*').count()..];
+            (0..=name.len()).any(|skip| segment_matches(rest, &name[skip..]))
+        }
+        Some('?') => !name.is_empty() && segment_matches(&pattern[1..], &name[1..]),
+        Some('[') => match (parse_class(pattern), name.first()) {
+            (Some((matches, length)), Some(&c)) => matches(c) && segment_matches(&pattern[length..], &name[1..]),
+            (Some(_), None) => false,
+            // An unterminated `[` is a literal
+            (None, first) => first == Some(&'[') && segment_matches(&pattern[1..], &name[1..]),
+        },
+        Some('\\') if pattern.len() > 1 => name.first() == Some(&pattern[1]) && segment_matches(&pattern[2..], &name[1..]),
+        Some(c) => name.first() == Some(c) && segment_matches(&pattern[1..], &name[1..]),
+    }
+}
+
+/// Parses a `[...]` class at the start of `pattern` into a matcher and the number of chars it spans.
+fn parse_class(pattern: &[char]) -> Option<(impl Fn(char) -> bool, usize)> {
+    let mut index = 1;
+    let negated = matches!(pattern.get(index), Some('!') | Some('^'));
+    if negated {
+        index += 1;
+    }
+
+    let mut ranges = Vec::new();
+    let start = index;
+    loop {
+        let c = *pattern.get(index)?;
+        // `]` right after the opening bracket is a member, not the end
+        if c == ']' && index > start {
+            break;
+        }
+        let low = match c {
+            '\\' => {
+                index += 1;
+                *pattern.get(index)?
+            }
+            c => c,
+        };
+        match (pattern.get(index + 1), pattern.get(index + 2)) {
+            (Some('-'), Some(&high)) if high != ']' => {
+                ranges.push((low, high));
+                index += 3;
+            }
+            _ => {
+                ranges.push((low, low));
+                index += 1;
+            }
+        }
+    }
+
+    let matches = move |c: char| ranges.iter().any(|&(low, high)| low <= c && c <= high) != negated;
+    Some((matches, index + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    type Case = (&'static str, &'static str, bool, bool);
+
+    #[test]
+    fn rules_match_like_gitignore() {
+        // (rules, path, is_dir, ignored)
+        let cases: [Case; 22] = [
+            ("*.log", "build.log", false, true),
+            ("*.log", "logs/deep/build.log", false, true),
+            ("*.log", "build.log.txt", false, false),
+            ("/build", "build", true, true),
+            ("/build", "src/build", true, false),
+            ("docs/*.md", "docs/a.md", false, true),
+            ("docs/*.md", "docs/sub/a.md", false, false),
+            ("docs/**/*.md", "docs/sub/deep/a.md", false, true),
+            ("docs/**", "docs", true, false),
+            ("docs/**", "docs/a", false, true),
+            ("cache/", "cache", true, true),
+            ("cache/", "cache", false, false),
+            ("cache/", "cache/inside/file", false, true),
+            ("file?.txt", "file1.txt", false, true),
+            ("file?.txt", "file10.txt", false, false),
+            ("[a-c]*.bin", "beta.bin", false, true),
+            ("[!a-c]*.bin", "beta.bin", false, false),
+            ("\\#not-a-comment", "#not-a-comment", false, true),
+            ("# a comment", "# a comment", false, false),
+            ("*.log\n!keep.log", "keep.log", false, false),
+            ("*.log\n!keep.log\nkeep.log", "keep.log", false, true),
+            // Nothing inside an ignored directory can be included again
+            ("out/\n!out/keep", "out/keep", false, true),
+        ];
+
+        for (rules, path, is_dir, ignored) in cases {
+            assert_eq!(IgnoreRules::parse(rules).is_ignored(Path::new(path), is_dir), ignored, "{:?} on {}", rules, path);
+        }
+    }
+
+    #[test]
+    fn excluded_by_names_the_rule_that_decided() {
+        let rules = IgnoreRules::parse("*.tmp\n/vendor/\n!important.tmp");
+
+        assert_eq!(rules.excluded_by(Path::new("a/b.tmp"), false).map(|rule| rule.source.as_str()), Some("*.tmp"));
+        assert_eq!(rules.excluded_by(Path::new("vendor/lib/x.rs"), false).map(|rule| rule.source.as_str()), Some("/vendor/"));
+        assert!(rules.excluded_by(Path::new("important.tmp"), false).is_none());
+        assert_eq!(IgnoreRules::parse("\n# only comments\n  \n/\n").rules().len(), 0);
+    }
+
+    #[test]
+    fn containers_start_from_the_defaults_and_may_reinclude_them() {
+        let dir = TempDir::new().unwrap();
+        let defaults = IgnoreRules::for_container(dir.path()).unwrap();
+        fs::write(dir.path().join(IGNORE_FILE), "!snapshots/\nbig/\n").unwrap();
+        let custom = IgnoreRules::for_container(dir.path()).unwrap();
+
+        for path in [".git/config", "a/.notes.swp", "runtime.json", "snapshots/1"] {
+            assert!(defaults.is_ignored(Path::new(path), false), "{}", path);
+        }
+        assert!(!custom.is_ignored(Path::new("snapshots/1"), false));
+        assert!(custom.is_ignored(Path::new("big/file"), false));
+        assert!(custom.is_ignored(Path::new(".git/config"), false));
+        assert_eq!(custom.rules().len(), DEFAULT_IGNORE_RULES.len() + 2);
+    }
+}
@@ -0,0 +1,339 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use crate::shared::audit;
+use crate::shared::clock;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::host;
+
+/// A lock file without a readable holder older than this is taken over. Holders write
+/// themselves right after creating the file, so only a crash in between leaves one.
+const STALE_UNKNOWN_LOCK_AFTER: Duration = Duration::from_secs(30);
+const LOCK_WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Process that holds a lock, written into the lock file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockHolder {
+    pub pid: u32,
+    /// Start time of `pid` as `host::process_start_time` reported it, so a reused pid
+    /// is not mistaken for the holder
+    pub process_start_time: Option<u64>,
+    pub hostname: String,
+    pub acquired_at: DateTime<Utc>,
+}
+
+impl LockHolder {
+    fn current() -> Self {
+        let pid = std::process::id();
+        Self {
+            pid,
+            process_start_time: host::process_start_time(pid),
+            hostname: host::hostname(),
+            acquired_at: clock::now(),
+        }
+    }
+
+    /// Whether the holder is known to be gone: it ran on this machine and its pid is
+    /// not running or now belongs to another process. Holders on other machines, which
+    /// share the lock over a network filesystem, are never considered gone.
+    pub fn is_dead(&self) -> bool {
+        if self.hostname != host::hostname() {
+            return false;
+        }
+        match (host::process_start_time(self.pid), self.process_start_time) {
+            (None, _) => true,
+            (Some(started), Some(recorded)) => started != recorded,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for LockHolder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "pid {} on {} since {}",
+            self.pid,
+            self.hostname,
+            self.acquired_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S")
+        )
+    }
+}
+
+/// Exclusive lock held by creating a file next to the state it guards; dropping
+/// it removes the file. Read-modify-write cycles of shared state files take it so
+/// two wrappy processes cannot lose each other's changes. The file names its
+/// holder, so a lock left behind by a killed process is broken instead of
+/// blocking every later command.
+pub struct LockFile {
+    path: PathBuf,
+    _file: File,
+}
+
+impl LockFile {
+    /// Takes the lock at `path`, waiting for another holder to release it.
+    /// `what` names the guarded state in messages.
+    pub fn acquire(path: &Path, what: &str) -> ContainerResult<Self> {
+        let mut waited = Duration::ZERO;
+        loop {
+            if let Some(lock) = Self::try_acquire(path, what)? {
+                return Ok(lock);
+            }
+            if waited >= LOCK_WAIT_TIMEOUT {
+                return Err(Self::timeout_error(path, what));
+            }
+            thread::sleep(LOCK_POLL_INTERVAL);
+            waited += LOCK_POLL_INTERVAL;
+        }
+    }
+
+    /// Takes the lock if it is free or its holder is gone; `None` while a live holder has it.
+    pub fn try_acquire(path: &Path, what: &str) -> ContainerResult<Option<Self>> {
+        if let Some(parent) = path.parent() {
+            filesystem::create_state_dir(parent)?;
+        }
+
+        match filesystem::state_file_options().write(true).create_new(true).open(path) {
+            Ok(mut file) => {
+                let holder = serde_json::to_vec(&LockHolder::current())?;
+                file.write_all(&holder).map_err(|e| Self::io_error(path, e))?;
+                return Ok(Some(Self {
+                    path: path.to_path_buf(),
+                    _file: file,
+                }));
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(Self::io_error(path, e)),
+        }
+
+        let Ok(content) = fs::read(path) else {
+            // Released between the attempt and the read; the next attempt gets it
+            return Ok(None);
+        };
+        let stale = match serde_json::from_slice::<LockHolder>(&content) {
+            Ok(holder) if holder.is_dead() => Some(Some(holder)),
+            Ok(_) => None,
+            Err(_) => {
+                let age = fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .and_then(|modified| clock::system_now().duration_since(modified).ok());
+                age.is_some_and(|age| age > STALE_UNKNOWN_LOCK_AFTER).then_some(None)
+            }
+        };
+        if let Some(holder) = stale {
+            if Self::remove_if_unchanged(path, &content) {
+                Self::report_broken(path, what, holder.as_ref());
+            }
+        }
+        Ok(None)
+    }
+
+    /// Removes the lock at `path` if it still holds `content`, the stale lock that was read.
+    /// The file is first renamed to a name of its own, so a lock another process took since
+    /// the read is never deleted: it is compared after the move and put back when it differs.
+    fn remove_if_unchanged(path: &Path, content: &[u8]) -> bool {
+        let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let random = Uuid::new_v4().simple().to_string();
+        let aside = path.with_file_name(format!(".{}.stale-{}-{}", file_name, process::id(), &random[..12]));
+        if fs::rename(path, &aside).is_err() {
+            // Released or broken by someone else meanwhile
+            return false;
+        }
+        if fs::read(&aside).is_ok_and(|moved| moved == content) {
+            let _ = fs::remove_file(&aside);
+            return true;
+        }
+        // Linking instead of renaming back cannot replace a lock taken after the move
+        if fs::hard_link(&aside, path).is_err() {
+            eprintln!("⚠️  Could not put back the lock {} taken while breaking a stale one", path.display());
+        }
+        let _ = fs::remove_file(&aside);
+        false
+    }
+
+    /// Who holds the lock at `path`, if it is held and names its holder.
+    pub fn holder(path: &Path) -> Option<LockHolder> {
+        serde_json::from_slice(&fs::read(path).ok()?).ok()
+    }
+
+    fn report_broken(path: &Path, what: &str, holder: Option<&LockHolder>) {
+        let reason = match holder {
+            Some(holder) => format!("held by {}, which is no longer running", holder),
+            None => format!("names no holder and is older than {}s", STALE_UNKNOWN_LOCK_AFTER.as_secs()),
+        };
+        eprintln!("🔓 Broke the stale lock on {} ({}): {}", what, path.display(), reason);
+        let details = serde_json::json!({
+            "lock": path,
+            "what": what,
+            "holder": holder,
+            "reason": reason,
+        });
+        if let Err(error) = audit::record("lock_broken", details) {
+            eprintln!("⚠️  Could not record the broken lock in the audit log: {}", error);
+        }
+    }
+
+    fn timeout_error(path: &Path, what: &str) -> ContainerError {
+        let message = match Self::holder(path) {
+            Some(holder) if holder.hostname != host::hostname() => format!(
+                "timed out waiting for the lock on {}: held by {}, another machine, so it is never broken automatically; \
+                 remove {} once that machine no longer uses it",
+                what,
+                holder,
+                path.display()
+            ),
+            Some(holder) => format!("timed out waiting for the lock on {}: held by {}, which is still running", what, holder),
+            None => format!(
+                "timed out waiting for the lock on {} ({}); remove it if no wrappy is running",
+                what,
+                path.display()
+            ),
+        };
+        ContainerError::Runtime { message }
+    }
+
+    fn io_error(path: &Path, source: io::Error) -> ContainerError {
+        ContainerError::IoError {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::clock::MockClock;
+    use crate::shared::test_support::TempHome;
+    use std::sync::Arc;
+
+    #[test]
+    fn a_lock_without_a_holder_is_only_broken_once_it_is_older_than_the_timeout() {
+        let home = TempHome::enter();
+        let path = home.path().join("state.lock");
+        fs::write(&path, "").unwrap();
+        let clock = Arc::new(MockClock::new(clock::now()));
+        clock::set_clock(clock.clone());
+
+        let young = LockFile::try_acquire(&path, "the test state").unwrap();
+        let survived = path.exists();
+        clock.advance(chrono::Duration::seconds(STALE_UNKNOWN_LOCK_AFTER.as_secs() as i64 + 1));
+        let breaking = LockFile::try_acquire(&path, "the test state").unwrap();
+        let broken = !path.exists();
+        let taken = LockFile::try_acquire(&path, "the test state").unwrap();
+        clock::reset_clock();
+
+        assert!(young.is_none() && survived);
+        assert!(breaking.is_none() && broken);
+        assert!(taken.is_some());
+        assert_eq!(LockFile::holder(&path).unwrap().pid, std::process::id());
+    }
+
+    /// Pid of a process that ran on this machine and has exited.
+    fn exited_pid() -> u32 {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        pid
+    }
+
+    fn holder(pid: u32, process_start_time: Option<u64>, hostname: &str) -> LockHolder {
+        LockHolder {
+            pid,
+            process_start_time,
+            hostname: hostname.to_string(),
+            acquired_at: clock::now(),
+        }
+    }
+
+    #[test]
+    fn only_holders_known_to_be_gone_from_this_machine_are_dead() {
+        let me = LockHolder::current();
+        let started = me.process_start_time.unwrap();
+        let local = host::hostname();
+
+        assert!(!me.is_dead());
+        assert!(!holder(me.pid, None, &local).is_dead(), "a running pid without a start time is trusted");
+        assert!(holder(me.pid, Some(started + 1), &local).is_dead(), "the pid was reused by another process");
+        assert!(holder(exited_pid(), Some(started), &local).is_dead());
+        assert!(!holder(exited_pid(), Some(started), "another-machine").is_dead());
+    }
+
+    #[test]
+    fn a_lock_of_an_exited_process_is_broken_audited_and_taken() {
+        let home = TempHome::enter();
+        let path = home.path().join("state.lock");
+        let dead = holder(exited_pid(), Some(1), &host::hostname());
+        fs::write(&path, serde_json::to_vec(&dead).unwrap()).unwrap();
+
+        let lock = LockFile::acquire(&path, "the test state").unwrap();
+
+        assert_eq!(LockFile::holder(&path).unwrap().pid, std::process::id());
+        let audit = fs::read_to_string(home.paths().audit_log_file()).unwrap();
+        let entry: serde_json::Value = serde_json::from_str(audit.lines().last().unwrap()).unwrap();
+        assert_eq!(entry["event"], "lock_broken");
+        assert_eq!(entry["details"]["holder"]["pid"], dead.pid);
+        drop(lock);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn a_lock_of_a_live_holder_is_left_alone() {
+        let home = TempHome::enter();
+        let path = home.path().join("state.lock");
+        let held = LockFile::try_acquire(&path, "the test state").unwrap().unwrap();
+
+        let second = LockFile::try_acquire(&path, "the test state").unwrap();
+
+        assert!(second.is_none());
+        assert!(path.exists());
+        assert!(!home.paths().audit_log_file().exists());
+        drop(held);
+    }
+
+    fn leftovers(dir: &Path) -> Vec<String> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.contains(".stale-"))
+            .collect()
+    }
+
+    #[test]
+    fn a_lock_taken_between_the_read_and_the_removal_of_a_stale_one_is_kept() {
+        let home = TempHome::enter();
+        let path = home.path().join("state.lock");
+        let stale = serde_json::to_vec(&holder(exited_pid(), Some(1), &host::hostname())).unwrap();
+        let fresh = serde_json::to_vec(&LockHolder::current()).unwrap();
+
+        // The stale lock was read, then released and taken by another process before the removal
+        fs::write(&path, &fresh).unwrap();
+        let removed = LockFile::remove_if_unchanged(&path, &stale);
+
+        assert!(!removed);
+        assert_eq!(fs::read(&path).unwrap(), fresh);
+        assert!(leftovers(home.path()).is_empty());
+
+        fs::write(&path, &stale).unwrap();
+        assert!(LockFile::remove_if_unchanged(&path, &stale));
+        assert!(!path.exists());
+        assert!(leftovers(home.path()).is_empty());
+        assert!(!LockFile::remove_if_unchanged(&path, &stale), "nothing left to remove");
+    }
+}
@@ -1,3 +1,39 @@
+pub mod archive;
+pub mod audit;
+pub mod buildinfo;
+pub mod cancellation;
+pub mod clock;
+pub mod config;
+pub mod digest;
 pub mod error;
+pub mod filesystem;
+pub mod glob;
+pub mod host;
+pub mod ignore;
+pub mod lock_file;
+pub mod network;
+pub mod path_list;
+pub mod path_sanity;
+pub mod paths;
+pub mod portability;
+pub mod privilege;
+pub mod prompt;
+pub mod redact;
+pub mod shell_quote;
+pub mod staging;
+pub mod summary;
+pub mod template;
+#[cfg(test)]
+pub mod test_support;
+pub mod traversal;
+pub mod watcher;
 
+pub use cancellation::CancellationToken;
+pub use clock::{Clock, MockClock, SystemClock};
+pub use config::Config;
 pub use error::*;
+pub use network::NetworkPolicy;
+pub use paths::WrappyPaths;
+pub use privilege::PrivilegeGuard;
+pub use redact::Redact;
+pub use staging::StagingArea;
@@ -0,0 +1,116 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::shared::config::Config;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::paths::WrappyPaths;
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Process-wide switch every network-using code path must pass through.
+/// Checked before a connection is attempted, so offline mode never depends
+/// on a timeout or a DNS failure to take effect.
+pub struct NetworkPolicy;
+
+impl NetworkPolicy {
+    /// Enables offline mode from the `--offline` flag or `network.offline` in the config.
+    /// An unreadable config cannot switch offline mode off, only the flag can switch it on.
+    pub fn configure(offline_flag: bool) {
+        let from_config = WrappyPaths::new()
+            .ok()
+            .and_then(|paths| Config::load_or_default(&paths).ok())
+            .is_some_and(|config| config.network.offline);
+        Self::set_offline(offline_flag || from_config);
+    }
+
+    pub fn set_offline(offline: bool) {
+        OFFLINE.store(offline, Ordering::Relaxed);
+    }
+
+    pub fn is_offline() -> bool {
+        OFFLINE.load(Ordering::Relaxed)
+    }
+
+    /// Fails fast with `OfflineMode` when `operation` would need the network.
+    pub fn ensure_online(operation: &str) -> ContainerResult<()> {
+        if Self::is_offline() {
+            return Err(ContainerError::OfflineMode {
+                operation: operation.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::TempHome;
+    use std::cell::Cell;
+
+    /// Stands in for an HTTP client: counts the connections it was asked to open.
+    struct MockClient {
+        connections: Cell<usize>,
+    }
+
+    impl MockClient {
+        fn fetch(&self, operation: &str) -> ContainerResult<()> {
+            NetworkPolicy::ensure_online(operation)?;
+            self.connections.set(self.connections.get() + 1);
+            Ok(())
+        }
+    }
+
+    fn configure_with(home: &TempHome, config_offline: bool, flag: bool) -> bool {
+        let paths = home.paths();
+        let mut config = Config::load_or_default(&paths).unwrap();
+        config.network.offline = config_offline;
+        config.save(&paths).unwrap();
+        NetworkPolicy::configure(flag);
+        let offline = NetworkPolicy::is_offline();
+        NetworkPolicy::set_offline(false);
+        offline
+    }
+
+    #[test]
+    fn offline_mode_fails_before_any_connection_is_opened() {
+        let _home = TempHome::enter();
+        let client = MockClient { connections: Cell::new(0) };
+
+        NetworkPolicy::set_offline(true);
+        let result = client.fetch("update the repository index");
+        NetworkPolicy::set_offline(false);
+
+        let error = result.unwrap_err();
+        assert_eq!(error.code(), "E_OFFLINE_MODE");
+        assert_eq!(error.to_string(), "Cannot update the repository index: wrappy is in offline mode");
+        assert_eq!(client.connections.get(), 0);
+
+        client.fetch("update the repository index").unwrap();
+        assert_eq!(client.connections.get(), 1);
+    }
+
+    #[test]
+    fn the_flag_or_the_config_switches_offline_mode_on() {
+        let home = TempHome::enter();
+
+        assert!(!configure_with(&home, false, false));
+        assert!(configure_with(&home, false, true));
+        assert!(configure_with(&home, true, false));
+        assert!(configure_with(&home, true, true));
+    }
+
+    #[test]
+    fn an_unreadable_config_leaves_the_flag_in_charge() {
+        let home = TempHome::enter();
+        let config_file = home.paths().config_file();
+        std::fs::create_dir_all(config_file.parent().unwrap()).unwrap();
+        std::fs::write(&config_file, "{ broken").unwrap();
+
+        NetworkPolicy::configure(true);
+        let offline = NetworkPolicy::is_offline();
+        NetworkPolicy::configure(false);
+        let online = !NetworkPolicy::is_offline();
+
+        assert!(offline && online);
+    }
+}
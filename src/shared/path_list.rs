@@ -0,0 +1,58 @@
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Read};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+/// Path argument that stands for "read the paths from stdin".
+pub const STDIN_MARKER: &str = "-";
+
+/// Replaces a `-` argument with the paths read from stdin, newline-delimited or, with
+/// `null`, NUL-delimited. Blank entries are dropped and the result is de-duplicated by
+/// canonical path, keeping the first spelling. Returns the paths and whether stdin was read.
+pub fn expand_stdin(paths: Vec<PathBuf>, null: bool) -> io::Result<(Vec<PathBuf>, bool)> {
+    if !paths.iter().any(|path| path.as_os_str() == STDIN_MARKER) {
+        return Ok((paths, false));
+    }
+
+    let mut input = Vec::new();
+    io::stdin().lock().read_to_end(&mut input)?;
+    let from_stdin = parse(&input, null);
+    let expanded = paths
+        .into_iter()
+        .flat_map(|path| match path.as_os_str() == STDIN_MARKER {
+            true => from_stdin.clone(),
+            false => vec![path],
+        })
+        .collect();
+    Ok((dedup(expanded), true))
+}
+
+/// Splits `input` into paths. Newline-delimited input also accepts CRLF and surrounding
+/// blanks, which never end a real path in a file list; NUL-delimited input is taken as is.
+pub fn parse(input: &[u8], null: bool) -> Vec<PathBuf> {
+    let separator = if null { b'\0' } else { b'\n' };
+    input
+        .split(|byte| *byte == separator)
+        .map(|entry| match null {
+            true => entry,
+            false => entry.trim_ascii(),
+        })
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| PathBuf::from(OsStr::from_bytes(entry)))
+        .collect()
+}
+
+/// Drops later entries naming the same path; missing paths are compared as written.
+pub fn dedup(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|path| seen.insert(canonical(path)))
+        .collect()
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.components().collect())
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::shell_quote::has_control_characters;
+
+/// Something in a container path that breaks a consumer embedding it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathIssue {
+    /// A newline or other control character splits every line-based format wrappy writes
+    ControlCharacter,
+    /// JSON state files and the registry only hold UTF-8 paths
+    NotUtf8,
+    /// `:` separates PATH entries, so the path cannot be put on PATH
+    Colon,
+}
+
+impl PathIssue {
+    /// Whether `--allow-weird-paths` can let a path with this issue through.
+    pub fn is_allowable(self) -> bool {
+        self == Self::Colon
+    }
+}
+
+impl fmt::Display for PathIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ControlCharacter => write!(f, "contains a control character such as a newline"),
+            Self::NotUtf8 => write!(f, "is not valid UTF-8, which wrappy's state files cannot record"),
+            Self::Colon => write!(f, "contains ':', which separates PATH entries"),
+        }
+    }
+}
+
+/// Every issue of `path`, in the order of `PathIssue`.
+pub fn path_issues(path: &Path) -> Vec<PathIssue> {
+    let mut issues = Vec::new();
+    let Some(text) = path.to_str() else {
+        let lossy = path.to_string_lossy();
+        if has_control_characters(&lossy) {
+            issues.push(PathIssue::ControlCharacter);
+        }
+        issues.push(PathIssue::NotUtf8);
+        return issues;
+    };
+    if has_control_characters(text) {
+        issues.push(PathIssue::ControlCharacter);
+    }
+    if text.contains(':') {
+        issues.push(PathIssue::Colon);
+    }
+    issues
+}
+
+/// Checks a path a container is installed or adopted at. Issues no escaping can fix
+/// are refused; the others are refused unless `allow_weird` is set and then returned
+/// so the caller can warn about them.
+pub fn check_container_path(path: &Path, allow_weird: bool) -> ContainerResult<Vec<PathIssue>> {
+    let issues = path_issues(path);
+    if let Some(issue) = issues.iter().find(|issue| !issue.is_allowable() || !allow_weird) {
+        let hint = match issue.is_allowable() {
+            true => "; pass --allow-weird-paths to use it anyway",
+            false => "",
+        };
+        return Err(ContainerError::InvalidPath {
+            path: path.to_path_buf(),
+            reason: format!("{}{}", issue, hint),
+        });
+    }
+    Ok(issues)
+}
+
+/// Refuses a directory that is about to be put on PATH and would not survive it.
+pub fn check_path_entry(dir: &Path) -> ContainerResult<()> {
+    match path_issues(dir).into_iter().next() {
+        Some(issue) => Err(ContainerError::InvalidPath {
+            path: dir.to_path_buf(),
+            reason: format!("cannot be put on PATH: it {}", issue),
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    #[test]
+    fn issues_are_found_in_the_order_they_are_declared() {
+        let cases: [(&Path, &[PathIssue]); 5] = [
+            (Path::new("/opt/my \"odd\" 'app' $dir"), &[]),
+            (Path::new("/opt/app:1"), &[PathIssue::Colon]),
+            (Path::new("/opt/app\n:1"), &[PathIssue::ControlCharacter, PathIssue::Colon]),
+            (Path::new(OsStr::from_bytes(b"/opt/app\xff")), &[PathIssue::NotUtf8]),
+            (Path::new(OsStr::from_bytes(b"/opt/\napp\xff")), &[PathIssue::ControlCharacter, PathIssue::NotUtf8]),
+        ];
+
+        for (path, expected) in cases {
+            assert_eq!(path_issues(path), expected, "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn only_a_colon_is_let_through_and_only_when_allowed() {
+        let colon = Path::new("/opt/app:1");
+
+        assert!(check_container_path(colon, false).is_err_and(|e| e.to_string().contains("--allow-weird-paths")));
+        assert_eq!(check_container_path(colon, true).unwrap(), [PathIssue::Colon]);
+        assert!(check_container_path(Path::new("/opt/app\n1"), true).is_err());
+        assert!(check_container_path(Path::new(OsStr::from_bytes(b"/opt/app\xff")), true).is_err());
+        assert!(check_container_path(Path::new("/opt/my app"), false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn no_issue_at_all_may_go_on_path() {
+        assert!(check_path_entry(Path::new("/opt/my 'app'/bin")).is_ok());
+        assert!(check_path_entry(Path::new("/opt/app:1/bin")).is_err());
+        assert!(check_path_entry(Path::new("/opt/app\t/bin")).is_err());
+    }
+}
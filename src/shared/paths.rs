@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use crate::shared::error::{ContainerError, ContainerResult};
+
+/// Single source for every location wrappy reads or writes, so commands agree
+/// on where the config, store and state live instead of rebuilding paths ad hoc.
+#[derive(Debug, Clone)]
+pub struct WrappyPaths {
+    pub home: PathBuf,
+    pub config_dir: PathBuf,
+    pub data_dir: PathBuf,
+    pub cache_dir: PathBuf,
+    pub bin_dir: PathBuf,
+}
+
+impl WrappyPaths {
+    /// Resolves standard locations from the current user's home directory.
+    pub fn new() -> ContainerResult<Self> {
+        let home = dirs::home_dir().ok_or_else(|| ContainerError::InvalidPath {
+            path: PathBuf::from("~"),
+            reason: "Could not determine home directory".to_string(),
+        })?;
+        Ok(Self::from_home(home))
+    }
+
+    pub fn from_home(home: PathBuf) -> Self {
+        Self {
+            config_dir: home.join(".config/wrappy"),
+            data_dir: home.join(".local/share/wrappy"),
+            cache_dir: home.join(".cache/wrappy"),
+            bin_dir: home.join(".local/bin"),
+            home,
+        }
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.config_dir.join("config.json")
+    }
+
+    /// Append-only record of every container run.
+    pub fn history_file(&self) -> PathBuf {
+        self.data_dir.join("history.jsonl")
+    }
+
+    /// Append-only record of actions wrappy took on its own, such as breaking a stale lock.
+    pub fn audit_log_file(&self) -> PathBuf {
+        self.data_dir.join("audit.jsonl")
+    }
+
+    /// Versioned record of the bindings wrappy created on the host.
+    pub fn binding_state_file(&self) -> PathBuf {
+        self.data_dir.join("bindings.json")
+    }
+
+    /// Where persistent data of removed containers is kept unless the user purges it.
+    pub fn orphaned_data_dir(&self, container_name: &str) -> PathBuf {
+        self.data_dir.join("orphaned-data").join(container_name)
+    }
+
+    /// Runtime state (pid file, activity stamp, log) of lazily started container services.
+    pub fn services_run_dir(&self) -> PathBuf {
+        self.data_dir.join("run")
+    }
+
+    /// State of removed containers kept for a later reinstall, one directory per name.
+    pub fn stash_dir(&self) -> PathBuf {
+        self.data_dir.join("stash")
+    }
+
+    /// Mutable state of containers kept in a git working tree, one directory per checkout.
+    pub fn container_state_dir(&self) -> PathBuf {
+        self.data_dir.join("container-state")
+    }
+
+    /// Values of manifest variables chosen at install time, one JSON file per container name.
+    pub fn variables_dir(&self) -> PathBuf {
+        self.data_dir.join("variables")
+    }
+
+    /// Deduplicated file contents shared by containers through hard links.
+    pub fn objects_dir(&self) -> PathBuf {
+        self.data_dir.join("objects")
+    }
+
+    /// Containers registered by path, such as adopted directories outside the store.
+    pub fn registry_file(&self) -> PathBuf {
+        self.data_dir.join("registry.json")
+    }
+
+    /// Missing setup components the last setup hint named, so it is not repeated.
+    pub fn setup_hint_file(&self) -> PathBuf {
+        self.cache_dir.join("setup-hint")
+    }
+
+    /// Scratch directories of installs, restores and exports in progress.
+    pub fn staging_dir(&self) -> PathBuf {
+        self.cache_dir.join("staging")
+    }
+
+    /// Private Ed25519 key `container export --attest` signs attestations with.
+    pub fn attestation_key_file(&self) -> PathBuf {
+        self.config_dir.join("keys/attestation")
+    }
+
+    /// Public half of the attestation key, trusted when verifying.
+    pub fn attestation_public_key_file(&self) -> PathBuf {
+        self.config_dir.join("keys/attestation.pub")
+    }
+
+    /// Public keys of other machines whose attestations are trusted.
+    pub fn trusted_signers_file(&self) -> PathBuf {
+        self.config_dir.join("keys/trusted_signers")
+    }
+
+    /// Default store location used when the config does not override it.
+    pub fn default_store_dir(&self) -> PathBuf {
+        self.data_dir.join("containers")
+    }
+}
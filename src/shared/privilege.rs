@@ -0,0 +1,100 @@
+use std::env;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::shared::error::{ContainerError, ContainerResult};
+
+static ALLOW_ROOT: AtomicBool = AtomicBool::new(false);
+
+/// Keeps `sudo wrappy ...` from leaving root-owned wrappers and state in a
+/// user's home, which that user can then neither update nor remove.
+pub struct PrivilegeGuard;
+
+impl PrivilegeGuard {
+    /// Set from `--allow-root` for setups that really belong to root.
+    pub fn set_allow_root(allow_root: bool) {
+        ALLOW_ROOT.store(allow_root, Ordering::Relaxed);
+    }
+
+    /// Refuses `operation` when wrappy runs as root but `path` (or its nearest
+    /// existing parent) belongs to another user.
+    pub fn ensure_owner(path: &Path, operation: &str) -> ContainerResult<()> {
+        // SAFETY: geteuid has no preconditions and cannot fail.
+        let euid = unsafe { libc::geteuid() };
+        let sudo_user = env::var("SUDO_USER").ok();
+        let owner = nearest_existing_owner(path);
+
+        Self::check(euid, owner, sudo_user.as_deref(), ALLOW_ROOT.load(Ordering::Relaxed)).map_err(|details| {
+            ContainerError::RunningAsRoot {
+                operation: operation.to_string(),
+                details: format!("{} {}", path.display(), details),
+            }
+        })
+    }
+
+    /// The decision itself, free of the process and filesystem so any combination can be checked.
+    pub fn check(euid: u32, owner: Option<u32>, sudo_user: Option<&str>, allow_root: bool) -> Result<(), String> {
+        if euid != 0 || allow_root {
+            return Ok(());
+        }
+        match (owner, sudo_user) {
+            (Some(owner), Some(user)) if owner != 0 => Err(format!(
+                "belongs to uid {} and wrappy was started through sudo by '{}'",
+                owner, user
+            )),
+            (Some(owner), None) if owner != 0 => Err(format!("belongs to uid {}", owner)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Owner of `path`, or of the closest ancestor that exists when `path` is yet to be created.
+fn nearest_existing_owner(path: &Path) -> Option<u32> {
+    path.ancestors()
+        .find_map(|ancestor| fs::symlink_metadata(ancestor).ok())
+        .map(|metadata| metadata.uid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// (label, euid, owner, SUDO_USER, --allow-root, refusal naming this or `None` when allowed)
+    type Case<'a> = (&'a str, u32, Option<u32>, Option<&'a str>, bool, Option<&'a str>);
+
+    #[test]
+    fn root_is_only_refused_on_paths_of_other_users_unless_allowed() {
+        let cases: [Case; 9] = [
+            ("a regular user", 1000, Some(1000), None, false, None),
+            ("a regular user on another user's path", 1000, Some(1001), None, false, None),
+            ("root on its own path", 0, Some(0), None, false, None),
+            ("root on a user's path", 0, Some(1000), None, false, Some("belongs to uid 1000")),
+            ("root on a user's path with --allow-root", 0, Some(1000), None, true, None),
+            ("sudo on the user's path", 0, Some(1000), Some("alice"), false, Some("started through sudo by 'alice'")),
+            ("sudo with --allow-root", 0, Some(1000), Some("alice"), true, None),
+            ("sudo on a path of root", 0, Some(0), Some("alice"), false, None),
+            ("root with an unknown owner", 0, None, Some("alice"), false, None),
+        ];
+
+        for (label, euid, owner, sudo_user, allow_root, refusal) in cases {
+            let result = PrivilegeGuard::check(euid, owner, sudo_user, allow_root);
+            match (refusal, result) {
+                (None, Ok(())) => {}
+                (Some(expected), Err(details)) => assert!(details.contains(expected), "{}: {}", label, details),
+                (refusal, result) => panic!("{}: expected {:?}, got {:?}", label, refusal, result),
+            }
+        }
+    }
+
+    #[test]
+    fn a_path_yet_to_be_created_takes_the_owner_of_its_nearest_parent() {
+        let dir = TempDir::new().unwrap();
+        let owner = fs::metadata(dir.path()).unwrap().uid();
+
+        assert_eq!(nearest_existing_owner(&dir.path().join("not/yet/there")), Some(owner));
+        assert_eq!(nearest_existing_owner(Path::new("relative/missing")), None);
+    }
+}
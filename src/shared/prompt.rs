@@ -0,0 +1,73 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Asks a yes/no question on the terminal.
+/// Falls back to `default` when stdin is not interactive so scripted runs never block.
+pub fn confirm(question: &str, default: bool) -> bool {
+    if !io::stdin().is_terminal() {
+        return default;
+    }
+
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    print!("{} {} ", question, hint);
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return default;
+    }
+
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    }
+}
+
+/// Lets the user pick one of `options` by number.
+/// Returns `None` when stdin is not interactive or the answer is not a valid choice.
+pub fn select(question: &str, options: &[String]) -> Option<usize> {
+    if !io::stdin().is_terminal() || options.is_empty() {
+        return None;
+    }
+
+    println!("{}", question);
+    for (index, option) in options.iter().enumerate() {
+        println!("  {}) {}", index + 1, option);
+    }
+    print!("Select [1-{}]: ", options.len());
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).ok()?;
+
+    answer
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|choice| (1..=options.len()).contains(choice))
+        .map(|choice| choice - 1)
+}
+
+/// Reads one line of free text; an empty answer yields `default`.
+/// Returns `None` when stdin is not interactive so callers fall back on their own.
+pub fn input(question: &str, default: Option<&str>) -> Option<String> {
+    if !io::stdin().is_terminal() {
+        return None;
+    }
+
+    match default {
+        Some(default) => print!("{} [{}]: ", question, default),
+        None => print!("{}: ", question),
+    }
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).ok()?;
+
+    let answer = answer.trim();
+    if answer.is_empty() {
+        default.map(str::to_string)
+    } else {
+        Some(answer.to_string())
+    }
+}
@@ -0,0 +1,29 @@
+/// Single-quotes a value for POSIX shells; embedded quotes become `'\''`.
+/// Everything inside single quotes is literal, so `$`, backslashes and spaces survive unchanged.
+pub fn posix_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Reverses `posix_quote`: reads one word of single-quoted parts joined by `\'`.
+/// `None` when `word` is not in that form.
+pub fn posix_unquote(word: &str) -> Option<String> {
+    let mut value = String::new();
+    let mut rest = word;
+    loop {
+        let quoted = rest.strip_prefix('\'')?;
+        let end = quoted.find('\'')?;
+        value.push_str(&quoted[..end]);
+        rest = &quoted[end + 1..];
+        if rest.is_empty() {
+            return Some(value);
+        }
+        rest = rest.strip_prefix(r"\'")?;
+        value.push('\'');
+    }
+}
+
+/// Whether `value` holds a control character such as a newline, which no quoting keeps
+/// on one line of a generated script.
+pub fn has_control_characters(value: &str) -> bool {
+    value.chars().any(char::is_control)
+}
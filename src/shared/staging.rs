@@ -0,0 +1,209 @@
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::shared::clock;
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::filesystem;
+use crate::shared::paths::WrappyPaths;
+
+/// Used next to the destination when the cache lives on another filesystem.
+pub const FALLBACK_STAGING_DIR: &str = ".wrappy-staging";
+
+/// Staging directories this old belong to a process that crashed or was killed.
+pub const STALE_STAGING_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Scratch directory for one install, restore or export, named `<pid>-<random>` so
+/// concurrent wrappy processes never share one. It is created on the filesystem of
+/// the destination, so moving the result into place is an atomic rename, and it is
+/// removed when dropped. Directories left by a crash are removed by `store gc`.
+#[derive(Debug)]
+pub struct StagingArea {
+    path: PathBuf,
+}
+
+impl StagingArea {
+    /// Creates a staging directory under `~/.cache/wrappy/staging`, or under
+    /// `.wrappy-staging` in `destination_dir` when the cache is on another filesystem.
+    pub fn create(paths: &WrappyPaths, destination_dir: &Path) -> ContainerResult<Self> {
+        // The parent of a bare file name is empty
+        let destination_dir = match destination_dir.as_os_str().is_empty() {
+            true => Path::new("."),
+            false => destination_dir,
+        };
+        let cache_root = paths.staging_dir();
+        filesystem::create_state_dir(&cache_root)?;
+        let root = match Self::same_device(&cache_root, destination_dir)? {
+            true => cache_root,
+            false => destination_dir.join(FALLBACK_STAGING_DIR),
+        };
+        Self::create_in(&root)
+    }
+
+    /// Creates a uniquely named staging directory inside `root`.
+    pub fn create_in(root: &Path) -> ContainerResult<Self> {
+        filesystem::create_state_dir(root)?;
+        let random = Uuid::new_v4().simple().to_string();
+        let path = root.join(format!("{}-{}", process::id(), &random[..12]));
+        fs::create_dir(&path).map_err(|e| ContainerError::IoError {
+            path: path.clone(),
+            source: e,
+        })?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path for `name` inside the staging directory.
+    pub fn join(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.path.join(name)
+    }
+
+    /// Every place staging directories are created in for a store at `store_dir`.
+    pub fn roots(paths: &WrappyPaths, store_dir: &Path) -> Vec<PathBuf> {
+        vec![paths.staging_dir(), store_dir.join(FALLBACK_STAGING_DIR)]
+    }
+
+    /// Removes staging directories in `root` last modified more than `max_age` ago.
+    /// Returns how many were removed and how many bytes they held.
+    pub fn sweep(root: &Path, max_age: Duration) -> ContainerResult<(usize, u64)> {
+        let entries = match fs::read_dir(root) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((0, 0)),
+            Err(e) => {
+                return Err(ContainerError::IoError {
+                    path: root.to_path_buf(),
+                    source: e,
+                })
+            }
+        };
+
+        let now = clock::system_now();
+        let (mut removed, mut bytes) = (0, 0);
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(metadata) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+            let age = metadata.modified().ok().and_then(|modified| now.duration_since(modified).ok());
+            if !metadata.is_dir() || age.is_none_or(|age| age < max_age) {
+                continue;
+            }
+            let size = filesystem::directory_size(&path).unwrap_or(0);
+            fs::remove_dir_all(&path).map_err(|e| ContainerError::IoError {
+                path: path.clone(),
+                source: e,
+            })?;
+            removed += 1;
+            bytes += size;
+        }
+        let _ = fs::remove_dir(root);
+        Ok((removed, bytes))
+    }
+
+    /// Compares `cache_root` with the nearest existing ancestor of `destination_dir`.
+    fn same_device(cache_root: &Path, destination_dir: &Path) -> ContainerResult<bool> {
+        let cache = fs::metadata(cache_root).map_err(|e| ContainerError::IoError {
+            path: cache_root.to_path_buf(),
+            source: e,
+        })?;
+        let destination = destination_dir
+            .ancestors()
+            .find_map(|ancestor| fs::metadata(ancestor).ok())
+            .ok_or_else(|| ContainerError::InvalidPath {
+                path: destination_dir.to_path_buf(),
+                reason: "has no existing parent directory".to_string(),
+            })?;
+        Ok(cache.dev() == destination.dev())
+    }
+}
+
+impl Drop for StagingArea {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+        // The fallback root next to a destination should not outlive its last user
+        if let Some(root) = self.path.parent().filter(|root| root.ends_with(FALLBACK_STAGING_DIR)) {
+            let _ = fs::remove_dir(root);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::clock::MockClock;
+    use crate::shared::test_support::TempHome;
+    use chrono::Utc;
+    use std::sync::Arc;
+
+    #[test]
+    fn sweep_removes_only_directories_older_than_the_max_age_by_the_clock() {
+        let home = TempHome::enter();
+        let root = home.path().join("staging");
+        fs::create_dir_all(root.join("100-crashed/container")).unwrap();
+        fs::write(root.join("100-crashed/container/file"), "12345").unwrap();
+        fs::write(root.join("not-a-directory"), "x").unwrap();
+        let clock = Arc::new(MockClock::new(Utc::now()));
+        clock::set_clock(clock.clone());
+
+        let fresh = StagingArea::sweep(&root, STALE_STAGING_AGE);
+        clock.advance(chrono::Duration::hours(25));
+        let stale = StagingArea::sweep(&root, STALE_STAGING_AGE);
+        clock::reset_clock();
+
+        assert_eq!(fresh.unwrap(), (0, 0));
+        assert_eq!(stale.unwrap(), (1, 5));
+        assert!(!root.join("100-crashed").exists());
+        assert!(root.join("not-a-directory").exists());
+    }
+
+    #[test]
+    fn concurrent_staging_areas_never_share_a_directory_and_clean_up_on_drop() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("staging");
+
+        let areas: Vec<StagingArea> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| (0..20).map(|_| StagingArea::create_in(&root).unwrap()).collect::<Vec<_>>()))
+                .collect();
+            workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+        });
+
+        let names: std::collections::HashSet<String> = areas
+            .iter()
+            .map(|area| area.path().file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names.len(), 160);
+        assert!(names.iter().all(|name| name.starts_with(&format!("{}-", process::id()))));
+        assert!(areas.iter().all(|area| area.path().is_dir()));
+        drop(areas);
+        assert_eq!(fs::read_dir(&root).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn staging_uses_the_cache_on_the_same_filesystem_and_removes_the_fallback_root_when_done() {
+        let home = TempHome::enter();
+        let paths = home.paths();
+        let destination = home.path().join("store");
+
+        let cached = StagingArea::create(&paths, &destination).unwrap();
+        let fallback_root = destination.join(FALLBACK_STAGING_DIR);
+        let fallback = StagingArea::create_in(&fallback_root).unwrap();
+        fs::write(fallback.join("partial"), "half written").unwrap();
+
+        assert_eq!(cached.path().parent(), Some(paths.staging_dir().as_path()));
+        assert_eq!(StagingArea::roots(&paths, &destination), [paths.staging_dir(), fallback_root.clone()]);
+        drop(fallback);
+        assert!(!fallback_root.exists());
+        let cached_path = cached.path().to_path_buf();
+        drop(cached);
+        assert!(!cached_path.exists());
+        assert!(paths.staging_dir().is_dir(), "the shared cache root is kept");
+    }
+}
@@ -0,0 +1,205 @@
+use serde::Serialize;
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::shared::error::ContainerError;
+
+/// How a mutating command reports: progress lines and a closing table, or one JSON document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SummaryFormat {
+    #[default]
+    Text,
+    /// Only the summary, as a JSON document on stdout
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemOutcome {
+    Created,
+    Updated,
+    Removed,
+    Skipped,
+    Failed,
+}
+
+/// One thing a command touched, usually a binding target.
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryItem {
+    pub outcome: ItemOutcome,
+    pub subject: String,
+    /// Why it was skipped or failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Stable error code of a failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'static str>,
+}
+
+/// Final accounting of a mutating command; the counts are derived from `items`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OperationSummary {
+    pub operation: String,
+    pub created: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub elapsed_ms: u64,
+    pub warnings: Vec<String>,
+    pub items: Vec<SummaryItem>,
+}
+
+impl OperationSummary {
+    /// Any failed item makes the command fail.
+    pub fn exit_code(&self) -> i32 {
+        if self.failed > 0 {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn push(&mut self, item: SummaryItem) {
+        let count = match item.outcome {
+            ItemOutcome::Created => &mut self.created,
+            ItemOutcome::Updated => &mut self.updated,
+            ItemOutcome::Removed => &mut self.removed,
+            ItemOutcome::Skipped => &mut self.skipped,
+            ItemOutcome::Failed => &mut self.failed,
+        };
+        *count += 1;
+        self.items.push(item);
+    }
+
+    fn print_table(&self) {
+        println!();
+        println!("📊 Summary: {} ({:.2}s)", self.operation, self.elapsed_ms as f64 / 1000.0);
+        println!("   created  updated  removed  skipped  failed  warnings");
+        println!(
+            "   {:<7}  {:<7}  {:<7}  {:<7}  {:<6}  {}",
+            self.created,
+            self.updated,
+            self.removed,
+            self.skipped,
+            self.failed,
+            self.warnings.len()
+        );
+    }
+}
+
+struct ReporterState {
+    summary: OperationSummary,
+    started: Instant,
+}
+
+/// Prints the progress of a mutating command and collects every reported item
+/// into its `OperationSummary`, so handlers never keep counters themselves.
+/// Clones share one summary; in JSON mode nothing is printed until `finish`.
+#[derive(Clone)]
+pub struct Reporter {
+    format: SummaryFormat,
+    state: Arc<Mutex<ReporterState>>,
+}
+
+impl Default for Reporter {
+    fn default() -> Self {
+        Self::new("", SummaryFormat::Text)
+    }
+}
+
+impl Reporter {
+    pub fn new(operation: impl Into<String>, format: SummaryFormat) -> Self {
+        Self {
+            format,
+            state: Arc::new(Mutex::new(ReporterState {
+                summary: OperationSummary {
+                    operation: operation.into(),
+                    ..OperationSummary::default()
+                },
+                started: Instant::now(),
+            })),
+        }
+    }
+
+    /// A progress line that is not about a single item.
+    pub fn info(&self, line: impl Display) {
+        if self.format == SummaryFormat::Text {
+            println!("{}", line);
+        }
+    }
+
+    pub fn warn(&self, message: impl Display) {
+        let message = message.to_string();
+        if self.format == SummaryFormat::Text {
+            println!("⚠️  {}", message);
+        }
+        self.with_summary(|summary| summary.warnings.push(message));
+    }
+
+    pub fn created(&self, subject: impl Display, line: impl Display) {
+        self.record(ItemOutcome::Created, subject, None, line);
+    }
+
+    pub fn updated(&self, subject: impl Display, line: impl Display) {
+        self.record(ItemOutcome::Updated, subject, None, line);
+    }
+
+    pub fn removed(&self, subject: impl Display, line: impl Display) {
+        self.record(ItemOutcome::Removed, subject, None, line);
+    }
+
+    pub fn skipped(&self, subject: impl Display, reason: impl Display, line: impl Display) {
+        self.record(ItemOutcome::Skipped, subject, Some(reason.to_string()), line);
+    }
+
+    /// Records a failure; the line goes to stderr.
+    pub fn failed(&self, subject: impl Display, error: &ContainerError, line: impl Display) {
+        if self.format == SummaryFormat::Text {
+            eprintln!("{}", line);
+        }
+        self.with_summary(|summary| {
+            summary.push(SummaryItem {
+                outcome: ItemOutcome::Failed,
+                subject: subject.to_string(),
+                detail: Some(error.to_string()),
+                code: Some(error.code()),
+            })
+        });
+    }
+
+    /// Stops the clock and prints the summary: a table in text mode, the document in JSON mode.
+    pub fn finish(&self) -> OperationSummary {
+        let summary = {
+            let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            state.summary.elapsed_ms = state.started.elapsed().as_millis() as u64;
+            state.summary.clone()
+        };
+        match self.format {
+            SummaryFormat::Text => summary.print_table(),
+            SummaryFormat::Json => match serde_json::to_string_pretty(&summary) {
+                Ok(json) => println!("{}", json),
+                Err(error) => eprintln!("❌ Failed to serialize the summary: {}", error),
+            },
+        }
+        summary
+    }
+
+    fn record(&self, outcome: ItemOutcome, subject: impl Display, detail: Option<String>, line: impl Display) {
+        self.info(line);
+        self.with_summary(|summary| {
+            summary.push(SummaryItem {
+                outcome,
+                subject: subject.to_string(),
+                detail,
+                code: None,
+            })
+        });
+    }
+
+    fn with_summary(&self, update: impl FnOnce(&mut OperationSummary)) {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        update(&mut state.summary);
+    }
+}
@@ -0,0 +1,63 @@
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+
+/// Namespace of placeholders filled from values chosen when a container is installed.
+pub const INSTALL_NAMESPACE: &str = "install";
+
+/// One `${NAME}` or `${namespace:NAME}` reference found in a manifest string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    pub namespace: Option<String>,
+    pub name: String,
+}
+
+fn placeholder_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"\$\{(?:([a-z]+):)?([A-Za-z_][A-Za-z0-9_]*)\}").expect("valid placeholder pattern")
+    })
+}
+
+/// Whether `name` can be referenced from a placeholder.
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Lists the placeholders of `input` in order of appearance.
+pub fn placeholders(input: &str) -> Vec<Placeholder> {
+    placeholder_pattern()
+        .captures_iter(input)
+        .map(|captures| Placeholder {
+            namespace: captures.get(1).map(|m| m.as_str().to_string()),
+            name: captures[2].to_string(),
+        })
+        .collect()
+}
+
+/// Replaces every placeholder `resolve` knows a value for.
+/// Unknown placeholders stay verbatim so a later pass (or an error message) can still see them.
+pub fn substitute<F>(input: &str, mut resolve: F) -> String
+where
+    F: FnMut(&Placeholder) -> Option<String>,
+{
+    placeholder_pattern()
+        .replace_all(input, |captures: &Captures| {
+            let placeholder = Placeholder {
+                namespace: captures.get(1).map(|m| m.as_str().to_string()),
+                name: captures[2].to_string(),
+            };
+            resolve(&placeholder).unwrap_or_else(|| captures[0].to_string())
+        })
+        .into_owned()
+}
+
+/// Names of the `${namespace:NAME}` placeholders of `input` within `namespace`.
+pub fn names_in(input: &str, namespace: &str) -> Vec<String> {
+    placeholders(input)
+        .into_iter()
+        .filter(|placeholder| placeholder.namespace.as_deref() == Some(namespace))
+        .map(|placeholder| placeholder.name)
+        .collect()
+}
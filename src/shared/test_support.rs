@@ -0,0 +1,117 @@
+//! Fixtures shared by the unit tests of every module.
+
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use crate::shared::paths::WrappyPaths;
+
+static PROCESS_STATE: Mutex<()> = Mutex::new(());
+
+/// Serializes tests that change process-wide state: `HOME`, `PATH` or the clock.
+pub fn process_lock() -> MutexGuard<'static, ()> {
+    PROCESS_STATE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A temporary directory set as `HOME` until the guard is dropped; `PATH` is restored
+/// too. Holds the process lock, so tests using it never see each other's home.
+pub struct TempHome {
+    dir: TempDir,
+    previous: Option<OsString>,
+    previous_path: Option<OsString>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TempHome {
+    pub fn enter() -> Self {
+        let lock = process_lock();
+        let dir = TempDir::new().expect("temporary home");
+        let previous = std::env::var_os("HOME");
+        std::env::set_var("HOME", dir.path());
+        Self {
+            dir,
+            previous,
+            previous_path: std::env::var_os("PATH"),
+            _lock: lock,
+        }
+    }
+
+    /// Puts `dir` first on `PATH` until the guard is dropped.
+    pub fn prepend_path(&self, dir: &Path) {
+        let mut entries = vec![dir.to_path_buf()];
+        entries.extend(self.previous_path.iter().flat_map(std::env::split_paths));
+        std::env::set_var("PATH", std::env::join_paths(entries).unwrap());
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn paths(&self) -> WrappyPaths {
+        WrappyPaths::from_home(self.dir.path().to_path_buf())
+    }
+}
+
+impl Drop for TempHome {
+    fn drop(&mut self) {
+        if let Some(path) = &self.previous_path {
+            std::env::set_var("PATH", path);
+        }
+        match &self.previous {
+            Some(home) => std::env::set_var("HOME", home),
+            None => std::env::remove_var("HOME"),
+        }
+    }
+}
+
+/// Manifest of `fixture_container`: a default script and one wrapper binding `<name>-tool`.
+pub fn fixture_manifest(name: &str, version: &str) -> Value {
+    json!({
+        "name": name,
+        "version": version,
+        "scripts": { "default": "scripts/default.sh" },
+        "bindings": {
+            "executables": [{ "source": "bin/tool", "target": format!("{}-tool", name) }]
+        }
+    })
+}
+
+/// Creates a valid container directory `<root>/<name>-<version>` and returns its path.
+pub fn fixture_container(root: &Path, name: &str, version: &str) -> PathBuf {
+    let dir = root.join(format!("{}-{}", name, version));
+    fixture_container_at(&dir, fixture_manifest(name, version));
+    dir
+}
+
+/// Creates a valid container directory at `dir` with the given manifest.
+pub fn fixture_container_at(dir: &Path, manifest: Value) {
+    for sub in ["scripts", "content", "config", "bin"] {
+        fs::create_dir_all(dir.join(sub)).unwrap();
+    }
+    write_executable(&dir.join("scripts/default.sh"), "#!/bin/sh\necho \"default $*\"\n");
+    write_executable(&dir.join("bin/tool"), "#!/bin/sh\necho \"tool $*\"\n");
+    write_manifest(dir, &manifest);
+}
+
+pub fn write_manifest(dir: &Path, manifest: &Value) {
+    fs::write(dir.join("manifest.json"), serde_json::to_string_pretty(manifest).unwrap()).unwrap();
+}
+
+pub fn write_executable(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(path, content).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// Container under `tests/fixtures/symlinks` whose `content/link` is a `kind` symlink:
+/// `dangling`, `absolute` or `cyclic`. Read only; install or copy it before changing anything.
+pub fn symlink_fixture(kind: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/symlinks").join(kind)
+}
@@ -0,0 +1,352 @@
+use std::collections::HashMap;
+use std::fs::{self, FileType, ReadDir};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::shared::error::{ContainerError, ContainerResult};
+use crate::shared::ignore::IgnoreRules;
+
+/// Deepest nesting below the root a walk descends into by default.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// Most entries a walk visits by default; well above any real container.
+pub const DEFAULT_MAX_ENTRIES: usize = 1_000_000;
+
+/// Bounds every walk over container or state directories, so a pathological tree
+/// fails with `TraversalLimit` instead of hanging or exhausting the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraversalLimits {
+    pub max_depth: usize,
+    pub max_entries: usize,
+}
+
+impl Default for TraversalLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+}
+
+impl TraversalLimits {
+    /// Error for a chain (of directories or dependencies) nested deeper than `max_depth`.
+    pub fn depth_exceeded(&self, path: &Path) -> ContainerError {
+        ContainerError::TraversalLimit {
+            path: path.to_path_buf(),
+            limit: format!("nested deeper than the limit of {} levels", self.max_depth),
+        }
+    }
+
+    fn entries_exceeded(&self, path: &Path) -> ContainerError {
+        ContainerError::TraversalLimit {
+            path: path.to_path_buf(),
+            limit: format!("more than the limit of {} entries", self.max_entries),
+        }
+    }
+}
+
+/// One path found by a `Walk`; the root has depth 0.
+#[derive(Debug)]
+pub struct WalkEntry {
+    pub path: PathBuf,
+    pub depth: usize,
+    pub file_type: FileType,
+}
+
+/// Depth-first walk over `root` and everything below it, root first. Symlinks are
+/// reported but not followed, except a symlinked root with `follow_root_link`.
+/// The walk keeps its own stack, so deep trees cannot overflow the thread's, and
+/// remembers the (device, inode) of every directory it entered, so a bind mount
+/// looping back to an ancestor is reported instead of walked.
+///
+/// Unreadable directories, directories past the depth limit and loops yield an error
+/// and are skipped; exceeding the entry limit yields an error and ends the walk.
+pub struct Walk {
+    limits: TraversalLimits,
+    root: Option<PathBuf>,
+    follow_root_link: bool,
+    stack: Vec<(ReadDir, PathBuf, usize)>,
+    /// Directory yielded last, entered on the next call unless skipped
+    pending: Option<(PathBuf, usize)>,
+    visited: HashMap<(u64, u64), PathBuf>,
+    entries: usize,
+    finished: bool,
+    /// Root the rules are relative to, and the rules
+    ignore: Option<(PathBuf, IgnoreRules)>,
+}
+
+impl Walk {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            limits: TraversalLimits::default(),
+            root: Some(root.into()),
+            follow_root_link: false,
+            stack: Vec::new(),
+            pending: None,
+            visited: HashMap::new(),
+            entries: 0,
+            finished: false,
+            ignore: None,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: TraversalLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Walks the target of `root` when it is a symlink, like `read_dir` does.
+    pub fn follow_root_link(mut self) -> Self {
+        self.follow_root_link = true;
+        self
+    }
+
+    /// Leaves out entries `rules` ignore, relative to the root, and never enters ignored directories.
+    pub fn ignoring(mut self, rules: IgnoreRules) -> Self {
+        self.ignore = self.root.clone().map(|root| (root, rules));
+        self
+    }
+
+    /// Does not descend into the directory returned by the last call to `next`.
+    pub fn skip_current_dir(&mut self) {
+        self.pending = None;
+    }
+
+    fn enter(&mut self, path: PathBuf, depth: usize) -> ContainerResult<()> {
+        if depth >= self.limits.max_depth {
+            return Err(self.limits.depth_exceeded(&path));
+        }
+        let metadata = fs::metadata(&path).map_err(|e| ContainerError::IoError {
+            path: path.clone(),
+            source: e,
+        })?;
+        if let Some(first) = self.visited.get(&(metadata.dev(), metadata.ino())) {
+            return Err(ContainerError::TraversalLimit {
+                limit: format!("the same directory as '{}', a directory loop", first.display()),
+                path,
+            });
+        }
+        let read_dir = fs::read_dir(&path).map_err(|e| ContainerError::IoError {
+            path: path.clone(),
+            source: e,
+        })?;
+        self.visited.insert((metadata.dev(), metadata.ino()), path.clone());
+        self.stack.push((read_dir, path, depth + 1));
+        Ok(())
+    }
+}
+
+impl Iterator for Walk {
+    type Item = ContainerResult<WalkEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        if let Some(root) = self.root.take() {
+            let metadata = match self.follow_root_link {
+                true => fs::metadata(&root),
+                false => fs::symlink_metadata(&root),
+            };
+            return Some(match metadata {
+                Ok(metadata) => {
+                    if metadata.is_dir() {
+                        self.pending = Some((root.clone(), 0));
+                    }
+                    Ok(WalkEntry {
+                        path: root,
+                        depth: 0,
+                        file_type: metadata.file_type(),
+                    })
+                }
+                Err(e) => {
+                    self.finished = true;
+                    Err(ContainerError::IoError { path: root, source: e })
+                }
+            });
+        }
+
+        if let Some((path, depth)) = self.pending.take() {
+            if let Err(error) = self.enter(path, depth) {
+                return Some(Err(error));
+            }
+        }
+
+        loop {
+            let (read_dir, directory, depth) = self.stack.last_mut()?;
+            let entry = match read_dir.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(e)) => {
+                    return Some(Err(ContainerError::IoError {
+                        path: directory.clone(),
+                        source: e,
+                    }))
+                }
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            let depth = *depth;
+            let path = entry.path();
+
+            self.entries += 1;
+            if self.entries > self.limits.max_entries {
+                self.finished = true;
+                return Some(Err(self.limits.entries_exceeded(&path)));
+            }
+
+            if let (Some((root, rules)), Ok(file_type)) = (&self.ignore, entry.file_type()) {
+                let relative = path.strip_prefix(root).unwrap_or(&path);
+                if rules.is_ignored(relative, file_type.is_dir()) {
+                    continue;
+                }
+            }
+
+            return Some(match entry.file_type() {
+                Ok(file_type) => {
+                    if file_type.is_dir() {
+                        self.pending = Some((path.clone(), depth));
+                    }
+                    Ok(WalkEntry { path, depth, file_type })
+                }
+                Err(e) => Err(ContainerError::IoError { path, source: e }),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    /// `levels` directories nested in `root`, named `d0/d1/...`; returns the innermost.
+    fn nested(root: &Path, levels: usize) -> PathBuf {
+        let innermost = (0..levels).fold(root.to_path_buf(), |path, level| path.join(format!("d{}", level)));
+        fs::create_dir_all(&innermost).unwrap();
+        innermost
+    }
+
+    fn relative(root: &Path, entries: &[WalkEntry]) -> Vec<(String, usize)> {
+        entries
+            .iter()
+            .map(|entry| (entry.path.strip_prefix(root).unwrap().display().to_string(), entry.depth))
+            .collect()
+    }
+
+    #[test]
+    fn the_root_comes_first_and_skipped_directories_are_not_entered() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("keep/inner")).unwrap();
+        fs::create_dir_all(dir.path().join("skip/inner")).unwrap();
+        fs::write(dir.path().join("keep/inner/file"), "x").unwrap();
+
+        let mut walk = Walk::new(dir.path());
+        let mut entries = Vec::new();
+        while let Some(entry) = walk.next() {
+            let entry = entry.unwrap();
+            if entry.path.ends_with("skip") {
+                walk.skip_current_dir();
+            }
+            entries.push(entry);
+        }
+        let mut found = relative(dir.path(), &entries);
+        found.sort();
+
+        assert_eq!(entries[0].path, dir.path());
+        assert_eq!(
+            found,
+            [
+                ("".to_string(), 0),
+                ("keep".to_string(), 1),
+                ("keep/inner".to_string(), 2),
+                ("keep/inner/file".to_string(), 3),
+                ("skip".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_symlink_cycle_is_reported_but_never_followed() {
+        let dir = TempDir::new().unwrap();
+        let tree = dir.path().join("tree");
+        fs::create_dir_all(tree.join("a/b")).unwrap();
+        symlink(&tree, tree.join("a/b/back-to-root")).unwrap();
+        symlink("..", tree.join("a/up")).unwrap();
+        let link = dir.path().join("link");
+        symlink(&tree, &link).unwrap();
+
+        let entries: Vec<WalkEntry> = Walk::new(&link).follow_root_link().map(Result::unwrap).collect();
+
+        let mut found = relative(&link, &entries);
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                ("".to_string(), 0),
+                ("a".to_string(), 1),
+                ("a/b".to_string(), 2),
+                ("a/b/back-to-root".to_string(), 3),
+                ("a/up".to_string(), 2),
+            ]
+        );
+        assert!(entries.iter().filter(|entry| entry.depth > 0 && !entry.file_type.is_dir()).all(|entry| entry.file_type.is_symlink()));
+    }
+
+    #[test]
+    fn a_tree_deeper_than_the_limit_yields_one_error_and_the_rest_of_the_walk() {
+        let dir = TempDir::new().unwrap();
+        nested(&dir.path().join("deep"), 10);
+        fs::write(dir.path().join("sibling"), "x").unwrap();
+        let limits = TraversalLimits { max_depth: 4, ..TraversalLimits::default() };
+
+        let results: Vec<_> = Walk::new(dir.path()).with_limits(limits).collect();
+
+        let errors: Vec<&ContainerError> = results.iter().filter_map(|result| result.as_ref().err()).collect();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], ContainerError::TraversalLimit { limit, .. } if limit.contains("4 levels")), "{:?}", errors);
+        let deepest = results.iter().flatten().map(|entry| entry.depth).max().unwrap();
+        assert_eq!(deepest, 4);
+        assert!(results.iter().flatten().any(|entry| entry.path.ends_with("sibling")));
+    }
+
+    #[test]
+    fn a_very_deep_tree_stops_at_the_default_limit_without_recursing() {
+        let dir = TempDir::new().unwrap();
+        nested(dir.path(), DEFAULT_MAX_DEPTH + 50);
+
+        let error = crate::shared::filesystem::directory_size(dir.path()).unwrap_err();
+
+        assert!(matches!(&error, ContainerError::TraversalLimit { .. }), "{:?}", error);
+        assert!(error.to_string().contains(&format!("{} levels", DEFAULT_MAX_DEPTH)), "{}", error);
+    }
+
+    #[test]
+    fn exceeding_the_entry_limit_ends_the_walk() {
+        let dir = TempDir::new().unwrap();
+        for index in 0..20 {
+            fs::write(dir.path().join(index.to_string()), "x").unwrap();
+        }
+        let limits = TraversalLimits { max_entries: 5, ..TraversalLimits::default() };
+
+        let results: Vec<_> = Walk::new(dir.path()).with_limits(limits).collect();
+
+        // The root, five entries, then the error
+        assert_eq!(results.len(), 7);
+        assert!(matches!(results.last().unwrap(), Err(ContainerError::TraversalLimit { limit, .. }) if limit.contains("5 entries")));
+    }
+
+    #[test]
+    fn a_missing_root_is_one_error() {
+        let dir = TempDir::new().unwrap();
+
+        let results: Vec<_> = Walk::new(dir.path().join("missing")).collect();
+
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], Err(ContainerError::IoError { .. })));
+    }
+}
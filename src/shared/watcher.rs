@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::shared::glob::glob_matches;
+use crate::shared::traversal::Walk;
+
+/// Interval between filesystem scans while watching.
+pub const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Directories that are large, churn constantly and never matter to a watched run.
+pub const DEFAULT_WATCH_IGNORES: [&str; 3] = [".git", "target", "node_modules"];
+
+/// How a `Watcher` turns raw scan differences into events.
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// Quiet period that must pass after a change before it is reported, so editors
+    /// saving several files at once cause one event instead of many
+    pub debounce: Duration,
+    /// Longest a batch waits for quiet; a tree that never settles still reports this often
+    pub max_delay: Duration,
+    /// Glob patterns; without a `/` they match any path component (`.git`, `*.swp`),
+    /// with one they match the path relative to its root as a prefix (`content/cache`)
+    pub ignore: Vec<String>,
+    /// Batches with more changes are reported as an overflow instead of path by path
+    pub max_changes: usize,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(300),
+            max_delay: Duration::from_secs(5),
+            ignore: DEFAULT_WATCH_IGNORES.iter().map(|name| name.to_string()).collect(),
+            max_changes: 1000,
+        }
+    }
+}
+
+/// One settled difference between two scans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+    /// A path that disappeared while the same file appeared elsewhere
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+impl Change {
+    /// The path the change leaves behind.
+    pub fn path(&self) -> &Path {
+        match self {
+            Change::Created(path) | Change::Modified(path) | Change::Removed(path) => path,
+            Change::Renamed { to, .. } => to,
+        }
+    }
+}
+
+/// What a debounced batch of filesystem activity amounts to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+    /// Individual changes, sorted by path
+    Changes(Vec<Change>),
+    /// Too many changes to list; consumers should treat everything as changed and rescan
+    Overflow { changes: usize },
+}
+
+impl fmt::Display for WatchEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchEvent::Changes(changes) => match changes.as_slice() {
+                [] => write!(f, "nothing"),
+                [Change::Renamed { from, to }] => write!(f, "{} (renamed from {})", to.display(), from.display()),
+                [change] => write!(f, "{}", change.path().display()),
+                [first, rest @ ..] => write!(f, "{} and {} more", first.path().display(), rest.len()),
+            },
+            WatchEvent::Overflow { changes } => write!(f, "{} files", changes),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    inode: (u64, u64),
+}
+
+/// Polling watcher over a set of files and directory trees, shared by every watch mode.
+/// Scans compare modification time, size and inode, which works the same on local and
+/// network mounts. A batch is the difference between the scan before its first change and
+/// the scan once things settled, so files that come and go within a batch never show up.
+pub struct Watcher {
+    roots: Vec<PathBuf>,
+    options: WatchOptions,
+    snapshot: HashMap<PathBuf, FileStamp>,
+}
+
+impl Watcher {
+    pub fn new(roots: Vec<PathBuf>, options: WatchOptions) -> Self {
+        let mut watcher = Self {
+            roots,
+            options,
+            snapshot: HashMap::new(),
+        };
+        watcher.snapshot = watcher.scan();
+        watcher
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Returns the next batch once changes have been quiet for the debounce window (or
+    /// `max_delay` passed), or `None` when nothing changed since the last call.
+    /// `cancelled` ends the wait early; what was seen so far is still reported.
+    pub fn next_event(&mut self, cancelled: impl Fn() -> bool) -> Option<WatchEvent> {
+        let baseline = self.snapshot.clone();
+        let mut current = self.scan();
+        if current == baseline {
+            return None;
+        }
+
+        let started = Instant::now();
+        while !cancelled() && started.elapsed() < self.options.max_delay {
+            thread::sleep(self.options.debounce);
+            let next = self.scan();
+            let settled = next == current;
+            current = next;
+            if settled {
+                break;
+            }
+        }
+
+        let changes = Self::diff(&baseline, &current);
+        self.snapshot = current;
+        match changes.len() {
+            0 => None,
+            count if count > self.options.max_changes => Some(WatchEvent::Overflow { changes: count }),
+            _ => Some(WatchEvent::Changes(changes)),
+        }
+    }
+
+    /// Differences between two scans; a removal and a creation of the same inode become a rename.
+    fn diff(before: &HashMap<PathBuf, FileStamp>, after: &HashMap<PathBuf, FileStamp>) -> Vec<Change> {
+        let mut removed: HashMap<(u64, u64), &PathBuf> = before
+            .iter()
+            .filter(|(path, _)| !after.contains_key(*path))
+            .map(|(path, stamp)| (stamp.inode, path))
+            .collect();
+
+        let mut changes = Vec::new();
+        for (path, stamp) in after {
+            match before.get(path) {
+                Some(old) if old == stamp => {}
+                Some(_) => changes.push(Change::Modified(path.clone())),
+                None => match removed.remove(&stamp.inode) {
+                    Some(from) => changes.push(Change::Renamed {
+                        from: from.clone(),
+                        to: path.clone(),
+                    }),
+                    None => changes.push(Change::Created(path.clone())),
+                },
+            }
+        }
+        changes.extend(removed.into_values().map(|path| Change::Removed(path.clone())));
+        changes.sort_by(|a, b| a.path().cmp(b.path()));
+        changes
+    }
+
+    /// Unreadable paths, and whatever lies past the traversal limits, are left out.
+    fn scan(&self) -> HashMap<PathBuf, FileStamp> {
+        let mut snapshot = HashMap::new();
+        for root in &self.roots {
+            let mut walk = Walk::new(root);
+            while let Some(entry) = walk.next() {
+                let Ok(entry) = entry else {
+                    continue;
+                };
+                if entry.depth > 0 && self.is_ignored(entry.path.strip_prefix(root).unwrap_or(&entry.path)) {
+                    walk.skip_current_dir();
+                    continue;
+                }
+                if entry.file_type.is_dir() {
+                    continue;
+                }
+                let Ok(metadata) = fs::symlink_metadata(&entry.path) else {
+                    continue;
+                };
+                snapshot.insert(
+                    entry.path,
+                    FileStamp {
+                        modified: metadata.modified().ok(),
+                        len: metadata.len(),
+                        inode: (metadata.dev(), metadata.ino()),
+                    },
+                );
+            }
+        }
+        snapshot
+    }
+
+    /// Component patterns only need the last name: ignored parents are never descended into.
+    fn is_ignored(&self, relative: &Path) -> bool {
+        let relative_text = relative.to_string_lossy();
+        let name = relative.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        self.options.ignore.iter().any(|pattern| match pattern.strip_suffix('/').unwrap_or(pattern) {
+            prefix if prefix.contains('/') => glob_matches(prefix, &relative_text) || relative.starts_with(prefix),
+            pattern => glob_matches(pattern, &name),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn options(max_changes: usize) -> WatchOptions {
+        WatchOptions {
+            debounce: Duration::from_millis(20),
+            max_delay: Duration::from_millis(200),
+            max_changes,
+            ..WatchOptions::default()
+        }
+    }
+
+    fn never() -> bool {
+        false
+    }
+
+    #[test]
+    fn a_burst_of_changes_is_one_event_and_a_quiet_tree_none() {
+        let dir = TempDir::new().unwrap();
+        let mut watcher = Watcher::new(vec![dir.path().to_path_buf()], options(1000));
+        assert_eq!(watcher.next_event(never), None);
+
+        for index in 0..200 {
+            fs::write(dir.path().join(format!("file-{:03}", index)), "x").unwrap();
+        }
+        let event = watcher.next_event(never);
+
+        match event {
+            Some(WatchEvent::Changes(changes)) => {
+                assert_eq!(changes.len(), 200);
+                assert!(changes.iter().all(|change| matches!(change, Change::Created(_))));
+                assert_eq!(changes[0].path(), dir.path().join("file-000"));
+            }
+            other => panic!("expected one batch, got {:?}", other),
+        }
+        assert_eq!(watcher.next_event(never), None);
+    }
+
+    #[test]
+    fn more_changes_than_the_limit_degrade_to_an_overflow() {
+        let dir = TempDir::new().unwrap();
+        let mut watcher = Watcher::new(vec![dir.path().to_path_buf()], options(50));
+
+        for index in 0..51 {
+            fs::write(dir.path().join(index.to_string()), "x").unwrap();
+        }
+
+        assert_eq!(watcher.next_event(never), Some(WatchEvent::Overflow { changes: 51 }));
+        assert_eq!(WatchEvent::Overflow { changes: 51 }.to_string(), "51 files");
+    }
+
+    #[test]
+    fn a_move_is_one_rename_and_a_file_that_came_and_went_is_not_reported() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("old"), "content").unwrap();
+        fs::write(dir.path().join("kept"), "1").unwrap();
+        let mut watcher = Watcher::new(vec![dir.path().to_path_buf()], options(1000));
+
+        fs::rename(dir.path().join("old"), dir.path().join("new")).unwrap();
+        fs::write(dir.path().join("temporary"), "x").unwrap();
+        fs::remove_file(dir.path().join("temporary")).unwrap();
+        fs::write(dir.path().join("kept"), "22").unwrap();
+        let event = watcher.next_event(never).unwrap();
+
+        assert_eq!(
+            event,
+            WatchEvent::Changes(vec![
+                Change::Modified(dir.path().join("kept")),
+                Change::Renamed { from: dir.path().join("old"), to: dir.path().join("new") },
+            ])
+        );
+    }
+
+    #[test]
+    fn ignored_directories_and_prefixes_are_not_watched() {
+        let dir = TempDir::new().unwrap();
+        for sub in [".git", "node_modules/pkg", "content/cache", "content/src"] {
+            fs::create_dir_all(dir.path().join(sub)).unwrap();
+        }
+        let mut options = options(1000);
+        options.ignore.push("content/cache".to_string());
+        options.ignore.push("*.swp".to_string());
+        let mut watcher = Watcher::new(vec![dir.path().to_path_buf()], options);
+
+        for ignored in [".git/index", "node_modules/pkg/index.js", "content/cache/blob", "content/src/.main.rs.swp"] {
+            fs::write(dir.path().join(ignored), "x").unwrap();
+        }
+        assert_eq!(watcher.next_event(never), None);
+
+        fs::write(dir.path().join("content/src/main.rs"), "x").unwrap();
+        assert_eq!(
+            watcher.next_event(never),
+            Some(WatchEvent::Changes(vec![Change::Created(dir.path().join("content/src/main.rs"))]))
+        );
+    }
+
+    #[test]
+    fn a_storm_that_never_settles_is_reported_at_most_once_per_max_delay() {
+        let dir = TempDir::new().unwrap();
+        let mut watcher = Watcher::new(vec![dir.path().to_path_buf()], options(usize::MAX));
+        let storm = Duration::from_millis(1000);
+        let done = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let (root, done) = (dir.path().to_path_buf(), done.clone());
+            thread::spawn(move || {
+                let started = Instant::now();
+                let mut index = 0u64;
+                while started.elapsed() < storm {
+                    fs::write(root.join(format!("f{}", index % 500)), index.to_string()).unwrap();
+                    index += 1;
+                    thread::sleep(Duration::from_millis(1));
+                }
+                done.store(true, Ordering::SeqCst);
+            })
+        };
+
+        let mut events = 0;
+        while !done.load(Ordering::SeqCst) {
+            if watcher.next_event(never).is_some() {
+                events += 1;
+            } else {
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+        writer.join().unwrap();
+        while watcher.next_event(never).is_some() {
+            events += 1;
+        }
+
+        // One batch per max_delay while the storm lasts, plus the one that catches its tail
+        let bound = (storm.as_millis() / options(0).max_delay.as_millis()) as usize + 2;
+        assert!((1..=bound).contains(&events), "{} events for a {:?} storm", events, storm);
+    }
+
+    #[test]
+    fn cancelling_still_reports_what_was_seen() {
+        let dir = TempDir::new().unwrap();
+        let mut watcher = Watcher::new(vec![dir.path().to_path_buf()], options(1000));
+        fs::write(dir.path().join("file"), "x").unwrap();
+
+        let event = watcher.next_event(|| true);
+
+        assert_eq!(event, Some(WatchEvent::Changes(vec![Change::Created(dir.path().join("file"))])));
+    }
+}
@@ -0,0 +1,101 @@
+//! End-to-end runs of the built `wrappy` binary, each under a throwaway HOME.
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use tempfile::TempDir;
+
+fn wrappy(home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_wrappy"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("HOME", home)
+        .output()
+        .unwrap()
+}
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[test]
+fn validate_passes_a_fixture_with_a_warning_and_strict_fails_it() {
+    let home = TempDir::new().unwrap();
+    let fixture = "tests/fixtures/symlinks/dangling";
+
+    let lenient = wrappy(home.path(), &["container", "validate", "--path", fixture]);
+    let strict = wrappy(home.path(), &["container", "validate", "--path", fixture, "--strict"]);
+
+    assert_eq!(lenient.status.code(), Some(0), "{}", text(&lenient.stderr));
+    assert!(text(&lenient.stdout).contains("Container 'dangling-links' (v1.0.0) is valid"), "{}", text(&lenient.stdout));
+    assert!(text(&lenient.stderr).contains("Broken symlink content/link -> missing.txt"), "{}", text(&lenient.stderr));
+    assert_eq!(strict.status.code(), Some(1));
+    let strict_output = text(&strict.stdout) + &text(&strict.stderr);
+    assert!(strict_output.contains("broken symlinks: content/link -> missing.txt"), "{}", strict_output);
+}
+
+#[test]
+fn validate_fails_on_a_directory_that_is_no_container() {
+    let home = TempDir::new().unwrap();
+
+    let output = wrappy(home.path(), &["container", "validate", "--path", "tests/fixtures/binding_state"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!text(&output.stdout).contains("is valid"), "{}", text(&output.stdout));
+}
+
+#[test]
+fn bindings_list_on_a_fresh_home_reports_no_bindings() {
+    let home = TempDir::new().unwrap();
+
+    let output = wrappy(home.path(), &["bindings", "list"]);
+
+    assert_eq!(output.status.code(), Some(0), "{}", text(&output.stderr));
+    let stdout = text(&output.stdout);
+    assert!(stdout.contains("Active Wrappy Bindings"), "{}", stdout);
+    assert!(stdout.contains("No active bindings found."), "{}", stdout);
+}
+
+/// A container at `dir` whose manifest sets a secret and a look-alike that is none.
+fn container_with_secrets(dir: &Path) {
+    for sub in ["scripts", "content", "config"] {
+        fs::create_dir_all(dir.join(sub)).unwrap();
+    }
+    let manifest = r#"{
+        "name": "secretive",
+        "version": "1.0.0",
+        "scripts": { "default": "scripts/default.sh" },
+        "environment": { "API_TOKEN": "hunter2", "LLM_MAX_OUTPUT_TOKENS": "4096" }
+    }"#;
+    fs::write(dir.join("manifest.json"), manifest).unwrap();
+    fs::write(dir.join("scripts/default.sh"), "#!/bin/sh\n").unwrap();
+    fs::set_permissions(dir.join("scripts/default.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn run_masks_secrets_in_show_env_and_dry_run_unless_asked_to_show_them() {
+    let home = TempDir::new().unwrap();
+    let container = home.path().join("secretive");
+    container_with_secrets(&container);
+    let container = container.to_string_lossy().into_owned();
+
+    for flags in [&["--show-env"][..], &["--dry-run"], &["--dry-run", "--format", "json"]] {
+        let args = |show: bool| {
+            let mut args = vec!["container", "run", container.as_str()];
+            args.extend(flags);
+            args.extend(show.then_some("--show-secrets"));
+            args
+        };
+
+        let masked = wrappy(home.path(), &args(false));
+        let shown = wrappy(home.path(), &args(true));
+
+        assert_eq!((masked.status.code(), shown.status.code()), (Some(0), Some(0)), "{:?}: {}", flags, text(&masked.stderr));
+        let (masked, shown) = (text(&masked.stdout), text(&shown.stdout));
+        assert!(!masked.contains("hunter2") && masked.contains("***"), "{:?}: {}", flags, masked);
+        assert!(masked.contains("4096"), "{:?}: {}", flags, masked);
+        assert!(shown.contains("hunter2") && !shown.contains("***"), "{:?}: {}", flags, shown);
+    }
+}